@@ -147,7 +147,8 @@ where
     B: Stream<Item = Result<Bytes, E>> + Unpin,
     BodyError: From<E>,
 {
-    if !encoder.is_eof() {
+    // chunked encoder can not decide it's eof state. compare with eof coding directly.
+    if encoder != TransferCoding::eof() {
         let mut body = Pin::new(body);
 
         // poll request body and encode.
@@ -61,6 +61,12 @@ impl<'a, const PAYLOAD_LIMIT: usize> Response<'a, PAYLOAD_LIMIT> {
         &mut self.res
     }
 
+    /// Consume self and return the inner response type. Response body can be streamed with it's
+    /// [Stream](futures_core::stream::Stream) impl. Timeout set on self does not apply to it.
+    pub fn into_inner(self) -> http::Response<ResponseBody<'a>> {
+        self.res
+    }
+
     /// Set payload size limit in bytes. Payload size beyond limit would be discarded.
     ///
     /// Default to 8 Mb.
@@ -1,4 +1,5 @@
 mod error;
+mod responder;
 mod route;
 mod service;
 mod state;
@@ -12,6 +13,12 @@ pub fn state_impl(item: TokenStream) -> TokenStream {
     state::state(item).unwrap_or_else(|e| e.to_compile_error().into())
 }
 
+#[proc_macro_derive(Responder, attributes(responder))]
+pub fn responder_impl(item: TokenStream) -> TokenStream {
+    let item = syn::parse_macro_input!(item);
+    responder::responder(item).unwrap_or_else(|e| e.to_compile_error().into())
+}
+
 /// attribute macro for `xitca-web` application.
 ///
 /// # Pattern
@@ -26,6 +33,17 @@ pub fn state_impl(item: TokenStream) -> TokenStream {
 ///   `method = get` for example.
 /// - `enclosed = <type>`: typed middleware applied to route.
 /// - `enclosed_fn = <async function>`: async function as middleware applied to route
+/// - `check_params`: opt-in compile time check of path params. See below for detail.
+///
+/// # Params
+/// With `check_params` attribute and `"path"` containing params (`"/users/:id"` for example) handler arguments of
+/// `Params<T>` and `LazyParams<T>` type are checked against them at compile time. `T` is assumed to be a struct
+/// with named fields and every path param must have a field with the same name. Fields not present in path are
+/// not checked.
+///
+/// Tuple, reference, `Option`, primitive and collection types are not checked. Path param not being a valid field
+/// name (`"/users/:user-id"` for example) is skipped. Field renamed by serde is not known to the macro and the
+/// attribute must not be used for such struct.
 /// ```
 #[proc_macro_attribute]
 pub fn route(attr: TokenStream, item: TokenStream) -> TokenStream {
@@ -0,0 +1,160 @@
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_quote, spanned::Spanned, Data, DeriveInput, Error, Fields, LitInt, LitStr, Variant, WherePredicate};
+
+pub(crate) fn responder(input: DeriveInput) -> Result<TokenStream, Error> {
+    let ty_ident = &input.ident;
+
+    let Data::Enum(ref ty) = input.data else {
+        return Err(Error::new(ty_ident.span(), "expect Enum"));
+    };
+
+    let mut generics = input.generics.clone();
+    generics.params.push(parse_quote!('__r));
+    generics.params.push(parse_quote!(__C));
+    generics.params.push(parse_quote!(__B));
+
+    let ctx_ty = quote! { ::xitca_web::WebContext<'__r, __C, __B> };
+
+    let mut predicates = Vec::<WherePredicate>::new();
+    let mut respond_arms = Vec::new();
+    let mut map_arms = Vec::new();
+
+    for variant in ty.variants.iter() {
+        let ident = &variant.ident;
+        let (status, content_type) = attributes(variant)?;
+
+        let status = status.map(|status| {
+            quote! {
+                *res.status_mut() = ::xitca_web::http::StatusCode::from_u16(#status).unwrap();
+            }
+        });
+
+        let content_type = content_type.map(|content_type| {
+            quote! {
+                res.headers_mut().insert(
+                    ::xitca_web::http::header::CONTENT_TYPE,
+                    ::xitca_web::http::header::HeaderValue::from_static(#content_type),
+                );
+            }
+        });
+
+        let (pat, field_ty) = match variant.fields {
+            Fields::Unit => (quote! { Self::#ident }, None),
+            Fields::Unnamed(ref fields) if fields.unnamed.len() == 1 => {
+                (quote! { Self::#ident(field) }, Some(&fields.unnamed[0].ty))
+            }
+            Fields::Named(ref fields) if fields.named.len() == 1 => {
+                let name = fields.named[0].ident.as_ref().unwrap();
+                (quote! { Self::#ident { #name: field } }, Some(&fields.named[0].ty))
+            }
+            ref fields => {
+                return Err(Error::new(
+                    fields.span(),
+                    "expect unit variant or variant with one field",
+                ))
+            }
+        };
+
+        match field_ty {
+            Some(field_ty) => {
+                predicates.push(parse_quote! {
+                    #field_ty: ::xitca_web::handler::Responder<#ctx_ty, Response = ::xitca_web::http::WebResponse>
+                });
+                predicates.push(parse_quote! {
+                    ::xitca_web::error::Error: From<<#field_ty as ::xitca_web::handler::Responder<#ctx_ty>>::Error>
+                });
+
+                respond_arms.push(quote! {
+                    #pat => {
+                        #[allow(unused_mut)]
+                        let mut res = <#field_ty as ::xitca_web::handler::Responder<#ctx_ty>>::respond(field, ctx).await?;
+                        #status
+                        #content_type
+                        Ok(res)
+                    }
+                });
+                map_arms.push(quote! {
+                    #pat => {
+                        #[allow(unused_mut)]
+                        let mut res = <#field_ty as ::xitca_web::handler::Responder<#ctx_ty>>::map(field, res)?;
+                        #status
+                        #content_type
+                        Ok(res)
+                    }
+                });
+            }
+            None => {
+                respond_arms.push(quote! {
+                    #pat => {
+                        #[allow(unused_mut)]
+                        let mut res = ctx.into_response(::xitca_web::body::ResponseBody::empty());
+                        #status
+                        #content_type
+                        Ok(res)
+                    }
+                });
+                map_arms.push(quote! {
+                    #pat => {
+                        #[allow(unused_mut)]
+                        let mut res = res;
+                        #status
+                        #content_type
+                        Ok(res)
+                    }
+                });
+            }
+        }
+    }
+
+    generics.make_where_clause().predicates.extend(predicates);
+
+    let (impl_gen, _, where_clause) = generics.split_for_impl();
+    let (_, ty_gen, _) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_gen ::xitca_web::handler::Responder<#ctx_ty> for #ty_ident #ty_gen #where_clause {
+            type Response = ::xitca_web::http::WebResponse;
+            type Error = ::xitca_web::error::Error;
+
+            async fn respond(self, ctx: #ctx_ty) -> Result<Self::Response, Self::Error> {
+                match self {
+                    #(#respond_arms)*
+                }
+            }
+
+            fn map(self, res: Self::Response) -> Result<Self::Response, Self::Error> {
+                match self {
+                    #(#map_arms)*
+                }
+            }
+        }
+    }
+    .into())
+}
+
+// parse #[responder(status = <u16>, content_type = "<value>")] attribute of variant.
+fn attributes(variant: &Variant) -> Result<(Option<LitInt>, Option<LitStr>), Error> {
+    let mut status = None;
+    let mut content_type = None;
+
+    for attr in variant.attrs.iter().filter(|attr| attr.path().is_ident("responder")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("status") {
+                let lit = meta.value()?.parse::<LitInt>()?;
+                match lit.base10_parse::<u16>()? {
+                    100..=999 => status = Some(lit),
+                    _ => return Err(meta.error("status must be in range of 100 to 999")),
+                }
+                Ok(())
+            } else if meta.path.is_ident("content_type") {
+                content_type = Some(meta.value()?.parse::<LitStr>()?);
+                Ok(())
+            } else {
+                Err(meta.error("expect status or content_type"))
+            }
+        })?;
+    }
+
+    Ok((status, content_type))
+}
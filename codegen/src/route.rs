@@ -1,10 +1,10 @@
 use proc_macro::TokenStream;
-use quote::quote;
+use quote::{quote, ToTokens};
 use syn::{
     parse::{Parse, ParseStream},
     punctuated::Punctuated,
     spanned::Spanned,
-    Error, Expr, FnArg, GenericArgument, ItemFn, PathArguments, Type,
+    Error, Expr, FnArg, GenericArgument, Ident, ItemFn, Lit, PathArguments, Type,
 };
 
 pub(crate) fn route(attr: Args, input: ItemFn) -> Result<TokenStream, Error> {
@@ -21,8 +21,16 @@ pub(crate) fn route(attr: Args, input: ItemFn) -> Result<TokenStream, Error> {
     let method = &*method.right;
 
     let mut middlewares = quote! {};
+    let mut check_params = false;
 
     for attr in attrs {
+        if let Expr::Path(ref name) = attr {
+            if name.path.is_ident("check_params") {
+                check_params = true;
+                continue;
+            }
+        }
+
         let Expr::Assign(pair) = attr else {
             return Err(Error::new(input.span(), "expect '<name> = <value>' expression"));
         };
@@ -82,7 +90,11 @@ pub(crate) fn route(attr: Args, input: ItemFn) -> Result<TokenStream, Error> {
         Full(&'a Type),
     }
 
+    // path params are only known at compile time when path is a string literal.
+    let params = check_params.then(|| path_params(&path)).flatten();
+
     let mut state = Vec::new();
+    let mut params_check = quote! {};
 
     for arg in input.sig.inputs.iter() {
         if let FnArg::Typed(ty) = arg {
@@ -110,6 +122,18 @@ pub(crate) fn route(attr: Args, input: ItemFn) -> Result<TokenStream, Error> {
                             _ => return Err(Error::new(ty.span(), "expect state type.")),
                         }
                     }
+                    "Params" | "LazyParams" => {
+                        let Some(ref params) = params else {
+                            continue;
+                        };
+                        let PathArguments::AngleBracketed(ref arg) = path.arguments else {
+                            return Err(Error::new(path.span(), format!("expect {ident}<_>")));
+                        };
+                        if let Some(GenericArgument::Type(ref ty)) = arg.args.last() {
+                            let check = params_pattern(ty, params);
+                            params_check = quote! { #params_check #check };
+                        }
+                    }
                     "WebContext" => {
                         let PathArguments::AngleBracketed(ref arg) = path.arguments else {
                             return Err(Error::new(path.span(), format!("expect &{ident}<'_, _>")));
@@ -186,6 +210,8 @@ pub(crate) fn route(attr: Args, input: ItemFn) -> Result<TokenStream, Error> {
             fn route() -> Self::Route {
                 #input
 
+                #params_check
+
                 use xitca_web::codegen::__private::IntoObject;
                 use xitca_web::WebContext;
                 use xitca_web::route::#method;
@@ -198,6 +224,108 @@ pub(crate) fn route(attr: Args, input: ItemFn) -> Result<TokenStream, Error> {
     .into())
 }
 
+// names of params declared in path template. None when path is not a string literal.
+// path syntax is validated by router and unnamed catch all param is skipped.
+fn path_params(path: &Expr) -> Option<Vec<String>> {
+    let Expr::Lit(ref lit) = *path else {
+        return None;
+    };
+    let Lit::Str(ref lit) = lit.lit else {
+        return None;
+    };
+
+    let path = lit.value();
+    let params = path
+        .split('/')
+        .filter_map(|segment| segment.find([':', '*']).map(|idx| &segment[idx + 1..]))
+        .filter(|name| !name.is_empty())
+        .map(String::from)
+        .collect();
+
+    Some(params)
+}
+
+// generate struct pattern with rest of fields ignored for type extracted from path params. it fails to compile
+// when a path param has no field with the same name.
+// tuple, reference, Option, scalar and collection types are skipped. params not being valid field names(e.g.
+// "user-id") are skipped as they can only be mapped to field renamed by serde.
+fn params_pattern(ty: &Type, params: &[String]) -> impl ToTokens {
+    let Type::Path(ty) = ty else {
+        return quote! {};
+    };
+
+    let Some(last) = ty.path.segments.last() else {
+        return quote! {};
+    };
+
+    if matches!(
+        last.ident.to_string().as_str(),
+        "u8" | "u16"
+            | "u32"
+            | "u64"
+            | "u128"
+            | "usize"
+            | "i8"
+            | "i16"
+            | "i32"
+            | "i64"
+            | "i128"
+            | "isize"
+            | "f32"
+            | "f64"
+            | "bool"
+            | "char"
+            | "str"
+            | "String"
+            | "Cow"
+            | "Box"
+            | "Option"
+            | "Vec"
+            | "HashMap"
+            | "BTreeMap"
+    ) {
+        return quote! {};
+    }
+
+    let fields = params
+        .iter()
+        // keyword can be used as field name in raw form except path keywords.
+        .filter_map(|p| {
+            syn::parse_str::<Ident>(p)
+                .or_else(|_| syn::parse_str::<Ident>(&format!("r#{p}")))
+                .ok()
+        })
+        .collect::<Vec<_>>();
+
+    if fields.is_empty() {
+        return quote! {};
+    }
+
+    // lifetimes of handler signature are not in scope of generated function and they are elided.
+    let mut ty = ty.clone();
+    let mut pat = ty.path.clone();
+    for seg in ty.path.segments.iter_mut() {
+        if let PathArguments::AngleBracketed(ref mut args) = seg.arguments {
+            args.args = core::mem::take(&mut args.args)
+                .into_iter()
+                .filter(|arg| !matches!(arg, GenericArgument::Lifetime(_)))
+                .collect();
+        }
+    }
+    if let Some(seg) = pat.segments.last_mut() {
+        seg.arguments = PathArguments::None;
+    }
+
+    quote! {
+        const _: () = {
+            #[allow(dead_code)]
+            fn check_params(params: #ty) {
+                let #pat { #(#fields: _,)* .. } = params;
+            }
+        };
+    }
+}
+
 pub struct Args {
     vars: Vec<Expr>,
 }
@@ -13,7 +13,7 @@ fn main() -> io::Result<()> {
     App::new()
         .at("/", handler_service(root))
         .enclosed(Compress)
-        .enclosed(Decompress)
+        .enclosed(Decompress::new())
         .enclosed(Logger::new())
         .serve()
         .bind("127.0.0.1:8080")?
@@ -0,0 +1,55 @@
+//! One application served as plain http on one port and https on another.
+//!
+//! visit http://127.0.0.1:8080/ and https://127.0.0.1:8443/ after start the example.
+//! both listeners share the same counter of visits.
+
+use std::{
+    fs, io,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use rustls::{pki_types::PrivateKeyDer, ServerConfig};
+use xitca_web::{
+    handler::{handler_service, state::StateRef},
+    route::get,
+    App,
+};
+
+fn main() -> io::Result<()> {
+    // state constructed outside of application and shared by all listeners and worker threads.
+    // application state is built once for every listener on every worker thread. without a shared
+    // smart pointer every one of them would have it's own counter.
+    let visits = Arc::new(AtomicUsize::new(0));
+
+    App::new()
+        .with_state(visits)
+        .at("/", get(handler_service(handler)))
+        .serve()
+        // plain http listener.
+        .bind("127.0.0.1:8080")?
+        // https listener with the same application.
+        .bind_rustls("127.0.0.1:8443", tls_config()?)?
+        .run()
+        .wait()
+}
+
+async fn handler(StateRef(visits): StateRef<'_, Arc<AtomicUsize>>) -> String {
+    let n = visits.fetch_add(1, Ordering::Relaxed) + 1;
+    format!("Hello,World! visited {n} time(s)")
+}
+
+fn tls_config() -> io::Result<ServerConfig> {
+    let cert = fs::read("../cert/cert.pem")?;
+    let key = fs::read("../cert/key.pem")?;
+
+    let key = rustls_pemfile::pkcs8_private_keys(&mut &*key).next().unwrap()?;
+    let cert = rustls_pemfile::certs(&mut &*cert).collect::<Result<_, _>>()?;
+
+    ServerConfig::builder()
+        .with_no_client_auth()
+        .with_single_cert(cert, PrivateKeyDer::from(key))
+        .map_err(io::Error::other)
+}
@@ -33,6 +33,12 @@ where
         Self { body, coder }
     }
 
+    /// Reference to the stream being coded.
+    #[inline]
+    pub fn get_ref(&self) -> &S {
+        &self.body
+    }
+
     #[inline]
     pub fn into_inner(self) -> S {
         self.body
@@ -29,6 +29,8 @@ pub enum ServeError {
     RangeNotSatisfied(u64),
     /// can not find requested file.
     NotFound,
+    /// requested file can not be accessed due to lack of permission.
+    Forbidden,
     /// I/O error from file system.
     Io(io::Error),
 }
@@ -90,6 +92,7 @@ impl ServeError {
                 res.headers_mut().insert(CONTENT_RANGE, val);
             }
             Self::NotFound => *res.status_mut() = StatusCode::NOT_FOUND,
+            Self::Forbidden => *res.status_mut() = StatusCode::FORBIDDEN,
             Self::Io(_) => *res.status_mut() = StatusCode::INTERNAL_SERVER_ERROR,
         }
         res
@@ -105,6 +108,7 @@ impl fmt::Display for ServeError {
             Self::PreconditionFailed => f.write_str("precondition failed. file has been modified"),
             Self::RangeNotSatisfied(size) => write!(f, "range is out of bound. max range of file is {size}"),
             Self::NotFound => f.write_str("file can not be found"),
+            Self::Forbidden => f.write_str("file can not be accessed"),
             Self::Io(ref e) => fmt::Display::fmt(e, f),
         }
     }
@@ -8,8 +8,9 @@ mod buf;
 mod chunk;
 mod date;
 mod error;
+mod named;
 
-pub use self::{chunk::ChunkReader, error::ServeError};
+pub use self::{chunk::ChunkReader, error::ServeError, named::NamedFile};
 
 use std::{
     io::SeekFrom,
@@ -102,58 +103,75 @@ impl<FS: AsyncFs> ServeDir<FS> {
             return Err(ServeError::InvalidPath);
         }
 
-        let ct = mime_guess::from_path(&path)
-            .first_raw()
-            .unwrap_or_else(|| mime::APPLICATION_OCTET_STREAM.as_ref());
+        let ct = content_type(&path);
 
-        let mut file = self.async_fs.open(path).await?;
+        let file = self.async_fs.open(path).await?;
 
-        let modified = date::mod_date_check(req, &mut file)?;
-
-        let mut res = Response::new(());
-
-        let mut size = file.len();
-
-        if let Some(range) = req
-            .headers()
-            .get(RANGE)
-            .and_then(|h| h.to_str().ok())
-            .and_then(|range| http_range_header::parse_range_header(range).ok())
-            .map(|range| range.validate(size))
-        {
-            let (start, end) = range
-                .map_err(|_| ServeError::RangeNotSatisfied(size))?
-                .pop()
-                .expect("http_range_header produced empty range")
-                .into_inner();
-
-            file.seek(SeekFrom::Start(start)).await?;
+        serve_file(file, ct, req, self.chunk_size).await
+    }
+}
 
-            *res.status_mut() = StatusCode::PARTIAL_CONTENT;
-            let val = buf_write_header!(0, "bytes {start}-{end}/{size}");
-            res.headers_mut().insert(CONTENT_RANGE, val);
+fn content_type(path: &Path) -> &'static str {
+    mime_guess::from_path(path)
+        .first_raw()
+        .unwrap_or_else(|| mime::APPLICATION_OCTET_STREAM.as_ref())
+}
 
-            size = end - start + 1;
-        }
+// generate response with stream reader of opened file. conditional and range request headers are respected.
+async fn serve_file<F, Ext>(
+    mut file: F,
+    ct: &'static str,
+    req: &Request<Ext>,
+    chunk_size: usize,
+) -> Result<Response<ChunkReader<F>>, ServeError>
+where
+    F: ChunkRead + Meta,
+{
+    let modified = date::mod_date_check(req, &mut file)?;
+
+    let mut res = Response::new(());
+
+    let mut size = file.len();
+
+    if let Some(range) = req
+        .headers()
+        .get(RANGE)
+        .and_then(|h| h.to_str().ok())
+        .and_then(|range| http_range_header::parse_range_header(range).ok())
+        .map(|range| range.validate(size))
+    {
+        let (start, end) = range
+            .map_err(|_| ServeError::RangeNotSatisfied(size))?
+            .pop()
+            .expect("http_range_header produced empty range")
+            .into_inner();
+
+        file.seek(SeekFrom::Start(start)).await?;
+
+        *res.status_mut() = StatusCode::PARTIAL_CONTENT;
+        let val = buf_write_header!(0, "bytes {start}-{end}/{size}");
+        res.headers_mut().insert(CONTENT_RANGE, val);
+
+        size = end - start + 1;
+    }
 
-        res.headers_mut().insert(CONTENT_TYPE, HeaderValue::from_static(ct));
-        res.headers_mut().insert(CONTENT_LENGTH, HeaderValue::from(size));
-        res.headers_mut()
-            .insert(ACCEPT_RANGES, HeaderValue::from_static("bytes"));
+    res.headers_mut().insert(CONTENT_TYPE, HeaderValue::from_static(ct));
+    res.headers_mut().insert(CONTENT_LENGTH, HeaderValue::from(size));
+    res.headers_mut()
+        .insert(ACCEPT_RANGES, HeaderValue::from_static("bytes"));
 
-        if let Some(modified) = modified {
-            let val = date::date_to_header(modified);
-            res.headers_mut().insert(LAST_MODIFIED, val);
-        }
+    if let Some(modified) = modified {
+        let val = date::date_to_header(modified);
+        res.headers_mut().insert(LAST_MODIFIED, val);
+    }
 
-        let stream = if matches!(*req.method(), Method::HEAD) {
-            ChunkReader::empty()
-        } else {
-            ChunkReader::reader(file, size, self.chunk_size)
-        };
+    let stream = if matches!(*req.method(), Method::HEAD) {
+        ChunkReader::empty()
+    } else {
+        ChunkReader::reader(file, size, chunk_size)
+    };
 
-        Ok(res.map(|_| stream))
-    }
+    Ok(res.map(|_| stream))
 }
 
 impl<FS: AsyncFs> ServeDir<FS> {
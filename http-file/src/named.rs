@@ -0,0 +1,230 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+use http::{
+    header::{HeaderValue, CONTENT_DISPOSITION},
+    Request, Response,
+};
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+
+use super::{
+    buf::buf_write_header,
+    content_type,
+    runtime::{self, AsyncFs, Meta},
+    serve_file, ChunkReader, ServeError,
+};
+
+/// a single file to be served. it's the single file counterpart of [ServeDir] where the file path is decided by
+/// caller instead of request uri.
+///
+/// [ServeDir]: crate::ServeDir
+#[cfg(feature = "tokio")]
+#[derive(Clone)]
+pub struct NamedFile<FS: AsyncFs = runtime::TokioFs> {
+    path: PathBuf,
+    chunk_size: usize,
+    attachment: bool,
+    async_fs: FS,
+}
+
+/// a single file to be served. it's the single file counterpart of [ServeDir] where the file path is decided by
+/// caller instead of request uri.
+///
+/// [ServeDir]: crate::ServeDir
+#[cfg(not(feature = "tokio"))]
+#[derive(Clone)]
+pub struct NamedFile<FS: AsyncFs> {
+    path: PathBuf,
+    chunk_size: usize,
+    attachment: bool,
+    async_fs: FS,
+}
+
+#[cfg(feature = "default")]
+impl NamedFile<runtime::TokioFs> {
+    /// Construct a new NamedFile with given path.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self::with_fs(path, runtime::TokioFs)
+    }
+}
+
+#[cfg(feature = "tokio-uring")]
+impl NamedFile<runtime::TokioUringFs> {
+    /// Construct a new NamedFile with given path.
+    pub fn new_tokio_uring(path: impl Into<PathBuf>) -> Self {
+        Self::with_fs(path, runtime::TokioUringFs)
+    }
+}
+
+impl<FS: AsyncFs> NamedFile<FS> {
+    /// construct a new NamedFile with given path and async file system type. The type must impl
+    /// [AsyncFs] trait for properly handling file streaming.
+    pub fn with_fs(path: impl Into<PathBuf>, async_fs: FS) -> Self {
+        Self {
+            path: path.into(),
+            chunk_size: 4096,
+            attachment: false,
+            async_fs,
+        }
+    }
+
+    /// hint for chunk size of async file streaming.
+    /// it's a best effort upper bound and should not be trusted to produce exact chunk size as
+    /// under/over shoot can happen
+    pub fn chunk_size(mut self, size: usize) -> Self {
+        self.chunk_size = size;
+        self
+    }
+
+    /// add `Content-Disposition: attachment` header with file name to response. client would download the file
+    /// instead of displaying it.
+    pub fn attachment(mut self) -> Self {
+        self.attachment = true;
+        self
+    }
+
+    /// path of file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// open the file and generate http response with stream reader of it. conditional and range request headers
+    /// are respected the same way as [ServeDir::serve]. request method is not checked and HEAD request gets
+    /// response without body.
+    ///
+    /// file that does not exist or is a directory produces [ServeError::NotFound] and file can not be accessed
+    /// due to lack of permission produces [ServeError::Forbidden].
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use http_file::NamedFile;
+    /// # use http::Request;
+    /// async fn serve(req: &Request<()>) {
+    ///     let file = NamedFile::new("sample/test.txt").attachment();
+    ///     let res = file.serve(&req).await;
+    /// }
+    /// ```
+    ///
+    /// [ServeDir::serve]: crate::ServeDir::serve
+    pub async fn serve<Ext>(&self, req: &Request<Ext>) -> Result<Response<ChunkReader<FS::File>>, ServeError> {
+        let ct = content_type(&self.path);
+
+        let file = self
+            .async_fs
+            .open(self.path.clone())
+            .await
+            .map_err(|e| match e.kind() {
+                io::ErrorKind::PermissionDenied => ServeError::Forbidden,
+                _ => ServeError::from(e),
+            })?;
+
+        // directory is checked with metadata of opened file without blocking the thread.
+        if file.is_dir() {
+            return Err(ServeError::NotFound);
+        }
+
+        let mut res = serve_file(file, ct, req, self.chunk_size).await?;
+
+        if self.attachment {
+            res.headers_mut().insert(CONTENT_DISPOSITION, self.disposition());
+        }
+
+        Ok(res)
+    }
+
+    fn disposition(&self) -> HeaderValue {
+        let name = self
+            .path
+            .file_name()
+            .map(|name| name.to_string_lossy())
+            .unwrap_or_default();
+
+        if name.is_empty() {
+            return HeaderValue::from_static("attachment");
+        }
+
+        // plain file name is used when it's safe for quoted string. otherwise fallback to rfc 5987 encoding.
+        if name
+            .bytes()
+            .all(|b| (b.is_ascii_graphic() && b != b'"' && b != b'\\') || b == b' ')
+        {
+            buf_write_header!(name.len() + 23, "attachment; filename=\"{name}\"")
+        } else {
+            let name = utf8_percent_encode(&name, NON_ALPHANUMERIC);
+            buf_write_header!(0, "attachment; filename*=UTF-8''{name}")
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use http::{
+        header::{CONTENT_LENGTH, CONTENT_RANGE, CONTENT_TYPE, LAST_MODIFIED},
+        Method, StatusCode,
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn serve() {
+        let req = Request::builder().body(()).unwrap();
+        let res = NamedFile::new("sample/test.txt").serve(&req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(
+            res.headers().get(CONTENT_TYPE).unwrap(),
+            HeaderValue::from_static("text/plain")
+        );
+        assert_eq!(
+            res.headers().get(CONTENT_LENGTH).unwrap(),
+            HeaderValue::from("hello, world!".len())
+        );
+        assert!(res.headers().contains_key(LAST_MODIFIED));
+        assert!(!res.headers().contains_key(CONTENT_DISPOSITION));
+    }
+
+    #[tokio::test]
+    async fn ranged_attachment() {
+        let req = Request::builder()
+            .method(Method::HEAD)
+            .header("range", "bytes=2-12")
+            .body(())
+            .unwrap();
+        let res = NamedFile::new("sample/test.txt")
+            .attachment()
+            .serve(&req)
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(
+            res.headers().get(CONTENT_RANGE).unwrap(),
+            HeaderValue::from_static("bytes 2-12/13")
+        );
+        assert_eq!(
+            res.headers().get(CONTENT_DISPOSITION).unwrap(),
+            HeaderValue::from_static("attachment; filename=\"test.txt\"")
+        );
+    }
+
+    #[tokio::test]
+    async fn not_found() {
+        let req = Request::builder().body(()).unwrap();
+        for path in ["sample/none.txt", "sample"] {
+            let e = NamedFile::new(path).serve(&req).await.err().unwrap();
+            assert!(matches!(e, ServeError::NotFound));
+        }
+    }
+
+    #[test]
+    fn disposition() {
+        assert_eq!(
+            NamedFile::new("foo/bar baz.txt").disposition(),
+            HeaderValue::from_static("attachment; filename=\"bar baz.txt\"")
+        );
+        assert_eq!(
+            NamedFile::new("foo/\"报告\".pdf").disposition(),
+            HeaderValue::from_static("attachment; filename*=UTF-8''%22%E6%8A%A5%E5%91%8A%22%2Epdf")
+        );
+    }
+}
@@ -27,6 +27,11 @@ pub trait Meta {
     /// the length hint of file.
     fn len(&self) -> u64;
 
+    /// check if the opened file is a directory. default to false.
+    fn is_dir(&self) -> bool {
+        false
+    }
+
     #[cold]
     #[inline(never)]
     fn is_empty(&self) -> bool {
@@ -79,10 +84,12 @@ mod tokio_impl {
                     let meta = file.metadata()?;
                     let modified_time = meta.modified().ok();
                     let len = meta.len();
+                    let is_dir = meta.is_dir();
                     Ok(TokioFile {
                         file: file.into(),
                         modified_time,
                         len,
+                        is_dir,
                     })
                 })
                 .await
@@ -95,6 +102,7 @@ mod tokio_impl {
         file: File,
         modified_time: Option<SystemTime>,
         len: u64,
+        is_dir: bool,
     }
 
     impl Meta for TokioFile {
@@ -105,6 +113,10 @@ mod tokio_impl {
         fn len(&self) -> u64 {
             self.len
         }
+
+        fn is_dir(&self) -> bool {
+            self.is_dir
+        }
     }
 
     impl ChunkRead for TokioFile {
@@ -166,12 +178,14 @@ mod tokio_uring_impl {
 
                 let modified_time = meta.modified().ok();
                 let len = meta.len();
+                let is_dir = meta.is_dir();
 
                 Ok(TokioUringFile {
                     file,
                     pos: 0,
                     modified_time,
                     len,
+                    is_dir,
                 })
             }
         }
@@ -182,6 +196,7 @@ mod tokio_uring_impl {
         pos: u64,
         modified_time: Option<SystemTime>,
         len: u64,
+        is_dir: bool,
     }
 
     impl Meta for TokioUringFile {
@@ -192,6 +207,10 @@ mod tokio_uring_impl {
         fn len(&self) -> u64 {
             self.len
         }
+
+        fn is_dir(&self) -> bool {
+            self.is_dir
+        }
     }
 
     impl ChunkRead for TokioUringFile {
@@ -1,4 +1,4 @@
-use core::fmt;
+use core::{fmt, time::Duration};
 
 use std::{error, time::Instant};
 
@@ -25,8 +25,15 @@ impl error::Error for TooManyRequests {}
 
 impl From<NotUntil<Instant>> for TooManyRequests {
     fn from(e: NotUntil<Instant>) -> Self {
-        let after_seconds = e.wait_time_from(DefaultTimer.now()).as_secs();
-        Self { after_seconds }
+        Self::from_wait_time(e.wait_time_from(DefaultTimer.now()))
+    }
+}
+
+impl TooManyRequests {
+    pub(crate) fn from_wait_time(wait: Duration) -> Self {
+        Self {
+            after_seconds: wait.as_secs(),
+        }
     }
 }
 
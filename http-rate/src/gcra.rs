@@ -33,7 +33,8 @@ where
     /// that are made in the meantime).
     #[inline]
     pub fn earliest_possible(&self) -> P {
-        let tat: Nanos = self.state.tat;
+        // NotUntil is only produced by gcra and it's snapshot always carries theoretical arrival time.
+        let tat: Nanos = self.state.tat().unwrap_or_default();
         self.start + tat
     }
 
@@ -51,7 +52,7 @@ where
 
     /// Returns the rate limiting [`Quota`] used to reach the decision.
     #[inline]
-    pub fn quota(&self) -> Quota {
+    pub fn quota(&self) -> Option<Quota> {
         self.state.quota()
     }
 }
@@ -61,7 +62,7 @@ where
     P: Reference,
 {
     fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
-        write!(f, "rate-limited until {:?}", self.start + self.state.tat)
+        write!(f, "rate-limited until {:?}", self.earliest_possible())
     }
 }
 
@@ -187,7 +188,7 @@ mod test {
                 assert_eq!(nu, nu);
                 assert_gt!(format!("{:?}", nu).len(), 0);
                 assert_eq!(format!("{}", nu), "rate-limited until Nanos(1s)");
-                assert_eq!(nu.quota(), quota);
+                assert_eq!(nu.quota(), Some(quota));
             })
             .is_err());
     }
@@ -8,30 +8,75 @@ mod quota;
 mod snapshot;
 mod state;
 mod timer;
+mod window;
 
 pub use error::TooManyRequests;
 pub use quota::Quota;
 pub use snapshot::RateSnapshot;
 
-use core::net::{IpAddr, SocketAddr};
+use core::{
+    net::{IpAddr, SocketAddr},
+    time::Duration,
+};
 
 use std::sync::Arc;
 
 use http::header::{HeaderMap, HeaderName, FORWARDED};
 
-use crate::state::{keyed::DefaultKeyedStateStore, RateLimiter};
-
+use crate::{
+    state::{keyed::DefaultKeyedStateStore, RateLimiter},
+    window::{WindowKind, WindowLimiter},
+};
+
+/// Client ip address based rate limiter.
+///
+/// # Algorithms
+/// - [RateLimit::new] uses token bucket(GCRA) algorithm. A client can burst up to the quota's burst size and the
+///   bucket is replenished evenly over time. Each client keeps a single timestamp as state.
+/// - [RateLimit::fixed_window] counts requests in fixed time windows and resets the count at window boundary.
+///   Each client keeps a single counter as state and the algorithm is cheap and easy to reason about. A client
+///   can pass up to twice the limit in a short period around window boundary.
+/// - [RateLimit::sliding_window] counts requests like fixed window but weights the count of previous window by
+///   how much of it still overlaps with a sliding window ending at current time. Each client keeps two counters
+///   as state. Burst around window boundary is smoothed out at the cost of being an approximation that assumes
+///   requests in previous window were evenly distributed. Unlike a sliding window log it does not store
+///   timestamp of every request so memory usage does not grow with the limit.
+///
+/// State of all algorithms is kept per client ip address and is not shrunk when client goes away.
 #[derive(Clone)]
 pub struct RateLimit {
-    limit: Arc<RateLimiter<IpAddr, DefaultKeyedStateStore<IpAddr>>>,
+    limit: Arc<Limiter>,
+}
+
+enum Limiter {
+    Gcra(RateLimiter<IpAddr, DefaultKeyedStateStore<IpAddr>>),
+    Window(WindowLimiter<IpAddr>),
 }
 
 impl RateLimit {
     /// Construct a new RateLimit with given quota.
     pub fn new(quota: Quota) -> Self {
-        Self {
-            limit: Arc::new(RateLimiter::hashmap(quota)),
-        }
+        Self::from_limiter(Limiter::Gcra(RateLimiter::hashmap(quota)))
+    }
+
+    /// Construct a new RateLimit allowing given number of requests in every fixed window of time.
+    ///
+    /// # Panics
+    /// - When limit or window is zero.
+    pub fn fixed_window(limit: u32, window: Duration) -> Self {
+        Self::from_limiter(Limiter::Window(WindowLimiter::new(WindowKind::Fixed, limit, window)))
+    }
+
+    /// Construct a new RateLimit allowing given number of requests in a sliding window of time.
+    ///
+    /// # Panics
+    /// - When limit or window is zero.
+    pub fn sliding_window(limit: u32, window: Duration) -> Self {
+        Self::from_limiter(Limiter::Window(WindowLimiter::new(WindowKind::Sliding, limit, window)))
+    }
+
+    fn from_limiter(limit: Limiter) -> Self {
+        Self { limit: Arc::new(limit) }
     }
 
     /// Rate limit [Request] based on it's [HeaderMap] state and given client [SocketAddr]
@@ -45,7 +90,10 @@ impl RateLimit {
             .or_else(|| maybe_x_real_ip(headers))
             .or_else(|| maybe_forwarded(headers))
             .unwrap_or_else(|| addr.ip());
-        self.limit.check_key(&addr).map_err(TooManyRequests::from)
+        match *self.limit {
+            Limiter::Gcra(ref limit) => limit.check_key(&addr).map_err(TooManyRequests::from),
+            Limiter::Window(ref limit) => limit.check_key(&addr),
+        }
     }
 }
 
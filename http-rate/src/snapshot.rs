@@ -10,14 +10,25 @@ use crate::{nanos::Nanos, quota::Quota};
 /// Information about the rate-limiting state used to reach a decision.
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub struct RateSnapshot {
-    // The "weight" of a single packet in units of time.
-    t: Nanos,
-    // The "burst capacity" of the bucket.
-    tau: Nanos,
-    // The time at which the measurement was taken.
-    pub(crate) time_of_measurement: Nanos,
-    // The next time a cell is expected to arrive
-    pub(crate) tat: Nanos,
+    inner: Inner,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+enum Inner {
+    Gcra {
+        // The "weight" of a single packet in units of time.
+        t: Nanos,
+        // The "burst capacity" of the bucket.
+        tau: Nanos,
+        // The time at which the measurement was taken.
+        time_of_measurement: Nanos,
+        // The next time a cell is expected to arrive
+        tat: Nanos,
+    },
+    Window {
+        limit: u32,
+        remaining: u32,
+    },
 }
 
 const X_RT_LIMIT: HeaderName = HeaderName::from_static("x-ratelimit-limit");
@@ -28,30 +39,64 @@ impl RateSnapshot {
     /// Header: `x-ratelimit-limit: <num>`
     /// Header: `x-ratelimit-remaining: <num>`
     pub fn extend_response<Ext>(&self, res: &mut Response<Ext>) {
-        let burst_size = self.quota().burst_size().get();
-        let remaining_burst_capacity = self.remaining_burst_capacity();
+        let (limit, remaining) = match self.inner {
+            Inner::Gcra { t, tau, .. } => (
+                Quota::from_gcra_parameters(t, tau).burst_size().get(),
+                self.remaining_burst_capacity(),
+            ),
+            Inner::Window { limit, remaining } => (limit, remaining),
+        };
         let headers = res.headers_mut();
-        headers.insert(X_RT_LIMIT, HeaderValue::from(burst_size));
-        headers.insert(X_RT_REMAINING, HeaderValue::from(remaining_burst_capacity));
+        headers.insert(X_RT_LIMIT, HeaderValue::from(limit));
+        headers.insert(X_RT_REMAINING, HeaderValue::from(remaining));
     }
 
     pub(crate) const fn new(t: Nanos, tau: Nanos, time_of_measurement: Nanos, tat: Nanos) -> Self {
         Self {
-            t,
-            tau,
-            time_of_measurement,
-            tat,
+            inner: Inner::Gcra {
+                t,
+                tau,
+                time_of_measurement,
+                tat,
+            },
+        }
+    }
+
+    pub(crate) const fn window(limit: u32, remaining: u32) -> Self {
+        Self {
+            inner: Inner::Window { limit, remaining },
+        }
+    }
+
+    // The next time a cell is expected to arrive. Only gcra snapshot carries it.
+    pub(crate) fn tat(&self) -> Option<Nanos> {
+        match self.inner {
+            Inner::Gcra { tat, .. } => Some(tat),
+            Inner::Window { .. } => None,
         }
     }
 
-    /// Returns the quota used to make the rate limiting decision.
-    pub(crate) fn quota(&self) -> Quota {
-        Quota::from_gcra_parameters(self.t, self.tau)
+    /// Returns the quota used to make the rate limiting decision. Only gcra snapshot carries it.
+    pub(crate) fn quota(&self) -> Option<Quota> {
+        match self.inner {
+            Inner::Gcra { t, tau, .. } => Some(Quota::from_gcra_parameters(t, tau)),
+            Inner::Window { .. } => None,
+        }
     }
 
     fn remaining_burst_capacity(&self) -> u32 {
-        let t0 = self.time_of_measurement + self.t;
-        (cmp::min((t0 + self.tau).saturating_sub(self.tat).as_u64(), self.tau.as_u64()) / self.t.as_u64()) as u32
+        match self.inner {
+            Inner::Gcra {
+                t,
+                tau,
+                time_of_measurement,
+                tat,
+            } => {
+                let t0 = time_of_measurement + t;
+                (cmp::min((t0 + tau).saturating_sub(tat).as_u64(), tau.as_u64()) / t.as_u64()) as u32
+            }
+            Inner::Window { remaining, .. } => remaining,
+        }
     }
 }
 
@@ -0,0 +1,220 @@
+//! fixed window and sliding window rate limiting algorithms.
+
+use core::{hash::Hash, time::Duration};
+
+use std::{collections::HashMap, sync::Mutex};
+
+use crate::{
+    error::TooManyRequests,
+    nanos::Nanos,
+    snapshot::RateSnapshot,
+    timer::{DefaultTimer, Reference, Timer},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum WindowKind {
+    Fixed,
+    Sliding,
+}
+
+/// A keyed rate limiter counting cells in time windows.
+///
+/// Windows are aligned to the creation time of rate limiter and every key keeps a constant size counter state.
+pub(crate) struct WindowLimiter<K, C = DefaultTimer>
+where
+    C: Timer,
+{
+    kind: WindowKind,
+    limit: u32,
+    window: Nanos,
+    clock: C,
+    start: C::Instant,
+    state: Mutex<HashMap<K, WindowState>>,
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct WindowState {
+    // index of the window the counters belong to.
+    index: u64,
+    // count of allowed cells in current window.
+    current: u32,
+    // count of allowed cells in the window right before current one.
+    previous: u32,
+}
+
+impl<K> WindowLimiter<K>
+where
+    K: Hash + Eq + Clone,
+{
+    pub(crate) fn new(kind: WindowKind, limit: u32, window: Duration) -> Self {
+        Self::with_clock(kind, limit, window, &DefaultTimer)
+    }
+}
+
+impl<K, C> WindowLimiter<K, C>
+where
+    K: Hash + Eq + Clone,
+    C: Timer,
+{
+    /// # Panics
+    /// - When limit or window is zero.
+    pub fn with_clock(kind: WindowKind, limit: u32, window: Duration, clock: &C) -> Self {
+        assert!(limit > 0, "window rate limit must allow at least one cell");
+        assert!(!window.is_zero(), "window of rate limit must not be zero");
+        Self {
+            kind,
+            limit,
+            window: window.into(),
+            clock: clock.clone(),
+            start: clock.now(),
+            state: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Allow a single cell through the rate limiter for the given key.
+    pub(crate) fn check_key(&self, key: &K) -> Result<RateSnapshot, TooManyRequests> {
+        let now = self.clock.now().duration_since(self.start).as_u64();
+        let window = self.window.as_u64();
+        let index = now / window;
+        let elapsed = now % window;
+
+        let mut map = self.state.lock().unwrap();
+        let state = map.entry(key.clone()).or_default();
+
+        if state.index != index {
+            // counters of the window right before current one are kept for sliding window's weighting.
+            state.previous = if state.index + 1 == index { state.current } else { 0 };
+            state.current = 0;
+            state.index = index;
+        }
+
+        match self.kind {
+            WindowKind::Fixed => {
+                if state.current >= self.limit {
+                    return Err(wait_time(window - elapsed));
+                }
+                state.current += 1;
+                Ok(RateSnapshot::window(self.limit, self.limit - state.current))
+            }
+            WindowKind::Sliding => {
+                // all math is scaled by window length to avoid floating point. the weighted count is:
+                // previous * (window - elapsed) / window + current
+                let (window, elapsed) = (u128::from(window), u128::from(elapsed));
+                let limit = u128::from(self.limit) * window;
+                let previous = u128::from(state.previous) * (window - elapsed);
+
+                if previous + u128::from(state.current) * window >= limit {
+                    let wait = if state.current >= self.limit {
+                        // previous window must be weighted down to zero in next window.
+                        window - elapsed + 1
+                    } else {
+                        // previous window must be weighted down enough to fit one more cell.
+                        let free = (limit - u128::from(state.current) * window) / u128::from(state.previous);
+                        (window - free + 1).saturating_sub(elapsed)
+                    };
+                    return Err(wait_time(wait as u64));
+                }
+
+                state.current += 1;
+                let used = (previous + u128::from(state.current) * window).div_ceil(window) as u32;
+                Ok(RateSnapshot::window(self.limit, self.limit.saturating_sub(used)))
+            }
+        }
+    }
+
+    #[cfg(test)]
+    fn len(&self) -> usize {
+        self.state.lock().unwrap().len()
+    }
+}
+
+fn wait_time(nanos: u64) -> TooManyRequests {
+    TooManyRequests::from_wait_time(Duration::from_nanos(nanos))
+}
+
+#[cfg(test)]
+mod test {
+    use http::Response;
+
+    use crate::timer::FakeRelativeClock;
+
+    use super::*;
+
+    fn remaining(snap: RateSnapshot) -> u32 {
+        let mut res = Response::new(());
+        snap.extend_response(&mut res);
+        res.headers()
+            .get("x-ratelimit-remaining")
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .parse()
+            .unwrap()
+    }
+
+    fn after(e: TooManyRequests) -> u64 {
+        let mut res = Response::new(());
+        e.extend_response(&mut res);
+        res.headers()
+            .get("x-ratelimit-after")
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .parse()
+            .unwrap()
+    }
+
+    #[test]
+    fn fixed_window() {
+        let clock = FakeRelativeClock::default();
+        let lim = WindowLimiter::with_clock(WindowKind::Fixed, 2, Duration::from_secs(10), &clock);
+
+        assert_eq!(lim.check_key(&1).map(remaining).unwrap(), 1);
+        assert_eq!(lim.check_key(&1).map(remaining).unwrap(), 0);
+        clock.advance(Duration::from_secs(3));
+        assert_eq!(lim.check_key(&1).map_err(after).err(), Some(7));
+
+        // other key has it's own counter.
+        assert_eq!(lim.check_key(&2).map(remaining).unwrap(), 1);
+        assert_eq!(lim.len(), 2);
+
+        // counter resets at window boundary and a burst up to 2 * limit can pass around it.
+        clock.advance(Duration::from_secs(7));
+        assert!(lim.check_key(&1).is_ok());
+        assert!(lim.check_key(&1).is_ok());
+        assert!(lim.check_key(&1).is_err());
+    }
+
+    #[test]
+    fn sliding_window() {
+        let clock = FakeRelativeClock::default();
+        let lim = WindowLimiter::with_clock(WindowKind::Sliding, 4, Duration::from_secs(10), &clock);
+
+        for n in (0..4).rev() {
+            assert_eq!(lim.check_key(&1).map(remaining).unwrap(), n);
+        }
+        assert_eq!(lim.check_key(&1).map_err(after).err(), Some(10));
+
+        // previous window is weighted 9/10 and counts as 3.6 cells.
+        clock.advance(Duration::from_secs(11));
+        assert_eq!(lim.check_key(&1).map(remaining).unwrap(), 0);
+        // next cell fits when previous window is weighted below 3/4.
+        assert_eq!(lim.check_key(&1).map_err(after).err(), Some(1));
+
+        clock.advance(Duration::from_secs(2));
+        assert!(lim.check_key(&1).is_ok());
+        assert!(lim.check_key(&1).is_err());
+
+        // previous window is forgotten when it's not adjacent to current one.
+        clock.advance(Duration::from_secs(20));
+        for n in (0..4).rev() {
+            assert_eq!(lim.check_key(&1).map(remaining).unwrap(), n);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn zero_window() {
+        WindowLimiter::<u32>::new(WindowKind::Fixed, 1, Duration::ZERO);
+    }
+}
@@ -8,6 +8,7 @@
 
 use core::{
     convert::Infallible,
+    fmt,
     marker::PhantomData,
     mem,
     pin::Pin,
@@ -69,6 +70,20 @@ impl Stream for RequestBody {
             Self::None => Poll::Ready(None),
         }
     }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self {
+            #[cfg(feature = "http1")]
+            Self::H1(body) => body.size_hint(),
+            #[cfg(feature = "http2")]
+            Self::H2(body) => body.size_hint(),
+            #[cfg(feature = "http3")]
+            Self::H3(body) => body.size_hint(),
+            Self::Unknown(body) => body.size_hint(),
+            Self::None => none_body_hint(),
+        }
+    }
 }
 
 impl<B> From<NoneBody<B>> for RequestBody {
@@ -298,6 +313,106 @@ impl Stream for BoxBody {
     }
 }
 
+pin_project! {
+    /// stream body with length declared ahead of polling.
+    ///
+    /// It's size hint is exact so response is sent with `content-length` header instead of chunked transfer
+    /// encoding. Stream producing more or less bytes than the declared length yields [BodyLengthMismatch]
+    /// error and dispatcher terminates the connection(http/1) or resets the stream(http/2 and http/3) so client
+    /// can tell the body is incomplete.
+    pub struct SizedStream<B> {
+        #[pin]
+        stream: B,
+        len: usize,
+        remaining: usize,
+    }
+}
+
+impl<B> SizedStream<B> {
+    /// construct a new body from stream and total length of it's bytes.
+    #[inline]
+    pub const fn new(stream: B, len: usize) -> Self {
+        Self {
+            stream,
+            len,
+            remaining: len,
+        }
+    }
+}
+
+impl<B, T, E> Stream for SizedStream<B>
+where
+    B: Stream<Item = Result<T, E>>,
+    T: Into<Bytes>,
+    E: Into<BodyError>,
+{
+    type Item = Result<Bytes, BodyError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+        match this.stream.poll_next(cx) {
+            Poll::Ready(Some(Ok(item))) => {
+                let bytes = item.into();
+                let Some(remaining) = this.remaining.checked_sub(bytes.len()) else {
+                    let actual = *this.len - *this.remaining + bytes.len();
+                    *this.remaining = 0;
+                    return Poll::Ready(Some(Err(BodyLengthMismatch::new(*this.len, actual).into())));
+                };
+                *this.remaining = remaining;
+                Poll::Ready(Some(Ok(bytes)))
+            }
+            Poll::Ready(Some(Err(e))) => Poll::Ready(Some(Err(e.into()))),
+            Poll::Ready(None) if *this.remaining > 0 => {
+                let actual = *this.len - mem::replace(this.remaining, 0);
+                Poll::Ready(Some(Err(BodyLengthMismatch::new(*this.len, actual).into())))
+            }
+            Poll::Ready(None) => Poll::Ready(None),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        exact_body_hint(self.len)
+    }
+}
+
+/// error of [SizedStream] producing bytes not matching it's declared length.
+#[derive(Debug)]
+pub struct BodyLengthMismatch {
+    expected: usize,
+    actual: usize,
+}
+
+impl BodyLengthMismatch {
+    const fn new(expected: usize, actual: usize) -> Self {
+        Self { expected, actual }
+    }
+
+    /// declared length of body.
+    pub fn expected(&self) -> usize {
+        self.expected
+    }
+
+    /// length of body produced when the mismatch is detected. when it's larger than declared length it's
+    /// counted up to the first item crossing the boundary.
+    pub fn actual(&self) -> usize {
+        self.actual
+    }
+}
+
+impl fmt::Display for BodyLengthMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "body length mismatch. expected {} bytes but stream produced {} bytes",
+            self.expected, self.actual
+        )
+    }
+}
+
+impl error::Error for BodyLengthMismatch {}
+
 pin_project! {
     /// A unified response body type.
     /// Generic type is for custom pinned response body(type implement [Stream](futures_core::Stream)).
@@ -339,6 +454,18 @@ impl ResponseBody {
     {
         Self::stream(BoxBody::new(stream))
     }
+
+    /// Construct a new Stream variant of ResponseBody with known total length of stream's bytes.
+    /// See [SizedStream] for detail.
+    #[inline]
+    pub fn sized_stream<B, T, E>(stream: B, len: usize) -> Self
+    where
+        B: Stream<Item = Result<T, E>> + 'static,
+        T: Into<Bytes>,
+        E: Into<BodyError>,
+    {
+        Self::box_stream(SizedStream::new(stream, len))
+    }
 }
 
 impl<B> ResponseBody<B> {
@@ -511,10 +638,49 @@ impl BodySize {
     }
 }
 
+/// Flush cadence hint of streaming response body.
+///
+/// By default dispatcher keeps polling response body and buffering it's output until write buffer is full or body
+/// stream is pending. Slow producers yielding small items(server sent events, streaming json etc) can insert it
+/// into response's [Extensions](crate::http::Extensions) to get buffered bytes written to client earlier for better
+/// time to first byte.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum Flush {
+    /// flush when write buffer is full or body stream is pending.
+    #[default]
+    Auto,
+    /// flush after every item of body stream.
+    Item,
+    /// flush after given amount of bytes of body is buffered.
+    Bytes(usize),
+}
+
+impl Flush {
+    // check if flush is due with given amount of bytes buffered since last flush.
+    #[cfg(any(feature = "http1", feature = "http2"))]
+    pub(crate) const fn is_due(&self, buffered: usize) -> bool {
+        match *self {
+            Self::Auto => false,
+            Self::Item => buffered > 0,
+            Self::Bytes(n) => buffered > 0 && buffered >= n,
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
 
+    #[cfg(any(feature = "http1", feature = "http2"))]
+    #[test]
+    fn flush_due() {
+        assert!(!Flush::Auto.is_due(usize::MAX));
+        assert!(!Flush::Item.is_due(0));
+        assert!(Flush::Item.is_due(1));
+        assert!(!Flush::Bytes(4).is_due(3));
+        assert!(Flush::Bytes(4).is_due(4));
+    }
+
     #[test]
     fn stream_body_size_hint() {
         let body = BoxBody::new(Once::new(Bytes::new()));
@@ -522,5 +688,49 @@ mod test {
 
         let body = BoxBody::new(NoneBody::<Bytes>::default());
         assert_eq!(BodySize::from_stream(&body), BodySize::None);
+
+        let body = ResponseBody::sized_stream(Once::new(Bytes::new()), 996);
+        assert_eq!(BodySize::from_stream(&body), BodySize::Sized(996));
+    }
+
+    #[test]
+    fn sized_stream_mismatch() {
+        use core::future::poll_fn;
+
+        use xitca_unsafe_collection::futures::NowOrPanic;
+
+        fn collect(len: usize) -> Result<usize, BodyError> {
+            let chunks = [Bytes::from_static(b"996"), Bytes::from_static(b"251")];
+            let body = SizedStream::new(Iter(chunks.into_iter()), len);
+            let mut body = core::pin::pin!(body);
+            let mut total = 0;
+            while let Some(res) = poll_fn(|cx| body.as_mut().poll_next(cx)).now_or_panic() {
+                total += res?.len();
+            }
+            Ok(total)
+        }
+
+        struct Iter<I>(I);
+
+        impl<I> Stream for Iter<I>
+        where
+            I: Iterator<Item = Bytes> + Unpin,
+        {
+            type Item = Result<Bytes, Infallible>;
+
+            fn poll_next(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+                Poll::Ready(self.get_mut().0.next().map(Ok))
+            }
+        }
+
+        assert_eq!(collect(6).unwrap(), 6);
+
+        let e = collect(7).unwrap_err();
+        let e = e.downcast_ref::<BodyLengthMismatch>().unwrap();
+        assert_eq!((e.expected(), e.actual()), (7, 6));
+
+        let e = collect(4).unwrap_err();
+        let e = e.downcast_ref::<BodyLengthMismatch>().unwrap();
+        assert_eq!((e.expected(), e.actual()), (4, 6));
     }
 }
@@ -37,6 +37,8 @@ pub struct HttpServiceBuilder<
 > {
     pub(crate) tls_factory: FA,
     pub(crate) config: HttpServiceConfig<HEADER_LIMIT, READ_BUF_LIMIT, WRITE_BUF_LIMIT>,
+    #[cfg(feature = "metrics")]
+    pub(crate) metrics: Option<crate::metrics::HttpMetrics>,
     pub(crate) _body: PhantomData<fn(V, St)>,
 }
 
@@ -69,6 +71,8 @@ impl
         HttpServiceBuilder {
             tls_factory: tls::NoOpTlsAcceptorBuilder,
             config,
+            #[cfg(feature = "metrics")]
+            metrics: None,
             _body: PhantomData,
         }
     }
@@ -86,6 +90,8 @@ impl
         HttpServiceBuilder {
             tls_factory: tls::NoOpTlsAcceptorBuilder,
             config: HttpServiceConfig::default(),
+            #[cfg(feature = "metrics")]
+            metrics: None,
             _body: PhantomData,
         }
     }
@@ -103,6 +109,8 @@ impl
         HttpServiceBuilder {
             tls_factory: tls::NoOpTlsAcceptorBuilder,
             config: HttpServiceConfig::default(),
+            #[cfg(feature = "metrics")]
+            metrics: None,
             _body: PhantomData,
         }
     }
@@ -114,33 +122,87 @@ impl
     }
 }
 
+impl<St, FA, const HEADER_LIMIT: usize, const READ_BUF_LIMIT: usize, const WRITE_BUF_LIMIT: usize>
+    HttpServiceBuilder<marker::Http, St, FA, HEADER_LIMIT, READ_BUF_LIMIT, WRITE_BUF_LIMIT>
+{
+    #[cfg(all(feature = "http1", feature = "http2"))]
+    /// enable cleartext http/2 (h2c) on connections not negotiated as http/2 by tls.
+    ///
+    /// See [HttpServiceConfig::h2c] for detail.
+    ///
+    /// # Note
+    /// This setting is part of [HttpServiceConfig] and must be set after [HttpServiceBuilder::config] is
+    /// called.
+    pub fn h2c(mut self) -> Self {
+        self.config = self.config.h2c();
+        self
+    }
+}
+
 impl<V, St, FA, const HEADER_LIMIT: usize, const READ_BUF_LIMIT: usize, const WRITE_BUF_LIMIT: usize>
     HttpServiceBuilder<V, St, FA, HEADER_LIMIT, READ_BUF_LIMIT, WRITE_BUF_LIMIT>
 {
     /// replace service middleware's configuration.
     pub fn config<const HEADER_LIMIT_2: usize, const READ_BUF_LIMIT_2: usize, const WRITE_BUF_LIMIT_2: usize>(
         self,
-        config: HttpServiceConfig<HEADER_LIMIT_2, READ_BUF_LIMIT_2, WRITE_BUF_LIMIT_2>,
+        mut config: HttpServiceConfig<HEADER_LIMIT_2, READ_BUF_LIMIT_2, WRITE_BUF_LIMIT_2>,
     ) -> HttpServiceBuilder<V, St, FA, HEADER_LIMIT_2, READ_BUF_LIMIT_2, WRITE_BUF_LIMIT_2> {
+        config.tls = self.config.tls;
         HttpServiceBuilder {
             tls_factory: self.tls_factory,
             config,
+            #[cfg(feature = "metrics")]
+            metrics: self.metrics,
             _body: PhantomData,
         }
     }
 
     /// replace tls service. tls service is used for Http/1 and Http/2 protocols.
+    ///
+    /// requests received from connections served by tls service are marked as secure.
+    /// See [RequestExt::is_secure](crate::http::RequestExt::is_secure) for detail.
     pub fn with_tls<TlsF>(
         self,
         tls_factory: TlsF,
     ) -> HttpServiceBuilder<V, St, TlsF, HEADER_LIMIT, READ_BUF_LIMIT, WRITE_BUF_LIMIT> {
+        let mut config = self.config;
+        config.tls = true;
         HttpServiceBuilder {
             tls_factory,
-            config: self.config,
+            config,
+            #[cfg(feature = "metrics")]
+            metrics: self.metrics,
             _body: PhantomData,
         }
     }
 
+    #[cfg(feature = "metrics")]
+    /// attach a [HttpMetrics] handle to service middleware. connection and request counters would be
+    /// collected into it and can be observed with [HttpMetrics::snapshot].
+    ///
+    /// [HttpMetrics]: crate::metrics::HttpMetrics
+    /// [HttpMetrics::snapshot]: crate::metrics::HttpMetrics::snapshot
+    pub fn metrics(mut self, metrics: crate::metrics::HttpMetrics) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    #[cfg(feature = "http1")]
+    /// enable per worker load shedding for http/1 connections.
+    ///
+    /// See [HttpServiceConfig::load_shed] for detail.
+    ///
+    /// # Note
+    /// This setting is part of [HttpServiceConfig] and must be set after [HttpServiceBuilder::config] is
+    /// called.
+    ///
+    /// # Panics
+    /// When `low_water_mark` is larger than `high_water_mark`.
+    pub fn load_shed(mut self, high_water_mark: usize, low_water_mark: usize) -> Self {
+        self.config = self.config.load_shed(high_water_mark, low_water_mark);
+        self
+    }
+
     #[cfg(feature = "openssl")]
     /// use openssl as tls service. tls service is used for Http/1 and Http/2 protocols.
     pub fn openssl(
@@ -198,6 +260,9 @@ where
     async fn call(&self, res: Result<S, E>) -> Result<Self::Response, Self::Error> {
         let service = res.map_err(|e| Box::new(e) as Error)?;
         let tls_acceptor = self.tls_factory.call(()).await.map_err(|e| Box::new(e) as Error)?;
-        Ok(HttpService::new(self.config, service, tls_acceptor))
+        let service = HttpService::new(self.config, service, tls_acceptor);
+        #[cfg(feature = "metrics")]
+        let service = service.with_metrics(self.metrics.clone().unwrap_or_default());
+        Ok(service)
     }
 }
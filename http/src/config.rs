@@ -19,6 +19,22 @@ pub const DEFAULT_WRITE_BUF_LIMIT: usize = 8192 + 4096 * 100;
 /// 64 chosen for no particular reason.
 pub const DEFAULT_HEADER_LIMIT: usize = 64;
 
+/// The default maximum request uri length in bytes for http/1.
+///
+/// Request with longer uri is rejected with "414 URI Too Long" response.
+pub const DEFAULT_MAX_URI_LEN: usize = 8 * 1024;
+
+/// The default initial flow control window size of http/2 request stream and connection.
+///
+/// This is the value defined by http/2 spec and the max amount of request body bytes a client can send before
+/// the application consumes any of them.
+pub const DEFAULT_H2_INITIAL_WINDOW_SIZE: u32 = 65_535;
+
+/// The default maximum number of concurrent http/2 request streams of a connection.
+///
+/// Stream opened by client beyond the limit is refused with `REFUSED_STREAM` error code.
+pub const DEFAULT_H2_MAX_CONCURRENT_STREAMS: u32 = 256;
+
 #[derive(Copy, Clone)]
 pub struct HttpServiceConfig<
     const HEADER_LIMIT: usize = DEFAULT_HEADER_LIMIT,
@@ -30,6 +46,20 @@ pub struct HttpServiceConfig<
     pub(crate) request_head_timeout: Duration,
     pub(crate) tls_accept_timeout: Duration,
     pub(crate) peek_protocol: bool,
+    pub(crate) date_header: bool,
+    pub(crate) server_header: Option<&'static str>,
+    pub(crate) max_uri_len: usize,
+    pub(crate) keep_alive_header: bool,
+    pub(crate) lenient_request_parsing: bool,
+    pub(crate) h2_initial_window_size: u32,
+    pub(crate) h2_initial_connection_window_size: u32,
+    pub(crate) h2_max_concurrent_streams: u32,
+    pub(crate) h2_ping_interval: Option<Duration>,
+    pub(crate) h2_ping_timeout: Option<Duration>,
+    pub(crate) h2c: bool,
+    pub(crate) load_shed: Option<(usize, usize)>,
+    // set by HttpServiceBuilder when a tls service is in use. not a user facing setting.
+    pub(crate) tls: bool,
 }
 
 impl Default for HttpServiceConfig {
@@ -46,6 +76,19 @@ impl HttpServiceConfig {
             request_head_timeout: Duration::from_secs(5),
             tls_accept_timeout: Duration::from_secs(3),
             peek_protocol: false,
+            date_header: true,
+            server_header: None,
+            max_uri_len: DEFAULT_MAX_URI_LEN,
+            keep_alive_header: true,
+            lenient_request_parsing: false,
+            h2_initial_window_size: DEFAULT_H2_INITIAL_WINDOW_SIZE,
+            h2_initial_connection_window_size: DEFAULT_H2_INITIAL_WINDOW_SIZE,
+            h2_max_concurrent_streams: DEFAULT_H2_MAX_CONCURRENT_STREAMS,
+            h2_ping_interval: None,
+            h2_ping_timeout: None,
+            h2c: false,
+            load_shed: None,
+            tls: false,
         }
     }
 }
@@ -128,6 +171,157 @@ impl<const HEADER_LIMIT: usize, const READ_BUF_LIMIT: usize, const WRITE_BUF_LIM
         self
     }
 
+    /// Disable `date` header automatically added to response.
+    ///
+    /// By default every response without `date` header gets one with value from a cached date time that is
+    /// updated every 500 milliseconds. Response with `date` header set by service is not affected by this setting.
+    pub fn disable_date_header(mut self) -> Self {
+        self.date_header = false;
+        self
+    }
+
+    /// Define value of `server` header automatically added to response.
+    ///
+    /// By default no `server` header is added. Response with `server` header set by service is not affected by
+    /// this setting.
+    ///
+    /// # Panics
+    /// When value is not a valid header value.
+    pub fn server_header(mut self, value: &'static str) -> Self {
+        let _ = crate::http::header::HeaderValue::from_static(value);
+        self.server_header = Some(value);
+        self
+    }
+
+    /// Define max length of request uri in bytes for http/1.
+    ///
+    /// Request line is checked before the rest of request head is parsed and request with longer uri is rejected
+    /// with "414 URI Too Long" response.
+    ///
+    /// See [DEFAULT_MAX_URI_LEN] for default value.
+    pub fn max_uri_len(mut self, len: usize) -> Self {
+        self.max_uri_len = len;
+        self
+    }
+
+    /// Disable `connection: keep-alive` and `keep-alive: timeout=<secs>` headers automatically added to http/1
+    /// response.
+    ///
+    /// By default these headers are added to response of HTTP/1.0 request when it's connection is kept alive.
+    /// HTTP/1.0 client does not reuse connection without them. The timeout is the value of
+    /// [HttpServiceConfig::keep_alive_timeout] in seconds. HTTP/1.1 response is not affected by this setting as
+    /// keep-alive is implicit for it.
+    pub fn disable_keep_alive_header(mut self) -> Self {
+        self.keep_alive_header = false;
+        self
+    }
+
+    /// Enable lenient parsing of http/1 request head for legacy client.
+    ///
+    /// By default request head is parsed strictly to defend against request smuggling and following requests are
+    /// rejected with "400 Bad Request" response and their connections are closed:
+    /// - request carrying both `content-length` and `transfer-encoding` headers.
+    /// - request carrying multiple `content-length` headers.
+    /// - request containing obsolete line folding in header.
+    ///
+    /// In lenient mode duplicate `content-length` headers with identical value are accepted and obsolete line
+    /// folding is replaced with spaces. Request carrying both `content-length` and `transfer-encoding` headers is
+    /// accepted when it's body can be decoded unambiguously and it's connection is closed after response is sent.
+    pub fn lenient_request_parsing(mut self) -> Self {
+        self.lenient_request_parsing = true;
+        self
+    }
+
+    /// Define initial flow control window size of every http/2 request stream.
+    ///
+    /// This is the upper bound of request body bytes buffered for a single request before the application
+    /// consumes them. Window is only released back to client when request body is read so a slow consumer
+    /// backpressures the client instead of buffering unboundedly on server side.
+    ///
+    /// See [DEFAULT_H2_INITIAL_WINDOW_SIZE] for default value.
+    pub fn h2_initial_window_size(mut self, size: u32) -> Self {
+        self.h2_initial_window_size = size;
+        self
+    }
+
+    /// Define initial flow control window size of http/2 connection.
+    ///
+    /// This is the upper bound of request body bytes buffered for all requests of a single connection before the
+    /// application consumes them.
+    ///
+    /// See [DEFAULT_H2_INITIAL_WINDOW_SIZE] for default value.
+    pub fn h2_initial_connection_window_size(mut self, size: u32) -> Self {
+        self.h2_initial_connection_window_size = size;
+        self
+    }
+
+    /// Define max number of concurrent http/2 request streams of a connection.
+    ///
+    /// The limit is advertised to client and stream opened beyond it is refused with `REFUSED_STREAM` error code
+    /// without being dispatched to service. The connection and it's other streams are not affected. A stream is
+    /// counted until it's response is sent and it's request body is received or dropped.
+    ///
+    /// See [DEFAULT_H2_MAX_CONCURRENT_STREAMS] for default value.
+    pub fn h2_max_concurrent_streams(mut self, max: u32) -> Self {
+        self.h2_max_concurrent_streams = max;
+        self
+    }
+
+    /// Define duration of how long a http/2 connection stays idle before server sends a PING frame to probe the
+    /// liveness of peer.
+    ///
+    /// Connection is idle when there is no request being served. Every PONG received from peer resets the
+    /// duration.
+    ///
+    /// By default the value of [HttpServiceConfig::keep_alive_timeout] is used.
+    pub fn h2_ping_interval(mut self, dur: Duration) -> Self {
+        self.h2_ping_interval = Some(dur);
+        self
+    }
+
+    /// Define duration of how long server waits for PONG frame after sending PING frame.
+    ///
+    /// Peer fails to answer in time is treated as dead and the connection is closed after sending GOAWAY frame.
+    /// This frees resources of half-open connection left behind by crashed client or flaky load balancer.
+    ///
+    /// By default 10 times of the value of [HttpServiceConfig::keep_alive_timeout] is used.
+    pub fn h2_ping_timeout(mut self, dur: Duration) -> Self {
+        self.h2_ping_timeout = Some(dur);
+        self
+    }
+
+    #[cfg(feature = "http1")]
+    /// Enable per worker load shedding for http/1 connections.
+    ///
+    /// Bytes buffered by connections (read buffer and write buffer not yet flushed to io) are tracked per worker.
+    /// When the sum exceeds `high_water_mark` new requests are answered with "503 Service Unavailable" and their
+    /// connections are closed until the sum drops to `low_water_mark` or below. Requests shed are counted by
+    /// `HttpMetrics` when metrics feature is enabled.
+    ///
+    /// # Panics
+    /// When `low_water_mark` is larger than `high_water_mark`.
+    pub fn load_shed(mut self, high_water_mark: usize, low_water_mark: usize) -> Self {
+        assert!(
+            low_water_mark <= high_water_mark,
+            "low_water_mark must not be larger than high_water_mark"
+        );
+        self.load_shed = Some((high_water_mark, low_water_mark));
+        self
+    }
+
+    #[cfg(all(feature = "http1", feature = "http2"))]
+    /// Enable cleartext http/2 (h2c) on connections not negotiated as http/2 by tls.
+    ///
+    /// Client can start http/2 either with prior knowledge by sending http/2 connection preface directly or with
+    /// a http/1 request carrying `Upgrade: h2c` and `HTTP2-Settings` headers. For the latter the request is
+    /// answered with "101 Switching Protocols" and served as the first request of http/2 connection without
+    /// reaching service as http/1 request. Upgrade request with malformed `HTTP2-Settings` header is served as
+    /// http/1 request.
+    pub fn h2c(mut self) -> Self {
+        self.h2c = true;
+        self
+    }
+
     #[doc(hidden)]
     /// A shortcut for mutating const generic params.
     pub fn mutate_const_generic<
@@ -143,6 +337,19 @@ impl<const HEADER_LIMIT: usize, const READ_BUF_LIMIT: usize, const WRITE_BUF_LIM
             request_head_timeout: self.request_head_timeout,
             tls_accept_timeout: self.tls_accept_timeout,
             peek_protocol: self.peek_protocol,
+            date_header: self.date_header,
+            server_header: self.server_header,
+            max_uri_len: self.max_uri_len,
+            keep_alive_header: self.keep_alive_header,
+            lenient_request_parsing: self.lenient_request_parsing,
+            h2_initial_window_size: self.h2_initial_window_size,
+            h2_initial_connection_window_size: self.h2_initial_connection_window_size,
+            h2_max_concurrent_streams: self.h2_max_concurrent_streams,
+            h2_ping_interval: self.h2_ping_interval,
+            h2_ping_timeout: self.h2_ping_timeout,
+            h2c: self.h2c,
+            load_shed: self.load_shed,
+            tls: self.tls,
         }
     }
 }
@@ -6,11 +6,16 @@ use core::{
     task::{Context, Poll, Waker},
 };
 
-use std::{collections::VecDeque, io, rc::Rc};
+use std::{
+    collections::VecDeque,
+    io,
+    rc::Rc,
+    sync::{Arc, Mutex},
+};
 
 use futures_core::stream::Stream;
 
-use crate::bytes::Bytes;
+use crate::{bytes::Bytes, http::HeaderMap};
 
 /// max buffer size 32k
 pub(crate) const MAX_BUFFER_SIZE: usize = 32_768;
@@ -68,6 +73,13 @@ impl Stream for RequestBody {
             RequestBodyInner::Completion(ref mut body) => Pin::new(body).poll_next(cx),
         }
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self.0 {
+            RequestBodyInner::None => crate::body::none_body_hint(),
+            _ => (0, None),
+        }
+    }
 }
 
 impl From<RequestBody> for crate::body::RequestBody {
@@ -76,6 +88,27 @@ impl From<RequestBody> for crate::body::RequestBody {
     }
 }
 
+/// trailer headers of chunked request body.
+///
+/// When request body is chunked encoded it's inserted into request's [Extensions] by http/1 dispatcher and
+/// can be retrieved from there. Trailers are only available after request body is fully consumed.
+///
+/// [Extensions]: crate::http::Extensions
+#[derive(Clone, Debug, Default)]
+pub struct RequestTrailers(Arc<Mutex<Option<HeaderMap>>>);
+
+impl RequestTrailers {
+    /// get a copy of trailer headers. returns [None] when request body is not fully consumed or when request
+    /// does not contain any trailer.
+    pub fn get(&self) -> Option<HeaderMap> {
+        self.0.lock().unwrap().clone()
+    }
+
+    pub(super) fn set(&self, trailers: HeaderMap) {
+        *self.0.lock().unwrap() = Some(trailers);
+    }
+}
+
 /// Sender part of the payload stream
 pub struct RequestBodySender(RequestBodyInner);
 
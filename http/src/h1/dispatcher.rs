@@ -16,20 +16,22 @@ use xitca_service::Service;
 use xitca_unsafe_collection::futures::{Select as _, SelectOutput};
 
 use crate::{
-    body::NoneBody,
+    body::{Flush, NoneBody},
     bytes::{Bytes, EitherBuf},
     config::HttpServiceConfig,
     date::DateTime,
     h1::{
-        body::{RequestBody, RequestBodySender},
+        body::{RequestBody, RequestBodySender, RequestTrailers},
         error::Error,
+        upgrade::{self, Upgrade, UpgradeKind},
     },
     http::{
         response::{Parts, Response},
-        StatusCode,
+        Disconnect, HeaderMap, StatusCode,
     },
     util::{
-        buffered::{BufferedIo, ListWriteBuf, ReadBuf, WriteBuf},
+        buffered::{BufInterest, BufferedIo, ListWriteBuf, ReadBuf, WriteBuf},
+        shed::LoadShed,
         timer::{KeepAlive, Timeout},
     },
 };
@@ -45,6 +47,8 @@ use super::proto::{
 type ExtRequest<B> = crate::http::Request<crate::http::RequestExt<B>>;
 
 /// function to generic over different writer buffer types dispatcher.
+///
+/// When [Upgrade] is returned the connection is taken over by service and caller must hand the io to it.
 pub(crate) async fn run<
     'a,
     St,
@@ -63,7 +67,8 @@ pub(crate) async fn run<
     config: HttpServiceConfig<HEADER_LIMIT, READ_BUF_LIMIT, WRITE_BUF_LIMIT>,
     service: &'a S,
     date: &'a D,
-) -> Result<(), Error<S::Error, BE>>
+    shed: Option<&'a LoadShed>,
+) -> Result<Option<Upgrade>, Error<S::Error, BE>>
 where
     S: Service<ExtRequest<ReqB>, Response = Response<ResB>>,
     ReqB: From<RequestBody>,
@@ -77,9 +82,9 @@ where
         EitherBuf::Right(WriteBuf::<WRITE_BUF_LIMIT>::default())
     };
 
-    Dispatcher::new(io, addr, timer, config, service, date, write_buf)
-        .run()
-        .await
+    let ctx = Context::with_config(addr, date, shed, &config);
+
+    Dispatcher::new(io, timer, config, ctx, service, write_buf).run().await
 }
 
 /// Http/1 dispatcher
@@ -88,6 +93,12 @@ struct Dispatcher<'a, St, S, ReqB, W, D, const HEADER_LIMIT: usize, const READ_B
     timer: Timer<'a>,
     ctx: Context<'a, D, HEADER_LIMIT>,
     service: &'a S,
+    disconnect: Disconnect,
+    // remote closed it's write side with pipelined requests left in read buffer.
+    read_closed: bool,
+    secure: bool,
+    #[cfg(feature = "http2")]
+    h2c: bool,
     _phantom: PhantomData<ReqB>,
 }
 
@@ -168,60 +179,106 @@ where
 {
     fn new<const WRITE_BUF_LIMIT: usize>(
         io: &'a mut St,
-        addr: SocketAddr,
         timer: Pin<&'a mut KeepAlive>,
         config: HttpServiceConfig<HEADER_LIMIT, READ_BUF_LIMIT, WRITE_BUF_LIMIT>,
+        ctx: Context<'a, D, HEADER_LIMIT>,
         service: &'a S,
-        date: &'a D,
         write_buf: W,
     ) -> Self {
         Self {
             io: BufferedIo::new(io, write_buf),
             timer: Timer::new(timer, config.keep_alive_timeout, config.request_head_timeout),
-            ctx: Context::with_addr(addr, date),
+            ctx,
             service,
+            disconnect: Disconnect::new(),
+            read_closed: false,
+            secure: config.tls,
+            #[cfg(feature = "http2")]
+            h2c: config.h2c,
             _phantom: PhantomData,
         }
     }
 
-    async fn run(mut self) -> Result<(), Error<S::Error, BE>> {
+    async fn run(mut self) -> Result<Option<Upgrade>, Error<S::Error, BE>> {
         loop {
             match self._run().await {
-                Ok(_) => {}
+                Ok(None) => {}
+                Ok(Some(upgrade)) => {
+                    // make sure response head is sent before connection is handed over.
+                    self.io.drain_write().await?;
+                    return Ok(Some(upgrade));
+                }
                 Err(Error::KeepAliveExpire) => {
                     trace!(target: "h1_dispatcher", "Connection keep-alive expired. Shutting down");
-                    return Ok(());
+                    return Ok(None);
                 }
                 Err(Error::RequestTimeout) => self.request_error(|| status_only(StatusCode::REQUEST_TIMEOUT)),
                 Err(Error::Proto(ProtoError::HeaderTooLarge)) => {
                     self.request_error(|| status_only(StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE))
                 }
+                Err(Error::Proto(ProtoError::UriTooLong)) => {
+                    self.request_error(|| status_only(StatusCode::URI_TOO_LONG))
+                }
                 Err(Error::Proto(_)) => self.request_error(|| status_only(StatusCode::BAD_REQUEST)),
                 Err(e) => return Err(e),
             }
 
             // TODO: add timeout for drain write?
             self.io.drain_write().await?;
+            self.track_buffered();
 
-            if self.ctx.is_connection_closed() {
-                return self.io.shutdown().await.map_err(Into::into);
+            // buffered requests are exhausted when remote has closed it's write side.
+            if self.ctx.is_connection_closed() || self.read_closed {
+                return self.io.shutdown().await.map(|_| None).map_err(Into::into);
             }
         }
     }
 
-    async fn _run(&mut self) -> Result<(), Error<S::Error, BE>> {
+    async fn _run(&mut self) -> Result<Option<Upgrade>, Error<S::Error, BE>> {
         self.timer.update(self.ctx.date().now());
         self.io
             .read()
             .timeout(self.timer.get())
             .await
             .map_err(|_| self.timer.map_to_err())??;
+        self.track_buffered();
 
         while let Some((req, decoder)) = self.ctx.decode_head::<READ_BUF_LIMIT>(&mut self.io.read_buf)? {
             self.timer.reset_state();
 
+            // worker is under memory pressure. reject request without calling service and close connection to
+            // release it's buffers.
+            if self.ctx.try_shed() {
+                self.request_error(|| status_only(StatusCode::SERVICE_UNAVAILABLE));
+                break;
+            }
+
+            // request with upgrade header is decoded with upgrade coding. h2c upgrade request is taken over before
+            // reaching service and served as the first request of http/2 connection.
+            #[cfg(feature = "http2")]
+            if self.h2c && decoder.is_upgrade() {
+                if let Some(h2c) = crate::h2c::H2cUpgrade::try_new(&req, self.secure) {
+                    self.io.write_buf.write_buf_static(crate::h2c::SWITCHING_PROTOCOLS);
+                    let read_buf = self.io.read_buf.split().freeze();
+                    return Ok(Some(Upgrade {
+                        kind: UpgradeKind::H2c(h2c),
+                        read_buf,
+                    }));
+                }
+            }
+
             let (mut body_reader, body) = BodyReader::from_coding(decoder);
-            let req = req.map(|ext| ext.map_body(|_| ReqB::from(body)));
+            let mut req = req.map(|ext| ext.map_body(|_| ReqB::from(body)));
+            req.body_mut().set_disconnect(self.disconnect.clone());
+            req.body_mut().set_secure(self.secure);
+            if let Some(ref trailers) = body_reader.trailers {
+                req.extensions_mut().insert(trailers.clone());
+            }
+            let upgrade = self.ctx.is_connect_method().then(|| {
+                let (tx, on_upgrade) = upgrade::channel();
+                req.extensions_mut().insert(on_upgrade);
+                tx
+            });
 
             let (parts, body) = match self
                 .service
@@ -235,32 +292,73 @@ where
                 SelectOutput::B(Ok(i)) => match i {},
             };
 
+            // remote has closed it's write side and this is the last buffered request. it's response is the last
+            // one of connection.
+            if self.read_closed && self.io.read_buf.is_empty() {
+                self.ctx.set_close();
+            }
+
+            let status = parts.status;
+            let flush = parts.extensions.get::<Flush>().copied().unwrap_or_default();
             let encoder = &mut self.encode_head(parts, &body)?;
+
+            // service accepted CONNECT request and want to take over the connection. response body is ignored and
+            // bytes left in read buffer belong to the tunnel.
+            if let Some(tx) = upgrade.filter(|tx| status.is_success() && tx.is_wanted()) {
+                let read_buf = self.io.read_buf.split().freeze();
+                return Ok(Some(Upgrade {
+                    kind: UpgradeKind::Connect(tx),
+                    read_buf,
+                }));
+            }
+
             let mut body = pin!(body);
 
-            loop {
-                match self
-                    .try_poll_body(body.as_mut())
-                    .select(self.io_ready(&mut body_reader))
-                    .await
-                {
-                    SelectOutput::A(Some(Ok(bytes))) => encoder.encode(bytes, &mut self.io.write_buf),
-                    SelectOutput::B(Ok(ready)) => {
-                        if ready.is_readable() {
-                            if let Err(e) = self.io.try_read() {
-                                body_reader.feed_error(e);
+            // bytes of body buffered since write buffer is last drained.
+            let mut buffered = 0;
+
+            // 204 and 304 response can not bear body and encoder is in eof state. body is dropped without being
+            // polled.
+            if !matches!(status, StatusCode::NO_CONTENT | StatusCode::NOT_MODIFIED) {
+                loop {
+                    if !self.io.write_buf.want_write_io() {
+                        buffered = 0;
+                    }
+
+                    match self
+                        .try_poll_body(body.as_mut(), flush.is_due(buffered))
+                        .select(self.io_ready(&mut body_reader))
+                        .await
+                    {
+                        SelectOutput::A(Some(Ok(bytes))) => {
+                            buffered += bytes.len();
+                            encoder.encode(bytes, &mut self.io.write_buf);
+                            self.track_buffered();
+                        }
+                        SelectOutput::B(Ok(ready)) => {
+                            if ready.is_readable() {
+                                if let Err(e) = self.io.try_read() {
+                                    body_reader.feed_error(e);
+                                }
                             }
+                            if ready.is_writable() {
+                                self.io.try_write()?;
+                            }
+                            self.track_buffered();
                         }
-                        if ready.is_writable() {
-                            self.io.try_write()?;
+                        SelectOutput::A(None) => {
+                            // body stream ended before writing the length declared by it's size hint. client is
+                            // waiting for bytes that would never come and connection must be terminated.
+                            if let TransferCoding::Length(remaining @ 1..) = *encoder {
+                                let msg = format!("response body ended with {remaining} bytes of content-length left");
+                                return Err(Error::Io(io::Error::new(io::ErrorKind::UnexpectedEof, msg)));
+                            }
+                            encoder.encode_eof(&mut self.io.write_buf);
+                            break;
                         }
+                        SelectOutput::B(Err(e)) => return Err(e.into()),
+                        SelectOutput::A(Some(Err(e))) => return Err(Error::Body(e)),
                     }
-                    SelectOutput::A(None) => {
-                        encoder.encode_eof(&mut self.io.write_buf);
-                        break;
-                    }
-                    SelectOutput::B(Err(e)) => return Err(e.into()),
-                    SelectOutput::A(Some(Err(e))) => return Err(Error::Body(e)),
                 }
             }
 
@@ -268,9 +366,15 @@ where
                 self.ctx.set_close();
                 break;
             }
+
+            // connection close requested by request or response header. pipelined requests remain in read buffer
+            // are not served.
+            if self.ctx.is_connection_closed() {
+                break;
+            }
         }
 
-        Ok(())
+        Ok(None)
     }
 
     fn encode_head(&mut self, parts: Parts, body: &impl Stream) -> Result<TransferCoding, ProtoError> {
@@ -279,9 +383,10 @@ where
 
     // an associated future of self.service that runs until service is resolved or error produced.
     async fn request_body_handler(&mut self, body_reader: &mut BodyReader) -> Result<Infallible, Error<S::Error, BE>> {
-        if self.ctx.is_expect_header() {
+        // body of CONNECT request is read on demand so bytes of tunnel stay in read buffer for connection take over.
+        if self.ctx.is_expect_header() || self.ctx.is_connect_method() {
             // wait for service future to start polling RequestBody.
-            if body_reader.wait_for_poll().await.is_ok() {
+            if body_reader.wait_for_poll().await.is_ok() && self.ctx.is_expect_header() {
                 // encode continue as service future want a body.
                 self.io.write_buf.write_buf_static(CONTINUE);
                 // use drain write to make sure continue is sent to client.
@@ -289,14 +394,39 @@ where
             }
         }
 
-        loop {
-            body_reader.ready(&mut self.io.read_buf).await;
+        while body_reader.ready_or_eof(&mut self.io.read_buf).await {
             self.io.read().await?;
+            self.track_buffered();
+        }
+
+        // request body is fully received. keep watching read side of io for client disconnect.
+        // pipelined request bytes are kept in read buffer and decoded after current response is sent.
+        while !self.read_closed && self.io.read_buf.want_write_buf() {
+            if self.io.read().await.is_err() {
+                // remote can close it's write side right after sending pipelined requests while still
+                // expecting responses. in such case buffered requests must be served in order with keep alive
+                // semantic and the connection is closed after response of the last buffered request.
+                if self.io.read_buf.is_empty() {
+                    trace!(target: "h1_dispatcher", "Connection closed by remote while serving request");
+                    self.disconnect.disconnect();
+                    self.ctx.set_close();
+                } else {
+                    self.read_closed = true;
+                }
+                break;
+            }
         }
+
+        pending().await
     }
 
-    fn try_poll_body<'b>(&self, mut body: Pin<&'b mut ResB>) -> impl Future<Output = Option<Result<Bytes, BE>>> + 'b {
-        let want_buf = self.io.write_buf.want_write_buf();
+    // when flush is due body is not polled until write buffer is drained to io.
+    fn try_poll_body<'b>(
+        &self,
+        mut body: Pin<&'b mut ResB>,
+        flush: bool,
+    ) -> impl Future<Output = Option<Result<Bytes, BE>>> + 'b {
+        let want_buf = self.io.write_buf.want_write_buf() && !flush;
         async move {
             if want_buf {
                 poll_fn(|cx| body.as_mut().poll_next(cx)).await
@@ -324,6 +454,11 @@ where
         }
     }
 
+    fn track_buffered(&mut self) {
+        self.ctx
+            .track_buffered(self.io.read_buf.len() + self.io.write_buf.buffered_len());
+    }
+
     #[cold]
     #[inline(never)]
     fn request_error(&mut self, func: impl FnOnce() -> Response<NoneBody<Bytes>>) {
@@ -336,33 +471,60 @@ where
 pub(super) struct BodyReader {
     pub(super) decoder: TransferCoding,
     tx: RequestBodySender,
+    trailers: Option<RequestTrailers>,
 }
 
 impl BodyReader {
     pub(super) fn from_coding(decoder: TransferCoding) -> (Self, RequestBody) {
         let (tx, body) = RequestBody::channel(decoder.is_eof());
-        let body_reader = BodyReader { decoder, tx };
+        let trailers = matches!(decoder, TransferCoding::DecodeChunked(..)).then(RequestTrailers::default);
+        let body_reader = BodyReader { decoder, tx, trailers };
         (body_reader, body)
     }
 
     // dispatcher MUST call this method before do any io reading.
     // a none ready state means the body consumer either is in backpressure or don't expect body.
     pub(super) async fn ready<const READ_BUF_LIMIT: usize>(&mut self, read_buf: &mut ReadBuf<READ_BUF_LIMIT>) {
+        if !self.ready_or_eof(read_buf).await {
+            pending().await
+        }
+    }
+
+    // same as BodyReader::ready except return false when body reached eof instead of pending.
+    pub(super) async fn ready_or_eof<const READ_BUF_LIMIT: usize>(
+        &mut self,
+        read_buf: &mut ReadBuf<READ_BUF_LIMIT>,
+    ) -> bool {
         loop {
-            match self.decoder.decode(&mut *read_buf) {
+            match self.decode(read_buf) {
                 ChunkResult::Ok(bytes) => self.tx.feed_data(bytes),
                 ChunkResult::InsufficientData => match self.tx.ready().await {
-                    Ok(_) => return,
+                    Ok(_) => return true,
                     // service future drop RequestBody so marker decoder to corrupted.
                     Err(_) => self.decoder.set_corrupted(),
                 },
                 ChunkResult::OnEof => self.tx.feed_eof(),
-                ChunkResult::AlreadyEof | ChunkResult::Corrupted => pending().await,
+                ChunkResult::AlreadyEof => return false,
+                ChunkResult::Corrupted => pending().await,
                 ChunkResult::Err(e) => self.feed_error(e),
             }
         }
     }
 
+    fn decode<const READ_BUF_LIMIT: usize>(&mut self, read_buf: &mut ReadBuf<READ_BUF_LIMIT>) -> ChunkResult {
+        match self.trailers {
+            Some(ref trailers) => {
+                let mut map = HeaderMap::new();
+                let res = self.decoder.decode_with_trailers(read_buf, Some(&mut map));
+                if !map.is_empty() {
+                    trailers.set(map);
+                }
+                res
+            }
+            None => self.decoder.decode(read_buf),
+        }
+    }
+
     // feed error to body sender and prepare for close connection.
     #[cold]
     #[inline(never)]
@@ -386,3 +548,171 @@ impl BodyReader {
 pub(super) fn status_only(status: StatusCode) -> Response<NoneBody<Bytes>> {
     Response::builder().status(status).body(NoneBody::default()).unwrap()
 }
+
+#[cfg(test)]
+mod test {
+    use core::{
+        net::Ipv4Addr,
+        task::{Context, Poll},
+    };
+
+    use std::io::{Read, Write};
+
+    use xitca_service::fn_service;
+
+    use crate::{
+        body::{BoxBody, ResponseBody},
+        date::SystemTimeDateTimeHandler,
+        h1::RequestTrailers,
+        http::{Request, RequestExt},
+    };
+
+    use super::*;
+
+    // in memory io yielding given bytes and reporting eof afterwards.
+    struct TestIo {
+        read: io::Cursor<&'static [u8]>,
+        write: Vec<u8>,
+    }
+
+    impl Read for TestIo {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.read.read(buf)
+        }
+    }
+
+    impl Write for TestIo {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.write.write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl AsyncIo for TestIo {
+        async fn ready(&mut self, _: Interest) -> io::Result<Ready> {
+            Ok(Ready::READABLE | Ready::WRITABLE)
+        }
+
+        fn poll_ready(&mut self, _: Interest, _: &mut Context<'_>) -> Poll<io::Result<Ready>> {
+            Poll::Ready(Ok(Ready::READABLE | Ready::WRITABLE))
+        }
+
+        fn is_vectored_write(&self) -> bool {
+            false
+        }
+
+        fn poll_shutdown(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    #[test]
+    fn request_trailers() {
+        let service = fn_service(|req: Request<RequestExt<RequestBody>>| async move {
+            let trailers = req.extensions().get::<RequestTrailers>().cloned().unwrap();
+            // trailers are not available before body is fully consumed.
+            assert!(trailers.get().is_none());
+
+            let (_, body) = req.into_body().replace_body(());
+            let mut body = pin!(body);
+            let mut len = 0;
+            while let Some(chunk) = poll_fn(|cx| body.as_mut().poll_next(cx)).await {
+                len += chunk.unwrap().len();
+            }
+            assert_eq!(len, 11);
+
+            let trailers = trailers.get().unwrap();
+            let value = trailers.get("x-checksum").unwrap().to_str().unwrap().to_owned();
+            Ok::<_, Infallible>(Response::new(ResponseBody::<BoxBody>::from(value)))
+        });
+
+        let mut io = TestIo {
+            read: io::Cursor::new(
+                b"POST / HTTP/1.1\r\ntransfer-encoding: chunked\r\n\r\n\
+                5\r\nhello\r\n6\r\n,world\r\n0\r\nx-checksum: 996\r\n\r\n",
+            ),
+            write: Vec::new(),
+        };
+
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap();
+
+        rt.block_on(async {
+            let service = service.call(()).await.unwrap();
+            let timer = pin!(KeepAlive::new(tokio::time::Instant::now() + Duration::from_secs(5)));
+            let res = run(
+                &mut io,
+                SocketAddr::from((Ipv4Addr::LOCALHOST, 8080)),
+                timer,
+                HttpServiceConfig::new(),
+                &service,
+                &SystemTimeDateTimeHandler,
+                None,
+            )
+            .await;
+            assert!(res.is_ok());
+        });
+
+        let res = String::from_utf8(io.write).unwrap();
+        assert!(res.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(res.ends_with("\r\n\r\n996"));
+    }
+
+    #[test]
+    fn body_forbidden_status() {
+        // endless body stream. dispatcher must not poll it for 204 and 304 response.
+        struct Endless;
+
+        impl Stream for Endless {
+            type Item = Result<Bytes, Infallible>;
+
+            fn poll_next(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+                panic!("body of 204 and 304 response must not be polled")
+            }
+        }
+
+        for status in [StatusCode::NO_CONTENT, StatusCode::NOT_MODIFIED] {
+            let service = fn_service(move |_: Request<RequestExt<RequestBody>>| async move {
+                let mut res = Response::new(Endless);
+                *res.status_mut() = status;
+                Ok::<_, Infallible>(res)
+            });
+
+            let mut io = TestIo {
+                read: io::Cursor::new(b"GET / HTTP/1.1\r\nconnection: close\r\n\r\n"),
+                write: Vec::new(),
+            };
+
+            let rt = tokio::runtime::Builder::new_current_thread()
+                .enable_time()
+                .build()
+                .unwrap();
+
+            rt.block_on(async {
+                let service = service.call(()).await.unwrap();
+                let timer = pin!(KeepAlive::new(tokio::time::Instant::now() + Duration::from_secs(5)));
+                let res = run(
+                    &mut io,
+                    SocketAddr::from((Ipv4Addr::LOCALHOST, 8080)),
+                    timer,
+                    HttpServiceConfig::new(),
+                    &service,
+                    &SystemTimeDateTimeHandler,
+                    None,
+                )
+                .await;
+                assert!(res.is_ok());
+            });
+
+            let res = String::from_utf8(io.write).unwrap();
+            assert!(res.starts_with(&format!("HTTP/1.1 {}", status.as_str())));
+            assert!(res.ends_with("\r\n\r\n"));
+            assert!(!res.contains("transfer-encoding"));
+        }
+    }
+}
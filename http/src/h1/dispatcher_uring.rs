@@ -23,7 +23,7 @@ use xitca_service::Service;
 use xitca_unsafe_collection::futures::SelectOutput;
 
 use crate::{
-    body::NoneBody,
+    body::{Flush, NoneBody},
     bytes::Bytes,
     config::HttpServiceConfig,
     date::DateTime,
@@ -122,7 +122,8 @@ where
         Self {
             io: Rc::new(io),
             timer: Timer::new(timer, config.keep_alive_timeout, config.request_head_timeout),
-            ctx: Context::<_, H_LIMIT>::with_addr(addr, date),
+            // load shedding is not supported by io-uring dispatcher.
+            ctx: Context::<_, H_LIMIT>::with_config(addr, date, None, &config),
             service,
             read_buf: BufOwned::new(),
             write_buf: BufOwned::new(),
@@ -143,6 +144,9 @@ where
                 Err(Error::Proto(ProtoError::HeaderTooLarge)) => {
                     self.request_error(|| status_only(StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE))
                 }
+                Err(Error::Proto(ProtoError::UriTooLong)) => {
+                    self.request_error(|| status_only(StatusCode::URI_TOO_LONG))
+                }
                 Err(Error::Proto(_)) => self.request_error(|| status_only(StatusCode::BAD_REQUEST)),
                 Err(e) => return Err(e),
             }
@@ -188,22 +192,33 @@ where
                 (Some(&mut self.notify), RequestBody::io_uring(body))
             };
 
+            // connection take over of CONNECT request is not supported. io-uring io can not be handed over as
+            // AsyncIo type and h1::OnUpgrade is not inserted into request extensions. CONNECT request is served
+            // with it's request and response body.
             let req = req.map(|ext| ext.map_body(|_| ReqB::from(body)));
 
             let (parts, body) = self.service.call(req).await.map_err(Error::Service)?.into_parts();
 
+            let status = parts.status;
+            let flush = parts.extensions.get::<Flush>().copied().unwrap_or_default();
             let mut encoder = self.ctx.encode_head(parts, &body, &mut *self.write_buf)?;
 
             // this block is necessary. ResB has to be dropped asap as it may hold ownership of
             // Body type which if not dropped before Notifier::notify is called would prevent
             // Notifier from waking up Notify.
-            {
+            //
+            // 204 and 304 response can not bear body and encoder is in eof state. body is dropped without being
+            // polled.
+            if !matches!(status, StatusCode::NO_CONTENT | StatusCode::NOT_MODIFIED) {
                 let mut body = pin!(body);
 
+                // bytes of body buffered since write buffer is last written to io.
+                let mut buffered = 0;
+
                 loop {
                     let buf = &mut *self.write_buf;
 
-                    if buf.len() < W_LIMIT {
+                    if buf.len() < W_LIMIT && !flush.is_due(buffered) {
                         let res = poll_fn(|cx| match body.as_mut().poll_next(cx) {
                             Poll::Ready(res) => Poll::Ready(SelectOutput::A(res)),
                             Poll::Pending if buf.is_empty() => Poll::Pending,
@@ -213,17 +228,30 @@ where
 
                         match res {
                             SelectOutput::A(Some(Ok(bytes))) => {
+                                buffered += bytes.len();
                                 encoder.encode(bytes, buf);
                                 continue;
                             }
                             SelectOutput::A(Some(Err(e))) => return self.on_body_error(e).await,
-                            SelectOutput::A(None) => break encoder.encode_eof(buf),
+                            SelectOutput::A(None) => {
+                                // body stream ended before writing the length declared by it's size hint. client
+                                // is waiting for bytes that would never come and connection must be terminated.
+                                if let TransferCoding::Length(remaining @ 1..) = encoder {
+                                    let msg =
+                                        format!("response body ended with {remaining} bytes of content-length left");
+                                    return Err(Error::Io(io::Error::new(io::ErrorKind::UnexpectedEof, msg)));
+                                }
+                                break encoder.encode_eof(buf);
+                            }
                             SelectOutput::B(_) => {}
                         }
                     }
 
                     self.write_buf.write_io(&*self.io).await?;
+                    buffered = 0;
                 }
+            } else {
+                drop(body);
             }
 
             if let Some(waiter) = waiter {
@@ -3,15 +3,17 @@
 pub mod proto;
 
 pub(crate) mod dispatcher;
+pub(crate) mod upgrade;
 
 mod body;
 mod builder;
 mod error;
 mod service;
 
-pub use self::body::RequestBody;
+pub use self::body::{RequestBody, RequestTrailers};
 pub use self::error::Error;
 pub use self::service::H1Service;
+pub use self::upgrade::{OnUpgrade, Upgraded};
 
 #[cfg(feature = "io-uring")]
 mod dispatcher_uring;
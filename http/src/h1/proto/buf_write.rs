@@ -19,6 +19,12 @@ pub trait H1BufWrite: BufWrite {
         self.write_buf(func)
     }
 
+    /// count of bytes buffered and not written to io yet.
+    #[inline]
+    fn buffered_len(&self) -> usize {
+        0
+    }
+
     /// write `&'static [u8]` to buffer.
     fn write_buf_static(&mut self, bytes: &'static [u8]) {
         let _ = self.write_buf(|buf| {
@@ -47,9 +53,19 @@ pub trait H1BufWrite: BufWrite {
     }
 }
 
-impl H1BufWrite for BytesMut {}
+impl H1BufWrite for BytesMut {
+    #[inline]
+    fn buffered_len(&self) -> usize {
+        self.len()
+    }
+}
 
-impl<const BUF_LIMIT: usize> H1BufWrite for WriteBuf<BUF_LIMIT> {}
+impl<const BUF_LIMIT: usize> H1BufWrite for WriteBuf<BUF_LIMIT> {
+    #[inline]
+    fn buffered_len(&self) -> usize {
+        WriteBuf::buffered_len(self)
+    }
+}
 
 // as special type for eof chunk when using transfer-encoding: chunked
 type Eof = Chain<Chain<Bytes, Bytes>, &'static [u8]>;
@@ -69,6 +85,11 @@ impl<const BUF_LIMIT: usize> H1BufWrite for ListWriteBuf<EncodedBuf<Bytes, Eof>,
         })
     }
 
+    #[inline]
+    fn buffered_len(&self) -> usize {
+        ListWriteBuf::buffered_len(self)
+    }
+
     #[inline]
     fn write_buf_static(&mut self, bytes: &'static [u8]) {
         self.buffer(EitherBuf::Right(EitherBuf::Right(bytes)));
@@ -104,6 +125,14 @@ where
         }
     }
 
+    #[inline]
+    fn buffered_len(&self) -> usize {
+        match *self {
+            Self::Left(ref l) => l.buffered_len(),
+            Self::Right(ref r) => r.buffered_len(),
+        }
+    }
+
     #[inline]
     fn write_buf_static(&mut self, bytes: &'static [u8]) {
         match *self {
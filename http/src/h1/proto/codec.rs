@@ -4,7 +4,10 @@ use std::io;
 
 use tracing::{trace, warn};
 
-use crate::bytes::{Buf, Bytes, BytesMut};
+use crate::{
+    bytes::{Buf, Bytes, BytesMut},
+    http::header::{HeaderMap, HeaderName, HeaderValue},
+};
 
 use super::{buf_write::H1BufWrite, error::ProtoError};
 
@@ -317,7 +320,17 @@ impl TransferCoding {
     }
 
     /// decode body. See [ChunkResult] for detailed outcome.
+    ///
+    /// trailer section of chunked body is validated and discarded. use [TransferCoding::decode_with_trailers]
+    /// for collecting it.
+    #[inline]
     pub fn decode(&mut self, src: &mut BytesMut) -> ChunkResult {
+        self.decode_with_trailers(src, None)
+    }
+
+    /// decode body and collect trailer headers of chunked body into given [HeaderMap].
+    /// See [ChunkResult] for detailed outcome.
+    pub fn decode_with_trailers(&mut self, src: &mut BytesMut, mut trailers: Option<&mut HeaderMap>) -> ChunkResult {
         match *self {
             // when decoder reaching eof state it would return ChunkResult::Eof and followed by
             // ChunkResult::AlreadyEof if decode is called again.
@@ -334,6 +347,16 @@ impl TransferCoding {
             Self::Upgrade => ChunkResult::Ok(src.split().freeze()),
             Self::DecodeChunked(ref mut state, ref mut size) => {
                 loop {
+                    // trailer section is parsed as a whole instead of going through byte by byte state stepping.
+                    if matches!(state, ChunkedState::EndCr) {
+                        match parse_trailers(src, trailers.as_deref_mut()) {
+                            Ok(Some(_)) => *state = ChunkedState::End,
+                            Ok(None) => return ChunkResult::InsufficientData,
+                            Err(e) => return ChunkResult::Err(e),
+                        }
+                        return self.decode_with_trailers(src, trailers);
+                    }
+
                     let mut buf = None;
                     // advances the chunked state
                     *state = match state.step(src, size, &mut buf) {
@@ -343,7 +366,7 @@ impl TransferCoding {
                     };
 
                     if matches!(state, ChunkedState::End) {
-                        return self.decode(src);
+                        return self.decode_with_trailers(src, trailers);
                     }
 
                     if let Some(buf) = buf {
@@ -392,6 +415,41 @@ impl From<io::Error> for ChunkResult {
     }
 }
 
+/// max count of trailer headers of a chunked body.
+const MAX_TRAILERS: usize = 32;
+/// max size of trailer section of a chunked body in bytes.
+const MAX_TRAILERS_SIZE: usize = 8 * 1024;
+
+// parse trailer section and the following empty line of chunked body. return Ok(None) when more bytes are needed.
+fn parse_trailers(src: &mut BytesMut, trailers: Option<&mut HeaderMap>) -> io::Result<Option<()>> {
+    let mut headers = [httparse::EMPTY_HEADER; MAX_TRAILERS];
+    match httparse::parse_headers(src, &mut headers) {
+        Ok(httparse::Status::Complete((len, headers))) => {
+            if let Some(trailers) = trailers {
+                for header in headers {
+                    let name = HeaderName::from_bytes(header.name.as_bytes()).map_err(invalid_trailer)?;
+                    let value = HeaderValue::from_bytes(header.value).map_err(invalid_trailer)?;
+                    trailers.append(name, value);
+                }
+            }
+            src.advance(len);
+            Ok(Some(()))
+        }
+        Ok(httparse::Status::Partial) if src.len() < MAX_TRAILERS_SIZE => Ok(None),
+        Ok(httparse::Status::Partial) => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "chunked trailer section too large",
+        )),
+        Err(e) => Err(invalid_trailer(e)),
+    }
+}
+
+#[cold]
+#[inline(never)]
+fn invalid_trailer(e: impl fmt::Display) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, format!("Invalid chunked trailer: {e}"))
+}
+
 fn bounded_split(rem: &mut u64, buf: &mut BytesMut) -> Bytes {
     let len = buf.len() as u64;
     if *rem >= len {
@@ -502,6 +560,61 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_read_chunked_trailers() {
+        let mock_buf = &mut BytesMut::from("3\r\nfoo\r\n0;ext\r\ndigest: sha-256=abc\r\nx-foo: bar\r\n\r\n");
+
+        let mut decoder = TransferCoding::decode_chunked();
+        let mut trailers = HeaderMap::new();
+
+        match decoder.decode_with_trailers(mock_buf, Some(&mut trailers)) {
+            ChunkResult::Ok(buf) => assert_eq!(buf.as_ref(), b"foo"),
+            state => panic!("{}", state),
+        }
+
+        match decoder.decode_with_trailers(mock_buf, Some(&mut trailers)) {
+            ChunkResult::OnEof => {}
+            state => panic!("{}", state),
+        }
+
+        assert!(mock_buf.is_empty());
+        assert_eq!(trailers.get("digest").unwrap(), "sha-256=abc");
+        assert_eq!(trailers.get("x-foo").unwrap(), "bar");
+    }
+
+    #[test]
+    fn test_read_chunked_trailers_partial() {
+        let mut decoder = TransferCoding::decode_chunked();
+        let mut trailers = HeaderMap::new();
+
+        let mock_buf = &mut BytesMut::from("0\r\nx-foo: b");
+        match decoder.decode_with_trailers(mock_buf, Some(&mut trailers)) {
+            ChunkResult::InsufficientData => {}
+            state => panic!("{}", state),
+        }
+
+        mock_buf.extend_from_slice(b"ar\r\n\r\n");
+        match decoder.decode_with_trailers(mock_buf, Some(&mut trailers)) {
+            ChunkResult::OnEof => {}
+            state => panic!("{}", state),
+        }
+
+        assert_eq!(trailers.get("x-foo").unwrap(), "bar");
+    }
+
+    #[test]
+    fn test_read_chunked_trailers_too_large() {
+        let mut decoder = TransferCoding::decode_chunked();
+
+        let mut mock_buf = BytesMut::from("0\r\nx-foo: ");
+        mock_buf.extend_from_slice(&[b'a'; MAX_TRAILERS_SIZE]);
+
+        match decoder.decode(&mut mock_buf) {
+            ChunkResult::Err(e) => assert_eq!(e.kind(), io::ErrorKind::InvalidData),
+            state => panic!("{}", state),
+        }
+    }
+
     #[test]
     fn test_read_chunked_trailer_with_missing_lf() {
         let mock_buf = &mut BytesMut::from("10\r\n1234567890abcdef\r\n0\r\nbad\r\r\n");
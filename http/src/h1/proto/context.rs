@@ -1,6 +1,10 @@
-use core::{mem, net::SocketAddr};
+use core::{mem, net::SocketAddr, time::Duration};
 
-use crate::http::{header::HeaderMap, Extensions};
+use crate::{
+    config::{HttpServiceConfig, DEFAULT_MAX_URI_LEN},
+    http::{header::HeaderMap, Extensions},
+    util::shed::{LoadShed, Tracker},
+};
 
 /// Context is connection specific struct contain states for processing.
 pub struct Context<'a, D, const HEADER_LIMIT: usize> {
@@ -11,6 +15,18 @@ pub struct Context<'a, D, const HEADER_LIMIT: usize> {
     // http extensions reused by next request.
     exts: Extensions,
     date: &'a D,
+    // write date header to response when it's not present.
+    date_header: bool,
+    // server header value written to response when it's not present.
+    server_header: Option<&'static str>,
+    // max length of request uri.
+    max_uri_len: usize,
+    // timeout in seconds advertised with keep-alive header to http/1.0 client.
+    keep_alive_header: Option<u64>,
+    // tolerate ambiguous request framing and obsolete line folding from legacy client.
+    lenient_parsing: bool,
+    // report buffered bytes of connection for per worker load shedding.
+    tracker: Option<Tracker<'a>>,
 }
 
 // A set of state for current request that are used after request's ownership is passed
@@ -26,6 +42,8 @@ impl ContextState {
     const HEAD: u8 = 0b_0100;
     // Enable when current connection is supposed to be closed after current response is sent.
     const CLOSE: u8 = 0b_1000;
+    // Enable when current request is HTTP/1.0.
+    const HTTP_10: u8 = 0b1_0000;
 
     const fn new() -> Self {
         Self(0)
@@ -64,15 +82,117 @@ impl<'a, D, const HEADER_LIMIT: usize> Context<'a, D, HEADER_LIMIT> {
             header: None,
             exts: Extensions::new(),
             date,
+            date_header: true,
+            server_header: None,
+            max_uri_len: DEFAULT_MAX_URI_LEN,
+            keep_alive_header: None,
+            lenient_parsing: false,
+            tracker: None,
         }
     }
 
+    // construct Context with settings from service config and load shedding state of worker.
+    pub(crate) fn with_config<const READ_BUF_LIMIT: usize, const WRITE_BUF_LIMIT: usize>(
+        addr: SocketAddr,
+        date: &'a D,
+        shed: Option<&'a LoadShed>,
+        config: &HttpServiceConfig<HEADER_LIMIT, READ_BUF_LIMIT, WRITE_BUF_LIMIT>,
+    ) -> Self {
+        let mut ctx = Self::with_addr(addr, date);
+        ctx.tracker = shed.map(LoadShed::tracker);
+        ctx.set_date_header(config.date_header);
+        ctx.set_server_header(config.server_header);
+        ctx.set_max_uri_len(config.max_uri_len);
+        if config.keep_alive_header {
+            ctx.set_keep_alive_header(Some(config.keep_alive_timeout));
+        }
+        ctx.set_lenient_parsing(config.lenient_request_parsing);
+        ctx
+    }
+
     /// Get Date type from Context.
     #[inline]
     pub fn date(&self) -> &D {
         self.date
     }
 
+    /// Set if `date` header is written to response when it's not present. Default to true.
+    #[inline]
+    pub fn set_date_header(&mut self, date_header: bool) {
+        self.date_header = date_header;
+    }
+
+    /// Set value of `server` header written to response when it's not present. Default to None.
+    #[inline]
+    pub fn set_server_header(&mut self, server_header: Option<&'static str>) {
+        self.server_header = server_header;
+    }
+
+    /// Set max length of request uri in bytes. Default to [DEFAULT_MAX_URI_LEN].
+    #[inline]
+    pub fn set_max_uri_len(&mut self, max_uri_len: usize) {
+        self.max_uri_len = max_uri_len;
+    }
+
+    /// Set timeout advertised to HTTP/1.0 client with `connection: keep-alive` and `keep-alive: timeout=<secs>`
+    /// headers when it's connection is kept alive. HTTP/1.1 response is not affected. Default to None where no
+    /// header is written.
+    #[inline]
+    pub fn set_keep_alive_header(&mut self, timeout: Option<Duration>) {
+        self.keep_alive_header = timeout.map(|dur| dur.as_secs());
+    }
+
+    /// Set if request head is parsed in lenient mode. Default to false.
+    ///
+    /// In strict mode request carrying both `content-length` and `transfer-encoding` headers, multiple
+    /// `content-length` headers or obsolete line folding in header is rejected. In lenient mode duplicate
+    /// `content-length` headers with identical value and obsolete line folding are accepted. Request carrying both
+    /// `content-length` and `transfer-encoding` headers is accepted when it can be decoded and it's connection is
+    /// closed after response is sent.
+    #[inline]
+    pub fn set_lenient_parsing(&mut self, lenient: bool) {
+        self.lenient_parsing = lenient;
+    }
+
+    // update buffered bytes of connection when load shedding is enabled.
+    #[inline]
+    pub(crate) fn track_buffered(&mut self, len: usize) {
+        if let Some(ref mut tracker) = self.tracker {
+            tracker.track(len);
+        }
+    }
+
+    // check if a new request should be shed.
+    #[inline]
+    pub(crate) fn try_shed(&self) -> bool {
+        self.tracker.as_ref().is_some_and(Tracker::try_shed)
+    }
+
+    #[inline]
+    pub(super) fn max_uri_len(&self) -> usize {
+        self.max_uri_len
+    }
+
+    #[inline]
+    pub(super) fn lenient_parsing(&self) -> bool {
+        self.lenient_parsing
+    }
+
+    #[inline]
+    pub(super) fn date_header(&self) -> bool {
+        self.date_header
+    }
+
+    #[inline]
+    pub(super) fn server_header(&self) -> Option<&'static str> {
+        self.server_header
+    }
+
+    #[inline]
+    pub(super) fn keep_alive_header(&self) -> Option<u64> {
+        self.keep_alive_header
+    }
+
     /// Take ownership of HeaderMap stored in Context.
     ///
     /// When Context does not have one a new HeaderMap is constructed.
@@ -125,6 +245,12 @@ impl<'a, D, const HEADER_LIMIT: usize> Context<'a, D, HEADER_LIMIT> {
         self.state.insert(ContextState::HEAD)
     }
 
+    /// Set Context's state to HTTP/1.0 request received.
+    #[inline]
+    pub fn set_http_10(&mut self) {
+        self.state.insert(ContextState::HTTP_10)
+    }
+
     /// Set Context's state to Close.
     #[inline]
     pub fn set_close(&mut self) {
@@ -155,6 +281,12 @@ impl<'a, D, const HEADER_LIMIT: usize> Context<'a, D, HEADER_LIMIT> {
         self.state.contains(ContextState::HEAD)
     }
 
+    /// Get HTTP/1.0 request state.
+    #[inline]
+    pub const fn is_http_10(&self) -> bool {
+        self.state.contains(ContextState::HTTP_10)
+    }
+
     /// Return true if connection type is `Connection: Close`.
     #[inline]
     pub const fn is_connection_closed(&self) -> bool {
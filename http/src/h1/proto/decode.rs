@@ -1,4 +1,4 @@
-use core::mem::MaybeUninit;
+use core::{cmp, mem::MaybeUninit};
 
 use httparse::Status;
 
@@ -6,7 +6,7 @@ use crate::{
     bytes::{Buf, Bytes, BytesMut},
     http::{
         header::{HeaderMap, HeaderName, HeaderValue, CONNECTION, CONTENT_LENGTH, EXPECT, TRANSFER_ENCODING, UPGRADE},
-        Extension, Method, Request, RequestExt, Uri, Version,
+        Extension, Method, Protocol, Request, RequestExt, Uri, Version,
     },
 };
 
@@ -19,12 +19,20 @@ use super::{
 
 type Decoded = (Request<RequestExt<()>>, TransferCoding);
 
+// max length of request line excluding uri. (method, spaces, version and line break)
+// method is a token without length limit and this value covers all registered methods.
+const REQUEST_LINE_OVERHEAD: usize = 64;
+
 impl<D, const MAX_HEADERS: usize> Context<'_, D, MAX_HEADERS> {
     // decode head and generate request and body decoder.
     pub fn decode_head<const READ_BUF_LIMIT: usize>(
         &mut self,
         buf: &mut BytesMut,
     ) -> Result<Option<Decoded>, ProtoError> {
+        if self.lenient_parsing() {
+            header::unfold_obsolete_line(buf);
+        }
+
         let mut req = httparse::Request::new(&mut []);
         let mut headers = [const { MaybeUninit::uninit() }; MAX_HEADERS];
 
@@ -33,6 +41,10 @@ impl<D, const MAX_HEADERS: usize> Context<'_, D, MAX_HEADERS> {
                 // Important: reset context state for new request.
                 self.reset();
 
+                if req.path.unwrap().len() > self.max_uri_len() {
+                    return Err(ProtoError::UriTooLong);
+                }
+
                 let method = Method::from_bytes(req.method.unwrap().as_bytes())?;
 
                 // default body decoder from method.
@@ -54,6 +66,7 @@ impl<D, const MAX_HEADERS: usize> Context<'_, D, MAX_HEADERS> {
                     // Default ctype is KeepAlive so set_ctype is skipped here.
                     Version::HTTP_11
                 } else {
+                    self.set_http_10();
                     self.set_close();
                     Version::HTTP_10
                 };
@@ -82,7 +95,9 @@ impl<D, const MAX_HEADERS: usize> Context<'_, D, MAX_HEADERS> {
                     self.try_write_header(&mut headers, &mut decoder, idx, &slice, version)?;
                 }
 
-                let ext = Extension::new(*self.socket_addr());
+                self.check_framing(&headers)?;
+
+                let ext = Extension::new(*self.socket_addr(), Protocol::Http1);
                 let mut req = Request::new(RequestExt::from_parts((), ext));
 
                 let extensions = self.take_extensions();
@@ -97,7 +112,12 @@ impl<D, const MAX_HEADERS: usize> Context<'_, D, MAX_HEADERS> {
             }
 
             Status::Partial => {
-                if buf.remaining() >= READ_BUF_LIMIT {
+                // reject over long uri before the rest of request head is received.
+                let limit = self.max_uri_len().saturating_add(REQUEST_LINE_OVERHEAD);
+                let line = &buf[..cmp::min(buf.len(), limit.saturating_add(1))];
+                if line.len() > limit && !line.contains(&b'\n') {
+                    Err(ProtoError::UriTooLong)
+                } else if buf.remaining() >= READ_BUF_LIMIT {
                     Err(ProtoError::HeaderTooLarge)
                 } else {
                     Ok(None)
@@ -131,7 +151,10 @@ impl<D, const MAX_HEADERS: usize> Context<'_, D, MAX_HEADERS> {
             }
             CONTENT_LENGTH => {
                 let len = header::parse_content_length(&value)?;
-                decoder.try_set(TransferCoding::length(len))?;
+                // duplicate header with identical value is checked in Context::check_framing.
+                if !matches!(*decoder, TransferCoding::Length(l) if l == len) {
+                    decoder.try_set(TransferCoding::length(len))?;
+                }
             }
             CONNECTION => self.try_set_close_from_header(&value)?,
             EXPECT => {
@@ -154,6 +177,32 @@ impl<D, const MAX_HEADERS: usize> Context<'_, D, MAX_HEADERS> {
         Ok(())
     }
 
+    // reject ambiguous body framing where front proxy and server could disagree on boundary of requests and it can
+    // be exploited for request smuggling.
+    fn check_framing(&mut self, headers: &HeaderMap) -> Result<(), ProtoError> {
+        let mut lens = headers.get_all(CONTENT_LENGTH).into_iter();
+
+        let Some(len) = lens.next() else {
+            return Ok(());
+        };
+
+        let lenient = self.lenient_parsing();
+
+        if lens.any(|val| !lenient || val != len) {
+            return Err(ProtoError::HeaderValue);
+        }
+
+        if headers.contains_key(TRANSFER_ENCODING) {
+            if !lenient {
+                return Err(ProtoError::HeaderName);
+            }
+            // connection can't be reused safely after a request with conflicting framing headers.
+            self.set_close();
+        }
+
+        Ok(())
+    }
+
     pub(super) fn try_set_close_from_header(&mut self, val: &HeaderValue) -> Result<(), ProtoError> {
         for val in val.to_str().map_err(|_| ProtoError::HeaderValue)?.split(',') {
             let val = val.trim();
@@ -171,6 +220,28 @@ impl<D, const MAX_HEADERS: usize> Context<'_, D, MAX_HEADERS> {
 mod test {
     use super::*;
 
+    #[test]
+    fn uri_too_long() {
+        let mut ctx = Context::<_, 4>::new(&());
+        ctx.set_max_uri_len(16);
+
+        let mut buf = BytesMut::from(&b"GET /0123456789abcde HTTP/1.1\r\n\r\n"[..]);
+        assert!(ctx.decode_head::<1024>(&mut buf).unwrap().is_some());
+
+        let mut buf = BytesMut::from(&b"GET /0123456789abcdef HTTP/1.1\r\n\r\n"[..]);
+        assert!(matches!(ctx.decode_head::<1024>(&mut buf), Err(ProtoError::UriTooLong)));
+
+        // incomplete request line is rejected before the rest of request head is received.
+        let mut buf = BytesMut::from(&b"GET /"[..]);
+        buf.extend_from_slice(&[b'a'; 16 + REQUEST_LINE_OVERHEAD]);
+        assert!(matches!(ctx.decode_head::<1024>(&mut buf), Err(ProtoError::UriTooLong)));
+
+        // incomplete request head with complete request line is waiting for more bytes.
+        let mut buf = BytesMut::from(&b"GET / HTTP/1.1\r\nHost: "[..]);
+        buf.extend_from_slice(&[b'a'; 16 + REQUEST_LINE_OVERHEAD]);
+        assert!(ctx.decode_head::<1024>(&mut buf).unwrap().is_none());
+    }
+
     #[test]
     fn connection_multiple_value() {
         let mut ctx = Context::<_, 4>::new(&());
@@ -311,4 +382,57 @@ mod test {
             "transfer coding is not decoded to chunked"
         );
     }
+
+    #[test]
+    fn framing_conflict() {
+        let mut ctx = Context::<_, 4>::new(&());
+
+        let decode = |ctx: &mut Context<'_, (), 4>, head: &[u8]| ctx.decode_head::<256>(&mut BytesMut::from(head));
+
+        let both = b"\
+                POST / HTTP/1.1\r\n\
+                Content-Length: 0\r\n\
+                Transfer-Encoding: chunked\r\n\
+                \r\n\
+                ";
+        let duplicate = b"\
+                POST / HTTP/1.1\r\n\
+                Content-Length: 3\r\n\
+                Content-Length: 3\r\n\
+                \r\n\
+                ";
+        let ambiguous = b"\
+                POST / HTTP/1.1\r\n\
+                Content-Length: 0\r\n\
+                Content-Length: 3\r\n\
+                \r\n\
+                ";
+        let folded = b"\
+                GET / HTTP/1.1\r\n\
+                X-Folded: foo\r\n\
+                \tbar\r\n\
+                \r\n\
+                ";
+
+        assert!(matches!(decode(&mut ctx, both), Err(ProtoError::HeaderName)));
+        assert!(matches!(decode(&mut ctx, duplicate), Err(ProtoError::HeaderValue)));
+        assert!(matches!(decode(&mut ctx, ambiguous), Err(ProtoError::HeaderValue)));
+        assert!(decode(&mut ctx, folded).is_err());
+
+        ctx.set_lenient_parsing(true);
+
+        let (_, decoder) = decode(&mut ctx, both).unwrap().unwrap();
+        assert!(matches!(decoder, TransferCoding::DecodeChunked(..)));
+        assert!(ctx.is_connection_closed());
+
+        let (req, decoder) = decode(&mut ctx, duplicate).unwrap().unwrap();
+        assert!(matches!(decoder, TransferCoding::Length(3)));
+        assert_eq!(req.headers().get_all(CONTENT_LENGTH).into_iter().count(), 2);
+        assert!(!ctx.is_connection_closed());
+
+        assert!(matches!(decode(&mut ctx, ambiguous), Err(ProtoError::HeaderValue)));
+
+        let (req, _) = decode(&mut ctx, folded).unwrap().unwrap();
+        assert_eq!(req.headers().get("x-folded").unwrap(), "foo  \tbar");
+    }
 }
@@ -6,7 +6,7 @@ use crate::{
     bytes::{Bytes, BytesMut},
     date::DateTime,
     http::{
-        header::{HeaderMap, CONNECTION, CONTENT_LENGTH, DATE, SET_COOKIE, TE, TRANSFER_ENCODING, UPGRADE},
+        header::{HeaderMap, CONNECTION, CONTENT_LENGTH, DATE, SERVER, SET_COOKIE, TE, TRANSFER_ENCODING, UPGRADE},
         response::Parts,
         StatusCode, Version,
     },
@@ -43,9 +43,17 @@ where
             ..
         } = parts;
 
+        // 204 and 304 response can not bear body. body and it's size headers are dropped.
+        let body_forbidden = matches!(status, StatusCode::NO_CONTENT | StatusCode::NOT_MODIFIED);
+        if body_forbidden {
+            headers.remove(CONTENT_LENGTH);
+            headers.remove(TRANSFER_ENCODING);
+        }
+
         // decide if content-length or transfer-encoding header would be skipped.
         let skip_ct_te = match status {
             StatusCode::SWITCHING_PROTOCOLS => true,
+            _ if body_forbidden => true,
             // Sending content-length or transfer-encoding header on 2xx response
             // to CONNECT is forbidden in RFC 7231.
             s if self.is_connect_method() && s.is_success() => true,
@@ -59,7 +67,11 @@ where
         // encode version, status code and reason
         encode_version_status_reason(buf, version, status);
 
-        let size = BodySize::from_stream(body);
+        let size = if body_forbidden {
+            BodySize::None
+        } else {
+            BodySize::from_stream(body)
+        };
 
         self.encode_headers(&mut headers, size, buf, skip_ct_te).inspect(|_| {
             // put header map back to cache.
@@ -113,7 +125,10 @@ where
         buf: &mut BytesMut,
         mut skip_ct_te: bool,
     ) -> Result<TransferCoding, ProtoError> {
-        let mut skip_date = false;
+        let mut skip_date = !self.date_header();
+        let mut skip_server = false;
+        let mut skip_connection = false;
+        let mut skip_keep_alive = false;
 
         // use the shortest header name as default
         let mut name = TE;
@@ -136,9 +151,15 @@ where
                         continue;
                     }
                     self.try_set_close_from_header(&value)?;
+                    if self.is_connection_closed() {
+                        // close header from response is written once afterwards.
+                        continue;
+                    }
+                    skip_connection = true;
                 }
                 UPGRADE => encoding = TransferCoding::upgrade(),
                 DATE => skip_date = true,
+                SERVER => skip_server = true,
                 CONTENT_LENGTH => {
                     debug_assert!(!skip_ct_te, "CONTENT_LENGTH header can not be set");
                     let value = header::parse_content_length(&value)?;
@@ -158,6 +179,7 @@ where
                 // multiple header lines for set-cookie header is allowed
                 // https://www.rfc-editor.org/rfc/rfc6265#section-3
                 SET_COOKIE => is_multi_value = false,
+                _ if name == "keep-alive" => skip_keep_alive = true,
                 _ => {}
             }
 
@@ -199,6 +221,16 @@ where
 
         if self.is_connection_closed() {
             buf.extend_from_slice(CLOSE_HEADER);
+        } else if self.is_http_10() {
+            // http/1.0 client does not keep connection alive implicitly. advertise it explicitly.
+            if let Some(timeout) = self.keep_alive_header() {
+                if !skip_connection {
+                    buf.extend_from_slice(KEEP_ALIVE_HEADER);
+                }
+                if !skip_keep_alive {
+                    write_keep_alive_timeout_header(buf, timeout);
+                }
+            }
         }
 
         // set date header if there is not any.
@@ -208,6 +240,12 @@ where
             self.date().with_date(|slice| buf.extend_from_slice(slice));
         }
 
+        if let (false, Some(server)) = (skip_server, self.server_header()) {
+            buf.reserve(server.len() + 10);
+            buf.extend_from_slice(b"\r\nserver: ");
+            buf.extend_from_slice(server.as_bytes());
+        }
+
         buf.extend_from_slice(b"\r\n\r\n");
 
         Ok(encoding)
@@ -216,6 +254,7 @@ where
 
 const CHUNKED_HEADER: &[u8; 28] = b"\r\ntransfer-encoding: chunked";
 const CLOSE_HEADER: &[u8; 19] = b"\r\nconnection: close";
+const KEEP_ALIVE_HEADER: &[u8; 24] = b"\r\nconnection: keep-alive";
 
 #[cold]
 #[inline(never)]
@@ -245,6 +284,15 @@ pub(crate) fn write_length_header(buf: &mut BytesMut, size: usize) {
     buf.extend_from_slice(buffer);
 }
 
+fn write_keep_alive_timeout_header(buf: &mut BytesMut, timeout: u64) {
+    let mut buffer = itoa::Buffer::new();
+    let buffer = buffer.format(timeout).as_bytes();
+
+    buf.reserve(buffer.len() + 22);
+    buf.extend_from_slice(b"\r\nkeep-alive: timeout=");
+    buf.extend_from_slice(buffer);
+}
+
 #[cfg(test)]
 mod test {
     use crate::{
@@ -285,6 +333,81 @@ mod test {
         }
     }
 
+    #[test]
+    fn close_header() {
+        let mut ctx = Context::<_, 64>::new(&SystemTimeDateTimeHandler);
+
+        let mut res = Response::new(BoxBody::new(Once::new(Bytes::new())));
+        res.headers_mut().insert(CONNECTION, HeaderValue::from_static("close"));
+
+        let (parts, body) = res.into_parts();
+
+        let mut buf = BytesMut::new();
+        ctx.encode_head(parts, &body, &mut buf).unwrap();
+
+        assert!(ctx.is_connection_closed());
+
+        let head = String::from_utf8(buf.to_vec()).unwrap();
+        assert_eq!(head.matches("connection: close").count(), 1);
+
+        // response can not revert close state requested by client.
+        let mut res = Response::new(BoxBody::new(Once::new(Bytes::new())));
+        res.headers_mut()
+            .insert(CONNECTION, HeaderValue::from_static("keep-alive"));
+
+        let (parts, body) = res.into_parts();
+
+        let mut buf = BytesMut::new();
+        ctx.encode_head(parts, &body, &mut buf).unwrap();
+
+        assert!(ctx.is_connection_closed());
+
+        let head = String::from_utf8(buf.to_vec()).unwrap();
+        assert!(head.contains("\r\nconnection: close\r\n"));
+        assert!(!head.contains("keep-alive"));
+    }
+
+    #[test]
+    fn keep_alive_header() {
+        let encode = |ctx: &mut Context<'_, SystemTimeDateTimeHandler, 64>, req: &[u8]| {
+            let mut buf = BytesMut::from(req);
+            ctx.decode_head::<1024>(&mut buf).unwrap().unwrap();
+
+            let res = Response::new(BoxBody::new(Once::new(Bytes::new())));
+            let (parts, body) = res.into_parts();
+            let mut buf = BytesMut::new();
+            ctx.encode_head(parts, &body, &mut buf).unwrap();
+            String::from_utf8(buf.to_vec()).unwrap()
+        };
+
+        let mut ctx = Context::<_, 64>::new(&SystemTimeDateTimeHandler);
+        ctx.set_keep_alive_header(Some(core::time::Duration::from_secs(5)));
+
+        // http/1.0 client asking for keep-alive.
+        let head = encode(&mut ctx, b"GET / HTTP/1.0\r\nConnection: keep-alive\r\n\r\n");
+        assert!(!ctx.is_connection_closed());
+        assert!(head.contains("\r\nconnection: keep-alive\r\n"));
+        assert!(head.contains("\r\nkeep-alive: timeout=5\r\n"));
+
+        // http/1.0 client without keep-alive.
+        let head = encode(&mut ctx, b"GET / HTTP/1.0\r\n\r\n");
+        assert!(ctx.is_connection_closed());
+        assert!(head.contains("\r\nconnection: close\r\n"));
+        assert!(!head.contains("keep-alive"));
+
+        // http/1.1 client keeps connection alive implicitly.
+        let head = encode(&mut ctx, b"GET / HTTP/1.1\r\n\r\n");
+        assert!(!ctx.is_connection_closed());
+        assert!(!head.contains("connection: "));
+        assert!(!head.contains("keep-alive"));
+
+        // header is not written when disabled.
+        ctx.set_keep_alive_header(None);
+        let head = encode(&mut ctx, b"GET / HTTP/1.0\r\nConnection: keep-alive\r\n\r\n");
+        assert!(!ctx.is_connection_closed());
+        assert!(!head.contains("keep-alive"));
+    }
+
     #[test]
     fn multi_set_cookie() {
         let mut ctx = Context::<_, 64>::new(&SystemTimeDateTimeHandler);
@@ -313,4 +436,55 @@ mod test {
         assert_eq!(header[1].name, "set-cookie");
         assert_eq!(header[1].value, b"bar=bar");
     }
+
+    #[test]
+    fn date_and_server_header() {
+        let encode = |ctx: &mut Context<'_, SystemTimeDateTimeHandler, 64>, server: Option<&'static str>| {
+            let mut res = Response::new(BoxBody::new(Once::new(Bytes::new())));
+            if let Some(server) = server {
+                res.headers_mut().insert(SERVER, HeaderValue::from_static(server));
+            }
+            let (parts, body) = res.into_parts();
+            let mut buf = BytesMut::new();
+            ctx.encode_head(parts, &body, &mut buf).unwrap();
+            String::from_utf8(buf.to_vec()).unwrap()
+        };
+
+        let mut ctx = Context::<_, 64>::new(&SystemTimeDateTimeHandler);
+        let head = encode(&mut ctx, None);
+        assert!(head.contains("\r\ndate: "));
+        assert!(!head.contains("\r\nserver: "));
+
+        ctx.set_date_header(false);
+        ctx.set_server_header(Some("xitca"));
+        let head = encode(&mut ctx, None);
+        assert!(!head.contains("\r\ndate: "));
+        assert!(head.contains("\r\nserver: xitca\r\n"));
+
+        // server header from response takes precedence.
+        let head = encode(&mut ctx, Some("custom"));
+        assert!(head.contains("\r\nserver: custom\r\n"));
+        assert!(!head.contains("xitca"));
+    }
+
+    #[test]
+    fn body_forbidden_status() {
+        let mut ctx = Context::<_, 64>::new(&SystemTimeDateTimeHandler);
+
+        for status in [StatusCode::NO_CONTENT, StatusCode::NOT_MODIFIED] {
+            let mut res = Response::new(Once::new(Bytes::from_static(b"hello")));
+            *res.status_mut() = status;
+            res.headers_mut().insert(CONTENT_LENGTH, HeaderValue::from_static("5"));
+
+            let (parts, body) = res.into_parts();
+            let mut buf = BytesMut::new();
+            let encoding = ctx.encode_head(parts, &body, &mut buf).unwrap();
+            assert!(matches!(encoding, TransferCoding::Eof));
+
+            let head = String::from_utf8(buf.to_vec()).unwrap();
+            assert!(head.starts_with(&format!("HTTP/1.1 {}", status.as_str())));
+            assert!(!head.contains("content-length"));
+            assert!(!head.contains("transfer-encoding"));
+        }
+    }
 }
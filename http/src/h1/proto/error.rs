@@ -5,6 +5,7 @@ pub enum ProtoError {
     HeaderName,
     HeaderValue,
     HeaderTooLarge,
+    UriTooLong,
     Method,
     Uri,
     NewLine,
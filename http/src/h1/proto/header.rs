@@ -37,3 +37,24 @@ pub(super) fn parse_content_length(val: &HeaderValue) -> Result<u64, ProtoError>
         .and_then(|v| v.parse().ok())
         .ok_or(ProtoError::HeaderValue)
 }
+
+/// Replace obsolete line folding(line break followed by space or horizontal tab) inside request head with spaces so
+/// folded header value can be parsed as one line. Request line and body are left untouched.
+pub(super) fn unfold_obsolete_line(buf: &mut [u8]) {
+    let end = buf
+        .windows(4)
+        .position(|w| w == b"\r\n\r\n")
+        .map(|pos| pos + 2)
+        .unwrap_or(buf.len());
+
+    let Some(start) = buf[..end].windows(2).position(|w| w == b"\r\n") else {
+        return;
+    };
+
+    for i in start + 2..end {
+        if buf[i] == b'\r' && buf.get(i + 1) == Some(&b'\n') && matches!(buf.get(i + 2), Some(b' ' | b'\t')) {
+            buf[i] = b' ';
+            buf[i + 1] = b' ';
+        }
+    }
+}
@@ -23,7 +23,7 @@ where
     S: Service<Request<RequestExt<RequestBody>>, Response = Response<B>>,
     A: Service<St>,
     St: AsyncIo,
-    A::Response: AsyncIo,
+    A::Response: AsyncIo + Send + 'static,
     B: Stream<Item = Result<Bytes, BE>>,
     HttpServiceError<S::Error, BE>: From<A::Error>,
 {
@@ -41,9 +41,22 @@ where
             .await
             .map_err(|_| HttpServiceError::Timeout(TimeoutError::TlsAccept))??;
 
-        super::dispatcher::run(&mut io, addr, timer, self.config, &self.service, self.date.get())
-            .await
-            .map_err(Into::into)
+        let upgrade = super::dispatcher::run(
+            &mut io,
+            addr,
+            timer,
+            self.config,
+            &self.service,
+            self.date.get(),
+            self.shed.as_ref(),
+        )
+        .await?;
+
+        if let Some(upgrade) = upgrade {
+            upgrade.send(io);
+        }
+
+        Ok(())
     }
 }
 
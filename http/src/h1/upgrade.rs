@@ -0,0 +1,213 @@
+//! connection take over for http/1 CONNECT request.
+
+use core::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll, Waker},
+};
+
+use std::{
+    io,
+    sync::{Arc, Mutex},
+};
+
+use xitca_io::io::{AsyncIo, AsyncIoDyn, AsyncRead, AsyncWrite, PollIoAdapter, ReadBuf};
+
+use crate::bytes::{Buf, Bytes};
+
+/// handle for taking over the connection of a http/1 CONNECT request.
+///
+/// It's inserted into request's [Extensions] by http/1 dispatcher for every CONNECT request. When service responds
+/// with 2xx status code while any copy of this handle is still alive the dispatcher sends response head and hands
+/// the connection over to it. Awaiting the handle then resolves to [Upgraded] connection. Body of the response is
+/// ignored in this case.
+///
+/// The handle must be moved out of request and awaited in a separately spawned task because the connection is
+/// only handed over after service returns response. When all copies of the handle are dropped before that the
+/// connection is served as usual and request/response body is used for tunneling.
+///
+/// # Note
+/// Request body of CONNECT request is read on demand and bytes sent by client before response are kept for
+/// [Upgraded] as long as request body is not polled.
+///
+/// Connection take over is not supported by io-uring http/1 dispatcher and the handle is not inserted into request
+/// extensions by it.
+///
+/// [Extensions]: crate::http::Extensions
+#[derive(Clone)]
+pub struct OnUpgrade(Arc<Mutex<Slot>>);
+
+struct Slot {
+    state: State,
+    waker: Option<Waker>,
+}
+
+enum State {
+    Pending,
+    Ready(Upgraded),
+    Taken,
+    Closed,
+}
+
+impl fmt::Debug for OnUpgrade {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OnUpgrade").finish_non_exhaustive()
+    }
+}
+
+impl Future for OnUpgrade {
+    type Output = io::Result<Upgraded>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut slot = self.0.lock().unwrap();
+        match core::mem::replace(&mut slot.state, State::Taken) {
+            State::Ready(upgraded) => Poll::Ready(Ok(upgraded)),
+            State::Pending => {
+                slot.state = State::Pending;
+                slot.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+            State::Taken => Poll::Ready(Err(io::Error::other("connection is already taken by other OnUpgrade"))),
+            State::Closed => {
+                slot.state = State::Closed;
+                Poll::Ready(Err(io::Error::new(
+                    io::ErrorKind::NotConnected,
+                    "connection is not upgraded",
+                )))
+            }
+        }
+    }
+}
+
+// sender part of OnUpgrade kept by dispatcher.
+pub(crate) struct UpgradeSender(Arc<Mutex<Slot>>);
+
+pub(crate) fn channel() -> (UpgradeSender, OnUpgrade) {
+    let slot = Arc::new(Mutex::new(Slot {
+        state: State::Pending,
+        waker: None,
+    }));
+    (UpgradeSender(slot.clone()), OnUpgrade(slot))
+}
+
+impl UpgradeSender {
+    // true when any copy of OnUpgrade is still alive.
+    pub(crate) fn is_wanted(&self) -> bool {
+        Arc::strong_count(&self.0) > 1
+    }
+
+    fn set(&self, state: State) {
+        let mut slot = self.0.lock().unwrap();
+        if matches!(slot.state, State::Pending) {
+            slot.state = state;
+            if let Some(waker) = slot.waker.take() {
+                waker.wake();
+            }
+        }
+    }
+}
+
+impl Drop for UpgradeSender {
+    fn drop(&mut self) {
+        self.set(State::Closed);
+    }
+}
+
+/// pending connection take over produced by http/1 dispatcher. the connection io is owned by caller of dispatcher
+/// and must be passed to [Upgrade::send] or http/2 dispatcher in case of [UpgradeKind::H2c].
+pub(crate) struct Upgrade {
+    pub(crate) kind: UpgradeKind,
+    pub(crate) read_buf: Bytes,
+}
+
+pub(crate) enum UpgradeKind {
+    // CONNECT request accepted by service.
+    Connect(UpgradeSender),
+    // h2c upgrade request. "101 Switching Protocols" response is sent and connection continues as http/2.
+    #[cfg(feature = "http2")]
+    H2c(crate::h2c::H2cUpgrade),
+}
+
+impl Upgrade {
+    pub(crate) fn send<Io>(self, io: Io)
+    where
+        Io: AsyncIo + Send + 'static,
+    {
+        match self.kind {
+            UpgradeKind::Connect(tx) => tx.set(State::Ready(Upgraded {
+                io: PollIoAdapter(Box::new(io)),
+                read_buf: self.read_buf,
+            })),
+            #[cfg(feature = "http2")]
+            UpgradeKind::H2c(_) => unreachable!("h2c upgrade must be handled by http/2 dispatcher"),
+        }
+    }
+}
+
+/// connection taken over from http/1 dispatcher. See [OnUpgrade] for detail.
+///
+/// Bytes received after request head and before the take over are yielded first when reading from it.
+pub struct Upgraded {
+    io: PollIoAdapter<Box<dyn AsyncIoDyn + Send>>,
+    read_buf: Bytes,
+}
+
+impl fmt::Debug for Upgraded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Upgraded")
+            .field("read_buf", &self.read_buf.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Upgraded {
+    /// destruct self into connection io and bytes already received from it.
+    pub fn into_parts(self) -> (Box<dyn AsyncIoDyn + Send>, Bytes) {
+        (self.io.0, self.read_buf)
+    }
+}
+
+impl AsyncRead for Upgraded {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        if !this.read_buf.is_empty() {
+            let len = core::cmp::min(this.read_buf.len(), buf.remaining());
+            buf.put_slice(&this.read_buf[..len]);
+            this.read_buf.advance(len);
+            return Poll::Ready(Ok(()));
+        }
+        Pin::new(&mut this.io).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for Upgraded {
+    #[inline]
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.get_mut().io).poll_write(cx, buf)
+    }
+
+    #[inline]
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().io).poll_flush(cx)
+    }
+
+    #[inline]
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        AsyncWrite::poll_shutdown(Pin::new(&mut self.get_mut().io), cx)
+    }
+
+    #[inline]
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.get_mut().io).poll_write_vectored(cx, bufs)
+    }
+
+    #[inline]
+    fn is_write_vectored(&self) -> bool {
+        self.io.is_write_vectored()
+    }
+}
@@ -9,6 +9,10 @@ use h2::RecvStream;
 use crate::{bytes::Bytes, error::BodyError};
 
 /// Request body type for Http/2 specifically.
+///
+/// Flow control window of request stream is released only when bytes are yielded from this stream. A request body
+/// not being consumed withholds window update and client is backpressured after it has sent up to
+/// [HttpServiceConfig::h2_initial_window_size](crate::config::HttpServiceConfig::h2_initial_window_size) bytes.
 pub struct RequestBody {
     end_stream: bool,
     stream: RecvStream,
@@ -40,6 +44,15 @@ impl Stream for RequestBody {
             None => Poll::Ready(None),
         }
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // request with END_STREAM flag set on it's headers frame does not have a body.
+        if self.end_stream || self.stream.is_end_stream() {
+            crate::body::none_body_hint()
+        } else {
+            (0, None)
+        }
+    }
 }
 
 impl From<RequestBody> for crate::body::RequestBody {
@@ -29,6 +29,8 @@ impl<St, FA, const HEADER_LIMIT: usize, const READ_BUF_LIMIT: usize, const WRITE
         HttpServiceBuilder {
             tls_factory: self.tls_factory,
             config: self.config,
+            #[cfg(feature = "metrics")]
+            metrics: self.metrics,
             _body: std::marker::PhantomData,
         }
     }
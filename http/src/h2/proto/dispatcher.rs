@@ -10,7 +10,7 @@ use core::{
 
 use ::h2::{
     server::{Connection, SendResponse},
-    Ping, PingPong,
+    Ping, PingPong, Reason,
 };
 use futures_core::stream::Stream;
 use tracing::trace;
@@ -19,26 +19,34 @@ use xitca_service::Service;
 use xitca_unsafe_collection::futures::{Select as _, SelectOutput};
 
 use crate::{
-    body::BodySize,
+    body::{BodySize, Flush},
     bytes::Bytes,
+    config::HttpServiceConfig,
     date::{DateTime, DateTimeHandle},
     error::HttpServiceError,
     h2::{body::RequestBody, error::Error},
     http::{
-        header::{HeaderMap, HeaderName, HeaderValue, CONNECTION, CONTENT_LENGTH, DATE, TRAILER},
-        Extension, Request, RequestExt, Response, Version,
+        header::{Entry, HeaderMap, HeaderName, HeaderValue, CONNECTION, CONTENT_LENGTH, DATE, SERVER, TRAILER},
+        Disconnect, Extension, Protocol, Request, RequestExt, Response, StatusCode, Version,
+    },
+    util::{
+        futures::Queue,
+        timer::{KeepAlive, Timeout},
     },
-    util::{futures::Queue, timer::KeepAlive},
 };
 
 /// Http/2 dispatcher
 pub(crate) struct Dispatcher<'a, TlsSt, S, ReqB> {
     io: &'a mut Connection<TlsSt, Bytes>,
     addr: SocketAddr,
+    secure: bool,
     keep_alive: Pin<&'a mut KeepAlive>,
-    ka_dur: Duration,
+    ping_interval: Duration,
+    ping_timeout: Duration,
     service: &'a S,
     date: &'a DateTimeHandle,
+    date_header: bool,
+    server_header: Option<&'static str>,
     _req_body: PhantomData<ReqB>,
 }
 
@@ -53,21 +61,27 @@ where
     TlsSt: AsyncRead + AsyncWrite + Unpin,
     ReqB: From<RequestBody>,
 {
-    pub(crate) fn new(
+    pub(crate) fn new<const HEADER_LIMIT: usize, const READ_BUF_LIMIT: usize, const WRITE_BUF_LIMIT: usize>(
         io: &'a mut Connection<TlsSt, Bytes>,
         addr: SocketAddr,
         keep_alive: Pin<&'a mut KeepAlive>,
-        ka_dur: Duration,
+        config: &HttpServiceConfig<HEADER_LIMIT, READ_BUF_LIMIT, WRITE_BUF_LIMIT>,
         service: &'a S,
         date: &'a DateTimeHandle,
     ) -> Self {
         Self {
             io,
             addr,
+            secure: config.tls,
             keep_alive,
-            ka_dur,
+            ping_interval: config.h2_ping_interval.unwrap_or(config.keep_alive_timeout),
+            // There is no particular reason for the default duration choice here. as h2 connection is
+            // suggested to be kept alive for a relative long time.
+            ping_timeout: config.h2_ping_timeout.unwrap_or(config.keep_alive_timeout * 10),
             service,
             date,
+            date_header: config.date_header,
+            server_header: config.server_header,
             _req_body: PhantomData,
         }
     }
@@ -76,26 +90,31 @@ where
         let Self {
             io,
             addr,
+            secure,
             mut keep_alive,
-            ka_dur,
+            ping_interval,
+            ping_timeout,
             service,
             date,
+            date_header,
+            server_header,
             ..
         } = self;
 
         let ping_pong = io.ping_pong().expect("first call to ping_pong should never fail");
 
-        // reset timer to keep alive.
-        let deadline = date.now() + ka_dur;
+        // reset timer to ping interval.
+        let deadline = date.now() + ping_interval;
         keep_alive.as_mut().update(deadline);
 
-        // timer for ping pong interval and keep alive.
+        // timer for ping pong interval and timeout.
         let mut ping_pong = H2PingPong {
             on_flight: false,
             keep_alive: keep_alive.as_mut(),
             ping_pong,
             date,
-            ka_dur,
+            interval: ping_interval,
+            timeout: ping_timeout,
         };
 
         let mut queue = Queue::new();
@@ -103,21 +122,34 @@ where
         loop {
             match io.accept().select(try_poll_queue(&mut queue, &mut ping_pong)).await {
                 SelectOutput::A(Some(Ok((req, tx)))) => {
+                    let disconnect = Disconnect::new();
+
                     // Convert http::Request body type to crate::h2::Body
                     // and reconstruct as HttpRequest.
                     let req = req.map(|body| {
                         let body = ReqB::from(RequestBody::from(body));
-                        RequestExt::from_parts(body, Extension::new(addr))
+                        let mut ext = RequestExt::from_parts(body, Extension::new(addr, Protocol::Http2));
+                        ext.set_disconnect(disconnect.clone());
+                        ext.set_secure(secure);
+                        ext
                     });
 
                     queue.push(async move {
                         let fut = service.call(req);
-                        h2_handler(fut, tx, date).await
+                        let date = date_header.then_some(date);
+                        h2_handler(fut, tx, date, server_header, disconnect).await
                     });
                 }
                 SelectOutput::B(SelectOutput::A(_)) => io.graceful_shutdown(),
                 SelectOutput::B(SelectOutput::B(Ok(_))) => {
-                    trace!("Connection keep-alive timeout. Shutting down");
+                    trace!("Connection ping timeout. Shutting down");
+                    io.abrupt_shutdown(Reason::NO_ERROR);
+                    // peer is likely dead. try to flush GOAWAY frame within ping timeout and give up.
+                    let deadline = date.now() + ping_timeout;
+                    ping_pong.keep_alive.as_mut().update(deadline);
+                    let _ = poll_fn(|cx| io.poll_closed(cx))
+                        .timeout(ping_pong.keep_alive.as_mut())
+                        .await;
                     return Ok(());
                 }
                 SelectOutput::A(None) => {
@@ -162,7 +194,8 @@ struct H2PingPong<'a> {
     keep_alive: Pin<&'a mut KeepAlive>,
     ping_pong: PingPong,
     date: &'a DateTimeHandle,
-    ka_dur: Duration,
+    interval: Duration,
+    timeout: Duration,
 }
 
 impl Future for H2PingPong<'_> {
@@ -173,14 +206,13 @@ impl Future for H2PingPong<'_> {
 
         loop {
             if this.on_flight {
-                // When have on flight ping pong. poll pong and and keep alive timer.
-                // on success pong received update keep alive timer to determine the next timing of
-                // ping pong.
+                // When have on flight ping pong. poll pong and and ping timeout timer.
+                // on success pong received update timer to determine the next timing of ping pong.
                 match this.ping_pong.poll_pong(cx)? {
                     Poll::Ready(_) => {
                         this.on_flight = false;
 
-                        let deadline = this.date.now() + this.ka_dur;
+                        let deadline = this.date.now() + this.interval;
 
                         this.keep_alive.as_mut().update(deadline);
                         this.keep_alive.as_mut().reset();
@@ -188,17 +220,15 @@ impl Future for H2PingPong<'_> {
                     Poll::Pending => return this.keep_alive.as_mut().poll(cx).map(|_| Ok(())),
                 }
             } else {
-                // When there is no on flight ping pong. keep alive timer is used to wait for next
-                // timing of ping pong. Therefore at this point it serves as an interval instead.
+                // When there is no on flight ping pong. timer is used to wait for next timing of ping pong.
+                // Therefore at this point it serves as an interval instead.
 
                 ready!(this.keep_alive.as_mut().poll(cx));
 
                 this.ping_pong.send_ping(Ping::opaque())?;
 
-                // Update the keep alive to 10 times the normal keep alive duration.
-                // There is no particular reason for the duration choice here. as h2 connection is
-                // suggested to be kept alive for a relative long time.
-                let deadline = this.date.now() + (this.ka_dur * 10);
+                // Update the timer to wait for pong.
+                let deadline = this.date.now() + this.timeout;
 
                 this.keep_alive.as_mut().update(deadline);
 
@@ -217,22 +247,45 @@ enum ConnectionState {
 async fn h2_handler<Fut, B, SE, BE>(
     fut: Fut,
     mut tx: SendResponse<Bytes>,
-    date: &DateTimeHandle,
+    date: Option<&DateTimeHandle>,
+    server: Option<&'static str>,
+    disconnect: Disconnect,
 ) -> Result<ConnectionState, Error<SE, BE>>
 where
     Fut: Future<Output = Result<Response<B>, SE>>,
     B: Stream<Item = Result<Bytes, BE>>,
     BE: fmt::Debug,
 {
+    let mut fut = pin!(fut);
+
+    // watch for stream reset from client while service is running.
+    let res = match fut.as_mut().select(poll_fn(|cx| tx.poll_reset(cx))).await {
+        SelectOutput::A(res) => res,
+        SelectOutput::B(_) => {
+            trace!("Stream reset by remote while serving request");
+            disconnect.disconnect();
+            // service is notified and decide when to give up. response is discarded as stream is gone.
+            fut.await.map_err(Error::Service)?;
+            return Ok(ConnectionState::KeepAlive);
+        }
+    };
+
     // split response to header and body.
-    let (res, body) = fut.await.map_err(Error::Service)?.into_parts();
+    let (res, body) = res.map_err(Error::Service)?.into_parts();
     let mut res = Response::from_parts(res, ());
 
+    let flush = res.extensions().get::<Flush>().copied().unwrap_or_default();
+
     // set response version.
     *res.version_mut() = Version::HTTP_2;
 
     // check eof state of response body and make sure header is valid.
     let is_eof = match BodySize::from_stream(&body) {
+        // 204 and 304 response can not bear body. body and it's size header are dropped.
+        _ if matches!(res.status(), StatusCode::NO_CONTENT | StatusCode::NOT_MODIFIED) => {
+            res.headers_mut().remove(CONTENT_LENGTH);
+            true
+        }
         BodySize::None => {
             debug_assert!(!res.headers().contains_key(CONTENT_LENGTH));
             true
@@ -252,15 +305,38 @@ where
 
     let mut trailers = HeaderMap::with_capacity(0);
 
-    while let Some(value) = res.headers_mut().remove(TRAILER) {
-        let name = HeaderName::from_bytes(value.as_bytes()).unwrap();
-        let value = res.headers_mut().remove(name.clone()).unwrap();
-        trailers.append(name, value);
+    // move every header field announced by trailer header(s) to trailers. trailer header can be present
+    // multiple times and each value can be a comma separated list of field names.
+    if let Entry::Occupied(entry) = res.headers_mut().entry(TRAILER) {
+        let names = entry
+            .remove_entry_mult()
+            .1
+            .filter_map(|value| value.to_str().map(str::to_owned).ok())
+            .collect::<Vec<_>>();
+        for name in names.iter().flat_map(|names| names.split(',')) {
+            let Ok(name) = HeaderName::from_bytes(name.trim().as_bytes()) else {
+                continue;
+            };
+            if let Entry::Occupied(entry) = res.headers_mut().entry(name) {
+                let (name, values) = entry.remove_entry_mult();
+                for value in values {
+                    trailers.append(name.clone(), value);
+                }
+            }
+        }
+    }
+
+    if let Some(date) = date {
+        if !res.headers().contains_key(DATE) {
+            let date = date.with_date(HeaderValue::from_bytes).unwrap();
+            res.headers_mut().insert(DATE, date);
+        }
     }
 
-    if !res.headers().contains_key(DATE) {
-        let date = date.with_date(HeaderValue::from_bytes).unwrap();
-        res.headers_mut().insert(DATE, date);
+    if let Some(server) = server {
+        if !res.headers().contains_key(SERVER) {
+            res.headers_mut().insert(SERVER, HeaderValue::from_static(server));
+        }
     }
 
     // check response header to determine if user want connection be closed.
@@ -280,9 +356,14 @@ where
     if !is_eof {
         let mut body = pin!(body);
 
+        // bytes of body sent since connection is last given chance to write frames to io.
+        let mut buffered = 0;
+
         while let Some(res) = poll_fn(|cx| body.as_mut().poll_next(cx)).await {
             let mut chunk = res.map_err(Error::Body)?;
 
+            buffered += chunk.len();
+
             while !chunk.is_empty() {
                 let len = chunk.len();
 
@@ -297,6 +378,13 @@ where
 
                 stream.send_data(bytes, false)?;
             }
+
+            // data frames are written by connection which runs in the same task. yield to it so the frames are not
+            // held back by body stream producing items without pending.
+            if flush.is_due(buffered) {
+                buffered = 0;
+                yield_now().await;
+            }
         }
     }
 
@@ -306,3 +394,16 @@ where
 }
 
 const CHUNK_SIZE: usize = 16_384;
+
+async fn yield_now() {
+    let mut yielded = false;
+    poll_fn(|cx| {
+        if yielded {
+            return Poll::Ready(());
+        }
+        yielded = true;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    })
+    .await
+}
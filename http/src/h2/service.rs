@@ -61,19 +61,15 @@ where
 
         let mut conn = ::h2::server::Builder::new()
             .enable_connect_protocol()
+            .initial_window_size(self.config.h2_initial_window_size)
+            .initial_connection_window_size(self.config.h2_initial_connection_window_size)
+            .max_concurrent_streams(self.config.h2_max_concurrent_streams)
             .handshake(PollIoAdapter(tls_stream))
             .timeout(timer.as_mut())
             .await
             .map_err(|_| HttpServiceError::Timeout(TimeoutError::H2Handshake))??;
 
-        let dispatcher = Dispatcher::new(
-            &mut conn,
-            addr,
-            timer,
-            self.config.keep_alive_timeout,
-            &self.service,
-            self.date.get(),
-        );
+        let dispatcher = Dispatcher::new(&mut conn, addr, timer, &self.config, &self.service, self.date.get());
 
         dispatcher.run().await?;
 
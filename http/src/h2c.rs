@@ -0,0 +1,502 @@
+//! cleartext http/2 (h2c) support for [HttpService] serving http/1 and http/2 on the same connection.
+//!
+//! Two ways of starting h2c are supported:
+//! - prior knowledge: client sends http/2 connection preface directly. it's detected from the first bytes of
+//!   connection and the connection is served as http/2 from the start.
+//! - upgrade: client sends a http/1 request with `Upgrade: h2c` header. http/1 dispatcher responds with
+//!   "101 Switching Protocols" and the request is served as stream 1 of http/2 connection.
+//!
+//! [HttpService]: crate::HttpService
+
+use core::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use std::io;
+
+use xitca_io::io::{AsyncIo, Interest, Ready};
+
+use crate::{
+    bytes::{Buf, BufMut, Bytes, BytesMut},
+    http::{
+        header::{CONNECTION, HOST, UPGRADE},
+        HeaderName, Method, Request, Version,
+    },
+};
+
+pub(crate) const PREFACE: &[u8; 24] = b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n";
+
+/// response head of accepted h2c upgrade request.
+pub(crate) const SWITCHING_PROTOCOLS: &[u8] =
+    b"HTTP/1.1 101 Switching Protocols\r\nconnection: upgrade\r\nupgrade: h2c\r\n\r\n";
+
+const HTTP2_SETTINGS: HeaderName = HeaderName::from_static("http2-settings");
+
+// http/2 frame header length and default max frame size both peers must accept.
+const FRAME_HEADER_LEN: usize = 9;
+const MAX_FRAME_SIZE: usize = 16_384;
+
+const FRAME_TYPE_HEADERS: u8 = 0x1;
+const FRAME_TYPE_SETTINGS: u8 = 0x4;
+const FLAG_END_STREAM: u8 = 0x1;
+const FLAG_END_HEADERS: u8 = 0x4;
+
+/// read from io until it's clear if connection starts with http/2 preface. bytes read are returned and must be
+/// replayed with [Rewind] regardless of the outcome.
+pub(crate) async fn peek_preface<Io>(io: &mut Io) -> io::Result<(bool, Bytes)>
+where
+    Io: AsyncIo,
+{
+    let mut buf = BytesMut::new();
+    loop {
+        let len = core::cmp::min(buf.len(), PREFACE.len());
+        if buf[..len] != PREFACE[..len] {
+            return Ok((false, buf.freeze()));
+        }
+        if len == PREFACE.len() {
+            return Ok((true, buf.freeze()));
+        }
+        // remote closed before sending enough bytes. let the following dispatcher observe it.
+        if read_more(io, &mut buf).await? == 0 {
+            return Ok((false, buf.freeze()));
+        }
+    }
+}
+
+async fn read_more<Io>(io: &mut Io, buf: &mut BytesMut) -> io::Result<usize>
+where
+    Io: AsyncIo,
+{
+    let mut chunk = [0; 4096];
+    loop {
+        io.ready(Interest::READABLE).await?;
+        match io.read(&mut chunk) {
+            Ok(n) => {
+                buf.put_slice(&chunk[..n]);
+                return Ok(n);
+            }
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => continue,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// accepted h2c upgrade request carrying it's head encoded as http/2 HEADERS frame of stream 1.
+pub(crate) struct H2cUpgrade {
+    headers: Bytes,
+}
+
+impl H2cUpgrade {
+    /// check if request head is a valid h2c upgrade request and encode it. body of request is ignored.
+    /// `secure` is the tls state of connection and decides the `:scheme` of encoded request.
+    ///
+    /// `None` is returned for request not asking for upgrade or can't be upgraded. in which case the request is
+    /// served as http/1.
+    pub(crate) fn try_new<B>(req: &Request<B>, secure: bool) -> Option<Self> {
+        if req.version() != Version::HTTP_11 || *req.method() == Method::CONNECT {
+            return None;
+        }
+
+        let headers = req.headers();
+
+        let has_token = |name, token: &str| {
+            headers
+                .get_all(name)
+                .iter()
+                .filter_map(|v| v.to_str().ok())
+                .flat_map(|v| v.split(','))
+                .any(|v| v.trim().eq_ignore_ascii_case(token))
+        };
+
+        if !has_token(UPGRADE, "h2c") || !has_token(CONNECTION, "upgrade") {
+            return None;
+        }
+
+        // exactly one HTTP2-Settings header carrying valid SETTINGS frame payload must be present.
+        let mut settings = headers.get_all(HTTP2_SETTINGS).iter();
+        match (settings.next(), settings.next()) {
+            (Some(value), None) if is_valid_settings(value.as_bytes()) => {}
+            _ => return None,
+        }
+
+        let authority = headers
+            .get(HOST)
+            .map(|v| v.as_bytes())
+            .or_else(|| req.uri().authority().map(|a| a.as_str().as_bytes()))
+            .unwrap_or_default();
+        let path = req.uri().path_and_query().map(|p| p.as_str()).unwrap_or("/");
+
+        let mut block = BytesMut::new();
+        encode_field(b":method", req.method().as_str().as_bytes(), &mut block);
+        let scheme: &[u8] = if secure { b"https" } else { b"http" };
+        encode_field(b":scheme", scheme, &mut block);
+        encode_field(b":authority", authority, &mut block);
+        encode_field(b":path", path.as_bytes(), &mut block);
+
+        for (name, value) in headers {
+            // connection specific headers are not allowed in http/2.
+            if matches!(
+                name.as_str(),
+                "host"
+                    | "connection"
+                    | "upgrade"
+                    | "http2-settings"
+                    | "transfer-encoding"
+                    | "te"
+                    | "keep-alive"
+                    | "proxy-connection"
+            ) {
+                continue;
+            }
+            encode_field(name.as_str().as_bytes(), value.as_bytes(), &mut block);
+        }
+
+        // header block not fitting in a single frame is not worth the complexity of CONTINUATION frames.
+        if block.len() > MAX_FRAME_SIZE {
+            return None;
+        }
+
+        let mut frame = BytesMut::with_capacity(FRAME_HEADER_LEN + block.len());
+        frame.put_uint(block.len() as u64, 3);
+        frame.put_u8(FRAME_TYPE_HEADERS);
+        frame.put_u8(FLAG_END_HEADERS | FLAG_END_STREAM);
+        frame.put_u32(1);
+        frame.put_slice(&block);
+
+        Some(Self {
+            headers: frame.freeze(),
+        })
+    }
+
+    /// wait for client's connection preface and first SETTINGS frame after "101 Switching Protocols" response is
+    /// sent and inject the upgrade request as HEADERS frame of stream 1 after them. http/2 server reading from the
+    /// returned io observes the upgrade request as the first request of connection.
+    pub(crate) async fn into_io<Io>(self, mut io: Rewind<Io>) -> io::Result<Rewind<Io>>
+    where
+        Io: AsyncIo,
+    {
+        let mut buf = BytesMut::new();
+
+        let settings_end = loop {
+            if buf.len() >= PREFACE.len() + FRAME_HEADER_LEN {
+                if &buf[..PREFACE.len()] != PREFACE || buf[PREFACE.len() + 3] != FRAME_TYPE_SETTINGS {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "invalid h2c connection preface",
+                    ));
+                }
+                let mut len = &buf[PREFACE.len()..PREFACE.len() + 3];
+                let end = PREFACE.len() + FRAME_HEADER_LEN + len.get_uint(3) as usize;
+                if buf.len() >= end {
+                    break end;
+                }
+            }
+            if read_more(&mut io, &mut buf).await? == 0 {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+        };
+
+        let rest = buf.split_off(settings_end);
+        buf.extend_from_slice(&self.headers);
+        buf.extend_from_slice(&rest);
+        io.prepend(buf.freeze());
+
+        Ok(io)
+    }
+}
+
+// HTTP2-Settings header value is base64url encoded payload of SETTINGS frame with trailing '=' omitted. the
+// settings are superseded by the SETTINGS frame client sends after connection preface and they are only validated
+// here.
+fn is_valid_settings(value: &[u8]) -> bool {
+    const SETTINGS_ENABLE_PUSH: u16 = 0x2;
+    const SETTINGS_INITIAL_WINDOW_SIZE: u16 = 0x4;
+    const SETTINGS_MAX_FRAME_SIZE: u16 = 0x5;
+
+    let value = value
+        .strip_suffix(b"==")
+        .or_else(|| value.strip_suffix(b"="))
+        .unwrap_or(value);
+
+    let mut payload = Vec::with_capacity(value.len() * 3 / 4);
+    let (mut acc, mut bits) = (0u32, 0);
+    for b in value {
+        let n = match b {
+            b'A'..=b'Z' => b - b'A',
+            b'a'..=b'z' => b - b'a' + 26,
+            b'0'..=b'9' => b - b'0' + 52,
+            b'-' => 62,
+            b'_' => 63,
+            _ => return false,
+        };
+        acc = (acc << 6 | n as u32) & 0xffff;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            payload.push((acc >> bits) as u8);
+        }
+    }
+
+    // a single trailing character can not encode a whole byte.
+    if bits >= 6 || payload.len() % 6 != 0 {
+        return false;
+    }
+
+    payload.chunks_exact(6).all(|mut setting| {
+        let id = setting.get_u16();
+        let value = setting.get_u32();
+        match id {
+            SETTINGS_ENABLE_PUSH => value <= 1,
+            SETTINGS_INITIAL_WINDOW_SIZE => value <= 0x7fff_ffff,
+            SETTINGS_MAX_FRAME_SIZE => (MAX_FRAME_SIZE as u32..=0xff_ffff).contains(&value),
+            // unknown setting must be ignored.
+            _ => true,
+        }
+    })
+}
+
+// encode header field as literal without indexing and huffman coding so no hpack state is needed.
+fn encode_field(name: &[u8], value: &[u8], buf: &mut BytesMut) {
+    buf.put_u8(0);
+    encode_str(name, buf);
+    encode_str(value, buf);
+}
+
+fn encode_str(s: &[u8], buf: &mut BytesMut) {
+    encode_int(s.len(), buf);
+    buf.put_slice(s);
+}
+
+// hpack integer with 7 bit prefix and huffman flag unset.
+fn encode_int(mut n: usize, buf: &mut BytesMut) {
+    const MAX: usize = 0x7f;
+    if n < MAX {
+        buf.put_u8(n as u8);
+        return;
+    }
+    buf.put_u8(MAX as u8);
+    n -= MAX;
+    while n >= 0x80 {
+        buf.put_u8((n & 0x7f) as u8 | 0x80);
+        n >>= 7;
+    }
+    buf.put_u8(n as u8);
+}
+
+/// io type yielding given bytes first before reading from inner io.
+pub(crate) struct Rewind<Io> {
+    pre: Bytes,
+    io: Io,
+}
+
+impl<Io> Rewind<Io> {
+    pub(crate) fn new(io: Io, pre: Bytes) -> Self {
+        Self { pre, io }
+    }
+
+    // bytes are yielded before the ones not read yet.
+    pub(crate) fn prepend(&mut self, bytes: Bytes) {
+        self.pre = if self.pre.is_empty() {
+            bytes
+        } else {
+            let mut buf = BytesMut::from(bytes.as_ref());
+            buf.extend_from_slice(&self.pre);
+            buf.freeze()
+        };
+    }
+}
+
+impl<Io> AsyncIo for Rewind<Io>
+where
+    Io: AsyncIo,
+{
+    fn ready(&mut self, interest: Interest) -> impl Future<Output = io::Result<Ready>> + Send {
+        let pre_ready = !self.pre.is_empty() && interest.is_readable();
+        let fut = self.io.ready(interest);
+        async move {
+            if pre_ready {
+                Ok(Ready::READABLE)
+            } else {
+                fut.await
+            }
+        }
+    }
+
+    fn poll_ready(&mut self, interest: Interest, cx: &mut Context<'_>) -> Poll<io::Result<Ready>> {
+        if !self.pre.is_empty() && interest.is_readable() {
+            return Poll::Ready(Ok(Ready::READABLE));
+        }
+        self.io.poll_ready(interest, cx)
+    }
+
+    #[inline]
+    fn is_vectored_write(&self) -> bool {
+        self.io.is_vectored_write()
+    }
+
+    #[inline]
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().io).poll_shutdown(cx)
+    }
+}
+
+impl<Io> io::Read for Rewind<Io>
+where
+    Io: io::Read,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.pre.is_empty() {
+            return self.io.read(buf);
+        }
+        let len = core::cmp::min(self.pre.len(), buf.len());
+        buf[..len].copy_from_slice(&self.pre[..len]);
+        self.pre.advance(len);
+        Ok(len)
+    }
+}
+
+impl<Io> io::Write for Rewind<Io>
+where
+    Io: io::Write,
+{
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.io.write(buf)
+    }
+
+    #[inline]
+    fn write_vectored(&mut self, bufs: &[io::IoSlice<'_>]) -> io::Result<usize> {
+        self.io.write_vectored(bufs)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        self.io.flush()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Read;
+
+    use super::*;
+
+    fn req(headers: &[(&'static str, &'static str)]) -> Request<()> {
+        let mut req = Request::builder().uri("/foo?bar=1");
+        for (name, value) in headers {
+            req = req.header(*name, *value);
+        }
+        req.body(()).unwrap()
+    }
+
+    const UPGRADE_HEADERS: &[(&str, &str)] = &[
+        ("host", "example.com"),
+        ("connection", "Upgrade, HTTP2-Settings"),
+        ("upgrade", "h2c"),
+        ("http2-settings", "AAMAAABkAAQAoAAAAAIAAAAA"),
+        ("accept", "*/*"),
+    ];
+
+    #[test]
+    fn upgrade_detect() {
+        assert!(H2cUpgrade::try_new(&req(UPGRADE_HEADERS), false).is_some());
+        // missing http2-settings header.
+        assert!(H2cUpgrade::try_new(&req(&UPGRADE_HEADERS[..3]), false).is_none());
+        // upgrade to other protocol.
+        assert!(H2cUpgrade::try_new(
+            &req(&[
+                ("connection", "upgrade"),
+                ("upgrade", "websocket"),
+                ("http2-settings", ""),
+            ]),
+            false
+        )
+        .is_none());
+
+        let mut req = req(UPGRADE_HEADERS);
+        *req.version_mut() = Version::HTTP_10;
+        assert!(H2cUpgrade::try_new(&req, false).is_none());
+    }
+
+    #[test]
+    fn upgrade_settings() {
+        let upgrade = |settings: &'static str| {
+            let mut headers = UPGRADE_HEADERS.to_vec();
+            headers[3].1 = settings;
+            H2cUpgrade::try_new(&req(&headers), false).is_some()
+        };
+
+        // no setting.
+        assert!(upgrade(""));
+        // MAX_CONCURRENT_STREAMS = 100.
+        assert!(upgrade("AAMAAABk"));
+        // unknown setting is ignored.
+        assert!(upgrade("AP8AAAAB"));
+        // not base64url.
+        assert!(!upgrade("AAMAAABk!"));
+        // payload length not multiple of 6.
+        assert!(!upgrade("AAMAAA"));
+        // ENABLE_PUSH = 2.
+        assert!(!upgrade("AAIAAAAC"));
+        // INITIAL_WINDOW_SIZE = 2^31.
+        assert!(!upgrade("AASAAAAA"));
+        // MAX_FRAME_SIZE = 16383.
+        assert!(!upgrade("AAUAAD__"));
+
+        let mut headers = UPGRADE_HEADERS.to_vec();
+        headers.push(("http2-settings", ""));
+        assert!(H2cUpgrade::try_new(&req(&headers), false).is_none());
+    }
+
+    #[test]
+    fn upgrade_headers_frame() {
+        let frame = H2cUpgrade::try_new(&req(UPGRADE_HEADERS), false).unwrap().headers;
+
+        let mut block = BytesMut::new();
+        encode_field(b":method", b"GET", &mut block);
+        encode_field(b":scheme", b"http", &mut block);
+        encode_field(b":authority", b"example.com", &mut block);
+        encode_field(b":path", b"/foo?bar=1", &mut block);
+        // connection specific headers are removed.
+        encode_field(b"accept", b"*/*", &mut block);
+
+        assert_eq!(&frame[..3], &(block.len() as u32).to_be_bytes()[1..]);
+        assert_eq!(frame[3], FRAME_TYPE_HEADERS);
+        assert_eq!(frame[4], FLAG_END_HEADERS | FLAG_END_STREAM);
+        assert_eq!(&frame[5..9], &1u32.to_be_bytes());
+        assert_eq!(&frame[9..], &block[..]);
+
+        // scheme follows tls state of connection.
+        let frame = H2cUpgrade::try_new(&req(UPGRADE_HEADERS), true).unwrap().headers;
+        let mut scheme = BytesMut::new();
+        encode_field(b":scheme", b"https", &mut scheme);
+        assert!(frame.windows(scheme.len()).any(|w| w == &scheme[..]));
+    }
+
+    #[test]
+    fn hpack_int() {
+        let mut buf = BytesMut::new();
+        encode_int(10, &mut buf);
+        assert_eq!(&buf[..], &[10]);
+
+        // example from rfc 7541 C.1.2 with 7 bit prefix.
+        let mut buf = BytesMut::new();
+        encode_int(1337 - 31 + 127, &mut buf);
+        assert_eq!(&buf[..], &[127, 154, 10]);
+
+        let mut buf = BytesMut::new();
+        encode_int(127, &mut buf);
+        assert_eq!(&buf[..], &[127, 0]);
+    }
+
+    #[test]
+    fn rewind_read() {
+        let mut io = Rewind::new(io::Cursor::new(b"world".to_vec()), Bytes::from_static(b"hello "));
+        io.prepend(Bytes::from_static(b">"));
+        let mut buf = String::new();
+        io.read_to_string(&mut buf).unwrap();
+        assert_eq!(buf, ">hello world");
+    }
+}
@@ -19,7 +19,7 @@ use crate::{
     bytes::Bytes,
     error::HttpServiceError,
     h3::{body::RequestBody, error::Error},
-    http::{Extension, Request, RequestExt, Response},
+    http::{Extension, Protocol, Request, RequestExt, Response},
     util::futures::Queue,
 };
 
@@ -69,7 +69,7 @@ where
                     // Reconstruct Request to attach crate body type.
                     let req = req.map(|_| {
                         let body = ReqB::from(RequestBody(rx));
-                        RequestExt::from_parts(body, Extension::new(self.addr))
+                        RequestExt::from_parts(body, Extension::new(self.addr, Protocol::Http3))
                     });
 
                     queue.push(async move {
@@ -4,12 +4,16 @@ pub use ::http::*;
 
 use core::{
     borrow::{Borrow, BorrowMut},
+    fmt,
+    future::Future,
     mem,
     net::SocketAddr,
     pin::Pin,
-    task::{Context, Poll},
+    task::{Context, Poll, Waker},
 };
 
+use std::sync::{Arc, Mutex};
+
 use futures_core::stream::Stream;
 use pin_project_lite::pin_project;
 
@@ -30,6 +34,7 @@ pub mod const_header_value {
         (TEXT, "text/plain"),
         (TEXT_UTF8, "text/plain; charset=utf-8"),
         (JSON, "application/json"),
+        (OCTET_STREAM, "application/octet-stream"),
         (APPLICATION_WWW_FORM_URLENCODED, "application/x-www-form-urlencoded"),
         (TEXT_HTML_UTF8, "text/html; charset=utf-8"),
         (GRPC, "application/grpc"),
@@ -113,7 +118,35 @@ where
 }
 
 #[cfg(feature = "router")]
-use super::util::service::router::Params;
+use super::util::service::router::{MatchedRoute, Params};
+
+/// protocol of connection a [Request] is received from.
+///
+/// Unlike [Request::version] which is the version claimed by request itself, protocol is decided by the dispatcher
+/// serving the connection and can not be changed by client. e.g. a request received from cleartext http/2
+/// upgraded http/1 connection is [Protocol::Http2].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Protocol {
+    /// http/1.0 and http/1.1. Request not received from a dispatcher(e.g. [RequestExt::default]) uses it as
+    /// default value.
+    #[default]
+    Http1,
+    /// http/2 over tls or cleartext.
+    Http2,
+    /// http/3 over quic.
+    Http3,
+}
+
+impl Protocol {
+    /// the most recent [Version] a connection of the protocol can carry.
+    pub const fn as_version(&self) -> Version {
+        match self {
+            Self::Http1 => Version::HTTP_11,
+            Self::Http2 => Version::HTTP_2,
+            Self::Http3 => Version::HTTP_3,
+        }
+    }
+}
 
 pin_project! {
     /// extension types for [Request]
@@ -146,11 +179,17 @@ where
 pub(crate) struct Extension(Box<_Extension>);
 
 impl Extension {
-    pub(crate) fn new(addr: SocketAddr) -> Self {
+    pub(crate) fn new(addr: SocketAddr, protocol: Protocol) -> Self {
         Self(Box::new(_Extension {
             addr,
+            protocol,
+            // http/3 runs on quic which is always encrypted.
+            secure: matches!(protocol, Protocol::Http3),
+            disconnect: Disconnect::default(),
             #[cfg(feature = "router")]
             params: Default::default(),
+            #[cfg(feature = "router")]
+            route: Default::default(),
         }))
     }
 }
@@ -158,8 +197,13 @@ impl Extension {
 #[derive(Clone, Debug)]
 struct _Extension {
     addr: SocketAddr,
+    protocol: Protocol,
+    secure: bool,
+    disconnect: Disconnect,
     #[cfg(feature = "router")]
     params: Params,
+    #[cfg(feature = "router")]
+    route: MatchedRoute,
 }
 
 impl<B> RequestExt<B> {
@@ -182,6 +226,41 @@ impl<B> RequestExt<B> {
         &mut self.ext.0.addr
     }
 
+    /// retrieve protocol of connection request is received from.
+    ///
+    /// See [Protocol] for detail.
+    #[inline]
+    pub fn protocol(&self) -> Protocol {
+        self.ext.0.protocol
+    }
+
+    /// check if request is received from a connection secured by tls.
+    ///
+    /// Unlike [Request::uri] scheme which is claimed by request itself, this is decided by the dispatcher
+    /// serving the connection. Http/3 connections are always secure.
+    #[inline]
+    pub fn is_secure(&self) -> bool {
+        self.ext.0.secure
+    }
+
+    #[cfg(any(feature = "http1", feature = "http2"))]
+    pub(crate) fn set_secure(&mut self, secure: bool) {
+        self.ext.0.secure = secure;
+    }
+
+    /// retrieve client disconnect signal of request.
+    ///
+    /// See [Disconnect] for detail.
+    #[inline]
+    pub fn disconnect(&self) -> &Disconnect {
+        &self.ext.0.disconnect
+    }
+
+    #[cfg(any(feature = "http1", feature = "http2"))]
+    pub(crate) fn set_disconnect(&mut self, disconnect: Disconnect) {
+        self.ext.0.disconnect = disconnect;
+    }
+
     /// map body type of self to another type with given function closure.
     #[inline]
     pub fn map_body<F, B1>(self, func: F) -> RequestExt<B1>
@@ -209,7 +288,10 @@ where
     B: Default,
 {
     fn default() -> Self {
-        Self::from_parts(B::default(), Extension::new(crate::unspecified_socket_addr()))
+        Self::from_parts(
+            B::default(),
+            Extension::new(crate::unspecified_socket_addr(), Protocol::Http1),
+        )
     }
 }
 
@@ -237,6 +319,123 @@ impl<B> Borrow<SocketAddr> for RequestExt<B> {
     }
 }
 
+/// signal for client disconnection of a request.
+///
+/// The signal is triggered by http dispatcher when it observes the remote peer of request is gone. For http/1
+/// this happens when the read side of connection reaches EOF or errors after request body is fully received.
+/// For http/2 this happens when client reset the stream of request.
+///
+/// A signal not associated with any connection (for example [Disconnect::default]) is never triggered.
+///
+/// # Note
+/// A http/1 client half closing it's write side of connection is observed as disconnect.
+#[derive(Clone, Default)]
+pub struct Disconnect(Option<Arc<Mutex<DisconnectState>>>);
+
+#[derive(Default)]
+struct DisconnectState {
+    disconnected: bool,
+    next_key: usize,
+    wakers: Vec<(usize, Waker)>,
+}
+
+impl Disconnect {
+    #[cfg(any(feature = "http1", feature = "http2"))]
+    pub(crate) fn new() -> Self {
+        Self(Some(Default::default()))
+    }
+
+    #[cfg(any(feature = "http1", feature = "http2"))]
+    pub(crate) fn disconnect(&self) {
+        if let Some(ref state) = self.0 {
+            let mut state = state.lock().unwrap();
+            state.disconnected = true;
+            state.wakers.drain(..).for_each(|(_, waker)| waker.wake());
+        }
+    }
+
+    /// check if client has disconnected.
+    pub fn is_disconnected(&self) -> bool {
+        self.0.as_ref().is_some_and(|state| state.lock().unwrap().disconnected)
+    }
+
+    /// construct a future that resolves when client disconnected.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use xitca_http::http::{Request, RequestExt};
+    /// async fn handler(req: Request<RequestExt<()>>) {
+    ///     let disconnected = req.body().disconnect().disconnected();
+    ///     // disconnected future can be raced with expensive work so it can be aborted early when client is gone.
+    ///     disconnected.await;
+    /// }
+    /// ```
+    pub fn disconnected(&self) -> Disconnected {
+        Disconnected {
+            disconnect: self.clone(),
+            key: None,
+        }
+    }
+}
+
+impl fmt::Debug for Disconnect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Disconnect")
+            .field("disconnected", &self.is_disconnected())
+            .finish()
+    }
+}
+
+/// future returned by [Disconnect::disconnected]. resolves when client disconnected.
+pub struct Disconnected {
+    disconnect: Disconnect,
+    key: Option<usize>,
+}
+
+impl Future for Disconnected {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        let Some(ref state) = this.disconnect.0 else {
+            return Poll::Pending;
+        };
+
+        let mut state = state.lock().unwrap();
+
+        if state.disconnected {
+            return Poll::Ready(());
+        }
+
+        match this.key {
+            Some(key) => {
+                if let Some((_, waker)) = state.wakers.iter_mut().find(|(k, _)| *k == key) {
+                    if !waker.will_wake(cx.waker()) {
+                        *waker = cx.waker().clone();
+                    }
+                }
+            }
+            None => {
+                let key = state.next_key;
+                state.next_key = key.wrapping_add(1);
+                state.wakers.push((key, cx.waker().clone()));
+                this.key = Some(key);
+            }
+        }
+
+        Poll::Pending
+    }
+}
+
+impl Drop for Disconnected {
+    fn drop(&mut self) {
+        if let (Some(ref state), Some(key)) = (&self.disconnect.0, self.key) {
+            state.lock().unwrap().wakers.retain(|(k, _)| *k != key);
+        }
+    }
+}
+
 #[cfg(feature = "router")]
 mod router {
     use super::*;
@@ -253,6 +452,18 @@ mod router {
         pub fn params_mut(&mut self) -> &mut Params {
             &mut self.ext.0.params
         }
+
+        /// retrieve shared reference of [MatchedRoute].
+        #[inline]
+        pub fn matched_route(&self) -> &MatchedRoute {
+            &self.ext.0.route
+        }
+
+        /// retrieve exclusive reference of [MatchedRoute].
+        #[inline]
+        pub fn matched_route_mut(&mut self) -> &mut MatchedRoute {
+            &mut self.ext.0.route
+        }
     }
 
     impl<B> Borrow<Params> for RequestExt<B> {
@@ -268,6 +479,20 @@ mod router {
             self.params_mut()
         }
     }
+
+    impl<B> Borrow<MatchedRoute> for RequestExt<B> {
+        #[inline]
+        fn borrow(&self) -> &MatchedRoute {
+            self.matched_route()
+        }
+    }
+
+    impl<B> BorrowMut<MatchedRoute> for RequestExt<B> {
+        #[inline]
+        fn borrow_mut(&mut self) -> &mut MatchedRoute {
+            self.matched_route_mut()
+        }
+    }
 }
 
 /// trait for Borrow &T from &Self.
@@ -336,3 +561,22 @@ where
         self.body_mut().borrow_mut()
     }
 }
+
+#[cfg(all(test, feature = "http1"))]
+mod test {
+    use xitca_unsafe_collection::futures::NowOrPanic;
+
+    use super::*;
+
+    #[test]
+    fn disconnect() {
+        let ext = RequestExt::<()>::default();
+        assert!(!ext.disconnect().is_disconnected());
+
+        let disconnect = Disconnect::new();
+        let mut fut = disconnect.disconnected();
+        disconnect.disconnect();
+        assert!(disconnect.is_disconnected());
+        fut.now_or_panic();
+    }
+}
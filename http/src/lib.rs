@@ -40,6 +40,8 @@ mod tls;
 
 #[cfg(feature = "runtime")]
 mod builder;
+#[cfg(all(feature = "http1", feature = "http2"))]
+mod h2c;
 #[cfg(feature = "runtime")]
 mod service;
 #[cfg(feature = "runtime")]
@@ -59,6 +61,8 @@ pub mod h1;
 pub mod h2;
 #[cfg(feature = "http3")]
 pub mod h3;
+#[cfg(feature = "metrics")]
+pub mod metrics;
 
 /// re-export bytes crate as module.
 pub use xitca_io::bytes;
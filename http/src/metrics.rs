@@ -0,0 +1,281 @@
+//! connection and request metrics for http service.
+//!
+//! metrics are collected by atomic counters shared between [HttpMetrics] handle and the http service(s) it's
+//! attached to. When the same handle is passed to a multi worker server the counters are aggregated among all
+//! workers.
+//!
+//! # Examples
+//! ```rust
+//! use xitca_http::{metrics::HttpMetrics, HttpServiceBuilder};
+//!
+//! let metrics = HttpMetrics::new();
+//!
+//! // attach metrics handle to http service builder.
+//! let builder = HttpServiceBuilder::new().metrics(metrics.clone());
+//!
+//! // scrape metrics from the handle at any time.
+//! let snapshot = metrics.snapshot();
+//! assert_eq!(snapshot.requests, 0);
+//! ```
+
+use core::{
+    cell::Cell,
+    future::Future,
+    pin::Pin,
+    sync::atomic::{AtomicUsize, Ordering},
+    task::{Context, Poll},
+};
+
+use std::{io, sync::Arc};
+
+use xitca_io::io::{AsyncIo, Interest, Ready};
+use xitca_service::Service;
+
+use crate::{http::Version, version::AsVersion};
+
+/// shared handle of http service metrics counters.
+#[derive(Clone, Default)]
+pub struct HttpMetrics(Arc<Counters>);
+
+#[derive(Default)]
+struct Counters {
+    requests: AtomicUsize,
+    connections: AtomicUsize,
+    active_connections: AtomicUsize,
+    keep_alive_reuse: AtomicUsize,
+    bytes_in: AtomicUsize,
+    bytes_out: AtomicUsize,
+    shed_requests: AtomicUsize,
+}
+
+/// point in time copy of [HttpMetrics] counters.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MetricsSnapshot {
+    /// total count of requests served.
+    pub requests: usize,
+    /// total count of accepted connections.
+    pub connections: usize,
+    /// count of connections currently being served.
+    pub active_connections: usize,
+    /// count of requests served on a connection that already served at least one request.
+    pub keep_alive_reuse: usize,
+    /// total count of bytes read from connections.
+    pub bytes_in: usize,
+    /// total count of bytes written to connections.
+    pub bytes_out: usize,
+    /// total count of requests rejected by load shedding. See [HttpServiceBuilder::load_shed] for detail.
+    ///
+    /// [HttpServiceBuilder::load_shed]: crate::HttpServiceBuilder::load_shed
+    pub shed_requests: usize,
+}
+
+impl HttpMetrics {
+    /// construct a new metrics handle with all counters set to zero.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// take a snapshot of current metrics counters.
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        let c = &self.0;
+        MetricsSnapshot {
+            requests: c.requests.load(Ordering::Relaxed),
+            connections: c.connections.load(Ordering::Relaxed),
+            active_connections: c.active_connections.load(Ordering::Relaxed),
+            keep_alive_reuse: c.keep_alive_reuse.load(Ordering::Relaxed),
+            bytes_in: c.bytes_in.load(Ordering::Relaxed),
+            bytes_out: c.bytes_out.load(Ordering::Relaxed),
+            shed_requests: c.shed_requests.load(Ordering::Relaxed),
+        }
+    }
+
+    // mark a new connection accepted. active connection count is decreased when returned guard is dropped.
+    pub(crate) fn connection(&self) -> ConnectionGuard<'_> {
+        self.0.connections.fetch_add(1, Ordering::Relaxed);
+        self.0.active_connections.fetch_add(1, Ordering::Relaxed);
+        ConnectionGuard(self)
+    }
+
+    fn add_bytes_in(&self, n: usize) {
+        self.0.bytes_in.fetch_add(n, Ordering::Relaxed);
+    }
+
+    fn add_bytes_out(&self, n: usize) {
+        self.0.bytes_out.fetch_add(n, Ordering::Relaxed);
+    }
+
+    #[cfg(all(feature = "runtime", feature = "http1"))]
+    pub(crate) fn add_shed(&self) {
+        self.0.shed_requests.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+pub(crate) struct ConnectionGuard<'a>(&'a HttpMetrics);
+
+impl Drop for ConnectionGuard<'_> {
+    fn drop(&mut self) {
+        self.0 .0.active_connections.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// per connection service wrapper counting requests and keep-alive reuse.
+pub(crate) struct MetricsService<'a, S> {
+    service: &'a S,
+    metrics: &'a HttpMetrics,
+    served: Cell<bool>,
+}
+
+impl<'a, S> MetricsService<'a, S> {
+    pub(crate) fn new(service: &'a S, metrics: &'a HttpMetrics) -> Self {
+        Self {
+            service,
+            metrics,
+            served: Cell::new(false),
+        }
+    }
+}
+
+impl<S, Req> Service<Req> for MetricsService<'_, S>
+where
+    S: Service<Req>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+
+    #[inline]
+    async fn call(&self, req: Req) -> Result<Self::Response, Self::Error> {
+        let c = &self.metrics.0;
+        c.requests.fetch_add(1, Ordering::Relaxed);
+        if self.served.replace(true) {
+            c.keep_alive_reuse.fetch_add(1, Ordering::Relaxed);
+        }
+        self.service.call(req).await
+    }
+}
+
+/// io wrapper counting bytes transferred through connection.
+pub(crate) struct MetricsIo<Io> {
+    io: Io,
+    metrics: HttpMetrics,
+}
+
+impl<Io> MetricsIo<Io> {
+    pub(crate) fn new(io: Io, metrics: &HttpMetrics) -> Self {
+        Self {
+            io,
+            metrics: metrics.clone(),
+        }
+    }
+}
+
+impl<Io> AsVersion for MetricsIo<Io>
+where
+    Io: AsVersion,
+{
+    #[inline]
+    fn as_version(&self) -> Version {
+        self.io.as_version()
+    }
+}
+
+impl<Io> AsyncIo for MetricsIo<Io>
+where
+    Io: AsyncIo,
+{
+    #[inline]
+    fn ready(&mut self, interest: Interest) -> impl Future<Output = io::Result<Ready>> + Send {
+        self.io.ready(interest)
+    }
+
+    #[inline]
+    fn poll_ready(&mut self, interest: Interest, cx: &mut Context<'_>) -> Poll<io::Result<Ready>> {
+        self.io.poll_ready(interest, cx)
+    }
+
+    fn is_vectored_write(&self) -> bool {
+        self.io.is_vectored_write()
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().io).poll_shutdown(cx)
+    }
+}
+
+impl<Io> io::Read for MetricsIo<Io>
+where
+    Io: io::Read,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.io.read(buf)?;
+        self.metrics.add_bytes_in(n);
+        Ok(n)
+    }
+}
+
+impl<Io> io::Write for MetricsIo<Io>
+where
+    Io: io::Write,
+{
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.io.write(buf)?;
+        self.metrics.add_bytes_out(n);
+        Ok(n)
+    }
+
+    fn write_vectored(&mut self, bufs: &[io::IoSlice<'_>]) -> io::Result<usize> {
+        let n = self.io.write_vectored(bufs)?;
+        self.metrics.add_bytes_out(n);
+        Ok(n)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        self.io.flush()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use xitca_service::fn_service;
+    use xitca_unsafe_collection::futures::NowOrPanic;
+
+    use super::*;
+
+    #[test]
+    fn count_requests() {
+        let metrics = HttpMetrics::new();
+
+        {
+            let _guard = metrics.connection();
+            assert_eq!(metrics.snapshot().active_connections, 1);
+
+            let service = fn_service(|_: ()| async { Ok::<_, ()>(()) });
+            let service = MetricsService::new(&service, &metrics);
+
+            for _ in 0..3 {
+                service.call(()).now_or_panic().unwrap();
+            }
+        }
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.connections, 1);
+        assert_eq!(snapshot.active_connections, 0);
+        assert_eq!(snapshot.requests, 3);
+        assert_eq!(snapshot.keep_alive_reuse, 2);
+    }
+
+    #[test]
+    fn count_bytes() {
+        use std::io::{Read, Write};
+
+        let metrics = HttpMetrics::new();
+        let mut io = MetricsIo::new(io::Cursor::new(vec![0u8; 8]), &metrics);
+
+        assert_eq!(io.read(&mut [0; 4]).unwrap(), 4);
+        io.write_all(b"996").unwrap();
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.bytes_in, 4);
+        assert_eq!(snapshot.bytes_out, 3);
+    }
+}
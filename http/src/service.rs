@@ -31,6 +31,10 @@ pub struct HttpService<
     pub(crate) date: DateTimeService,
     pub(crate) service: S,
     pub(crate) tls_acceptor: A,
+    #[cfg(feature = "metrics")]
+    pub(crate) metrics: crate::metrics::HttpMetrics,
+    #[cfg(feature = "http1")]
+    pub(crate) shed: Option<crate::util::shed::LoadShed>,
     _body: PhantomData<(St, ReqB)>,
 }
 
@@ -47,10 +51,46 @@ impl<St, S, ReqB, A, const HEADER_LIMIT: usize, const READ_BUF_LIMIT: usize, con
             date: DateTimeService::new(),
             service,
             tls_acceptor,
+            #[cfg(feature = "metrics")]
+            metrics: crate::metrics::HttpMetrics::new(),
+            #[cfg(feature = "http1")]
+            shed: config
+                .load_shed
+                .map(|(high, low)| crate::util::shed::LoadShed::new(high, low)),
             _body: PhantomData,
         }
     }
 
+    // wrap connection io for collecting metrics. no op when metrics feature is not enabled.
+    #[cfg(feature = "metrics")]
+    fn wrap_io<Io>(&self, io: Io) -> crate::metrics::MetricsIo<Io> {
+        crate::metrics::MetricsIo::new(io, &self.metrics)
+    }
+
+    #[cfg(not(feature = "metrics"))]
+    #[inline(always)]
+    fn wrap_io<Io>(&self, io: Io) -> Io {
+        io
+    }
+
+    #[cfg(feature = "metrics")]
+    pub(crate) fn with_metrics(mut self, metrics: crate::metrics::HttpMetrics) -> Self {
+        #[cfg(feature = "http1")]
+        if let Some(ref mut shed) = self.shed {
+            shed.set_metrics(metrics.clone());
+        }
+        self.metrics = metrics;
+        self
+    }
+
+    /// metrics handle of http service. See [HttpMetrics] for detail.
+    ///
+    /// [HttpMetrics]: crate::metrics::HttpMetrics
+    #[cfg(feature = "metrics")]
+    pub fn metrics(&self) -> &crate::metrics::HttpMetrics {
+        &self.metrics
+    }
+
     #[cfg(feature = "http2")]
     pub(crate) fn update_first_request_deadline(&self, timer: core::pin::Pin<&mut KeepAlive>) {
         let request_dur = self.config.request_head_timeout;
@@ -58,6 +98,112 @@ impl<St, S, ReqB, A, const HEADER_LIMIT: usize, const READ_BUF_LIMIT: usize, con
         timer.update(deadline);
     }
 
+    #[cfg(feature = "http2")]
+    async fn serve_h2<Io, S2, ResB, BE>(
+        &self,
+        io: Io,
+        addr: core::net::SocketAddr,
+        mut timer: core::pin::Pin<&mut KeepAlive>,
+        service: &S2,
+    ) -> Result<(), HttpServiceError<S2::Error, BE>>
+    where
+        Io: AsyncIo,
+        S2: Service<Request<RequestExt<RequestBody>>, Response = Response<ResB>>,
+        S2::Error: fmt::Debug,
+        ResB: Stream<Item = Result<Bytes, BE>>,
+        BE: fmt::Debug,
+    {
+        // update timer to first request timeout.
+        self.update_first_request_deadline(timer.as_mut());
+
+        let mut conn = ::h2::server::Builder::new()
+            .enable_connect_protocol()
+            .initial_window_size(self.config.h2_initial_window_size)
+            .initial_connection_window_size(self.config.h2_initial_connection_window_size)
+            .max_concurrent_streams(self.config.h2_max_concurrent_streams)
+            .handshake(xitca_io::io::PollIoAdapter(io))
+            .timeout(timer.as_mut())
+            .await
+            .map_err(|_| HttpServiceError::Timeout(TimeoutError::H2Handshake))??;
+
+        super::h2::Dispatcher::new(&mut conn, addr, timer, &self.config, service, self.date.get())
+            .run()
+            .await
+            .map_err(Into::into)
+    }
+
+    // serve plain connection with h2c enabled. connection is served as http/2 when it starts with http/2 preface
+    // or it's first http/1 request is a h2c upgrade request.
+    #[cfg(all(feature = "http1", feature = "http2"))]
+    async fn serve_h2c<Io, S2, ResB, BE>(
+        &self,
+        mut io: Io,
+        addr: core::net::SocketAddr,
+        mut timer: core::pin::Pin<&mut KeepAlive>,
+        service: &S2,
+    ) -> Result<(), HttpServiceError<S2::Error, BE>>
+    where
+        Io: AsyncIo + Send + 'static,
+        S2: Service<Request<RequestExt<RequestBody>>, Response = Response<ResB>>,
+        S2::Error: fmt::Debug,
+        ResB: Stream<Item = Result<Bytes, BE>>,
+        BE: fmt::Debug,
+    {
+        use super::{
+            h1::upgrade::UpgradeKind,
+            h2c::{peek_preface, Rewind},
+        };
+
+        // client with prior knowledge sends preface right away and bytes of it must arrive within request head
+        // timeout. connection not sending any byte in time is closed like an expired http/1 connection.
+        self.update_first_request_deadline(timer.as_mut());
+        let Ok(res) = peek_preface(&mut io).timeout(timer.as_mut()).await else {
+            return Ok(());
+        };
+        let (is_preface, pre) = res.map_err(super::h1::Error::from)?;
+
+        let mut io = Rewind::new(io, pre);
+
+        if is_preface {
+            return self.serve_h2(io, addr, timer, service).await;
+        }
+
+        let upgrade = super::h1::dispatcher::run(
+            &mut io,
+            addr,
+            timer.as_mut(),
+            self.config,
+            service,
+            self.date.get(),
+            self.shed.as_ref(),
+        )
+        .await?;
+
+        let Some(upgrade) = upgrade else {
+            return Ok(());
+        };
+
+        match upgrade.kind {
+            UpgradeKind::H2c(h2c) => {
+                io.prepend(upgrade.read_buf);
+                // wait for client preface after "101 Switching Protocols" response and replay the upgrade request
+                // to http/2 dispatcher.
+                self.update_first_request_deadline(timer.as_mut());
+                let io = h2c
+                    .into_io(io)
+                    .timeout(timer.as_mut())
+                    .await
+                    .map_err(|_| HttpServiceError::Timeout(TimeoutError::H2Handshake))?
+                    .map_err(super::h1::Error::from)?;
+                self.serve_h2(io, addr, timer, service).await
+            }
+            _ => {
+                upgrade.send(io);
+                Ok(())
+            }
+        }
+    }
+
     // keep alive start with timer for `HttpServiceConfig.tls_accept_timeout`.
     // It would be re-used for all following timer operation.
     // This is an optimization for reducing heap allocation of multiple timers.
@@ -74,7 +220,7 @@ impl<S, ResB, BE, A, const HEADER_LIMIT: usize, const READ_BUF_LIMIT: usize, con
 where
     S: Service<Request<RequestExt<RequestBody>>, Response = Response<ResB>>,
     A: Service<TcpStream>,
-    A::Response: AsyncIo + AsVersion,
+    A::Response: AsyncIo + AsVersion + Send + 'static,
     HttpServiceError<S::Error, BE>: From<A::Error>,
     S::Error: fmt::Debug,
     ResB: Stream<Item = Result<Bytes, BE>>,
@@ -88,21 +234,30 @@ where
         let timer = self.keep_alive();
         let mut timer = pin!(timer);
 
+        #[cfg(feature = "metrics")]
+        let _guard = self.metrics.connection();
+        #[cfg(feature = "metrics")]
+        let service = &crate::metrics::MetricsService::new(&self.service, &self.metrics);
+        #[cfg(not(feature = "metrics"))]
+        let service = &self.service;
+
         match io {
             #[cfg(feature = "http3")]
-            ServerStream::Udp(io, addr) => super::h3::Dispatcher::new(io, addr, &self.service)
+            ServerStream::Udp(io, addr) => super::h3::Dispatcher::new(io, addr, service)
                 .run()
                 .await
                 .map_err(From::from),
             ServerStream::Tcp(io, _addr) => {
                 let io = TcpStream::from_std(io).expect("TODO: handle io error");
-                let mut _tls_stream = self
+                let _tls_stream = self
                     .tls_acceptor
                     .call(io)
                     .timeout(timer.as_mut())
                     .await
                     .map_err(|_| HttpServiceError::Timeout(TimeoutError::TlsAccept))??;
 
+                let mut _tls_stream = self.wrap_io(_tls_stream);
+
                 let version = if self.config.peek_protocol {
                     // peek version from connection to figure out the real protocol used
                     // regardless of AsVersion's outcome.
@@ -113,40 +268,31 @@ where
 
                 match version {
                     #[cfg(feature = "http1")]
-                    super::http::Version::HTTP_11 | super::http::Version::HTTP_10 => super::h1::dispatcher::run(
-                        &mut _tls_stream,
-                        _addr,
-                        timer.as_mut(),
-                        self.config,
-                        &self.service,
-                        self.date.get(),
-                    )
-                    .await
-                    .map_err(From::from),
-                    #[cfg(feature = "http2")]
-                    super::http::Version::HTTP_2 => {
-                        // update timer to first request timeout.
-                        self.update_first_request_deadline(timer.as_mut());
-
-                        let mut conn = ::h2::server::Builder::new()
-                            .enable_connect_protocol()
-                            .handshake(xitca_io::io::PollIoAdapter(_tls_stream))
-                            .timeout(timer.as_mut())
-                            .await
-                            .map_err(|_| HttpServiceError::Timeout(TimeoutError::H2Handshake))??;
-
-                        super::h2::Dispatcher::new(
-                            &mut conn,
+                    super::http::Version::HTTP_11 | super::http::Version::HTTP_10 => {
+                        #[cfg(feature = "http2")]
+                        if self.config.h2c {
+                            return self.serve_h2c(_tls_stream, _addr, timer, service).await;
+                        }
+
+                        let upgrade = super::h1::dispatcher::run(
+                            &mut _tls_stream,
                             _addr,
                             timer.as_mut(),
-                            self.config.keep_alive_timeout,
-                            &self.service,
+                            self.config,
+                            service,
                             self.date.get(),
+                            self.shed.as_ref(),
                         )
-                        .run()
-                        .await
-                        .map_err(Into::into)
+                        .await?;
+
+                        if let Some(upgrade) = upgrade {
+                            upgrade.send(_tls_stream);
+                        }
+
+                        Ok(())
                     }
+                    #[cfg(feature = "http2")]
+                    super::http::Version::HTTP_2 => self.serve_h2(_tls_stream, _addr, timer, service).await,
                     version => Err(HttpServiceError::UnSupportedVersion(version)),
                 }
             }
@@ -159,18 +305,32 @@ where
 
                 #[cfg(feature = "http1")]
                 {
-                    let mut io = xitca_io::net::UnixStream::from_std(_io).expect("TODO: handle io error");
+                    let io = xitca_io::net::UnixStream::from_std(_io).expect("TODO: handle io error");
+                    let mut io = self.wrap_io(io);
+
+                    #[cfg(feature = "http2")]
+                    if self.config.h2c {
+                        return self
+                            .serve_h2c(io, crate::unspecified_socket_addr(), timer, service)
+                            .await;
+                    }
 
-                    super::h1::dispatcher::run(
+                    let upgrade = super::h1::dispatcher::run(
                         &mut io,
                         crate::unspecified_socket_addr(),
                         timer.as_mut(),
                         self.config,
-                        &self.service,
+                        service,
                         self.date.get(),
+                        self.shed.as_ref(),
                     )
-                    .await
-                    .map_err(From::from)
+                    .await?;
+
+                    if let Some(upgrade) = upgrade {
+                        upgrade.send(io);
+                    }
+
+                    Ok(())
                 }
             }
         }
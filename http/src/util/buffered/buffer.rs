@@ -111,6 +111,12 @@ impl<const LIMIT: usize> WriteBuf<LIMIT> {
         Self(xitca_io::bytes::WriteBuf::new())
     }
 
+    /// count of bytes buffered and not written to io yet.
+    #[inline]
+    pub fn buffered_len(&self) -> usize {
+        self.0.len()
+    }
+
     #[cfg(test)]
     pub fn buf(&self) -> &[u8] {
         self.0.buf()
@@ -165,6 +171,12 @@ impl<B: Buf, const LIMIT: usize> Default for ListWriteBuf<B, LIMIT> {
 }
 
 impl<B: Buf, const LIMIT: usize> ListWriteBuf<B, LIMIT> {
+    /// count of bytes buffered and not written to io yet.
+    #[inline]
+    pub fn buffered_len(&self) -> usize {
+        self.list.remaining()
+    }
+
     /// split buf field from Self.
     /// this is often coupled with [BufWrite::write_buf] method to obtain what has been written to
     /// the buf.
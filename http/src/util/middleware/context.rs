@@ -161,7 +161,7 @@ mod service {
 mod router_impl {
     use xitca_service::object::ServiceObject;
 
-    use crate::util::service::router::{IntoObject, PathGen, RouteGen, RouteObject};
+    use crate::util::service::router::{IntoObject, PathGen, RouteGen, RouteInfo, RouteObject};
 
     use super::*;
 
@@ -187,6 +187,14 @@ mod router_impl {
                 fn path_gen(&mut self, prefix: &str) -> String {
                     self.0.path_gen(prefix)
                 }
+
+                fn path_alias(&self, prefix: &str) -> Vec<String> {
+                    self.0.path_alias(prefix)
+                }
+
+                fn route_info(&self, path: &str, routes: &mut Vec<RouteInfo>) {
+                    self.0.route_info(path, routes)
+                }
             }
 
             impl<I, Req, C> RouteGen for Builder<I, Req, C>
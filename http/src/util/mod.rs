@@ -7,5 +7,7 @@ pub mod service;
 #[cfg(any(feature = "http1", feature = "http2"))]
 pub mod buffered;
 pub(crate) mod futures;
+#[cfg(all(feature = "runtime", feature = "http1"))]
+pub(crate) mod shed;
 #[cfg(feature = "runtime")]
 pub(crate) mod timer;
@@ -158,6 +158,10 @@ from_req_impl! { A, B, C, D, E, F, G, H, I, }
 
 /// Make Response with ownership of Req.
 /// The Output type is what returns from [handler_service] function.
+///
+/// Tuple of responders is a responder itself. The first element generates response with [Responder::respond]
+/// and the rest of elements mutate it in order with [Responder::map]. For example `(StatusCode, HeaderMap, T)`
+/// would produce response from status code, then merge header map and body from `T` onto it.
 #[diagnostic::on_unimplemented(
     message = "`{Self}` does not impl Responder trait",
     label = "handler function return type must impl Responder trait",
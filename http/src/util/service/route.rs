@@ -8,7 +8,7 @@ use xitca_service::{ready::ReadyService, Service};
 
 use crate::http::{BorrowReq, Method};
 
-use super::router::RouterError;
+use super::router::{PathGen, RouteInfo, RouterError};
 
 macro_rules! method {
     ($method_fn: ident, $method: ident) => {
@@ -199,6 +199,29 @@ impl fmt::Display for MethodNotAllowed {
 
 impl error::Error for MethodNotAllowed {}
 
+impl<R, N, const M: usize> PathGen for Route<R, N, M>
+where
+    N: PathGen,
+{
+    fn route_info(&self, path: &str, routes: &mut Vec<RouteInfo>) {
+        let mut next = Vec::new();
+        self.next.route_info(path, &mut next);
+
+        let mut methods = self.methods.to_vec();
+        methods.extend(next.into_iter().flat_map(|route| route.methods));
+
+        routes.push(RouteInfo {
+            path: String::from(path),
+            methods,
+        });
+    }
+}
+
+// terminal of Route chain contributes no method.
+impl<R> PathGen for MethodNotAllowedBuilder<R> {
+    fn route_info(&self, _: &str, _: &mut Vec<RouteInfo>) {}
+}
+
 pub struct MethodNotAllowedBuilder<R>(PhantomData<fn(R)>);
 
 impl<R> MethodNotAllowedBuilder<R> {
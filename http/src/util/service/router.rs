@@ -2,11 +2,12 @@ pub use xitca_router::{params::Params, MatchError};
 
 use core::{fmt, marker::PhantomData};
 
-use std::{collections::HashMap, error};
+use std::{collections::HashMap, error, sync::Arc};
 
+use xitca_router::InsertError;
 use xitca_service::{object::BoxedServiceObject, pipeline::PipelineT, BoxFuture, FnService, Service};
 
-use crate::http::Request;
+use crate::http::{Method, Request};
 
 use super::{
     handler::HandlerService,
@@ -20,9 +21,13 @@ pub use self::object::RouteObject;
 /// An [ServiceObject](xitca_service::object::ServiceObject) must be specified as a type parameter
 /// in order to determine how the router type-erases node services.
 pub struct Router<Obj> {
-    // record for last time PathGen is called with certain route string prefix.
-    prefix: Option<usize>,
+    // accumulated route string prefix from PathGen calls of outer routers.
+    prefix: Option<String>,
     routes: HashMap<String, Obj>,
+    // additional paths routed to the same service of a route. key is alias path and value is route path.
+    aliases: HashMap<String, String>,
+    // pattern only router for validating routes eagerly when they are inserted.
+    patterns: xitca_router::Router<()>,
 }
 
 impl<Obj> Default for Router<Obj> {
@@ -36,6 +41,8 @@ impl<Obj> Router<Obj> {
         Router {
             prefix: None,
             routes: HashMap::new(),
+            aliases: HashMap::new(),
+            patterns: xitca_router::Router::new(),
         }
     }
 }
@@ -47,21 +54,44 @@ impl<Obj> Router<Obj> {
     ///
     /// # Panic:
     ///
-    /// When multiple services inserted to the same path.
+    /// - When multiple services inserted to the same path. Services of different methods on the same path must be
+    ///   combined into one [Route]. e.g. `get(a).post(b)`
+    /// - When path conflicts with a previously inserted path that router can not tell apart from it. e.g. `/:a`
+    ///   and `/:b`
+    /// - When path is not a valid route pattern.
     pub fn insert<F, Arg, Req>(mut self, path: &'static str, mut builder: F) -> Self
     where
         F: Service<Arg> + RouteGen + Send + Sync,
         F::Response: Service<Req>,
         Req: IntoObject<F::Route<F>, Arg, Object = Obj>,
     {
+        let aliases = builder.path_alias(path);
         let path = builder.path_gen(path);
-        assert!(self
-            .routes
-            .insert(path, Req::into_object(F::route_gen(builder)))
-            .is_none());
+        for alias in aliases {
+            self.insert_pattern(&alias);
+            self.aliases.insert(alias, path.clone());
+        }
+        self.insert_route(path, Req::into_object(F::route_gen(builder)));
         self
     }
 
+    /// collect information of registered routes. nested routers are flattened with their path prefix.
+    /// routes are sorted by their path.
+    pub fn routes(&self) -> Vec<RouteInfo>
+    where
+        Obj: PathGen,
+    {
+        let mut routes = Vec::new();
+        for (path, route) in self.routes.iter() {
+            route.route_info(path, &mut routes);
+        }
+        for (alias, path) in self.aliases.iter() {
+            self.routes[path].route_info(alias, &mut routes);
+        }
+        routes.sort_by(|a, b| a.path.cmp(&b.path));
+        routes
+    }
+
     #[doc(hidden)]
     /// See [TypedRoute] for detail.
     pub fn insert_typed<T, M>(mut self, _: T) -> Router<Obj>
@@ -70,9 +100,28 @@ impl<Obj> Router<Obj> {
     {
         let path = T::path();
         let route = T::route();
-        assert!(self.routes.insert(String::from(path), route).is_none());
+        self.insert_route(String::from(path), route);
         self
     }
+
+    fn insert_route(&mut self, path: String, route: Obj) {
+        self.insert_pattern(&path);
+        self.routes.insert(path, route);
+    }
+
+    fn insert_pattern(&mut self, path: &str) {
+        match self.patterns.insert(path, ()) {
+            Ok(_) => {}
+            Err(InsertError::Conflict { with }) if with == path => panic!(
+                "route {path:?} is registered more than once. services of different methods on the same path must \
+                 be combined into one route. e.g. get(a).post(b)"
+            ),
+            Err(InsertError::Conflict { with }) => {
+                panic!("route {path:?} conflicts with route {with:?} and router can not tell them apart")
+            }
+            Err(e) => panic!("route {path:?} is invalid: {e}"),
+        }
+    }
 }
 
 impl<Obj, Arg> Service<Arg> for Router<Obj>
@@ -86,18 +135,60 @@ where
     async fn call(&self, arg: Arg) -> Result<Self::Response, Self::Error> {
         let mut router = xitca_router::Router::new();
 
+        let mut services = Vec::with_capacity(self.routes.len());
+        let mut indexes = HashMap::with_capacity(self.routes.len());
+
+        let prefix = self.prefix.as_deref().unwrap_or_default();
+
         for (path, service) in self.routes.iter() {
             let service = service.call(arg.clone()).await?;
-            router.insert(path.to_string(), service).unwrap();
+            let route = MatchedRoute(Some(Arc::from(format!("{prefix}{path}"))));
+            router
+                .insert(path.to_string(), (route, services.len()))
+                .expect("route patterns are validated when they are inserted");
+            indexes.insert(path.as_str(), services.len());
+            services.push(service);
+        }
+
+        for (alias, path) in self.aliases.iter() {
+            let route = MatchedRoute(Some(Arc::from(format!("{prefix}{alias}"))));
+            router
+                .insert(alias.to_string(), (route, indexes[path.as_str()]))
+                .expect("route patterns are validated when they are inserted");
         }
 
         Ok(service::RouterService {
-            prefix: self.prefix,
+            prefix: self.prefix.as_ref().map(String::len),
             router,
+            services: services.into_boxed_slice(),
         })
     }
 }
 
+/// information of a registered route. See [Router::routes].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RouteInfo {
+    /// path pattern of route.
+    pub path: String,
+    /// allowed methods of route. empty when route does not restrict method.
+    pub methods: Vec<Method>,
+}
+
+/// path pattern of the route matched by [Router] for current request.
+///
+/// Patterns of nested routers are prefixed with the path they are nested at. For example a route registered as
+/// `/:id` inside a router nested at `/users/*` is `/users/:id`. Request not matched by any router carries none.
+#[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MatchedRoute(Option<Arc<str>>);
+
+impl MatchedRoute {
+    /// path pattern of matched route.
+    #[inline]
+    pub fn as_str(&self) -> Option<&str> {
+        self.0.as_deref()
+    }
+}
+
 /// Error type of Router service.
 pub enum RouterError<E> {
     /// failed to match on a routed service.
@@ -149,6 +240,25 @@ pub trait PathGen {
     fn path_gen(&mut self, prefix: &str) -> String {
         String::from(prefix)
     }
+
+    /// additional paths registered for the route besides the one produced by [PathGen::path_gen]. requests matching
+    /// any of them are routed to the same service.
+    ///
+    /// default to no additional path.
+    fn path_alias(&self, prefix: &str) -> Vec<String> {
+        let _ = prefix;
+        Vec::new()
+    }
+
+    /// collect information of route(s) registered with given path.
+    ///
+    /// default to a single route with no method restriction.
+    fn route_info(&self, path: &str, routes: &mut Vec<RouteInfo>) {
+        routes.push(RouteInfo {
+            path: String::from(path),
+            methods: Vec::new(),
+        });
+    }
 }
 
 /// trait for specialized route generation when utilizing [Router::insert].
@@ -182,8 +292,7 @@ where
             path.pop();
         }
 
-        let prefix = self.prefix.get_or_insert(0);
-        *prefix += path.len();
+        self.prefix.get_or_insert_with(String::new).insert_str(0, &path);
 
         self.routes.iter_mut().for_each(|(_, v)| {
             v.path_gen(path.as_str());
@@ -193,6 +302,16 @@ where
 
         path
     }
+
+    fn route_info(&self, path: &str, routes: &mut Vec<RouteInfo>) {
+        let prefix = path.strip_suffix("/*").unwrap_or(path);
+        for (path, route) in self.routes.iter() {
+            route.route_info(&format!("{prefix}{path}"), routes);
+        }
+        for (alias, path) in self.aliases.iter() {
+            self.routes[path].route_info(&format!("{prefix}{alias}"), routes);
+        }
+    }
 }
 
 impl<Obj> RouteGen for Router<Obj>
@@ -206,9 +325,10 @@ where
     }
 }
 
-impl<R, N, const M: usize> PathGen for Route<R, N, M> {}
-
-impl<R, N, const M: usize> RouteGen for Route<R, N, M> {
+impl<R, N, const M: usize> RouteGen for Route<R, N, M>
+where
+    N: PathGen,
+{
     type Route<R1> = R1;
 
     fn route_gen<R1>(route: R1) -> Self::Route<R1> {
@@ -243,6 +363,14 @@ where
     fn path_gen(&mut self, prefix: &str) -> String {
         self.first.path_gen(prefix)
     }
+
+    fn path_alias(&self, prefix: &str) -> Vec<String> {
+        self.first.path_alias(prefix)
+    }
+
+    fn route_info(&self, path: &str, routes: &mut Vec<RouteInfo>) {
+        self.first.route_info(path, routes)
+    }
 }
 
 impl<F, S, M> RouteGen for PipelineT<F, S, M>
@@ -266,6 +394,14 @@ where
     fn path_gen(&mut self, prefix: &str) -> String {
         self.0.path_gen(prefix)
     }
+
+    fn path_alias(&self, prefix: &str) -> Vec<String> {
+        self.0.path_alias(prefix)
+    }
+
+    fn route_info(&self, path: &str, routes: &mut Vec<RouteInfo>) {
+        self.0.route_info(path, routes)
+    }
 }
 
 impl<S> RouteGen for RouterMapErr<S>
@@ -342,6 +478,14 @@ mod object {
         fn path_gen(&mut self, prefix: &str) -> String {
             self.0.path_gen(prefix)
         }
+
+        fn path_alias(&self, prefix: &str) -> Vec<String> {
+            self.0.path_alias(prefix)
+        }
+
+        fn route_info(&self, path: &str, routes: &mut Vec<RouteInfo>) {
+            self.0.route_info(path, routes)
+        }
     }
 
     impl<Arg, S, E> RouteGen for RouteObject<Arg, S, E> {
@@ -380,6 +524,14 @@ where
             fn path_gen(&mut self, prefix: &str) -> String {
                 self.0.path_gen(prefix)
             }
+
+            fn path_alias(&self, prefix: &str) -> Vec<String> {
+                self.0.path_alias(prefix)
+            }
+
+            fn route_info(&self, path: &str, routes: &mut Vec<RouteInfo>) {
+                self.0.route_info(path, routes)
+            }
         }
 
         impl<T, Req> RouteGen for Builder<T, Req>
@@ -430,19 +582,21 @@ mod service {
 
     use crate::http::{BorrowReq, BorrowReqMut, Uri};
 
-    use super::{Params, RouterError, Service};
+    use super::{MatchedRoute, Params, RouterError, Service};
 
     pub struct RouterService<S> {
         // a length record of prefix of current router.
         // when it's Some the request path has to be sliced to exclude the string path prefix.
         pub(super) prefix: Option<usize>,
-        pub(super) router: xitca_router::Router<S>,
+        pub(super) router: xitca_router::Router<(MatchedRoute, usize)>,
+        // services indexed by router. multiple paths can share one service.
+        pub(super) services: Box<[S]>,
     }
 
     impl<S, Req, E> Service<Req> for RouterService<S>
     where
         S: Service<Req, Error = RouterError<E>>,
-        Req: BorrowReq<Uri> + BorrowReqMut<Params>,
+        Req: BorrowReq<Uri> + BorrowReqMut<Params> + BorrowReqMut<MatchedRoute>,
     {
         type Response = S::Response;
         type Error = S::Error;
@@ -459,9 +613,15 @@ mod service {
                     path = &path[prefix..];
                 }
 
-                let xitca_router::Match { value, params } = self.router.at(path).map_err(RouterError::Match)?;
-                *req.borrow_mut() = params;
-                Service::call(value, req).await
+                let xitca_router::Match {
+                    value: (route, idx),
+                    params,
+                } = self.router.at(path).map_err(RouterError::Match)?;
+                let service = &self.services[*idx];
+                *BorrowReqMut::<Params>::borrow_mut(&mut req) = params;
+                // nested router is called after outer one and overwrites it with a more specific pattern.
+                *BorrowReqMut::<MatchedRoute>::borrow_mut(&mut req) = route.clone();
+                Service::call(service, req).await
             }
         }
     }
@@ -521,6 +681,22 @@ mod test {
         bound_check(Router::new().insert("/", fn_service(func)))
     }
 
+    #[test]
+    #[should_panic(expected = "is registered more than once")]
+    fn router_duplicate_route() {
+        let _ = Router::new()
+            .insert("/", fn_service(func))
+            .insert("/", fn_service(func));
+    }
+
+    #[test]
+    #[should_panic(expected = "conflicts with route \"/users/:id\"")]
+    fn router_ambiguous_route() {
+        let _ = Router::new()
+            .insert("/users/:id", fn_service(func))
+            .insert("/users/:name", fn_service(func));
+    }
+
     #[test]
     fn router_accept_request() {
         Router::new()
@@ -637,6 +813,33 @@ mod test {
             .unwrap();
     }
 
+    #[test]
+    fn router_matched_route() {
+        let handler = |route: &'static str| {
+            fn_service(move |req: Request<RequestExt<()>>| async move {
+                assert_eq!(req.body().matched_route().as_str(), Some(route));
+                Ok::<_, Infallible>(Response::new(()))
+            })
+        };
+
+        let service = Router::new()
+            .insert("/users/:id", handler("/users/:id"))
+            .insert(
+                "/api",
+                Router::new().insert("/v2", Router::new().insert("/items/:id", handler("/api/v2/items/:id"))),
+            )
+            .call(())
+            .now_or_panic()
+            .unwrap();
+
+        for uri in ["/users/1", "/api/v2/items/2"] {
+            let req = Request::builder().uri(uri).body(Default::default()).unwrap();
+            service.call(req).now_or_panic().unwrap();
+        }
+
+        assert!(RequestExt::<()>::default().matched_route().as_str().is_none());
+    }
+
     #[test]
     fn router_service_call_size() {
         let service = Router::new()
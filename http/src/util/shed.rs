@@ -0,0 +1,127 @@
+//! per worker load shedding driven by bytes buffered by connections.
+
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+/// load shedding state shared by all connections served by one http service instance(one per worker thread).
+///
+/// Shedding starts when buffered bytes exceed high water mark and stops when they drop to low water mark or
+/// below. State only changes on buffered bytes update so the outcome is deterministic for given traffic.
+///
+/// State is only accessed from one worker thread. atomics are used so reference of it is [Send] and can be held by
+/// connection context whose future must be [Send].
+pub(crate) struct LoadShed {
+    high: usize,
+    low: usize,
+    used: AtomicUsize,
+    shedding: AtomicBool,
+    #[cfg(feature = "metrics")]
+    metrics: Option<crate::metrics::HttpMetrics>,
+}
+
+impl LoadShed {
+    pub(crate) fn new(high: usize, low: usize) -> Self {
+        Self {
+            high,
+            low,
+            used: AtomicUsize::new(0),
+            shedding: AtomicBool::new(false),
+            #[cfg(feature = "metrics")]
+            metrics: None,
+        }
+    }
+
+    #[cfg(feature = "metrics")]
+    pub(crate) fn set_metrics(&mut self, metrics: crate::metrics::HttpMetrics) {
+        self.metrics = Some(metrics);
+    }
+
+    /// construct a tracker for a new connection.
+    pub(crate) fn tracker(&self) -> Tracker<'_> {
+        Tracker { shed: self, tracked: 0 }
+    }
+
+    /// check if a new request should be shed. shed count of metrics is increased when returning true.
+    pub(crate) fn try_shed(&self) -> bool {
+        let shedding = self.shedding.load(Ordering::Relaxed);
+        #[cfg(feature = "metrics")]
+        if shedding {
+            if let Some(ref metrics) = self.metrics {
+                metrics.add_shed();
+            }
+        }
+        shedding
+    }
+
+    fn update(&self, old: usize, new: usize) {
+        let used = self.used.load(Ordering::Relaxed) - old + new;
+        self.used.store(used, Ordering::Relaxed);
+        if used > self.high {
+            self.shedding.store(true, Ordering::Relaxed);
+        } else if used <= self.low {
+            self.shedding.store(false, Ordering::Relaxed);
+        }
+    }
+}
+
+/// per connection handle reporting it's buffered bytes to [LoadShed]. reported bytes are removed on drop.
+pub(crate) struct Tracker<'a> {
+    shed: &'a LoadShed,
+    tracked: usize,
+}
+
+impl Tracker<'_> {
+    /// update buffered bytes of connection.
+    pub(crate) fn track(&mut self, len: usize) {
+        if len != self.tracked {
+            self.shed.update(self.tracked, len);
+            self.tracked = len;
+        }
+    }
+
+    pub(crate) fn try_shed(&self) -> bool {
+        self.shed.try_shed()
+    }
+}
+
+impl Drop for Tracker<'_> {
+    fn drop(&mut self) {
+        self.shed.update(self.tracked, 0);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn water_mark() {
+        let shed = LoadShed::new(100, 50);
+
+        let mut t1 = shed.tracker();
+        let mut t2 = shed.tracker();
+
+        t1.track(60);
+        t2.track(40);
+        assert!(!t1.try_shed());
+
+        // exceed high water mark.
+        t2.track(41);
+        assert!(t1.try_shed());
+
+        // between water marks. keep shedding.
+        t1.track(20);
+        assert!(t2.try_shed());
+
+        // drop to low water mark.
+        drop(t1);
+        t2.track(50);
+        assert!(!t2.try_shed());
+
+        // between water marks. keep serving.
+        t2.track(100);
+        assert!(!t2.try_shed());
+
+        drop(t2);
+        assert_eq!(shed.used.load(Ordering::Relaxed), 0);
+    }
+}
@@ -2,9 +2,14 @@ use core::future::Future;
 
 use std::{
     collections::HashMap,
-    sync::{Arc, Mutex},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::Instant,
 };
 
+use postgres_protocol::message::{backend, frontend};
 use xitca_io::bytes::BytesMut;
 use xitca_unsafe_collection::no_hash::NoHashBuilder;
 
@@ -13,17 +18,19 @@ use super::{
     driver::{
         codec::{
             encode::{self, Encode},
-            Response,
+            AsParams, Response,
         },
         DriverTx,
     },
-    error::Error,
+    error::{BatchError, DbError, DeadlineExceeded, DriverDown, Error},
+    execute::Execute,
     prepare::Prepare,
-    query::Query,
+    query::{Query, RowStreamOwned},
     session::Session,
     statement::Statement,
-    transaction::Transaction,
+    transaction::{Transaction, TransactionStatus},
     types::{Oid, Type},
+    BoxedFuture,
 };
 
 /// a marker trait to confirm a mut reference of Client can be borrowed from self.
@@ -104,6 +111,7 @@ pub struct Client {
 pub(crate) struct ClientCache {
     session: Session,
     type_info: Mutex<CachedTypeInfo>,
+    poisoned: AtomicBool,
 }
 
 /// A cache of type info and prepared statements for fetching type info
@@ -120,8 +128,13 @@ struct CachedTypeInfo {
     /// Corresponds to [TYPEINFO_QUERY](prepare::TYPEINFO_COMPOSITE_QUERY) (or
     /// its fallback).
     typeinfo_enum: Option<Statement>,
+    /// A statement for looking up oid of a type from its name.
+    /// Corresponds to [TYPEINFO_NAME_QUERY](prepare::TYPEINFO_NAME_QUERY).
+    typeinfo_name: Option<Statement>,
     /// Cache of types already looked up.
     types: HashMap<Oid, Type, NoHashBuilder>,
+    /// Cache of types already looked up by name.
+    named_types: HashMap<String, Type>,
 }
 
 impl Client {
@@ -131,6 +144,169 @@ impl Client {
         Transaction::<Self>::builder().begin(self)
     }
 
+    /// run given closure inside a transaction and retry it on serialization failure and deadlock.
+    ///
+    /// See [`TransactionBuilder::retry`] for detail and how to run it with different isolation level.
+    ///
+    /// [`TransactionBuilder::retry`]: crate::transaction::TransactionBuilder::retry
+    #[inline]
+    pub async fn transaction_retry<F, T>(&mut self, func: F) -> Result<T, Error>
+    where
+        F: for<'t, 'c> FnMut(&'t mut Transaction<'c, Self>) -> BoxedFuture<'t, Result<T, Error>>,
+    {
+        Transaction::<Self>::builder().retry(self, func).await
+    }
+
+    /// prepare sql string with parameter types inferred by database, bind it to given parameter values and query it
+    /// in one call. the prepared statement is closed after query.
+    ///
+    /// # Performance
+    /// every call prepares the statement again which costs an additional round trip to database and parsing/planning
+    /// of sql on database side. when the same sql is queried repeatedly(e.g. in a loop) prefer preparing a
+    /// [`Statement`] once and reuse it with [`Statement::bind`]. [`PoolConnection`] can be used to cache prepared
+    /// statements for the lifetime of connection. when parameter types are known ahead [`Statement::unnamed`] saves
+    /// the round trip of preparing.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use xitca_postgres::{iter::AsyncLendingIterator, Client, Error};
+    /// # async fn query(cli: Client) -> Result<(), Error> {
+    /// let mut stream = cli.query_once("SELECT id, name FROM users WHERE id = $1", [9527_i32]).await?;
+    /// while let Some(row) = stream.try_next().await? {
+    ///     let _: &str = row.get("name");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`PoolConnection`]: crate::pool::PoolConnection
+    pub async fn query_once<P>(&self, sql: &str, params: P) -> Result<RowStreamOwned, Error>
+    where
+        P: AsParams,
+    {
+        let stmt = Statement::named(sql, &[]).execute(self).await?;
+        stmt.bind(params).query(self).await.map(RowStreamOwned::from)
+    }
+
+    /// [`Client::query_once`] for executing sql and return the number of rows affected.
+    ///
+    /// See [`Client::query_once`] for performance difference with reusing a prepared [`Statement`].
+    pub async fn execute_once<P>(&self, sql: &str, params: P) -> Result<u64, Error>
+    where
+        P: AsParams,
+    {
+        let stmt = Statement::named(sql, &[]).execute(self).await?;
+        stmt.bind(params).execute(self).await
+    }
+
+    /// execute sql string containing zero or more semicolon separated statements with simple query protocol.
+    /// Useful for running schema migrations or setup scripts. statements can't have parameters and rows returned
+    /// by them are discarded.
+    ///
+    /// unless sql string contains explicit transaction control statements database runs all statements in one
+    /// implicit transaction. statements executed before a failed one are rolled back together with it.
+    ///
+    /// # Errors
+    /// When a statement is failed by database a [`BatchError`] is returned with the position of failed statement
+    /// in sql string. remaining responses are consumed before returning so the connection stays usable.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use xitca_postgres::{Client, Error};
+    /// # async fn migrate(cli: Client) -> Result<(), Error> {
+    /// cli.batch_execute(
+    ///     "CREATE TABLE users (id INT PRIMARY KEY, name TEXT NOT NULL);
+    ///      CREATE INDEX users_name ON users (name);",
+    /// )
+    /// .await
+    /// # }
+    /// ```
+    pub async fn batch_execute(&self, sql: &str) -> Result<(), Error> {
+        let (_, mut res) = self.tx.send(|buf| frontend::query(sql, buf).map_err(Error::from), 1)?;
+
+        let mut index = 0;
+        let mut err = None;
+
+        loop {
+            match res.recv().await {
+                Ok(backend::Message::CommandComplete(_)) => index += 1,
+                Ok(
+                    backend::Message::RowDescription(_)
+                    | backend::Message::DataRow(_)
+                    | backend::Message::EmptyQueryResponse,
+                ) => {}
+                Ok(backend::Message::ReadyForQuery(_)) => return err.map_or(Ok(()), Err),
+                // keep draining till ReadyForQuery so the response of next request is not polluted.
+                Ok(_) => {
+                    err.get_or_insert_with(Error::unexpected);
+                }
+                // database skips remaining statements after error and keep on sending messages till ReadyForQuery.
+                Err(e) if e.is::<DbError>() && err.is_none() => err = Some(BatchError::wrap(index, e)),
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// query statement with given parameter values and cap it's execution time on database side by remaining time
+    /// of given deadline. e.g. the time budget of a http request the query is serving.
+    ///
+    /// The query runs inside a transaction with `SET LOCAL statement_timeout` set to the remaining time in
+    /// milliseconds(rounded up). database cancels the query once it goes beyond the timeout and the row stream
+    /// yields a [`DbError`] with [`SqlState::QUERY_CANCELED`]. The timeout is scoped to the transaction and does
+    /// not leak to other queries on the same connection.
+    ///
+    /// # Errors
+    /// When deadline has already passed [`DeadlineExceeded`] is returned without sending anything to database.
+    ///
+    /// # Memory
+    /// The transaction is committed before the row stream is returned, therefore all rows of query are buffered
+    /// in memory before they can be consumed from stream.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use std::time::{Duration, Instant};
+    /// # use xitca_postgres::{iter::AsyncLendingIterator, statement::Statement, types::Type, Client, Error, Execute};
+    /// # async fn query(mut cli: Client) -> Result<(), Error> {
+    /// // leak statement from it's guard so it does not borrow client which is mutably borrowed by query.
+    /// let stmt = Statement::named("SELECT name FROM users WHERE id = $1", &[Type::INT4])
+    ///     .execute(&cli)
+    ///     .await?
+    ///     .leak();
+    /// // the query can not outlive the time budget of current request.
+    /// let deadline = Instant::now() + Duration::from_secs(3);
+    /// let mut stream = cli.query_deadline(&stmt, [9527_i32], deadline).await?;
+    /// while let Some(row) = stream.try_next().await? {
+    ///     let _: &str = row.get("name");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`DbError`]: crate::error::DbError
+    /// [`SqlState::QUERY_CANCELED`]: crate::error::SqlState::QUERY_CANCELED
+    pub async fn query_deadline<P>(
+        &mut self,
+        stmt: &Statement,
+        params: P,
+        deadline: Instant,
+    ) -> Result<RowStreamOwned, Error>
+    where
+        P: AsParams,
+    {
+        let millis = deadline_millis(deadline)?;
+
+        let txn = self.transaction().await?;
+
+        // setting timeout and query are pipelined. when setting fails the query is rejected by database too.
+        let set = format!("SET LOCAL statement_timeout = {millis}").as_str().execute(&txn);
+        let stream = stmt.bind(params).query(&txn).await.map(RowStreamOwned::from)?;
+        set.await?;
+
+        txn.commit().await?;
+
+        Ok(stream)
+    }
+
     /// Executes a `COPY FROM STDIN` statement, returning a sink used to write the copy data.
     ///
     /// PostgreSQL does not support parameters in `COPY` statements, so this method does not take any. The copy *must*
@@ -156,8 +332,79 @@ impl Client {
 
     /// a lossy hint of running state of io driver. an io driver shutdown can happen
     /// at the same time this api is called.
+    ///
+    /// poisoned client is treated as closed. See [Client::poison] for detail.
     pub fn closed(&self) -> bool {
-        self.tx.is_closed()
+        self.is_poisoned() || self.tx.is_closed()
+    }
+
+    /// mark client as poisoned. poisoned client is treated as closed and connection pool discards it instead of
+    /// handing it out again. queries can still be sent with it but their outcome is undefined.
+    ///
+    /// it's used when connection is suspected to be out of sync with database. e.g. after
+    /// [`Error::is_unexpected`] error is produced by a query.
+    pub fn poison(&self) {
+        self.cache.poisoned.store(true, Ordering::Relaxed);
+    }
+
+    /// check if client is poisoned by [Client::poison] or failed [Client::recover].
+    pub fn is_poisoned(&self) -> bool {
+        self.cache.poisoned.load(Ordering::Relaxed)
+    }
+
+    /// attempt to recover connection from protocol error. e.g. [`Error::is_unexpected`] error produced mid stream.
+    ///
+    /// remaining messages of dropped responses are drained by io driver. this method sends a sync message and
+    /// waits for database to respond with matching `ReadyForQuery` message. any other response means connection
+    /// is out of sync and the client is poisoned. See [Client::poison] for detail.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use xitca_postgres::{Client, Error, Execute};
+    /// # async fn recover(cli: Client) -> Result<(), Error> {
+    /// if let Err(e) = "SELECT 1".execute(&cli).await {
+    ///     if e.is_unexpected() {
+    ///         // try to bring connection back in sync. on failure the client is poisoned and closed.
+    ///         cli.recover().await?;
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn recover(&self) -> Result<(), Error> {
+        if self.closed() {
+            return Err(DriverDown.into());
+        }
+
+        let res = self.sync().await;
+        if res.is_err() {
+            self.poison();
+        }
+        res
+    }
+
+    async fn sync(&self) -> Result<(), Error> {
+        let (_, mut res) = self.tx.send(
+            |buf| {
+                frontend::sync(buf);
+                Ok(())
+            },
+            1,
+        )?;
+
+        match res.recv().await? {
+            backend::Message::ReadyForQuery(_) => Ok(()),
+            _ => Err(Error::unexpected()),
+        }
+    }
+
+    /// transaction status of connection reported by database at the last sync point driver received.
+    ///
+    /// when queries are in flight the status can be behind the actual state of connection. It's suggested to check
+    /// it after responses of queries are fully consumed. [TransactionStatus::Failed] indicates a `ROLLBACK` is
+    /// needed before the connection can be used for new queries.
+    pub fn transaction_status(&self) -> TransactionStatus {
+        self.tx.transaction_status()
     }
 
     pub fn typeinfo(&self) -> Option<Statement> {
@@ -202,6 +449,20 @@ impl Client {
         self.cache.type_info.lock().unwrap().typeinfo_enum = Some(statement.duplicate());
     }
 
+    pub(crate) fn typeinfo_name(&self) -> Option<Statement> {
+        self.cache
+            .type_info
+            .lock()
+            .unwrap()
+            .typeinfo_name
+            .as_ref()
+            .map(Statement::duplicate)
+    }
+
+    pub(crate) fn set_typeinfo_name(&self, statement: &Statement) {
+        self.cache.type_info.lock().unwrap().typeinfo_name = Some(statement.duplicate());
+    }
+
     pub fn type_(&self, oid: Oid) -> Option<Type> {
         self.cache.type_info.lock().unwrap().types.get(&oid).cloned()
     }
@@ -216,7 +477,59 @@ impl Client {
     /// them for the lifetime of the client. If those definitions are changed in the database, this method can be used
     /// to flush the local cache and allow the new, updated definitions to be loaded.
     pub fn clear_type_cache(&self) {
-        self.cache.type_info.lock().unwrap().types.clear();
+        let mut cache = self.cache.type_info.lock().unwrap();
+        cache.types.clear();
+        cache.named_types.clear();
+    }
+
+    /// Looks up a type by it's name and caches it for the lifetime of the client.
+    ///
+    /// Name is resolved by database with `search_path` of the connection and it can be schema qualified
+    /// (`"public.mood"` for example). Returned [Type] carries the oid and definition (labels of enum, base type of
+    /// domain etc) of user-defined type and it can be used as parameter type of [Statement::named]. Rust type can
+    /// be mapped to it with `ToSql` and `FromSql` derive macros from `postgres-derive` crate.
+    ///
+    /// See [Client::clear_type_cache] for flushing the cache.
+    ///
+    /// # Errors
+    /// When type does not exist [UnknownType] error is returned.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use xitca_postgres::{Client, Error, Execute, Statement};
+    /// # async fn lookup(cli: Client) -> Result<(), Error> {
+    /// // CREATE TYPE mood AS ENUM ('sad', 'ok', 'happy');
+    /// let mood = cli.type_by_name("mood").await?;
+    /// let stmt = Statement::named("SELECT $1", &[mood]).execute(&cli).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [UnknownType]: crate::error::UnknownType
+    pub async fn type_by_name(&self, name: &str) -> Result<Type, Error> {
+        if let Some(ty) = self.cache.type_info.lock().unwrap().named_types.get(name) {
+            return Ok(ty.clone());
+        }
+        let ty = self.get_type_by_name(name).await?;
+        self.cache
+            .type_info
+            .lock()
+            .unwrap()
+            .named_types
+            .insert(name.into(), ty.clone());
+        Ok(ty)
+    }
+
+    /// Looks up multiple types by their names. See [Client::type_by_name] for detail.
+    ///
+    /// This is useful right after connecting for loading all custom types used by application upfront and failing
+    /// early when any of them is missing from database.
+    pub async fn load_types(&self, names: &[&str]) -> Result<Vec<Type>, Error> {
+        let mut types = Vec::with_capacity(names.len());
+        for name in names {
+            types.push(self.type_by_name(name).await?);
+        }
+        Ok(types)
     }
 
     pub(crate) fn new(tx: DriverTx, session: Session) -> Self {
@@ -228,13 +541,27 @@ impl Client {
                     typeinfo: None,
                     typeinfo_composite: None,
                     typeinfo_enum: None,
+                    typeinfo_name: None,
                     types: HashMap::default(),
+                    named_types: HashMap::new(),
                 }),
+                poisoned: AtomicBool::new(false),
             }),
         }
     }
 }
 
+// remaining time of deadline in milliseconds. statement_timeout of 0 disables timeout and remaining time is
+// rounded up so sub millisecond budget is not turned into no timeout. statement_timeout is an int parameter and
+// remaining time is clamped to i32::MAX so a far away deadline does not get rejected by database.
+fn deadline_millis(deadline: Instant) -> Result<i32, DeadlineExceeded> {
+    deadline
+        .checked_duration_since(Instant::now())
+        .filter(|dur| !dur.is_zero())
+        .map(|dur| i32::try_from(dur.as_nanos().div_ceil(1_000_000)).unwrap_or(i32::MAX))
+        .ok_or(DeadlineExceeded)
+}
+
 impl ClientBorrowMut for Client {
     #[inline]
     fn _borrow_mut(&mut self) -> &mut Client {
@@ -288,12 +615,13 @@ impl Drop for Client {
     fn drop(&mut self) {
         // convert leaked statements to guarded statements.
         // this is to cancel the statement on client go away.
-        let (type_info, typeinfo_composite, typeinfo_enum) = {
+        let (type_info, typeinfo_composite, typeinfo_enum, typeinfo_name) = {
             let cache = self.cache.type_info.get_mut().unwrap();
             (
                 cache.typeinfo.take(),
                 cache.typeinfo_composite.take(),
                 cache.typeinfo_enum.take(),
+                cache.typeinfo_name.take(),
             )
         };
 
@@ -308,5 +636,27 @@ impl Drop for Client {
         if let Some(stmt) = typeinfo_enum {
             drop(stmt.into_guarded(&*self));
         }
+
+        if let Some(stmt) = typeinfo_name {
+            drop(stmt.into_guarded(&*self));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use core::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn deadline_millis_clamp() {
+        let now = Instant::now();
+        assert!(deadline_millis(now).is_err());
+        assert!(deadline_millis(now + Duration::from_secs(1)).unwrap() <= 1000);
+        assert_eq!(
+            deadline_millis(now + Duration::from_secs(60 * 60 * 24 * 365)).unwrap(),
+            i32::MAX
+        );
     }
 }
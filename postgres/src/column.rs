@@ -1,18 +1,24 @@
 use core::fmt;
 
+use postgres_protocol::message::backend::Field;
+
 use super::types::Type;
 
 /// Information about a column of a query.
 #[derive(Clone)]
 pub struct Column {
     name: Box<str>,
+    table_oid: Option<u32>,
+    column_id: Option<i16>,
     r#type: Type,
 }
 
 impl Column {
-    pub(crate) fn new(name: &str, r#type: Type) -> Column {
+    pub(crate) fn new(field: &Field<'_>, r#type: Type) -> Column {
         Column {
-            name: Box::from(name),
+            name: Box::from(field.name()),
+            table_oid: Some(field.table_oid()).filter(|n| *n != 0),
+            column_id: Some(field.column_id()).filter(|n| *n != 0),
             r#type,
         }
     }
@@ -23,17 +29,39 @@ impl Column {
         &self.name
     }
 
+    /// Returns the OID of the underlying database table when the column can be identified as a column of
+    /// a specific table.
+    #[inline]
+    pub fn table_oid(&self) -> Option<u32> {
+        self.table_oid
+    }
+
+    /// Returns the attribute number of the column in it's table when the column can be identified as a
+    /// column of a specific table.
+    #[inline]
+    pub fn column_id(&self) -> Option<i16> {
+        self.column_id
+    }
+
     /// Returns the type of the column.
     #[inline]
     pub fn r#type(&self) -> &Type {
         &self.r#type
     }
+
+    /// Returns the OID of the column's type.
+    #[inline]
+    pub fn type_oid(&self) -> u32 {
+        self.r#type.oid()
+    }
 }
 
 impl fmt::Debug for Column {
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
         fmt.debug_struct("Column")
             .field("name", &self.name)
+            .field("table_oid", &self.table_oid)
+            .field("column_id", &self.column_id)
             .field("type", &self.r#type)
             .finish()
     }
@@ -4,7 +4,11 @@ use postgres_protocol::message::{backend, frontend};
 use xitca_io::bytes::{Buf, Bytes, BytesMut};
 
 use super::{
-    client::ClientBorrowMut, driver::codec::Response, error::Error, iter::AsyncLendingIterator, query::Query,
+    client::ClientBorrowMut,
+    driver::codec::Response,
+    error::Error,
+    iter::{AsyncLendingItem, AsyncLendingIterator},
+    query::Query,
     statement::Statement,
 };
 
@@ -136,3 +140,12 @@ impl AsyncLendingIterator for CopyOut {
         }
     }
 }
+
+impl AsyncLendingItem<'_> for CopyOut {
+    type Item = Bytes;
+
+    #[inline]
+    fn lend(bytes: Bytes) -> Bytes {
+        bytes
+    }
+}
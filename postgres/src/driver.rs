@@ -5,7 +5,7 @@ pub(crate) mod generic;
 
 mod connect;
 
-pub(crate) use generic::DriverTx;
+pub(crate) use generic::{DriverTx, WeakDriverTx};
 
 #[cfg(feature = "tls")]
 mod tls;
@@ -32,7 +32,7 @@ use super::{
     client::Client,
     config::{Config, SslMode, SslNegotiation},
     error::{unexpected_eof_err, ConfigError, Error},
-    iter::AsyncLendingIterator,
+    iter::{AsyncLendingItem, AsyncLendingIterator},
     session::{ConnectInfo, Session},
 };
 
@@ -53,10 +53,19 @@ pub(super) async fn connect(cfg: &mut Config) -> Result<(Client, Driver), Error>
         return Err(ConfigError::EmptyPort.into());
     }
 
+    let timeout = cfg.get_connect_timeout().copied();
+
     let mut err = None;
     let hosts = cfg.get_hosts().to_vec();
     for host in hosts {
-        match self::connect::connect_host(host, cfg).await {
+        let fut = self::connect::connect_host(host, cfg);
+        let res = match timeout {
+            Some(dur) => tokio::time::timeout(dur, fut)
+                .await
+                .unwrap_or_else(|_| Err(connect_timeout_err().into())),
+            None => fut.await,
+        };
+        match res {
             Ok((tx, session, drv)) => return Ok((Client::new(tx, session), drv)),
             Err(e) => err = Some(e),
         }
@@ -65,6 +74,12 @@ pub(super) async fn connect(cfg: &mut Config) -> Result<(Client, Driver), Error>
     Err(err.unwrap())
 }
 
+#[cold]
+#[inline(never)]
+fn connect_timeout_err() -> io::Error {
+    io::Error::new(io::ErrorKind::TimedOut, "connecting to database host timed out")
+}
+
 pub(super) async fn connect_io<Io>(io: Io, cfg: &mut Config) -> Result<(Client, Driver), Error>
 where
     Io: AsyncIo + Send + 'static,
@@ -129,7 +144,7 @@ where
         SslMode::Disable => Ok(false),
         _ if matches!(ssl_negotiation, SslNegotiation::Direct) => Ok(true),
         mode => match (query_tls_availability(io).await?, mode) {
-            (false, SslMode::Require) => Err(Error::todo()),
+            (false, SslMode::Require | SslMode::VerifyCa | SslMode::VerifyFull) => Err(Error::todo()),
             (bool, _) => Ok(bool),
         },
     }
@@ -249,6 +264,15 @@ impl AsyncLendingIterator for Driver {
     }
 }
 
+impl AsyncLendingItem<'_> for Driver {
+    type Item = backend::Message;
+
+    #[inline]
+    fn lend(msg: backend::Message) -> backend::Message {
+        msg
+    }
+}
+
 impl IntoFuture for Driver {
     type Output = Result<(), Error>;
     type IntoFuture = Pin<Box<dyn Future<Output = Self::Output> + Send>>;
@@ -15,7 +15,7 @@ use crate::{
     types::BorrowToSql,
 };
 
-use super::DriverTx;
+use super::{DriverTx, WeakDriverTx};
 
 pub(super) fn request_pair(msg_count: usize) -> (ResponseSender, Response) {
     let (tx, rx) = unbounded_channel();
@@ -25,6 +25,7 @@ pub(super) fn request_pair(msg_count: usize) -> (ResponseSender, Response) {
             rx,
             buf: BytesMut::new(),
             complete: false,
+            driver: None,
         },
     )
 }
@@ -34,9 +35,15 @@ pub struct Response {
     rx: ResponseReceiver,
     buf: BytesMut,
     complete: bool,
+    // handle for sending message to driver after response is consumed. See Encode::DRIVER_HANDLE.
+    driver: Option<WeakDriverTx>,
 }
 
 impl Response {
+    pub(crate) fn take_driver(&mut self) -> Option<WeakDriverTx> {
+        self.driver.take()
+    }
+
     pub(crate) fn blocking_recv(&mut self) -> Result<backend::Message, Error> {
         if self.buf.is_empty() {
             let res = self.rx.blocking_recv();
@@ -174,6 +181,8 @@ pub(super) type ResponseReceiver = UnboundedReceiver<BytesMessage>;
 pub(super) struct BytesMessage {
     buf: BytesMut,
     complete: bool,
+    // transaction status byte of ReadyForQuery message when message is complete.
+    pub(super) status: u8,
 }
 
 impl BytesMessage {
@@ -197,6 +206,7 @@ impl ResponseMessage {
     pub(crate) fn try_from_buf(buf: &mut BytesMut) -> Result<Option<Self>, Error> {
         let mut tail = 0;
         let mut complete = false;
+        let mut status = 0;
 
         loop {
             let slice = &buf[tail..];
@@ -222,6 +232,7 @@ impl ResponseMessage {
                     tail += len;
                     if matches!(tag, backend::READY_FOR_QUERY_TAG) {
                         complete = true;
+                        status = slice[len - 1];
                         break;
                     }
                 }
@@ -234,6 +245,7 @@ impl ResponseMessage {
             Ok(Some(ResponseMessage::Normal(BytesMessage {
                 buf: buf.split_to(tail),
                 complete,
+                status,
             })))
         }
     }
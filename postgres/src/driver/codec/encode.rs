@@ -42,6 +42,13 @@ pub trait Encode: sealed::Sealed + Sized {
     fn count_hint(&self) -> usize {
         1
     }
+
+    #[doc(hidden)]
+    /// when true the [`Response`] of this encode type carries a weak handle to [`Driver`] so the output can
+    /// talk to it after response is consumed. e.g. closing prepared statement when it's dropped.
+    ///
+    /// [`Driver`]: crate::driver::Driver
+    const DRIVER_HANDLE: bool = false;
 }
 
 impl sealed::Sealed for &str {}
@@ -80,6 +87,8 @@ where
 {
     type Output = StatementCreateResponse<'c, C>;
 
+    const DRIVER_HANDLE: bool = true;
+
     #[inline]
     fn encode<const SYNC_MODE: bool>(self, buf: &mut BytesMut) -> Result<Self::Output, Error> {
         let Self { name, stmt, types, cli } = self;
@@ -95,6 +104,8 @@ where
 {
     type Output = StatementCreateResponseBlocking<'c, C>;
 
+    const DRIVER_HANDLE: bool = true;
+
     #[inline]
     fn encode<const SYNC_MODE: bool>(self, buf: &mut BytesMut) -> Result<Self::Output, Error> {
         let Self { name, stmt, types, cli } = self;
@@ -268,7 +279,11 @@ where
     S: Encode,
 {
     let msg_count = stmt.count_hint();
-    tx.send(|buf| stmt.encode::<true>(buf), msg_count)
+    let (o, mut res) = tx.send(|buf| stmt.encode::<true>(buf), msg_count)?;
+    if S::DRIVER_HANDLE {
+        res.driver = Some(tx.downgrade());
+    }
+    Ok((o, res))
 }
 
 fn encode_bind<P>(stmt: &str, types: &[Type], params: P, portal: &str, buf: &mut BytesMut) -> Result<(), Error>
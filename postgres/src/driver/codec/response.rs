@@ -6,12 +6,12 @@ use crate::{
     error::Error,
     pipeline::PipelineStream,
     prepare::Prepare,
-    query::{RowSimpleStream, RowStream, RowStreamGuarded},
+    query::{Query, RowSimpleStream, RowStream, RowStreamGuarded},
     statement::Statement,
     BoxedFuture,
 };
 
-use super::{sealed, Response};
+use super::{encode::StatementCancel, sealed, Response};
 
 /// trait for generic over how to construct an async stream rows
 pub trait IntoResponse: sealed::Sealed + Sized {
@@ -97,9 +97,12 @@ where
     type Response = BoxedFuture<'s, Result<Statement, Error>>;
 
     fn into_response(self, mut res: Response) -> Self::Response {
-        Box::pin(async move {
-            let Self { name, cli } = self;
+        let Self { name, cli } = self;
+        // statement is already sent to server at this point. arm the guard before the future is polled so
+        // dropping it without polling still closes the statement.
+        let guard = CancelGuard::new(name, cli);
 
+        Box::pin(async move {
             match res.recv().await? {
                 backend::Message::ParseComplete => {}
                 _ => return Err(Error::unexpected()),
@@ -128,12 +131,12 @@ where
                 let mut it = row_description.fields();
                 while let Some(field) = it.next()? {
                     let type_ = cli._get_type(field.type_oid()).await?;
-                    let column = Column::new(field.name(), type_);
+                    let column = Column::new(&field, type_);
                     columns.push(column);
                 }
             }
 
-            Ok(Statement::new(name, params, columns))
+            Ok(Statement::new(guard.disarm(), params, columns, res.take_driver()))
         })
     }
 }
@@ -153,6 +156,7 @@ where
 
     fn into_response(self, mut res: Response) -> Self::Response {
         let Self { name, cli } = self;
+        let guard = CancelGuard::new(name, cli);
 
         match res.blocking_recv()? {
             backend::Message::ParseComplete => {}
@@ -182,11 +186,46 @@ where
             let mut it = row_description.fields();
             while let Some(field) = it.next()? {
                 let type_ = cli._get_type_blocking(field.type_oid())?;
-                let column = Column::new(field.name(), type_);
+                let column = Column::new(&field, type_);
                 columns.push(column);
             }
         }
 
-        Ok(Statement::new(name, params, columns))
+        Ok(Statement::new(guard.disarm(), params, columns, res.take_driver()))
+    }
+}
+
+// guard for closing statement on server side when preparing failed or it's future is dropped before finish.
+// without it the statement would be left on server for the lifetime of connection with nothing referencing it.
+// closing a statement server never created(parse failed) is not an error.
+struct CancelGuard<'a, C>
+where
+    C: Query,
+{
+    name: Option<String>,
+    cli: &'a C,
+}
+
+impl<'a, C> CancelGuard<'a, C>
+where
+    C: Query,
+{
+    fn new(name: String, cli: &'a C) -> Self {
+        Self { name: Some(name), cli }
+    }
+
+    fn disarm(mut self) -> String {
+        self.name.take().unwrap()
+    }
+}
+
+impl<C> Drop for CancelGuard<'_, C>
+where
+    C: Query,
+{
+    fn drop(&mut self) {
+        if let Some(ref name) = self.name {
+            let _ = self.cli._send_encode_query(StatementCancel { name });
+        }
     }
 }
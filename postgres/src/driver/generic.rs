@@ -7,7 +7,10 @@ use core::{
 use std::{
     collections::VecDeque,
     io,
-    sync::{Arc, Mutex},
+    sync::{
+        atomic::{AtomicU8, Ordering},
+        Arc, Mutex, Weak,
+    },
 };
 
 use futures_core::task::__internal::AtomicWaker;
@@ -18,7 +21,10 @@ use xitca_io::{
 };
 use xitca_unsafe_collection::futures::{Select as _, SelectOutput};
 
-use crate::error::{DriverDown, Error};
+use crate::{
+    error::{DriverDown, Error},
+    transaction::TransactionStatus,
+};
 
 use super::codec::{Response, ResponseMessage, ResponseSender, SenderState};
 
@@ -44,15 +50,52 @@ impl DriverTx {
         Arc::strong_count(&self.0) == 1
     }
 
+    pub(crate) fn transaction_status(&self) -> TransactionStatus {
+        TransactionStatus::from_u8(self.0.transaction_status.load(Ordering::Relaxed))
+    }
+
     pub(crate) fn send_one_way<F>(&self, func: F) -> Result<(), Error>
     where
         F: FnOnce(&mut BytesMut) -> Result<(), Error>,
     {
-        self._send(func, |_| {})?;
+        self.0._send(func, |_| {})?;
         Ok(())
     }
 
     pub(crate) fn send<F, O>(&self, func: F, msg_count: usize) -> Result<(O, Response), Error>
+    where
+        F: FnOnce(&mut BytesMut) -> Result<O, Error>,
+    {
+        self.0.send(func, msg_count)
+    }
+
+    pub(crate) fn downgrade(&self) -> WeakDriverTx {
+        WeakDriverTx(Arc::downgrade(&self.0))
+    }
+}
+
+// weak reference of DriverTx. it does not keep driver alive and does not terminate it when dropped.
+#[derive(Clone, Debug)]
+pub(crate) struct WeakDriverTx(Weak<SharedState>);
+
+impl WeakDriverTx {
+    pub(crate) fn send<F, O>(&self, func: F, msg_count: usize) -> Result<(O, Response), Error>
+    where
+        F: FnOnce(&mut BytesMut) -> Result<O, Error>,
+    {
+        self.0.upgrade().ok_or(DriverDown)?.send(func, msg_count)
+    }
+}
+
+pub(crate) struct SharedState {
+    guarded: Mutex<State>,
+    waker: AtomicWaker,
+    // status byte from last ReadyForQuery message.
+    transaction_status: AtomicU8,
+}
+
+impl SharedState {
+    fn send<F, O>(&self, func: F, msg_count: usize) -> Result<(O, Response), Error>
     where
         F: FnOnce(&mut BytesMut) -> Result<O, Error>,
     {
@@ -68,7 +111,7 @@ impl DriverTx {
         F: FnOnce(&mut BytesMut) -> Result<O, Error>,
         F2: FnOnce(&mut State) -> T,
     {
-        let mut inner = self.0.guarded.lock().unwrap();
+        let mut inner = self.guarded.lock().unwrap();
 
         if inner.closed {
             return Err(DriverDown.into());
@@ -80,18 +123,11 @@ impl DriverTx {
         let t = on_send(&mut inner);
 
         drop(inner);
-        self.0.waker.wake();
+        self.waker.wake();
 
         Ok((o, t))
     }
-}
-
-pub(crate) struct SharedState {
-    guarded: Mutex<State>,
-    waker: AtomicWaker,
-}
 
-impl SharedState {
     async fn wait(&self) -> WaitState {
         poll_fn(|cx| {
             let inner = self.guarded.lock().unwrap();
@@ -159,6 +195,7 @@ where
                 res: VecDeque::new(),
             }),
             waker: AtomicWaker::new(),
+            transaction_status: AtomicU8::new(b'I'),
         });
 
         (
@@ -329,6 +366,11 @@ where
         while let Some(res) = ResponseMessage::try_from_buf(self.read_buf.get_mut())? {
             match res {
                 ResponseMessage::Normal(mut msg) => {
+                    if msg.status != 0 {
+                        self.shared_state
+                            .transaction_status
+                            .store(msg.status, Ordering::Relaxed);
+                    }
                     let mut inner = self.shared_state.guarded.lock().unwrap();
                     let front = inner.res.front_mut().ok_or_else(|| msg.parse_error())?;
                     match front.send(msg) {
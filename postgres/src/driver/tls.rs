@@ -1,18 +1,22 @@
-use std::sync::Arc;
+use std::{borrow::Cow, fs, sync::Arc};
 
 use sha2::{Digest, Sha256};
 use xitca_io::io::AsyncIo;
 use xitca_tls::rustls::{
     self,
-    client::danger::HandshakeSignatureValid,
-    crypto::{verify_tls12_signature, verify_tls13_signature},
+    client::{
+        danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier},
+        verify_server_cert_signed_by_trust_anchor,
+    },
+    crypto::{verify_tls12_signature, verify_tls13_signature, WebPkiSupportedAlgorithms},
     pki_types::{CertificateDer, ServerName, UnixTime},
+    server::ParsedCertificate,
     ClientConfig, ClientConnection, DigitallySignedStruct, RootCertStore, TlsStream,
 };
 
 use crate::{
-    config::{Config, SslNegotiation},
-    error::Error,
+    config::{Config, SslMode, SslNegotiation, SslRootCert},
+    error::{ConfigError, Error},
 };
 
 pub(super) async fn connect_tls<Io>(
@@ -24,7 +28,10 @@ where
     Io: AsyncIo,
 {
     let name = ServerName::try_from(host).map_err(|_| Error::todo())?.to_owned();
-    let mut config = dangerous_config(Vec::new());
+    let mut config = match cfg.get_ssl_mode() {
+        SslMode::VerifyCa | SslMode::VerifyFull => verified_config(cfg)?,
+        _ => dangerous_config(Vec::new()),
+    };
 
     if cfg.get_ssl_negotiation() == SslNegotiation::Direct {
         config.alpn_protocols = vec![b"postgresql".to_vec()];
@@ -47,6 +54,94 @@ where
     Ok(stream)
 }
 
+// config verifying server certificate with trusted root certificates. host name is only verified with
+// SslMode::VerifyFull.
+fn verified_config(cfg: &Config) -> Result<ClientConfig, Error> {
+    let root_store = Arc::new(root_store(cfg.ssl_root_cert.as_ref())?);
+
+    let builder = ClientConfig::builder();
+
+    let config = if cfg.get_ssl_mode() == SslMode::VerifyFull {
+        builder.with_root_certificates(root_store)
+    } else {
+        builder.dangerous().with_custom_certificate_verifier(Arc::new(VerifyCa {
+            roots: root_store,
+            algs: rustls::crypto::ring::default_provider().signature_verification_algorithms,
+        }))
+    };
+
+    Ok(config.with_no_client_auth())
+}
+
+fn root_store(root_cert: Option<&SslRootCert>) -> Result<RootCertStore, Error> {
+    let mut root_store = RootCertStore::empty();
+
+    let pem = match root_cert {
+        None => {
+            root_store.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+            return Ok(root_store);
+        }
+        Some(SslRootCert::Pem(pem)) => Cow::Borrowed(pem.as_ref()),
+        Some(SslRootCert::Path(path)) => Cow::Owned(fs::read(path)?),
+    };
+
+    for cert in rustls_pemfile::certs(&mut pem.as_ref()) {
+        let cert = cert.map_err(|_| ConfigError::InvalidRootCert)?;
+        root_store.add(cert).map_err(|_| ConfigError::InvalidRootCert)?;
+    }
+
+    if root_store.is_empty() {
+        return Err(ConfigError::InvalidRootCert.into());
+    }
+
+    Ok(root_store)
+}
+
+// verifier for SslMode::VerifyCa where only certificate chain is verified against trusted root certificates.
+// host name is not checked at all.
+#[derive(Debug)]
+struct VerifyCa {
+    roots: Arc<RootCertStore>,
+    algs: WebPkiSupportedAlgorithms,
+}
+
+impl ServerCertVerifier for VerifyCa {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp: &[u8],
+        now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        let cert = ParsedCertificate::try_from(end_entity)?;
+        verify_server_cert_signed_by_trust_anchor(&cert, &self.roots, intermediates, now, self.algs.all)?;
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls12_signature(message, cert, dss, &self.algs)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls13_signature(message, cert, dss, &self.algs)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<rustls::SignatureScheme> {
+        self.algs.supported_schemes()
+    }
+}
+
 pub(crate) fn dangerous_config(alpn: Vec<Vec<u8>>) -> xitca_tls::rustls::ClientConfig {
     let mut root_store = RootCertStore::empty();
 
@@ -71,7 +166,7 @@ impl SkipServerVerification {
     }
 }
 
-impl rustls::client::danger::ServerCertVerifier for SkipServerVerification {
+impl ServerCertVerifier for SkipServerVerification {
     fn verify_server_cert(
         &self,
         _end_entity: &CertificateDer<'_>,
@@ -79,8 +174,8 @@ impl rustls::client::danger::ServerCertVerifier for SkipServerVerification {
         _server_name: &ServerName<'_>,
         _ocsp: &[u8],
         _now: UnixTime,
-    ) -> Result<rustls::client::danger::ServerCertVerified, rustls::Error> {
-        Ok(rustls::client::danger::ServerCertVerified::assertion())
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
     }
 
     fn verify_tls12_signature(
@@ -117,3 +212,83 @@ impl rustls::client::danger::ServerCertVerifier for SkipServerVerification {
             .supported_schemes()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn root_store_from_pem() {
+        let cert = rcgen::generate_simple_self_signed(vec!["localhost".to_owned()]).unwrap();
+        let pem = SslRootCert::Pem(Box::from(cert.cert.pem().as_bytes()));
+        assert_eq!(root_store(Some(&pem)).unwrap().len(), 1);
+
+        let pem = SslRootCert::Pem(Box::from(b"not a certificate".as_slice()));
+        let e = root_store(Some(&pem)).unwrap_err();
+        assert!(matches!(
+            e.downcast_ref::<ConfigError>(),
+            Some(ConfigError::InvalidRootCert)
+        ));
+
+        assert!(!root_store(None).unwrap().is_empty());
+    }
+
+    // handshake with server presenting certificate issued to a different host name than the one connected to.
+    async fn connect_mismatched_san(mode: SslMode, root: &str) -> Result<(), Error> {
+        use std::{net::TcpListener, thread};
+
+        use xitca_tls::rustls::{pki_types::PrivateKeyDer, ServerConfig, ServerConnection};
+
+        let cert = rcgen::generate_simple_self_signed(vec!["example.com".to_owned()]).unwrap();
+        let key = PrivateKeyDer::Pkcs8(cert.key_pair.serialize_der().into());
+        let config = ServerConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+            .with_safe_default_protocol_versions()
+            .unwrap()
+            .with_no_client_auth()
+            .with_single_cert(vec![cert.cert.der().clone()], key)
+            .unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut conn = ServerConnection::new(Arc::new(config)).unwrap();
+            while conn.is_handshaking() {
+                if conn.complete_io(&mut stream).is_err() {
+                    break;
+                }
+            }
+            conn.send_close_notify();
+            let _ = conn.complete_io(&mut stream);
+        });
+
+        let root = match root {
+            "self" => cert.cert.pem(),
+            _ => rcgen::generate_simple_self_signed(vec![root.to_owned()])
+                .unwrap()
+                .cert
+                .pem(),
+        };
+
+        let mut cfg = Config::new();
+        cfg.ssl_mode(mode).ssl_root_cert(root);
+
+        let io = xitca_io::net::TcpStream::connect(addr).await?;
+        let res = connect_tls(io, "localhost", &mut cfg).await.map(|_| ());
+
+        server.join().unwrap();
+
+        res
+    }
+
+    #[tokio::test]
+    async fn verify_ca_skip_host_name() {
+        // certificate chain is trusted and host name mismatch is ignored.
+        connect_mismatched_san(SslMode::VerifyCa, "self").await.unwrap();
+        // untrusted certificate chain is rejected.
+        assert!(connect_mismatched_san(SslMode::VerifyCa, "localhost").await.is_err());
+        // host name mismatch is rejected.
+        assert!(connect_mismatched_san(SslMode::VerifyFull, "self").await.is_err());
+    }
+}
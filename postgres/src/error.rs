@@ -42,6 +42,14 @@ impl Error {
         self.0.is::<DriverDown>() || self.0.is::<DriverDownReceiving>()
     }
 
+    /// check if error is caused by database responding with unexpected message. It indicates connection could be
+    /// out of sync with database. See [`Client::recover`] for recovering from it.
+    ///
+    /// [`Client::recover`]: crate::Client::recover
+    pub fn is_unexpected(&self) -> bool {
+        self.0.is::<UnexpectedMessage>()
+    }
+
     pub(crate) fn todo() -> Self {
         Self(Box::new(ToDo {
             back_trace: Backtrace::capture(),
@@ -260,6 +268,25 @@ impl error::Error for InvalidColumnIndex {}
 
 from_impl!(InvalidColumnIndex);
 
+/// error for looking up type by name that does not exist in database.
+pub struct UnknownType(pub String);
+
+impl fmt::Debug for UnknownType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UnknownType").finish()
+    }
+}
+
+impl fmt::Display for UnknownType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown type: {}", self.0)
+    }
+}
+
+impl error::Error for UnknownType {}
+
+from_impl!(UnknownType);
+
 #[derive(Debug)]
 pub struct InvalidParamCount {
     pub expected: usize,
@@ -305,6 +332,7 @@ pub enum ConfigError {
     MissingUserName,
     MissingPassWord,
     WrongPassWord,
+    InvalidRootCert,
 }
 
 impl fmt::Display for ConfigError {
@@ -316,6 +344,7 @@ impl fmt::Display for ConfigError {
             Self::MissingUserName => f.write_str("username is missing"),
             Self::MissingPassWord => f.write_str("password is missing"),
             Self::WrongPassWord => f.write_str("password is wrong"),
+            Self::InvalidRootCert => f.write_str("ssl root certificate is invalid or empty"),
         }
     }
 }
@@ -324,6 +353,63 @@ impl error::Error for ConfigError {}
 
 from_impl!(ConfigError);
 
+/// error happens when parsing [`Config`] from connection string.
+///
+/// # Examples
+/// ```rust
+/// use xitca_postgres::{error::ConfigParseError, Config};
+///
+/// let e = Config::try_from("postgres://localhost/db?sslmode=bogus").unwrap_err();
+/// assert!(e.downcast_ref::<ConfigParseError>().is_some());
+/// ```
+///
+/// [`Config`]: crate::config::Config
+#[non_exhaustive]
+#[derive(Debug)]
+pub enum ConfigParseError {
+    /// key is not a known connection parameter.
+    UnknownKey(Box<str>),
+    /// value can not be parsed for the given key.
+    InvalidValue { key: Box<str>, value: Box<str> },
+    /// unexpected character found at byte position of key/value string.
+    UnexpectedChar { pos: usize, expected: char, found: char },
+    /// string ended while more input is expected.
+    UnexpectedEof,
+    /// malformed connection url.
+    InvalidUrl(&'static str),
+}
+
+impl ConfigParseError {
+    pub(crate) fn invalid_value(key: &str, value: &str) -> Self {
+        Self::InvalidValue {
+            key: Box::from(key),
+            value: Box::from(value),
+        }
+    }
+}
+
+impl fmt::Display for ConfigParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Config parse error: ")?;
+        match self {
+            Self::UnknownKey(key) => write!(f, "unknown key `{key}`"),
+            Self::InvalidValue { key, value } => write!(f, "invalid value `{value}` for key `{key}`"),
+            Self::UnexpectedChar { pos, expected, found } => {
+                write!(
+                    f,
+                    "unexpected character at byte {pos}: expected `{expected}` but got `{found}`"
+                )
+            }
+            Self::UnexpectedEof => f.write_str("unexpected end of input"),
+            Self::InvalidUrl(reason) => write!(f, "invalid url: {reason}"),
+        }
+    }
+}
+
+impl error::Error for ConfigParseError {}
+
+from_impl!(ConfigParseError);
+
 #[non_exhaustive]
 #[derive(Debug)]
 pub enum SystemError {
@@ -381,6 +467,97 @@ impl error::Error for RuntimeError {}
 
 from_impl!(RuntimeError);
 
+/// error indicate a sync point of pipelined queries reported connection is in failed transaction block.
+///
+/// queries after the sync point are rejected by database until the transaction block is ended with `ROLLBACK`.
+#[derive(Debug)]
+pub struct TransactionFailed;
+
+impl fmt::Display for TransactionFailed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Connection is in failed transaction block. Queries are rejected until ROLLBACK")
+    }
+}
+
+impl error::Error for TransactionFailed {}
+
+from_impl!(TransactionFailed);
+
+/// error indicate deadline of query has already passed before it's sent to database.
+///
+/// query related to this error has not been sent to database.
+#[derive(Debug)]
+pub struct DeadlineExceeded;
+
+impl fmt::Display for DeadlineExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Deadline of query has already passed. Query has not been sent to database")
+    }
+}
+
+impl error::Error for DeadlineExceeded {}
+
+from_impl!(DeadlineExceeded);
+
+/// error indicate a statement inside sql string of [`Client::batch_execute`] is failed by database.
+///
+/// [`BatchError::index`] is the position of failed statement in the sql string. statements before it have been
+/// executed and statements after it are skipped by database. the database error is the [`error::Error::source`] of
+/// this type and can be obtained with [`BatchError::error`]. it's [`DbError::position`] is relative to the start of
+/// whole sql string.
+///
+/// # Examples
+/// ```rust
+/// use xitca_postgres::error::{BatchError, DbError, Error};
+///
+/// fn failed_statement(e: &Error) -> Option<(usize, &DbError)> {
+///     let e = e.downcast_ref::<BatchError>()?;
+///     e.error().downcast_ref::<DbError>().map(|db| (e.index(), db))
+/// }
+/// ```
+///
+/// [`Client::batch_execute`]: crate::Client::batch_execute
+#[derive(Debug)]
+pub struct BatchError {
+    index: usize,
+    error: Error,
+}
+
+impl BatchError {
+    // only database error is bound to position of statement. other errors are returned as is.
+    pub(crate) fn wrap(index: usize, error: Error) -> Error {
+        if error.is::<DbError>() {
+            Error::from(Self { index, error })
+        } else {
+            error
+        }
+    }
+
+    /// position of failed statement in sql string. starting from 0.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// error of failed statement.
+    pub fn error(&self) -> &Error {
+        &self.error
+    }
+}
+
+impl fmt::Display for BatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "batched statement at index {} failed: {}", self.index, self.error)
+    }
+}
+
+impl error::Error for BatchError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&*self.error)
+    }
+}
+
+from_impl!(BatchError);
+
 /// error for database returning backend message type that is not expected.
 /// it indicates there might be protocol error on either side of the connection.
 #[derive(Debug)]
@@ -427,6 +604,7 @@ pub struct DbError {
     file: Option<String>,
     line: Option<u32>,
     routine: Option<String>,
+    pipeline_index: Option<usize>,
 }
 
 impl DbError {
@@ -519,9 +697,14 @@ impl DbError {
             file,
             line,
             routine,
+            pipeline_index: None,
         })
     }
 
+    pub(crate) fn set_pipeline_index(&mut self, index: usize) {
+        self.pipeline_index = Some(index);
+    }
+
     /// The field contents are ERROR, FATAL, or PANIC (in an error message),
     /// or WARNING, NOTICE, DEBUG, INFO, or LOG (in a notice message), or a
     /// localized translation of one of these.
@@ -632,6 +815,25 @@ impl DbError {
     pub fn routine(&self) -> Option<&str> {
         self.routine.as_deref()
     }
+
+    /// Position of failed query in the order it's added to pipeline. starting from 0.
+    ///
+    /// Only error of query executed through [`Pipeline`] carries it.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use xitca_postgres::error::{DbError, Error};
+    ///
+    /// fn failed_query(e: &Error) -> Option<(usize, &DbError)> {
+    ///     let e = e.downcast_ref::<DbError>()?;
+    ///     e.pipeline_index().map(|index| (index, e))
+    /// }
+    /// ```
+    ///
+    /// [`Pipeline`]: crate::pipeline::Pipeline
+    pub fn pipeline_index(&self) -> Option<usize> {
+        self.pipeline_index
+    }
 }
 
 impl fmt::Display for DbError {
@@ -20,7 +20,7 @@ impl<I> AsyncLendingIteratorExt for I where I: AsyncLendingIterator {}
 pub trait AsyncLendingIteratorExt: AsyncLendingIterator {
     fn map_ok<F, O>(self, func: F) -> MapOk<Self, F>
     where
-        F: Fn(Self::Ok<'_>) -> O,
+        F: FnMut(Self::Ok<'_>) -> O,
         Self: Sized,
     {
         MapOk { iter: self, func }
@@ -65,6 +65,159 @@ pub trait AsyncLendingIteratorExt: AsyncLendingIterator {
     }
 }
 
+/// lent item of [`AsyncLendingIterator`] in a form free of generic associated type.
+///
+/// Closure taking [`AsyncLendingIterator::Ok`] as argument is higher ranked over the lifetime of lent item and due
+/// to a current limitation of the type system it requires iterator to be `'static`. Closures of
+/// [`AsyncLendingItemExt`] take [`AsyncLendingItem::Item`] instead which does not carry this requirement and
+/// iterators borrowing from other types (e.g. [`RowStream`] and [`PipelineStream`]) can use them.
+///
+/// [`RowStream`]: crate::RowStream
+/// [`PipelineStream`]: crate::pipeline::PipelineStream
+pub trait AsyncLendingItem<'i>: AsyncLendingIterator {
+    /// the same type as [`AsyncLendingIterator::Ok`] with lifetime of `'i`.
+    type Item;
+
+    /// convert lent item to [`AsyncLendingItem::Item`]. it's an identity function for most of implementors.
+    fn lend(ok: Self::Ok<'i>) -> Self::Item
+    where
+        Self: 'i;
+}
+
+impl<I> AsyncLendingItemExt for I where I: for<'i> AsyncLendingItem<'i> {}
+
+/// adapters for [`AsyncLendingIterator`] that work on borrowed iterator. See [`AsyncLendingItem`] for detail.
+///
+/// [`RowStreamOwned`] implements [`Iterator`] as well and methods sharing the same name must be called with fully
+/// qualified syntax when both traits are in scope.
+///
+/// [`RowStreamOwned`]: crate::RowStreamOwned
+pub trait AsyncLendingItemExt: AsyncLendingIterator + for<'i> AsyncLendingItem<'i> {
+    /// transform every item lent by iterator into owned output of given closure. items still borrow the iterator
+    /// when they are passed to closure and they must be consumed or converted to owned type inside it.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use xitca_postgres::{iter::{AsyncLendingItemExt, AsyncLendingIteratorExt}, Error, RowStream};
+    /// # async fn map(stream: RowStream<'_>) -> Result<(), Error> {
+    /// let ids = stream.map(|row| row.get::<i32>(0)).try_collect_lent::<Vec<_>>().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    fn map<F, O>(self, func: F) -> MapItem<Self, F>
+    where
+        F: for<'i> FnMut(<Self as AsyncLendingItem<'i>>::Item) -> O,
+        Self: Sized,
+    {
+        MapItem { iter: self, func }
+    }
+
+    /// fold every item lent by iterator into an accumulator with given fallible closure. folding stops at the first
+    /// error produced by iterator or closure.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use xitca_postgres::{iter::AsyncLendingItemExt, Error, RowStream};
+    /// # async fn fold(stream: RowStream<'_>) -> Result<i64, Error> {
+    /// stream.try_fold(0, |sum, row| row.try_get::<i64>(0).map(|n| sum + n)).await
+    /// # }
+    /// ```
+    fn try_fold<B, F, E>(mut self, init: B, mut func: F) -> impl Future<Output = Result<B, E>> + Send
+    where
+        F: for<'i> FnMut(B, <Self as AsyncLendingItem<'i>>::Item) -> Result<B, E> + Send,
+        B: Send,
+        E: From<Self::Err>,
+        Self: Send + Sized,
+    {
+        async move {
+            let mut acc = init;
+            while let Some(item) = self.try_next().await? {
+                acc = func(acc, Self::lend(item))?;
+            }
+            Ok(acc)
+        }
+    }
+
+    /// collect items lent by iterator into collection of [`AsyncLendingItem::Item`].
+    ///
+    /// Unlike [`AsyncLendingIteratorExt::try_collect`] iterators borrowing from other types can be collected.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use xitca_postgres::{iter::{AsyncLendingItemExt, AsyncLendingIteratorExt}, Error, RowStream};
+    /// # async fn collect(stream: RowStream<'_>) -> Result<(), Error> {
+    /// let names = stream.map(|row| row.get::<String>(0)).try_collect_lent::<Vec<_>>().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    fn try_collect_lent<T>(self) -> impl Future<Output = Result<T, Self::Err>> + Send
+    where
+        T: Default + for<'i> Extend<<Self as AsyncLendingItem<'i>>::Item> + Send,
+        Self: Send + Sized,
+    {
+        self.try_collect_lent_into(T::default())
+    }
+
+    /// collect items lent by iterator into given collection. See [`AsyncLendingItemExt::try_collect_lent`] for
+    /// detail.
+    fn try_collect_lent_into<T>(mut self, mut collection: T) -> impl Future<Output = Result<T, Self::Err>> + Send
+    where
+        T: for<'i> Extend<<Self as AsyncLendingItem<'i>>::Item> + Send,
+        Self: Send + Sized,
+    {
+        async move {
+            while let Some(item) = self.try_next().await? {
+                collection.extend([Self::lend(item)]);
+            }
+            Ok(collection)
+        }
+    }
+}
+
+pub struct MapItem<I, F> {
+    iter: I,
+    func: F,
+}
+
+impl<I, F, O> AsyncLendingIterator for MapItem<I, F>
+where
+    I: AsyncLendingIterator + for<'i> AsyncLendingItem<'i> + Send,
+    F: for<'i> FnMut(<I as AsyncLendingItem<'i>>::Item) -> O + Send,
+    O: Send,
+{
+    type Ok<'i>
+        = O
+    where
+        Self: 'i;
+    type Err = I::Err;
+
+    async fn try_next(&mut self) -> Result<Option<Self::Ok<'_>>, Self::Err> {
+        let next = self.iter.try_next().await?;
+        Ok(next.map(|item| (self.func)(I::lend(item))))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<I, F, O> AsyncLendingItem<'_> for MapItem<I, F>
+where
+    I: AsyncLendingIterator + for<'i> AsyncLendingItem<'i> + Send,
+    F: for<'i> FnMut(<I as AsyncLendingItem<'i>>::Item) -> O + Send,
+    O: Send,
+{
+    type Item = O;
+
+    #[inline]
+    fn lend(ok: O) -> O {
+        ok
+    }
+}
+
 pub struct MapOk<I, F> {
     iter: I,
     func: F,
@@ -73,7 +226,7 @@ pub struct MapOk<I, F> {
 impl<I, F, O> AsyncLendingIterator for MapOk<I, F>
 where
     I: AsyncLendingIterator + Send,
-    F: Fn(I::Ok<'_>) -> O + Send,
+    F: FnMut(I::Ok<'_>) -> O + Send,
     O: Send,
 {
     type Ok<'i>
@@ -84,7 +237,7 @@ where
 
     async fn try_next(&mut self) -> Result<Option<Self::Ok<'_>>, Self::Err> {
         let next = self.iter.try_next().await?;
-        Ok(next.map(|item| (self.func)(item)))
+        Ok(next.map(&mut self.func))
     }
 
     #[inline]
@@ -93,6 +246,20 @@ where
     }
 }
 
+impl<I, F, O> AsyncLendingItem<'_> for MapOk<I, F>
+where
+    I: AsyncLendingIterator + Send,
+    F: FnMut(I::Ok<'_>) -> O + Send,
+    O: Send,
+{
+    type Item = O;
+
+    #[inline]
+    fn lend(ok: O) -> O {
+        ok
+    }
+}
+
 pub struct MapErr<I, F> {
     iter: I,
     func: F,
@@ -120,6 +287,23 @@ where
     }
 }
 
+impl<'i, I, F, O> AsyncLendingItem<'i> for MapErr<I, F>
+where
+    I: AsyncLendingItem<'i> + Send,
+    F: Fn(I::Err) -> O + Send,
+    O: Send,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn lend(ok: Self::Ok<'i>) -> Self::Item
+    where
+        Self: 'i,
+    {
+        I::lend(ok)
+    }
+}
+
 pub struct Map<I, F> {
     iter: I,
     func: F,
@@ -152,6 +336,21 @@ where
     }
 }
 
+impl<I, F, T, E> AsyncLendingItem<'_> for Map<I, F>
+where
+    I: AsyncLendingIterator + Send,
+    F: Fn(Result<I::Ok<'_>, I::Err>) -> Result<T, E> + Send,
+    T: Send,
+    E: Send,
+{
+    type Item = T;
+
+    #[inline]
+    fn lend(ok: T) -> T {
+        ok
+    }
+}
+
 async fn _map_ok_err_try_collect(stream: crate::RowStreamOwned) -> Result<Vec<String>, crate::error::Error> {
     stream
         .map_ok(|row| row.get(0))
@@ -15,6 +15,7 @@ mod session;
 pub mod copy;
 pub mod error;
 pub mod iter;
+pub mod notify;
 pub mod pipeline;
 pub mod pool;
 pub mod row;
@@ -0,0 +1,180 @@
+//! utilities for postgres `LISTEN`/`NOTIFY` feature.
+
+use core::{pin::pin, time::Duration};
+
+use postgres_protocol::message::backend;
+use tokio::{sync::oneshot, task::JoinHandle};
+use xitca_unsafe_collection::futures::{Select, SelectOutput};
+
+use super::{config::Config, error::Error, execute::Execute, iter::AsyncLendingIterator, Postgres};
+
+/// event passed to callback of [`PgInvalidator`].
+#[derive(Debug)]
+pub enum Invalidation<'a> {
+    /// notification received from listened channel.
+    Notify {
+        /// process id of database session sent the notification.
+        process_id: i32,
+        /// payload of notification. empty string when notification is sent without payload.
+        payload: &'a str,
+    },
+    /// connection is re-established after it's lost and channel is listened again. notifications sent while
+    /// connection was lost are not delivered and this event should be treated as invalidation of everything.
+    Reconnect,
+}
+
+/// background task listening on a channel with `LISTEN` and invoking callback on every notification. e.g. for
+/// clearing in process cache when data is changed by other database sessions.
+///
+/// Notifications are delivered through [`Driver`] of connection and can not be observed from a [`Client`] driven
+/// by others. Therefore the task owns a dedicated connection built from given [`Config`]. When the connection is
+/// lost it's reconnected and channel is listened again after retry interval.
+///
+/// The task is spawned on tokio runtime and it stops when the returned [`InvalidatorHandle`] is shut down or
+/// dropped.
+///
+/// # Examples
+/// ```rust
+/// # use std::sync::{Arc, Mutex};
+/// # use std::collections::HashMap;
+/// # use xitca_postgres::{notify::{Invalidation, PgInvalidator}, Config};
+/// # async fn invalidate(cfg: Config) {
+/// let cache = Arc::new(Mutex::new(HashMap::<String, String>::new()));
+///
+/// let handle = PgInvalidator::new(cfg, "cache_invalidate").spawn({
+///     let cache = cache.clone();
+///     move |event| match event {
+///         // payload of notification is the cache key changed. e.g. `NOTIFY cache_invalidate, 'user:1'`
+///         Invalidation::Notify { payload, .. } => {
+///             cache.lock().unwrap().remove(payload);
+///         }
+///         // notifications could be missed when connection was lost.
+///         Invalidation::Reconnect => cache.lock().unwrap().clear(),
+///     }
+/// });
+///
+/// // stop listening and wait for background task to finish.
+/// handle.shutdown().await;
+/// # }
+/// ```
+///
+/// [`Driver`]: crate::Driver
+/// [`Client`]: crate::Client
+pub struct PgInvalidator {
+    cfg: Config,
+    channel: String,
+    retry: Duration,
+}
+
+impl PgInvalidator {
+    /// construct invalidator listening on given channel. channel name is case sensitive and quoted as identifier.
+    pub fn new(cfg: Config, channel: impl Into<String>) -> Self {
+        Self {
+            cfg,
+            channel: channel.into(),
+            retry: Duration::from_secs(1),
+        }
+    }
+
+    /// set interval between reconnecting attempts after connection is lost or failed to establish.
+    ///
+    /// # Default
+    /// 1 second.
+    pub fn retry_interval(mut self, dur: Duration) -> Self {
+        self.retry = dur;
+        self
+    }
+
+    /// spawn background task on current tokio runtime and call given function with [`Invalidation`] events.
+    ///
+    /// function is called inside the task driving connection and it should not block.
+    ///
+    /// # Panics
+    /// When called outside of tokio runtime.
+    pub fn spawn<F>(self, func: F) -> InvalidatorHandle
+    where
+        F: FnMut(Invalidation<'_>) + Send + 'static,
+    {
+        let (tx, rx) = oneshot::channel();
+        let handle = tokio::spawn(self.run(func, rx));
+        InvalidatorHandle { tx, handle }
+    }
+
+    async fn run<F>(self, mut func: F, mut shutdown: oneshot::Receiver<()>)
+    where
+        F: FnMut(Invalidation<'_>),
+    {
+        let sql = format!("LISTEN \"{}\"", self.channel.replace('"', "\"\""));
+        let mut reconnect = false;
+
+        loop {
+            match listen(&self.cfg, &sql, reconnect, &mut func)
+                .select(&mut shutdown)
+                .await
+            {
+                SelectOutput::A(Ok(())) => tracing::warn!("notification connection closed. reconnecting"),
+                SelectOutput::A(Err(e)) => tracing::warn!("notification connection error: {e}. reconnecting"),
+                SelectOutput::B(_) => return,
+            }
+
+            reconnect = true;
+
+            if let SelectOutput::B(_) = tokio::time::sleep(self.retry).select(&mut shutdown).await {
+                return;
+            }
+        }
+    }
+}
+
+// connect, listen and dispatch notifications until connection is lost.
+async fn listen<F>(cfg: &Config, sql: &str, reconnect: bool, func: &mut F) -> Result<(), Error>
+where
+    F: FnMut(Invalidation<'_>),
+{
+    let (cli, mut drv) = Postgres::new(cfg.clone()).connect().await?;
+
+    // response of LISTEN is received by driver and it must be driven at the same time.
+    let mut fut = pin!(sql.execute(&cli));
+    let mut listening = false;
+
+    loop {
+        let msg = if listening {
+            drv.try_next().await
+        } else {
+            match drv.try_next().select(fut.as_mut()).await {
+                SelectOutput::A(msg) => msg,
+                SelectOutput::B(res) => {
+                    res?;
+                    listening = true;
+                    if reconnect {
+                        func(Invalidation::Reconnect);
+                    }
+                    continue;
+                }
+            }
+        };
+
+        match msg? {
+            Some(backend::Message::NotificationResponse(body)) => func(Invalidation::Notify {
+                process_id: body.process_id(),
+                payload: body.message()?,
+            }),
+            Some(_) => {}
+            None => return Ok(()),
+        }
+    }
+}
+
+/// handle of background task spawned by [`PgInvalidator::spawn`]. dropping it stops the task without waiting.
+pub struct InvalidatorHandle {
+    tx: oneshot::Sender<()>,
+    handle: JoinHandle<()>,
+}
+
+impl InvalidatorHandle {
+    /// stop background task and wait for it to finish. connection of the task is closed afterwards.
+    pub async fn shutdown(self) {
+        drop(self.tx);
+        let _ = self.handle.await;
+    }
+}
@@ -8,19 +8,26 @@
 //! - ordered response handling with a single stream type. reduce memory footprint and possibility of deadlock
 //!
 //! [`tokio-postgres`]: https://docs.rs/tokio-postgres/latest/tokio_postgres/#pipelining
-use core::ops::{Deref, DerefMut, Range};
+use core::{
+    future::ready,
+    ops::{Deref, DerefMut, Range},
+};
+
+use std::collections::VecDeque;
 
 use postgres_protocol::message::{backend, frontend};
 use xitca_io::bytes::BytesMut;
 
 use super::{
     column::Column,
-    driver::codec::{self, encode::Encode, Response},
-    error::{Completed, Error},
+    driver::codec::{self, encode::Encode, AsParams, Response},
+    error::{Completed, DbError, Error, TransactionFailed},
     execute::{Execute, ExecuteBlocking},
-    iter::AsyncLendingIterator,
+    iter::{AsyncLendingItem, AsyncLendingIterator},
     query::Query,
     row::Row,
+    statement::StatementQueryMany,
+    transaction::TransactionStatus,
     BoxedFuture,
 };
 
@@ -64,6 +71,47 @@ pub struct Pipeline<'a, B = Owned, const SYNC_MODE: bool = true> {
     pub(crate) columns: Vec<&'a [Column]>,
     // type for either owned or borrowed bytes buffer.
     pub(crate) buf: B,
+    flush: Option<AutoFlush<'a>>,
+}
+
+type FlushFn<'a> = Box<dyn Fn(&[u8], usize) -> Result<Response, Error> + Send + Sync + 'a>;
+
+// state of pipeline with auto flush enabled.
+struct AutoFlush<'a> {
+    threshold: usize,
+    send: FlushFn<'a>,
+    // responses of flushed segments.
+    responses: Vec<Segment>,
+    // count of queries already flushed.
+    flushed: usize,
+}
+
+impl AutoFlush<'_> {
+    fn flush<const SYNC_MODE: bool>(&mut self, buf: &mut BytesMut, queries: usize) -> Result<(), Error> {
+        let len = buf.len();
+        let count = if SYNC_MODE {
+            queries - self.flushed
+        } else {
+            frontend::sync(buf);
+            1
+        };
+        let res = (self.send)(buf.as_ref(), count).inspect_err(|_| buf.truncate(len))?;
+        buf.clear();
+        self.responses.push(Segment {
+            res,
+            sync: count,
+            queries: self.flushed..queries,
+        });
+        self.flushed = queries;
+        Ok(())
+    }
+}
+
+// response of flushed segment with it's sync point count and range of queries it contains.
+struct Segment {
+    res: Response,
+    sync: usize,
+    queries: Range<usize>,
 }
 
 /// borrowed bytes buffer supplied by api caller
@@ -219,6 +267,7 @@ impl<const SYNC_MODE: bool> Pipeline<'_, Owned, SYNC_MODE> {
         Self {
             columns: Vec::with_capacity(cap),
             buf: Owned(BytesMut::new()),
+            flush: None,
         }
     }
 }
@@ -229,6 +278,72 @@ impl<'b, const SYNC_MODE: bool> Pipeline<'_, Borrowed<'b>, SYNC_MODE> {
         Self {
             columns: Vec::with_capacity(cap),
             buf: Borrowed(buf),
+            flush: None,
+        }
+    }
+}
+
+impl<'a, B, const SYNC_MODE: bool> Pipeline<'a, B, SYNC_MODE>
+where
+    B: DerefMut<Target = BytesMut>,
+{
+    /// enable auto flush of pipeline. when encoded queries in buffer exceed `threshold` bytes they are sent to
+    /// database through given client and the buffer is reused for following queries. This bounds the memory
+    /// footprint of pipeline with large amount of queries.
+    ///
+    /// the pipeline must be queried with the same client afterwards and responses of all queries are observed
+    /// from the [PipelineStream] in order like a pipeline without auto flush. flushed queries are executed by
+    /// database even when pipeline is dropped without being queried.
+    ///
+    /// # Sync point
+    /// - in sync mode every query has it's own sync point and auto flush is transparent to database server.
+    /// - in un-sync mode a sync point is added to every flush. queries between two flushes are seen as one
+    ///   single binding by database server instead of the whole pipeline. an error of query causes the rest of
+    ///   queries between the same flushes to be skipped by database server.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use xitca_postgres::{pipeline::Pipeline, Client, Error, Execute, Statement};
+    /// # async fn pipeline(client: &Client) -> Result<(), Error> {
+    /// let statement = Statement::named("INSERT INTO foo (id) VALUES ($1)", &[]).execute(client).await?;
+    ///
+    /// // send encoded queries to database every time they exceed 64KB.
+    /// let mut pipe = Pipeline::new().auto_flush(client, 64 * 1024);
+    ///
+    /// for id in 0..100_000 {
+    ///     statement.bind([id]).query(&mut pipe)?;
+    /// }
+    ///
+    /// let row_affected = pipe.execute(client).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn auto_flush<C>(mut self, cli: &'a C, threshold: usize) -> Self
+    where
+        C: Query + Sync,
+    {
+        self.flush = Some(AutoFlush {
+            threshold,
+            send: Box::new(move |buf: &[u8], count: usize| {
+                cli._send_encode_query(PipelineQuery {
+                    count,
+                    columns: Vec::new(),
+                    buf,
+                })
+                .map(|(_, res)| res)
+            }),
+            responses: Vec::new(),
+            flushed: 0,
+        });
+        self
+    }
+
+    fn try_flush(&mut self) -> Result<(), Error> {
+        match self.flush {
+            Some(ref mut flush) if self.buf.len() >= flush.threshold => {
+                flush.flush::<SYNC_MODE>(&mut self.buf, self.columns.len())
+            }
+            _ => Ok(()),
         }
     }
 }
@@ -249,10 +364,12 @@ where
     fn query(self, pipe: &mut Pipeline<'a, B, SYNC_MODE>) -> Self::QueryOutput {
         let len = pipe.buf.len();
 
-        self.encode::<SYNC_MODE>(&mut pipe.buf)
-            .map(|columns| pipe.columns.push(columns))
+        let columns = self
+            .encode::<SYNC_MODE>(&mut pipe.buf)
             // revert back to last pipelined query when encoding error occurred.
-            .inspect_err(|_| pipe.buf.truncate(len))
+            .inspect_err(|_| pipe.buf.truncate(len))?;
+        pipe.columns.push(columns);
+        pipe.try_flush()
     }
 }
 
@@ -284,7 +401,20 @@ where
 
     #[inline]
     fn query(self, cli: &C) -> Self::QueryOutput {
-        let Pipeline { columns, mut buf } = self;
+        let Pipeline {
+            columns,
+            mut buf,
+            flush,
+        } = self;
+
+        if let Some(mut flush) = flush {
+            if !buf.is_empty() {
+                flush.flush::<SYNC_MODE>(&mut buf, columns.len())?;
+            }
+            assert!(!flush.responses.is_empty());
+            return Ok(PipelineStream::from_flushed(flush.responses, columns));
+        }
+
         assert!(!buf.is_empty());
 
         let count = if SYNC_MODE {
@@ -315,20 +445,16 @@ where
         let mut row_affected = 0;
 
         loop {
-            match res.res.blocking_recv()? {
+            match res.recv_blocking()? {
                 backend::Message::BindComplete => {
-                    let item = PipelineItem {
-                        finished: false,
-                        res: &mut res.res,
-                        ranges: &mut res.ranges,
-                        columns: res.columns.pop_front(),
-                    };
+                    let item = res.next_item();
                     row_affected += item.row_affected_blocking()?;
                 }
-                backend::Message::ReadyForQuery(_) => {
+                backend::Message::ReadyForQuery(body) => {
                     if res.columns.is_empty() {
                         return Ok(row_affected);
                     }
+                    ready_check(&body)?;
                 }
                 _ => return Err(Error::unexpected()),
             }
@@ -340,10 +466,83 @@ where
     }
 }
 
+impl<'s, I, const SYNC_MODE: bool> StatementQueryMany<'s, I, SYNC_MODE>
+where
+    I: IntoIterator,
+    I::Item: AsParams,
+{
+    // encode statement with every param set into pipeline. None when there is no param set at all.
+    fn into_pipeline(self) -> Result<Option<Pipeline<'s, Owned, SYNC_MODE>>, Error> {
+        let StatementQueryMany { stmt, params } = self;
+        let params = params.into_iter();
+        let mut pipe = Pipeline::_with_capacity(params.size_hint().0);
+        for params in params {
+            stmt.bind(params).query(&mut pipe)?;
+        }
+        Ok((!pipe.columns.is_empty()).then_some(pipe))
+    }
+}
+
+impl<'s, C, I, const SYNC_MODE: bool> Execute<&C> for StatementQueryMany<'s, I, SYNC_MODE>
+where
+    C: Query,
+    I: IntoIterator,
+    I::Item: AsParams,
+{
+    type ExecuteOutput = BoxedFuture<'s, Result<u64, Error>>;
+    type QueryOutput = Result<PipelineStream<'s>, Error>;
+
+    fn execute(self, cli: &C) -> Self::ExecuteOutput {
+        match self.into_pipeline() {
+            Ok(Some(pipe)) => pipe.execute(cli),
+            Ok(None) => Box::pin(ready(Ok(0))),
+            Err(e) => Box::pin(ready(Err(e))),
+        }
+    }
+
+    /// # Panics
+    /// when there is no param set to bind to.
+    #[inline]
+    fn query(self, cli: &C) -> Self::QueryOutput {
+        let pipe = self.into_pipeline()?.expect("StatementQueryMany must not be empty");
+        pipe.query(cli)
+    }
+}
+
+impl<'s, C, I, const SYNC_MODE: bool> ExecuteBlocking<&C> for StatementQueryMany<'s, I, SYNC_MODE>
+where
+    C: Query,
+    I: IntoIterator,
+    I::Item: AsParams,
+{
+    type ExecuteOutput = Result<u64, Error>;
+    type QueryOutput = Result<PipelineStream<'s>, Error>;
+
+    fn execute_blocking(self, cli: &C) -> Self::ExecuteOutput {
+        match self.into_pipeline()? {
+            Some(pipe) => pipe.execute_blocking(cli),
+            None => Ok(0),
+        }
+    }
+
+    /// # Panics
+    /// when there is no param set to bind to.
+    #[inline]
+    fn query_blocking(self, cli: &C) -> Self::QueryOutput {
+        let pipe = self.into_pipeline()?.expect("StatementQueryMany must not be empty");
+        pipe.query_blocking(cli)
+    }
+}
+
 /// streaming response of pipeline.
 /// impl [AsyncLendingIterator] trait and can be collected asynchronously.
 pub struct PipelineStream<'a> {
     res: Response,
+    // remaining sync points and end of query range of current response and responses of following flushed segments
+    // when pipeline is auto flushed.
+    remaining: usize,
+    end: usize,
+    next: VecDeque<Segment>,
     columns: Columns<'a>,
     ranges: Ranges,
 }
@@ -352,14 +551,102 @@ impl<'a> PipelineStream<'a> {
     pub(crate) const fn new(res: Response, columns: Vec<&'a [Column]>) -> Self {
         Self {
             res,
+            remaining: 0,
+            end: 0,
+            next: VecDeque::new(),
+            columns: Columns { columns, next: 0 },
+            ranges: Vec::new(),
+        }
+    }
+
+    fn from_flushed(responses: Vec<Segment>, columns: Vec<&'a [Column]>) -> Self {
+        let mut next = VecDeque::from(responses);
+        let Segment { res, sync, queries } = next.pop_front().expect("pipeline must not be empty");
+        Self {
+            res,
+            remaining: sync,
+            end: queries.end,
+            next,
             columns: Columns { columns, next: 0 },
             ranges: Vec::new(),
         }
     }
+
+    // move to response of next flushed segment after all sync points of current one are received.
+    // queries skipped by database server after an error in un-sync segment are skipped as well so following segment
+    // is paired with it's own columns.
+    fn on_ready(&mut self) {
+        if self.remaining == 0 {
+            return;
+        }
+        self.remaining -= 1;
+        if self.remaining == 0 {
+            self.columns.next = self.end;
+            if let Some(Segment { res, sync, queries }) = self.next.pop_front() {
+                self.res = res;
+                self.remaining = sync;
+                self.end = queries.end;
+            }
+        }
+    }
+
+    fn next_item(&mut self) -> PipelineItem<'_> {
+        let index = self.columns.next;
+        PipelineItem {
+            finished: false,
+            index,
+            res: &mut self.res,
+            ranges: &mut self.ranges,
+            columns: self.columns.pop_front(),
+        }
+    }
+
+    async fn recv(&mut self) -> Result<backend::Message, Error> {
+        let res = self.res.recv().await;
+        self.on_recv(res)
+    }
+
+    fn recv_blocking(&mut self) -> Result<backend::Message, Error> {
+        let res = self.res.blocking_recv();
+        self.on_recv(res)
+    }
+
+    // database error before BindComplete message belongs to the next query in pipeline. it's skipped so following
+    // queries are paired with their own columns.
+    fn on_recv(&mut self, res: Result<backend::Message, Error>) -> Result<backend::Message, Error> {
+        let res = res.map_err(|e| {
+            let index = self.columns.next;
+            let e = with_index(index, e);
+            if e.is::<DbError>() && !self.columns.is_empty() {
+                self.columns.pop_front();
+            }
+            e
+        });
+        if let Ok(backend::Message::ReadyForQuery(_)) = res {
+            self.on_ready();
+        }
+        res
+    }
 }
 
 type Ranges = Vec<Range<usize>>;
 
+// only database error is bound to position of query. other errors are returned as is.
+fn with_index(index: usize, mut e: Error) -> Error {
+    if let Some(e) = e.downcast_mut::<DbError>() {
+        e.set_pipeline_index(index);
+    }
+    e
+}
+
+// sync point in middle of pipeline with failed transaction status means remaining queries would be rejected.
+fn ready_check(body: &backend::ReadyForQueryBody) -> Result<(), Error> {
+    match TransactionStatus::from_u8(body.status()) {
+        TransactionStatus::Failed => Err(TransactionFailed.into()),
+        _ => Ok(()),
+    }
+}
+
 struct Columns<'a> {
     columns: Vec<&'a [Column]>,
     next: usize,
@@ -392,23 +679,17 @@ impl<'a> AsyncLendingIterator for PipelineStream<'a> {
 
     async fn try_next(&mut self) -> Result<Option<Self::Ok<'_>>, Self::Err> {
         loop {
-            match self.res.recv().await? {
-                backend::Message::BindComplete => {
-                    return Ok(Some(PipelineItem {
-                        finished: false,
-                        res: &mut self.res,
-                        ranges: &mut self.ranges,
-                        columns: self.columns.pop_front(),
-                    }));
-                }
+            match self.recv().await? {
+                backend::Message::BindComplete => return Ok(Some(self.next_item())),
                 backend::Message::DataRow(_) | backend::Message::CommandComplete(_) => {
                     // last PipelineItem dropped before finish. do some catch up until next
                     // item arrives.
                 }
-                backend::Message::ReadyForQuery(_) => {
+                backend::Message::ReadyForQuery(body) => {
                     if self.columns.is_empty() {
                         return Ok(None);
                     }
+                    ready_check(&body)?;
                 }
                 _ => return Err(Error::unexpected()),
             }
@@ -422,16 +703,39 @@ impl<'a> AsyncLendingIterator for PipelineStream<'a> {
     }
 }
 
+impl<'i> AsyncLendingItem<'i> for PipelineStream<'_> {
+    type Item = PipelineItem<'i>;
+
+    #[inline]
+    fn lend(ok: Self::Ok<'i>) -> Self::Item
+    where
+        Self: 'i,
+    {
+        ok
+    }
+}
+
 /// streaming item of certain query inside pipeline's [PipelineStream].
 /// impl [AsyncLendingIterator] and can be used to collect [Row] from item.
 pub struct PipelineItem<'a> {
     finished: bool,
+    index: usize,
     res: &'a mut Response,
     ranges: &'a mut Ranges,
     columns: &'a [Column],
 }
 
 impl PipelineItem<'_> {
+    /// position of this query in pipeline. starting from 0.
+    #[inline]
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    fn on_err(&self, e: Error) -> Error {
+        with_index(self.index, e)
+    }
+
     /// collect rows affected by this pipelined query. [Row] information will be ignored.
     pub async fn row_affected(mut self) -> Result<u64, Error> {
         if self.finished {
@@ -439,7 +743,7 @@ impl PipelineItem<'_> {
         }
 
         loop {
-            match self.res.recv().await? {
+            match self.res.recv().await.map_err(|e| self.on_err(e))? {
                 backend::Message::DataRow(_) => {}
                 backend::Message::CommandComplete(body) => {
                     self.finished = true;
@@ -457,7 +761,7 @@ impl PipelineItem<'_> {
         }
 
         loop {
-            match self.res.blocking_recv()? {
+            match self.res.blocking_recv().map_err(|e| self.on_err(e))? {
                 backend::Message::DataRow(_) => {}
                 backend::Message::CommandComplete(body) => {
                     self.finished = true;
@@ -478,7 +782,7 @@ impl AsyncLendingIterator for PipelineItem<'_> {
 
     async fn try_next(&mut self) -> Result<Option<Self::Ok<'_>>, Self::Err> {
         if !self.finished {
-            match self.res.recv().await? {
+            match self.res.recv().await.map_err(|e| self.on_err(e))? {
                 backend::Message::DataRow(body) => {
                     return Row::try_new(self.columns, body, self.ranges).map(Some);
                 }
@@ -490,3 +794,15 @@ impl AsyncLendingIterator for PipelineItem<'_> {
         Ok(None)
     }
 }
+
+impl<'i> AsyncLendingItem<'i> for PipelineItem<'_> {
+    type Item = Row<'i>;
+
+    #[inline]
+    fn lend(ok: Self::Ok<'i>) -> Self::Item
+    where
+        Self: 'i,
+    {
+        ok
+    }
+}
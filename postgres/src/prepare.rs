@@ -2,7 +2,7 @@ use postgres_types::{Field, Kind, Oid};
 
 use super::{
     client::Client,
-    error::{DbError, Error, SqlState},
+    error::{DbError, Error, SqlState, UnknownType},
     execute::{Execute, ExecuteBlocking},
     iter::AsyncLendingIterator,
     query::Query,
@@ -159,7 +159,21 @@ const TYPEINFO_COMPOSITE_QUERY: StatementNamed = Statement::named(
     &[],
 );
 
+// to_regtype resolves name with search_path of connection and produces NULL for unknown type.
+const TYPEINFO_NAME_QUERY: StatementNamed = Statement::named("SELECT to_regtype($1)::oid", &[]);
+
 impl Client {
+    pub(crate) async fn get_type_by_name(&self, name: &str) -> Result<Type, Error> {
+        let oid = {
+            let stmt = self.typeinfo_name_statement().await?;
+            let mut rows = stmt.bind([name]).query(self).await?;
+            let row = rows.try_next().await?.ok_or_else(Error::unexpected)?;
+            row.try_get::<Option<Oid>>(0)?
+        };
+        let oid = oid.ok_or_else(|| UnknownType(name.into()))?;
+        self._get_type(oid).await
+    }
+
     async fn get_enum_variants(&self, oid: Oid) -> Result<Vec<String>, Error> {
         let stmt = self.typeinfo_enum_statement().await?;
         let mut rows = stmt.bind([oid]).query(self).await?;
@@ -188,14 +202,17 @@ impl Client {
         if let Some(stmt) = self.typeinfo() {
             return Ok(stmt);
         }
-        let stmt = match TYPEINFO_QUERY.execute(self).await.map(|stmt| stmt.leak()) {
+        let stmt = match TYPEINFO_QUERY.execute(self).await.map(|stmt| stmt.leak().keep_open()) {
             Ok(stmt) => stmt,
             Err(e) => {
                 return if e
                     .downcast_ref::<DbError>()
                     .is_some_and(|e| SqlState::UNDEFINED_TABLE.eq(e.code()))
                 {
-                    TYPEINFO_FALLBACK_QUERY.execute(self).await.map(|stmt| stmt.leak())
+                    TYPEINFO_FALLBACK_QUERY
+                        .execute(self)
+                        .await
+                        .map(|stmt| stmt.leak().keep_open())
                 } else {
                     Err(e)
                 }
@@ -210,13 +227,16 @@ impl Client {
             return Ok(stmt);
         }
         let stmt = match TYPEINFO_ENUM_QUERY.execute(self).await {
-            Ok(stmt) => stmt.leak(),
+            Ok(stmt) => stmt.leak().keep_open(),
             Err(e) => {
                 return if e
                     .downcast_ref::<DbError>()
                     .is_some_and(|e| SqlState::UNDEFINED_COLUMN.eq(e.code()))
                 {
-                    TYPEINFO_ENUM_FALLBACK_QUERY.execute(self).await.map(|stmt| stmt.leak())
+                    TYPEINFO_ENUM_FALLBACK_QUERY
+                        .execute(self)
+                        .await
+                        .map(|stmt| stmt.leak().keep_open())
                 } else {
                     Err(e)
                 }
@@ -226,11 +246,20 @@ impl Client {
         Ok(stmt)
     }
 
+    async fn typeinfo_name_statement(&self) -> Result<Statement, Error> {
+        if let Some(stmt) = self.typeinfo_name() {
+            return Ok(stmt);
+        }
+        let stmt = TYPEINFO_NAME_QUERY.execute(self).await?.leak().keep_open();
+        self.set_typeinfo_name(&stmt);
+        Ok(stmt)
+    }
+
     async fn typeinfo_composite_statement(&self) -> Result<Statement, Error> {
         if let Some(stmt) = self.typeinfo_composite() {
             return Ok(stmt);
         }
-        let stmt = TYPEINFO_COMPOSITE_QUERY.execute(self).await?.leak();
+        let stmt = TYPEINFO_COMPOSITE_QUERY.execute(self).await?.leak().keep_open();
         self.set_typeinfo_composite(&stmt);
         Ok(stmt)
     }
@@ -266,13 +295,15 @@ impl Client {
             return Ok(stmt);
         }
         let stmt = match TYPEINFO_QUERY.execute_blocking(self) {
-            Ok(stmt) => stmt.leak(),
+            Ok(stmt) => stmt.leak().keep_open(),
             Err(e) => {
                 return if e
                     .downcast_ref::<DbError>()
                     .is_some_and(|e| SqlState::UNDEFINED_TABLE.eq(e.code()))
                 {
-                    TYPEINFO_FALLBACK_QUERY.execute_blocking(self).map(|stmt| stmt.leak())
+                    TYPEINFO_FALLBACK_QUERY
+                        .execute_blocking(self)
+                        .map(|stmt| stmt.leak().keep_open())
                 } else {
                     Err(e)
                 }
@@ -287,7 +318,7 @@ impl Client {
             return Ok(stmt);
         }
         let stmt = match TYPEINFO_ENUM_QUERY.execute_blocking(self) {
-            Ok(stmt) => stmt.leak(),
+            Ok(stmt) => stmt.leak().keep_open(),
             Err(e) => {
                 return if e
                     .downcast_ref::<DbError>()
@@ -295,7 +326,7 @@ impl Client {
                 {
                     TYPEINFO_ENUM_FALLBACK_QUERY
                         .execute_blocking(self)
-                        .map(|stmt| stmt.leak())
+                        .map(|stmt| stmt.leak().keep_open())
                 } else {
                     Err(e)
                 }
@@ -309,7 +340,7 @@ impl Client {
         if let Some(stmt) = self.typeinfo_composite() {
             return Ok(stmt);
         }
-        let stmt = TYPEINFO_COMPOSITE_QUERY.execute_blocking(self)?.leak();
+        let stmt = TYPEINFO_COMPOSITE_QUERY.execute_blocking(self)?.leak().keep_open();
         self.set_typeinfo_composite(&stmt);
         Ok(stmt)
     }
@@ -15,7 +15,7 @@ use crate::{
     column::Column,
     driver::codec::Response,
     error::Error,
-    iter::AsyncLendingIterator,
+    iter::{AsyncLendingItem, AsyncLendingIterator},
     prepare::Prepare,
     row::{marker, Row, RowOwned, RowSimple, RowSimpleOwned},
     types::Type,
@@ -56,6 +56,27 @@ impl<'a> AsyncLendingIterator for RowStream<'a> {
     }
 }
 
+impl<'i> AsyncLendingItem<'i> for RowStream<'_> {
+    type Item = Row<'i>;
+
+    #[inline]
+    fn lend(ok: Self::Ok<'i>) -> Self::Item
+    where
+        Self: 'i,
+    {
+        ok
+    }
+}
+
+impl RowStream<'_> {
+    /// collect all rows of stream into owned [`RowOwned`] that can be used after stream is dropped.
+    ///
+    /// See [`RowStreamOwned::try_collect_owned`] for detail.
+    pub async fn try_collect_owned(self) -> Result<Vec<RowOwned>, Error> {
+        RowStreamOwned::from(self).try_collect_owned().await
+    }
+}
+
 async fn try_next<'r>(
     res: &mut Response,
     col: &'r [Column],
@@ -132,6 +153,55 @@ impl AsyncLendingIterator for RowStreamOwned {
     }
 }
 
+impl<'i> AsyncLendingItem<'i> for RowStreamOwned {
+    type Item = Row<'i>;
+
+    #[inline]
+    fn lend(ok: Self::Ok<'i>) -> Self::Item
+    where
+        Self: 'i,
+    {
+        ok
+    }
+}
+
+impl RowStreamOwned {
+    /// collect all rows of stream into owned [`RowOwned`] that can be used after stream is dropped.
+    ///
+    /// Column information is shared among collected rows and every row keeps it's own raw data buffer and value
+    /// offsets. This trades additional memory allocation for the convenience of moving rows around.
+    ///
+    /// # Examples
+    /// ```
+    /// # use xitca_postgres::{Client, Error, Execute, Statement};
+    /// # async fn collect(cli: Client) -> Result<(), Error> {
+    /// let stmt = Statement::named("SELECT id, name FROM users", &[]).execute(&cli).await?;
+    /// let rows = stmt.query(&cli).await?.try_collect_owned().await?;
+    ///
+    /// // rows outlive the stream they are collected from.
+    /// for row in rows.iter() {
+    ///     let _: i32 = row.get("id");
+    ///     let _: &str = row.get("name");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn try_collect_owned(mut self) -> Result<Vec<RowOwned>, Error> {
+        let mut rows = Vec::new();
+        loop {
+            match self.res.recv().await? {
+                backend::Message::DataRow(body) => rows.push(RowOwned::try_new(self.col.clone(), body, Vec::new())?),
+                backend::Message::BindComplete
+                | backend::Message::EmptyQueryResponse
+                | backend::Message::CommandComplete(_)
+                | backend::Message::PortalSuspended => {}
+                backend::Message::ReadyForQuery(_) => return Ok(rows),
+                _ => return Err(Error::unexpected()),
+            }
+        }
+    }
+}
+
 impl IntoIterator for RowStream<'_> {
     type Item = Result<RowOwned, Error>;
     type IntoIter = RowStreamOwned;
@@ -184,7 +254,7 @@ impl AsyncLendingIterator for RowSimpleStream {
                         // where column's pg type is always assumed as Option<&str>.
                         // (no runtime pg type check so this does not really matter. it's
                         // better to keep the type consistent though)
-                        .map(|f| Ok(Column::new(f.name(), Type::TEXT)))
+                        .map(|f| Ok(Column::new(&f, Type::TEXT)))
                         .collect::<Vec<_>>()?;
                 }
                 backend::Message::DataRow(body) => {
@@ -198,6 +268,18 @@ impl AsyncLendingIterator for RowSimpleStream {
     }
 }
 
+impl<'i> AsyncLendingItem<'i> for RowSimpleStream {
+    type Item = RowSimple<'i>;
+
+    #[inline]
+    fn lend(ok: Self::Ok<'i>) -> Self::Item
+    where
+        Self: 'i,
+    {
+        ok
+    }
+}
+
 /// [`RowSimpleStreamOwned`] with static lifetime
 pub type RowSimpleStreamOwned = GenericRowStream<Arc<[Column]>, marker::NoTyped>;
 
@@ -230,7 +312,7 @@ impl Iterator for RowSimpleStreamOwned {
                 Ok(msg) => match msg {
                     backend::Message::RowDescription(body) => match body
                         .fields()
-                        .map(|f| Ok(Column::new(f.name(), Type::TEXT)))
+                        .map(|f| Ok(Column::new(&f, Type::TEXT)))
                         .collect::<Vec<_>>()
                     {
                         Ok(col) => self.col = col.into(),
@@ -286,7 +368,7 @@ where
                     let mut it = body.fields();
                     while let Some(field) = it.next()? {
                         let ty = self.cli._get_type(field.type_oid()).await?;
-                        self.col.push(Column::new(field.name(), ty));
+                        self.col.push(Column::new(&field, ty));
                     }
                 }
                 backend::Message::DataRow(body) => return Row::try_new(&self.col, body, &mut self.ranges).map(Some),
@@ -304,6 +386,21 @@ where
     }
 }
 
+impl<'i, C> AsyncLendingItem<'i> for RowStreamGuarded<'_, C>
+where
+    C: Prepare + Sync,
+{
+    type Item = Row<'i>;
+
+    #[inline]
+    fn lend(ok: Self::Ok<'i>) -> Self::Item
+    where
+        Self: 'i,
+    {
+        ok
+    }
+}
+
 pub struct RowStreamGuardedOwned<'a, C> {
     res: Response,
     col: Arc<[Column]>,
@@ -348,7 +445,7 @@ where
                             .map_err(Error::from)
                             .map(|f| {
                                 let ty = self.cli._get_type_blocking(f.type_oid())?;
-                                Ok(Column::new(f.name(), ty))
+                                Ok(Column::new(&f, ty))
                             })
                             .collect::<Vec<_>>()
                         {
@@ -3,8 +3,14 @@
 mod traits;
 mod types;
 
+#[cfg(feature = "json")]
+mod json;
+
 pub use types::{Row, RowOwned, RowSimple, RowSimpleOwned};
 
+#[cfg(feature = "json")]
+pub use json::row_to_json;
+
 // Marker types for specialized impl on row types
 pub(crate) mod marker {
     #[derive(Debug)]
@@ -0,0 +1,355 @@
+use core::{fmt::Write, ops::Range};
+
+use fallible_iterator::FallibleIterator;
+use postgres_protocol::types;
+use postgres_types::FromSql;
+use serde_json::{Map, Value};
+
+use crate::{
+    column::Column,
+    error::Error,
+    from_sql::FromSqlError,
+    types::{Kind, Type},
+};
+
+use super::{marker, types::GenericRow};
+
+/// convert a row to json object with column names as keys. column values are mapped by their postgres types:
+///
+/// - `null` value of any type is mapped to json `null`.
+/// - `bool` is mapped to json boolean.
+/// - `int2`, `int4`, `int8`, `oid`, `float4` and `float8` are mapped to json number. non finite float is mapped
+///   to json `null`.
+/// - `numeric` is mapped to json string to keep it's precision.
+/// - `text`, `varchar`, `bpchar`, `name` and other string types are mapped to json string.
+/// - `json` and `jsonb` are passed through as json value.
+/// - `timestamp`, `timestamptz`, `date` and `time` are mapped to json string in ISO 8601 format.
+/// - `uuid` is mapped to json string in hyphenated format.
+/// - `bytea` is mapped to json string in base64 encoding.
+/// - array of above types is mapped to json array. multi-dimensional array is mapped to nested json array.
+/// - other types are mapped to json string with their raw value as text when it's valid utf-8 or in base64
+///   encoding otherwise.
+///
+/// When multiple columns share the same name the value of last column is kept.
+///
+/// # Errors
+/// When column value is malformed and can't be decoded according to it's type.
+///
+/// # Examples
+/// ```rust
+/// # use xitca_postgres::{iter::AsyncLendingIterator, row::row_to_json, Client, Error, Execute, Statement};
+/// # async fn query(cli: Client) -> Result<(), Error> {
+/// // query with columns not known at compile time.
+/// let stmt = Statement::named("SELECT 1 AS id, 'foo' AS name", &[]).execute(&cli).await?;
+/// let mut stream = stmt.query(&cli).await?;
+/// while let Some(row) = stream.try_next().await? {
+///     let value = row_to_json(&row)?;
+///     assert_eq!(value["name"], "foo");
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn row_to_json<C, R>(row: &GenericRow<C, R, marker::Typed>) -> Result<Value, Error>
+where
+    C: AsRef<[Column]>,
+    R: AsRef<[Range<usize>]> + AsMut<Vec<Range<usize>>>,
+{
+    let mut map = Map::with_capacity(row.len());
+    for (idx, col) in row.columns().iter().enumerate() {
+        let Raw(raw) = row.try_get(idx)?;
+        let value = to_json(col.r#type(), raw)?;
+        map.insert(col.name().to_owned(), value);
+    }
+    Ok(Value::Object(map))
+}
+
+// raw bytes of column value of any type.
+struct Raw<'a>(Option<&'a [u8]>);
+
+impl<'a> FromSql<'a> for Raw<'a> {
+    fn from_sql(_: &Type, raw: &'a [u8]) -> Result<Self, FromSqlError> {
+        Ok(Raw(Some(raw)))
+    }
+
+    fn from_sql_null(_: &Type) -> Result<Self, FromSqlError> {
+        Ok(Raw(None))
+    }
+
+    fn accepts(_: &Type) -> bool {
+        true
+    }
+}
+
+fn to_json(ty: &Type, raw: Option<&[u8]>) -> Result<Value, FromSqlError> {
+    let Some(raw) = raw else { return Ok(Value::Null) };
+
+    if let Kind::Array(member) = ty.kind() {
+        return array_to_json(member, raw);
+    }
+
+    let value = match *ty {
+        Type::BOOL => Value::from(bool::from_sql(ty, raw)?),
+        Type::CHAR => Value::from(i8::from_sql(ty, raw)?),
+        Type::INT2 => Value::from(i16::from_sql(ty, raw)?),
+        Type::INT4 => Value::from(i32::from_sql(ty, raw)?),
+        Type::INT8 => Value::from(i64::from_sql(ty, raw)?),
+        Type::OID => Value::from(u32::from_sql(ty, raw)?),
+        Type::FLOAT4 => Value::from(f32::from_sql(ty, raw)?),
+        Type::FLOAT8 => Value::from(f64::from_sql(ty, raw)?),
+        Type::NUMERIC => Value::from(numeric(raw)?),
+        Type::JSON | Type::JSONB => Value::from_sql(ty, raw)?,
+        Type::BYTEA => Value::from(base64(raw)),
+        Type::TIMESTAMP => Value::from(timestamp(types::timestamp_from_sql(raw)?, "")),
+        Type::TIMESTAMPTZ => Value::from(timestamp(types::timestamp_from_sql(raw)?, "Z")),
+        Type::DATE => Value::from(date(types::date_from_sql(raw)?)),
+        Type::TIME => Value::from(time(types::time_from_sql(raw)?)),
+        Type::UUID => Value::from(uuid(types::uuid_from_sql(raw)?)),
+        _ if <&str as FromSql>::accepts(ty) => Value::from(<&str>::from_sql(ty, raw)?),
+        _ => match core::str::from_utf8(raw) {
+            Ok(str) => Value::from(str),
+            Err(_) => Value::from(base64(raw)),
+        },
+    };
+
+    Ok(value)
+}
+
+fn array_to_json(member: &Type, raw: &[u8]) -> Result<Value, FromSqlError> {
+    let array = types::array_from_sql(raw)?;
+    let dims = array.dimensions().map(|dim| Ok(dim.len as usize)).collect::<Vec<_>>()?;
+    let mut values = array.values().map(|raw| to_json(member, raw));
+    nest(&dims, &mut values)
+}
+
+// build nested json array from flattened values in row major order.
+fn nest<I>(dims: &[usize], values: &mut I) -> Result<Value, FromSqlError>
+where
+    I: FallibleIterator<Item = Value, Error = FromSqlError>,
+{
+    let Some((&len, rest)) = dims.split_first() else {
+        return Ok(Value::Array(Vec::new()));
+    };
+
+    let mut array = Vec::with_capacity(len);
+    for _ in 0..len {
+        let value = match rest {
+            [] => values.next()?.ok_or("array has less values than it's dimensions")?,
+            rest => nest(rest, values)?,
+        };
+        array.push(value);
+    }
+    Ok(Value::Array(array))
+}
+
+// binary format of numeric: digit count, weight of first digit, sign, display scale and digits in base 10000.
+fn numeric(raw: &[u8]) -> Result<String, FromSqlError> {
+    const NEG: u16 = 0x4000;
+    const NAN: u16 = 0xC000;
+    const PINF: u16 = 0xD000;
+    const NINF: u16 = 0xF000;
+
+    let read = |idx: usize| -> Result<u16, FromSqlError> {
+        raw.get(idx * 2..idx * 2 + 2)
+            .map(|b| u16::from_be_bytes([b[0], b[1]]))
+            .ok_or_else(|| "invalid numeric value".into())
+    };
+
+    let ndigits = read(0)? as usize;
+    let weight = read(1)? as i16 as i32;
+    let sign = read(2)?;
+    let scale = read(3)? as usize;
+
+    match sign {
+        NAN => return Ok(String::from("NaN")),
+        PINF => return Ok(String::from("Infinity")),
+        NINF => return Ok(String::from("-Infinity")),
+        _ => {}
+    }
+
+    let digits = (0..ndigits).map(|i| read(4 + i)).collect::<Result<Vec<_>, _>>()?;
+    let digit = |idx: i32| {
+        usize::try_from(idx)
+            .ok()
+            .and_then(|i| digits.get(i))
+            .copied()
+            .unwrap_or(0)
+    };
+
+    let mut out = String::new();
+    if sign == NEG {
+        out.push('-');
+    }
+
+    // integer part. digits with index 0..=weight.
+    if weight < 0 {
+        out.push('0');
+    } else {
+        for idx in 0..=weight {
+            if idx == 0 {
+                write!(out, "{}", digit(idx))?;
+            } else {
+                write!(out, "{:04}", digit(idx))?;
+            }
+        }
+    }
+
+    // fraction part truncated to display scale.
+    if scale > 0 {
+        let mut frac = String::with_capacity(scale + 4);
+        let mut idx = weight + 1;
+        while frac.len() < scale {
+            write!(frac, "{:04}", digit(idx))?;
+            idx += 1;
+        }
+        frac.truncate(scale);
+        out.push('.');
+        out.push_str(&frac);
+    }
+
+    Ok(out)
+}
+
+const USECS_PER_SEC: i64 = 1_000_000;
+const USECS_PER_DAY: i64 = 86_400 * USECS_PER_SEC;
+
+// microseconds since 2000-01-01 00:00:00.
+fn timestamp(usecs: i64, suffix: &str) -> String {
+    match usecs {
+        i64::MAX => String::from("infinity"),
+        i64::MIN => String::from("-infinity"),
+        usecs => {
+            let days = usecs.div_euclid(USECS_PER_DAY);
+            let usecs = usecs.rem_euclid(USECS_PER_DAY);
+            format!("{}T{}{suffix}", date(days as i32), time(usecs))
+        }
+    }
+}
+
+// days since 2000-01-01.
+fn date(days: i32) -> String {
+    match days {
+        i32::MAX => String::from("infinity"),
+        i32::MIN => String::from("-infinity"),
+        days => {
+            // civil from days algorithm with epoch shifted from 1970-01-01 to 2000-01-01.
+            let z = days as i64 + 10_957 + 719_468;
+            let era = z.div_euclid(146_097);
+            let doe = z.rem_euclid(146_097);
+            let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+            let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+            let mp = (5 * doy + 2) / 153;
+            let day = doy - (153 * mp + 2) / 5 + 1;
+            let month = if mp < 10 { mp + 3 } else { mp - 9 };
+            let year = yoe + era * 400 + i64::from(month <= 2);
+            format!("{year:04}-{month:02}-{day:02}")
+        }
+    }
+}
+
+// microseconds since midnight.
+fn time(usecs: i64) -> String {
+    let secs = usecs / USECS_PER_SEC;
+    let mut out = format!("{:02}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60);
+    let frac = usecs % USECS_PER_SEC;
+    if frac > 0 {
+        let _ = write!(out, ".{frac:06}");
+    }
+    out
+}
+
+fn uuid(bytes: [u8; 16]) -> String {
+    let mut out = String::with_capacity(36);
+    for (idx, b) in bytes.iter().enumerate() {
+        if matches!(idx, 4 | 6 | 8 | 10) {
+            out.push('-');
+        }
+        let _ = write!(out, "{b:02x}");
+    }
+    out
+}
+
+fn base64(raw: &[u8]) -> String {
+    const TABLE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut out = String::with_capacity(raw.len().div_ceil(3) * 4);
+    for chunk in raw.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, b)| n | u32::from(*b) << (16 - i * 8));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(TABLE[(n >> (18 - i * 6)) as usize & 0x3f] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn date_time() {
+        assert_eq!(date(0), "2000-01-01");
+        assert_eq!(date(-1), "1999-12-31");
+        assert_eq!(date(60), "2000-03-01");
+        assert_eq!(date(9_132), "2025-01-01");
+        assert_eq!(time(0), "00:00:00");
+        assert_eq!(time(45_296_000_001), "12:34:56.000001");
+        assert_eq!(timestamp(USECS_PER_DAY + 1_500_000, "Z"), "2000-01-02T00:00:01.500000Z");
+        assert_eq!(timestamp(-1, ""), "1999-12-31T23:59:59.999999");
+        assert_eq!(timestamp(i64::MAX, ""), "infinity");
+    }
+
+    #[test]
+    fn numeric_text() {
+        let encode = |ndigits: u16, weight: i16, sign: u16, scale: u16, digits: &[u16]| {
+            let mut buf = Vec::new();
+            for v in [ndigits, weight as u16, sign, scale].iter().chain(digits) {
+                buf.extend_from_slice(&v.to_be_bytes());
+            }
+            numeric(&buf).unwrap()
+        };
+
+        // 12345.678
+        assert_eq!(encode(3, 1, 0, 3, &[1, 2345, 6780]), "12345.678");
+        // -0.0012
+        assert_eq!(encode(1, -1, 0x4000, 4, &[12]), "-0.0012");
+        // 10000
+        assert_eq!(encode(1, 1, 0, 0, &[1]), "10000");
+        assert_eq!(encode(0, 0, 0, 2, &[]), "0.00");
+        assert_eq!(encode(0, 0, 0xC000, 0, &[]), "NaN");
+    }
+
+    #[test]
+    fn encode_text() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+        assert_eq!(
+            uuid(0x0011_2233_4455_6677_8899_aabb_ccdd_eeffu128.to_be_bytes()),
+            "00112233-4455-6677-8899-aabbccddeeff"
+        );
+    }
+
+    #[test]
+    fn to_json_value() {
+        assert_eq!(to_json(&Type::INT4, None).unwrap(), Value::Null);
+        assert_eq!(to_json(&Type::INT4, Some(&996i32.to_be_bytes()[..])).unwrap(), 996);
+        assert_eq!(to_json(&Type::TEXT, Some(&b"foo"[..])).unwrap(), "foo");
+        assert_eq!(to_json(&Type::BYTEA, Some(&b"foo"[..])).unwrap(), "Zm9v");
+        assert_eq!(
+            to_json(&Type::FLOAT8, Some(&f64::NAN.to_be_bytes()[..])).unwrap(),
+            Value::Null
+        );
+        assert_eq!(
+            to_json(&Type::JSONB, Some(&b"\x01{\"a\":1}"[..])).unwrap(),
+            serde_json::json!({ "a": 1 })
+        );
+    }
+}
@@ -83,6 +83,31 @@ where
         self.columns.as_ref()
     }
 
+    /// Returns the index of column with given name. Name is matched the same way as [`Self::get`] does when it's
+    /// called with column name.
+    ///
+    /// Looking up column by name scans all columns of the row. For wide rows fetched in tight loop the index can be
+    /// resolved once and reused for every row sharing the same columns.
+    ///
+    /// # Examples
+    /// ```
+    /// # use xitca_postgres::{iter::AsyncLendingIterator, Client, Error, Execute, Statement};
+    /// # async fn index_of(cli: Client) -> Result<(), Error> {
+    /// let stmt = Statement::named("SELECT id, name FROM users", &[]).execute(&cli).await?;
+    /// let mut stream = stmt.query(&cli).await?;
+    /// let mut name_idx = None;
+    /// while let Some(row) = stream.try_next().await? {
+    ///     // resolve index from the first row and reuse it for the following ones.
+    ///     let idx = *name_idx.get_or_insert_with(|| row.index_of("name").unwrap());
+    ///     let _name: &str = row.get(idx);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn index_of(&self, name: &str) -> Option<usize> {
+        name._from_columns(self.columns()).map(|(idx, _)| idx)
+    }
+
     /// Determines if the row contains no values.
     #[inline]
     pub fn is_empty(&self) -> bool {
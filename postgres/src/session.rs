@@ -10,7 +10,7 @@ use postgres_protocol::{
 use xitca_io::{bytes::BytesMut, io::AsyncIo};
 
 use super::{
-    config::{Config, SslMode, SslNegotiation},
+    config::{Config, SslMode, SslNegotiation, SslRootCert},
     driver::generic::GenericDriver,
     error::{ConfigError, Error},
 };
@@ -40,14 +40,16 @@ pub(crate) struct ConnectInfo {
     pub(crate) addr: Addr,
     pub(crate) ssl_mode: SslMode,
     pub(crate) ssl_negotiation: SslNegotiation,
+    pub(crate) ssl_root_cert: Option<SslRootCert>,
 }
 
 impl ConnectInfo {
-    pub(crate) fn new(addr: Addr, ssl_mode: SslMode, ssl_negotiation: SslNegotiation) -> Self {
+    pub(crate) fn new(addr: Addr, cfg: &Config) -> Self {
         Self {
             addr,
-            ssl_mode,
-            ssl_negotiation,
+            ssl_mode: cfg.get_ssl_mode(),
+            ssl_negotiation: cfg.get_ssl_negotiation(),
+            ssl_root_cert: cfg.ssl_root_cert.clone(),
         }
     }
 }
@@ -15,9 +15,31 @@ use super::{
     BoxedFuture,
 };
 
-pub use builder::TransactionBuilder;
+pub use builder::{IsolationLevel, TransactionBuilder};
 pub use portal::Portal;
 
+/// transaction status of connection reported by database on every sync point.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TransactionStatus {
+    /// not in a transaction block.
+    Idle,
+    /// in a transaction block.
+    InTransaction,
+    /// in a failed transaction block. queries are rejected by database until the transaction block is ended
+    /// with `ROLLBACK`.
+    Failed,
+}
+
+impl TransactionStatus {
+    pub(crate) const fn from_u8(status: u8) -> Self {
+        match status {
+            b'T' => Self::InTransaction,
+            b'E' => Self::Failed,
+            _ => Self::Idle,
+        }
+    }
+}
+
 pub struct Transaction<'a, C>
 where
     C: Prepare + ClientBorrowMut,
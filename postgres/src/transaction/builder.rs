@@ -1,4 +1,18 @@
-use crate::{client::ClientBorrowMut, error::Error, execute::Execute, prepare::Prepare, query::Query};
+use core::{
+    hash::{BuildHasher, Hasher},
+    time::Duration,
+};
+
+use std::collections::hash_map::RandomState;
+
+use crate::{
+    client::ClientBorrowMut,
+    error::{DbError, Error, SqlState},
+    execute::Execute,
+    prepare::Prepare,
+    query::Query,
+    BoxedFuture,
+};
 
 use super::Transaction;
 
@@ -38,18 +52,23 @@ impl IsolationLevel {
 }
 
 /// A builder for database transactions.
+#[derive(Debug, Clone, Copy)]
 pub struct TransactionBuilder {
     isolation_level: Option<IsolationLevel>,
     read_only: Option<bool>,
     deferrable: Option<bool>,
+    max_retries: u32,
 }
 
+const DEFAULT_MAX_RETRIES: u32 = 3;
+
 impl TransactionBuilder {
     pub(crate) fn new() -> Self {
         Self {
             isolation_level: None,
             read_only: None,
             deferrable: None,
+            max_retries: DEFAULT_MAX_RETRIES,
         }
     }
 
@@ -75,6 +94,13 @@ impl TransactionBuilder {
         self
     }
 
+    /// Sets the max count of retries after the first attempt for [`TransactionBuilder::retry`].
+    /// Default to 3.
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
     /// Begins the transaction.
     ///
     /// The transaction will roll back by default - use the `commit` method to commit it.
@@ -85,12 +111,81 @@ impl TransactionBuilder {
         // marker check to ensure exclusive borrowing Client. see ClientBorrowMut for detail
         let _c = cli._borrow_mut();
 
+        self.query().as_str().execute(cli).await.map(|_| Transaction::new(cli))
+    }
+
+    /// Runs given closure inside a transaction and commits it when closure returns successfully. The transaction is
+    /// rolled back and retried from the start when closure or commit fails with serialization failure(SQLSTATE
+    /// `40001`) or deadlock(SQLSTATE `40P01`). Retries are delayed with exponential backoff and bounded by
+    /// [`TransactionBuilder::max_retries`]. Error is returned as is when it's not retryable or retries are exhausted.
+    ///
+    /// Retry is performed on the whole transaction rather than rolling back to a save point because a transaction
+    /// with serialization failure can not make progress with it's current snapshot.
+    ///
+    /// # Idempotency
+    /// The closure can be called multiple times and only the database side effects of the last call are committed.
+    /// It must not have side effects outside of database (sending email, mutating shared memory etc) or such side
+    /// effects must be idempotent.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use xitca_postgres::{error::Error, transaction::{IsolationLevel, Transaction}, Client, Execute};
+    /// async fn transfer(cli: &mut Client) -> Result<u64, Error> {
+    ///     Transaction::<Client>::builder()
+    ///         .isolation_level(IsolationLevel::Serializable)
+    ///         .retry(cli, |tx| {
+    ///             Box::pin(async move {
+    ///                 "UPDATE account SET balance = balance - 1 WHERE id = 1".execute(&*tx).await?;
+    ///                 "UPDATE account SET balance = balance + 1 WHERE id = 2".execute(&*tx).await
+    ///             })
+    ///         })
+    ///         .await
+    /// }
+    /// ```
+    pub async fn retry<C, F, T>(self, cli: &mut C, mut func: F) -> Result<T, Error>
+    where
+        C: Prepare + Query + ClientBorrowMut,
+        F: for<'t, 'c> FnMut(&'t mut Transaction<'c, C>) -> BoxedFuture<'t, Result<T, Error>>,
+    {
+        // marker check to ensure exclusive borrowing Client. see ClientBorrowMut for detail
+        let _c = cli._borrow_mut();
+
+        let query = self.query();
+        let mut retries = 0;
+
+        loop {
+            let res = async {
+                query.as_str().execute(cli).await?;
+                let mut tx = Transaction::new(cli);
+                match func(&mut tx).await {
+                    Ok(t) => tx.commit().await.map(|_| t),
+                    Err(e) => {
+                        // rollback error is ignored. connection level error would surface in next attempt.
+                        let _ = tx.rollback().await;
+                        Err(e)
+                    }
+                }
+            }
+            .await;
+
+            match res {
+                Err(e) if retries < self.max_retries && is_retryable(&e) => {
+                    retries += 1;
+                    tokio::time::sleep(backoff(retries)).await;
+                }
+                res => return res,
+            }
+        }
+    }
+
+    fn query(self) -> String {
         let mut query = String::from("START TRANSACTION");
 
         let Self {
             isolation_level,
             read_only,
             deferrable,
+            ..
         } = self;
 
         if let Some(isolation_level) = isolation_level {
@@ -111,6 +206,24 @@ impl TransactionBuilder {
             query.pop();
         }
 
-        query.as_str().execute(cli).await.map(|_| Transaction::new(cli))
+        query
     }
 }
+
+fn is_retryable(e: &Error) -> bool {
+    e.downcast_ref::<DbError>().is_some_and(|e| {
+        let code = e.code();
+        *code == SqlState::T_R_SERIALIZATION_FAILURE || *code == SqlState::T_R_DEADLOCK_DETECTED
+    })
+}
+
+// exponential backoff starting from 10ms and capped at 1s. a random jitter up to the same amount is added so
+// conflicting transactions don't retry in lockstep.
+fn backoff(retries: u32) -> Duration {
+    const BASE: u64 = 10;
+    const CAP: u64 = 1000;
+
+    let millis = BASE.saturating_mul(1 << retries.min(16)).min(CAP);
+    let jitter = RandomState::new().build_hasher().finish() % (millis + 1);
+    Duration::from_millis(millis + jitter)
+}
@@ -1,6 +1,81 @@
 //! Types.
 //!
-//! This module is a reexport of the `postgres_types` crate.
+//! This module is a reexport of the `postgres_types` crate with additional type mappings.
+//!
+//! Mappings of third party crates are guarded by crate features:
+//! - `chrono`: `timestamp`, `timestamptz`, `date` and `time` with `chrono` types.
+//! - `time`: `timestamp`, `timestamptz`, `date` and `time` with `time` types.
+//! - `uuid`: `uuid` with `uuid::Uuid` type.
+//! - `json`: `json` and `jsonb` with `serde_json::Value` and typed [Json] wrapper.
+//! - `decimal`: `numeric` with [Decimal] type.
+//!
+//! `interval` is mapped to [Interval] type and it can be converted from/to duration types of `chrono` and `time`
+//! crates when according feature is enabled.
+
+mod interval;
 
 #[doc(inline)]
 pub use postgres_types::*;
+
+pub use interval::{Interval, IntervalOutOfRange};
+
+#[cfg(feature = "decimal")]
+pub use rust_decimal::Decimal;
+
+#[cfg(test)]
+mod test {
+    use core::fmt::Debug;
+
+    use xitca_io::bytes::BytesMut;
+
+    use super::*;
+
+    pub(super) fn round_trip<T>(ty: Type, value: T)
+    where
+        T: ToSql + for<'a> FromSql<'a> + PartialEq + Debug,
+    {
+        assert!(<T as ToSql>::accepts(&ty));
+        assert!(<T as FromSql>::accepts(&ty));
+        let mut buf = BytesMut::new();
+        value.to_sql_checked(&ty, &mut buf).unwrap();
+        assert_eq!(T::from_sql(&ty, &buf).unwrap(), value);
+    }
+
+    #[cfg(feature = "uuid")]
+    #[test]
+    fn uuid() {
+        round_trip(Type::UUID, uuid::Uuid::from_u128(996_251));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn json() {
+        let value = serde_json::json!({ "name": "arisu", "age": 14 });
+        round_trip(Type::JSON, value.clone());
+        round_trip(Type::JSONB, value);
+    }
+
+    #[cfg(feature = "decimal")]
+    #[test]
+    fn decimal() {
+        round_trip(Type::NUMERIC, Decimal::new(-31_415_926, 7));
+        round_trip(Type::NUMERIC, Decimal::ZERO);
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn chrono() {
+        let time = chrono::DateTime::from_timestamp(1_700_000_000, 123_000).unwrap();
+        round_trip(Type::TIMESTAMPTZ, time);
+        round_trip(Type::TIMESTAMP, time.naive_utc());
+        round_trip(Type::DATE, time.date_naive());
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn time() {
+        let time = time::OffsetDateTime::from_unix_timestamp(1_700_000_000).unwrap();
+        round_trip(Type::TIMESTAMPTZ, time);
+        round_trip(Type::DATE, time.date());
+    }
+}
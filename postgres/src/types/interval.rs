@@ -0,0 +1,191 @@
+use core::fmt;
+
+use std::error;
+
+use postgres_types::{accepts, to_sql_checked, FromSql, IsNull, ToSql, Type};
+use xitca_io::bytes::{BufMut, BytesMut};
+
+#[cfg(any(feature = "chrono", feature = "time"))]
+const USECS_PER_DAY: i64 = 24 * 60 * 60 * 1_000_000;
+
+/// postgres `interval` type.
+///
+/// interval is stored as separate months, days and microseconds components on database server side and
+/// they are kept as is by this type as the length of month and day can vary.
+///
+/// # Examples
+/// ```rust
+/// # use xitca_postgres::types::Interval;
+/// // 1 month, 2 days and 3 seconds.
+/// let interval = Interval::new(1, 2, 3_000_000);
+/// assert_eq!(interval.months(), 1);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Interval {
+    months: i32,
+    days: i32,
+    microseconds: i64,
+}
+
+impl Interval {
+    /// construct interval from months, days and microseconds components.
+    pub const fn new(months: i32, days: i32, microseconds: i64) -> Self {
+        Self {
+            months,
+            days,
+            microseconds,
+        }
+    }
+
+    /// months component of interval.
+    pub const fn months(&self) -> i32 {
+        self.months
+    }
+
+    /// days component of interval.
+    pub const fn days(&self) -> i32 {
+        self.days
+    }
+
+    /// microseconds component of interval.
+    pub const fn microseconds(&self) -> i64 {
+        self.microseconds
+    }
+
+    // total microseconds of interval treating every day as 24 hours. interval with months component can not be
+    // converted as a month does not have fixed length.
+    #[cfg(any(feature = "chrono", feature = "time"))]
+    fn total_microseconds(&self) -> Result<i64, IntervalOutOfRange> {
+        if self.months != 0 {
+            return Err(IntervalOutOfRange);
+        }
+        i64::from(self.days)
+            .checked_mul(USECS_PER_DAY)
+            .and_then(|days| days.checked_add(self.microseconds))
+            .ok_or(IntervalOutOfRange)
+    }
+
+    #[cfg(any(feature = "chrono", feature = "time"))]
+    fn from_microseconds(microseconds: i64) -> Result<Self, IntervalOutOfRange> {
+        // days are split out so interval is displayed in conventional form on database server side.
+        let days = i32::try_from(microseconds / USECS_PER_DAY).map_err(|_| IntervalOutOfRange)?;
+        Ok(Self::new(0, days, microseconds % USECS_PER_DAY))
+    }
+}
+
+/// error type for conversion between [Interval] and duration types.
+///
+/// produced when interval has non zero months component or when the value overflows target type.
+#[derive(Debug)]
+pub struct IntervalOutOfRange;
+
+impl fmt::Display for IntervalOutOfRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("interval out of range of target type")
+    }
+}
+
+impl error::Error for IntervalOutOfRange {}
+
+impl ToSql for Interval {
+    fn to_sql(&self, _: &Type, out: &mut BytesMut) -> Result<IsNull, Box<dyn error::Error + Sync + Send>> {
+        out.put_i64(self.microseconds);
+        out.put_i32(self.days);
+        out.put_i32(self.months);
+        Ok(IsNull::No)
+    }
+
+    accepts!(INTERVAL);
+
+    to_sql_checked!();
+}
+
+impl<'a> FromSql<'a> for Interval {
+    fn from_sql(_: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn error::Error + Sync + Send>> {
+        let raw = <&[u8; 16]>::try_from(raw).map_err(|_| "invalid message length: interval size mismatch")?;
+        let (micro, rest) = raw.split_at(8);
+        let (days, months) = rest.split_at(4);
+        Ok(Self {
+            microseconds: i64::from_be_bytes(micro.try_into().unwrap()),
+            days: i32::from_be_bytes(days.try_into().unwrap()),
+            months: i32::from_be_bytes(months.try_into().unwrap()),
+        })
+    }
+
+    accepts!(INTERVAL);
+}
+
+#[cfg(feature = "chrono")]
+const _: () = {
+    impl TryFrom<Interval> for chrono::TimeDelta {
+        type Error = IntervalOutOfRange;
+
+        fn try_from(interval: Interval) -> Result<Self, Self::Error> {
+            interval.total_microseconds().map(chrono::TimeDelta::microseconds)
+        }
+    }
+
+    impl TryFrom<chrono::TimeDelta> for Interval {
+        type Error = IntervalOutOfRange;
+
+        fn try_from(delta: chrono::TimeDelta) -> Result<Self, Self::Error> {
+            delta
+                .num_microseconds()
+                .ok_or(IntervalOutOfRange)
+                .and_then(Interval::from_microseconds)
+        }
+    }
+};
+
+#[cfg(feature = "time")]
+const _: () = {
+    impl TryFrom<Interval> for time::Duration {
+        type Error = IntervalOutOfRange;
+
+        fn try_from(interval: Interval) -> Result<Self, Self::Error> {
+            interval.total_microseconds().map(time::Duration::microseconds)
+        }
+    }
+
+    impl TryFrom<time::Duration> for Interval {
+        type Error = IntervalOutOfRange;
+
+        fn try_from(duration: time::Duration) -> Result<Self, Self::Error> {
+            i64::try_from(duration.whole_microseconds())
+                .map_err(|_| IntervalOutOfRange)
+                .and_then(Interval::from_microseconds)
+        }
+    }
+};
+
+#[cfg(test)]
+mod test {
+    use super::{super::test::round_trip, *};
+
+    #[test]
+    fn interval() {
+        round_trip(Type::INTERVAL, Interval::new(1, -2, 3_000_000));
+        round_trip(Type::INTERVAL, Interval::new(i32::MIN, i32::MAX, i64::MIN));
+        assert!(Interval::from_sql(&Type::INTERVAL, &[0; 15]).is_err());
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn chrono() {
+        let delta = chrono::TimeDelta::days(3) + chrono::TimeDelta::microseconds(-7);
+        let interval = Interval::try_from(delta).unwrap();
+        assert_eq!(interval, Interval::new(0, 2, USECS_PER_DAY - 7));
+        assert_eq!(chrono::TimeDelta::try_from(interval).unwrap(), delta);
+        assert!(chrono::TimeDelta::try_from(Interval::new(1, 0, 0)).is_err());
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn time() {
+        let duration = time::Duration::days(3) + time::Duration::microseconds(-7);
+        let interval = Interval::try_from(duration).unwrap();
+        assert_eq!(interval, Interval::new(0, 2, USECS_PER_DAY - 7));
+        assert_eq!(time::Duration::try_from(interval).unwrap(), duration);
+        assert!(time::Duration::try_from(Interval::new(1, 0, 0)).is_err());
+    }
+}
@@ -10,10 +10,44 @@ use crate::{
     server::{IntoServiceObj, Server, ServerFuture, ServiceObj},
 };
 
+/// Runtime model of server workers. Set with [`Builder::runtime_model`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum RuntimeModel {
+    /// Every worker thread runs it's own single threaded runtime. IO driver, timer and blocking task thread pool
+    /// are not shared between workers.
+    #[default]
+    ThreadPerCore,
+    /// All worker threads share one multi-threaded runtime. IO driver, timer and blocking task thread pool are
+    /// shared and `Send` tasks spawned with [`tokio::spawn`] are balanced across workers.
+    ///
+    /// Not supported with `io-uring` feature and [`Builder::build`] would return error.
+    Shared,
+}
+
+/// Builder for configuring and starting server.
+///
+/// # Runtime model
+/// Accepting connections always happens on a small multi-threaded runtime with [`Builder::server_threads`] threads.
+/// Connections are served on [`Builder::worker_threads`] worker threads and how worker threads are driven is decided
+/// by [`RuntimeModel`]:
+/// - [`RuntimeModel::ThreadPerCore`](default): every worker runs it's own single threaded runtime.
+/// - [`RuntimeModel::Shared`]: every worker drives a shared multi-threaded runtime.
+///
+/// In both models service factory is called once on every worker and the service it produced only lives on that
+/// worker. Service and it's futures are not required to be `Send` and connection tasks are spawned with
+/// [`tokio::task::spawn_local`] on the worker accepted them. Therefore state constructed by service factory can be
+/// `!Send`(for example one database connection per worker) regardless of runtime model. State shared between
+/// workers must be constructed outside of factory and wrapped in thread safe smart pointer like
+/// [`Arc`](std::sync::Arc).
+///
+/// [`RuntimeModel::Shared`] is useful when application offloads `Send` tasks with [`tokio::spawn`] or uses resources
+/// bound to the runtime they are created in(for example a connection pool shared by workers). With thread per core
+/// model these resources would be tied to the worker runtime creating them.
 pub struct Builder {
     pub(crate) server_threads: usize,
     pub(crate) worker_threads: usize,
     pub(crate) worker_max_blocking_threads: usize,
+    pub(crate) runtime_model: RuntimeModel,
     pub(crate) listeners: HashMap<String, Vec<Box<dyn AsListener>>>,
     pub(crate) factories: HashMap<String, ServiceObj>,
     pub(crate) enable_signal: bool,
@@ -35,6 +69,7 @@ impl Builder {
             server_threads: 1,
             worker_threads: std::thread::available_parallelism().map(|size| size.get()).unwrap_or(1),
             worker_max_blocking_threads: 512,
+            runtime_model: RuntimeModel::ThreadPerCore,
             listeners: HashMap::new(),
             factories: HashMap::new(),
             enable_signal: true,
@@ -71,7 +106,8 @@ impl Builder {
 
     /// Set max number of threads for each worker's blocking task thread pool.
     ///
-    /// One thread pool is set up **per worker**; not shared across workers.
+    /// One thread pool is set up **per worker**; not shared across workers. With [`RuntimeModel::Shared`] one thread
+    /// pool is set up for the shared runtime.
     ///
     /// # Examples:
     /// ```
@@ -89,6 +125,23 @@ impl Builder {
         self
     }
 
+    /// Set runtime model of worker threads.
+    ///
+    /// Default set to [`RuntimeModel::ThreadPerCore`]. See [`Builder`]'s doc for detail.
+    ///
+    /// # Examples:
+    /// ```
+    /// # use xitca_server::{Builder, RuntimeModel};
+    /// let builder = Builder::new()
+    ///     .worker_threads(4) // shared runtime has 4 worker threads.
+    ///     .worker_max_blocking_threads(4) // shared runtime has 4 max blocking threads.
+    ///     .runtime_model(RuntimeModel::Shared);
+    /// ```
+    pub fn runtime_model(mut self, model: RuntimeModel) -> Self {
+        self.runtime_model = model;
+        self
+    }
+
     /// Disable signal listening.
     /// Server would only be shutdown from [ServerHandle](crate::server::ServerHandle)
     pub fn disable_signal(mut self) -> Self {
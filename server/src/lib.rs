@@ -8,8 +8,9 @@ mod signals;
 mod worker;
 
 pub mod net;
+pub mod shutdown;
 
-pub use builder::Builder;
+pub use builder::{Builder, RuntimeModel};
 pub use server::{ServerFuture, ServerHandle};
 
 #[cfg(all(not(target_os = "linux"), feature = "io-uring"))]
@@ -27,4 +28,44 @@ mod test {
             .listen("test", listener, fn_service(|_: TcpStream| async { Ok::<_, ()>(()) }))
             .build();
     }
+
+    #[test]
+    fn shared_runtime() {
+        use std::io::{Read, Write};
+
+        let listener = std::net::TcpListener::bind("localhost:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let mut server = crate::builder::Builder::new()
+            .worker_threads(2)
+            .runtime_model(crate::RuntimeModel::Shared)
+            .disable_signal()
+            .listen(
+                "test",
+                listener,
+                fn_service(|stream: TcpStream| async move {
+                    // Send task is spawned on shared runtime's thread pool.
+                    let name = tokio::spawn(async { std::thread::current().name().map(String::from) })
+                        .await
+                        .unwrap()
+                        .unwrap_or_default();
+                    let mut stream = stream.into_std().unwrap();
+                    stream.set_nonblocking(false).unwrap();
+                    stream.write_all(name.as_bytes()).unwrap();
+                    Ok::<_, ()>(())
+                }),
+            )
+            .build();
+
+        let handle = server.handle().unwrap();
+        let thread = std::thread::spawn(move || server.wait());
+
+        let mut stream = std::net::TcpStream::connect(addr).unwrap();
+        let mut name = String::new();
+        stream.read_to_string(&mut name).unwrap();
+        assert_eq!(name, "xitca-server-worker-shared");
+
+        handle.stop(false);
+        thread.join().unwrap().unwrap();
+    }
 }
@@ -22,6 +22,9 @@ use tokio::{
 
 use crate::{builder::Builder, worker};
 
+#[cfg(not(target_family = "wasm"))]
+use crate::builder::RuntimeModel;
+
 pub struct Server {
     is_graceful_shutdown: Arc<AtomicBool>,
     tx_cmd: UnboundedSender<Command>,
@@ -92,6 +95,7 @@ impl Server {
             server_threads,
             worker_threads,
             worker_max_blocking_threads,
+            runtime_model,
             listeners,
             factories,
             shutdown_timeout,
@@ -99,6 +103,27 @@ impl Server {
             ..
         } = builder;
 
+        #[cfg(feature = "io-uring")]
+        if runtime_model == RuntimeModel::Shared {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "RuntimeModel::Shared can not be used with io-uring feature",
+            ));
+        }
+
+        // shared runtime driven by all worker threads.
+        let shared_rt = match runtime_model {
+            RuntimeModel::ThreadPerCore => None,
+            RuntimeModel::Shared => Some(
+                tokio::runtime::Builder::new_multi_thread()
+                    .enable_all()
+                    .worker_threads(worker_threads)
+                    .max_blocking_threads(worker_max_blocking_threads)
+                    .thread_name("xitca-server-worker-shared")
+                    .build()?,
+            ),
+        };
+
         let rt = tokio::runtime::Builder::new_multi_thread()
             .enable_all()
             // This worker threads is only used for accepting connections.
@@ -132,6 +157,8 @@ impl Server {
 
                 // TODO: wait for startup error(including panic) and return as io::Error on call site.
                 // currently the error only show when shared scope thread is joined with handle.
+                let shared_rt = shared_rt.as_ref();
+
                 thread::scope(|scope| {
                     for idx in 0..worker_threads {
                         let thread = thread::Builder::new().name(format!("xitca-server-worker-{idx}"));
@@ -156,21 +183,29 @@ impl Server {
                         };
 
                         #[cfg(not(feature = "io-uring"))]
-                        {
-                            let rt = tokio::runtime::Builder::new_current_thread()
-                                .enable_all()
-                                .max_blocking_threads(worker_max_blocking_threads)
-                                .build()?;
-
-                            thread.spawn_scoped(scope, move || {
-                                rt.block_on(tokio::task::LocalSet::new().run_until(task()))
-                            })?;
+                        match shared_rt {
+                            // worker drives shared runtime and runs it's local tasks on current thread.
+                            Some(rt) => {
+                                thread.spawn_scoped(scope, move || {
+                                    rt.block_on(tokio::task::LocalSet::new().run_until(task()))
+                                })?;
+                            }
+                            None => {
+                                let rt = tokio::runtime::Builder::new_current_thread()
+                                    .enable_all()
+                                    .max_blocking_threads(worker_max_blocking_threads)
+                                    .build()?;
+
+                                thread.spawn_scoped(scope, move || {
+                                    rt.block_on(tokio::task::LocalSet::new().run_until(task()))
+                                })?;
+                            }
                         }
 
                         #[cfg(feature = "io-uring")]
                         {
                             thread.spawn_scoped(scope, move || {
-                                let _ = worker_max_blocking_threads;
+                                let _ = (worker_max_blocking_threads, shared_rt);
                                 tokio_uring::start(task())
                             })?;
                         }
@@ -0,0 +1,66 @@
+//! Shutdown signal for services running on server worker threads.
+//!
+//! When server starts to stop (either by [ServerHandle::stop](crate::ServerHandle::stop) or os signal) every worker
+//! stops accepting new connections and then fires the signal on it's thread. From this point connections already
+//! accepted are given the remaining time of [Builder::shutdown_timeout](crate::Builder::shutdown_timeout) (the grace
+//! deadline) to finish. Connections still alive after the deadline are force dropped.
+//!
+//! Long lived connections like long-polling and streaming responses would hold the worker until the deadline if they
+//! are unaware of shutdown. Awaiting [on_shutdown] in these services and ending them early makes graceful shutdown
+//! finish as soon as possible.
+
+use core::future::Future;
+
+use tokio::sync::watch;
+
+thread_local! {
+    static SHUTDOWN: watch::Sender<bool> = watch::channel(false).0;
+}
+
+/// Returns a future resolving when the worker of current thread starts shutdown.
+///
+/// The future resolves immediately if shutdown has already started. It never resolves when called outside of server
+/// worker threads unless the thread is about to exit.
+///
+/// # Note
+/// The signal fires for both graceful and force shutdown. For force shutdown connections are dropped right after the
+/// signal and services would not have a chance to act on it.
+pub fn on_shutdown() -> impl Future<Output = ()> + 'static {
+    let mut rx = SHUTDOWN.with(|tx| tx.subscribe());
+    async move {
+        // sender dropped means the thread is exiting which is treated as shutdown.
+        let _ = rx.wait_for(|shutdown| *shutdown).await;
+    }
+}
+
+/// Returns true if the worker of current thread has started shutdown.
+pub fn is_shutting_down() -> bool {
+    SHUTDOWN.with(|tx| *tx.borrow())
+}
+
+pub(crate) fn notify() {
+    SHUTDOWN.with(|tx| {
+        tx.send_replace(true);
+    });
+}
+
+#[cfg(test)]
+mod test {
+    use xitca_unsafe_collection::futures::NowOrPanic;
+
+    use super::*;
+
+    #[test]
+    fn signal() {
+        std::thread::spawn(|| {
+            let mut fut = on_shutdown();
+            assert!(!is_shutting_down());
+            notify();
+            assert!(is_shutting_down());
+            fut.now_or_panic();
+            on_shutdown().now_or_panic();
+        })
+        .join()
+        .unwrap();
+    }
+}
@@ -66,6 +66,9 @@ pub(crate) async fn wait_for_stop(
             .unwrap_or_else(|e| with_worker_name_str(|name| error!("{name} exit on error: {e}")));
     }
 
+    // accept loops are stopped. notify services of current thread before waiting for them to finish.
+    crate::shutdown::notify();
+
     shutdown_handle.shutdown().await;
 }
 
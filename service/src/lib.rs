@@ -46,7 +46,7 @@ pub mod ready;
 
 pub use self::{
     async_fn::AsyncFn,
-    pipeline::{EnclosedBuilder, EnclosedFnBuilder, MapBuilder, MapErrorBuilder},
+    pipeline::{EnclosedBuilder, EnclosedFnBuilder, EnclosedGuardFnBuilder, MapBuilder, MapErrorBuilder},
     service::{fn_build, fn_service, FnService, Service, ServiceExt},
 };
 
@@ -0,0 +1,45 @@
+use core::ops::ControlFlow;
+
+use crate::{
+    async_fn,
+    pipeline::{marker, PipelineT},
+    service::Service,
+};
+
+/// transform given async function to middleware that decide if request should be passed to the service it
+/// enclosed upon through [ServiceExt::enclosed] API.
+///
+/// the function receives mutable reference of request. returning [ControlFlow::Continue] passes the request to
+/// inner service and [ControlFlow::Break] short-circuits with it's output without calling inner service.
+///
+/// [ServiceExt::enclosed]: crate::service::ServiceExt::enclosed
+pub struct GuardFn<F>(pub F);
+
+impl<S, E, F> Service<Result<S, E>> for GuardFn<F>
+where
+    F: Clone,
+{
+    type Response = PipelineT<S, F, marker::GuardFn>;
+    type Error = E;
+
+    async fn call(&self, arg: Result<S, E>) -> Result<Self::Response, Self::Error> {
+        arg.map(|service| PipelineT::new(service, self.0.clone()))
+    }
+}
+
+impl<S, Req, F> Service<Req> for PipelineT<S, F, marker::GuardFn>
+where
+    S: Service<Req>,
+    F: for<'r> async_fn::AsyncFn<(&'r mut Req,), Output = ControlFlow<Result<S::Response, S::Error>>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+
+    #[inline]
+    async fn call(&self, mut req: Req) -> Result<Self::Response, Self::Error> {
+        match self.second.call((&mut req,)).await {
+            ControlFlow::Continue(_) => self.first.call(req).await,
+            ControlFlow::Break(res) => res,
+        }
+    }
+}
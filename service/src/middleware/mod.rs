@@ -4,8 +4,10 @@
 
 mod async_fn;
 mod group;
+mod guard_fn;
 mod unchecked_ready;
 
 pub use async_fn::AsyncFn;
 pub use group::Group;
+pub use guard_fn::GuardFn;
 pub use unchecked_ready::UncheckedReady;
@@ -8,3 +8,4 @@ pub struct BuildAndThen;
 pub struct AndThen;
 pub struct BuildEnclosed;
 pub struct AsyncFn;
+pub struct GuardFn;
@@ -11,6 +11,9 @@ pub use r#struct::Pipeline as PipelineT;
 /// Type alias for specialized [PipelineT] type.
 pub type EnclosedFnBuilder<F, S> = PipelineT<F, crate::middleware::AsyncFn<S>, marker::BuildEnclosed>;
 
+/// Type alias for specialized [PipelineT] type.
+pub type EnclosedGuardFnBuilder<F, S> = PipelineT<F, crate::middleware::GuardFn<S>, marker::BuildEnclosed>;
+
 /// Type alias for specialized [PipelineT] type.
 pub type EnclosedBuilder<F, S> = PipelineT<F, S, marker::BuildEnclosed>;
 
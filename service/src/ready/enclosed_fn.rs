@@ -1,4 +1,7 @@
-use crate::pipeline::{marker::AsyncFn, PipelineT};
+use crate::pipeline::{
+    marker::{AsyncFn, GuardFn},
+    PipelineT,
+};
 
 use super::ReadyService;
 
@@ -13,3 +16,15 @@ where
         self.first.ready().await
     }
 }
+
+impl<S, T> ReadyService for PipelineT<S, T, GuardFn>
+where
+    S: ReadyService,
+{
+    type Ready = S::Ready;
+
+    #[inline]
+    async fn ready(&self) -> Self::Ready {
+        self.first.ready().await
+    }
+}
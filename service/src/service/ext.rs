@@ -26,6 +26,22 @@ pub trait ServiceExt<Arg>: Service<Arg> {
         self.enclosed(middleware::AsyncFn(func))
     }
 
+    /// Function version of [Self::enclosed] method that can short-circuit without calling `Self`'s service.
+    ///
+    /// Unlike [Self::enclosed_fn] the function does not receive the service. It receives mutable reference of
+    /// request and returns [ControlFlow::Continue] for passing request to the service or [ControlFlow::Break] for
+    /// responding with it's output directly. Useful for middleware like authentication and rate limiting.
+    ///
+    /// [ControlFlow::Continue]: core::ops::ControlFlow::Continue
+    /// [ControlFlow::Break]: core::ops::ControlFlow::Break
+    fn enclosed_guard_fn<T, Req>(self, func: T) -> PipelineT<Self, middleware::GuardFn<T>, marker::BuildEnclosed>
+    where
+        T: for<'r> async_fn::AsyncFn<(&'r mut Req,)> + Clone,
+        Self: Sized,
+    {
+        self.enclosed(middleware::GuardFn(func))
+    }
+
     /// Mutate `<<Self::Response as Service<Req>>::Future as Future>::Output` type with given
     /// closure.
     fn map<F, Res, ResMap>(self, mapper: F) -> PipelineT<Self, F, marker::BuildMap>
@@ -60,6 +76,8 @@ impl<S, Arg> ServiceExt<Arg> for S where S: Service<Arg> {}
 
 #[cfg(test)]
 mod test {
+    use core::ops::ControlFlow;
+
     use xitca_unsafe_collection::futures::NowOrPanic;
 
     use crate::fn_service;
@@ -153,6 +171,30 @@ mod test {
         assert_eq!(res, "251");
     }
 
+    #[test]
+    fn enclosed_guard_fn() {
+        async fn guard(req: &mut &'static str) -> ControlFlow<Result<&'static str, ()>> {
+            match *req {
+                "996" => ControlFlow::Break(Err(())),
+                "251" => {
+                    *req = "007";
+                    ControlFlow::Continue(())
+                }
+                _ => ControlFlow::Continue(()),
+            }
+        }
+
+        let service = fn_service(index)
+            .enclosed_guard_fn(guard)
+            .call(())
+            .now_or_panic()
+            .unwrap();
+
+        assert!(service.call("996").now_or_panic().is_err());
+        assert_eq!(service.call("251").now_or_panic().unwrap(), "007");
+        assert_eq!(service.call("964").now_or_panic().unwrap(), "964");
+    }
+
     #[cfg(feature = "alloc")]
     #[test]
     fn enclosed_opt() {
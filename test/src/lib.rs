@@ -11,7 +11,7 @@ use std::{
 
 use futures_util::Stream;
 use xitca_http::{
-    body::ResponseBody,
+    body::{RequestBody, ResponseBody},
     config::HttpServiceConfig,
     h1, h2, h3,
     http::{Request, RequestExt, Response},
@@ -83,18 +83,83 @@ where
     B: Stream<Item = Result<Bytes, E>> + 'static,
     E: fmt::Debug + 'static,
 {
-    test_server::<_, (TcpStream, SocketAddr)>(
-        service.enclosed(
-            HttpServiceBuilder::h2().config(
-                HttpServiceConfig::new()
-                    .request_head_timeout(Duration::from_millis(500))
-                    .tls_accept_timeout(Duration::from_millis(500))
-                    .keep_alive_timeout(Duration::from_millis(500)),
-            ),
-        ),
+    test_h2_server_with_config(
+        service,
+        HttpServiceConfig::new()
+            .request_head_timeout(Duration::from_millis(500))
+            .tls_accept_timeout(Duration::from_millis(500))
+            .keep_alive_timeout(Duration::from_millis(500)),
     )
 }
 
+/// A specialized http/2 server on top of [test_server] with given config.
+pub fn test_h2_server_with_config<T, B, E>(service: T, config: HttpServiceConfig) -> Result<TestServerHandle, Error>
+where
+    T: Service + Send + Sync + 'static,
+    T::Response: ReadyService + Service<Request<RequestExt<h2::RequestBody>>, Response = HResponse<B>> + 'static,
+    <T::Response as Service<Request<RequestExt<h2::RequestBody>>>>::Error: fmt::Debug,
+    T::Error: error::Error + 'static,
+    B: Stream<Item = Result<Bytes, E>> + 'static,
+    E: fmt::Debug + 'static,
+{
+    test_server::<_, (TcpStream, SocketAddr)>(service.enclosed(HttpServiceBuilder::h2().config(config)))
+}
+
+/// A specialized http/1 and cleartext http/2 (h2c) server on top of [test_server]
+pub fn test_h2c_server<T, B, E>(service: T) -> Result<TestServerHandle, Error>
+where
+    T: Service + Send + Sync + 'static,
+    T::Response: ReadyService + Service<Request<RequestExt<RequestBody>>, Response = HResponse<B>> + 'static,
+    <T::Response as Service<Request<RequestExt<RequestBody>>>>::Error: fmt::Debug,
+    T::Error: fmt::Debug + 'static,
+    B: Stream<Item = Result<Bytes, E>> + 'static,
+    E: fmt::Debug + 'static,
+{
+    test_h2c_server_with_config(
+        service,
+        HttpServiceConfig::new()
+            .request_head_timeout(Duration::from_millis(500))
+            .keep_alive_timeout(Duration::from_millis(500)),
+    )
+}
+
+/// A specialized http/1 and cleartext http/2 (h2c) server on top of [test_server] with given config.
+pub fn test_h2c_server_with_config<T, B, E>(service: T, config: HttpServiceConfig) -> Result<TestServerHandle, Error>
+where
+    T: Service + Send + Sync + 'static,
+    T::Response: ReadyService + Service<Request<RequestExt<RequestBody>>, Response = HResponse<B>> + 'static,
+    <T::Response as Service<Request<RequestExt<RequestBody>>>>::Error: fmt::Debug,
+    T::Error: fmt::Debug + 'static,
+    B: Stream<Item = Result<Bytes, E>> + 'static,
+    E: fmt::Debug + 'static,
+{
+    test_server::<_, NetStream>(service.enclosed(HttpServiceBuilder::with_config(config).h2c()))
+}
+
+/// A http/1 and http/2 server with openssl as tls service on top of [test_server]
+pub fn test_openssl_server<T, B, E>(service: T) -> Result<TestServerHandle, Error>
+where
+    T: Service + Send + Sync + 'static,
+    T::Response: ReadyService + Service<Request<RequestExt<RequestBody>>, Response = HResponse<B>> + 'static,
+    <T::Response as Service<Request<RequestExt<RequestBody>>>>::Error: fmt::Debug,
+    T::Error: fmt::Debug + 'static,
+    B: Stream<Item = Result<Bytes, E>> + 'static,
+    E: fmt::Debug + 'static,
+{
+    use openssl::ssl::{SslAcceptor, SslFiletype, SslMethod};
+
+    let mut builder = SslAcceptor::mozilla_intermediate(SslMethod::tls())?;
+    builder.set_private_key_file("../examples/cert/key.pem", SslFiletype::PEM)?;
+    builder.set_certificate_chain_file("../examples/cert/cert.pem")?;
+    let acceptor = builder.build();
+
+    let config = HttpServiceConfig::new()
+        .request_head_timeout(Duration::from_millis(500))
+        .tls_accept_timeout(Duration::from_millis(500))
+        .keep_alive_timeout(Duration::from_millis(500));
+    test_server::<_, NetStream>(service.enclosed(HttpServiceBuilder::with_config(config).openssl(acceptor)))
+}
+
 /// A specialized http/3 server
 pub fn test_h3_server<T, B, E>(service: T) -> Result<TestServerHandle, Error>
 where
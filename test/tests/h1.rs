@@ -1,22 +1,27 @@
 use futures_util::StreamExt;
 use std::{
+    convert::Infallible,
     io::{Read, Write},
-    net::TcpStream,
+    net::{SocketAddr, TcpStream},
+    pin::Pin,
+    task::{Context, Poll},
     time::Duration,
 };
 
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use xitca_client::Client;
 use xitca_http::{
-    body::{BoxBody, ResponseBody},
+    body::{BoxBody, Flush, ResponseBody},
     bytes::{Bytes, BytesMut},
     h1,
     http::{
         header::{self, HeaderValue, CONNECTION},
-        Method, Request, RequestExt, Response, Version,
+        Method, Request, RequestExt, Response, StatusCode, Version,
     },
+    HttpServiceBuilder,
 };
-use xitca_service::fn_service;
-use xitca_test::{test_h1_server, Error};
+use xitca_service::{fn_service, ServiceExt};
+use xitca_test::{test_h1_server, test_openssl_server, test_server, Error};
 
 #[tokio::test]
 async fn h1_get() -> Result<(), Error> {
@@ -201,6 +206,29 @@ async fn h1_request_too_large() -> Result<(), Error> {
     Ok(())
 }
 
+// Request uri length is limited by DEFAULT_MAX_URI_LEN which is 8KB by default.
+// Over long request line is rejected without waiting for the rest of request head.
+#[tokio::test]
+async fn h1_uri_too_long() -> Result<(), Error> {
+    let mut handle = test_h1_server(fn_service(handle))?;
+
+    let mut stream = TcpStream::connect(handle.addr())?;
+
+    let mut req = b"GET /".to_vec();
+    req.extend_from_slice(&[b'a'; 16 * 1024]);
+    stream.write_all(&req)?;
+
+    let mut buf = [0; 128];
+    let n = stream.read(&mut buf)?;
+    assert!(buf[..n].starts_with(b"HTTP/1.1 414"));
+
+    handle.try_handle()?.stop(true);
+
+    handle.await?;
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn h1_keepalive() -> Result<(), Error> {
     let mut handle = test_h1_server(fn_service(handle))?;
@@ -243,9 +271,312 @@ async fn h1_keepalive() -> Result<(), Error> {
     Ok(())
 }
 
+// http/1.0 client is told connection is kept alive with explicit headers while http/1.1 client is not.
+#[tokio::test]
+async fn h1_keepalive_header() -> Result<(), Error> {
+    let mut handle = test_h1_server(fn_service(handle))?;
+
+    let mut stream = TcpStream::connect(handle.addr())?;
+
+    let mut buf = [0; 256];
+    for _ in 0..2 {
+        stream.write_all(b"GET / HTTP/1.0\r\nConnection: keep-alive\r\n\r\n")?;
+
+        let mut res = Vec::new();
+        while !res.ends_with(b"GET Response") {
+            let n = stream.read(&mut buf)?;
+            assert_ne!(n, 0, "connection must be kept alive");
+            res.extend_from_slice(&buf[..n]);
+        }
+
+        let res = String::from_utf8(res)?;
+        assert!(res.contains("\r\nconnection: keep-alive\r\n"));
+        assert!(res.contains("\r\nkeep-alive: timeout=5\r\n"));
+    }
+
+    stream.write_all(SIMPLE_GET_REQ)?;
+
+    let mut res = Vec::new();
+    while !res.ends_with(b"GET Response") {
+        let n = stream.read(&mut buf)?;
+        res.extend_from_slice(&buf[..n]);
+    }
+
+    let res = String::from_utf8(res)?;
+    assert!(!res.contains("connection: "));
+    assert!(!res.contains("keep-alive"));
+
+    handle.try_handle()?.stop(true);
+
+    handle.await?;
+
+    Ok(())
+}
+
+#[cfg(not(feature = "io-uring"))]
+#[tokio::test]
+async fn h1_connect_upgrade() -> Result<(), Error> {
+    let mut handle = test_h1_server(fn_service(handle))?;
+
+    let mut stream = TcpStream::connect(handle.addr())?;
+
+    // bytes sent along with request head belong to the tunnel.
+    stream.write_all(b"CONNECT example.com:443 HTTP/1.1\r\nhost: example.com:443\r\n\r\n996")?;
+
+    let mut buf = [0; 256];
+    let mut res = Vec::new();
+    while !res.ends_with(b"\r\n\r\n996") {
+        let n = stream.read(&mut buf)?;
+        assert_ne!(n, 0);
+        res.extend_from_slice(&buf[..n]);
+    }
+
+    let res = String::from_utf8(res)?;
+    assert!(res.starts_with("HTTP/1.1 200 OK\r\n"));
+    assert!(!res.contains("content-length"));
+    assert!(!res.contains("transfer-encoding"));
+
+    for _ in 0..3 {
+        stream.write_all(b"251")?;
+        let mut res = Vec::new();
+        while res.len() < 3 {
+            let n = stream.read(&mut buf)?;
+            assert_ne!(n, 0);
+            res.extend_from_slice(&buf[..n]);
+        }
+        assert_eq!(res, b"251");
+    }
+
+    handle.try_handle()?.stop(true);
+
+    handle.await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn h1_pipeline() -> Result<(), Error> {
+    let mut handle = test_h1_server(fn_service(handle))?;
+
+    let mut stream = TcpStream::connect(handle.addr())?;
+
+    // send all requests in one write and close write side of connection. server must respond to every
+    // pipelined request in order before closing connection.
+    let mut reqs = Vec::new();
+    for i in 0..8 {
+        reqs.extend_from_slice(format!("GET /pipeline/{i} HTTP/1.1\r\ncontent-length: 0\r\n\r\n").as_bytes());
+    }
+    stream.write_all(&reqs)?;
+    stream.shutdown(std::net::Shutdown::Write)?;
+
+    let mut buf = Vec::new();
+    stream.read_to_end(&mut buf)?;
+    let res = String::from_utf8(buf)?;
+
+    assert_eq!(res.matches("HTTP/1.1 200 OK").count(), 8);
+
+    // buffered requests are served with keep alive semantic. only the last response can announce connection close
+    // depending on when server observes the closed read side.
+    let res_heads = res
+        .split("HTTP/1.1 200 OK")
+        .skip(1)
+        .map(|res| res.split("\r\n\r\n").next().unwrap().to_ascii_lowercase())
+        .collect::<Vec<_>>();
+    assert_eq!(res_heads.len(), 8);
+    for head in &res_heads[..7] {
+        assert!(!head.contains("connection: close"));
+    }
+
+    // read_to_end returning means server has closed the socket after the last response.
+    assert_eq!(stream.read(&mut [0; 8])?, 0);
+
+    let mut last = 0;
+    for i in 0..8 {
+        let pos = res.find(&format!("/pipeline/{i}")).unwrap();
+        assert!(pos >= last, "pipelined responses must be in request order");
+        last = pos;
+    }
+
+    handle.try_handle()?.stop(true);
+
+    handle.await?;
+
+    Ok(())
+}
+
+#[cfg(not(feature = "io-uring"))]
+#[tokio::test]
+async fn h1_load_shed() -> Result<(), Error> {
+    let service = fn_service(handle).enclosed(HttpServiceBuilder::h1().load_shed(64, 0));
+    let mut handle = test_server::<_, (xitca_io::net::TcpStream, SocketAddr)>(service)?;
+
+    // request head larger than high water mark puts worker into shedding state.
+    let mut stream = TcpStream::connect(handle.addr())?;
+    let mut req = b"GET / HTTP/1.1\r\nx-padding: ".to_vec();
+    req.extend_from_slice(&[b'a'; 64]);
+    req.extend_from_slice(b"\r\n\r\n");
+    stream.write_all(&req)?;
+
+    let mut res = Vec::new();
+    stream.read_to_end(&mut res)?;
+    assert!(res.starts_with(b"HTTP/1.1 503"));
+
+    // buffers of shed connection are released and worker recovers from shedding.
+    let mut stream = TcpStream::connect(handle.addr())?;
+    stream.write_all(b"GET / HTTP/1.1\r\nconnection: close\r\n\r\n")?;
+
+    let mut res = Vec::new();
+    stream.read_to_end(&mut res)?;
+    assert!(res.starts_with(b"HTTP/1.1 200 OK"));
+
+    handle.try_handle()?.stop(true);
+
+    handle.await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn h1_tls_require_https() -> Result<(), Error> {
+    use openssl::ssl::{SslConnector, SslMethod, SslVerifyMode};
+    use xitca_web::{handler::handler_service, middleware::https::RequireHttps, App};
+
+    let app = App::new()
+        .at("/", handler_service(|| async { "secure" }))
+        .enclosed(RequireHttps::new())
+        .finish_boxed();
+    let mut handle = test_openssl_server(app)?;
+
+    let mut connector = SslConnector::builder(SslMethod::tls())?;
+    connector.set_verify(SslVerifyMode::NONE);
+    let mut stream = connector
+        .build()
+        .connect("localhost", TcpStream::connect(handle.addr())?)?;
+
+    // http/1 request does not carry scheme and it's secured by tls connection it's received from.
+    stream.write_all(b"GET / HTTP/1.1\r\nhost: localhost\r\nconnection: close\r\n\r\n")?;
+
+    let mut res = Vec::new();
+    let _ = stream.read_to_end(&mut res);
+    let res = String::from_utf8(res)?;
+    assert!(res.starts_with("HTTP/1.1 200 OK"));
+    assert!(res.ends_with("secure"));
+
+    handle.try_handle()?.stop(true);
+
+    handle.await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn h1_no_content_body_dropped() -> Result<(), Error> {
+    let mut handle = test_h1_server(fn_service(handle))?;
+
+    let mut stream = TcpStream::connect(handle.addr())?;
+
+    stream.write_all(b"GET /no_content HTTP/1.1\r\n\r\n")?;
+
+    let mut buf = [0; 1];
+    let mut res = Vec::new();
+    while !res.ends_with(b"\r\n\r\n") {
+        let n = stream.read(&mut buf)?;
+        assert_ne!(n, 0);
+        res.extend_from_slice(&buf[..n]);
+    }
+    let res = String::from_utf8(res)?.to_ascii_lowercase();
+    assert!(res.starts_with("http/1.1 204 no content\r\n"));
+    assert!(!res.contains("content-length") && !res.contains("transfer-encoding"));
+
+    // body of 204 response is not polled and next request is served on the same connection.
+    stream.set_read_timeout(Some(Duration::from_secs(1)))?;
+    stream.write_all(b"GET / HTTP/1.1\r\nconnection: close\r\n\r\n")?;
+
+    let mut res = Vec::new();
+    stream.read_to_end(&mut res)?;
+    let res = String::from_utf8(res)?;
+    assert!(res.starts_with("HTTP/1.1 200 OK\r\n"));
+    assert!(res.ends_with("\r\n\r\nGET Response"));
+
+    handle.try_handle()?.stop(true);
+
+    handle.await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn h1_short_sized_body() -> Result<(), Error> {
+    let mut handle = test_h1_server(fn_service(handle))?;
+
+    let mut stream = TcpStream::connect(handle.addr())?;
+
+    stream.write_all(b"GET /short_body HTTP/1.1\r\n\r\n")?;
+
+    // connection is terminated right away instead of leaving client waiting for bytes that would never come.
+    // response is incomplete and it's partial bytes are not guaranteed to be written.
+    stream.set_read_timeout(Some(Duration::from_secs(1)))?;
+    let mut res = Vec::new();
+    stream.read_to_end(&mut res)?;
+    let res = String::from_utf8(res)?;
+    assert!(res.is_empty() || res.ends_with("\r\n\r\nabc"));
+
+    handle.try_handle()?.stop(true);
+
+    handle.await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn h1_flush_item() -> Result<(), Error> {
+    let mut handle = test_h1_server(fn_service(handle))?;
+
+    let mut stream = TcpStream::connect(handle.addr())?;
+
+    stream.write_all(b"GET /flush_item HTTP/1.1\r\n\r\n")?;
+
+    // first item is written to client before the server thread is blocked by body stream.
+    stream.set_read_timeout(Some(Duration::from_secs(1)))?;
+    let mut buf = [0; 128];
+    let mut res = Vec::new();
+    while !res.ends_with(b"first\r\n") {
+        let n = stream.read(&mut buf)?;
+        assert_ne!(n, 0);
+        res.extend_from_slice(&buf[..n]);
+    }
+    let res = String::from_utf8(res)?;
+    assert!(res.starts_with("HTTP/1.1 200 OK\r\n"));
+
+    handle.try_handle()?.stop(false);
+
+    handle.await?;
+
+    Ok(())
+}
+
 async fn handle(req: Request<RequestExt<h1::RequestBody>>) -> Result<Response<ResponseBody>, Error> {
     match (req.method(), req.uri().path()) {
+        // take over connection and echo tunneled bytes back.
+        (&Method::CONNECT, _) => {
+            let on_upgrade = req.extensions().get::<h1::OnUpgrade>().unwrap().clone();
+            tokio::task::spawn_local(async move {
+                let mut io = on_upgrade.await.unwrap();
+                let mut buf = [0; 128];
+                loop {
+                    let n = io.read(&mut buf).await.unwrap();
+                    if n == 0 {
+                        break;
+                    }
+                    io.write_all(&buf[..n]).await.unwrap();
+                }
+            });
+            Ok(Response::new(Bytes::new().into()))
+        }
         (&Method::GET, "/") | (&Method::HEAD, "/") => Ok(Response::new(Bytes::from("GET Response").into())),
+        (&Method::GET, path) if path.starts_with("/pipeline/") => {
+            Ok(Response::new(Bytes::copy_from_slice(path.as_bytes()).into()))
+        }
         (&Method::POST, "/") => {
             let length = req.headers().get(header::CONTENT_LENGTH).unwrap().clone();
             let ty = req.headers().get(header::CONTENT_TYPE).unwrap().clone();
@@ -277,6 +608,46 @@ async fn handle(req: Request<RequestExt<h1::RequestBody>>) -> Result<Response<Re
 
             Ok(Response::new(Bytes::new().into()))
         }
+        // body of 204 response must be dropped without being polled.
+        (&Method::GET, "/no_content") => {
+            let body = futures_util::stream::pending::<Result<Bytes, Infallible>>();
+            let mut res = Response::new(ResponseBody::box_stream(body));
+            *res.status_mut() = StatusCode::NO_CONTENT;
+            Ok(res)
+        }
+        // body stream ending before it's declared length. server should close connection afterwards.
+        (&Method::GET, "/short_body") => {
+            struct Short(Option<Bytes>);
+
+            impl futures_util::Stream for Short {
+                type Item = Result<Bytes, Infallible>;
+
+                fn poll_next(mut self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+                    Poll::Ready(self.0.take().map(Ok))
+                }
+
+                fn size_hint(&self) -> (usize, Option<usize>) {
+                    (10, Some(10))
+                }
+            }
+
+            Ok(Response::new(ResponseBody::box_stream(Short(Some(Bytes::from("abc"))))))
+        }
+        // body stream blocking it's thread after first item. the item must be flushed before polling stream again.
+        (&Method::GET, "/flush_item") => {
+            let mut first = true;
+            let body = futures_util::stream::poll_fn(move |_| {
+                if first {
+                    first = false;
+                    return Poll::Ready(Some(Ok::<_, Infallible>(Bytes::from_static(b"first"))));
+                }
+                std::thread::sleep(Duration::from_secs(2));
+                Poll::Ready(None)
+            });
+            let mut res = Response::new(ResponseBody::box_stream(body));
+            res.extensions_mut().insert(Flush::Item);
+            Ok(res)
+        }
         (&Method::GET, "/close_connection") => {
             let mut res = Response::new(Bytes::new().into());
             res.headers_mut().insert(CONNECTION, HeaderValue::from_static("close"));
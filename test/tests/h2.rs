@@ -9,7 +9,7 @@ use xitca_http::{
     http::{header, Method, Request, RequestExt, Response, Version},
 };
 use xitca_service::fn_service;
-use xitca_test::{test_h2_server, Error};
+use xitca_test::{test_h2_server, test_h2_server_with_config, Error};
 
 #[tokio::test]
 async fn h2_get() -> Result<(), Error> {
@@ -60,6 +60,114 @@ async fn h2_post() -> Result<(), Error> {
     Ok(())
 }
 
+#[tokio::test]
+async fn h2_post_backpressure() -> Result<(), Error> {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use xitca_http::config::DEFAULT_H2_INITIAL_WINDOW_SIZE;
+
+    const CHUNK: usize = 16 * 1024;
+    const CHUNKS: usize = 256;
+
+    static SENT: AtomicUsize = AtomicUsize::new(0);
+
+    let mut handle = test_h2_server(fn_service(|req: Request<RequestExt<h2::RequestBody>>| async move {
+        // slow consumer. client must be blocked by flow control before any request body is read.
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        let sent = SENT.load(Ordering::SeqCst);
+
+        let (_, mut body) = req.into_parts();
+        let mut len = 0;
+        while let Some(bytes) = body.next().await {
+            len += bytes?.len();
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(len, CHUNK * CHUNKS);
+
+        Ok::<_, Error>(Response::<ResponseBody>::new(Bytes::from(sent.to_string()).into()))
+    }))?;
+
+    let server_url = format!("https://{}/", handle.ip_port_string());
+
+    // unfold stream carries no size hint so the request body is sent as a stream without content-length.
+    let body = futures_util::stream::unfold(0, |n| async move {
+        (n < CHUNKS).then(|| {
+            SENT.fetch_add(CHUNK, Ordering::SeqCst);
+            (Ok::<_, Error>(Bytes::from(vec![b'a'; CHUNK])), n + 1)
+        })
+    });
+
+    let c = Client::new();
+    let res = c.post(&server_url).version(Version::HTTP_2).stream(body).send().await?;
+    assert_eq!(res.status().as_u16(), 200);
+
+    // bytes sent before server consumes body are bound to stream window and the chunk client holds while waiting
+    // for capacity.
+    let sent = res.string().await?.parse::<usize>()?;
+    assert!(
+        sent <= DEFAULT_H2_INITIAL_WINDOW_SIZE as usize + CHUNK,
+        "client sent {sent} bytes without backpressure"
+    );
+
+    handle.try_handle()?.stop(false);
+
+    handle.await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn h2_max_concurrent_streams() -> Result<(), Error> {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use xitca_http::config::HttpServiceConfig;
+
+    const MAX: usize = 2;
+
+    static ACTIVE: AtomicUsize = AtomicUsize::new(0);
+    static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+    let mut handle = test_h2_server_with_config(
+        fn_service(|_: Request<RequestExt<h2::RequestBody>>| async {
+            let active = ACTIVE.fetch_add(1, Ordering::SeqCst) + 1;
+            PEAK.fetch_max(active, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            ACTIVE.fetch_sub(1, Ordering::SeqCst);
+            Ok::<_, Error>(Response::<ResponseBody>::new(Bytes::from("GET Response").into()))
+        }),
+        HttpServiceConfig::new().h2_max_concurrent_streams(MAX as _),
+    )?;
+
+    let server_url = format!("https://{}/", handle.ip_port_string());
+
+    let c = Client::new();
+
+    // establish shared connection before sending concurrent requests.
+    let res = c.get(&server_url).version(Version::HTTP_2).send().await?;
+    assert_eq!(res.status().as_u16(), 200);
+    let _ = res.body().await;
+
+    // client respects the advertised limit and excess requests wait for stream capacity instead of failing.
+    let res = futures_util::future::join_all((0..MAX * 4).map(|_| async {
+        let res = c.get(&server_url).version(Version::HTTP_2).send().await?;
+        assert_eq!(res.status().as_u16(), 200);
+        res.string().await.map_err(Error::from)
+    }))
+    .await;
+
+    for body in res {
+        assert_eq!(body?, "GET Response");
+    }
+
+    assert!(PEAK.load(Ordering::SeqCst) <= MAX);
+
+    handle.try_handle()?.stop(false);
+
+    handle.await?;
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn h2_connect() -> Result<(), Error> {
     let mut handle = test_h2_server(fn_service(handle))?;
@@ -148,6 +256,62 @@ async fn h2_keepalive() -> Result<(), Error> {
     Ok(())
 }
 
+#[tokio::test]
+async fn h2_ping_timeout() -> Result<(), Error> {
+    use xitca_http::config::HttpServiceConfig;
+
+    let mut handle = test_h2_server_with_config(
+        fn_service(handle),
+        HttpServiceConfig::new()
+            .h2_ping_interval(Duration::from_millis(100))
+            .h2_ping_timeout(Duration::from_millis(200)),
+    )?;
+
+    let server_url = format!("https://{}/", handle.ip_port_string());
+
+    let (tx, rx) = std::sync::mpsc::sync_channel::<()>(1);
+    let (tx_done, rx_done) = std::sync::mpsc::sync_channel::<()>(1);
+
+    let thread = std::thread::spawn(move || {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(async move {
+                let c = Client::new();
+
+                let res = c.get(&server_url).version(Version::HTTP_2).send().await?;
+                assert_eq!(res.status().as_u16(), 200);
+                let body = res.string().await?;
+                assert_eq!("GET Response", body);
+
+                tx.send(()).unwrap();
+
+                // block the thread so client can not reply to server ping and appears as dead peer. thread is
+                // unblocked when server is stopped or test is timed out.
+                let _ = rx_done.recv_timeout(Duration::from_secs(10));
+                drop(c);
+                Ok::<_, Error>(())
+            })
+    });
+
+    rx.recv().unwrap();
+
+    handle.try_handle()?.stop(true);
+
+    let now = Instant::now();
+
+    handle.await?;
+
+    // dead peer is detected by missing pong and connection is closed without waiting for default timeouts.
+    assert!(now.elapsed() < Duration::from_secs(5));
+
+    tx_done.send(()).unwrap();
+    thread.join().unwrap()?;
+
+    Ok(())
+}
+
 async fn handle(req: Request<RequestExt<h2::RequestBody>>) -> Result<Response<ResponseBody>, Error> {
     // Some yield for testing h2 dispatcher's concurrent future handling.
     tokio::task::yield_now().await;
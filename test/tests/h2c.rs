@@ -0,0 +1,268 @@
+use std::{
+    io::{Read, Write},
+    net::TcpStream,
+    time::Duration,
+};
+
+use xitca_http::{
+    body::{RequestBody, ResponseBody},
+    bytes::Bytes,
+    config::HttpServiceConfig,
+    http::{Request, RequestExt, Response},
+};
+use xitca_service::fn_service;
+use xitca_test::{test_h2c_server, test_h2c_server_with_config, Error};
+
+const PREFACE: &[u8] = b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n";
+
+// empty SETTINGS frame.
+const SETTINGS: &[u8] = &[0, 0, 0, 4, 0, 0, 0, 0, 0];
+
+#[tokio::test]
+async fn h2c_prior_knowledge() -> Result<(), Error> {
+    let mut handle = test_h2c_server(fn_service(handle))?;
+
+    let mut stream = TcpStream::connect(handle.addr())?;
+
+    let mut block = Vec::new();
+    for (name, value) in [
+        (":method", "GET"),
+        (":scheme", "http"),
+        (":authority", "localhost"),
+        (":path", "/prior"),
+    ] {
+        // literal header field without indexing and huffman coding.
+        block.push(0);
+        block.push(name.len() as u8);
+        block.extend_from_slice(name.as_bytes());
+        block.push(value.len() as u8);
+        block.extend_from_slice(value.as_bytes());
+    }
+
+    let mut req = Vec::new();
+    req.extend_from_slice(PREFACE);
+    req.extend_from_slice(SETTINGS);
+    req.extend_from_slice(&(block.len() as u32).to_be_bytes()[1..]);
+    // HEADERS frame of stream 1 with END_STREAM and END_HEADERS flags.
+    req.extend_from_slice(&[1, 5, 0, 0, 0, 1]);
+    req.extend_from_slice(&block);
+    stream.write_all(&req)?;
+
+    assert_eq!(read_stream_1(&mut stream)?, "GET /prior HTTP/2.0");
+
+    handle.try_handle()?.stop(false);
+
+    handle.await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn h2c_upgrade() -> Result<(), Error> {
+    let mut handle = test_h2c_server(fn_service(handle))?;
+
+    let mut stream = TcpStream::connect(handle.addr())?;
+
+    stream.write_all(
+        b"GET /upgrade HTTP/1.1\r\n\
+        host: localhost\r\n\
+        connection: Upgrade, HTTP2-Settings\r\n\
+        upgrade: h2c\r\n\
+        http2-settings: AAMAAABkAAQAoAAAAAIAAAAA\r\n\r\n",
+    )?;
+
+    let mut buf = [0; 1];
+    let mut res = Vec::new();
+    while !res.ends_with(b"\r\n\r\n") {
+        let n = stream.read(&mut buf)?;
+        assert_ne!(n, 0);
+        res.extend_from_slice(&buf[..n]);
+    }
+    let res = String::from_utf8(res)?;
+    assert!(res.starts_with("HTTP/1.1 101 Switching Protocols\r\n"));
+    assert!(res.contains("upgrade: h2c"));
+
+    // upgrade request is answered on stream 1 after connection preface.
+    stream.write_all(PREFACE)?;
+    stream.write_all(SETTINGS)?;
+
+    assert_eq!(read_stream_1(&mut stream)?, "GET /upgrade HTTP/2.0");
+
+    handle.try_handle()?.stop(false);
+
+    handle.await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn h2c_http1() -> Result<(), Error> {
+    let mut handle = test_h2c_server(fn_service(handle))?;
+
+    let mut stream = TcpStream::connect(handle.addr())?;
+
+    // plain http/1 request is not affected.
+    stream.write_all(b"GET /h1 HTTP/1.1\r\nconnection: close\r\n\r\n")?;
+
+    let mut res = Vec::new();
+    stream.read_to_end(&mut res)?;
+    let res = String::from_utf8(res)?;
+    assert!(res.starts_with("HTTP/1.1 200 OK\r\n"));
+    assert!(res.ends_with("GET /h1 HTTP/1.1"));
+
+    handle.try_handle()?.stop(false);
+
+    handle.await?;
+
+    Ok(())
+}
+
+// read frames until stream 1 ends and collect it's DATA frames.
+#[tokio::test]
+async fn h2c_scheme_https_not_secure() -> Result<(), Error> {
+    use xitca_web::{handler::handler_service, middleware::https::RequireHttps, App};
+
+    let app = App::new()
+        .at("/", handler_service(|| async { "secure" }))
+        .enclosed(RequireHttps::new())
+        .finish_boxed();
+    let mut handle = test_h2c_server(app)?;
+
+    let mut stream = TcpStream::connect(handle.addr())?;
+
+    // cleartext http/2 request claiming https scheme.
+    let mut block = Vec::new();
+    for (name, value) in [
+        (":method", "GET"),
+        (":scheme", "https"),
+        (":authority", "localhost"),
+        (":path", "/"),
+    ] {
+        block.push(0);
+        block.push(name.len() as u8);
+        block.extend_from_slice(name.as_bytes());
+        block.push(value.len() as u8);
+        block.extend_from_slice(value.as_bytes());
+    }
+
+    let mut req = Vec::new();
+    req.extend_from_slice(PREFACE);
+    req.extend_from_slice(SETTINGS);
+    req.extend_from_slice(&(block.len() as u32).to_be_bytes()[1..]);
+    req.extend_from_slice(&[1, 5, 0, 0, 0, 1]);
+    req.extend_from_slice(&block);
+    stream.write_all(&req)?;
+
+    // request is redirected with empty body instead of reaching handler.
+    assert_eq!(read_stream_1(&mut stream)?, "");
+
+    handle.try_handle()?.stop(false);
+
+    handle.await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn h2c_refused_stream() -> Result<(), Error> {
+    let mut handle = test_h2c_server_with_config(
+        fn_service(|req: Request<RequestExt<RequestBody>>| async move {
+            // keep stream 1 open while stream 3 is opened.
+            tokio::time::sleep(Duration::from_millis(500)).await;
+            handle(req).await
+        }),
+        HttpServiceConfig::new().h2_max_concurrent_streams(1),
+    )?;
+
+    let mut stream = TcpStream::connect(handle.addr())?;
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+
+    let mut block = Vec::new();
+    for (name, value) in [
+        (":method", "GET"),
+        (":scheme", "http"),
+        (":authority", "localhost"),
+        (":path", "/"),
+    ] {
+        // literal header field without indexing and huffman coding.
+        block.push(0);
+        block.push(name.len() as u8);
+        block.extend_from_slice(name.as_bytes());
+        block.push(value.len() as u8);
+        block.extend_from_slice(value.as_bytes());
+    }
+
+    let mut req = Vec::new();
+    req.extend_from_slice(PREFACE);
+    req.extend_from_slice(SETTINGS);
+    // HEADERS frames of stream 1 and 3 with END_STREAM and END_HEADERS flags. stream 3 goes beyond the limit.
+    for id in [1u8, 3] {
+        req.extend_from_slice(&(block.len() as u32).to_be_bytes()[1..]);
+        req.extend_from_slice(&[1, 5, 0, 0, 0, id]);
+        req.extend_from_slice(&block);
+    }
+    stream.write_all(&req)?;
+
+    // stream 3 is refused while stream 1 is served.
+    loop {
+        let mut head = [0; 9];
+        stream.read_exact(&mut head)?;
+        let len = u32::from_be_bytes([0, head[0], head[1], head[2]]) as usize;
+        let ty = head[3];
+        let id = u32::from_be_bytes([head[5], head[6], head[7], head[8]]) & 0x7fff_ffff;
+
+        let mut payload = vec![0; len];
+        stream.read_exact(&mut payload)?;
+
+        // RST_STREAM frame.
+        if ty == 3 {
+            assert_eq!(id, 3);
+            // REFUSED_STREAM error code.
+            assert_eq!(u32::from_be_bytes(payload[..4].try_into()?), 7);
+            break;
+        }
+    }
+
+    assert_eq!(read_stream_1(&mut stream)?, "GET / HTTP/2.0");
+
+    handle.try_handle()?.stop(false);
+
+    handle.await?;
+
+    Ok(())
+}
+
+fn read_stream_1(stream: &mut TcpStream) -> Result<String, Error> {
+    let mut body = Vec::new();
+    loop {
+        let mut head = [0; 9];
+        stream.read_exact(&mut head)?;
+        let len = u32::from_be_bytes([0, head[0], head[1], head[2]]) as usize;
+        let (ty, flags) = (head[3], head[4]);
+        let id = u32::from_be_bytes([head[5], head[6], head[7], head[8]]) & 0x7fff_ffff;
+
+        let mut payload = vec![0; len];
+        stream.read_exact(&mut payload)?;
+
+        if id != 1 {
+            continue;
+        }
+
+        match ty {
+            // DATA frame.
+            0 => body.extend_from_slice(&payload),
+            // HEADERS frame.
+            1 => {}
+            ty => panic!("unexpected frame type {ty} on stream 1"),
+        }
+
+        if flags & 1 == 1 {
+            return Ok(String::from_utf8(body)?);
+        }
+    }
+}
+
+async fn handle(req: Request<RequestExt<RequestBody>>) -> Result<Response<ResponseBody>, Error> {
+    let body = format!("{} {} {:?}", req.method(), req.uri().path(), req.version());
+    Ok(Response::new(ResponseBody::from(Bytes::from(body))))
+}
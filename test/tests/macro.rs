@@ -135,3 +135,63 @@ impl MyError {
         WebResponse::builder().status(*status).body(Default::default()).unwrap()
     }
 }
+
+#[derive(xitca_codegen::Responder)]
+enum ApiResult<T> {
+    Ok(T),
+    #[responder(status = 404)]
+    NotFound,
+    #[responder(status = 409, content_type = "text/plain; charset=utf-8")]
+    Conflict {
+        msg: &'static str,
+    },
+}
+
+#[tokio::test]
+async fn responder_derive() {
+    use xitca_web::{
+        handler::{handler_service, path::PathRef},
+        http::{header::CONTENT_TYPE, Uri, WebRequest},
+        test::collect_string_body,
+        App,
+    };
+
+    async fn handler(PathRef(path): PathRef<'_>) -> ApiResult<String> {
+        match path {
+            "/ok" => ApiResult::Ok(String::from("996")),
+            "/conflict" => ApiResult::Conflict { msg: "251" },
+            _ => ApiResult::NotFound,
+        }
+    }
+
+    let service = App::new()
+        .at("/ok", handler_service(handler))
+        .at("/none", handler_service(handler))
+        .at("/conflict", handler_service(handler))
+        .finish()
+        .call(())
+        .await
+        .unwrap();
+
+    let call = |path: &'static str| {
+        let mut req = WebRequest::default();
+        *req.uri_mut() = Uri::from_static(path);
+        Service::call(&service, req)
+    };
+
+    // inner field is rendered by it's own responder.
+    let res = call("/ok").await.unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.headers().get(CONTENT_TYPE).unwrap(), "text/plain; charset=utf-8");
+    assert_eq!(collect_string_body(res.into_body()).await.unwrap(), "996");
+
+    let res = call("/none").await.unwrap();
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    assert!(res.headers().get(CONTENT_TYPE).is_none());
+    assert!(collect_string_body(res.into_body()).await.unwrap().is_empty());
+
+    let res = call("/conflict").await.unwrap();
+    assert_eq!(res.status(), StatusCode::CONFLICT);
+    assert_eq!(res.headers().get(CONTENT_TYPE).unwrap(), "text/plain; charset=utf-8");
+    assert_eq!(collect_string_body(res.into_body()).await.unwrap(), "251");
+}
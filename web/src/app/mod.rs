@@ -1,32 +1,39 @@
 mod object;
 mod router;
+mod state;
 
 use core::{
+    any::Any,
     convert::Infallible,
     fmt,
     future::{ready, Future},
     pin::Pin,
 };
 
-use std::error;
+use std::mem;
 
 use futures_core::stream::Stream;
-use xitca_http::util::{
-    middleware::context::ContextBuilder,
-    service::router::{IntoObject, PathGen, RouteGen, RouteObject, TypedRoute},
-};
+use xitca_http::util::service::router::{IntoObject, PathGen, RouteGen, RouteInfo, RouteObject, TypedRoute};
 
 use crate::{
     body::{Either, RequestBody, ResponseBody},
     bytes::Bytes,
     context::WebContext,
-    error::{Error, RouterError},
-    http::{WebRequest, WebResponse},
+    error::{BodyError, Error, RouterError},
+    handler::config::ConfigHolder,
+    http::{header::HeaderValue, WebRequest, WebResponse},
     middleware::eraser::TypeEraser,
-    service::{ready::ReadyService, AsyncFn, EnclosedBuilder, EnclosedFnBuilder, MapBuilder, Service, ServiceExt},
+    service::{
+        ready::ReadyService, AsyncFn, EnclosedBuilder, EnclosedFnBuilder, EnclosedGuardFnBuilder, MapBuilder, Service,
+        ServiceExt,
+    },
 };
 
-use self::{object::WebObject, router::AppRouter};
+use self::{
+    object::WebObject,
+    router::AppRouter,
+    state::{EncloseCtx, StateBuilder, StateEnclosed},
+};
 
 /// composed application type with router, stateful context and default middlewares.
 pub struct App<R = (), CF = ()> {
@@ -40,25 +47,43 @@ type DefaultWebObject<C> = WebObject<C, RequestBody, WebResponse, RouterError<Er
 type DefaultAppRouter<C> = AppRouter<RouteObject<(), DefaultWebObject<C>, Infallible>>;
 
 // helper trait to poly between () and Box<dyn Fn()> as application state.
+// Enclose type is middleware constructed from reference of the state.
 pub trait IntoCtx {
     type Ctx;
+    type Enclose: Send + Sync;
 
-    fn into_ctx(self) -> impl Fn() -> BoxFuture<Self::Ctx> + Send + Sync;
+    fn into_ctx(self) -> (impl Fn() -> BoxFuture<Self::Ctx> + Send + Sync, Self::Enclose);
 }
 
 impl IntoCtx for () {
     type Ctx = ();
+    type Enclose = ();
 
-    fn into_ctx(self) -> impl Fn() -> BoxFuture<Self::Ctx> + Send + Sync {
-        || Box::pin(ready(Ok(())))
+    fn into_ctx(self) -> (impl Fn() -> BoxFuture<Self::Ctx> + Send + Sync, Self::Enclose) {
+        (|| Box::pin(ready(Ok(()))), ())
     }
 }
 
 impl<C> IntoCtx for CtxBuilder<C> {
     type Ctx = C;
+    type Enclose = ();
 
-    fn into_ctx(self) -> impl Fn() -> BoxFuture<Self::Ctx> + Send + Sync {
-        self
+    fn into_ctx(self) -> (impl Fn() -> BoxFuture<Self::Ctx> + Send + Sync, Self::Enclose) {
+        (self, ())
+    }
+}
+
+impl<CF, F> IntoCtx for StateEnclosed<CF, F>
+where
+    CF: IntoCtx,
+    F: Send + Sync,
+{
+    type Ctx = CF::Ctx;
+    type Enclose = (CF::Enclose, F);
+
+    fn into_ctx(self) -> (impl Fn() -> BoxFuture<Self::Ctx> + Send + Sync, Self::Enclose) {
+        let (ctx_builder, enclose) = self.ctx_builder.into_ctx();
+        (ctx_builder, (enclose, self.transform))
     }
 }
 
@@ -292,6 +317,20 @@ impl<Obj, CF> App<AppRouter<Obj>, CF> {
     ///     "todo"
     /// }
     /// ```
+    ///
+    /// # Panics
+    /// Conflicting routes are rejected when they are registered:
+    /// - the same path registered more than once. services of different methods on the same path must be combined
+    ///   into one route. e.g. `.at("/", get(a).post(b))`
+    /// - dynamic paths router can not tell apart. e.g. `/users/:id` and `/users/:name`
+    /// - catch-all param not placed at the end of path. e.g. `/files/*path/raw`
+    /// ```rust,should_panic
+    /// # use xitca_web::{handler::handler_service, route::{get, post}, App, WebContext};
+    /// App::new()
+    ///     .at("/", get(handler_service(|| async { "get" })))
+    ///     .at("/", post(handler_service(|| async { "post" })))
+    ///     # .at("/nah", handler_service(|_: &WebContext<'_>| async { "used for infer type" }));
+    /// ```
     pub fn at<F, C, B>(mut self, path: &'static str, builder: F) -> Self
     where
         F: RouteGen + Service + Send + Sync,
@@ -310,9 +349,71 @@ impl<Obj, CF> App<AppRouter<Obj>, CF> {
         self.router = self.router.insert_typed(typed);
         self
     }
+
+    /// list routes registered to application. routes of nested application and router are flattened with their
+    /// path prefix. routes are sorted by their path.
+    ///
+    /// Route without method restriction would have empty [RouteInfo::methods].
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use xitca_web::{handler::handler_service, http::Method, route::get, App, WebContext};
+    /// let app = App::new()
+    ///     .at("/", get(handler_service(|| async { "hello" })).post(handler_service(|| async { "world" })))
+    ///     .at("/any", handler_service(|| async { "any" }))
+    ///     # .at("/infer", handler_service(|_: &WebContext<'_>| async{ "infer type" }))
+    ///     ;
+    ///
+    /// let routes = app.routes();
+    /// assert_eq!(routes[0].path, "/");
+    /// assert_eq!(routes[0].methods, [Method::GET, Method::POST]);
+    /// assert_eq!(routes[1].path, "/any");
+    /// assert!(routes[1].methods.is_empty());
+    /// ```
+    pub fn routes(&self) -> Vec<RouteInfo>
+    where
+        Obj: PathGen,
+    {
+        self.router.routes()
+    }
+
+    /// set content type and body of "404 Not Found" response produced when request doesn't match any route.
+    /// It's a lighter alternative of registering a fallback route when only the body of response needs to be
+    /// customized. See [RouterErrorBody] for detail.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use xitca_web::{handler::handler_service, http::const_header_value::JSON, App, WebContext};
+    /// App::new()
+    ///     .at("/", handler_service(|| async { "hello,world!" }))
+    ///     # .at("/infer", handler_service(|_: &WebContext<'_>| async{ "infer type" }))
+    ///     .not_found_body(JSON, r#"{"error":"not found"}"#);
+    /// ```
+    ///
+    /// [RouterErrorBody]: crate::error::RouterErrorBody
+    pub fn not_found_body(mut self, content_type: HeaderValue, body: impl Into<Bytes>) -> Self {
+        let error_body = self.router.error_body();
+        *error_body = mem::take(error_body).not_found(content_type, body);
+        self
+    }
+
+    /// set content type and body of "405 Method Not Allowed" response produced when request matches a route but
+    /// not it's methods. `Allow` header is still added to response. See [RouterErrorBody] for detail.
+    ///
+    /// [RouterErrorBody]: crate::error::RouterErrorBody
+    pub fn method_not_allowed_body(mut self, content_type: HeaderValue, body: impl Into<Bytes>) -> Self {
+        let error_body = self.router.error_body();
+        *error_body = mem::take(error_body).method_not_allowed(content_type, body);
+        self
+    }
 }
 
-impl<R, CF> App<R, CF> {
+// state constructing methods are not offered after App::enclosed_with_state where they would replace the state
+// along with middleware constructed from it.
+impl<R, CF> App<R, CF>
+where
+    CF: IntoCtx<Enclose = ()>,
+{
     /// Construct App with a thread safe state that will be shared among all tasks and worker threads.
     ///
     /// State accessing is based on generic type approach where the State type and it's typed fields are generally
@@ -374,9 +475,112 @@ impl<R, CF> App<R, CF> {
         self.with_async_state(move || ready(Ok::<_, Infallible>(state.clone())))
     }
 
+    /// Construct App with typed configuration as state.
+    ///
+    /// This is a shortcut of [App::with_state] with [ConfigHolder] as state type. Configuration is loaded and
+    /// deserialized by application before constructing App and handlers read it through
+    /// [Config](crate::handler::config::Config) extractor. A clone of holder can be kept for replacing configuration
+    /// at runtime and all requests started after the replacement observe the new value.
+    ///
+    /// When App needs other state besides configuration use [App::with_state] with a type implementing
+    /// [BorrowState](crate::handler::state::BorrowState) for [ConfigHolder] instead.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use xitca_web::{handler::{config::{Config, ConfigHolder}, handler_service}, App, WebContext};
+    /// struct Settings {
+    ///     greeting: String,
+    /// }
+    ///
+    /// let holder = ConfigHolder::new(Settings {
+    ///     greeting: std::env::var("GREETING").unwrap_or_else(|_| String::from("hello")),
+    /// });
+    ///
+    /// App::new()
+    ///     .with_config(holder.clone())
+    ///     .at("/", handler_service(index))
+    ///     # .at("/nah", handler_service(|_: &WebContext<'_, ConfigHolder<Settings>>| async { "used for infer type" }))
+    ///     ;
+    ///
+    /// // reload configuration from somewhere else. e.g. a signal handler or file watcher.
+    /// holder.store(Settings { greeting: String::from("hi") });
+    ///
+    /// async fn index(Config(settings): Config<Settings>) -> String {
+    ///     settings.greeting.clone()
+    /// }
+    /// ```
+    pub fn with_config<T>(self, config: ConfigHolder<T>) -> App<R, CtxBuilder<ConfigHolder<T>>>
+    where
+        T: Send + Sync + 'static,
+    {
+        self.with_state(config)
+    }
+
+    /// Construct App with sync and fallible closure which it's output would be used as state.
+    ///
+    /// This is a shortcut of [App::with_async_state] for state that can be built without async code but may fail
+    /// (for example opening a file or reading environment variables). The closure is called on every worker thread
+    /// of server and error returned by it would prevent server from starting.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use xitca_web::{handler::{handler_service, state::StateRef}, App, WebContext};
+    /// #[derive(Debug)]
+    /// struct MissingEnv(&'static str);
+    ///
+    /// App::new()
+    ///     .with_state_init(|| {
+    ///         let key = std::env::var("API_KEY").map_err(|_| MissingEnv("API_KEY"))?;
+    ///         Ok::<_, MissingEnv>(key)
+    ///     })
+    ///     .at("/", handler_service(index))
+    ///     # .at("/nah", handler_service(|_: &WebContext<'_, String>| async { "used for infer type" }))
+    ///     ;
+    ///
+    /// async fn index(StateRef(key): StateRef<'_, String>) -> String {
+    ///     key.len().to_string()
+    /// }
+    /// ```
+    pub fn with_state_init<CF1, C, E>(self, init: CF1) -> App<R, CtxBuilder<C>>
+    where
+        CF1: Fn() -> Result<C, E> + Send + Sync + 'static,
+        C: 'static,
+        E: fmt::Debug + 'static,
+    {
+        self.with_async_state(move || ready(init()))
+    }
+
     /// Construct App with async closure which it's output would be used as state.
     /// async state is used to produce thread per core and/or non thread safe state copies.
     /// The output state is not bound to `Send` and `Sync` auto traits.
+    ///
+    /// The closure is called on every worker thread of server when the thread starts serving the application.
+    /// Each worker thread owns the state it produced and no cross thread synchronization is needed when accessing
+    /// it. This makes it the hook for thread local resources like database connections that are cheaper kept
+    /// per thread. Error returned by closure would prevent server from starting.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::{cell::RefCell, convert::Infallible, rc::Rc};
+    /// # use xitca_web::{handler::{handler_service, state::StateRef}, App, WebContext};
+    /// // a thread local state that is not Send nor Sync.
+    /// #[derive(Clone, Default)]
+    /// struct Local(Rc<RefCell<Vec<String>>>);
+    ///
+    /// App::new()
+    ///     .with_async_state(|| async {
+    ///         // connect to database or warm up thread local connection pool here.
+    ///         Ok::<_, Infallible>(Local::default())
+    ///     })
+    ///     .at("/", handler_service(index))
+    ///     # .at("/nah", handler_service(|_: &WebContext<'_, Local>| async { "used for infer type" }))
+    ///     ;
+    ///
+    /// async fn index(StateRef(local): StateRef<'_, Local>) -> String {
+    ///     local.0.borrow_mut().push(String::from("visited"));
+    ///     local.0.borrow().len().to_string()
+    /// }
+    /// ```
     pub fn with_async_state<CF1, Fut, C, E>(self, builder: CF1) -> App<R, CtxBuilder<C>>
     where
         CF1: Fn() -> Fut + Send + Sync + 'static,
@@ -424,6 +628,99 @@ where
         }
     }
 
+    /// Enclose App with function as middleware type that can short-circuit without calling App's service.
+    ///
+    /// The function receives mutable reference of [WebContext] and returns [ControlFlow::Continue] for passing
+    /// the request to App's service or [ControlFlow::Break] for responding with it's output directly. Useful for
+    /// middleware like authentication and rate limiting where pass through case does not touch response.
+    /// See [middleware](crate::middleware) for more.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use core::ops::ControlFlow;
+    /// # use xitca_web::{
+    /// #     error::Error,
+    /// #     handler::handler_service,
+    /// #     http::{header::AUTHORIZATION, StatusCode, WebResponse},
+    /// #     App, WebContext
+    /// # };
+    /// // reject request without expected authorization header with 401 status code.
+    /// async fn auth(ctx: &mut WebContext<'_>) -> ControlFlow<Result<WebResponse, Error>> {
+    ///     match ctx.req().headers().get(AUTHORIZATION) {
+    ///         Some(v) if v == "Bearer dagongren" => ControlFlow::Continue(()),
+    ///         _ => ControlFlow::Break(Err(Error::from(StatusCode::UNAUTHORIZED))),
+    ///     }
+    /// }
+    ///
+    /// App::new()
+    ///     .at("/", handler_service(|| async { "authorized" }))
+    ///     # .at("/nah", handler_service(|_: &WebContext<'_>| async { "used for infer type" }))
+    ///     .enclosed_guard_fn(auth);
+    /// ```
+    ///
+    /// [ControlFlow::Continue]: core::ops::ControlFlow::Continue
+    /// [ControlFlow::Break]: core::ops::ControlFlow::Break
+    pub fn enclosed_guard_fn<Req, T>(self, transform: T) -> App<EnclosedGuardFnBuilder<R, T>, CF>
+    where
+        T: for<'s> AsyncFn<(&'s mut Req,)> + Clone,
+    {
+        App {
+            router: self.router.enclosed_guard_fn(transform),
+            ctx_builder: self.ctx_builder,
+        }
+    }
+
+    /// Enclose App with middleware type constructed from reference of application state. The closure is called
+    /// with the state every time App is built(once per worker thread of server) and it's output must impl
+    /// [Service] trait as middleware. See [middleware](crate::middleware) for more.
+    ///
+    /// This is useful for middleware depending on application state at construction time. e.g. reading config
+    /// or sharing a limiter owned by state. Middleware only reading state per request can use [App::enclosed]
+    /// and access state from [WebContext::state].
+    ///
+    /// Middleware enclosed with this method is applied after the state is constructed and it encloses all
+    /// middleware enclosed by [App::enclosed] and [App::enclosed_fn] regardless of the order of method calls.
+    /// It must be called after [App::with_state] and other state constructing methods and they are not available
+    /// on the App it returns:
+    ///
+    /// ```compile_fail
+    /// # use xitca_web::{handler::handler_service, middleware::limit::Limit, App, WebContext};
+    /// App::new()
+    ///     .at("/", handler_service(|_: &WebContext<'_, usize>| async { "" }))
+    ///     .with_state(1024usize)
+    ///     .enclosed_with_state(|limit: &usize| Limit::new().set_request_body_max_size(*limit))
+    ///     // state can not be replaced after middleware is constructed from it.
+    ///     .with_state(2048usize);
+    /// ```
+    ///
+    /// # Example
+    /// ```rust
+    /// # use xitca_web::{handler::handler_service, middleware::limit::Limit, App, WebContext};
+    /// #[derive(Clone)]
+    /// struct Config {
+    ///     body_limit: usize,
+    /// }
+    ///
+    /// App::new()
+    ///     .at("/", handler_service(|_: &WebContext<'_, Config>| async { "" }))
+    ///     .with_state(Config { body_limit: 1024 })
+    ///     // construct middleware with config from state.
+    ///     .enclosed_with_state(|config: &Config| Limit::new().set_request_body_max_size(config.body_limit));
+    /// ```
+    pub fn enclosed_with_state<F, T>(self, transform: F) -> App<R, StateEnclosed<CF, F>>
+    where
+        CF: IntoCtx,
+        F: Fn(&CF::Ctx) -> T + Send + Sync,
+    {
+        App {
+            router: self.router,
+            ctx_builder: StateEnclosed {
+                ctx_builder: self.ctx_builder,
+                transform,
+            },
+        }
+    }
+
     /// Mutate `<<Self::Response as Service<Req>>::Future as Future>::Output` type with given
     /// closure.
     pub fn map<T, Res, ResMap>(self, mapper: T) -> App<MapBuilder<R, T>, CF>
@@ -444,36 +741,62 @@ where
     R::Error: fmt::Debug + 'static,
 {
     /// Finish App build. No other App method can be called afterwards.
-    pub fn finish<C, ResB, SE>(
+    pub fn finish<C, S, ResB, SE>(
         self,
     ) -> impl Service<
-        Response = impl ReadyService + Service<WebRequest, Response = WebResponse<EitherResBody<ResB>>, Error = Infallible>,
+        Response = impl ReadyService<Ready = S::Ready>
+                       + Service<WebRequest, Response = WebResponse<EitherResBody<ResB>>, Error = Infallible>,
         Error = impl fmt::Debug,
     >
     where
-        R::Response: ReadyService + for<'r> Service<WebContext<'r, C>, Response = WebResponse<ResB>, Error = SE>,
+        S: ReadyService + for<'r> Service<WebContext<'r, C>, Response = WebResponse<ResB>, Error = SE>,
         SE: for<'r> Service<WebContext<'r, C>, Response = WebResponse, Error = Infallible>,
         CF: IntoCtx<Ctx = C>,
+        CF::Enclose: EncloseCtx<C, R::Response, Service = S>,
         C: 'static,
     {
         let App { ctx_builder, router } = self;
-        router
-            .enclosed(crate::middleware::WebContext)
-            .enclosed(ContextBuilder::new(ctx_builder.into_ctx()))
+        let (ctx_builder, enclose) = ctx_builder.into_ctx();
+        router.enclosed(StateBuilder { ctx_builder, enclose })
     }
 
-    /// Finish App build. No other App method can be called afterwards.
-    pub fn finish_boxed<C, ResB, SE, BE>(
-        self,
-    ) -> AppObject<impl ReadyService + Service<WebRequest, Response = WebResponse, Error = Infallible>>
+    /// Finish App build with all types erased. No other App method can be called afterwards.
+    ///
+    /// Unlike [App::finish] the output has concrete type of [AppObject] building [AppService] and it can be stored
+    /// in struct field or returned from function without leaking `impl Trait` types. Request, response and error
+    /// types are the same as [App::finish] except response body is erased to [ResponseBody].
+    ///
+    /// # Cost
+    /// Building service and every call of [Service::call] and [ReadyService::ready] goes through dynamic dispatch
+    /// and a heap allocated future. It's negligible for most applications but [App::finish] should be preferred
+    /// in hot path where naming the type is not needed.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use xitca_web::{handler::handler_service, App, AppObject, AppService};
+    /// struct Server {
+    ///     app: AppObject<AppService>,
+    /// }
+    ///
+    /// fn app() -> AppObject<AppService> {
+    ///     App::new()
+    ///         .at("/", handler_service(|| async { "hello,world!" }))
+    ///         .finish_boxed()
+    /// }
+    ///
+    /// let server = Server { app: app() };
+    /// ```
+    pub fn finish_boxed<C, S, ResB, SE, BE>(self) -> AppObject<AppService>
     where
         R: 'static,
-        R::Response:
-            ReadyService + for<'r> Service<WebContext<'r, C>, Response = WebResponse<ResB>, Error = SE> + 'static,
+        S: ReadyService + for<'r> Service<WebContext<'r, C>, Response = WebResponse<ResB>, Error = SE> + 'static,
+        S::Ready: 'static,
         SE: for<'r> Service<WebContext<'r, C>, Response = WebResponse, Error = Infallible> + 'static,
         ResB: Stream<Item = Result<Bytes, BE>> + 'static,
-        BE: error::Error + Send + Sync + 'static,
+        BE: 'static,
+        BodyError: From<BE>,
         CF: IntoCtx<Ctx = C> + 'static,
+        CF::Enclose: EncloseCtx<C, R::Response, Service = S> + 'static,
         C: 'static,
     {
         struct BoxApp<S>(S);
@@ -481,13 +804,19 @@ where
         impl<S, Arg> Service<Arg> for BoxApp<S>
         where
             S: Service<Arg>,
+            S::Response: ReadyService + Service<WebRequest, Response = WebResponse, Error = Infallible> + 'static,
+            <S::Response as ReadyService>::Ready: 'static,
             S::Error: fmt::Debug + 'static,
         {
-            type Response = S::Response;
+            type Response = AppService;
             type Error = Box<dyn fmt::Debug>;
 
             async fn call(&self, arg: Arg) -> Result<Self::Response, Self::Error> {
-                self.0.call(arg).await.map_err(|e| Box::new(e) as _)
+                self.0
+                    .call(arg)
+                    .await
+                    .map(|s| AppService(Box::new(s)))
+                    .map_err(|e| Box::new(e) as _)
             }
         }
 
@@ -498,7 +827,7 @@ where
     /// Finish App build and serve is with [HttpServer]. No other App method can be called afterwards.
     ///
     /// [HttpServer]: crate::server::HttpServer
-    pub fn serve<C, ResB, SE>(
+    pub fn serve<C, S, ResB, SE>(
         self,
     ) -> crate::server::HttpServer<
         impl Service<
@@ -509,10 +838,11 @@ where
     >
     where
         R: 'static,
-        R::Response: ReadyService + for<'r> Service<WebContext<'r, C>, Response = WebResponse<ResB>, Error = SE>,
+        S: ReadyService + for<'r> Service<WebContext<'r, C>, Response = WebResponse<ResB>, Error = SE> + 'static,
         SE: for<'r> Service<WebContext<'r, C>, Response = WebResponse, Error = Infallible> + 'static,
         ResB: 'static,
         CF: IntoCtx<Ctx = C> + 'static,
+        CF::Enclose: EncloseCtx<C, R::Response, Service = S> + 'static,
         C: 'static,
     {
         crate::server::HttpServer::serve(self.finish())
@@ -528,6 +858,14 @@ where
     fn path_gen(&mut self, prefix: &str) -> String {
         self.router.path_gen(prefix)
     }
+
+    fn path_alias(&self, prefix: &str) -> Vec<String> {
+        self.router.path_alias(prefix)
+    }
+
+    fn route_info(&self, path: &str, routes: &mut Vec<RouteInfo>) {
+        self.router.route_info(path, routes)
+    }
 }
 
 impl<R, F> RouteGen for App<R, F>
@@ -601,6 +939,53 @@ where
 pub type AppObject<S> =
     Box<dyn xitca_service::object::ServiceObject<(), Response = S, Error = Box<dyn fmt::Debug>> + Send + Sync>;
 
+/// type erased application service built by [App::finish_boxed].
+///
+/// [ReadyService::Ready] of application is boxed as [Any] trait object. It's only meant to be held until
+/// [Service::call] is finished and dropped afterwards.
+pub struct AppService(Box<dyn AppServiceObject>);
+
+// method names are distinct from ReadyService and Service traits to avoid ambiguity as the trait is implemented for
+// all services.
+trait AppServiceObject {
+    fn ready_boxed(&self) -> Pin<Box<dyn Future<Output = Box<dyn Any>> + '_>>;
+
+    fn call_boxed(&self, req: WebRequest) -> xitca_service::BoxFuture<'_, WebResponse, Infallible>;
+}
+
+impl<S> AppServiceObject for S
+where
+    S: ReadyService + Service<WebRequest, Response = WebResponse, Error = Infallible>,
+    S::Ready: 'static,
+{
+    fn ready_boxed(&self) -> Pin<Box<dyn Future<Output = Box<dyn Any>> + '_>> {
+        Box::pin(async { Box::new(ReadyService::ready(self).await) as _ })
+    }
+
+    fn call_boxed(&self, req: WebRequest) -> xitca_service::BoxFuture<'_, WebResponse, Infallible> {
+        Box::pin(Service::call(self, req))
+    }
+}
+
+impl Service<WebRequest> for AppService {
+    type Response = WebResponse;
+    type Error = Infallible;
+
+    #[inline]
+    async fn call(&self, req: WebRequest) -> Result<Self::Response, Self::Error> {
+        self.0.call_boxed(req).await
+    }
+}
+
+impl ReadyService for AppService {
+    type Ready = Box<dyn Any>;
+
+    #[inline]
+    async fn ready(&self) -> Self::Ready {
+        self.0.ready_boxed().await
+    }
+}
+
 #[cfg(test)]
 mod test {
     use xitca_unsafe_collection::futures::NowOrPanic;
@@ -790,4 +1175,309 @@ mod test {
 
         assert_eq!(res.status().as_u16(), 200);
     }
+
+    #[test]
+    fn routes() {
+        async fn handler(_: &WebContext<'_>) -> &'static str {
+            "996"
+        }
+
+        let app = App::new()
+            .at("/", get(handler_service(handler)).post(handler_service(handler)))
+            .at("/any", handler_service(handler))
+            .at(
+                "/scope",
+                App::new()
+                    .at("/nest", get(handler_service(handler)))
+                    .at("/deep", App::new().at("/inner", handler_service(handler))),
+            );
+
+        let routes = app.routes();
+
+        let paths = routes.iter().map(|r| r.path.as_str()).collect::<Vec<_>>();
+        assert_eq!(paths, ["/", "/any", "/scope/deep/inner", "/scope/nest"]);
+
+        assert_eq!(routes[0].methods, [Method::GET, Method::POST]);
+        assert!(routes[1].methods.is_empty());
+        assert!(routes[2].methods.is_empty());
+        assert_eq!(routes[3].methods, [Method::GET]);
+    }
+
+    #[cfg(feature = "params")]
+    #[test]
+    fn catch_all() {
+        use crate::{handler::params::ParamsRef, http::StatusCode, test::collect_string_body};
+
+        async fn file(params: ParamsRef<'_>) -> String {
+            format!("file {}", params.get("path").unwrap())
+        }
+
+        async fn index() -> &'static str {
+            "index"
+        }
+
+        let service = App::new()
+            .at("/files/index.html", get(handler_service(index)))
+            .at("/files/*path", get(handler_service(file)))
+            .finish()
+            .call(())
+            .now_or_panic()
+            .unwrap();
+
+        let call = |uri: &'static str| {
+            let req = request::Builder::default().uri(uri).body(Default::default()).unwrap();
+            let res = service.call(req).now_or_panic().unwrap();
+            let status = res.status();
+            let body = collect_string_body(res.into_body()).now_or_panic().unwrap();
+            (status, body)
+        };
+
+        // more specific route takes priority over catch-all.
+        assert_eq!(call("/files/index.html"), (StatusCode::OK, String::from("index")));
+        // catch-all param contains the remaining path.
+        assert_eq!(
+            call("/files/css/app.css"),
+            (StatusCode::OK, String::from("file css/app.css"))
+        );
+        assert_eq!(call("/files/index.html/raw").1, "file index.html/raw");
+        assert_eq!(call("/other").0, StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    #[should_panic(expected = "catch-all parameters are only allowed at the end of a route")]
+    fn catch_all_not_at_end() {
+        let _ = App::new()
+            .at("/files/*path/raw", get(handler_service(|| async { "raw" })))
+            .at("/infer", handler_service(|_: &WebContext<'_>| async { "infer" }));
+    }
+
+    #[test]
+    fn router_error_body() {
+        use crate::{
+            http::{const_header_value::JSON, header::ALLOW, StatusCode},
+            test::collect_string_body,
+        };
+
+        let service = App::new()
+            .at("/", get(handler_service(|| async { "root" })))
+            .at(
+                "/v2",
+                App::new().at("/index", get(handler_service(|| async { "index" }))),
+            )
+            .not_found_body(JSON, r#"{"error":"not found"}"#)
+            .method_not_allowed_body(TEXT_UTF8, "not allowed")
+            .finish()
+            .call(())
+            .now_or_panic()
+            .unwrap();
+
+        let call = |method: Method, uri: &'static str| {
+            let req = request::Builder::default()
+                .method(method)
+                .uri(uri)
+                .body(Default::default())
+                .unwrap();
+            service.call(req).now_or_panic().unwrap()
+        };
+
+        for uri in ["/none", "/v2/none"] {
+            let res = call(Method::GET, uri);
+            assert_eq!(res.status(), StatusCode::NOT_FOUND);
+            assert_eq!(res.headers().get(CONTENT_TYPE).unwrap(), JSON);
+            let body = collect_string_body(res.into_body()).now_or_panic().unwrap();
+            assert_eq!(body, r#"{"error":"not found"}"#);
+        }
+
+        let res = call(Method::POST, "/");
+        assert_eq!(res.status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(res.headers().get(CONTENT_TYPE).unwrap(), TEXT_UTF8);
+        assert!(res.headers().get(ALLOW).unwrap().to_str().unwrap().contains("GET"));
+        let body = collect_string_body(res.into_body()).now_or_panic().unwrap();
+        assert_eq!(body, "not allowed");
+    }
+
+    #[test]
+    fn enclosed_guard_fn() {
+        use core::ops::ControlFlow;
+
+        use crate::{
+            http::{header::AUTHORIZATION, StatusCode},
+            test::collect_string_body,
+        };
+
+        async fn auth<C, B>(ctx: &mut WebContext<'_, C, B>) -> ControlFlow<Result<WebResponse, Error>> {
+            match ctx.req().headers().get(AUTHORIZATION) {
+                Some(v) if v == "Bearer dagongren" => ControlFlow::Continue(()),
+                _ => ControlFlow::Break(Err(Error::from(StatusCode::UNAUTHORIZED))),
+            }
+        }
+
+        let service = App::new()
+            .at("/", handler_service(|| async { "authorized" }))
+            .enclosed_guard_fn(auth)
+            .finish()
+            .call(())
+            .now_or_panic()
+            .unwrap();
+
+        let res = service.call(WebRequest::default()).now_or_panic().unwrap();
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+        let mut req = WebRequest::default();
+        req.headers_mut()
+            .insert(AUTHORIZATION, HeaderValue::from_static("Bearer dagongren"));
+        let res = service.call(req).now_or_panic().unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = collect_string_body(res.into_body()).now_or_panic().unwrap();
+        assert_eq!(body, "authorized");
+    }
+
+    #[test]
+    fn async_state_per_build() {
+        use std::{
+            rc::Rc,
+            sync::atomic::{AtomicUsize, Ordering},
+        };
+
+        static BUILT: AtomicUsize = AtomicUsize::new(0);
+
+        async fn handler(StateRef(state): StateRef<'_, Rc<usize>>) -> String {
+            state.to_string()
+        }
+
+        let app = App::new()
+            .with_async_state(|| async {
+                let n = BUILT.fetch_add(1, Ordering::SeqCst);
+                Ok::<_, Infallible>(Rc::new(n))
+            })
+            .at("/", handler_service(handler))
+            .finish();
+
+        // every built service(one per worker thread) owns a separate copy of state.
+        let _s1 = app.call(()).now_or_panic().unwrap();
+        let _s2 = app.call(()).now_or_panic().unwrap();
+        assert_eq!(BUILT.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn enclosed_with_state() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        static BUILT: AtomicUsize = AtomicUsize::new(0);
+
+        let app = App::new()
+            .at("/", get(handler_service(stateless_handler)))
+            .enclosed(Middleware)
+            .with_state(String::from("state"))
+            .enclosed_with_state(|state: &String| {
+                assert_eq!(state, "state");
+                BUILT.fetch_add(1, Ordering::SeqCst);
+                Middleware
+            })
+            .enclosed_with_state(|_: &String| UncheckedReady)
+            .finish();
+
+        // middleware is constructed with state every time app is built.
+        let service = app.call(()).now_or_panic().unwrap();
+        let _ = app.call(()).now_or_panic().unwrap();
+        assert_eq!(BUILT.load(Ordering::SeqCst), 2);
+
+        let res = service.call(WebRequest::default()).now_or_panic().unwrap();
+        assert_eq!(res.status().as_u16(), 200);
+    }
+
+    #[test]
+    fn state_init() {
+        async fn handler(StateRef(state): StateRef<'_, usize>) -> String {
+            state.to_string()
+        }
+
+        let service = App::new()
+            .with_state_init(|| "996".parse::<usize>())
+            .at("/", handler_service(handler))
+            .finish()
+            .call(())
+            .now_or_panic()
+            .unwrap();
+
+        let res = service.call(WebRequest::default()).now_or_panic().unwrap();
+        assert_eq!(res.status().as_u16(), 200);
+
+        let res = App::new()
+            .with_state_init(|| "nah".parse::<usize>())
+            .at("/", handler_service(handler))
+            .finish()
+            .call(())
+            .now_or_panic();
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn finish_build_multiple() {
+        use std::sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        };
+
+        async fn handler(StateRef(state): StateRef<'_, Arc<AtomicUsize>>) -> String {
+            state.fetch_add(1, Ordering::SeqCst).to_string()
+        }
+
+        let shared = Arc::new(AtomicUsize::new(0));
+        let builds = Arc::new(AtomicUsize::new(0));
+
+        let factory = App::new()
+            .with_state_init({
+                let shared = shared.clone();
+                let builds = builds.clone();
+                move || {
+                    builds.fetch_add(1, Ordering::SeqCst);
+                    Ok::<_, Infallible>(shared.clone())
+                }
+            })
+            .at("/", handler_service(handler))
+            .finish();
+
+        // one finished app can build service for every listener.
+        let service1 = factory.call(()).now_or_panic().unwrap();
+        let service2 = factory.call(()).now_or_panic().unwrap();
+        assert_eq!(builds.load(Ordering::SeqCst), 2);
+
+        let res = service1.call(WebRequest::default()).now_or_panic().unwrap();
+        assert_eq!(res.status().as_u16(), 200);
+        let res = service2.call(WebRequest::default()).now_or_panic().unwrap();
+        assert_eq!(res.status().as_u16(), 200);
+
+        // state shared through smart pointer is observed by services of all listeners.
+        assert_eq!(shared.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn finish_boxed() {
+        async fn handler(StateRef(state): StateRef<'_, String>) -> String {
+            state.clone()
+        }
+
+        struct Server {
+            app: AppObject<AppService>,
+        }
+
+        let server = Server {
+            app: App::new()
+                .with_state(String::from("state"))
+                .at("/", handler_service(handler))
+                .finish_boxed(),
+        };
+
+        let service = server.app.call(()).now_or_panic().unwrap();
+        let _ready = service.ready().now_or_panic();
+
+        let res = service.call(WebRequest::default()).now_or_panic().unwrap();
+        assert_eq!(res.status().as_u16(), 200);
+
+        let mut req = WebRequest::default();
+        *req.uri_mut() = "/nah".parse().unwrap();
+        let res = service.call(req).now_or_panic().unwrap();
+        assert_eq!(res.status().as_u16(), 404);
+    }
 }
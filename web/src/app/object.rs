@@ -1,9 +1,9 @@
 use core::marker::PhantomData;
 
-use xitca_http::util::service::router::{IntoObject, PathGen, RouteGen, RouteObject};
+use xitca_http::util::service::router::{IntoObject, PathGen, RouteGen, RouteInfo, RouteObject};
 use xitca_service::{object::ServiceObject, Service};
 
-use crate::context::WebContext;
+use crate::{context::WebContext, middleware::server_timing::service::TimedRoute};
 
 pub type WebObject<C, B, Res, Err> = Box<dyn for<'r> ServiceObject<WebContext<'r, C, B>, Response = Res, Error = Err>>;
 
@@ -26,6 +26,14 @@ where
             fn path_gen(&mut self, prefix: &str) -> String {
                 self.0.path_gen(prefix)
             }
+
+            fn path_alias(&self, prefix: &str) -> Vec<String> {
+                self.0.path_alias(prefix)
+            }
+
+            fn route_info(&self, path: &str, routes: &mut Vec<RouteInfo>) {
+                self.0.route_info(path, routes)
+            }
         }
 
         impl<I, C, B> RouteGen for Builder<I, C, B>
@@ -48,7 +56,7 @@ where
             type Error = I::Error;
 
             async fn call(&self, arg: ()) -> Result<Self::Response, Self::Error> {
-                self.0.call(arg).await.map(|s| Box::new(s) as _)
+                self.0.call(arg).await.map(|s| Box::new(TimedRoute(s)) as _)
             }
         }
 
@@ -1,17 +1,23 @@
-use xitca_http::util::service::router::{IntoObject, PathGen, RouteGen, Router, RouterError, RouterMapErr, TypedRoute};
+use xitca_http::util::service::router::{
+    IntoObject, PathGen, RouteGen, RouteInfo, Router, RouterError, RouterMapErr, TypedRoute,
+};
 
 use crate::{
-    error::Error,
+    error::{Error, RouterErrorBody},
     service::{ready::ReadyService, Service},
     WebContext,
 };
 
 /// application wrap around [Router] and transform it's error type into [Error]
-pub struct AppRouter<Obj>(Router<Obj>);
+pub struct AppRouter<Obj>(Router<Obj>, Option<RouterErrorBody>);
 
 impl<Obj> AppRouter<Obj> {
     pub(super) fn new() -> Self {
-        Self(Router::new())
+        Self(Router::new(), None)
+    }
+
+    pub(super) fn error_body(&mut self) -> &mut RouterErrorBody {
+        self.1.get_or_insert_with(RouterErrorBody::new)
     }
 
     pub(super) fn insert<F, Arg, Req>(mut self, path: &'static str, builder: F) -> Self
@@ -31,6 +37,13 @@ impl<Obj> AppRouter<Obj> {
         self.0 = self.0.insert_typed(t);
         self
     }
+
+    pub(super) fn routes(&self) -> Vec<RouteInfo>
+    where
+        Obj: PathGen,
+    {
+        self.0.routes()
+    }
 }
 
 impl<Obj> PathGen for AppRouter<Obj>
@@ -40,6 +53,14 @@ where
     fn path_gen(&mut self, prefix: &str) -> String {
         self.0.path_gen(prefix)
     }
+
+    fn path_alias(&self, prefix: &str) -> Vec<String> {
+        self.0.path_alias(prefix)
+    }
+
+    fn route_info(&self, path: &str, routes: &mut Vec<RouteInfo>) {
+        self.0.route_info(path, routes)
+    }
 }
 
 impl<Obj> RouteGen for AppRouter<Obj>
@@ -61,11 +82,12 @@ where
     type Error = <Router<Obj> as Service<Arg>>::Error;
 
     async fn call(&self, arg: Arg) -> Result<Self::Response, Self::Error> {
-        self.0.call(arg).await.map(RouterService)
+        let service = self.0.call(arg).await?;
+        Ok(RouterService(service, self.1.clone()))
     }
 }
 
-pub struct RouterService<S>(S);
+pub struct RouterService<S>(S, Option<RouterErrorBody>);
 
 impl<'r, S, C, B, Res, E> Service<WebContext<'r, C, B>> for RouterService<S>
 where
@@ -76,8 +98,18 @@ where
     type Error = Error;
 
     #[inline]
-    async fn call(&self, req: WebContext<'r, C, B>) -> Result<Self::Response, Self::Error> {
-        self.0.call(req).await.map_err(Into::into)
+    async fn call(&self, mut req: WebContext<'r, C, B>) -> Result<Self::Response, Self::Error> {
+        self.0.call(req.reborrow()).await.map_err(|e| {
+            // error of nested application is passed through outer application's router. the body set by inner
+            // most application or already in request extensions takes precedence.
+            if let Some(ref body) = self.1 {
+                let ext = req.req_mut().extensions_mut();
+                if ext.get::<RouterErrorBody>().is_none() {
+                    ext.insert(body.clone());
+                }
+            }
+            e.into()
+        })
     }
 }
 
@@ -0,0 +1,109 @@
+use core::{convert::Infallible, fmt, future::Future};
+
+use crate::{
+    body::{Either, ResponseBody},
+    context::WebContext,
+    http::{WebRequest, WebResponse},
+    middleware::context::service::call_with_state,
+    service::{ready::ReadyService, Service},
+};
+
+use super::BoxFuture;
+
+/// application state builder with middleware constructed from reference of the state.
+///
+/// See [App::enclosed_with_state](super::App::enclosed_with_state) for detail.
+pub struct StateEnclosed<CF, F> {
+    pub(super) ctx_builder: CF,
+    pub(super) transform: F,
+}
+
+// helper trait for enclosing service with middleware constructed from reference of application state.
+pub trait EncloseCtx<C, S> {
+    type Service;
+
+    fn enclose(&self, ctx: &C, service: S) -> impl Future<Output = Result<Self::Service, Box<dyn fmt::Debug>>>;
+}
+
+impl<C, S> EncloseCtx<C, S> for () {
+    type Service = S;
+
+    async fn enclose(&self, _: &C, service: S) -> Result<Self::Service, Box<dyn fmt::Debug>> {
+        Ok(service)
+    }
+}
+
+impl<C, S, E, F, T> EncloseCtx<C, S> for (E, F)
+where
+    E: EncloseCtx<C, S>,
+    F: Fn(&C) -> T,
+    T: Service<Result<E::Service, Infallible>>,
+    T::Error: fmt::Debug + 'static,
+{
+    type Service = T::Response;
+
+    async fn enclose(&self, ctx: &C, service: S) -> Result<Self::Service, Box<dyn fmt::Debug>> {
+        let service = self.0.enclose(ctx, service).await?;
+        (self.1)(ctx)
+            .call(Ok(service))
+            .await
+            .map_err(|e| Box::new(e) as Box<dyn fmt::Debug>)
+    }
+}
+
+// middleware builder constructing application state and enclosing application service with middleware
+// depending on it.
+pub struct StateBuilder<CF, E> {
+    pub(super) ctx_builder: CF,
+    pub(super) enclose: E,
+}
+
+impl<CF, E, C, S, Err> Service<Result<S, Err>> for StateBuilder<CF, E>
+where
+    CF: Fn() -> BoxFuture<C>,
+    E: EncloseCtx<C, S>,
+    Err: fmt::Debug + 'static,
+{
+    type Response = StateService<C, E::Service>;
+    type Error = Box<dyn fmt::Debug>;
+
+    async fn call(&self, res: Result<S, Err>) -> Result<Self::Response, Self::Error> {
+        let service = res.map_err(|e| Box::new(e) as Box<dyn fmt::Debug>)?;
+        let state = (self.ctx_builder)().await?;
+        let service = self.enclose.enclose(&state, service).await?;
+        Ok(StateService { state, service })
+    }
+}
+
+pub struct StateService<C, S> {
+    state: C,
+    service: S,
+}
+
+type EitherResBody<B> = Either<B, ResponseBody>;
+
+impl<C, S, ResB, SE> Service<WebRequest> for StateService<C, S>
+where
+    S: for<'r> Service<WebContext<'r, C>, Response = WebResponse<ResB>, Error = SE>,
+    SE: for<'r> Service<WebContext<'r, C>, Response = WebResponse, Error = Infallible>,
+{
+    type Response = WebResponse<EitherResBody<ResB>>;
+    type Error = Infallible;
+
+    #[inline]
+    async fn call(&self, req: WebRequest) -> Result<Self::Response, Self::Error> {
+        call_with_state(&self.service, req, &self.state).await
+    }
+}
+
+impl<C, S> ReadyService for StateService<C, S>
+where
+    S: ReadyService,
+{
+    type Ready = S::Ready;
+
+    #[inline]
+    async fn ready(&self) -> Self::Ready {
+        self.service.ready().await
+    }
+}
@@ -1,8 +1,23 @@
 //! http body types and traits.
 
+use core::{
+    cmp,
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+
+use std::io;
+
 use futures_core::stream::Stream;
+use pin_project_lite::pin_project;
+use tokio::io::{AsyncBufRead, AsyncRead, ReadBuf};
 
-pub use xitca_http::body::{none_body_hint, BoxBody, RequestBody, ResponseBody, NONE_BODY_HINT};
+pub use xitca_http::body::{
+    none_body_hint, BodyLengthMismatch, BoxBody, Flush, RequestBody, ResponseBody, SizedStream, NONE_BODY_HINT,
+};
+
+#[cfg(feature = "http1")]
+pub use xitca_http::h1::RequestTrailers;
 
 pub(crate) use xitca_http::body::Either;
 
@@ -24,6 +39,96 @@ where
     type Error = E;
 }
 
+pin_project! {
+    /// adapter of [BodyStream] implementing [AsyncRead] and [AsyncBufRead] traits. Useful for byte oriented
+    /// libraries(file copying, xml/csv parsers, etc) expecting an async reader instead of a stream of chunks.
+    ///
+    /// Chunk of body is buffered until it's fully read. Error produced by body is converted to [io::Error] and
+    /// [io::Error] produced by body is forwarded as is.
+    ///
+    /// For handler functions see [BodyReader] type extractor. For middleware see [WebContext::body_reader].
+    ///
+    /// [WebContext::body_reader]: crate::WebContext::body_reader
+    pub struct BodyReader<B>
+    where
+        B: BodyStream,
+    {
+        #[pin]
+        body: B,
+        chunk: Option<B::Chunk>,
+        pos: usize,
+    }
+}
+
+impl<B> BodyReader<B>
+where
+    B: BodyStream,
+{
+    /// construct a new reader from given body stream.
+    pub const fn new(body: B) -> Self {
+        Self {
+            body,
+            chunk: None,
+            pos: 0,
+        }
+    }
+}
+
+impl<B> AsyncBufRead for BodyReader<B>
+where
+    B: BodyStream,
+{
+    fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
+        let mut this = self.project();
+        loop {
+            if this
+                .chunk
+                .as_ref()
+                .is_some_and(|chunk| chunk.as_ref().len() > *this.pos)
+            {
+                break;
+            }
+            match ready!(this.body.as_mut().poll_next(cx)) {
+                Some(Ok(chunk)) => {
+                    *this.chunk = Some(chunk);
+                    *this.pos = 0;
+                }
+                Some(Err(e)) => {
+                    *this.chunk = None;
+                    let e = match e.into().downcast::<io::Error>() {
+                        Ok(e) => *e,
+                        Err(e) => io::Error::other(e),
+                    };
+                    return Poll::Ready(Err(e));
+                }
+                None => {
+                    *this.chunk = None;
+                    return Poll::Ready(Ok(&[]));
+                }
+            }
+        }
+        let chunk = this.chunk.as_ref().unwrap().as_ref();
+        Poll::Ready(Ok(&chunk[*this.pos..]))
+    }
+
+    fn consume(self: Pin<&mut Self>, amt: usize) {
+        *self.project().pos += amt;
+    }
+}
+
+impl<B> AsyncRead for BodyReader<B>
+where
+    B: BodyStream,
+{
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        let chunk = ready!(self.as_mut().poll_fill_buf(cx))?;
+        let n = cmp::min(chunk.len(), buf.remaining());
+        buf.put_slice(&chunk[..n]);
+        self.consume(n);
+        Poll::Ready(Ok(()))
+    }
+}
+
 #[cfg(feature = "nightly")]
 pub use nightly::AsyncBody;
 
@@ -1,14 +1,20 @@
 //! web context types.
 
 use core::{
+    any::Any,
     cell::{Ref, RefCell, RefMut},
     mem,
 };
 
+use std::net::SocketAddr;
+
 use super::{
-    body::{RequestBody, ResponseBody},
+    body::{BodyReader, BodyStream, RequestBody, ResponseBody},
     handler::FromRequest,
-    http::{BorrowReq, BorrowReqMut, IntoResponse, Request, RequestExt, WebRequest, WebResponse},
+    http::{
+        BorrowReq, BorrowReqMut, Disconnected, Extensions, HeaderMap, IntoResponse, Method, Request, RequestExt, Uri,
+        Version, WebRequest, WebResponse,
+    },
 };
 
 /// web context type focus on stateful and side effect based request data access.
@@ -85,6 +91,169 @@ impl<'a, C, B> WebContext<'a, C, B> {
         self.ctx
     }
 
+    /// Get a future that resolves when client of current request disconnected.
+    ///
+    /// Long running handler can race it's work with the returned future and abort early when client is gone.
+    /// See [Disconnect](crate::http::Disconnect) for detail of when disconnect is observed.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use xitca_web::WebContext;
+    /// async fn handler(ctx: &WebContext<'_>) -> &'static str {
+    ///     tokio::select! {
+    ///         _ = ctx.on_disconnect() => "client is gone",
+    ///         res = expensive_work() => res
+    ///     }
+    /// }
+    ///
+    /// # async fn expensive_work() -> &'static str { "done" }
+    /// ```
+    #[inline]
+    pub fn on_disconnect(&self) -> Disconnected {
+        self.req.body().disconnect().disconnected()
+    }
+
+    /// Get a future that resolves when the server worker handling current request starts shutdown.
+    ///
+    /// After shutdown starts the server stops accepting new connections and gives in-flight requests the remaining
+    /// time of shutdown timeout (the grace deadline) to finish. Requests still running after the deadline are force
+    /// dropped. Long-polling and streaming handlers can race their work with the returned future and end early so
+    /// graceful shutdown is not held up until the deadline. See [shutdown](xitca_server::shutdown) for detail.
+    ///
+    /// The returned future never resolves when App is not running on [HttpServer](crate::HttpServer). (For example
+    /// in tests calling App service directly.)
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use xitca_web::WebContext;
+    /// async fn long_poll(ctx: &WebContext<'_>) -> &'static str {
+    ///     tokio::select! {
+    ///         _ = ctx.on_shutdown() => "server is going away. retry later",
+    ///         res = wait_for_update() => res
+    ///     }
+    /// }
+    ///
+    /// # async fn wait_for_update() -> &'static str { "updated" }
+    /// ```
+    #[cfg(feature = "__server")]
+    #[inline]
+    pub fn on_shutdown(&self) -> impl core::future::Future<Output = ()> + 'static {
+        xitca_server::shutdown::on_shutdown()
+    }
+
+    /// Look up typed data by it's type. Returns [None] when no data of given type is registered.
+    ///
+    /// The look up order is:
+    /// - App state when it's exactly the type of T. (See [App::with_state](crate::App::with_state))
+    /// - request's [Extensions](crate::http::Extensions). (See [Extension](crate::middleware::Extension))
+    ///
+    /// This is a runtime alternative of [StateRef](crate::handler::state::StateRef) and
+    /// [ExtensionRef](crate::handler::extension::ExtensionRef) extractors for middleware operating on raw
+    /// WebContext.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use xitca_web::WebContext;
+    /// fn middleware_logic(ctx: &WebContext<'_, usize>) {
+    ///     // app state is usize.
+    ///     assert!(ctx.data::<usize>().is_some());
+    ///     // String type is not registered.
+    ///     assert!(ctx.data::<String>().is_none());
+    /// }
+    /// ```
+    pub fn data<T>(&self) -> Option<&T>
+    where
+        C: 'static,
+        T: Send + Sync + 'static,
+    {
+        (self.ctx as &dyn Any)
+            .downcast_ref()
+            .or_else(|| self.req.extensions().get())
+    }
+
+    /// Insert typed data into request's [Extensions](crate::http::Extensions). Previous data of the same type
+    /// would be replaced and returned.
+    ///
+    /// Inserted data is visible to all downstream services sharing the same request, including the ones receiving
+    /// context from [WebContext::reborrow], and can be extracted by handler with
+    /// [ExtensionRef](crate::handler::extension::ExtensionRef) and
+    /// [ExtensionOwn](crate::handler::extension::ExtensionOwn) extractors.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use xitca_web::{
+    /// #   error::Error,
+    /// #   handler::{extension::ExtensionRef, handler_service},
+    /// #   http::WebResponse,
+    /// #   service::Service,
+    /// #   App, WebContext
+    /// # };
+    /// #[derive(Clone)]
+    /// struct RequestId(u64);
+    ///
+    /// async fn middleware<S>(next: &S, mut ctx: WebContext<'_>) -> Result<WebResponse, Error>
+    /// where
+    ///     S: for<'r> Service<WebContext<'r>, Response = WebResponse, Error = Error>
+    /// {
+    ///     ctx.insert_extension(RequestId(996));
+    ///     next.call(ctx).await
+    /// }
+    ///
+    /// async fn handler(ExtensionRef(id): ExtensionRef<'_, RequestId>) -> String {
+    ///     id.0.to_string()
+    /// }
+    ///
+    /// App::new()
+    ///     .at("/", handler_service(handler))
+    ///     .enclosed_fn(middleware);
+    /// ```
+    #[inline]
+    pub fn insert_extension<T>(&mut self, value: T) -> Option<T>
+    where
+        T: Clone + Send + Sync + 'static,
+    {
+        self.req.extensions_mut().insert(value)
+    }
+
+    /// Get an immutable reference of typed data from request's [Extensions](crate::http::Extensions).
+    /// Returns [None] when no data of given type is inserted.
+    #[inline]
+    pub fn extension<T>(&self) -> Option<&T>
+    where
+        T: Send + Sync + 'static,
+    {
+        self.req.extensions().get()
+    }
+
+    /// Clone head of request into an owned [RequestSnapshot] that can be moved into spawned task.
+    ///
+    /// WebContext borrows request and can't outlive the handler. Snapshot is useful for fire-and-forget work like
+    /// logging and auditing that runs after response is sent. Request body is not included as it's consumed by
+    /// type extractors.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use xitca_web::WebContext;
+    /// async fn handler(ctx: &WebContext<'_>) -> &'static str {
+    ///     let snapshot = ctx.snapshot();
+    ///     tokio::spawn(async move {
+    ///         // audit request in background.
+    ///         println!("{} {} from {}", snapshot.method, snapshot.uri, snapshot.socket_addr);
+    ///     });
+    ///     "done"
+    /// }
+    /// ```
+    pub fn snapshot(&self) -> RequestSnapshot {
+        RequestSnapshot {
+            method: self.req.method().clone(),
+            uri: self.req.uri().clone(),
+            version: self.req.version(),
+            headers: self.req.headers().clone(),
+            extensions: self.req.extensions().clone(),
+            socket_addr: *self.req.body().socket_addr(),
+        }
+    }
+
     /// Get an immutable reference of [WebRequest]
     #[inline]
     pub fn req(&self) -> &WebRequest<()> {
@@ -117,12 +286,54 @@ impl<'a, C, B> WebContext<'a, C, B> {
         self.body.get_mut()
     }
 
+    /// Take ownership of request body stream and leave a default(empty) body in it's place. Type extractors and
+    /// handlers observe the body as consumed afterwards.
+    ///
+    /// The stream is moved out as is without collecting it into memory and can be passed to response directly
+    /// with [ResponseBody::box_stream]. e.g. for echoing or forwarding large upload.
+    ///
+    /// This API takes &mut WebContext so the body can't be borrowed through [WebContext::body] or
+    /// [WebContext::body_borrow_mut] while it's taken and there is no runtime borrow check overhead.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use std::convert::Infallible;
+    /// # use xitca_web::{body::ResponseBody, http::WebResponse, service::fn_service, App, WebContext};
+    /// async fn echo(mut ctx: WebContext<'_>) -> Result<WebResponse, Infallible> {
+    ///     let body = ctx.take_body();
+    ///     Ok(ctx.into_response(ResponseBody::box_stream(body)))
+    /// }
+    ///
+    /// App::new().at("/echo", fn_service(echo));
+    /// ```
+    ///
+    /// For handler functions see [Body](crate::handler::body::Body) type extractor.
+    #[inline]
+    pub fn take_body(&mut self) -> B
+    where
+        B: Default,
+    {
+        mem::take(self.body_get_mut())
+    }
+
+    /// Take ownership of request body stream like [WebContext::take_body] and wrap it with [BodyReader] which
+    /// implements `tokio::io::{AsyncRead, AsyncBufRead}` traits.
+    ///
+    /// For handler functions see [BodyReader] type extractor.
+    #[inline]
+    pub fn body_reader(&mut self) -> BodyReader<B>
+    where
+        B: BodyStream + Default,
+    {
+        BodyReader::new(self.take_body())
+    }
+
     pub fn take_request(&mut self) -> WebRequest<B>
     where
         B: Default,
     {
-        let head = mem::take(self.req_mut());
-        let body = self.take_body_mut();
+        let head = self.take_req();
+        let body = self.take_body();
         head.map(|ext| ext.map_body(|_| body))
     }
 
@@ -130,8 +341,8 @@ impl<'a, C, B> WebContext<'a, C, B> {
     ///
     /// The heap allocation of request would be re-used.
     #[inline]
-    pub fn into_response<ResB: Into<ResponseBody>>(self, body: ResB) -> WebResponse {
-        self.req.as_response(body.into())
+    pub fn into_response<ResB: Into<ResponseBody>>(mut self, body: ResB) -> WebResponse {
+        self.as_response(body)
     }
 
     /// Transform &mut self to a WebResponse with given body type.
@@ -139,7 +350,18 @@ impl<'a, C, B> WebContext<'a, C, B> {
     /// The heap allocation of request would be re-used.
     #[inline]
     pub fn as_response<ResB: Into<ResponseBody>>(&mut self, body: ResB) -> WebResponse {
-        self.req.as_response(body.into())
+        self.take_req().into_response(body.into())
+    }
+
+    // take request head and leave a default one in it's place. matched route is copied to the default head so
+    // middlewares enclosing router can still observe it after request is consumed by inner services.
+    pub(crate) fn take_req(&mut self) -> WebRequest<()> {
+        let req = mem::take(self.req_mut());
+        self.req_mut()
+            .body_mut()
+            .matched_route_mut()
+            .clone_from(req.body().matched_route());
+        req
     }
 
     pub(crate) fn take_body_ref(&self) -> B
@@ -148,13 +370,24 @@ impl<'a, C, B> WebContext<'a, C, B> {
     {
         mem::take(&mut *self.body_borrow_mut())
     }
+}
 
-    pub(crate) fn take_body_mut(&mut self) -> B
-    where
-        B: Default,
-    {
-        mem::take(self.body_get_mut())
-    }
+/// owned copy of request head produced by [WebContext::snapshot].
+#[derive(Clone, Debug)]
+pub struct RequestSnapshot {
+    /// method of request.
+    pub method: Method,
+    /// uri of request.
+    pub uri: Uri,
+    /// http version of request.
+    pub version: Version,
+    /// headers of request.
+    pub headers: HeaderMap,
+    /// extensions of request at the time of snapshot. Data inserted into request extensions must be [Clone] and
+    /// it's cloned along with the snapshot.
+    pub extensions: Extensions,
+    /// socket address of client.
+    pub socket_addr: SocketAddr,
 }
 
 impl<C, B, T> BorrowReq<T> for WebContext<'_, C, B>
@@ -227,4 +460,80 @@ mod test {
 
         assert_eq!(path, "/foo");
     }
+
+    #[test]
+    fn snapshot() {
+        let mut ctx = WebContext::new_test(());
+        let mut ctx = ctx.as_web_ctx();
+
+        *ctx.req_mut().uri_mut() = Uri::from_static("/audit?id=996");
+        ctx.req_mut()
+            .headers_mut()
+            .insert("x-user", crate::http::header::HeaderValue::from_static("foo"));
+        ctx.insert_extension(String::from("996"));
+
+        let snapshot = ctx.snapshot();
+
+        std::thread::spawn(move || {
+            assert_eq!(snapshot.method, Method::GET);
+            assert_eq!(snapshot.uri.path(), "/audit");
+            assert_eq!(snapshot.headers.get("x-user").unwrap(), "foo");
+            assert_eq!(snapshot.extensions.get::<String>().unwrap(), "996");
+        })
+        .join()
+        .unwrap();
+    }
+
+    #[test]
+    fn data() {
+        let mut ctx = WebContext::new_test(996usize);
+        let mut ctx = ctx.as_web_ctx();
+
+        assert_eq!(ctx.data::<usize>(), Some(&996));
+        assert!(ctx.data::<String>().is_none());
+
+        ctx.req_mut().extensions_mut().insert(String::from("996"));
+
+        assert_eq!(ctx.data::<String>().map(String::as_str), Some("996"));
+    }
+
+    #[test]
+    fn extension() {
+        use crate::{
+            handler::{extension::ExtensionRef, handler_service},
+            service::Service,
+            App,
+        };
+
+        let mut ctx = WebContext::new_test(());
+        let mut ctx = ctx.as_web_ctx();
+
+        assert!(ctx.extension::<String>().is_none());
+        assert!(ctx.reborrow().insert_extension(String::from("996")).is_none());
+        assert_eq!(ctx.extension::<String>().map(String::as_str), Some("996"));
+        assert_eq!(ctx.insert_extension(String::from("251")).as_deref(), Some("996"));
+
+        async fn middleware<S>(next: &S, mut ctx: WebContext<'_>) -> Result<WebResponse, crate::error::Error>
+        where
+            S: for<'r> Service<WebContext<'r>, Response = WebResponse, Error = crate::error::Error>,
+        {
+            ctx.insert_extension(996usize);
+            next.call(ctx).await
+        }
+
+        async fn handler(ExtensionRef(n): ExtensionRef<'_, usize>) -> String {
+            n.to_string()
+        }
+
+        let service = App::new()
+            .at("/", handler_service(handler))
+            .enclosed_fn(middleware)
+            .finish()
+            .call(())
+            .now_or_panic()
+            .unwrap();
+
+        let res = service.call(WebRequest::default()).now_or_panic().unwrap();
+        assert_eq!(res.status().as_u16(), 200);
+    }
 }
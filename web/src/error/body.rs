@@ -2,7 +2,7 @@ use core::fmt;
 
 use std::error;
 
-use super::{error_from_service, forward_blank_bad_request};
+use super::{blank_error_service, error_from_service, forward_blank_bad_request};
 
 pub use xitca_http::error::BodyError;
 
@@ -21,3 +21,42 @@ impl error::Error for BodyOverFlow {}
 
 error_from_service!(BodyOverFlow);
 forward_blank_bad_request!(BodyOverFlow);
+
+/// error type for decompressed request body going beyond limits of [`Decompress`] middleware.
+/// produce "413 Payload Too Large" response.
+///
+/// [`Decompress`]: crate::middleware::decompress::Decompress
+#[derive(Debug, Clone)]
+pub struct DecompressOverFlow {
+    #[cfg(any(feature = "compress-br", feature = "compress-gz", feature = "compress-de"))]
+    pub(crate) kind: DecompressOverFlowKind,
+}
+
+#[cfg(any(feature = "compress-br", feature = "compress-gz", feature = "compress-de"))]
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum DecompressOverFlowKind {
+    Size(usize),
+    Ratio(usize),
+}
+
+impl fmt::Display for DecompressOverFlow {
+    #[cfg(any(feature = "compress-br", feature = "compress-gz", feature = "compress-de"))]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            DecompressOverFlowKind::Size(limit) => write!(f, "decompressed body size reached limit: {limit} bytes"),
+            DecompressOverFlowKind::Ratio(limit) => {
+                write!(f, "decompressed body reached compression ratio limit: {limit}")
+            }
+        }
+    }
+
+    #[cfg(not(any(feature = "compress-br", feature = "compress-gz", feature = "compress-de")))]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("decompressed body reached limit")
+    }
+}
+
+impl error::Error for DecompressOverFlow {}
+
+error_from_service!(DecompressOverFlow);
+blank_error_service!(DecompressOverFlow, crate::http::StatusCode::PAYLOAD_TOO_LARGE);
@@ -73,14 +73,18 @@
 mod body;
 mod extension;
 mod header;
+mod precondition;
 mod router;
 mod status;
+mod validate;
 
 pub use body::*;
 pub use extension::*;
 pub use header::*;
+pub use precondition::*;
 pub use router::*;
 pub use status::*;
+pub use validate::*;
 
 use core::{any::Any, convert::Infallible, fmt};
 
@@ -375,8 +379,8 @@ type StdErr = Box<dyn error::Error + Send + Sync>;
 
 impl From<StdErr> for Error {
     fn from(e: StdErr) -> Self {
-        // this is a hack for middleware::Limit where it wraps around request stream body
-        // and produce BodyOverFlow error and return it as BodyError. In the mean time
+        // this is a hack for middleware::{Limit, Decompress} where they wrap around request stream body
+        // and produce BodyOverFlow(or DecompressOverFlow) error and return it as BodyError. In the mean time
         // BodyError is another type alias share the same real type of StdErr and both share
         // the same conversion path when converting into Error.
         //
@@ -390,6 +394,10 @@ impl From<StdErr> for Error {
             return Self::from(e.clone());
         }
 
+        if let Some(e) = e.downcast_ref::<DecompressOverFlow>() {
+            return Self::from(e.clone());
+        }
+
         Self(Box::new(StdError(e)))
     }
 }
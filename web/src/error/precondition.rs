@@ -0,0 +1,26 @@
+use core::fmt;
+
+use std::error;
+
+use crate::http::StatusCode;
+
+use super::{blank_error_service, error_from_service};
+
+/// error type when conditional request headers are not satisfied. produce "412 Precondition Failed" response.
+///
+/// See [`Precondition`] for usage.
+///
+/// [`Precondition`]: crate::handler::precondition::Precondition
+#[derive(Debug)]
+pub struct PreconditionFailed;
+
+impl fmt::Display for PreconditionFailed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("precondition of request is not satisfied")
+    }
+}
+
+impl error::Error for PreconditionFailed {}
+
+error_from_service!(PreconditionFailed);
+blank_error_service!(PreconditionFailed, StatusCode::PRECONDITION_FAILED);
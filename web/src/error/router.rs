@@ -7,15 +7,103 @@ use core::convert::Infallible;
 
 use crate::{
     body::ResponseBody,
-    http::{header::ALLOW, StatusCode, WebResponse},
+    bytes::Bytes,
+    http::{
+        header::{HeaderValue, ALLOW, CONTENT_TYPE},
+        StatusCode, WebResponse,
+    },
     service::Service,
     WebContext,
 };
 
-use super::{blank_error_service, error_from_service, Error};
+use super::{error_from_service, Error};
+
+/// body and content type of "404 Not Found" and "405 Method Not Allowed" responses produced by [MatchError] and
+/// [MethodNotAllowed].
+///
+/// By default both responses are produced without body. It can be set for application with
+/// [App::not_found_body] and [App::method_not_allowed_body], or attached to request extensions with
+/// [Extension](crate::middleware::Extension) middleware where the one already in request extensions takes
+/// precedence. `Allow` header of "405 Method Not Allowed" response is always present.
+///
+/// # Examples
+/// ```rust
+/// # use xitca_web::{
+/// #   error::RouterErrorBody,
+/// #   handler::handler_service,
+/// #   http::const_header_value::JSON,
+/// #   middleware::Extension,
+/// #   App, WebContext
+/// # };
+/// let body = RouterErrorBody::new().not_found(JSON, r#"{"error":"not found"}"#);
+///
+/// App::new()
+///     .at("/", handler_service(|| async { "hello,world!" }))
+///     # .at("/infer", handler_service(|_: &WebContext<'_>| async{ "infer type" }))
+///     .enclosed(Extension::new(body));
+/// ```
+///
+/// [App::not_found_body]: crate::App::not_found_body
+/// [App::method_not_allowed_body]: crate::App::method_not_allowed_body
+#[derive(Clone, Debug, Default)]
+pub struct RouterErrorBody {
+    not_found: Option<(HeaderValue, Bytes)>,
+    method_not_allowed: Option<(HeaderValue, Bytes)>,
+}
+
+impl RouterErrorBody {
+    /// construct with empty body for both responses.
+    pub const fn new() -> Self {
+        Self {
+            not_found: None,
+            method_not_allowed: None,
+        }
+    }
+
+    /// set content type and body of "404 Not Found" response.
+    pub fn not_found(mut self, content_type: HeaderValue, body: impl Into<Bytes>) -> Self {
+        self.not_found = Some((content_type, body.into()));
+        self
+    }
+
+    /// set content type and body of "405 Method Not Allowed" response.
+    pub fn method_not_allowed(mut self, content_type: HeaderValue, body: impl Into<Bytes>) -> Self {
+        self.method_not_allowed = Some((content_type, body.into()));
+        self
+    }
+}
+
+// construct response with body from RouterErrorBody in request extensions.
+fn error_response<C, B, F>(ctx: WebContext<'_, C, B>, status: StatusCode, func: F) -> WebResponse
+where
+    F: FnOnce(&RouterErrorBody) -> Option<&(HeaderValue, Bytes)>,
+{
+    let body = ctx.req().extensions().get::<RouterErrorBody>().and_then(func).cloned();
+
+    let mut res = match body {
+        Some((content_type, body)) => {
+            let mut res = ctx.into_response(body);
+            res.headers_mut().insert(CONTENT_TYPE, content_type);
+            res
+        }
+        None => ctx.into_response(ResponseBody::empty()),
+    };
+    *res.status_mut() = status;
+    res
+}
 
 error_from_service!(MatchError);
-blank_error_service!(MatchError, StatusCode::NOT_FOUND);
+
+impl<'r, C, B> Service<WebContext<'r, C, B>> for MatchError {
+    type Response = WebResponse;
+    type Error = Infallible;
+
+    async fn call(&self, ctx: WebContext<'r, C, B>) -> Result<Self::Response, Self::Error> {
+        Ok(error_response(ctx, StatusCode::NOT_FOUND, |body| {
+            body.not_found.as_ref()
+        }))
+    }
+}
 
 error_from_service!(MethodNotAllowed);
 
@@ -24,7 +112,9 @@ impl<'r, C, B> Service<WebContext<'r, C, B>> for MethodNotAllowed {
     type Error = Infallible;
 
     async fn call(&self, ctx: WebContext<'r, C, B>) -> Result<Self::Response, Self::Error> {
-        let mut res = ctx.into_response(ResponseBody::empty());
+        let mut res = error_response(ctx, StatusCode::METHOD_NOT_ALLOWED, |body| {
+            body.method_not_allowed.as_ref()
+        });
 
         let allowed = self.allowed_methods();
 
@@ -39,7 +129,6 @@ impl<'r, C, B> Service<WebContext<'r, C, B>> for MethodNotAllowed {
         methods.pop();
 
         res.headers_mut().insert(ALLOW, methods.parse().unwrap());
-        *res.status_mut() = StatusCode::METHOD_NOT_ALLOWED;
 
         Ok(res)
     }
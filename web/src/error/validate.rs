@@ -0,0 +1,38 @@
+use core::fmt;
+
+use std::{borrow::Cow, error};
+
+use crate::http::StatusCode;
+
+use super::{blank_error_service, error_from_service};
+
+/// error type when extracted value failed validation. produce "422 Unprocessable Entity" response.
+///
+/// See [`Validated`] for usage.
+///
+/// [`Validated`]: crate::handler::validate::Validated
+#[derive(Debug)]
+pub struct ValidationError(Cow<'static, str>);
+
+impl ValidationError {
+    /// construct a new validation error with given reason message.
+    pub fn new(reason: impl Into<Cow<'static, str>>) -> Self {
+        Self(reason.into())
+    }
+
+    /// reason message of validation failure.
+    pub fn reason(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "validation failed: {}", self.0)
+    }
+}
+
+impl error::Error for ValidationError {}
+
+error_from_service!(ValidationError);
+blank_error_service!(ValidationError, StatusCode::UNPROCESSABLE_ENTITY);
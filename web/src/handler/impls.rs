@@ -1,10 +1,10 @@
-use core::net::SocketAddr;
+use core::{any::Any, net::SocketAddr};
 
 use crate::{
-    body::ResponseBody,
+    body::{Flush, ResponseBody},
     context::WebContext,
-    error::{Error, ErrorStatus},
-    http::{Method, RequestExt, StatusCode, WebRequest, WebResponse},
+    error::{Error, ErrorStatus, InvalidHeaderValue, ValidationError},
+    http::{Method, Protocol, RequestExt, StatusCode, WebRequest, WebResponse},
 };
 
 use super::{FromRequest, Responder};
@@ -22,19 +22,61 @@ where
     }
 }
 
+/// optional extractor that yield `None` when `T` can not be extracted from request. (e.g. header or cookie
+/// is absent).
+///
+/// Value present in request but malformed is not treated as absent. When `T` fails with [InvalidHeaderValue]
+/// or [ValidationError](either directly or wrapped in [Error]) the error is returned as is. Other error types
+/// always yield `None`. For inspecting every error of `T` `Result<T, E>` extractor can be used.
 impl<'a, 'r, C, B, T> FromRequest<'a, WebContext<'r, C, B>> for Option<T>
 where
     T: FromRequest<'a, WebContext<'r, C, B>>,
+    T::Error: 'static,
 {
     type Type<'b> = Option<T::Type<'b>>;
     type Error = Error;
 
     #[inline]
     async fn from_request(ctx: &'a WebContext<'r, C, B>) -> Result<Self, Self::Error> {
-        Ok(T::from_request(ctx).await.ok())
+        match T::from_request(ctx).await {
+            Ok(t) => Ok(Some(t)),
+            Err(e) => match malformed(e) {
+                Some(e) => Err(e),
+                None => Ok(None),
+            },
+        }
     }
 }
 
+// pick error types telling value is present in request but malformed. everything else is treated as absent.
+fn malformed<E: 'static>(e: E) -> Option<Error> {
+    let mut e = Some(e);
+    let e = &mut e as &mut dyn Any;
+
+    if let Some(e) = e.downcast_mut::<Option<Error>>() {
+        return e.take().filter(is_malformed);
+    }
+
+    if let Some(e) = e.downcast_mut::<Option<InvalidHeaderValue>>() {
+        return e.take().map(Error::from);
+    }
+
+    e.downcast_mut::<Option<ValidationError>>()
+        .and_then(Option::take)
+        .map(Error::from)
+}
+
+fn is_malformed(e: &Error) -> bool {
+    let e = e.upcast();
+
+    #[cfg(feature = "validator")]
+    if e.is::<validator::ValidationErrors>() {
+        return true;
+    }
+
+    e.is::<InvalidHeaderValue>() || e.is::<ValidationError>()
+}
+
 impl<'a, 'r, C, B> FromRequest<'a, WebContext<'r, C, B>> for &'a WebContext<'a, C, B>
 where
     C: 'static,
@@ -109,6 +151,17 @@ impl<'a, 'r, C, B> FromRequest<'a, WebContext<'r, C, B>> for SocketAddr {
     }
 }
 
+/// protocol of connection request is received from. middleware can read it from [RequestExt::protocol].
+impl<'a, 'r, C, B> FromRequest<'a, WebContext<'r, C, B>> for Protocol {
+    type Type<'b> = Protocol;
+    type Error = Error;
+
+    #[inline]
+    async fn from_request(ctx: &'a WebContext<'r, C, B>) -> Result<Self, Self::Error> {
+        Ok(ctx.req().body().protocol())
+    }
+}
+
 impl<'a, 'r, C, B> FromRequest<'a, WebContext<'r, C, B>> for &'a Method {
     type Type<'b> = &'b Method;
     type Error = Error;
@@ -183,6 +236,10 @@ impl<'r, C, B> Responder<WebContext<'r, C, B>> for ErrorStatus {
     }
 }
 
+/// status only response with empty body. e.g. `StatusCode::NO_CONTENT` can be returned from handler directly.
+///
+/// when used in tuple with other responders it only overrides response status. body of 204 and 304 response
+/// (along with it's `content-length` header) is dropped by http layer.
 impl<'r, C, B> Responder<WebContext<'r, C, B>> for StatusCode {
     type Response = WebResponse;
     type Error = Error;
@@ -198,6 +255,23 @@ impl<'r, C, B> Responder<WebContext<'r, C, B>> for StatusCode {
     }
 }
 
+/// insert [Flush] hint into response extensions. it's meant to be used in tuple with streaming body responder.
+/// e.g. `(ResponseBody::box_stream(stream), Flush::Item)`
+impl<'r, C, B> Responder<WebContext<'r, C, B>> for Flush {
+    type Response = WebResponse;
+    type Error = Error;
+
+    async fn respond(self, ctx: WebContext<'r, C, B>) -> Result<Self::Response, Self::Error> {
+        let res = ctx.into_response(ResponseBody::empty());
+        Responder::<WebContext<'r, C, B>>::map(self, res)
+    }
+
+    fn map(self, mut res: Self::Response) -> Result<Self::Response, Self::Error> {
+        res.extensions_mut().insert(self);
+        Ok(res)
+    }
+}
+
 // shared error impl for serde enabled features: json, urlencoded, etc.
 #[cfg(feature = "serde")]
 const _: () = {
@@ -209,7 +283,10 @@ const _: () = {
 mod test {
     use xitca_unsafe_collection::futures::NowOrPanic;
 
-    use crate::http::header::{HeaderMap, HeaderValue, CONTENT_TYPE, COOKIE};
+    use crate::{
+        error::HeaderNotFound,
+        http::header::{HeaderMap, HeaderValue, AUTHORIZATION, CONTENT_TYPE, COOKIE},
+    };
 
     use super::*;
 
@@ -225,6 +302,67 @@ mod test {
         <&WebContext<'_>>::from_request(&req).now_or_panic().unwrap();
 
         <()>::from_request(&req).now_or_panic().unwrap();
+
+        assert_eq!(Protocol::from_request(&req).now_or_panic().unwrap(), Protocol::Http1);
+    }
+
+    #[derive(Debug)]
+    struct Bearer;
+
+    impl<'a, 'r, C, B> FromRequest<'a, WebContext<'r, C, B>> for Bearer {
+        type Type<'b> = Bearer;
+        type Error = Error;
+
+        async fn from_request(ctx: &'a WebContext<'r, C, B>) -> Result<Self, Self::Error> {
+            let value = ctx
+                .req()
+                .headers()
+                .get(AUTHORIZATION)
+                .ok_or(HeaderNotFound(AUTHORIZATION))?;
+            if !value.as_bytes().starts_with(b"Bearer ") {
+                return Err(InvalidHeaderValue(AUTHORIZATION).into());
+            }
+            Ok(Bearer)
+        }
+    }
+
+    #[test]
+    fn extract_option() {
+        let mut req = WebContext::new_test(());
+        let mut req = req.as_web_ctx();
+
+        assert!(Option::<Bearer>::from_request(&req).now_or_panic().unwrap().is_none());
+
+        req.req_mut()
+            .headers_mut()
+            .insert(AUTHORIZATION, HeaderValue::from_static("Basic foo"));
+        let e = Option::<Bearer>::from_request(&req).now_or_panic().unwrap_err();
+        assert!(e.upcast().downcast_ref::<InvalidHeaderValue>().is_some());
+
+        req.req_mut()
+            .headers_mut()
+            .insert(AUTHORIZATION, HeaderValue::from_static("Bearer foo"));
+        assert!(Option::<Bearer>::from_request(&req).now_or_panic().unwrap().is_some());
+    }
+
+    struct Foreign;
+
+    impl<'a, 'r, C, B> FromRequest<'a, WebContext<'r, C, B>> for Foreign {
+        type Type<'b> = Foreign;
+        type Error = &'static str;
+
+        async fn from_request(_: &'a WebContext<'r, C, B>) -> Result<Self, Self::Error> {
+            Err("foreign")
+        }
+    }
+
+    #[test]
+    fn extract_option_foreign_error() {
+        let mut req = WebContext::new_test(());
+        let req = req.as_web_ctx();
+
+        // error type not convertible to Error is treated as absent value.
+        assert!(Option::<Foreign>::from_request(&req).now_or_panic().unwrap().is_none());
     }
 
     #[test]
@@ -262,4 +400,58 @@ mod test {
             .unwrap();
         check_res(res);
     }
+
+    #[test]
+    fn respond_flush() {
+        let mut req = WebContext::new_test(());
+        let req = req.as_web_ctx();
+
+        let res = ("hello,world!", Flush::Bytes(1024))
+            .respond(req)
+            .now_or_panic()
+            .unwrap();
+        assert_eq!(res.extensions().get::<Flush>(), Some(&Flush::Bytes(1024)));
+    }
+
+    #[test]
+    fn respond_tuple_header_override() {
+        use crate::http::const_header_value::TEXT_HTML_UTF8;
+
+        let mut req = WebContext::new_test(());
+        let mut req = req.as_web_ctx();
+
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, TEXT_HTML_UTF8);
+
+        // content type from header map must not be overwritten by inner text responder.
+        let res = (StatusCode::CREATED, headers.clone(), "<p>hello,world!</p>")
+            .respond(req.reborrow())
+            .now_or_panic()
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::CREATED);
+        assert_eq!(res.headers().get(CONTENT_TYPE).unwrap(), TEXT_HTML_UTF8);
+
+        // header map following body responder replaces existing header value.
+        let res = ("<p>hello,world!</p>", headers).respond(req).now_or_panic().unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers().get_all(CONTENT_TYPE).iter().count(), 1);
+        assert_eq!(res.headers().get(CONTENT_TYPE).unwrap(), TEXT_HTML_UTF8);
+    }
+
+    #[test]
+    fn respond_status() {
+        use xitca_http::body::BodySize;
+
+        let mut req = WebContext::new_test(());
+        let mut req = req.as_web_ctx();
+
+        // responder always produces empty body. dropping body and it's size header of 204 and 304 response is
+        // tested in h1 encoder.
+        for status in [StatusCode::NO_CONTENT, StatusCode::NOT_MODIFIED, StatusCode::NOT_FOUND] {
+            let res = status.respond(req.reborrow()).now_or_panic().unwrap();
+            assert_eq!(res.status(), status);
+            assert!(res.headers().is_empty());
+            assert_eq!(BodySize::from_stream(res.body()), BodySize::Sized(0));
+        }
+    }
 }
@@ -0,0 +1,174 @@
+//! type extractor for body object negotiated by request content type.
+
+use core::{
+    fmt,
+    ops::{Deref, DerefMut},
+};
+
+use serde::de::Deserialize;
+
+use crate::{
+    body::BodyStream,
+    context::WebContext,
+    error::Error,
+    handler::FromRequest,
+    http::{header::CONTENT_TYPE, StatusCode},
+};
+
+use super::{form::Form, json::Json};
+
+pub const DEFAULT_LIMIT: usize = 1024 * 1024;
+
+/// Extract type for object decoded from either json or urlencoded form body according to request's `Content-Type`
+/// header. const generic param LIMIT is for max size of the object in bytes.
+///
+/// - `application/json` and `application/*+json` are decoded as [Json].
+/// - `application/x-www-form-urlencoded` is decoded as [Form].
+/// - other or missing content type produces "415 Unsupported Media Type" response.
+///
+/// Default limit is [DEFAULT_LIMIT] in bytes.
+///
+/// Request body is consumed by this extractor the same way [Json] and [Form] do. Other body extractor used along
+/// with it in the same handler would observe an empty body.
+///
+/// # Examples
+/// ```rust
+/// # use xitca_web::{handler::{handler_service, any_body::AnyBody}, App, WebContext};
+/// #[derive(serde::Deserialize)]
+/// struct Login {
+///     user: String,
+/// }
+///
+/// // accept both json and form encoded login.
+/// async fn handler(AnyBody(login): AnyBody<Login>) -> String {
+///     login.user
+/// }
+///
+/// App::new()
+///     .at("/", handler_service(handler))
+///     # .at("/infer", handler_service(|_: &WebContext<'_>| async { "used for infer type" }));
+/// ```
+pub struct AnyBody<T, const LIMIT: usize = DEFAULT_LIMIT>(pub T);
+
+impl<T, const LIMIT: usize> fmt::Debug for AnyBody<T, LIMIT>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AnyBody")
+            .field("value", &self.0)
+            .field("limit", &LIMIT)
+            .finish()
+    }
+}
+
+impl<T, const LIMIT: usize> Deref for AnyBody<T, LIMIT> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T, const LIMIT: usize> DerefMut for AnyBody<T, LIMIT> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<'a, 'r, C, B, T, const LIMIT: usize> FromRequest<'a, WebContext<'r, C, B>> for AnyBody<T, LIMIT>
+where
+    B: BodyStream + Default,
+    T: for<'de> Deserialize<'de>,
+{
+    type Type<'b> = AnyBody<T, LIMIT>;
+    type Error = Error;
+
+    async fn from_request(ctx: &'a WebContext<'r, C, B>) -> Result<Self, Self::Error> {
+        let mime = ctx
+            .req()
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.split(';').next())
+            .map(str::trim)
+            .unwrap_or_default();
+
+        if is_json(mime) {
+            Json::<T, LIMIT>::from_request(ctx).await.map(|Json(t)| AnyBody(t))
+        } else if mime.eq_ignore_ascii_case("application/x-www-form-urlencoded") {
+            Form::<T, LIMIT>::from_request(ctx).await.map(|Form(t)| AnyBody(t))
+        } else {
+            Err(Error::from(StatusCode::UNSUPPORTED_MEDIA_TYPE))
+        }
+    }
+}
+
+fn is_json(mime: &str) -> bool {
+    let Some((ty, sub)) = mime.split_once('/') else {
+        return false;
+    };
+    let sub = sub.as_bytes();
+    ty.eq_ignore_ascii_case("application")
+        && (sub.eq_ignore_ascii_case(b"json") || (sub.len() > 5 && sub[sub.len() - 5..].eq_ignore_ascii_case(b"+json")))
+}
+
+#[cfg(test)]
+mod test {
+    use xitca_unsafe_collection::futures::NowOrPanic;
+
+    use crate::http::{
+        const_header_value::{APPLICATION_WWW_FORM_URLENCODED, JSON, TEXT_UTF8},
+        header::{HeaderValue, CONTENT_LENGTH},
+    };
+
+    use super::*;
+
+    #[derive(serde::Deserialize)]
+    struct Student {
+        name: String,
+        age: u8,
+    }
+
+    fn extract(ct: HeaderValue, body: &'static [u8]) -> Result<Student, Error> {
+        let mut ctx = WebContext::new_test(());
+        let mut ctx = ctx.as_web_ctx();
+
+        ctx.req_mut().headers_mut().insert(CONTENT_TYPE, ct);
+        ctx.req_mut().headers_mut().insert(CONTENT_LENGTH, body.len().into());
+        *ctx.body_borrow_mut() = body.into();
+
+        let res = AnyBody::<Student>::from_request(&ctx).now_or_panic();
+        res.map(|AnyBody(s)| s)
+    }
+
+    #[test]
+    fn negotiate() {
+        let s = extract(JSON, br#"{"name":"arisu","age":14}"#).unwrap();
+        assert_eq!(s.name, "arisu");
+        assert_eq!(s.age, 14);
+
+        let s = extract(
+            HeaderValue::from_static("application/vnd.api+json; charset=utf-8"),
+            br#"{"name":"arisu","age":14}"#,
+        )
+        .unwrap();
+        assert_eq!(s.name, "arisu");
+
+        let s = extract(APPLICATION_WWW_FORM_URLENCODED, b"name=arisu&age=14").unwrap();
+        assert_eq!(s.name, "arisu");
+        assert_eq!(s.age, 14);
+
+        assert!(extract(TEXT_UTF8, b"name=arisu&age=14").is_err());
+    }
+
+    #[test]
+    fn mime() {
+        assert!(is_json("application/json"));
+        assert!(is_json("Application/JSON"));
+        assert!(is_json("application/problem+json"));
+        assert!(!is_json("application/+json"));
+        assert!(!is_json("text/json"));
+        assert!(!is_json("application/x-www-form-urlencoded"));
+    }
+}
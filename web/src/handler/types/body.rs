@@ -1,18 +1,39 @@
 //! type extractor for request body stream.
 
-use core::{cmp, convert::Infallible, future::poll_fn, pin::pin};
+use core::{any::Any, cmp, convert::Infallible, future::poll_fn, pin::pin};
+
+use futures_core::stream::Stream;
 
 use crate::{
-    body::{BodyStream, BoxBody, ResponseBody},
+    body::{BodyReader, BodyStream, BoxBody, ResponseBody},
     bytes::{Bytes, BytesMut},
     context::WebContext,
-    error::{BodyOverFlow, Error},
+    error::{BodyError, BodyOverFlow, Error},
     handler::{FromRequest, Responder},
-    http::{IntoResponse, WebResponse},
+    http::{const_header_value::OCTET_STREAM, header::CONTENT_TYPE, IntoResponse, WebResponse},
 };
 
 use super::header::{self, HeaderRef};
 
+/// Extract type for request body stream. The stream is taken from request as is without collecting it into
+/// memory and following type extractors observe the body as consumed.
+///
+/// Body of streaming types can be passed back as response body directly. e.g. for echoing or forwarding large
+/// upload without buffering it.
+///
+/// # Examples
+/// ```rust
+/// # use xitca_web::{body::RequestBody, handler::{body::Body, handler_service}, App, WebContext};
+/// // response body is streamed from request body.
+/// async fn echo(body: Body<RequestBody>) -> Body<RequestBody> {
+///     body
+/// }
+///
+/// App::new()
+///     .at("/echo", handler_service(echo))
+///     # .at("/infer", handler_service(|_: &WebContext<'_>| async { "used for infer type" }))
+///     ;
+/// ```
 pub struct Body<B>(pub B);
 
 impl<'a, 'r, C, B> FromRequest<'a, WebContext<'r, C, B>> for Body<B>
@@ -28,13 +49,44 @@ where
     }
 }
 
+/// Extract request body stream as [BodyReader] implementing `tokio::io::{AsyncRead, AsyncBufRead}` traits.
+/// Like [Body] the stream is taken from request as is and following type extractors observe the body as consumed.
+///
+/// # Examples
+/// ```rust
+/// # use xitca_web::{body::{BodyReader, RequestBody}, handler::handler_service, App, WebContext};
+/// async fn upload(reader: BodyReader<RequestBody>) -> &'static str {
+///     // pass reader to byte oriented library. e.g. tokio::io::copy(&mut reader, &mut file)
+///     # let _ = reader;
+///     "uploaded"
+/// }
+///
+/// App::new()
+///     .at("/upload", handler_service(upload))
+///     # .at("/infer", handler_service(|_: &WebContext<'_>| async { "used for infer type" }))
+///     ;
+/// ```
+impl<'a, 'r, C, B> FromRequest<'a, WebContext<'r, C, B>> for BodyReader<B>
+where
+    B: BodyStream + Default,
+{
+    type Type<'b> = BodyReader<B>;
+    type Error = Error;
+
+    #[inline]
+    async fn from_request(ctx: &'a WebContext<'r, C, B>) -> Result<Self, Self::Error> {
+        Ok(BodyReader::new(ctx.take_body_ref()))
+    }
+}
+
 /// helper type for limiting body size.
 /// when LIMIT > 0 body size is limited to LIMIT in bytes.
 /// when LIMIT == 0 body size is unlimited.
 pub struct Limit<const LIMIT: usize>;
 
+// collect body into buffer constructed by $new with a size hint and finalized by $finish.
 macro_rules! from_bytes_impl {
-    ($type: ty, $original: tt) => {
+    ($type: ty, $new: path, $finish: path) => {
         impl<'a, 'r, C, B, const LIMIT: usize> FromRequest<'a, WebContext<'r, C, B>> for ($type, Limit<LIMIT>)
         where
             B: BodyStream + Default,
@@ -43,20 +95,29 @@ macro_rules! from_bytes_impl {
             type Error = Error;
 
             async fn from_request(ctx: &'a WebContext<'r, C, B>) -> Result<Self, Self::Error> {
-                let limit = HeaderRef::<'a, { header::CONTENT_LENGTH }>::from_request(ctx)
+                let len = HeaderRef::<'a, { header::CONTENT_LENGTH }>::from_request(ctx)
                     .await
                     .ok()
-                    .and_then(|header| header.to_str().ok().and_then(|s| s.parse().ok()))
-                    // when content length is 0 the http library should be producing an immediate
-                    // yielding streaming body which result in an empty body collection type.
-                    .map(|len| cmp::min(len, LIMIT))
-                    .unwrap_or_else(|| LIMIT);
+                    .and_then(|header| header.to_str().ok().and_then(|s| s.parse::<usize>().ok()));
+
+                // when content length is 0 the http library should be producing an immediate
+                // yielding streaming body which result in an empty body collection type.
+                let limit = len.map(|len| cmp::min(len, LIMIT)).unwrap_or(LIMIT);
 
                 let body = ctx.take_body_ref();
 
+                // reserve for expected size of body instead of the limit so small body without content length does
+                // not over allocate.
+                let hint = len.unwrap_or_else(|| match body.size_hint() {
+                    // exact hint(including none body) carries the size in upper bound.
+                    (_, Some(upper)) => upper,
+                    (lower, None) => lower,
+                });
+                let hint = if limit > 0 { cmp::min(hint, limit) } else { hint };
+
                 let mut body = pin!(body);
 
-                let mut buf = <$type>::new();
+                let mut buf = $new(cmp::min(hint, pool::MAX_CAPACITY));
 
                 while let Some(chunk) = poll_fn(|cx| body.as_mut().poll_next(cx)).await {
                     let chunk = chunk.map_err(Into::into)?;
@@ -66,7 +127,7 @@ macro_rules! from_bytes_impl {
                     }
                 }
 
-                Ok((buf, Limit))
+                Ok(($finish(buf), Limit))
             }
         }
 
@@ -90,8 +151,10 @@ macro_rules! from_bytes_impl {
     };
 }
 
-from_bytes_impl!(BytesMut, _);
-from_bytes_impl!(Vec<u8>, _);
+// BytesMut is split from per worker thread pooled buffer. Vec<u8> is handed to caller as is and can't be pooled
+// without an extra copy of body.
+from_bytes_impl!(BytesMut, pool::take, pool::collected);
+from_bytes_impl!(Vec<u8>, Vec::with_capacity, core::convert::identity);
 
 impl<'a, 'r, C, B, const LIMIT: usize> FromRequest<'a, WebContext<'r, C, B>> for (Bytes, Limit<LIMIT>)
 where
@@ -110,6 +173,62 @@ where
 
 from_bytes_impl!(Bytes);
 
+// per worker thread buffer pool for collecting request body.
+//
+// large collected body is split off the pooled buffer and shares allocation with it. Once every split off body of a
+// request is dropped(json/form/grpc extractors drop it right after deserializing) the allocation is reclaimed by
+// next collection on the same thread instead of allocating new memory. Small body is copied out of the pooled
+// buffer so it does not pin the allocation when it's kept by caller. Buffer exceeding MAX_CAPACITY is never
+// returned to the pool so a single large request does not permanently inflate the memory held by worker.
+mod pool {
+    use core::cell::Cell;
+
+    use crate::bytes::BytesMut;
+
+    pub(super) const MAX_CAPACITY: usize = 64 * 1024;
+
+    thread_local! {
+        static BUF: Cell<BytesMut> = Cell::new(BytesMut::new());
+    }
+
+    // take buffer from pool. returned buffer is always empty.
+    pub(super) fn take(hint: usize) -> BytesMut {
+        let mut buf = BUF.try_with(Cell::take).unwrap_or_default();
+        buf.clear();
+        // reserve reclaims allocation of previously collected body when it's no longer referenced.
+        buf.reserve(hint);
+        if buf.capacity() > MAX_CAPACITY {
+            buf = BytesMut::with_capacity(hint);
+        }
+        buf
+    }
+
+    // split collected body from buffer and return remaining buffer to pool. body taking less than half of buffer's
+    // allocation is copied out and the whole buffer is returned to pool.
+    pub(super) fn collected(mut buf: BytesMut) -> BytesMut {
+        if buf.len() * 2 < buf.capacity() {
+            let body = BytesMut::from(&buf[..]);
+            buf.clear();
+            put(buf);
+            return body;
+        }
+
+        let body = buf.split();
+        if body.len() + buf.capacity() <= MAX_CAPACITY {
+            put(buf);
+        }
+        body
+    }
+
+    fn put(buf: BytesMut) {
+        if buf.capacity() <= MAX_CAPACITY {
+            let _ = BUF.try_with(|pool| pool.set(buf));
+        }
+    }
+}
+
+// binary responders extend CONTENT_TYPE header with OCTET_STREAM value. content type set by prior responder
+// takes precedence and a following header responder can override it.
 macro_rules! responder_impl {
     ($type: ty) => {
         impl<'r, C, B> Responder<WebContext<'r, C, B>> for $type {
@@ -118,12 +237,16 @@ macro_rules! responder_impl {
 
             #[inline]
             async fn respond(self, ctx: WebContext<'r, C, B>) -> Result<Self::Response, Self::Error> {
-                Ok(ctx.into_response(self))
+                let mut res = ctx.into_response(self);
+                res.headers_mut().insert(CONTENT_TYPE, OCTET_STREAM);
+                Ok(res)
             }
 
             #[inline]
             fn map(self, res: Self::Response) -> Result<Self::Response, Self::Error> {
-                Ok(res.map(|_| self.into()))
+                let mut res = res.map(|_| self.into());
+                res.headers_mut().entry(CONTENT_TYPE).or_insert(OCTET_STREAM);
+                Ok(res)
             }
         }
     };
@@ -132,14 +255,16 @@ macro_rules! responder_impl {
 responder_impl!(Bytes);
 responder_impl!(BytesMut);
 responder_impl!(Vec<u8>);
+responder_impl!(Box<[u8]>);
+responder_impl!(&'static [u8]);
 
 impl<'r, C, B, ResB> Responder<WebContext<'r, C, B>> for ResponseBody<ResB> {
     type Response = WebResponse<ResponseBody<ResB>>;
     type Error = Error;
 
     #[inline]
-    async fn respond(self, ctx: WebContext<'r, C, B>) -> Result<Self::Response, Self::Error> {
-        Ok(ctx.req.as_response(self))
+    async fn respond(self, mut ctx: WebContext<'r, C, B>) -> Result<Self::Response, Self::Error> {
+        Ok(ctx.take_req().into_response(self))
     }
 
     #[inline]
@@ -148,6 +273,83 @@ impl<'r, C, B, ResB> Responder<WebContext<'r, C, B>> for ResponseBody<ResB> {
     }
 }
 
+impl<'r, C, B, ResB, T, E> Responder<WebContext<'r, C, B>> for Body<ResB>
+where
+    ResB: Stream<Item = Result<T, E>> + 'static,
+    T: Into<Bytes>,
+    E: Into<BodyError>,
+{
+    type Response = WebResponse;
+    type Error = Error;
+
+    #[inline]
+    async fn respond(self, ctx: WebContext<'r, C, B>) -> Result<Self::Response, Self::Error> {
+        ResponseBody::box_stream(self.0).respond(ctx).await
+    }
+
+    #[inline]
+    fn map(self, res: Self::Response) -> Result<Self::Response, Self::Error> {
+        Responder::<WebContext<'r, C, B>>::map(ResponseBody::box_stream(self.0), res)
+    }
+}
+
+/// Responder for already built response with any body stream type. e.g. response proxied from upstream.
+///
+/// Status, headers and body are passed through untouched. Response with [ResponseBody] is passed as is and other
+/// body types are boxed into it.
+///
+/// # Examples
+/// ```rust
+/// # use xitca_web::{
+/// #   bytes::Bytes,
+/// #   handler::{body::BuiltResponse, handler_service},
+/// #   http::{Response, StatusCode},
+/// #   App, WebContext,
+/// # };
+/// # use xitca_http::body::Once;
+/// async fn handler() -> BuiltResponse<Once<Bytes>> {
+///     let mut res = Response::new(Once::new(Bytes::from_static(b"built")));
+///     *res.status_mut() = StatusCode::ACCEPTED;
+///     BuiltResponse(res)
+/// }
+///
+/// App::new()
+///     .at("/", handler_service(handler))
+///     # .at("/infer", handler_service(|_: &WebContext<'_>| async { "used for infer type" }))
+///     ;
+/// ```
+pub struct BuiltResponse<B>(pub WebResponse<B>);
+
+impl<'r, C, B, ResB, T, E> Responder<WebContext<'r, C, B>> for BuiltResponse<ResB>
+where
+    ResB: Stream<Item = Result<T, E>> + 'static,
+    T: Into<Bytes>,
+    E: Into<BodyError>,
+{
+    type Response = WebResponse;
+    type Error = Error;
+
+    #[inline]
+    async fn respond(self, _: WebContext<'r, C, B>) -> Result<Self::Response, Self::Error> {
+        let (parts, body) = self.0.into_parts();
+        Ok(WebResponse::from_parts(parts, into_response_body(body)))
+    }
+}
+
+fn into_response_body<ResB, T, E>(body: ResB) -> ResponseBody
+where
+    ResB: Stream<Item = Result<T, E>> + 'static,
+    T: Into<Bytes>,
+    E: Into<BodyError>,
+{
+    // avoid double boxing when body is already the default response body type.
+    let mut body = Some(body);
+    match (&mut body as &mut dyn Any).downcast_mut::<Option<ResponseBody>>() {
+        Some(body) => body.take().unwrap(),
+        None => ResponseBody::box_stream(body.unwrap()),
+    }
+}
+
 impl<'r, C, B> Responder<WebContext<'r, C, B>> for BoxBody {
     type Response = WebResponse;
     type Error = Error;
@@ -162,3 +364,194 @@ impl<'r, C, B> Responder<WebContext<'r, C, B>> for BoxBody {
         Responder::<WebContext<'r, C, B>>::map(ResponseBody::stream(self), res)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use xitca_http::body::BodySize;
+    use xitca_unsafe_collection::futures::NowOrPanic;
+
+    use crate::{
+        body::RequestBody,
+        http::{const_header_value::TEXT_HTML_UTF8, header::HeaderMap, StatusCode},
+        test::collect_body,
+    };
+
+    use super::*;
+
+    #[test]
+    fn forward_body() {
+        let mut ctx = WebContext::new_test(());
+        let mut ctx = ctx.as_web_ctx();
+
+        *ctx.body_borrow_mut() = Bytes::from_static(b"996").into();
+
+        let Body(body) = Body::from_request(&ctx).now_or_panic().unwrap();
+        // body is moved out of context and left consumed.
+        assert!(matches!(*ctx.body(), RequestBody::None));
+
+        let res = Body(body).respond(ctx.reborrow()).now_or_panic().unwrap();
+        // exact size of request body is forwarded to response.
+        assert_eq!(BodySize::from_stream(res.body()), BodySize::Sized(3));
+        let body = collect_body(res.into_body()).now_or_panic().unwrap();
+        assert_eq!(body, b"996");
+
+        *ctx.body_borrow_mut() = Bytes::from_static(b"251").into();
+
+        let body = ctx.take_body();
+        assert!(matches!(*ctx.body(), RequestBody::None));
+        let res = ResponseBody::box_stream(body).respond(ctx).now_or_panic().unwrap();
+        let body = collect_body(res.into_body()).now_or_panic().unwrap();
+        assert_eq!(body, b"251");
+    }
+
+    #[test]
+    fn body_reader() {
+        use core::{pin::Pin, task::Poll};
+
+        use std::io;
+
+        use futures_util::stream;
+        use tokio::io::{AsyncRead, ReadBuf};
+
+        fn read<R: AsyncRead>(mut reader: Pin<&mut R>, buf: &mut [u8]) -> io::Result<usize> {
+            poll_fn(|cx| {
+                let mut buf = ReadBuf::new(buf);
+                match reader.as_mut().poll_read(cx, &mut buf) {
+                    Poll::Ready(res) => Poll::Ready(res.map(|_| buf.filled().len())),
+                    Poll::Pending => Poll::Pending,
+                }
+            })
+            .now_or_panic()
+        }
+
+        let mut ctx = WebContext::new_test(());
+        let ctx = ctx.as_web_ctx();
+
+        *ctx.body_borrow_mut() = Bytes::from_static(b"hello,world!").into();
+
+        let reader = BodyReader::from_request(&ctx).now_or_panic().unwrap();
+        assert!(matches!(*ctx.body(), RequestBody::None));
+        let mut reader = pin!(reader);
+
+        // chunk is read in parts with small buffer.
+        let mut buf = [0; 5];
+        assert_eq!(read(reader.as_mut(), &mut buf).unwrap(), 5);
+        assert_eq!(&buf, b"hello");
+        let mut buf = [0; 16];
+        assert_eq!(read(reader.as_mut(), &mut buf).unwrap(), 7);
+        assert_eq!(&buf[..7], b",world!");
+        assert_eq!(read(reader.as_mut(), &mut buf).unwrap(), 0);
+
+        // body error surfaces as io error.
+        let body = stream::iter([
+            Ok(Bytes::from_static(b"996")),
+            Err(io::Error::new(io::ErrorKind::ConnectionReset, "reset")),
+        ]);
+        let mut reader = pin!(BodyReader::new(body));
+        assert_eq!(read(reader.as_mut(), &mut buf).unwrap(), 3);
+        let e = read(reader.as_mut(), &mut buf).err().unwrap();
+        assert_eq!(e.kind(), io::ErrorKind::ConnectionReset);
+    }
+
+    #[test]
+    fn respond_binary() {
+        let mut ctx = WebContext::new_test(());
+        let mut ctx: WebContext<'_> = ctx.as_web_ctx();
+
+        let res = b"996".as_slice().respond(ctx.reborrow()).now_or_panic().unwrap();
+        assert_eq!(res.headers().get(CONTENT_TYPE).unwrap(), OCTET_STREAM);
+        assert_eq!(BodySize::from_stream(res.body()), BodySize::Sized(3));
+        let body = collect_body(res.into_body()).now_or_panic().unwrap();
+        assert_eq!(body, b"996");
+
+        let res = Bytes::from_static(b"996")
+            .respond(ctx.reborrow())
+            .now_or_panic()
+            .unwrap();
+        assert_eq!(res.headers().get(CONTENT_TYPE).unwrap(), OCTET_STREAM);
+
+        // content type can be overridden by header responder.
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, TEXT_HTML_UTF8);
+
+        let res = (StatusCode::CREATED, headers.clone(), vec![b'9'; 3])
+            .respond(ctx.reborrow())
+            .now_or_panic()
+            .unwrap();
+        assert_eq!(res.headers().get(CONTENT_TYPE).unwrap(), TEXT_HTML_UTF8);
+
+        // binary responder's error is Infallible and a leading status responder is needed for error conversion
+        // of the following header responder.
+        let res = (StatusCode::OK, Box::<[u8]>::from(b"996".as_slice()), headers)
+            .respond(ctx)
+            .now_or_panic()
+            .unwrap();
+        assert_eq!(res.headers().get_all(CONTENT_TYPE).iter().count(), 1);
+        assert_eq!(res.headers().get(CONTENT_TYPE).unwrap(), TEXT_HTML_UTF8);
+    }
+
+    #[test]
+    fn pooled_buffer() {
+        // small body is copied out and allocation stays in pool.
+        let mut buf = pool::take(16);
+        buf.extend_from_slice(b"996");
+        let ptr = buf.as_ptr();
+        let body = pool::collected(buf);
+        assert_eq!(body, b"996".as_slice());
+        assert_ne!(body.as_ptr(), ptr);
+
+        let mut buf = pool::take(16);
+        assert!(buf.is_empty());
+        assert_eq!(buf.as_ptr(), ptr);
+
+        // large body shares allocation and it's reclaimed when body is dropped.
+        buf.extend_from_slice(&[0; 16]);
+        let body = pool::collected(buf);
+        assert_eq!(body.as_ptr(), ptr);
+        drop(body);
+
+        let buf = pool::take(16);
+        assert!(buf.is_empty());
+        assert_eq!(buf.as_ptr(), ptr);
+        drop(pool::collected(buf));
+
+        // large buffer is not returned to pool.
+        let mut buf = pool::take(0);
+        buf.extend_from_slice(&[0; pool::MAX_CAPACITY + 1]);
+        drop(pool::collected(buf));
+        assert_eq!(pool::take(0).capacity(), 0);
+    }
+
+    #[test]
+    fn respond_built_response() {
+        use xitca_http::body::{BodySize, Once};
+
+        use crate::{
+            body::BoxBody,
+            http::header::{HeaderValue, ETAG},
+            test::collect_string_body,
+        };
+
+        let mut req = WebContext::new_test(());
+        let mut req = req.as_web_ctx();
+
+        let mut res = WebResponse::new(Once::new(Bytes::from_static(b"upstream")));
+        *res.status_mut() = StatusCode::PARTIAL_CONTENT;
+        res.headers_mut().insert(ETAG, HeaderValue::from_static("996"));
+
+        let res = BuiltResponse(res).respond(req.reborrow()).now_or_panic().unwrap();
+        assert_eq!(res.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(res.headers().get(ETAG).unwrap(), "996");
+        let body = collect_string_body(res.into_body()).now_or_panic().unwrap();
+        assert_eq!(body, "upstream");
+
+        // response with default body type is passed through without being boxed again.
+        let res = BuiltResponse(WebResponse::new(ResponseBody::<BoxBody>::bytes("default")))
+            .respond(req)
+            .now_or_panic()
+            .unwrap();
+        assert_eq!(BodySize::from_stream(res.body()), BodySize::Sized(7));
+        let body = collect_string_body(res.into_body()).now_or_panic().unwrap();
+        assert_eq!(body, "default");
+    }
+}
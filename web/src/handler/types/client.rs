@@ -0,0 +1,238 @@
+//! type extractor for client allowed by ip address range.
+
+use core::{
+    fmt,
+    net::{IpAddr, SocketAddr},
+    ops::Deref,
+    str::FromStr,
+};
+
+use std::error;
+
+use crate::{
+    context::WebContext,
+    error::Error,
+    handler::{state::BorrowState, FromRequest},
+    http::StatusCode,
+};
+
+/// ip address range in CIDR notation. e.g. `10.0.0.0/8` or `fd00::/8`.
+///
+/// Bare ip address without prefix length is parsed as range containing only itself.
+///
+/// # Examples
+/// ```rust
+/// # use xitca_web::handler::client::Cidr;
+/// let cidr = "192.168.0.0/16".parse::<Cidr>().unwrap();
+/// assert!(cidr.contains("192.168.1.1".parse().unwrap()));
+/// assert!(!cidr.contains("10.0.0.1".parse().unwrap()));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cidr {
+    addr: IpAddr,
+    prefix: u8,
+}
+
+impl Cidr {
+    /// construct range from network address and prefix length. host bits of address are ignored.
+    ///
+    /// # Errors
+    /// When prefix length is larger than 32 for ipv4 or 128 for ipv6 address.
+    pub fn new(addr: IpAddr, prefix: u8) -> Result<Self, CidrParseError> {
+        let max = match addr {
+            IpAddr::V4(_) => 32,
+            IpAddr::V6(_) => 128,
+        };
+        if prefix > max {
+            return Err(CidrParseError(()));
+        }
+        Ok(Self { addr, prefix })
+    }
+
+    /// check if given ip address is within range. ipv4 mapped ipv6 address is matched as ipv4 address.
+    pub fn contains(&self, addr: IpAddr) -> bool {
+        match (self.addr, addr.to_canonical()) {
+            (IpAddr::V4(net), IpAddr::V4(addr)) => {
+                let mask = u32::MAX.checked_shl(32 - u32::from(self.prefix)).unwrap_or(0);
+                u32::from(net) & mask == u32::from(addr) & mask
+            }
+            (IpAddr::V6(net), IpAddr::V6(addr)) => {
+                let mask = u128::MAX.checked_shl(128 - u32::from(self.prefix)).unwrap_or(0);
+                u128::from(net) & mask == u128::from(addr) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+impl FromStr for Cidr {
+    type Err = CidrParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (addr, prefix) = match s.split_once('/') {
+            Some((addr, prefix)) => (addr, Some(prefix)),
+            None => (s, None),
+        };
+
+        let addr = addr.parse::<IpAddr>().map_err(|_| CidrParseError(()))?;
+
+        let prefix = match prefix {
+            Some(prefix) => prefix.parse().map_err(|_| CidrParseError(()))?,
+            None if addr.is_ipv4() => 32,
+            None => 128,
+        };
+
+        Self::new(addr, prefix)
+    }
+}
+
+/// error type for invalid CIDR notation.
+#[derive(Debug)]
+pub struct CidrParseError(());
+
+impl fmt::Display for CidrParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("invalid CIDR notation")
+    }
+}
+
+impl error::Error for CidrParseError {}
+
+/// allowlist of ip address ranges for [`TrustedClient`] extractor. it must be part of application state.
+#[derive(Debug, Clone, Default)]
+pub struct CidrList(Vec<Cidr>);
+
+impl CidrList {
+    /// construct allowlist from given ranges.
+    pub fn new(cidrs: impl IntoIterator<Item = Cidr>) -> Self {
+        Self(cidrs.into_iter().collect())
+    }
+
+    /// check if given ip address is within any range of allowlist.
+    pub fn contains(&self, addr: IpAddr) -> bool {
+        self.0.iter().any(|cidr| cidr.contains(addr))
+    }
+}
+
+impl FromIterator<Cidr> for CidrList {
+    fn from_iter<T: IntoIterator<Item = Cidr>>(iter: T) -> Self {
+        Self::new(iter)
+    }
+}
+
+/// extractor for client whose peer address is within [`CidrList`] borrowed from application state.
+/// client out of allowlist is rejected with "403 Forbidden" response.
+///
+/// Only peer address of connection is checked. Client address forwarded by reverse proxy through http headers
+/// is not trusted and when application is deployed behind proxy the allowlist applies to proxy's address.
+///
+/// # Examples
+/// ```rust
+/// # use xitca_web::{handler::{client::{CidrList, TrustedClient}, handler_service}, App, WebContext};
+/// // internal only endpoint.
+/// async fn metrics(TrustedClient(addr): TrustedClient) -> String {
+///     format!("hello internal client: {addr}")
+/// }
+///
+/// let list = ["127.0.0.0/8", "::1/128", "10.0.0.0/8"]
+///     .into_iter()
+///     .map(|cidr| cidr.parse().unwrap())
+///     .collect::<CidrList>();
+///
+/// App::new()
+///     .with_state(list)
+///     .at("/metrics", handler_service(metrics))
+///     # .at("/infer", handler_service(|_: &WebContext<'_, CidrList>| async{ "infer type" }))
+///     ;
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct TrustedClient(pub SocketAddr);
+
+impl Deref for TrustedClient {
+    type Target = SocketAddr;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<'a, 'r, C, B> FromRequest<'a, WebContext<'r, C, B>> for TrustedClient
+where
+    C: BorrowState<CidrList>,
+{
+    type Type<'b> = TrustedClient;
+    type Error = Error;
+
+    async fn from_request(ctx: &'a WebContext<'r, C, B>) -> Result<Self, Self::Error> {
+        let addr = *ctx.req().body().socket_addr();
+        if ctx.state().borrow().contains(addr.ip()) {
+            Ok(TrustedClient(addr))
+        } else {
+            Err(Error::from(StatusCode::FORBIDDEN))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use xitca_unsafe_collection::futures::NowOrPanic;
+
+    use crate::{handler::handler_service, http::WebRequest, service::Service, App};
+
+    use super::*;
+
+    #[test]
+    fn cidr() {
+        let contains = |cidr: &str, addr: &str| cidr.parse::<Cidr>().unwrap().contains(addr.parse().unwrap());
+
+        assert!(contains("10.0.0.0/8", "10.255.0.1"));
+        assert!(!contains("10.0.0.0/8", "11.0.0.1"));
+        assert!(contains("10.1.2.3/8", "10.0.0.1"));
+        assert!(contains("0.0.0.0/0", "1.2.3.4"));
+        assert!(contains("127.0.0.1", "127.0.0.1"));
+        assert!(!contains("127.0.0.1", "127.0.0.2"));
+        assert!(contains("10.0.0.0/8", "::ffff:10.0.0.1"));
+        assert!(!contains("10.0.0.0/8", "fd00::1"));
+
+        assert!(contains("fd00::/8", "fd12:3456::1"));
+        assert!(!contains("fd00::/8", "fe80::1"));
+        assert!(contains("::/0", "2001:db8::1"));
+        assert!(contains("::1", "::1"));
+        assert!(!contains("::1/128", "127.0.0.1"));
+
+        assert!("10.0.0.0/33".parse::<Cidr>().is_err());
+        assert!("::/129".parse::<Cidr>().is_err());
+        assert!("10.0.0.0/".parse::<Cidr>().is_err());
+        assert!("localhost/8".parse::<Cidr>().is_err());
+    }
+
+    #[test]
+    fn trusted_client() {
+        async fn handler(TrustedClient(addr): TrustedClient) -> String {
+            addr.ip().to_string()
+        }
+
+        let build = |list: &[&str]| {
+            App::new()
+                .with_state(list.iter().map(|cidr| cidr.parse().unwrap()).collect::<CidrList>())
+                .at("/", handler_service(handler))
+                .finish()
+                .call(())
+                .now_or_panic()
+                .unwrap()
+        };
+
+        // default request comes from unspecified ipv4 address.
+        let res = build(&["0.0.0.0/32"])
+            .call(WebRequest::default())
+            .now_or_panic()
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let res = build(&["10.0.0.0/8", "::/0"])
+            .call(WebRequest::default())
+            .now_or_panic()
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+    }
+}
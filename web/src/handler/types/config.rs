@@ -0,0 +1,200 @@
+//! type extractor and storage for typed application configuration.
+
+use core::{fmt, ops::Deref};
+
+use std::sync::{Arc, PoisonError, RwLock};
+
+use crate::{context::WebContext, error::Error, handler::FromRequest};
+
+use super::state::BorrowState;
+
+/// Shared holder of typed configuration that can be replaced at runtime.
+///
+/// Loading configuration from environment variables, files or remote sources is up to application. The holder only
+/// stores the deserialized value and hands out snapshot of it. Clones of holder share the same value and a new value
+/// stored through any of them is observed by all following [ConfigHolder::load] calls. Snapshots loaded before the
+/// store keep the old value alive until they are dropped so in-flight requests see a consistent configuration.
+///
+/// # Examples
+/// ```rust
+/// # use xitca_web::handler::config::ConfigHolder;
+/// struct Settings {
+///     greeting: String,
+/// }
+///
+/// let holder = ConfigHolder::new(Settings { greeting: String::from("hello") });
+///
+/// // keep a clone for reloading configuration.
+/// let reload = holder.clone();
+///
+/// let old = holder.load();
+/// reload.store(Settings { greeting: String::from("hi") });
+///
+/// assert_eq!(old.greeting, "hello");
+/// assert_eq!(holder.load().greeting, "hi");
+/// ```
+pub struct ConfigHolder<T>(Arc<RwLock<Arc<T>>>);
+
+impl<T> Clone for ConfigHolder<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T> fmt::Debug for ConfigHolder<T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ConfigHolder").field(&self.load()).finish()
+    }
+}
+
+impl<T> ConfigHolder<T> {
+    /// construct holder with initial configuration.
+    pub fn new(config: T) -> Self {
+        Self(Arc::new(RwLock::new(Arc::new(config))))
+    }
+
+    /// load snapshot of current configuration.
+    pub fn load(&self) -> Arc<T> {
+        self.0.read().unwrap_or_else(PoisonError::into_inner).clone()
+    }
+
+    /// replace current configuration with new one and return the previous one.
+    pub fn store(&self, config: T) -> Arc<T> {
+        let mut current = self.0.write().unwrap_or_else(PoisonError::into_inner);
+        core::mem::replace(&mut *current, Arc::new(config))
+    }
+}
+
+/// Typed configuration extractor.
+///
+/// Extract snapshot of configuration stored in [ConfigHolder]. App state must be the holder itself (See
+/// [App::with_config](crate::App::with_config)) or a type implementing [BorrowState] for it. The snapshot is loaded
+/// once per extraction and is not affected by configuration reloaded while request is being handled.
+///
+/// # Examples
+/// ```rust
+/// # use xitca_web::{
+/// #   handler::{
+/// #       config::{Config, ConfigHolder},
+/// #       handler_service,
+/// #       state::BorrowState,
+/// #   },
+/// #   App, WebContext,
+/// # };
+/// struct Settings {
+///     greeting: String,
+/// }
+///
+/// async fn handler(Config(settings): Config<Settings>) -> String {
+///     settings.greeting.clone()
+/// }
+///
+/// // configuration as the only app state.
+/// App::new()
+///     .with_config(ConfigHolder::new(Settings { greeting: String::from("hello") }))
+///     .at("/", handler_service(handler))
+///     # .at("/infer", handler_service(|_: &WebContext<'_, ConfigHolder<Settings>>| async { "infer type" }))
+///     ;
+///
+/// // configuration as part of app state.
+/// #[derive(Clone)]
+/// struct State {
+///     config: ConfigHolder<Settings>,
+/// }
+///
+/// impl BorrowState<ConfigHolder<Settings>> for State {
+///     fn borrow(&self) -> &ConfigHolder<Settings> {
+///         &self.config
+///     }
+/// }
+///
+/// App::new()
+///     .with_state(State { config: ConfigHolder::new(Settings { greeting: String::from("hello") }) })
+///     .at("/", handler_service(handler))
+///     # .at("/infer", handler_service(|_: &WebContext<'_, State>| async { "infer type" }))
+///     ;
+/// ```
+pub struct Config<T>(pub Arc<T>);
+
+impl<T> fmt::Debug for Config<T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Config({:?})", self.0)
+    }
+}
+
+impl<T> Deref for Config<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<'a, 'r, C, B, T> FromRequest<'a, WebContext<'r, C, B>> for Config<T>
+where
+    C: BorrowState<ConfigHolder<T>>,
+    T: 'static,
+{
+    type Type<'b> = Config<T>;
+    type Error = Error;
+
+    #[inline]
+    async fn from_request(ctx: &'a WebContext<'r, C, B>) -> Result<Self, Self::Error> {
+        Ok(Config(ctx.state().borrow().load()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use xitca_unsafe_collection::futures::NowOrPanic;
+
+    use crate::{
+        handler::handler_service,
+        http::{StatusCode, WebRequest},
+        service::Service,
+        test::collect_string_body,
+        App,
+    };
+
+    use super::*;
+
+    struct Settings {
+        greeting: &'static str,
+    }
+
+    #[test]
+    fn extract_reload() {
+        let holder = ConfigHolder::new(Settings { greeting: "hello" });
+
+        let service = App::new()
+            .with_config(holder.clone())
+            .at(
+                "/",
+                handler_service(|Config(settings): Config<Settings>| async move { settings.greeting }),
+            )
+            .finish()
+            .call(())
+            .now_or_panic()
+            .unwrap();
+
+        let call = || {
+            let res = service.call(WebRequest::default()).now_or_panic().unwrap();
+            assert_eq!(res.status(), StatusCode::OK);
+            collect_string_body(res.into_body()).now_or_panic().unwrap()
+        };
+
+        assert_eq!(call(), "hello");
+
+        let old = holder.store(Settings { greeting: "hi" });
+        assert_eq!(old.greeting, "hello");
+
+        assert_eq!(call(), "hi");
+    }
+}
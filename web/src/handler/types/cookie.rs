@@ -1,14 +1,19 @@
 //! type extractor and responder for cookies.
 
-use core::{borrow::Borrow, marker::PhantomData, ops::Deref};
+use core::{borrow::Borrow, fmt, marker::PhantomData, ops::Deref, time::Duration};
 
-pub use cookie::{Cookie, Key, ParseError};
+use std::{borrow::Cow, error};
 
-use cookie::CookieJar as _CookieJar;
+pub use cookie::{Cookie, Key, ParseError, SameSite};
+
+use cookie::{CookieBuilder as _CookieBuilder, CookieJar as _CookieJar};
 
 use crate::{
     body::ResponseBody,
-    error::{error_from_service, forward_blank_bad_request, Error, ErrorStatus, ExtensionNotFound, HeaderNotFound},
+    error::{
+        error_from_service, forward_blank_bad_request, forward_blank_internal, Error, ErrorStatus, ExtensionNotFound,
+        HeaderNotFound,
+    },
     handler::{FromRequest, Responder},
     http::{
         header::ToStrError,
@@ -128,6 +133,164 @@ impl CookieJar {
     }
 }
 
+/// typed builder for [Cookie] with validation of attribute combinations.
+///
+/// Cookie with `SameSite=None` or `Partitioned`([CHIPS]) attribute is rejected by browsers without `Secure`
+/// attribute. When secure is not set explicitly it's enabled automatically for these cookies and when it's
+/// explicitly disabled [CookieBuilder::build] returns [InvalidCookieAttribute] error.
+///
+/// # Examples
+/// ```rust
+/// # use xitca_web::handler::cookie::{CookieBuilder, CookieJar, SameSite};
+/// let cookie = CookieBuilder::new("session", "996")
+///     .path("/")
+///     .http_only(true)
+///     .same_site(SameSite::None)
+///     .partitioned(true)
+///     .build()
+///     .unwrap();
+///
+/// // secure is enabled automatically.
+/// assert_eq!(cookie.secure(), Some(true));
+///
+/// let mut jar = CookieJar::plain();
+/// jar.add(cookie);
+/// ```
+///
+/// [CHIPS]: https://developer.mozilla.org/en-US/docs/Web/Privacy/Privacy_sandbox/Partitioned_cookies
+pub struct CookieBuilder {
+    inner: _CookieBuilder<'static>,
+    secure: Option<bool>,
+    same_site: Option<SameSite>,
+    partitioned: bool,
+}
+
+impl CookieBuilder {
+    /// construct a new builder with given cookie name and value.
+    pub fn new<N, V>(name: N, value: V) -> Self
+    where
+        N: Into<Cow<'static, str>>,
+        V: Into<Cow<'static, str>>,
+    {
+        Self {
+            inner: Cookie::build((name, value)),
+            secure: None,
+            same_site: None,
+            partitioned: false,
+        }
+    }
+
+    /// set `Path` attribute of cookie.
+    pub fn path<P>(mut self, path: P) -> Self
+    where
+        P: Into<Cow<'static, str>>,
+    {
+        self.inner = self.inner.path(path);
+        self
+    }
+
+    /// set `Domain` attribute of cookie.
+    pub fn domain<D>(mut self, domain: D) -> Self
+    where
+        D: Into<Cow<'static, str>>,
+    {
+        self.inner = self.inner.domain(domain);
+        self
+    }
+
+    /// set `Max-Age` attribute of cookie. duration overflows cookie's max age is saturated.
+    pub fn max_age(mut self, dur: Duration) -> Self {
+        let dur = cookie::time::Duration::try_from(dur).unwrap_or(cookie::time::Duration::MAX);
+        self.inner = self.inner.max_age(dur);
+        self
+    }
+
+    /// set `HttpOnly` attribute of cookie.
+    pub fn http_only(mut self, value: bool) -> Self {
+        self.inner = self.inner.http_only(value);
+        self
+    }
+
+    /// set `Secure` attribute of cookie.
+    pub fn secure(mut self, value: bool) -> Self {
+        self.secure = Some(value);
+        self
+    }
+
+    /// set `SameSite` attribute of cookie. [SameSite::None] enables `Secure` attribute when it's not
+    /// set explicitly.
+    pub fn same_site(mut self, value: SameSite) -> Self {
+        self.same_site = Some(value);
+        self
+    }
+
+    /// set `Partitioned` attribute of cookie. `Partitioned` enables `Secure` attribute when it's not
+    /// set explicitly.
+    pub fn partitioned(mut self, value: bool) -> Self {
+        self.partitioned = value;
+        self
+    }
+
+    /// validate attributes and build cookie.
+    ///
+    /// # Errors
+    /// When `SameSite=None` or `Partitioned` attribute is set while `Secure` attribute is explicitly disabled.
+    pub fn build(self) -> Result<Cookie<'static>, InvalidCookieAttribute> {
+        let Self {
+            mut inner,
+            secure,
+            same_site,
+            partitioned,
+        } = self;
+
+        let require_secure = if same_site == Some(SameSite::None) {
+            Some(InvalidCookieAttribute::SameSiteNone)
+        } else if partitioned {
+            Some(InvalidCookieAttribute::Partitioned)
+        } else {
+            None
+        };
+
+        let secure = match (secure, require_secure) {
+            (Some(false), Some(e)) => return Err(e),
+            (None, Some(_)) => Some(true),
+            (secure, _) => secure,
+        };
+
+        if let Some(secure) = secure {
+            inner = inner.secure(secure);
+        }
+        if let Some(same_site) = same_site {
+            inner = inner.same_site(same_site);
+        }
+
+        Ok(inner.partitioned(partitioned).build())
+    }
+}
+
+/// error type for cookie with invalid combination of attributes. See [CookieBuilder] for detail.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum InvalidCookieAttribute {
+    /// `SameSite=None` attribute without `Secure` attribute.
+    SameSiteNone,
+    /// `Partitioned` attribute without `Secure` attribute.
+    Partitioned,
+}
+
+impl fmt::Display for InvalidCookieAttribute {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::SameSiteNone => f.write_str("cookie with SameSite=None attribute must be Secure"),
+            Self::Partitioned => f.write_str("cookie with Partitioned attribute must be Secure"),
+        }
+    }
+}
+
+impl error::Error for InvalidCookieAttribute {}
+
+error_from_service!(InvalidCookieAttribute);
+forward_blank_internal!(InvalidCookieAttribute);
+
 #[doc(hidden)]
 pub struct Plain;
 
@@ -310,6 +473,49 @@ mod test {
         assert_eq!(header.to_str().unwrap(), "996=251");
     }
 
+    #[test]
+    fn cookie_builder() {
+        let cookie = CookieBuilder::new("foo", "bar")
+            .same_site(SameSite::Lax)
+            .build()
+            .unwrap();
+        assert_eq!(cookie.to_string(), "foo=bar; SameSite=Lax");
+
+        let cookie = CookieBuilder::new("foo", "bar")
+            .path("/")
+            .same_site(SameSite::None)
+            .build()
+            .unwrap();
+        assert_eq!(cookie.secure(), Some(true));
+        let value = cookie.to_string();
+        assert!(value.contains("; SameSite=None"));
+        assert_eq!(value.matches("; Secure").count(), 1);
+        assert!(value.contains("; Path=/"));
+
+        let cookie = CookieBuilder::new("foo", "bar")
+            .same_site(SameSite::Strict)
+            .partitioned(true)
+            .build()
+            .unwrap();
+        assert_eq!(cookie.secure(), Some(true));
+        assert_eq!(cookie.partitioned(), Some(true));
+        assert!(cookie.to_string().contains("; Partitioned"));
+
+        let err = CookieBuilder::new("foo", "bar")
+            .same_site(SameSite::None)
+            .secure(false)
+            .build()
+            .unwrap_err();
+        assert_eq!(err, InvalidCookieAttribute::SameSiteNone);
+
+        let err = CookieBuilder::new("foo", "bar")
+            .secure(false)
+            .partitioned(true)
+            .build()
+            .unwrap_err();
+        assert_eq!(err, InvalidCookieAttribute::Partitioned);
+    }
+
     #[derive(Clone)]
     struct MyKey(Key);
 
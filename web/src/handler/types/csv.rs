@@ -0,0 +1,404 @@
+//! type extractor and response generator for csv
+
+use core::{
+    fmt,
+    ops::{Deref, DerefMut},
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+
+use csv::{ReaderBuilder, WriterBuilder};
+use futures_core::stream::Stream;
+use pin_project_lite::pin_project;
+use serde::{de::DeserializeOwned, ser::Serialize};
+
+use crate::{
+    body::{BodyStream, ResponseBody},
+    bytes::{BufMutWriter, Bytes, BytesMut},
+    context::WebContext,
+    error::{error_from_service, forward_blank_bad_request, BodyError, Error},
+    handler::{FromRequest, Responder},
+    http::{
+        header::{HeaderValue, CONTENT_TYPE},
+        Extensions, WebResponse,
+    },
+};
+
+use super::{
+    body::Limit,
+    header::{self, HeaderRef},
+};
+
+pub const DEFAULT_LIMIT: usize = 1024 * 1024;
+
+#[allow(clippy::declare_interior_mutable_const)]
+const TEXT_CSV: HeaderValue = HeaderValue::from_static("text/csv; charset=utf-8");
+
+/// Extract type for csv records and response generator for collection of them. const generic param LIMIT is for
+/// max size of the body in bytes. Body larger than limit would be treated as error.
+///
+/// Default limit is [DEFAULT_LIMIT] in bytes.
+///
+/// When extracting the first row of body is treated as header and fields of records are matched by it's names.
+/// When responding a header row is written from field names of the first record. Fields containing delimiter,
+/// quote or line break are quoted. Delimiter is decided by [CsvDelimiter] attached to request extensions.
+///
+/// # Examples
+/// ```rust
+/// # use serde::{Deserialize, Serialize};
+/// # use xitca_web::{
+/// #   handler::{handler_service, csv::Csv},
+/// #   App, WebContext
+/// # };
+/// #[derive(Deserialize, Serialize)]
+/// struct Row {
+///     name: String,
+///     score: u32,
+/// }
+///
+/// // echo uploaded records back to client with header row.
+/// async fn handler(Csv(rows): Csv<Vec<Row>>) -> Csv<Vec<Row>> {
+///     Csv(rows)
+/// }
+///
+/// App::new()
+///     .at("/", handler_service(handler))
+///     # .at("/infer", handler_service(|_: &WebContext<'_>| async { "used for infer type" }));
+/// ```
+#[derive(Clone)]
+pub struct Csv<T, const LIMIT: usize = DEFAULT_LIMIT>(pub T);
+
+impl<T, const LIMIT: usize> fmt::Debug for Csv<T, LIMIT>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Csv")
+            .field("value", &self.0)
+            .field("limit", &LIMIT)
+            .finish()
+    }
+}
+
+impl<T, const LIMIT: usize> Deref for Csv<T, LIMIT> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T, const LIMIT: usize> DerefMut for Csv<T, LIMIT> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<'a, 'r, C, B, T, const LIMIT: usize> FromRequest<'a, WebContext<'r, C, B>> for Csv<Vec<T>, LIMIT>
+where
+    B: BodyStream + Default,
+    T: DeserializeOwned,
+{
+    type Type<'b> = Csv<Vec<T>, LIMIT>;
+    type Error = Error;
+
+    async fn from_request(ctx: &'a WebContext<'r, C, B>) -> Result<Self, Self::Error> {
+        HeaderRef::<'a, { header::CONTENT_TYPE }>::from_request(ctx).await?;
+        let delimiter = CsvDelimiter::from_extensions(ctx.req().extensions());
+        let (bytes, _) = <(BytesMut, Limit<LIMIT>)>::from_request(ctx).await?;
+        ReaderBuilder::new()
+            .delimiter(delimiter.0)
+            .from_reader(bytes.as_ref())
+            .deserialize()
+            .collect::<Result<_, _>>()
+            .map(Csv)
+            .map_err(Into::into)
+    }
+}
+
+impl<'r, C, B, T> Responder<WebContext<'r, C, B>> for Csv<Vec<T>>
+where
+    T: Serialize,
+{
+    type Response = WebResponse;
+    type Error = Error;
+
+    #[inline]
+    async fn respond(self, ctx: WebContext<'r, C, B>) -> Result<Self::Response, Self::Error> {
+        let delimiter = CsvDelimiter::from_extensions(ctx.req().extensions());
+        self._respond(delimiter, |bytes| ctx.into_response(bytes))
+    }
+
+    // request extensions are not accessible when mapping response of prior responder. default delimiter is used.
+    #[inline]
+    fn map(self, res: Self::Response) -> Result<Self::Response, Self::Error> {
+        self._respond(CsvDelimiter::default(), |bytes| res.map(|_| bytes.into()))
+    }
+}
+
+impl<T> Csv<Vec<T>> {
+    fn _respond<F>(self, delimiter: CsvDelimiter, func: F) -> Result<WebResponse, Error>
+    where
+        T: Serialize,
+        F: FnOnce(Bytes) -> WebResponse,
+    {
+        let mut bytes = BytesMut::new();
+        {
+            let mut writer = WriterBuilder::new()
+                .delimiter(delimiter.0)
+                .from_writer(BufMutWriter(&mut bytes));
+            for record in self.0 {
+                writer.serialize(record)?;
+            }
+            writer.flush().map_err(csv::Error::from)?;
+        }
+        let mut res = func(bytes.freeze());
+        // content type set by prior responder takes precedence.
+        res.headers_mut().entry(CONTENT_TYPE).or_insert(TEXT_CSV);
+        Ok(res)
+    }
+}
+
+/// streaming response generator for csv records.
+///
+/// Records are serialized when response body is polled and a header row is written from field names of the
+/// first record. Useful for exporting large amount of rows without buffering them in memory. Delimiter is
+/// decided by [CsvDelimiter] attached to request extensions.
+///
+/// # Examples
+/// ```rust
+/// # use std::convert::Infallible;
+/// # use futures_util::stream;
+/// # use serde::Serialize;
+/// # use xitca_web::{
+/// #   handler::{handler_service, csv::CsvStream},
+/// #   App, WebContext,
+/// # };
+/// #[derive(Serialize)]
+/// struct Row {
+///     id: u64,
+/// }
+///
+/// let export = || async { CsvStream(stream::iter((0..3).map(|id| Ok::<_, Infallible>(Row { id })))) };
+///
+/// App::new()
+///     .at("/", handler_service(export))
+///     # .at("/infer", handler_service(|_: &WebContext<'_>| async { "used for infer type" }));
+/// ```
+pub struct CsvStream<S>(pub S);
+
+impl<S> fmt::Debug for CsvStream<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CsvStream").finish()
+    }
+}
+
+impl<'r, C, B, S, T, E> Responder<WebContext<'r, C, B>> for CsvStream<S>
+where
+    S: Stream<Item = Result<T, E>> + 'static,
+    T: Serialize,
+    E: Into<BodyError>,
+{
+    type Response = WebResponse;
+    type Error = Error;
+
+    #[inline]
+    async fn respond(self, ctx: WebContext<'r, C, B>) -> Result<Self::Response, Self::Error> {
+        let delimiter = CsvDelimiter::from_extensions(ctx.req().extensions());
+        let res = ctx.into_response(ResponseBody::none());
+        Ok(self._respond(delimiter, res))
+    }
+
+    // request extensions are not accessible when mapping response of prior responder. default delimiter is used.
+    #[inline]
+    fn map(self, res: Self::Response) -> Result<Self::Response, Self::Error> {
+        Ok(self._respond(CsvDelimiter::default(), res))
+    }
+}
+
+impl<S> CsvStream<S> {
+    fn _respond<T, E>(self, delimiter: CsvDelimiter, res: WebResponse) -> WebResponse
+    where
+        S: Stream<Item = Result<T, E>> + 'static,
+        T: Serialize,
+        E: Into<BodyError>,
+    {
+        let mut res = res.map(|_| {
+            ResponseBody::box_stream(RecordStream {
+                stream: self.0,
+                delimiter,
+                has_headers: true,
+                buf: BytesMut::new(),
+            })
+        });
+        res.headers_mut().entry(CONTENT_TYPE).or_insert(TEXT_CSV);
+        res
+    }
+}
+
+pin_project! {
+    struct RecordStream<S> {
+        #[pin]
+        stream: S,
+        delimiter: CsvDelimiter,
+        has_headers: bool,
+        buf: BytesMut,
+    }
+}
+
+impl<S, T, E> Stream for RecordStream<S>
+where
+    S: Stream<Item = Result<T, E>>,
+    T: Serialize,
+    E: Into<BodyError>,
+{
+    type Item = Result<Bytes, BodyError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+        match ready!(this.stream.poll_next(cx)) {
+            Some(Ok(record)) => {
+                let res = {
+                    // header row is only written for the first record.
+                    let mut writer = WriterBuilder::new()
+                        .delimiter(this.delimiter.0)
+                        .has_headers(*this.has_headers)
+                        .from_writer(BufMutWriter(&mut *this.buf));
+                    writer
+                        .serialize(record)
+                        .and_then(|_| writer.flush().map_err(Into::into))
+                };
+                *this.has_headers = false;
+                Poll::Ready(Some(res.map(|_| this.buf.split().freeze()).map_err(BodyError::from)))
+            }
+            Some(Err(e)) => Poll::Ready(Some(Err(e.into()))),
+            None => Poll::Ready(None),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.stream.size_hint()
+    }
+}
+
+/// delimiter of fields for [Csv] extractor and responder and [CsvStream] responder. Default to comma.
+///
+/// Can be attached to request extensions with [Extension](crate::middleware::Extension) middleware.
+///
+/// # Examples
+/// ```rust
+/// # use xitca_web::{
+/// #   handler::{handler_service, csv::{Csv, CsvDelimiter}},
+/// #   middleware::Extension,
+/// #   App, WebContext
+/// # };
+/// async fn handler(Csv(rows): Csv<Vec<(String, u32)>>) -> Csv<Vec<(String, u32)>> {
+///     Csv(rows)
+/// }
+///
+/// App::new()
+///     .at("/", handler_service(handler))
+///     # .at("/infer", handler_service(|_: &WebContext<'_>| async { "used for infer type" }))
+///     // tab separated values.
+///     .enclosed(Extension::new(CsvDelimiter(b'\t')));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CsvDelimiter(pub u8);
+
+impl Default for CsvDelimiter {
+    fn default() -> Self {
+        Self(b',')
+    }
+}
+
+impl CsvDelimiter {
+    fn from_extensions(ext: &Extensions) -> Self {
+        ext.get::<Self>().copied().unwrap_or_default()
+    }
+}
+
+error_from_service!(csv::Error);
+forward_blank_bad_request!(csv::Error);
+
+#[cfg(test)]
+mod test {
+    use core::convert::Infallible;
+
+    use futures_util::stream;
+    use xitca_unsafe_collection::futures::NowOrPanic;
+
+    use crate::{
+        handler::handler_service,
+        http::{header::CONTENT_LENGTH, StatusCode, WebRequest},
+        middleware::Extension,
+        service::Service,
+        test::collect_string_body,
+        App,
+    };
+
+    use super::*;
+
+    #[derive(serde::Deserialize, serde::Serialize, Clone, Debug, PartialEq)]
+    struct Row {
+        name: String,
+        score: u32,
+    }
+
+    fn rows() -> Vec<Row> {
+        vec![
+            Row {
+                name: "a,b".into(),
+                score: 1,
+            },
+            Row {
+                name: "line\n\"quoted\"".into(),
+                score: 2,
+            },
+        ]
+    }
+
+    const BODY: &str = "name,score\n\"a,b\",1\n\"line\n\"\"quoted\"\"\",2\n";
+
+    #[test]
+    fn extract() {
+        let mut ctx = WebContext::new_test(());
+        let mut ctx = ctx.as_web_ctx();
+
+        ctx.req_mut().headers_mut().insert(CONTENT_TYPE, TEXT_CSV);
+        ctx.req_mut().headers_mut().insert(CONTENT_LENGTH, BODY.len().into());
+        *ctx.body_borrow_mut() = BODY.as_bytes().into();
+
+        let Csv(records) = Csv::<Vec<Row>>::from_request(&ctx).now_or_panic().unwrap();
+        assert_eq!(records, rows());
+    }
+
+    #[test]
+    fn respond() {
+        let mut ctx = WebContext::new_test(());
+        let ctx = ctx.as_web_ctx();
+
+        let res = Csv(rows()).respond(ctx).now_or_panic().unwrap();
+        assert_eq!(res.headers().get(CONTENT_TYPE).unwrap(), TEXT_CSV);
+        let body = collect_string_body(res.into_body()).now_or_panic().unwrap();
+        assert_eq!(body, BODY);
+    }
+
+    #[test]
+    fn stream_with_delimiter() {
+        let handler = || async { CsvStream(stream::iter(rows().into_iter().map(Ok::<_, Infallible>))) };
+
+        let service = App::new()
+            .at("/", handler_service(handler))
+            .enclosed(Extension::new(CsvDelimiter(b';')))
+            .finish()
+            .call(())
+            .now_or_panic()
+            .unwrap();
+
+        let res = service.call(WebRequest::default()).now_or_panic().unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers().get(CONTENT_TYPE).unwrap(), TEXT_CSV);
+        let body = collect_string_body(res.into_body()).now_or_panic().unwrap();
+        assert_eq!(body, "name;score\na,b;1\n\"line\n\"\"quoted\"\"\";2\n");
+    }
+}
@@ -0,0 +1,124 @@
+//! type responder for serving single file.
+
+use http_file::{runtime::AsyncFs, ServeError};
+
+use crate::{body::ResponseBody, context::WebContext, error::Error, handler::Responder, http::WebResponse};
+
+pub use http_file::NamedFile;
+
+/// file is opened asynchronously and streamed as response body with `Content-Type`, `Content-Length` and
+/// `Last-Modified` headers. conditional and range request headers are respected.
+///
+/// file that does not exist produces "404 Not Found" error and file can not be accessed due to lack of permission
+/// produces "403 Forbidden" error.
+impl<'r, C, B, F> Responder<WebContext<'r, C, B>> for NamedFile<F>
+where
+    F: AsyncFs,
+    F::File: 'static,
+{
+    type Response = WebResponse;
+    type Error = Error;
+
+    async fn respond(self, ctx: WebContext<'r, C, B>) -> Result<Self::Response, Self::Error> {
+        match self.serve(ctx.req()).await {
+            Ok(res) => Ok(res.map(ResponseBody::box_stream)),
+            Err(ServeError::Io(e)) => Err(Error::from(e)),
+            // these responses carry headers and are not errors to the client.
+            Err(e @ (ServeError::NotModified | ServeError::RangeNotSatisfied(_))) => {
+                Ok(e.into_response().map(|_| ResponseBody::none()))
+            }
+            Err(e) => Err(Error::from(e.into_response().status())),
+        }
+    }
+}
+
+/// serve file of given path with [NamedFile].
+///
+/// # Examples
+/// ```rust
+/// # use xitca_web::{
+/// #     handler::{file::NamedFile, handler_service},
+/// #     route::get,
+/// #     App, WebContext,
+/// # };
+/// # use std::path::PathBuf;
+/// // decide which file to serve dynamically.
+/// async fn index() -> PathBuf {
+///     PathBuf::from("./static/index.html")
+/// }
+///
+/// // ask client to download the file.
+/// async fn download() -> NamedFile {
+///     NamedFile::new("./report.pdf").attachment()
+/// }
+///
+/// App::new()
+///     .at("/", get(handler_service(index)))
+///     .at("/download", get(handler_service(download)))
+///     # .at("/infer", handler_service(|_: &WebContext<'_>| async { "used for infer type" }));
+/// ```
+#[cfg(feature = "file")]
+impl<'r, C, B> Responder<WebContext<'r, C, B>> for std::path::PathBuf {
+    type Response = WebResponse;
+    type Error = Error;
+
+    async fn respond(self, ctx: WebContext<'r, C, B>) -> Result<Self::Response, Self::Error> {
+        NamedFile::new(self).respond(ctx).await
+    }
+}
+
+#[cfg(all(test, feature = "file"))]
+mod test {
+    use std::path::PathBuf;
+
+    use crate::{
+        handler::handler_service,
+        http::{
+            header::{CONTENT_DISPOSITION, CONTENT_LENGTH},
+            request, StatusCode,
+        },
+        route::get,
+        service::Service,
+        test::collect_body,
+        App,
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn named_file() {
+        let service = App::new()
+            .at(
+                "/",
+                get(handler_service(|| async { NamedFile::new("Cargo.toml").attachment() })),
+            )
+            .at("/none", get(handler_service(|| async { PathBuf::from("none.toml") })))
+            .finish()
+            .call(())
+            .await
+            .unwrap();
+
+        let req = request::Builder::default().uri("/").body(Default::default()).unwrap();
+        let res = service.call(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(
+            res.headers().get(CONTENT_DISPOSITION).unwrap(),
+            "attachment; filename=\"Cargo.toml\""
+        );
+
+        let file = std::fs::read("Cargo.toml").unwrap();
+        assert_eq!(
+            res.headers().get(CONTENT_LENGTH).unwrap(),
+            file.len().to_string().as_str()
+        );
+        let body = collect_body(res.into_body()).await.unwrap();
+        assert_eq!(body, file);
+
+        let req = request::Builder::default()
+            .uri("/none")
+            .body(Default::default())
+            .unwrap();
+        let res = service.call(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    }
+}
@@ -18,7 +18,9 @@ use crate::{
     http::{const_header_value::APPLICATION_WWW_FORM_URLENCODED, header::CONTENT_TYPE, WebResponse},
 };
 
-use super::body::Limit;
+use super::{body::Limit, urlencoded};
+
+pub use super::urlencoded::UrlencodedMode;
 
 pub const DEFAULT_LIMIT: usize = 1024 * 1024;
 
@@ -26,6 +28,9 @@ pub const DEFAULT_LIMIT: usize = 1024 * 1024;
 /// Object larger than limit would be treated as error.
 ///
 /// Default limit is [DEFAULT_LIMIT] in bytes.
+///
+/// Form is parsed with [UrlencodedMode::Strict] by default. [UrlencodedMode::Nested] can be attached to request
+/// extensions for collecting repeated and bracketed keys. See [UrlencodedMode] for detail.
 pub struct Form<T, const LIMIT: usize = DEFAULT_LIMIT>(pub T);
 
 impl<T, const LIMIT: usize> fmt::Debug for Form<T, LIMIT>
@@ -65,12 +70,19 @@ where
     async fn from_request(ctx: &'a WebContext<'r, C, B>) -> Result<Self, Self::Error> {
         HeaderRef::<'a, { header::CONTENT_TYPE }>::from_request(ctx).await?;
         let (bytes, _) = <(BytesMut, Limit<LIMIT>)>::from_request(ctx).await?;
-        serde_urlencoded::from_bytes(&bytes).map(Form).map_err(Into::into)
+        let mode = ctx
+            .req()
+            .extensions()
+            .get::<UrlencodedMode>()
+            .copied()
+            .unwrap_or_default();
+        urlencoded::from_bytes(mode, &bytes).map(Form).map_err(Into::into)
     }
 }
 
 /// lazy deserialize type.
 /// it lowers the deserialization to handler function where zero copy deserialize can happen.
+/// Lazy form is always parsed with [UrlencodedMode::Strict].
 pub struct LazyForm<T, const LIMIT: usize = DEFAULT_LIMIT> {
     bytes: Vec<u8>,
     _form: PhantomData<T>,
@@ -129,7 +141,10 @@ impl<T> Form<T> {
     {
         let string = serde_urlencoded::to_string(self.0)?;
         let mut res = func(Bytes::from(string));
-        res.headers_mut().insert(CONTENT_TYPE, APPLICATION_WWW_FORM_URLENCODED);
+        // content type set by prior responder takes precedence.
+        res.headers_mut()
+            .entry(CONTENT_TYPE)
+            .or_insert(APPLICATION_WWW_FORM_URLENCODED);
         Ok(res)
     }
 }
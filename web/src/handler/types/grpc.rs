@@ -0,0 +1,416 @@
+//! type extractor and response generator for unary gRPC call.
+//!
+//! Only unary call(one request message and one response message) with uncompressed message is supported.
+//! gRPC requires http/2 transport so `http2` feature should be enabled alongside with `grpc` feature when
+//! hosting gRPC services.
+
+use core::{
+    convert::Infallible,
+    fmt,
+    ops::{Deref, DerefMut},
+};
+
+use std::{borrow::Cow, error};
+
+use prost::Message;
+
+use crate::{
+    body::{BodyStream, ResponseBody},
+    bytes::{BufMut, Bytes, BytesMut},
+    context::WebContext,
+    error::{error_from_service, Error},
+    handler::{FromRequest, Responder},
+    http::{
+        const_header_value::GRPC,
+        header::{HeaderName, HeaderValue, CONTENT_TYPE, TRAILER},
+        WebResponse,
+    },
+    service::Service,
+};
+
+use super::body::Limit;
+
+pub const DEFAULT_LIMIT: usize = 4 * 1024 * 1024;
+
+#[allow(clippy::declare_interior_mutable_const)]
+const GRPC_STATUS: HeaderName = HeaderName::from_static("grpc-status");
+#[allow(clippy::declare_interior_mutable_const)]
+const GRPC_MESSAGE: HeaderName = HeaderName::from_static("grpc-message");
+
+// length of message frame prefix. 1 byte compress flag and 4 bytes big endian message length.
+const PREFIX_LEN: usize = 5;
+
+/// Extract type and responder for unary gRPC message. const generic param LIMIT is for max size of
+/// the request message frame in bytes.
+///
+/// Default limit is [DEFAULT_LIMIT] in bytes.
+///
+/// As responder the message is length prefixed and sent with `application/grpc` content type. `grpc-status`
+/// is sent in trailers when response is served with http/2.
+///
+/// # Examples
+/// ```rust
+/// # use xitca_web::{
+/// #   handler::{grpc::{Grpc, GrpcCode, GrpcStatus}, handler_service},
+/// #   route::post,
+/// #   App, WebContext
+/// # };
+/// #[derive(Clone, PartialEq, prost::Message)]
+/// struct HelloRequest {
+///     #[prost(string, tag = "1")]
+///     name: String,
+/// }
+///
+/// #[derive(Clone, PartialEq, prost::Message)]
+/// struct HelloReply {
+///     #[prost(string, tag = "1")]
+///     message: String,
+/// }
+///
+/// // unary gRPC service. returned GrpcStatus is sent to client as error status of the call.
+/// async fn say_hello(Grpc(req): Grpc<HelloRequest>) -> Result<Grpc<HelloReply>, GrpcStatus> {
+///     if req.name.is_empty() {
+///         return Err(GrpcStatus::new(GrpcCode::InvalidArgument, "name must not be empty"));
+///     }
+///     Ok(Grpc(HelloReply {
+///         message: format!("hello, {}", req.name),
+///     }))
+/// }
+///
+/// App::new()
+///     .at("/helloworld.Greeter/SayHello", post(handler_service(say_hello)))
+///     # .at("/infer", handler_service(|_: &WebContext<'_>| async { "used for infer type" }));
+/// ```
+#[derive(Clone)]
+pub struct Grpc<T, const LIMIT: usize = DEFAULT_LIMIT>(pub T);
+
+impl<T, const LIMIT: usize> fmt::Debug for Grpc<T, LIMIT>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Grpc")
+            .field("value", &self.0)
+            .field("limit", &LIMIT)
+            .finish()
+    }
+}
+
+impl<T, const LIMIT: usize> Deref for Grpc<T, LIMIT> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T, const LIMIT: usize> DerefMut for Grpc<T, LIMIT> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<'a, 'r, C, B, T, const LIMIT: usize> FromRequest<'a, WebContext<'r, C, B>> for Grpc<T, LIMIT>
+where
+    B: BodyStream + Default,
+    T: Message + Default,
+{
+    type Type<'b> = Grpc<T, LIMIT>;
+    type Error = Error;
+
+    async fn from_request(ctx: &'a WebContext<'r, C, B>) -> Result<Self, Self::Error> {
+        let is_grpc = ctx
+            .req()
+            .headers()
+            .get(CONTENT_TYPE)
+            .map(|v| v.as_bytes().starts_with(GRPC.as_bytes()))
+            .unwrap_or(false);
+
+        if !is_grpc {
+            return Err(GrpcStatus::new(GrpcCode::InvalidArgument, "content type is not application/grpc").into());
+        }
+
+        let (bytes, _) = <(BytesMut, Limit<LIMIT>)>::from_request(ctx).await?;
+        decode(&bytes).map(Grpc).map_err(Into::into)
+    }
+}
+
+fn decode<T>(bytes: &[u8]) -> Result<T, GrpcStatus>
+where
+    T: Message + Default,
+{
+    if bytes.len() < PREFIX_LEN {
+        return Err(GrpcStatus::new(GrpcCode::InvalidArgument, "incomplete message frame"));
+    }
+
+    if bytes[0] != 0 {
+        return Err(GrpcStatus::new(
+            GrpcCode::Unimplemented,
+            "compressed message is not supported",
+        ));
+    }
+
+    let len = u32::from_be_bytes([bytes[1], bytes[2], bytes[3], bytes[4]]) as usize;
+    let msg = &bytes[PREFIX_LEN..];
+
+    if msg.len() != len {
+        return Err(GrpcStatus::new(
+            GrpcCode::InvalidArgument,
+            "message length does not match frame prefix",
+        ));
+    }
+
+    T::decode(msg).map_err(|e| GrpcStatus::new(GrpcCode::InvalidArgument, e.to_string()))
+}
+
+impl<'r, C, B, T, const LIMIT: usize> Responder<WebContext<'r, C, B>> for Grpc<T, LIMIT>
+where
+    T: Message,
+{
+    type Response = WebResponse;
+    type Error = Error;
+
+    #[inline]
+    async fn respond(self, ctx: WebContext<'r, C, B>) -> Result<Self::Response, Self::Error> {
+        self._respond(|bytes| ctx.into_response(bytes))
+    }
+
+    #[inline]
+    fn map(self, res: Self::Response) -> Result<Self::Response, Self::Error> {
+        self._respond(|bytes| res.map(|_| bytes.into()))
+    }
+}
+
+impl<T, const LIMIT: usize> Grpc<T, LIMIT> {
+    fn _respond<F>(self, func: F) -> Result<WebResponse, Error>
+    where
+        T: Message,
+        F: FnOnce(Bytes) -> WebResponse,
+    {
+        let len = self.0.encoded_len();
+        let mut bytes = BytesMut::with_capacity(PREFIX_LEN + len);
+        bytes.put_u8(0);
+        bytes.put_u32(len as u32);
+        self.0
+            .encode(&mut bytes)
+            .map_err(|e| GrpcStatus::new(GrpcCode::Internal, e.to_string()))?;
+
+        let mut res = func(bytes.freeze());
+        let headers = res.headers_mut();
+        headers.insert(CONTENT_TYPE, GRPC);
+        // grpc-status is moved to trailers by http/2 dispatcher.
+        headers.insert(GRPC_STATUS, GrpcCode::Ok.as_header_value());
+        headers.append(TRAILER, HeaderValue::from_static("grpc-status"));
+        Ok(res)
+    }
+}
+
+/// status code of gRPC call.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum GrpcCode {
+    Ok = 0,
+    Cancelled = 1,
+    Unknown = 2,
+    InvalidArgument = 3,
+    DeadlineExceeded = 4,
+    NotFound = 5,
+    AlreadyExists = 6,
+    PermissionDenied = 7,
+    ResourceExhausted = 8,
+    FailedPrecondition = 9,
+    Aborted = 10,
+    OutOfRange = 11,
+    Unimplemented = 12,
+    Internal = 13,
+    Unavailable = 14,
+    DataLoss = 15,
+    Unauthenticated = 16,
+}
+
+impl GrpcCode {
+    fn as_header_value(&self) -> HeaderValue {
+        HeaderValue::from(*self as u16)
+    }
+}
+
+/// error type of gRPC call. produce a trailers only gRPC response with `grpc-status` and `grpc-message`
+/// headers.
+#[derive(Debug)]
+pub struct GrpcStatus {
+    code: GrpcCode,
+    message: Cow<'static, str>,
+}
+
+impl GrpcStatus {
+    /// construct a new status with given code and message.
+    pub fn new(code: GrpcCode, message: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+
+    /// status code of gRPC call.
+    pub fn code(&self) -> GrpcCode {
+        self.code
+    }
+
+    /// message describing the status.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for GrpcStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "grpc status: {:?}, message: {}", self.code, self.message)
+    }
+}
+
+impl error::Error for GrpcStatus {}
+
+error_from_service!(GrpcStatus);
+
+impl<'r, C, B> Service<WebContext<'r, C, B>> for GrpcStatus {
+    type Response = WebResponse;
+    type Error = Infallible;
+
+    async fn call(&self, ctx: WebContext<'r, C, B>) -> Result<Self::Response, Self::Error> {
+        // gRPC error is always carried with "200 OK" response.
+        let mut res = ctx.into_response(ResponseBody::empty());
+        let headers = res.headers_mut();
+        headers.insert(CONTENT_TYPE, GRPC);
+        headers.insert(GRPC_STATUS, self.code.as_header_value());
+        if !self.message.is_empty() {
+            let msg = percent_encode(&self.message);
+            headers.insert(GRPC_MESSAGE, HeaderValue::try_from(msg).unwrap());
+        }
+        Ok(res)
+    }
+}
+
+// grpc-message is percent encoded with unreserved range of printable ascii.
+fn percent_encode(msg: &str) -> String {
+    use core::fmt::Write;
+
+    let mut out = String::with_capacity(msg.len());
+    for b in msg.bytes() {
+        match b {
+            b' '..=b'~' if b != b'%' => out.push(b as char),
+            _ => write!(out, "%{b:02X}").unwrap(),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use xitca_unsafe_collection::futures::NowOrPanic;
+
+    use crate::{
+        handler::handler_service,
+        http::{header::CONTENT_LENGTH, StatusCode},
+        test::collect_body,
+    };
+
+    use super::*;
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    struct Hello {
+        #[prost(string, tag = "1")]
+        name: String,
+    }
+
+    fn frame(msg: &Hello) -> Vec<u8> {
+        let mut buf = vec![0];
+        buf.extend_from_slice(&(msg.encoded_len() as u32).to_be_bytes());
+        msg.encode(&mut buf).unwrap();
+        buf
+    }
+
+    #[test]
+    fn unary() {
+        async fn handler(Grpc(req): Grpc<Hello>) -> Result<Grpc<Hello>, GrpcStatus> {
+            if req.name.is_empty() {
+                return Err(GrpcStatus::new(GrpcCode::InvalidArgument, "empty name"));
+            }
+            Ok(Grpc(Hello {
+                name: format!("hello, {}", req.name),
+            }))
+        }
+
+        let service = handler_service(handler).call(()).now_or_panic().unwrap();
+
+        let body = frame(&Hello { name: "xitca".into() });
+        let mut ctx = WebContext::new_test(&());
+        let mut ctx = ctx.as_web_ctx();
+        ctx.req_mut().headers_mut().insert(CONTENT_TYPE, GRPC);
+        ctx.req_mut().headers_mut().insert(CONTENT_LENGTH, body.len().into());
+        *ctx.body_borrow_mut() = body.into();
+
+        let res = service.call(ctx).now_or_panic().unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers().get(CONTENT_TYPE).unwrap(), GRPC);
+        assert_eq!(res.headers().get(GRPC_STATUS).unwrap(), "0");
+        assert_eq!(res.headers().get(TRAILER).unwrap(), "grpc-status");
+
+        let body = collect_body(res.into_body()).now_or_panic().unwrap();
+        let reply = decode::<Hello>(&body).unwrap();
+        assert_eq!(reply.name, "hello, xitca");
+
+        let body = frame(&Hello::default());
+        let mut ctx = WebContext::new_test(&());
+        let mut ctx = ctx.as_web_ctx();
+        ctx.req_mut().headers_mut().insert(CONTENT_TYPE, GRPC);
+        ctx.req_mut().headers_mut().insert(CONTENT_LENGTH, body.len().into());
+        *ctx.body_borrow_mut() = body.into();
+
+        let err = service.call(ctx).now_or_panic().err().unwrap();
+        let mut ctx = WebContext::new_test(&());
+        let res = err.call(ctx.as_web_ctx()).now_or_panic().unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers().get(GRPC_STATUS).unwrap(), "3");
+        assert_eq!(res.headers().get(GRPC_MESSAGE).unwrap(), "empty name");
+    }
+
+    #[test]
+    fn custom_limit() {
+        async fn echo(req: Grpc<Hello, 64>) -> Grpc<Hello, 64> {
+            req
+        }
+
+        let service = handler_service(echo).call(()).now_or_panic().unwrap();
+
+        let body = frame(&Hello { name: "xitca".into() });
+        let mut ctx = WebContext::new_test(&());
+        let mut ctx = ctx.as_web_ctx();
+        ctx.req_mut().headers_mut().insert(CONTENT_TYPE, GRPC);
+        ctx.req_mut().headers_mut().insert(CONTENT_LENGTH, body.len().into());
+        *ctx.body_borrow_mut() = body.into();
+
+        let res = service.call(ctx).now_or_panic().unwrap();
+        assert_eq!(res.headers().get(GRPC_STATUS).unwrap(), "0");
+        let body = collect_body(res.into_body()).now_or_panic().unwrap();
+        assert_eq!(decode::<Hello>(&body).unwrap().name, "xitca");
+    }
+
+    #[test]
+    fn invalid_frame() {
+        assert_eq!(decode::<Hello>(&[0, 0]).unwrap_err().code(), GrpcCode::InvalidArgument);
+        assert_eq!(
+            decode::<Hello>(&[1, 0, 0, 0, 0]).unwrap_err().code(),
+            GrpcCode::Unimplemented
+        );
+        assert_eq!(
+            decode::<Hello>(&[0, 0, 0, 0, 1]).unwrap_err().code(),
+            GrpcCode::InvalidArgument
+        );
+    }
+
+    #[test]
+    fn message_encode() {
+        assert_eq!(percent_encode("bad 100%"), "bad 100%25");
+        assert_eq!(percent_encode("好"), "%E5%A5%BD");
+    }
+}
@@ -40,7 +40,8 @@ where
     }
 
     fn map(self, mut res: Self::Response) -> Result<Self::Response, Self::Error> {
-        res.headers_mut().insert(CONTENT_TYPE, TEXT_HTML_UTF8);
+        // content type set by prior responder takes precedence.
+        res.headers_mut().entry(CONTENT_TYPE).or_insert(TEXT_HTML_UTF8);
         Ok(res.map(|_| self.0.into()))
     }
 }
@@ -5,18 +5,27 @@ use core::{
     fmt,
     marker::PhantomData,
     ops::{Deref, DerefMut},
+    pin::Pin,
+    task::{ready, Context, Poll},
 };
 
+use futures_core::stream::Stream;
+use pin_project_lite::pin_project;
 use serde::{de::Deserialize, ser::Serialize};
 use xitca_http::util::service::router::{PathGen, RouteGen, RouterMapErr};
 
 use crate::{
-    body::BodyStream,
+    body::{BodyStream, Flush, ResponseBody},
     bytes::{BufMutWriter, Bytes, BytesMut},
     context::WebContext,
-    error::{error_from_service, forward_blank_bad_request, Error},
+    error::{error_from_service, BodyError, Error},
     handler::{FromRequest, Responder},
-    http::{const_header_value::JSON, header::CONTENT_TYPE, WebResponse},
+    http::{
+        const_header_value::JSON,
+        header::{HeaderValue, CONTENT_TYPE},
+        StatusCode, WebResponse,
+    },
+    middleware::server_timing::{self, Phase},
     service::Service,
 };
 
@@ -25,12 +34,16 @@ use super::{
     header::{self, HeaderRef},
 };
 
+mod number;
+
 pub const DEFAULT_LIMIT: usize = 1024 * 1024;
 
 /// Extract type for Json object. const generic param LIMIT is for max size of the object in bytes.
 /// Object larger than limit would be treated as error.
 ///
 /// Default limit is [DEFAULT_LIMIT] in bytes.
+///
+/// Number of the object is extracted and responded according to [JsonNumberFormat] attached to request extensions.
 #[derive(Clone)]
 pub struct Json<T, const LIMIT: usize = DEFAULT_LIMIT>(pub T);
 
@@ -70,8 +83,9 @@ where
 
     async fn from_request(ctx: &'a WebContext<'r, C, B>) -> Result<Self, Self::Error> {
         HeaderRef::<'a, { header::CONTENT_TYPE }>::from_request(ctx).await?;
+        let format = JsonNumberFormat::from_extensions(ctx.req().extensions());
         let (bytes, _) = <(BytesMut, Limit<LIMIT>)>::from_request(ctx).await?;
-        serde_json::from_slice(&bytes).map(Json).map_err(Into::into)
+        format.deserialize(&bytes).map(Json).map_err(Into::into)
     }
 }
 
@@ -109,6 +123,7 @@ where
 /// ```
 pub struct LazyJson<T, const LIMIT: usize = DEFAULT_LIMIT> {
     bytes: Vec<u8>,
+    format: JsonNumberFormat,
     _json: PhantomData<T>,
 }
 
@@ -117,7 +132,7 @@ impl<T, const LIMIT: usize> LazyJson<T, LIMIT> {
     where
         T: Deserialize<'de>,
     {
-        serde_json::from_slice(&self.bytes).map_err(Into::into)
+        self.format.deserialize(&self.bytes).map_err(Into::into)
     }
 }
 
@@ -131,9 +146,11 @@ where
 
     async fn from_request(ctx: &'a WebContext<'r, C, B>) -> Result<Self, Self::Error> {
         HeaderRef::<'a, { header::CONTENT_TYPE }>::from_request(ctx).await?;
+        let format = JsonNumberFormat::from_extensions(ctx.req().extensions());
         let (bytes, _) = <(Vec<u8>, Limit<LIMIT>)>::from_request(ctx).await?;
         Ok(LazyJson {
             bytes,
+            format,
             _json: PhantomData,
         })
     }
@@ -148,25 +165,31 @@ where
 
     #[inline]
     async fn respond(self, ctx: WebContext<'r, C, B>) -> Result<Self::Response, Self::Error> {
-        self._respond(|bytes| ctx.into_response(bytes))
+        let timings = server_timing::timings(ctx.req().extensions());
+        let format = JsonNumberFormat::from_extensions(ctx.req().extensions());
+        server_timing::measure(timings, Phase::Serialization, || {
+            self._respond(format, |bytes| ctx.into_response(bytes))
+        })
     }
 
+    // request extensions are not accessible when mapping response of prior responder. default format is used.
     #[inline]
     fn map(self, res: Self::Response) -> Result<Self::Response, Self::Error> {
-        self._respond(|bytes| res.map(|_| bytes.into()))
+        self._respond(JsonNumberFormat::default(), |bytes| res.map(|_| bytes.into()))
     }
 }
 
 impl<T> Json<T> {
-    fn _respond<F>(self, func: F) -> Result<WebResponse, Error>
+    fn _respond<F>(self, format: JsonNumberFormat, func: F) -> Result<WebResponse, Error>
     where
         T: Serialize,
         F: FnOnce(Bytes) -> WebResponse,
     {
         let mut bytes = BytesMut::new();
-        serde_json::to_writer(BufMutWriter(&mut bytes), &self.0)?;
+        format.to_writer(BufMutWriter(&mut bytes), &self.0)?;
         let mut res = func(bytes.freeze());
-        res.headers_mut().insert(CONTENT_TYPE, JSON);
+        // content type set by prior responder takes precedence.
+        res.headers_mut().entry(CONTENT_TYPE).or_insert(JSON);
         Ok(res)
     }
 }
@@ -186,8 +209,260 @@ impl<'r, C, B> Responder<WebContext<'r, C, B>> for serde_json::Value {
     }
 }
 
+#[allow(clippy::declare_interior_mutable_const)]
+const NDJSON: HeaderValue = HeaderValue::from_static("application/x-ndjson");
+
+/// Response generator for stream of json values. Every item of stream is written as one line of newline delimited
+/// json and body is flushed to client after each item.
+///
+/// Number of items is written according to [JsonNumberFormat] attached to request extensions.
+///
+/// # Examples
+/// ```rust
+/// # use std::convert::Infallible;
+/// # use futures_util::stream;
+/// # use xitca_web::{
+/// #   handler::{handler_service, json::JsonStream},
+/// #   App, WebContext,
+/// # };
+/// async fn handler() -> JsonStream<impl futures_core::Stream<Item = Result<u64, Infallible>>> {
+///     JsonStream(stream::iter((0..3).map(Ok)))
+/// }
+///
+/// App::new()
+///     .at("/", handler_service(handler))
+///     # .at("/infer", handler_service(|_: &WebContext<'_>| async { "used for infer type" }));
+/// ```
+pub struct JsonStream<S>(pub S);
+
+impl<S> fmt::Debug for JsonStream<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JsonStream").finish()
+    }
+}
+
+impl<'r, C, B, S, T, E> Responder<WebContext<'r, C, B>> for JsonStream<S>
+where
+    S: Stream<Item = Result<T, E>> + 'static,
+    T: Serialize,
+    E: Into<BodyError>,
+{
+    type Response = WebResponse;
+    type Error = Error;
+
+    async fn respond(self, ctx: WebContext<'r, C, B>) -> Result<Self::Response, Self::Error> {
+        let format = JsonNumberFormat::from_extensions(ctx.req().extensions());
+        let res = ctx.into_response(ResponseBody::none());
+        Ok(self.map_with(res, format))
+    }
+
+    // request extensions are not accessible when mapping response of prior responder. default format is used.
+    fn map(self, res: Self::Response) -> Result<Self::Response, Self::Error> {
+        Ok(self.map_with(res, JsonNumberFormat::default()))
+    }
+}
+
+impl<S> JsonStream<S> {
+    fn map_with<T, E>(self, mut res: WebResponse, format: JsonNumberFormat) -> WebResponse
+    where
+        S: Stream<Item = Result<T, E>> + 'static,
+        T: Serialize,
+        E: Into<BodyError>,
+    {
+        res.headers_mut().entry(CONTENT_TYPE).or_insert(NDJSON);
+        // flush hint set by prior responder takes precedence.
+        res.extensions_mut().get_or_insert(Flush::Item);
+        let stream = LineStream {
+            stream: self.0,
+            format,
+            buf: BytesMut::new(),
+        };
+        res.map(|_| ResponseBody::box_stream(stream))
+    }
+}
+
+pin_project! {
+    struct LineStream<S> {
+        #[pin]
+        stream: S,
+        format: JsonNumberFormat,
+        buf: BytesMut,
+    }
+}
+
+impl<S, T, E> Stream for LineStream<S>
+where
+    S: Stream<Item = Result<T, E>>,
+    T: Serialize,
+    E: Into<BodyError>,
+{
+    type Item = Result<Bytes, BodyError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+        let item = match ready!(this.stream.poll_next(cx)) {
+            Some(Ok(item)) => item,
+            Some(Err(e)) => return Poll::Ready(Some(Err(e.into()))),
+            None => return Poll::Ready(None),
+        };
+        let res = this.format.to_writer(BufMutWriter(&mut *this.buf), &item).map(|_| {
+            this.buf.extend_from_slice(b"\n");
+            this.buf.split().freeze()
+        });
+        Poll::Ready(Some(res.map_err(Into::into)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.stream.size_hint()
+    }
+}
+
+/// format of error response produced by [serde_json::Error].
+///
+/// By default a blank "400 Bad Request" response is produced and no detail of the error is exposed to client.
+/// [JsonErrorFormat::Verbose] can be opted in by attaching it to request extensions with
+/// [Extension](crate::middleware::Extension) middleware. When enabled the response body is a json object
+/// containing the error message, it's category and the line and column where deserialization failed.
+/// Verbose format is meant for development and it can leak internal detail of data types to client.
+///
+/// # Examples
+/// ```rust
+/// # use xitca_web::{
+/// #   handler::{handler_service, json::{Json, JsonErrorFormat}},
+/// #   middleware::Extension,
+/// #   App, WebContext
+/// # };
+/// async fn handler(Json(_): Json<Vec<u8>>) -> &'static str {
+///     "done"
+/// }
+///
+/// App::new()
+///     .at("/", handler_service(handler))
+///     # .at("/infer", handler_service(|_: &WebContext<'_>| async { "used for infer type" }))
+///     // expose detail of json error to client.
+///     .enclosed(Extension::new(JsonErrorFormat::Verbose));
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum JsonErrorFormat {
+    /// blank response without body.
+    #[default]
+    Blank,
+    /// json object response body with detail of error.
+    Verbose,
+}
+
+/// format of number for [Json] and [LazyJson] extractors and [Json] responder.
+///
+/// Javascript number can not represent integer outside of `±(2^53 - 1)` without losing precision and 64 bit ids
+/// generated by server are often corrupted by javascript client. [JsonNumberFormat::BigIntAsString] can be opted
+/// in by attaching it to request extensions with [Extension](crate::middleware::Extension) middleware.
+///
+/// Number precision of `serde_json::Value` and `serde_json::Number` is limited to 64 bit integer and f64 by default.
+/// `json-arbitrary-precision` feature enables arbitrary precision number and they are handled the same as integer
+/// types by [JsonNumberFormat::BigIntAsString].
+///
+/// # Note
+/// [Json] responder can only observe the format when it's producing the response. e.g. `(Json(_), StatusCode)`
+/// respects the format while `(StatusCode, Json(_))` always uses [JsonNumberFormat::Number].
+///
+/// # Examples
+/// ```rust
+/// # use xitca_web::{
+/// #   handler::{handler_service, json::{Json, JsonNumberFormat}},
+/// #   middleware::Extension,
+/// #   App, WebContext
+/// # };
+/// // id larger than 2^53 - 1 is responded as json string. it can be extracted from both json number and string.
+/// async fn handler(Json(id): Json<u64>) -> Json<u64> {
+///     Json(id + 1)
+/// }
+///
+/// App::new()
+///     .at("/", handler_service(handler))
+///     # .at("/infer", handler_service(|_: &WebContext<'_>| async { "used for infer type" }))
+///     .enclosed(Extension::new(JsonNumberFormat::BigIntAsString));
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum JsonNumberFormat {
+    /// number is always written and read as json number.
+    #[default]
+    Number,
+    /// integer outside of javascript safe range is written as json string. Integer types accept both json number
+    /// and string containing integer when extracting.
+    BigIntAsString,
+}
+
+impl JsonNumberFormat {
+    fn from_extensions(ext: &crate::http::Extensions) -> Self {
+        ext.get::<Self>().copied().unwrap_or_default()
+    }
+
+    fn deserialize<'de, T>(self, bytes: &'de [u8]) -> Result<T, serde_json::Error>
+    where
+        T: Deserialize<'de>,
+    {
+        match self {
+            Self::Number => serde_json::from_slice(bytes),
+            Self::BigIntAsString => {
+                let mut de = serde_json::Deserializer::from_slice(bytes);
+                let value = T::deserialize(number::Lenient(&mut de))?;
+                de.end()?;
+                Ok(value)
+            }
+        }
+    }
+
+    fn to_writer<W, T>(self, writer: W, value: &T) -> Result<(), serde_json::Error>
+    where
+        W: std::io::Write,
+        T: ?Sized + Serialize,
+    {
+        match self {
+            Self::Number => serde_json::to_writer(writer, value),
+            Self::BigIntAsString => {
+                let mut ser = serde_json::Serializer::with_formatter(writer, number::SafeIntFormatter::default());
+                value.serialize(&mut ser)
+            }
+        }
+    }
+}
+
 error_from_service!(serde_json::Error);
-forward_blank_bad_request!(serde_json::Error);
+
+impl<'r, C, B> Service<WebContext<'r, C, B>> for serde_json::Error {
+    type Response = WebResponse;
+    type Error = Infallible;
+
+    async fn call(&self, ctx: WebContext<'r, C, B>) -> Result<Self::Response, Self::Error> {
+        let format = ctx
+            .req()
+            .extensions()
+            .get::<JsonErrorFormat>()
+            .copied()
+            .unwrap_or_default();
+        match format {
+            JsonErrorFormat::Blank => StatusCode::BAD_REQUEST.call(ctx).await,
+            JsonErrorFormat::Verbose => {
+                let category = match self.classify() {
+                    serde_json::error::Category::Io => "io",
+                    serde_json::error::Category::Syntax => "syntax",
+                    serde_json::error::Category::Data => "data",
+                    serde_json::error::Category::Eof => "eof",
+                };
+                let body = serde_json::json!({
+                    "message": self.to_string(),
+                    "category": category,
+                    "line": self.line(),
+                    "column": self.column(),
+                });
+                let mut res = ctx.into_response(Bytes::from(body.to_string()));
+                *res.status_mut() = StatusCode::BAD_REQUEST;
+                res.headers_mut().insert(CONTENT_TYPE, JSON);
+                Ok(res)
+            }
+        }
+    }
+}
 
 impl<T> PathGen for Json<T> {}
 
@@ -226,6 +501,7 @@ where
 
 #[cfg(test)]
 mod test {
+    use futures_util::stream;
     use xitca_unsafe_collection::futures::NowOrPanic;
 
     use crate::{
@@ -237,7 +513,7 @@ mod test {
 
     use super::*;
 
-    #[derive(serde::Deserialize, serde::Serialize, Clone)]
+    #[derive(serde::Deserialize, serde::Serialize, Clone, Debug)]
     struct Gacha<'a> {
         credit_card: &'a str,
     }
@@ -284,4 +560,122 @@ mod test {
             .unwrap();
         assert_eq!(res.status().as_u16(), 200);
     }
+
+    #[test]
+    fn error_format() {
+        let err = serde_json::from_str::<Gacha<'_>>("{\"credit_card\": 996}").unwrap_err();
+
+        let mut ctx = WebContext::new_test(&());
+        let res = err.call(ctx.as_web_ctx()).now_or_panic().unwrap();
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+        assert!(res.headers().get(CONTENT_TYPE).is_none());
+
+        let mut ctx = WebContext::new_test(&());
+        let mut ctx = ctx.as_web_ctx();
+        ctx.req_mut().extensions_mut().insert(JsonErrorFormat::Verbose);
+        let res = err.call(ctx).now_or_panic().unwrap();
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(res.headers().get(CONTENT_TYPE).unwrap(), JSON);
+
+        let body = collect_string_body(res.into_body()).now_or_panic().unwrap();
+        let value = serde_json::from_str::<serde_json::Value>(&body).unwrap();
+        assert_eq!(value["category"], "data");
+        assert_eq!(value["line"], 1);
+    }
+
+    #[test]
+    fn number_format() {
+        #[derive(serde::Deserialize, serde::Serialize, Debug, PartialEq)]
+        struct Ids {
+            id: u64,
+            neg: i64,
+            small: i32,
+            list: Vec<u64>,
+            map: std::collections::BTreeMap<u64, u64>,
+            opt: Option<u64>,
+        }
+
+        let ids = Ids {
+            id: 9_007_199_254_740_993,
+            neg: -9_007_199_254_740_993,
+            small: 1,
+            list: vec![9_007_199_254_740_991, u64::MAX],
+            map: [(u64::MAX, 2)].into(),
+            opt: Some(9_007_199_254_740_992),
+        };
+
+        let format = JsonNumberFormat::BigIntAsString;
+
+        let mut buf = Vec::new();
+        format.to_writer(&mut buf, &ids).unwrap();
+        let json = String::from_utf8(buf).unwrap();
+        assert_eq!(
+            json,
+            r#"{"id":"9007199254740993","neg":"-9007199254740993","small":1,"list":[9007199254740991,"18446744073709551615"],"map":{"18446744073709551615":2},"opt":"9007199254740992"}"#
+        );
+
+        assert_eq!(format.deserialize::<Ids>(json.as_bytes()).unwrap(), ids);
+        assert!(JsonNumberFormat::Number.deserialize::<Ids>(json.as_bytes()).is_err());
+
+        let json = serde_json::to_string(&ids).unwrap();
+        assert_eq!(format.deserialize::<Ids>(json.as_bytes()).unwrap(), ids);
+
+        assert!(format.deserialize::<u8>(br#""256""#).is_err());
+        assert!(format.deserialize::<u64>(br#""abc""#).is_err());
+
+        let big = u64::MAX as u128 + 1;
+        assert_eq!(format.deserialize::<u128>(b"18446744073709551616").unwrap(), big);
+        assert_eq!(format.deserialize::<u128>(br#""18446744073709551616""#).unwrap(), big);
+        assert_eq!(
+            format
+                .deserialize::<u128>(b"340282366920938463463374607431768211455")
+                .unwrap(),
+            u128::MAX
+        );
+        assert_eq!(
+            format.deserialize::<i128>(b"-18446744073709551617").unwrap(),
+            -(big as i128) - 1
+        );
+        assert_eq!(
+            format.deserialize::<i128>(br#""-18446744073709551617""#).unwrap(),
+            -(big as i128) - 1
+        );
+        assert_eq!(
+            format
+                .deserialize::<Vec<u128>>(br#"[1,"18446744073709551616"]"#)
+                .unwrap(),
+            [1, big]
+        );
+        assert!(format.deserialize::<u128>(b"1.5").is_err());
+
+        let mut ctx = WebContext::new_test(&());
+        let mut ctx = ctx.as_web_ctx();
+
+        let body = r#""18446744073709551615""#;
+        ctx.req_mut().headers_mut().insert(CONTENT_TYPE, JSON);
+        ctx.req_mut().headers_mut().insert(CONTENT_LENGTH, body.len().into());
+        ctx.req_mut().extensions_mut().insert(format);
+        *ctx.body_borrow_mut() = body.as_bytes().into();
+
+        let Json(id) = <Json<u64>>::from_request(&ctx).now_or_panic().unwrap();
+        assert_eq!(id, u64::MAX);
+
+        let res = Json(id).respond(ctx).now_or_panic().unwrap();
+        let body = collect_string_body(res.into_body()).now_or_panic().unwrap();
+        assert_eq!(body, r#""18446744073709551615""#);
+    }
+
+    #[test]
+    fn json_stream() {
+        let mut ctx = WebContext::new_test(&());
+        let mut ctx = ctx.as_web_ctx();
+        ctx.req_mut().extensions_mut().insert(JsonNumberFormat::BigIntAsString);
+
+        let items = [1, u64::MAX].map(Ok::<_, Infallible>);
+        let res = JsonStream(stream::iter(items)).respond(ctx).now_or_panic().unwrap();
+        assert_eq!(res.headers().get(CONTENT_TYPE).unwrap(), "application/x-ndjson");
+        assert_eq!(res.extensions().get::<Flush>(), Some(&Flush::Item));
+        let body = collect_string_body(res.into_body()).now_or_panic().unwrap();
+        assert_eq!(body, "1\n\"18446744073709551615\"\n");
+    }
 }
@@ -0,0 +1,450 @@
+// serializer formatter and deserializer adapter for JsonNumberFormat::BigIntAsString.
+
+use core::fmt;
+
+use std::io;
+
+use serde::de::{
+    self, value::MapAccessDeserializer, Deserialize, DeserializeSeed, Deserializer, EnumAccess, MapAccess, SeqAccess,
+    VariantAccess, Visitor,
+};
+use serde_json::{
+    ser::{CompactFormatter, Formatter},
+    value::RawValue,
+};
+
+// max integer can be represented by javascript number without losing precision. (2^53 - 1)
+const MAX_SAFE_INTEGER: u64 = 9_007_199_254_740_991;
+
+const fn is_safe_i128(n: i128) -> bool {
+    n.unsigned_abs() <= MAX_SAFE_INTEGER as u128
+}
+
+// formatter writing integer outside of safe range as json string.
+#[derive(Default)]
+pub(super) struct SafeIntFormatter {
+    // integer map key is already written inside string by serde_json.
+    in_string: bool,
+}
+
+impl SafeIntFormatter {
+    fn write_int<W, I>(&mut self, writer: &mut W, safe: bool, value: I) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+        I: fmt::Display,
+    {
+        if safe || self.in_string {
+            write!(writer, "{value}")
+        } else {
+            write!(writer, "\"{value}\"")
+        }
+    }
+}
+
+impl Formatter for SafeIntFormatter {
+    fn write_i64<W: ?Sized + io::Write>(&mut self, writer: &mut W, value: i64) -> io::Result<()> {
+        self.write_int(writer, is_safe_i128(value as i128), value)
+    }
+
+    fn write_u64<W: ?Sized + io::Write>(&mut self, writer: &mut W, value: u64) -> io::Result<()> {
+        self.write_int(writer, value <= MAX_SAFE_INTEGER, value)
+    }
+
+    fn write_i128<W: ?Sized + io::Write>(&mut self, writer: &mut W, value: i128) -> io::Result<()> {
+        self.write_int(writer, is_safe_i128(value), value)
+    }
+
+    fn write_u128<W: ?Sized + io::Write>(&mut self, writer: &mut W, value: u128) -> io::Result<()> {
+        self.write_int(writer, value <= MAX_SAFE_INTEGER as u128, value)
+    }
+
+    // number of arbitrary precision. only integer is written as string.
+    fn write_number_str<W: ?Sized + io::Write>(&mut self, writer: &mut W, value: &str) -> io::Result<()> {
+        let is_int = !value.contains(['.', 'e', 'E']);
+        let safe = !is_int || value.parse::<i128>().is_ok_and(is_safe_i128);
+        self.write_int(writer, safe, value)
+    }
+
+    fn begin_string<W: ?Sized + io::Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.in_string = true;
+        CompactFormatter.begin_string(writer)
+    }
+
+    fn end_string<W: ?Sized + io::Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.in_string = false;
+        CompactFormatter.end_string(writer)
+    }
+}
+
+// deserializer adapter accepting json string for integer types.
+pub(super) struct Lenient<D>(pub(super) D);
+
+macro_rules! int_methods {
+    ($($method: ident),*) => {
+        $(
+            fn $method<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+            where
+                V: Visitor<'de>,
+            {
+                self.0.deserialize_any(IntVisitor(visitor))
+            }
+        )*
+    };
+}
+
+macro_rules! forward_methods {
+    ($($method: ident $(($($arg: ident: $ty: ty),*))?),*) => {
+        $(
+            fn $method<V>(self, $($($arg: $ty,)*)? visitor: V) -> Result<V::Value, Self::Error>
+            where
+                V: Visitor<'de>,
+            {
+                self.0.$method($($($arg,)*)? Wrap(visitor))
+            }
+        )*
+    };
+}
+
+impl<'de, D> Deserializer<'de> for Lenient<D>
+where
+    D: Deserializer<'de>,
+{
+    type Error = D::Error;
+
+    int_methods!(
+        deserialize_i8,
+        deserialize_i16,
+        deserialize_i32,
+        deserialize_i64,
+        deserialize_u8,
+        deserialize_u16,
+        deserialize_u32,
+        deserialize_u64
+    );
+
+    fn deserialize_i128<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        big_int(self.0, visitor)
+    }
+
+    fn deserialize_u128<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        big_int(self.0, visitor)
+    }
+
+    forward_methods!(
+        deserialize_any,
+        deserialize_bool,
+        deserialize_f32,
+        deserialize_f64,
+        deserialize_char,
+        deserialize_str,
+        deserialize_string,
+        deserialize_bytes,
+        deserialize_byte_buf,
+        deserialize_option,
+        deserialize_unit,
+        deserialize_unit_struct(name: &'static str),
+        deserialize_newtype_struct(name: &'static str),
+        deserialize_seq,
+        deserialize_tuple(len: usize),
+        deserialize_tuple_struct(name: &'static str, len: usize),
+        deserialize_map,
+        deserialize_struct(name: &'static str, fields: &'static [&'static str]),
+        deserialize_enum(name: &'static str, variants: &'static [&'static str]),
+        deserialize_identifier,
+        deserialize_ignored_any
+    );
+
+    fn is_human_readable(&self) -> bool {
+        self.0.is_human_readable()
+    }
+}
+
+// serde_json reads json number outside of 64 bit range as f64 through deserialize_any. buffer the raw json text
+// and parse it for exact value of 128 bit integer.
+fn big_int<'de, D, V>(de: D, visitor: V) -> Result<V::Value, D::Error>
+where
+    D: Deserializer<'de>,
+    V: Visitor<'de>,
+{
+    let raw = Box::<RawValue>::deserialize(de)?;
+    let raw = raw.get();
+    let int = raw.strip_prefix('"').and_then(|s| s.strip_suffix('"')).unwrap_or(raw);
+    if int.parse::<i128>().is_ok() || int.parse::<u128>().is_ok() {
+        IntVisitor(visitor).visit_str(int)
+    } else {
+        // not an integer. let serde_json produce the type error.
+        serde_json::from_str::<serde_json::Value>(raw)
+            .and_then(|value| value.deserialize_any(visitor))
+            .map_err(de::Error::custom)
+    }
+}
+
+// visitor parsing integer from string.
+struct IntVisitor<V>(V);
+
+macro_rules! forward_visit {
+    ($($method: ident($ty: ty)),*) => {
+        $(
+            fn $method<E>(self, v: $ty) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                self.0.$method(v)
+            }
+        )*
+    };
+}
+
+impl<'de, V> Visitor<'de> for IntVisitor<V>
+where
+    V: Visitor<'de>,
+{
+    type Value = V::Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.expecting(f)
+    }
+
+    forward_visit!(
+        visit_bool(bool),
+        visit_i8(i8),
+        visit_i16(i16),
+        visit_i32(i32),
+        visit_i64(i64),
+        visit_i128(i128),
+        visit_u8(u8),
+        visit_u16(u16),
+        visit_u32(u32),
+        visit_u64(u64),
+        visit_u128(u128),
+        visit_f32(f32),
+        visit_f64(f64)
+    );
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        match v.parse::<i64>() {
+            Ok(n) => self.0.visit_i64(n),
+            Err(_) => match v.parse::<u64>() {
+                Ok(n) => self.0.visit_u64(n),
+                Err(_) => match v.parse::<i128>() {
+                    Ok(n) => self.0.visit_i128(n),
+                    Err(_) => match v.parse::<u128>() {
+                        Ok(n) => self.0.visit_u128(n),
+                        Err(_) => self.0.visit_str(v),
+                    },
+                },
+            },
+        }
+    }
+
+    // number of arbitrary precision is passed as map by serde_json.
+    fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let number = serde_json::Number::deserialize(MapAccessDeserializer::new(map))?;
+        self.visit_str(&number.to_string())
+    }
+}
+
+// visitor wrapping nested deserializers with Lenient.
+struct Wrap<V>(V);
+
+impl<'de, V> Visitor<'de> for Wrap<V>
+where
+    V: Visitor<'de>,
+{
+    type Value = V::Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.expecting(f)
+    }
+
+    forward_visit!(
+        visit_bool(bool),
+        visit_i8(i8),
+        visit_i16(i16),
+        visit_i32(i32),
+        visit_i64(i64),
+        visit_i128(i128),
+        visit_u8(u8),
+        visit_u16(u16),
+        visit_u32(u32),
+        visit_u64(u64),
+        visit_u128(u128),
+        visit_f32(f32),
+        visit_f64(f64),
+        visit_char(char),
+        visit_str(&str),
+        visit_borrowed_str(&'de str),
+        visit_string(String),
+        visit_bytes(&[u8]),
+        visit_borrowed_bytes(&'de [u8]),
+        visit_byte_buf(Vec<u8>)
+    );
+
+    fn visit_none<E>(self) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        self.0.visit_none()
+    }
+
+    fn visit_unit<E>(self) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        self.0.visit_unit()
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        self.0.visit_some(Lenient(deserializer))
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        self.0.visit_newtype_struct(Lenient(deserializer))
+    }
+
+    fn visit_seq<A>(self, seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        self.0.visit_seq(Wrap(seq))
+    }
+
+    fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        self.0.visit_map(Wrap(map))
+    }
+
+    fn visit_enum<A>(self, data: A) -> Result<Self::Value, A::Error>
+    where
+        A: EnumAccess<'de>,
+    {
+        self.0.visit_enum(Wrap(data))
+    }
+}
+
+impl<'de, A> SeqAccess<'de> for Wrap<A>
+where
+    A: SeqAccess<'de>,
+{
+    type Error = A::Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        self.0.next_element_seed(Seed(seed))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.0.size_hint()
+    }
+}
+
+impl<'de, A> MapAccess<'de> for Wrap<A>
+where
+    A: MapAccess<'de>,
+{
+    type Error = A::Error;
+
+    // serde_json parses integer map key from string by itself.
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        self.0.next_key_seed(seed)
+    }
+
+    fn next_value_seed<T>(&mut self, seed: T) -> Result<T::Value, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        self.0.next_value_seed(Seed(seed))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.0.size_hint()
+    }
+}
+
+impl<'de, A> EnumAccess<'de> for Wrap<A>
+where
+    A: EnumAccess<'de>,
+{
+    type Error = A::Error;
+    type Variant = Wrap<A::Variant>;
+
+    fn variant_seed<T>(self, seed: T) -> Result<(T::Value, Self::Variant), Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        self.0.variant_seed(seed).map(|(value, variant)| (value, Wrap(variant)))
+    }
+}
+
+impl<'de, A> VariantAccess<'de> for Wrap<A>
+where
+    A: VariantAccess<'de>,
+{
+    type Error = A::Error;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        self.0.unit_variant()
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        self.0.newtype_variant_seed(Seed(seed))
+    }
+
+    fn tuple_variant<V>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.0.tuple_variant(len, Wrap(visitor))
+    }
+
+    fn struct_variant<V>(self, fields: &'static [&'static str], visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.0.struct_variant(fields, Wrap(visitor))
+    }
+}
+
+struct Seed<S>(S);
+
+impl<'de, S> DeserializeSeed<'de> for Seed<S>
+where
+    S: DeserializeSeed<'de>,
+{
+    type Value = S::Value;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        self.0.deserialize(Lenient(deserializer))
+    }
+}
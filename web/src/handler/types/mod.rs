@@ -1,12 +1,17 @@
 pub mod body;
+pub mod client;
+pub mod config;
 pub mod extension;
 pub mod header;
 pub mod html;
 pub mod path;
+pub mod precondition;
 pub mod redirect;
+pub mod sse;
 pub mod state;
 pub mod text;
 pub mod uri;
+pub mod validate;
 
 #[cfg(feature = "params")]
 pub mod params;
@@ -17,14 +22,35 @@ pub mod query;
 #[cfg(feature = "urlencoded")]
 pub mod form;
 
+#[cfg(feature = "urlencoded")]
+mod urlencoded;
+
+#[cfg(all(feature = "params", feature = "urlencoded"))]
+pub mod path_query;
+
 #[cfg(feature = "json")]
 pub mod json;
 
+#[cfg(feature = "csv")]
+pub mod csv;
+
+#[cfg(all(feature = "json", feature = "urlencoded"))]
+pub mod any_body;
+
+#[cfg(feature = "grpc")]
+pub mod grpc;
+
 #[cfg(feature = "cookie")]
 pub mod cookie;
 
 #[cfg(feature = "multipart")]
 pub mod multipart;
 
+#[cfg(feature = "file-raw")]
+pub mod file;
+
 #[cfg(feature = "websocket")]
 pub mod websocket;
+
+#[cfg(feature = "http1")]
+pub mod upgrade;
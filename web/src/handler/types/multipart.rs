@@ -1,10 +1,38 @@
+//! type extractor and responder for multipart body.
+
+use core::{
+    convert::Infallible,
+    fmt::Write,
+    hash::BuildHasher,
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+
+use std::collections::{hash_map::RandomState, VecDeque};
+
+use futures_core::stream::Stream;
+
 use crate::{
-    body::{BodyStream, RequestBody},
+    body::{BodyStream, BoxBody, RequestBody, ResponseBody},
+    bytes::{Bytes, BytesMut},
     context::WebContext,
-    error::{forward_blank_bad_request, Error},
-    handler::FromRequest,
+    error::{forward_blank_bad_request, BodyError, Error},
+    handler::{FromRequest, Responder},
+    http::{
+        header::{HeaderMap, HeaderName, HeaderValue, CONTENT_TYPE},
+        WebResponse,
+    },
 };
 
+#[cfg(feature = "serde")]
+mod form;
+mod sniff;
+
+#[cfg(feature = "serde")]
+pub use form::{MultipartForm, MultipartFormError, UploadedFile, DEFAULT_LIMIT};
+
+pub use sniff::{ContentSniff, ContentSniffError, FileType, SniffedField};
+
 pub type Multipart<B = RequestBody> = http_multipart::Multipart<B>;
 
 impl<'a, 'r, C, B> FromRequest<'a, WebContext<'r, C, B>> for Multipart<B>
@@ -22,16 +50,256 @@ where
 
 forward_blank_bad_request!(http_multipart::MultipartError);
 
+/// responder producing multipart response body. e.g. `multipart/mixed` for batch api response or
+/// `multipart/byteranges` for multiple ranges of a file.
+///
+/// A random boundary is generated for every response and it's set as `boundary` parameter of `Content-Type`
+/// header. The boundary is regenerated when it's found in any in memory part. Streaming part can not be checked
+/// ahead of time and the randomness of boundary(128 bits) makes collision with it's content practically impossible.
+///
+/// Parts are streamed in order and streaming part's body is not buffered.
+///
+/// # Examples
+/// ```rust
+/// # use xitca_web::{
+/// #     handler::{handler_service, multipart::{MultipartResponse, Part}},
+/// #     http::header::{HeaderValue, CONTENT_RANGE, CONTENT_TYPE},
+/// #     App, WebContext,
+/// # };
+/// async fn handler() -> MultipartResponse {
+///     MultipartResponse::byteranges()
+///         .part(
+///             Part::new("hello")
+///                 .header(CONTENT_TYPE, HeaderValue::from_static("text/plain"))
+///                 .header(CONTENT_RANGE, HeaderValue::from_static("bytes 0-4/11")),
+///         )
+///         .part(
+///             Part::new("world")
+///                 .header(CONTENT_TYPE, HeaderValue::from_static("text/plain"))
+///                 .header(CONTENT_RANGE, HeaderValue::from_static("bytes 6-10/11")),
+///         )
+/// }
+///
+/// App::new()
+///     .at("/", handler_service(handler))
+///     # .at("/infer", handler_service(|_: &WebContext<'_>| async{ "infer type" }))
+///     ;
+/// ```
+pub struct MultipartResponse {
+    subtype: &'static str,
+    parts: Vec<Part>,
+}
+
+impl MultipartResponse {
+    /// construct response with `multipart/mixed` content type.
+    pub fn mixed() -> Self {
+        Self::new("mixed")
+    }
+
+    /// construct response with `multipart/byteranges` content type.
+    pub fn byteranges() -> Self {
+        Self::new("byteranges")
+    }
+
+    fn new(subtype: &'static str) -> Self {
+        Self {
+            subtype,
+            parts: Vec::new(),
+        }
+    }
+
+    /// append a part to response.
+    pub fn part(mut self, part: Part) -> Self {
+        self.parts.push(part);
+        self
+    }
+
+    fn into_body(self) -> (HeaderValue, ResponseBody) {
+        let boundary = loop {
+            let boundary = boundary();
+            let collide = self.parts.iter().any(|part| match part.body {
+                PartBody::Bytes(ref bytes) => bytes.windows(boundary.len()).any(|w| w == boundary.as_bytes()),
+                PartBody::Stream(_) => false,
+            });
+            if !collide {
+                break boundary;
+            }
+        };
+
+        let content_type = HeaderValue::try_from(format!("multipart/{}; boundary={boundary}", self.subtype))
+            .expect("boundary must be valid header value");
+
+        let is_stream = self.parts.iter().any(|part| matches!(part.body, PartBody::Stream(_)));
+
+        let mut body = MultipartBody {
+            boundary,
+            parts: self.parts.into(),
+            current: None,
+            first: true,
+            done: false,
+        };
+
+        let body = if is_stream {
+            ResponseBody::box_stream(body)
+        } else {
+            // every part is in memory and response body can be sent with content-length header.
+            let mut buf = BytesMut::new();
+            while let Some(chunk) = body.next_head() {
+                buf.extend_from_slice(&chunk);
+            }
+            ResponseBody::bytes(buf.freeze())
+        };
+
+        (content_type, body)
+    }
+}
+
+impl<'r, C, B> Responder<WebContext<'r, C, B>> for MultipartResponse {
+    type Response = WebResponse;
+    type Error = Infallible;
+
+    async fn respond(self, ctx: WebContext<'r, C, B>) -> Result<Self::Response, Self::Error> {
+        let res = ctx.into_response(ResponseBody::empty());
+        Responder::<WebContext<'r, C, B>>::map(self, res)
+    }
+
+    fn map(self, res: Self::Response) -> Result<Self::Response, Self::Error> {
+        let (content_type, body) = self.into_body();
+        let mut res = res.map(|_| body);
+        res.headers_mut().insert(CONTENT_TYPE, content_type);
+        Ok(res)
+    }
+}
+
+/// a single part of [`MultipartResponse`].
+pub struct Part {
+    headers: HeaderMap,
+    body: PartBody,
+}
+
+enum PartBody {
+    Bytes(Bytes),
+    Stream(BoxBody),
+}
+
+impl Part {
+    /// construct part with in memory body.
+    pub fn new(body: impl Into<Bytes>) -> Self {
+        Self {
+            headers: HeaderMap::new(),
+            body: PartBody::Bytes(body.into()),
+        }
+    }
+
+    /// construct part with streaming body.
+    pub fn stream<S, T, E>(body: S) -> Self
+    where
+        S: Stream<Item = Result<T, E>> + 'static,
+        T: Into<Bytes>,
+        E: Into<BodyError>,
+    {
+        Self {
+            headers: HeaderMap::new(),
+            body: PartBody::Stream(BoxBody::new(body)),
+        }
+    }
+
+    /// append header to part.
+    pub fn header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.headers.append(name, value);
+        self
+    }
+}
+
+// 16 bytes of random data encoded in hex.
+fn boundary() -> String {
+    let state = RandomState::new();
+    let mut boundary = String::with_capacity(32);
+    for i in 0..2u8 {
+        write!(boundary, "{:016x}", state.hash_one(i)).unwrap();
+    }
+    boundary
+}
+
+struct MultipartBody {
+    boundary: String,
+    parts: VecDeque<Part>,
+    current: Option<BoxBody>,
+    first: bool,
+    done: bool,
+}
+
+impl MultipartBody {
+    // produce boundary and headers of next part. in memory body of part is included. streaming body is set as
+    // current body and it must be drained before calling this method again.
+    fn next_head(&mut self) -> Option<Bytes> {
+        if self.done {
+            return None;
+        }
+
+        let mut buf = BytesMut::new();
+
+        if !self.first {
+            buf.extend_from_slice(b"\r\n");
+        }
+        self.first = false;
+
+        buf.extend_from_slice(b"--");
+        buf.extend_from_slice(self.boundary.as_bytes());
+
+        match self.parts.pop_front() {
+            Some(part) => {
+                buf.extend_from_slice(b"\r\n");
+                for (name, value) in part.headers.iter() {
+                    buf.extend_from_slice(name.as_str().as_bytes());
+                    buf.extend_from_slice(b": ");
+                    buf.extend_from_slice(value.as_bytes());
+                    buf.extend_from_slice(b"\r\n");
+                }
+                buf.extend_from_slice(b"\r\n");
+                match part.body {
+                    PartBody::Bytes(bytes) => buf.extend_from_slice(&bytes),
+                    PartBody::Stream(stream) => self.current = Some(stream),
+                }
+            }
+            None => {
+                buf.extend_from_slice(b"--\r\n");
+                self.done = true;
+            }
+        }
+
+        Some(buf.freeze())
+    }
+}
+
+impl Stream for MultipartBody {
+    type Item = Result<Bytes, BodyError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        if let Some(stream) = this.current.as_mut() {
+            match ready!(Pin::new(stream).poll_next(cx)) {
+                Some(res) => return Poll::Ready(Some(res)),
+                None => this.current = None,
+            }
+        }
+
+        Poll::Ready(this.next_head().map(Ok))
+    }
+}
+
 #[cfg(test)]
 mod test {
     use core::pin::pin;
 
+    use xitca_http::body::BodySize;
     use xitca_unsafe_collection::futures::NowOrPanic;
 
     use crate::{
         handler::handler_service,
         http::{
-            header::{HeaderValue, CONTENT_TYPE, TRANSFER_ENCODING},
+            header::{HeaderValue, CONTENT_DISPOSITION, CONTENT_TYPE, TRANSFER_ENCODING},
             request, Method, RequestExt,
         },
         route::post,
@@ -109,4 +377,67 @@ mod test {
 
         assert_eq!(body, b"testtestdata");
     }
+
+    #[test]
+    fn response() {
+        const FOO: &str = "form-data; name=\"file\"; filename=\"foo.txt\"";
+        const BAR: &str = "form-data; name=\"file\"; filename=\"bar.txt\"";
+
+        let service = App::new()
+            .at("/", post(handler_service(handler)))
+            .finish()
+            .call(())
+            .now_or_panic()
+            .unwrap();
+
+        let stream = futures_util::stream::iter([Ok::<_, Infallible>("test"), Ok("data")]);
+
+        for (res, is_stream) in [
+            (
+                MultipartResponse::mixed()
+                    .part(Part::new("test").header(CONTENT_DISPOSITION, HeaderValue::from_static(FOO)))
+                    .part(Part::new("testdata").header(CONTENT_DISPOSITION, HeaderValue::from_static(BAR))),
+                false,
+            ),
+            (
+                MultipartResponse::mixed()
+                    .part(Part::new("test").header(CONTENT_DISPOSITION, HeaderValue::from_static(FOO)))
+                    .part(Part::stream(stream).header(CONTENT_DISPOSITION, HeaderValue::from_static(BAR))),
+                true,
+            ),
+        ] {
+            let mut ctx = WebContext::new_test(());
+            let res = res.respond(ctx.as_web_ctx()).now_or_panic().unwrap();
+
+            let content_type = res.headers().get(CONTENT_TYPE).unwrap().clone();
+            let boundary = content_type
+                .to_str()
+                .unwrap()
+                .strip_prefix("multipart/mixed; boundary=")
+                .unwrap()
+                .to_owned();
+            assert_eq!(boundary.len(), 32);
+            assert_eq!(BodySize::from_stream(res.body()) == BodySize::Stream, is_stream);
+
+            let body = collect_body(res.into_body()).now_or_panic().unwrap();
+            assert!(body.starts_with(format!("--{boundary}\r\n").as_bytes()));
+            assert!(body.ends_with(format!("\r\n--{boundary}--\r\n").as_bytes()));
+
+            // response body can be parsed by multipart extractor.
+            let req = request::Builder::default()
+                .method(Method::POST)
+                .header(CONTENT_TYPE, content_type)
+                .body(RequestExt::default().map_body(|_: ()| body.into()))
+                .unwrap();
+
+            let res = service.call(req).now_or_panic().unwrap();
+            let body = collect_body(res.into_body()).now_or_panic().unwrap();
+            assert_eq!(body, b"testtestdata");
+        }
+    }
+
+    #[test]
+    fn boundary_unique() {
+        assert_ne!(boundary(), boundary());
+    }
 }
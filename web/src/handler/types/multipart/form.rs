@@ -0,0 +1,859 @@
+use core::{
+    convert::Infallible,
+    fmt,
+    ops::{Deref, DerefMut},
+    pin::pin,
+};
+
+use std::{error, vec};
+
+use serde::de::{self, Deserialize, DeserializeOwned, DeserializeSeed, Deserializer, IntoDeserializer, Visitor};
+
+use crate::{
+    body::BodyStream,
+    bytes::Bytes,
+    context::WebContext,
+    error::{error_from_service, BodyOverFlow, Error},
+    handler::FromRequest,
+    http::{const_header_value::TEXT_UTF8, header::CONTENT_TYPE, StatusCode, WebResponse},
+    service::Service,
+};
+
+use super::Multipart;
+
+pub const DEFAULT_LIMIT: usize = 1024 * 1024 * 8;
+
+/// Extract type for multipart form. Named fields of form are mapped into typed struct with [Deserialize] trait.
+/// const generic param LIMIT is for max size of all fields in bytes. Form larger than limit would be treated as
+/// error.
+///
+/// Default limit is [DEFAULT_LIMIT] in bytes.
+///
+/// - text field is deserialized from it's string value. Numbers and booleans are parsed from the string.
+/// - file field(field with `filename` parameter) is deserialized as [UploadedFile]. A file field without file
+///   name and content (what browser sends when no file is chosen) is treated as absent.
+/// - repeated field can be collected into sequence type like `Vec<T>`.
+/// - field of `Option<T>` type is optional. Absent required field produces [MultipartFormError] with
+///   "422 Unprocessable Entity" response.
+/// - max size of a single file field can be set with const generic param of [UploadedFile].
+///
+/// # Examples
+/// ```rust
+/// # use serde::Deserialize;
+/// # use xitca_web::{
+/// #     handler::{handler_service, multipart::{MultipartForm, UploadedFile}},
+/// #     route::post,
+/// #     App, WebContext,
+/// # };
+/// #[derive(Deserialize)]
+/// struct Upload {
+///     title: String,
+///     tag: Option<u32>,
+///     // file can not be larger than 1MB.
+///     avatar: UploadedFile<{ 1024 * 1024 }>,
+///     attachments: Vec<UploadedFile>,
+/// }
+///
+/// async fn handler(MultipartForm(upload): MultipartForm<Upload>) -> String {
+///     format!("{}: {} bytes", upload.title, upload.avatar.size())
+/// }
+///
+/// App::new()
+///     .at("/", post(handler_service(handler)))
+///     # .at("/infer", handler_service(|_: &WebContext<'_>| async{ "infer type" }))
+///     ;
+/// ```
+pub struct MultipartForm<T, const LIMIT: usize = DEFAULT_LIMIT>(pub T);
+
+impl<T, const LIMIT: usize> fmt::Debug for MultipartForm<T, LIMIT>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MultipartForm")
+            .field("value", &self.0)
+            .field("limit", &LIMIT)
+            .finish()
+    }
+}
+
+impl<T, const LIMIT: usize> Deref for MultipartForm<T, LIMIT> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T, const LIMIT: usize> DerefMut for MultipartForm<T, LIMIT> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<'a, 'r, C, B, T, const LIMIT: usize> FromRequest<'a, WebContext<'r, C, B>> for MultipartForm<T, LIMIT>
+where
+    B: BodyStream + Default,
+    T: DeserializeOwned,
+{
+    type Type<'b> = MultipartForm<T, LIMIT>;
+    type Error = Error;
+
+    async fn from_request(ctx: &'a WebContext<'r, C, B>) -> Result<Self, Self::Error> {
+        let multipart = Multipart::<B>::from_request(ctx).await?;
+        let mut multipart = pin!(multipart);
+
+        let mut fields = Fields::default();
+        let mut size = 0;
+
+        while let Some(mut field) = multipart.try_next().await.map_err(Error::from_service)? {
+            let name = field
+                .name()
+                .ok_or_else(|| MultipartFormError::invalid(None, "field name is not found"))?
+                .to_owned();
+            let file_name = field.file_name().map(str::to_owned);
+            let content_type = field
+                .headers()
+                .get(CONTENT_TYPE)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_owned);
+
+            let mut data = Vec::new();
+            while let Some(chunk) = field.try_next().await.map_err(Error::from_service)? {
+                size += chunk.len();
+                if size > LIMIT {
+                    return Err(BodyOverFlow { limit: LIMIT }.into());
+                }
+                data.extend_from_slice(chunk.as_ref());
+            }
+
+            let value = match file_name {
+                // browser sends empty file field when no file is chosen.
+                Some(ref file_name) if file_name.is_empty() && data.is_empty() => continue,
+                Some(_) => Value::File {
+                    file_name,
+                    content_type,
+                    data,
+                },
+                None => match String::from_utf8(data) {
+                    Ok(text) => Value::Text(text),
+                    Err(_) => return Err(MultipartFormError::invalid(Some(name), "text is not valid utf-8").into()),
+                },
+            };
+
+            fields.push(name, value);
+        }
+
+        T::deserialize(FormDeserializer(fields.0))
+            .map(MultipartForm)
+            .map_err(Into::into)
+    }
+}
+
+/// file field of [MultipartForm]. const generic param LIMIT is for max size of the file in bytes. File larger
+/// than limit would be treated as error.
+///
+/// Default limit is [usize::MAX] and file size is only bounded by the limit of [MultipartForm].
+pub struct UploadedFile<const LIMIT: usize = { usize::MAX }> {
+    file_name: Option<String>,
+    content_type: Option<String>,
+    data: Bytes,
+}
+
+impl<const LIMIT: usize> fmt::Debug for UploadedFile<LIMIT> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UploadedFile")
+            .field("file_name", &self.file_name)
+            .field("content_type", &self.content_type)
+            .field("size", &self.data.len())
+            .finish()
+    }
+}
+
+impl<const LIMIT: usize> UploadedFile<LIMIT> {
+    /// file name from `filename` parameter of `Content-Disposition` header of field.
+    pub fn file_name(&self) -> Option<&str> {
+        self.file_name.as_deref()
+    }
+
+    /// value of `Content-Type` header of field.
+    pub fn content_type(&self) -> Option<&str> {
+        self.content_type.as_deref()
+    }
+
+    /// size of file in bytes.
+    pub fn size(&self) -> usize {
+        self.data.len()
+    }
+
+    /// content of file.
+    pub fn data(&self) -> &Bytes {
+        &self.data
+    }
+
+    /// take ownership of file content.
+    pub fn into_data(self) -> Bytes {
+        self.data
+    }
+}
+
+// marker name of UploadedFile used by FormDeserializer to tell file type from other structs.
+const UPLOADED_FILE: &str = "$xitca_web::UploadedFile";
+
+impl<'de, const LIMIT: usize> Deserialize<'de> for UploadedFile<LIMIT> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct FileVisitor<const LIMIT: usize>;
+
+        impl<'de, const LIMIT: usize> Visitor<'de> for FileVisitor<LIMIT> {
+            type Value = UploadedFile<LIMIT>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("file field of multipart form")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: de::SeqAccess<'de>,
+            {
+                let file_name = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(0, &self))?;
+                let content_type = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(1, &self))?;
+                let Data(data) = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(2, &self))?;
+
+                if data.len() > LIMIT {
+                    return Err(de::Error::custom(format_args!(
+                        "file size reached limit: {LIMIT} bytes"
+                    )));
+                }
+
+                Ok(UploadedFile {
+                    file_name,
+                    content_type,
+                    data,
+                })
+            }
+        }
+
+        deserializer.deserialize_struct(UPLOADED_FILE, &["file_name", "content_type", "data"], FileVisitor)
+    }
+}
+
+struct Data(Bytes);
+
+impl<'de> Deserialize<'de> for Data {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct DataVisitor;
+
+        impl Visitor<'_> for DataVisitor {
+            type Value = Data;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("bytes")
+            }
+
+            fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E> {
+                Ok(Data(Bytes::copy_from_slice(v)))
+            }
+
+            fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<Self::Value, E> {
+                Ok(Data(Bytes::from(v)))
+            }
+        }
+
+        deserializer.deserialize_byte_buf(DataVisitor)
+    }
+}
+
+/// error type of [MultipartForm] extractor. produce "422 Unprocessable Entity" response with error message as
+/// body.
+#[derive(Debug)]
+pub struct MultipartFormError {
+    field: Option<String>,
+    kind: ErrorKind,
+}
+
+#[derive(Debug)]
+enum ErrorKind {
+    Missing,
+    Invalid(String),
+}
+
+impl MultipartFormError {
+    fn invalid(field: Option<String>, msg: impl fmt::Display) -> Self {
+        Self {
+            field,
+            kind: ErrorKind::Invalid(msg.to_string()),
+        }
+    }
+
+    fn with_field(mut self, field: &str) -> Self {
+        if self.field.is_none() {
+            self.field = Some(field.to_owned());
+        }
+        self
+    }
+
+    /// name of the field causing error.
+    pub fn field(&self) -> Option<&str> {
+        self.field.as_deref()
+    }
+
+    /// check if error is caused by absent required field.
+    pub fn is_missing(&self) -> bool {
+        matches!(self.kind, ErrorKind::Missing)
+    }
+}
+
+impl fmt::Display for MultipartFormError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.kind, &self.field) {
+            (ErrorKind::Missing, Some(field)) => write!(f, "missing field `{field}`"),
+            (ErrorKind::Missing, None) => f.write_str("missing field"),
+            (ErrorKind::Invalid(msg), Some(field)) => write!(f, "invalid field `{field}`: {msg}"),
+            (ErrorKind::Invalid(msg), None) => write!(f, "invalid multipart form: {msg}"),
+        }
+    }
+}
+
+impl error::Error for MultipartFormError {}
+
+impl de::Error for MultipartFormError {
+    fn custom<T>(msg: T) -> Self
+    where
+        T: fmt::Display,
+    {
+        Self::invalid(None, msg)
+    }
+
+    fn missing_field(field: &'static str) -> Self {
+        Self {
+            field: Some(field.to_owned()),
+            kind: ErrorKind::Missing,
+        }
+    }
+}
+
+error_from_service!(MultipartFormError);
+
+impl<'r, C, B> Service<WebContext<'r, C, B>> for MultipartFormError {
+    type Response = WebResponse;
+    type Error = Infallible;
+
+    async fn call(&self, ctx: WebContext<'r, C, B>) -> Result<Self::Response, Self::Error> {
+        let mut res = ctx.into_response(self.to_string());
+        *res.status_mut() = StatusCode::UNPROCESSABLE_ENTITY;
+        res.headers_mut().insert(CONTENT_TYPE, TEXT_UTF8);
+        Ok(res)
+    }
+}
+
+enum Value {
+    Text(String),
+    File {
+        file_name: Option<String>,
+        content_type: Option<String>,
+        data: Vec<u8>,
+    },
+}
+
+// fields grouped by name in the order of their first appearance.
+#[derive(Default)]
+struct Fields(Vec<(String, Vec<Value>)>);
+
+impl Fields {
+    fn push(&mut self, name: String, value: Value) {
+        match self.0.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, values)) => values.push(value),
+            None => self.0.push((name, vec![value])),
+        }
+    }
+}
+
+type DeResult<T> = Result<T, MultipartFormError>;
+
+struct FormDeserializer(Vec<(String, Vec<Value>)>);
+
+impl<'de> Deserializer<'de> for FormDeserializer {
+    type Error = MultipartFormError;
+
+    fn deserialize_any<V>(self, visitor: V) -> DeResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_map(FieldsAccess {
+            fields: self.0.into_iter(),
+            current: None,
+        })
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+struct FieldsAccess {
+    fields: vec::IntoIter<(String, Vec<Value>)>,
+    current: Option<(String, Vec<Value>)>,
+}
+
+impl<'de> de::MapAccess<'de> for FieldsAccess {
+    type Error = MultipartFormError;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> DeResult<Option<K::Value>>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        match self.fields.next() {
+            Some((name, values)) => {
+                let key = seed.deserialize(name.as_str().into_deserializer())?;
+                self.current = Some((name, values));
+                Ok(Some(key))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> DeResult<V::Value>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let (name, values) = self
+            .current
+            .take()
+            .ok_or_else(|| MultipartFormError::invalid(None, "value is visited before key"))?;
+        seed.deserialize(ValuesDeserializer(values))
+            .map_err(|e| e.with_field(&name))
+    }
+}
+
+// all values of a single field name.
+struct ValuesDeserializer(Vec<Value>);
+
+// scalar types are parsed from text of single value.
+macro_rules! forward_single {
+    ($($method: ident),*) => {
+        $(
+            fn $method<V>(self, visitor: V) -> DeResult<V::Value>
+            where
+                V: Visitor<'de>,
+            {
+                self.single()?.$method(visitor)
+            }
+        )*
+    };
+}
+
+impl<'de> Deserializer<'de> for ValuesDeserializer {
+    type Error = MultipartFormError;
+
+    fn deserialize_any<V>(mut self, visitor: V) -> DeResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match self.0.len() {
+            1 => ValueDeserializer(self.0.pop().unwrap()).deserialize_any(visitor),
+            _ => self.deserialize_seq(visitor),
+        }
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> DeResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_seq(ValuesAccess(self.0.into_iter()))
+    }
+
+    fn deserialize_tuple<V>(self, _: usize, visitor: V) -> DeResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> DeResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V>(self, _: &'static str, visitor: V) -> DeResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    forward_single! {
+        deserialize_bool,
+        deserialize_i8,
+        deserialize_i16,
+        deserialize_i32,
+        deserialize_i64,
+        deserialize_i128,
+        deserialize_u8,
+        deserialize_u16,
+        deserialize_u32,
+        deserialize_u64,
+        deserialize_u128,
+        deserialize_f32,
+        deserialize_f64
+    }
+
+    serde::forward_to_deserialize_any! {
+        char str string bytes byte_buf unit unit_struct tuple_struct map identifier ignored_any
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> DeResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.single()?.deserialize_struct(name, fields, visitor)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> DeResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.single()?.deserialize_enum(name, variants, visitor)
+    }
+}
+
+impl ValuesDeserializer {
+    fn single(mut self) -> DeResult<ValueDeserializer> {
+        match self.0.len() {
+            1 => Ok(ValueDeserializer(self.0.pop().unwrap())),
+            _ => Err(MultipartFormError::invalid(None, "field is repeated")),
+        }
+    }
+}
+
+struct ValuesAccess(vec::IntoIter<Value>);
+
+impl<'de> de::SeqAccess<'de> for ValuesAccess {
+    type Error = MultipartFormError;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> DeResult<Option<T::Value>>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        self.0
+            .next()
+            .map(|value| seed.deserialize(ValueDeserializer(value)))
+            .transpose()
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.0.len())
+    }
+}
+
+struct ValueDeserializer(Value);
+
+macro_rules! parse_text {
+    ($($method: ident => $visit: ident),*) => {
+        $(
+            fn $method<V>(self, visitor: V) -> DeResult<V::Value>
+            where
+                V: Visitor<'de>,
+            {
+                match self.0 {
+                    Value::Text(ref text) => match text.parse() {
+                        Ok(v) => visitor.$visit(v),
+                        Err(e) => Err(MultipartFormError::invalid(None, e)),
+                    },
+                    _ => self.deserialize_any(visitor),
+                }
+            }
+        )*
+    };
+}
+
+impl<'de> Deserializer<'de> for ValueDeserializer {
+    type Error = MultipartFormError;
+
+    fn deserialize_any<V>(self, visitor: V) -> DeResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match self.0 {
+            Value::Text(text) => visitor.visit_string(text),
+            Value::File { data, .. } => visitor.visit_byte_buf(data),
+        }
+    }
+
+    parse_text! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_u128 => visit_u128,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> DeResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V>(self, _: &'static str, visitor: V) -> DeResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_struct<V>(self, name: &'static str, _: &'static [&'static str], visitor: V) -> DeResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match self.0 {
+            Value::File {
+                file_name,
+                content_type,
+                data,
+            } if name == UPLOADED_FILE => visitor.visit_seq(FileAccess {
+                file_name,
+                content_type,
+                data: Some(data),
+                idx: 0,
+            }),
+            Value::File { .. } => Err(MultipartFormError::invalid(
+                None,
+                "file field can not be deserialized as struct",
+            )),
+            _ if name == UPLOADED_FILE => Err(MultipartFormError::invalid(None, "expecting file field")),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_enum<V>(self, _: &'static str, _: &'static [&'static str], visitor: V) -> DeResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match self.0 {
+            Value::Text(text) => visitor.visit_enum(text.into_deserializer()),
+            _ => Err(MultipartFormError::invalid(
+                None,
+                "file field can not be deserialized as enum",
+            )),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        char str string bytes byte_buf unit unit_struct seq tuple
+        tuple_struct map identifier ignored_any
+    }
+}
+
+struct FileAccess {
+    file_name: Option<String>,
+    content_type: Option<String>,
+    data: Option<Vec<u8>>,
+    idx: u8,
+}
+
+impl<'de> de::SeqAccess<'de> for FileAccess {
+    type Error = MultipartFormError;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> DeResult<Option<T::Value>>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        let value = match self.idx {
+            0 => seed.deserialize(AttrDeserializer::Text(self.file_name.take()))?,
+            1 => seed.deserialize(AttrDeserializer::Text(self.content_type.take()))?,
+            2 => match self.data.take() {
+                Some(data) => seed.deserialize(AttrDeserializer::Data(data))?,
+                None => return Ok(None),
+            },
+            _ => return Ok(None),
+        };
+        self.idx += 1;
+        Ok(Some(value))
+    }
+}
+
+// attributes of file field.
+enum AttrDeserializer {
+    Text(Option<String>),
+    Data(Vec<u8>),
+}
+
+impl<'de> Deserializer<'de> for AttrDeserializer {
+    type Error = MultipartFormError;
+
+    fn deserialize_any<V>(self, visitor: V) -> DeResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Self::Text(Some(text)) => visitor.visit_some(text.into_deserializer()),
+            Self::Text(None) => visitor.visit_none(),
+            Self::Data(data) => visitor.visit_byte_buf(data),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde::Deserialize;
+    use xitca_unsafe_collection::futures::NowOrPanic;
+
+    use crate::{
+        handler::handler_service,
+        http::{header::HeaderValue, request, Method, RequestExt},
+        route::post,
+        test::collect_string_body,
+        App,
+    };
+
+    use super::*;
+
+    #[derive(Deserialize)]
+    struct Upload {
+        title: String,
+        count: u32,
+        tag: Option<String>,
+        avatar: UploadedFile<8>,
+        #[serde(default)]
+        attachments: Vec<UploadedFile>,
+    }
+
+    async fn handler(MultipartForm(upload): MultipartForm<Upload>) -> String {
+        assert_eq!(upload.avatar.file_name(), Some("avatar.png"));
+        assert_eq!(upload.avatar.content_type(), Some("image/png"));
+        format!(
+            "{} {} {:?} {} {}",
+            upload.title,
+            upload.count,
+            upload.tag,
+            upload.avatar.size(),
+            upload.attachments.len()
+        )
+    }
+
+    fn text(name: &str, value: &str) -> String {
+        format!("--boundary\r\nContent-Disposition: form-data; name=\"{name}\"\r\n\r\n{value}\r\n")
+    }
+
+    fn file(name: &str, file_name: &str, value: &str) -> String {
+        format!(
+            "--boundary\r\nContent-Disposition: form-data; name=\"{name}\"; filename=\"{file_name}\"\r\n\
+            Content-Type: image/png\r\n\r\n{value}\r\n"
+        )
+    }
+
+    fn form(parts: &[String]) -> (StatusCode, String) {
+        let mut body = parts.concat();
+        body.push_str("--boundary--\r\n");
+
+        let req = request::Builder::default()
+            .method(Method::POST)
+            .header(
+                CONTENT_TYPE,
+                HeaderValue::from_static("multipart/form-data; boundary=boundary"),
+            )
+            .body(RequestExt::default().map_body(|_: ()| Bytes::from(body).into()))
+            .unwrap();
+
+        let res = App::new()
+            .at("/", post(handler_service(handler)))
+            .finish()
+            .call(())
+            .now_or_panic()
+            .unwrap()
+            .call(req)
+            .now_or_panic()
+            .unwrap();
+
+        let status = res.status();
+        let body = collect_string_body(res.into_body()).now_or_panic().unwrap();
+        (status, body)
+    }
+
+    #[test]
+    fn typed_form() {
+        let (status, body) = form(&[
+            text("title", "hello"),
+            text("count", "3"),
+            file("avatar", "avatar.png", "png"),
+            file("attachments", "a.png", "a"),
+            file("attachments", "b.png", "bb"),
+        ]);
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, "hello 3 None 3 2");
+
+        let (status, body) = form(&[
+            text("title", "hello"),
+            text("count", "3"),
+            text("tag", "foo"),
+            file("avatar", "avatar.png", "png"),
+            // empty file field is treated as absent.
+            file("attachments", "", ""),
+        ]);
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, "hello 3 Some(\"foo\") 3 0");
+    }
+
+    #[test]
+    fn missing_field() {
+        let (status, body) = form(&[text("count", "3"), file("avatar", "avatar.png", "png")]);
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(body, "missing field `title`");
+    }
+
+    #[test]
+    fn invalid_field() {
+        let (status, body) = form(&[
+            text("title", "hello"),
+            text("count", "three"),
+            file("avatar", "avatar.png", "png"),
+        ]);
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert!(body.starts_with("invalid field `count`"));
+
+        let (status, body) = form(&[
+            text("title", "hello"),
+            text("count", "3"),
+            file("avatar", "avatar.png", "too large png"),
+        ]);
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(body, "invalid field `avatar`: file size reached limit: 8 bytes");
+
+        let (status, _) = form(&[text("title", "hello"), text("count", "3"), text("avatar", "png")]);
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    }
+}
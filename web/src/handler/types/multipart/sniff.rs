@@ -0,0 +1,355 @@
+use core::{convert::Infallible, fmt};
+
+use std::{collections::VecDeque, error};
+
+use http_multipart::{Field, MultipartError};
+
+use crate::{
+    body::BodyStream,
+    bytes::Bytes,
+    context::WebContext,
+    error::{error_from_service, Error},
+    http::{const_header_value::TEXT_UTF8, header::CONTENT_TYPE, StatusCode, WebResponse},
+    service::Service,
+};
+
+// max bytes needed for matching signature of any FileType.
+const PEEK_LIMIT: usize = 12;
+
+/// file type recognizable by it's leading magic bytes. used by [ContentSniff] for verifying declared
+/// `Content-Type` of multipart field.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FileType {
+    /// `image/png`
+    Png,
+    /// `image/jpeg`
+    Jpeg,
+    /// `image/gif`
+    Gif,
+    /// `image/webp`
+    Webp,
+    /// `application/pdf`
+    Pdf,
+    /// `application/zip`
+    Zip,
+}
+
+impl FileType {
+    /// mime type of file type.
+    pub const fn mime(&self) -> &'static str {
+        match *self {
+            Self::Png => "image/png",
+            Self::Jpeg => "image/jpeg",
+            Self::Gif => "image/gif",
+            Self::Webp => "image/webp",
+            Self::Pdf => "application/pdf",
+            Self::Zip => "application/zip",
+        }
+    }
+
+    /// check if given leading bytes of file match the signature of file type.
+    pub fn matches(&self, head: &[u8]) -> bool {
+        match *self {
+            Self::Png => head.starts_with(b"\x89PNG\r\n\x1a\n"),
+            Self::Jpeg => head.starts_with(b"\xff\xd8\xff"),
+            Self::Gif => head.starts_with(b"GIF87a") || head.starts_with(b"GIF89a"),
+            Self::Webp => head.len() >= 12 && head.starts_with(b"RIFF") && &head[8..12] == b"WEBP",
+            Self::Pdf => head.starts_with(b"%PDF-"),
+            Self::Zip => head.starts_with(b"PK\x03\x04"),
+        }
+    }
+}
+
+/// guard for file field of [Multipart] verifying it's declared `Content-Type` header matches the magic bytes at the
+/// start of it's content. field with content type not in the allowed set or content not matching it is rejected
+/// with [ContentSniffError] and "415 Unsupported Media Type" response.
+///
+/// leading bytes of field are peeked and replayed by [SniffedField] so handler observes the complete content. peek
+/// buffer is bounded to the bytes needed by the longest signature.
+///
+/// # Examples
+/// ```rust
+/// # use core::pin::pin;
+/// # use xitca_web::{
+/// #     error::Error,
+/// #     handler::{handler_service, multipart::{ContentSniff, FileType, Multipart}},
+/// #     route::post,
+/// #     App, WebContext,
+/// # };
+/// async fn upload(multipart: Multipart) -> Result<String, Error> {
+///     let sniff = ContentSniff::new().allow(FileType::Png).allow(FileType::Jpeg);
+///
+///     let mut multipart = pin!(multipart);
+///     let mut size = 0;
+///     while let Some(mut field) = multipart.try_next().await.map_err(Error::from_service)? {
+///         // reject field when it's not png or jpeg image.
+///         let mut field = sniff.check(&mut field).await?;
+///         while let Some(chunk) = field.try_next().await.map_err(Error::from_service)? {
+///             size += chunk.len();
+///         }
+///     }
+///
+///     Ok(format!("uploaded {size} bytes"))
+/// }
+///
+/// App::new()
+///     .at("/", post(handler_service(upload)))
+///     # .at("/nah", handler_service(|_: &WebContext<'_>| async { "used for infer type" }));
+/// ```
+///
+/// [Multipart]: super::Multipart
+#[derive(Clone, Debug, Default)]
+pub struct ContentSniff {
+    types: Vec<FileType>,
+}
+
+impl ContentSniff {
+    /// construct a new guard with empty allowed set. every field would be rejected until file types are allowed.
+    pub const fn new() -> Self {
+        Self { types: Vec::new() }
+    }
+
+    /// add file type to allowed set.
+    pub fn allow(mut self, ty: FileType) -> Self {
+        if !self.types.contains(&ty) {
+            self.types.push(ty);
+        }
+        self
+    }
+
+    /// verify declared content type of field against it's leading bytes.
+    ///
+    /// on success the returned [SniffedField] yields the complete content of field including the peeked bytes.
+    /// error of reading field and [ContentSniffError] are returned as [Error].
+    pub async fn check<'f, 'a, B>(&self, field: &'f mut Field<'a, B>) -> Result<SniffedField<'f, 'a, B>, Error>
+    where
+        B: BodyStream,
+    {
+        let ty = declared(field).and_then(|mime| self.types.iter().find(|ty| ty.mime().eq_ignore_ascii_case(mime)));
+
+        let Some(ty) = ty else {
+            return Err(ContentSniffError::new(field).into());
+        };
+
+        let mut peeked = VecDeque::new();
+        let mut head = [0; PEEK_LIMIT];
+        let mut len = 0;
+
+        while len < PEEK_LIMIT {
+            match field.try_next().await.map_err(Error::from_service)? {
+                Some(chunk) => {
+                    let n = core::cmp::min(PEEK_LIMIT - len, chunk.len());
+                    head[len..len + n].copy_from_slice(&chunk[..n]);
+                    len += n;
+                    if !chunk.is_empty() {
+                        peeked.push_back(chunk);
+                    }
+                }
+                None => break,
+            }
+        }
+
+        if !ty.matches(&head[..len]) {
+            return Err(ContentSniffError::new(field).into());
+        }
+
+        Ok(SniffedField { field, peeked })
+    }
+}
+
+// mime type of Content-Type header of field without parameters.
+fn declared<'f, B>(field: &'f Field<'_, B>) -> Option<&'f str>
+where
+    B: BodyStream,
+{
+    field
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.split(';').next().unwrap_or_default().trim())
+}
+
+/// multipart field verified by [ContentSniff]. bytes peeked for verification are yielded before the remaining
+/// content of field.
+pub struct SniffedField<'f, 'a, B> {
+    field: &'f mut Field<'a, B>,
+    peeked: VecDeque<Bytes>,
+}
+
+impl<'a, B> SniffedField<'_, 'a, B>
+where
+    B: BodyStream,
+{
+    /// reference of the underlying field for accessing it's name and headers.
+    pub fn field(&self) -> &Field<'a, B> {
+        self.field
+    }
+
+    /// same as [Field::try_next] with peeked bytes yielded first.
+    pub async fn try_next(&mut self) -> Result<Option<Bytes>, MultipartError> {
+        match self.peeked.pop_front() {
+            Some(chunk) => Ok(Some(chunk)),
+            None => self.field.try_next().await,
+        }
+    }
+}
+
+/// error type of [ContentSniff]. produce "415 Unsupported Media Type" response with error message as body.
+#[derive(Debug)]
+pub struct ContentSniffError {
+    field: Option<String>,
+    declared: Option<String>,
+}
+
+impl ContentSniffError {
+    fn new<B>(field: &Field<'_, B>) -> Self
+    where
+        B: BodyStream,
+    {
+        Self {
+            field: field.name().map(str::to_owned),
+            declared: declared(field).map(str::to_owned),
+        }
+    }
+
+    /// name of the field causing error.
+    pub fn field(&self) -> Option<&str> {
+        self.field.as_deref()
+    }
+
+    /// declared `Content-Type` of the field. `None` when the header is absent.
+    pub fn declared(&self) -> Option<&str> {
+        self.declared.as_deref()
+    }
+}
+
+impl fmt::Display for ContentSniffError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.field {
+            Some(ref field) => write!(f, "file field `{field}` ")?,
+            None => f.write_str("file field ")?,
+        }
+        match self.declared {
+            Some(ref declared) => write!(f, "is not allowed or does not match it's content type `{declared}`"),
+            None => f.write_str("has no content type"),
+        }
+    }
+}
+
+impl error::Error for ContentSniffError {}
+
+error_from_service!(ContentSniffError);
+
+impl<'r, C, B> Service<WebContext<'r, C, B>> for ContentSniffError {
+    type Response = WebResponse;
+    type Error = Infallible;
+
+    async fn call(&self, ctx: WebContext<'r, C, B>) -> Result<Self::Response, Self::Error> {
+        let mut res = ctx.into_response(self.to_string());
+        *res.status_mut() = StatusCode::UNSUPPORTED_MEDIA_TYPE;
+        res.headers_mut().insert(CONTENT_TYPE, TEXT_UTF8);
+        Ok(res)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use core::pin::pin;
+
+    use xitca_unsafe_collection::futures::NowOrPanic;
+
+    use crate::{
+        handler::{handler_service, multipart::Multipart},
+        http::{header::HeaderValue, request, Method, RequestExt},
+        route::post,
+        test::collect_body,
+        App,
+    };
+
+    use super::*;
+
+    async fn handler(multipart: Multipart) -> Result<Vec<u8>, Error> {
+        let sniff = ContentSniff::new().allow(FileType::Png).allow(FileType::Pdf);
+
+        let mut multipart = pin!(multipart);
+        let mut res = Vec::new();
+
+        while let Some(mut field) = multipart.try_next().await.map_err(Error::from_service)? {
+            let mut field = sniff.check(&mut field).await?;
+            while let Some(chunk) = field.try_next().await.map_err(Error::from_service)? {
+                res.extend_from_slice(chunk.as_ref());
+            }
+        }
+
+        Ok(res)
+    }
+
+    fn body(content_type: &str, content: &[u8]) -> Vec<u8> {
+        let mut body = Vec::new();
+        body.extend_from_slice(b"--abbc761f78ff4d7cb7573b5a23f96ef0\r\n");
+        body.extend_from_slice(b"Content-Disposition: form-data; name=\"file\"; filename=\"foo\"\r\n");
+        body.extend_from_slice(format!("Content-Type: {content_type}\r\n\r\n").as_bytes());
+        body.extend_from_slice(content);
+        body.extend_from_slice(b"\r\n--abbc761f78ff4d7cb7573b5a23f96ef0--\r\n");
+        body
+    }
+
+    #[test]
+    fn file_type_matches() {
+        assert!(FileType::Png.matches(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"));
+        assert!(FileType::Jpeg.matches(b"\xff\xd8\xff\xe0"));
+        assert!(FileType::Gif.matches(b"GIF89a"));
+        assert!(FileType::Webp.matches(b"RIFF\0\0\0\0WEBPVP8 "));
+        assert!(!FileType::Webp.matches(b"RIFF\0\0\0\0WAVE"));
+        assert!(FileType::Pdf.matches(b"%PDF-1.7"));
+        assert!(FileType::Zip.matches(b"PK\x03\x04"));
+        assert!(!FileType::Png.matches(b"\x89PN"));
+    }
+
+    #[test]
+    fn sniff() {
+        let service = App::new()
+            .at("/", post(handler_service(handler)))
+            .finish()
+            .call(())
+            .now_or_panic()
+            .unwrap();
+
+        let png = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR-png-content";
+
+        for (content_type, content, status) in [
+            ("image/png", &png[..], StatusCode::OK),
+            ("Image/PNG; charset=binary", &png[..], StatusCode::OK),
+            ("application/pdf", &b"%PDF-1.7"[..], StatusCode::OK),
+            // declared type does not match content.
+            ("image/png", &b"%PDF-1.7"[..], StatusCode::UNSUPPORTED_MEDIA_TYPE),
+            // content shorter than signature.
+            ("image/png", &b"\x89PNG"[..], StatusCode::UNSUPPORTED_MEDIA_TYPE),
+            // declared type is not allowed.
+            (
+                "image/jpeg",
+                &b"\xff\xd8\xff\xe0"[..],
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            ),
+        ] {
+            let body = body(content_type, content);
+            let req = request::Builder::default()
+                .method(Method::POST)
+                .header(
+                    CONTENT_TYPE,
+                    HeaderValue::from_static("multipart/form-data; boundary=abbc761f78ff4d7cb7573b5a23f96ef0"),
+                )
+                .body(RequestExt::default().map_body(|_: ()| body.into()))
+                .unwrap();
+
+            let res = service.call(req).now_or_panic().unwrap();
+            assert_eq!(res.status(), status);
+
+            if status == StatusCode::OK {
+                // peeked bytes are replayed to handler.
+                let body = collect_body(res.into_body()).now_or_panic().unwrap();
+                assert_eq!(body, content);
+            }
+        }
+    }
+}
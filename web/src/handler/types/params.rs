@@ -332,8 +332,8 @@ macro_rules! parse_value {
     };
 }
 
-struct Value<'de> {
-    value: &'de str,
+pub(super) struct Value<'de> {
+    pub(super) value: &'de str,
 }
 
 impl<'de> Deserializer<'de> for Value<'de> {
@@ -0,0 +1,330 @@
+//! type extractor for uri params and query merged into one object.
+
+use core::{
+    cell::Cell,
+    fmt,
+    ops::{Deref, DerefMut},
+};
+
+use std::error;
+
+use serde::{
+    de::{self, value::BorrowedStrDeserializer, Deserializer, Visitor},
+    forward_to_deserialize_any, Deserialize,
+};
+
+use crate::{
+    context::WebContext,
+    error::{error_from_service, forward_blank_bad_request, Error},
+    handler::FromRequest,
+};
+
+use super::params::Value;
+
+/// Extract type for object deserialized from both matched uri params and uri query.
+///
+/// Params and query are merged into one key value map before deserializing. On key collision the value from uri
+/// params takes precedence and the query value with the same key is ignored. Route decides the params and client can
+/// not override them through query.
+///
+/// Query is always parsed as plain key value pairs. [UrlencodedMode](super::query::UrlencodedMode) attached to
+/// request extensions is not respected.
+///
+/// Failed extraction produces [PathAndQueryError] and "400 Bad Request" response.
+///
+/// # Examples
+/// ```rust
+/// # use xitca_web::{handler::{handler_service, path_query::PathAndQuery}, App, WebContext};
+/// #[derive(serde::Deserialize)]
+/// struct Report {
+///     id: u64,
+///     format: String,
+/// }
+///
+/// // handle request like "/reports/996?format=csv"
+/// async fn handler(PathAndQuery(report): PathAndQuery<Report>) -> String {
+///     format!("report {} in {}", report.id, report.format)
+/// }
+///
+/// App::new()
+///     .at("/reports/:id", handler_service(handler))
+///     # .at("/infer", handler_service(|_: &WebContext<'_>| async { "used for infer type" }))
+///     ;
+/// ```
+pub struct PathAndQuery<T>(pub T);
+
+impl<T> fmt::Debug for PathAndQuery<T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PathAndQuery").field("value", &self.0).finish()
+    }
+}
+
+impl<T> Deref for PathAndQuery<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for PathAndQuery<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<'a, 'r, C, B, T> FromRequest<'a, WebContext<'r, C, B>> for PathAndQuery<T>
+where
+    T: for<'de> Deserialize<'de>,
+{
+    type Type<'b> = PathAndQuery<T>;
+    type Error = Error;
+
+    async fn from_request(ctx: &'a WebContext<'r, C, B>) -> Result<Self, Self::Error> {
+        let params = ctx.req().body().params();
+        let query = ctx.req().uri().query().unwrap_or_default();
+
+        let query = serde_urlencoded::from_str::<Vec<(String, String)>>(query).map_err(|error| PathAndQueryError {
+            origin: Some(Origin::Query),
+            key: None,
+            error,
+        })?;
+
+        let mut pairs = params.iter().map(|(k, v)| (k, v, Origin::Path)).collect::<Vec<_>>();
+        pairs.extend(
+            query
+                .iter()
+                .filter(|(k, _)| params.iter().all(|(key, _)| key != k))
+                .map(|(k, v)| (k.as_str(), v.as_str(), Origin::Query)),
+        );
+
+        let failed = Cell::new(None);
+
+        T::deserialize(PairsDeserializer {
+            pairs: &pairs,
+            failed: &failed,
+        })
+        .map(PathAndQuery)
+        .map_err(|error| {
+            let (origin, key) = failed.get().unzip();
+            Error::from(PathAndQueryError {
+                origin,
+                key: key.map(Into::into),
+                error,
+            })
+        })
+    }
+}
+
+/// source of a value extracted by [PathAndQuery].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Origin {
+    /// value comes from matched uri params.
+    Path,
+    /// value comes from uri query.
+    Query,
+}
+
+impl fmt::Display for Origin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Path => f.write_str("path param"),
+            Self::Query => f.write_str("query"),
+        }
+    }
+}
+
+/// error type of [PathAndQuery] extractor. produce "400 Bad Request" response.
+#[derive(Debug)]
+pub struct PathAndQueryError {
+    origin: Option<Origin>,
+    key: Option<Box<str>>,
+    error: de::value::Error,
+}
+
+impl PathAndQueryError {
+    /// where the invalid value comes from. None when the error is not caused by a specific value. For example a
+    /// missing field that can be provided by either uri params or query.
+    pub fn origin(&self) -> Option<Origin> {
+        self.origin
+    }
+
+    /// key of the invalid value.
+    pub fn key(&self) -> Option<&str> {
+        self.key.as_deref()
+    }
+}
+
+impl fmt::Display for PathAndQueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.origin, self.key.as_deref()) {
+            (Some(origin), Some(key)) => write!(f, "invalid {origin} `{key}`: {}", self.error),
+            (Some(origin), None) => write!(f, "invalid {origin}: {}", self.error),
+            (None, _) => write!(f, "{} in path params and query", self.error),
+        }
+    }
+}
+
+impl error::Error for PathAndQueryError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+error_from_service!(PathAndQueryError);
+forward_blank_bad_request!(PathAndQueryError);
+
+struct PairsDeserializer<'a, 'de> {
+    pairs: &'a [(&'de str, &'de str, Origin)],
+    // origin and key of the value failed to deserialize.
+    failed: &'a Cell<Option<(Origin, &'de str)>>,
+}
+
+impl<'de> Deserializer<'de> for PairsDeserializer<'_, 'de> {
+    type Error = de::value::Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_map(MapAccess {
+            pairs: self.pairs.iter(),
+            current: None,
+            failed: self.failed,
+        })
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+struct MapAccess<'a, 'de, I> {
+    pairs: I,
+    current: Option<(&'de str, &'de str, Origin)>,
+    failed: &'a Cell<Option<(Origin, &'de str)>>,
+}
+
+impl<'a, 'de, I> de::MapAccess<'de> for MapAccess<'a, 'de, I>
+where
+    I: Iterator<Item = &'a (&'de str, &'de str, Origin)>,
+    'de: 'a,
+{
+    type Error = de::value::Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+    where
+        K: de::DeserializeSeed<'de>,
+    {
+        self.current = self.pairs.next().copied();
+        match self.current {
+            Some((key, ..)) => seed.deserialize(BorrowedStrDeserializer::new(key)).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        let (key, value, origin) = self
+            .current
+            .take()
+            .ok_or_else(|| de::Error::custom("unexpected item"))?;
+        seed.deserialize(Value { value })
+            .inspect_err(|_| self.failed.set(Some((origin, key))))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use xitca_unsafe_collection::futures::NowOrPanic;
+
+    use crate::{
+        handler::handler_service,
+        http::{StatusCode, Uri, WebRequest},
+        service::Service,
+        test::collect_string_body,
+        App,
+    };
+
+    use super::*;
+
+    #[derive(Deserialize)]
+    struct Report {
+        id: u64,
+        format: String,
+        limit: Option<u32>,
+    }
+
+    async fn handler(res: Result<PathAndQuery<Report>, Error>) -> String {
+        match res {
+            Ok(PathAndQuery(report)) => format!("{} {} {:?}", report.id, report.format, report.limit),
+            Err(e) => {
+                let e = e.upcast().downcast_ref::<PathAndQueryError>().unwrap();
+                format!("{:?} {:?} {e}", e.origin(), e.key())
+            }
+        }
+    }
+
+    #[test]
+    fn extract() {
+        let service = App::new()
+            .at("/reports/:id", handler_service(handler))
+            .finish()
+            .call(())
+            .now_or_panic()
+            .unwrap();
+
+        let call = |uri| {
+            let mut req = WebRequest::default();
+            *req.uri_mut() = Uri::from_static(uri);
+            let res = service.call(req).now_or_panic().unwrap();
+            assert_eq!(res.status(), StatusCode::OK);
+            collect_string_body(res.into_body()).now_or_panic().unwrap()
+        };
+
+        assert_eq!(call("/reports/996?format=csv&limit=8"), "996 csv Some(8)");
+
+        // params take precedence over query.
+        assert_eq!(call("/reports/996?format=csv&id=251"), "996 csv None");
+
+        assert_eq!(
+            call("/reports/abc?format=csv"),
+            r#"Some(Path) Some("id") invalid path param `id`: can not parse "abc" to a u64"#
+        );
+
+        assert_eq!(
+            call("/reports/996?format=csv&limit=abc"),
+            r#"Some(Query) Some("limit") invalid query `limit`: can not parse "abc" to a u32"#
+        );
+
+        assert_eq!(
+            call("/reports/996"),
+            "None None missing field `format` in path params and query"
+        );
+    }
+
+    #[test]
+    fn bad_request() {
+        let service = App::new()
+            .at(
+                "/reports/:id",
+                handler_service(|_: PathAndQuery<Report>| async { "unreachable" }),
+            )
+            .finish()
+            .call(())
+            .now_or_panic()
+            .unwrap();
+
+        let mut req = WebRequest::default();
+        *req.uri_mut() = Uri::from_static("/reports/abc?format=csv");
+        let res = service.call(req).now_or_panic().unwrap();
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,264 @@
+//! type extractor and helper for conditional request with `If-Match` and `If-Unmodified-Since` headers.
+
+use core::time::Duration;
+
+use std::time::SystemTime;
+
+use httpdate::HttpDate;
+
+use crate::{
+    context::WebContext,
+    error::{Error, InvalidHeaderValue, PreconditionFailed},
+    handler::FromRequest,
+    http::header::{HeaderMap, IF_MATCH, IF_UNMODIFIED_SINCE},
+};
+
+/// Extract conditions of request from `If-Match` and `If-Unmodified-Since` headers.
+///
+/// Useful for optimistic concurrency control of state changing methods like PUT and DELETE. Handler evaluates
+/// the conditions against entity-tag and/or last modified time of current representation of target resource with
+/// [Precondition::evaluate] and the method is only performed when they are satisfied. Otherwise the returned
+/// [PreconditionFailed] error produces "412 Precondition Failed" response.
+///
+/// Malformed `If-Match` header is rejected with "400 Bad Request" response. `*` combined with entity-tags in
+/// multiple `If-Match` headers is treated as malformed regardless of their order. Malformed `If-Unmodified-Since`
+/// header is ignored.
+///
+/// # Examples
+/// ```rust
+/// # use xitca_web::{
+/// #   error::Error,
+/// #   handler::{handler_service, precondition::Precondition},
+/// #   route::put,
+/// #   App, WebContext,
+/// # };
+/// async fn update(cond: Precondition, body: String) -> Result<String, Error> {
+///     // entity-tag of current representation. usually a hash or version of resource.
+///     let etag = "\"v1\"";
+///     cond.evaluate(Some(etag), None)?;
+///     // perform the update.
+///     Ok(body)
+/// }
+///
+/// App::new()
+///     .at("/", put(handler_service(update)))
+///     # .at("/nah", handler_service(|_: &WebContext<'_>| async { "used for infer type" }));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Precondition {
+    if_match: Option<IfMatch>,
+    if_unmodified_since: Option<SystemTime>,
+}
+
+/// parsed value of `If-Match` header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IfMatch {
+    /// `If-Match: *`. matches any current representation.
+    Any,
+    /// list of entity-tags in their wire format. `"xyzzy"` or `W/"xyzzy"` for example.
+    Tags(Vec<String>),
+}
+
+impl Precondition {
+    /// parse conditions from given request headers.
+    pub fn from_headers(headers: &HeaderMap) -> Result<Self, InvalidHeaderValue> {
+        let mut if_match = None;
+
+        for value in headers.get_all(IF_MATCH) {
+            let value = value.to_str().map_err(|_| InvalidHeaderValue(IF_MATCH))?;
+            if value.trim() == "*" {
+                // "*" can not be combined with other values.
+                if if_match.replace(IfMatch::Any).is_some() {
+                    return Err(InvalidHeaderValue(IF_MATCH));
+                }
+                continue;
+            }
+            let tags = match if_match.get_or_insert_with(|| IfMatch::Tags(Vec::new())) {
+                IfMatch::Tags(tags) => tags,
+                IfMatch::Any => return Err(InvalidHeaderValue(IF_MATCH)),
+            };
+            parse_tags(value, tags).ok_or(InvalidHeaderValue(IF_MATCH))?;
+        }
+
+        let if_unmodified_since = headers
+            .get(IF_UNMODIFIED_SINCE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<HttpDate>().ok())
+            .map(SystemTime::from);
+
+        Ok(Self {
+            if_match,
+            if_unmodified_since,
+        })
+    }
+
+    /// parsed `If-Match` header. `None` when header is absent.
+    pub fn if_match(&self) -> Option<&IfMatch> {
+        self.if_match.as_ref()
+    }
+
+    /// parsed `If-Unmodified-Since` header. `None` when header is absent or not a valid http date.
+    pub fn if_unmodified_since(&self) -> Option<SystemTime> {
+        self.if_unmodified_since
+    }
+
+    /// evaluate conditions against entity-tag and last modified time of current representation of target
+    /// resource. `etag` is in it's wire format like [IfMatch::Tags]. `None` is passed for both when target
+    /// resource does not exist.
+    ///
+    /// Conditions are evaluated in the order defined by RFC 9110:
+    /// - `If-Match` is satisfied when it's `*` and resource exists or any of it's tags strongly matches `etag`.
+    ///   Weak entity-tags never match.
+    /// - `If-Unmodified-Since` is only evaluated when `If-Match` is absent. It's satisfied when `last_modified` is
+    ///   not later than it's date in second precision or `last_modified` is unknown.
+    pub fn evaluate(&self, etag: Option<&str>, last_modified: Option<SystemTime>) -> Result<(), PreconditionFailed> {
+        let satisfied = match (self.if_match.as_ref(), self.if_unmodified_since) {
+            (Some(IfMatch::Any), _) => etag.is_some() || last_modified.is_some(),
+            (Some(IfMatch::Tags(tags)), _) => {
+                etag.is_some_and(|etag| !is_weak(etag) && tags.iter().any(|tag| !is_weak(tag) && tag == etag))
+            }
+            (None, Some(since)) => last_modified.is_none_or(|m| not_modified_since(m, since)),
+            (None, None) => true,
+        };
+
+        if satisfied {
+            Ok(())
+        } else {
+            Err(PreconditionFailed)
+        }
+    }
+}
+
+// compare last modified time in second precision of http date. time before unix epoch is earlier than any http
+// date.
+fn not_modified_since(last_modified: SystemTime, since: SystemTime) -> bool {
+    match last_modified.duration_since(SystemTime::UNIX_EPOCH) {
+        Ok(dur) => SystemTime::UNIX_EPOCH + Duration::from_secs(dur.as_secs()) <= since,
+        Err(_) => true,
+    }
+}
+
+fn is_weak(tag: &str) -> bool {
+    tag.starts_with("W/")
+}
+
+// parse comma separated entity-tags. comma is a valid character inside of quoted tag so a naive split is not used.
+fn parse_tags(mut value: &str, tags: &mut Vec<String>) -> Option<()> {
+    loop {
+        value = value.trim_start_matches([' ', '\t', ',']);
+        if value.is_empty() {
+            return Some(());
+        }
+        let prefix = if value.starts_with("W/") { 2 } else { 0 };
+        let rest = value[prefix..].strip_prefix('"')?;
+        let end = prefix + 2 + rest.find('"')?;
+        tags.push(value[..end].to_owned());
+        value = &value[end..];
+        if !value.is_empty() && !value.starts_with([' ', '\t', ',']) {
+            return None;
+        }
+    }
+}
+
+impl<'a, 'r, C, B> FromRequest<'a, WebContext<'r, C, B>> for Precondition {
+    type Type<'b> = Precondition;
+    type Error = Error;
+
+    #[inline]
+    async fn from_request(ctx: &'a WebContext<'r, C, B>) -> Result<Self, Self::Error> {
+        Precondition::from_headers(ctx.req().headers()).map_err(Into::into)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use xitca_unsafe_collection::futures::NowOrPanic;
+
+    use crate::http::{header::HeaderValue, StatusCode};
+
+    use super::*;
+
+    fn precondition(headers: &[(crate::http::header::HeaderName, &'static str)]) -> Result<Precondition, Error> {
+        let mut ctx = WebContext::new_test(());
+        let mut ctx = ctx.as_web_ctx();
+        for (name, value) in headers {
+            ctx.req_mut()
+                .headers_mut()
+                .append(name.clone(), HeaderValue::from_static(value));
+        }
+        let res = Precondition::from_request(&ctx).now_or_panic();
+        res
+    }
+
+    #[test]
+    fn if_match() {
+        let cond = precondition(&[]).unwrap();
+        assert_eq!(cond, Precondition::default());
+        assert!(cond.evaluate(None, None).is_ok());
+
+        let cond = precondition(&[(IF_MATCH, "\"a,b\", W/\"c\""), (IF_MATCH, "\"d\"")]).unwrap();
+        assert_eq!(
+            cond.if_match(),
+            Some(&IfMatch::Tags(vec!["\"a,b\"".into(), "W/\"c\"".into(), "\"d\"".into()]))
+        );
+        assert!(cond.evaluate(Some("\"a,b\""), None).is_ok());
+        assert!(cond.evaluate(Some("\"d\""), None).is_ok());
+        // weak comparison is not allowed.
+        assert!(cond.evaluate(Some("W/\"c\""), None).is_err());
+        assert!(cond.evaluate(Some("\"c\""), None).is_err());
+        assert!(cond.evaluate(None, None).is_err());
+
+        let cond = precondition(&[(IF_MATCH, "*")]).unwrap();
+        assert!(cond.evaluate(Some("\"a\""), None).is_ok());
+        assert!(cond.evaluate(None, None).is_err());
+
+        let e = precondition(&[(IF_MATCH, "a")]).unwrap_err();
+        let mut ctx = WebContext::new_test(());
+        let res = crate::service::Service::call(&e, ctx.as_web_ctx())
+            .now_or_panic()
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+        // "*" combined with tags is malformed in either order.
+        assert!(precondition(&[(IF_MATCH, "*"), (IF_MATCH, "\"a\"")]).is_err());
+        assert!(precondition(&[(IF_MATCH, "\"a\""), (IF_MATCH, "*")]).is_err());
+    }
+
+    #[test]
+    fn if_unmodified_since() {
+        let since = SystemTime::UNIX_EPOCH + Duration::from_secs(784111777);
+
+        let cond = precondition(&[(IF_UNMODIFIED_SINCE, "Sun, 06 Nov 1994 08:49:37 GMT")]).unwrap();
+        assert_eq!(cond.if_unmodified_since(), Some(since));
+        assert!(cond.evaluate(None, Some(since)).is_ok());
+        // sub second difference is ignored.
+        assert!(cond.evaluate(None, Some(since + Duration::from_millis(500))).is_ok());
+        assert!(cond.evaluate(None, Some(since - Duration::from_secs(1))).is_ok());
+        assert!(cond.evaluate(None, None).is_ok());
+        // last modified time out of range of http date does not panic.
+        assert!(cond
+            .evaluate(None, Some(SystemTime::UNIX_EPOCH - Duration::from_secs(1)))
+            .is_ok());
+
+        let e = cond.evaluate(None, Some(since + Duration::from_secs(1))).unwrap_err();
+        let mut ctx = WebContext::new_test(());
+        let res = crate::service::Service::call(&e, ctx.as_web_ctx())
+            .now_or_panic()
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::PRECONDITION_FAILED);
+
+        // If-Match takes precedence.
+        let cond = precondition(&[
+            (IF_MATCH, "\"a\""),
+            (IF_UNMODIFIED_SINCE, "Sun, 06 Nov 1994 08:49:37 GMT"),
+        ])
+        .unwrap();
+        assert!(cond
+            .evaluate(Some("\"a\""), Some(since + Duration::from_secs(1)))
+            .is_ok());
+
+        // invalid date is ignored.
+        let cond = precondition(&[(IF_UNMODIFIED_SINCE, "yesterday")]).unwrap();
+        assert_eq!(cond.if_unmodified_since(), None);
+    }
+}
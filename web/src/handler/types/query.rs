@@ -1,6 +1,10 @@
 //! type extractor for request uri query
 
-use core::{fmt, marker::PhantomData};
+use core::{
+    fmt,
+    marker::PhantomData,
+    ops::{Deref, DerefMut},
+};
 
 use serde::de::Deserialize;
 
@@ -10,6 +14,14 @@ use crate::{
     handler::FromRequest,
 };
 
+use super::urlencoded;
+
+pub use super::urlencoded::UrlencodedMode;
+
+/// Extract type for uri query object.
+///
+/// Query is parsed with [UrlencodedMode::Strict] by default. [UrlencodedMode::Nested] can be attached to request
+/// extensions for collecting repeated and bracketed keys. See [UrlencodedMode] for detail.
 pub struct Query<T>(pub T);
 
 impl<T> fmt::Debug for Query<T>
@@ -21,6 +33,20 @@ where
     }
 }
 
+impl<T> Deref for Query<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Query<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
 impl<'a, 'r, C, B, T> FromRequest<'a, WebContext<'r, C, B>> for Query<T>
 where
     T: for<'de> Deserialize<'de>,
@@ -30,7 +56,13 @@ where
 
     #[inline]
     async fn from_request(ctx: &'a WebContext<'r, C, B>) -> Result<Self, Self::Error> {
-        serde_urlencoded::from_str(ctx.req().uri().query().unwrap_or_default())
+        let mode = ctx
+            .req()
+            .extensions()
+            .get::<UrlencodedMode>()
+            .copied()
+            .unwrap_or_default();
+        urlencoded::from_bytes(mode, ctx.req().uri().query().unwrap_or_default().as_bytes())
             .map(Query)
             .map_err(Error::from_service)
     }
@@ -38,6 +70,7 @@ where
 
 /// lazy deserialize type.
 /// it lowers the deserialization to handler function where zero copy deserialize can happen.
+/// Lazy query is always parsed with [UrlencodedMode::Strict].
 pub struct LazyQuery<'a, T> {
     query: &'a [u8],
     _query: PhantomData<T>,
@@ -98,6 +131,26 @@ mod test {
         assert_eq!(id.id, "dagongren");
     }
 
+    #[test]
+    fn query_nested() {
+        #[derive(serde::Deserialize)]
+        struct Tags {
+            tag: Vec<String>,
+        }
+
+        let mut req = WebContext::new_test(());
+        let mut req = req.as_web_ctx();
+
+        *req.req_mut().uri_mut() = Uri::from_static("/?tag=a&tag=b");
+
+        assert!(Query::<Tags>::from_request(&req).now_or_panic().is_err());
+
+        req.req_mut().extensions_mut().insert(UrlencodedMode::Nested);
+
+        let Query(tags) = Query::<Tags>::from_request(&req).now_or_panic().unwrap();
+        assert_eq!(tags.tag, ["a", "b"]);
+    }
+
     #[test]
     fn query_lazy() {
         let mut ctx = WebContext::new_test(());
@@ -0,0 +1,387 @@
+//! response generator for server-sent events.
+
+use core::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    task::{ready, Context, Poll},
+    time::Duration,
+};
+
+use futures_core::stream::Stream;
+use pin_project_lite::pin_project;
+
+use crate::{
+    body::{Flush, ResponseBody},
+    bytes::{Bytes, BytesMut},
+    context::WebContext,
+    error::{BodyError, Error},
+    handler::Responder,
+    http::{
+        header::{HeaderName, HeaderValue, CACHE_CONTROL, CONTENT_TYPE},
+        WebResponse,
+    },
+};
+
+#[allow(clippy::declare_interior_mutable_const)]
+const LAST_EVENT_ID: HeaderName = HeaderName::from_static("last-event-id");
+#[allow(clippy::declare_interior_mutable_const)]
+const TEXT_EVENT_STREAM: HeaderValue = HeaderValue::from_static("text/event-stream");
+#[allow(clippy::declare_interior_mutable_const)]
+const NO_CACHE: HeaderValue = HeaderValue::from_static("no-cache");
+
+/// a single event of server-sent events stream.
+///
+/// # Examples
+/// ```rust
+/// # use xitca_web::handler::sse::Event;
+/// let event = Event::new().id("1").event("update").data("line 1\nline 2");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Event {
+    id: Option<String>,
+    event: Option<String>,
+    data: Option<String>,
+    retry: Option<Duration>,
+    comment: Option<String>,
+}
+
+impl Event {
+    /// construct an empty event. An event without data is not dispatched by client and can be used to only
+    /// carry id, retry or comment.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// set data of event. line breaks are preserved by splitting data into multiple `data` fields.
+    pub fn data(mut self, data: impl Into<String>) -> Self {
+        self.data = Some(data.into());
+        self
+    }
+
+    /// set id of event. client sends the last received id with `Last-Event-ID` header when reconnecting.
+    ///
+    /// # Panics
+    /// When id contains line break or null character.
+    pub fn id(mut self, id: impl Into<String>) -> Self {
+        let id = id.into();
+        assert!(
+            !id.contains(['\r', '\n', '\0']),
+            "event id must not contain line break or null character"
+        );
+        self.id = Some(id);
+        self
+    }
+
+    /// set type name of event.
+    ///
+    /// # Panics
+    /// When name contains line break.
+    pub fn event(mut self, name: impl Into<String>) -> Self {
+        let name = name.into();
+        assert!(!name.contains(['\r', '\n']), "event name must not contain line break");
+        self.event = Some(name);
+        self
+    }
+
+    /// set reconnection time of client in milliseconds precision.
+    pub fn retry(mut self, dur: Duration) -> Self {
+        self.retry = Some(dur);
+        self
+    }
+
+    /// set comment of event. comment is ignored by client and an event with only comment is useful for keeping
+    /// idle connection alive.
+    ///
+    /// # Panics
+    /// When comment contains line break.
+    pub fn comment(mut self, comment: impl Into<String>) -> Self {
+        let comment = comment.into();
+        assert!(
+            !comment.contains(['\r', '\n']),
+            "event comment must not contain line break"
+        );
+        self.comment = Some(comment);
+        self
+    }
+
+    fn encode(&self, buf: &mut BytesMut) {
+        fn field(buf: &mut BytesMut, name: &str, value: &str) {
+            buf.extend_from_slice(name.as_bytes());
+            buf.extend_from_slice(b": ");
+            buf.extend_from_slice(value.as_bytes());
+            buf.extend_from_slice(b"\n");
+        }
+
+        if let Some(ref comment) = self.comment {
+            field(buf, "", comment);
+        }
+        if let Some(ref event) = self.event {
+            field(buf, "event", event);
+        }
+        if let Some(ref id) = self.id {
+            field(buf, "id", id);
+        }
+        if let Some(ref retry) = self.retry {
+            field(buf, "retry", &retry.as_millis().to_string());
+        }
+        if let Some(ref data) = self.data {
+            // all of CRLF, LF and CR are line breaks of event stream.
+            for line in data
+                .split('\n')
+                .flat_map(|l| l.strip_suffix('\r').unwrap_or(l).split('\r'))
+            {
+                field(buf, "data", line);
+            }
+        }
+        buf.extend_from_slice(b"\n");
+    }
+}
+
+/// server-sent events responder.
+///
+/// The closure passed to [Sse::new] is called with the value of `Last-Event-ID` header sent by reconnecting
+/// client and the returned stream of [Event] is sent to client. A producer can resume from the event after it
+/// instead of replaying the whole feed.
+///
+/// # Backpressure
+/// Event stream is only polled when connection is ready to buffer more bytes and one event is encoded per poll.
+/// A slow client makes server stop polling the stream instead of buffering events unboundedly. To keep it that way
+/// the stream should produce events lazily or be fed by a bounded channel.
+///
+/// Every event is flushed to client once it's produced. See [Flush] for adjusting it.
+///
+/// # Examples
+/// ```rust
+/// # use std::convert::Infallible;
+/// # use futures_util::stream;
+/// # use xitca_web::{
+/// #   handler::{handler_service, sse::{Event, Sse}},
+/// #   App, WebContext,
+/// # };
+/// let feed = || async {
+///     Sse::new(|last_event_id: Option<String>| {
+///         // resume after the last event client received.
+///         let start = last_event_id.and_then(|id| id.parse::<usize>().ok()).map_or(0, |id| id + 1);
+///         let events = (start..3).map(|i| Event::new().id(i.to_string()).data(format!("event {i}")));
+///         stream::iter(events.map(Ok::<_, Infallible>))
+///     })
+/// };
+///
+/// App::new()
+///     .at("/", handler_service(feed))
+///     # .at("/nah", handler_service(|_: &WebContext<'_>| async { "used for infer type" }));
+/// ```
+pub struct Sse<F> {
+    func: F,
+    #[cfg(feature = "__server")]
+    end_on_shutdown: bool,
+}
+
+impl<F> fmt::Debug for Sse<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sse").finish()
+    }
+}
+
+impl<F> Sse<F> {
+    /// construct a new responder with closure producing event stream from last event id.
+    pub fn new<S, E>(func: F) -> Self
+    where
+        F: FnOnce(Option<String>) -> S,
+        S: Stream<Item = Result<Event, E>>,
+    {
+        Self {
+            func,
+            #[cfg(feature = "__server")]
+            end_on_shutdown: false,
+        }
+    }
+
+    /// end event stream when the server worker handling the request starts shutdown.
+    ///
+    /// Event stream is usually long lived and would hold graceful shutdown until the grace deadline set by server's
+    /// shutdown timeout. With this option the stream ends on the shutdown signal and client can reconnect to another
+    /// server with `Last-Event-ID` header. See [WebContext::on_shutdown] for detail.
+    #[cfg(feature = "__server")]
+    pub fn end_on_shutdown(mut self) -> Self {
+        self.end_on_shutdown = true;
+        self
+    }
+}
+
+impl<'r, C, B, F, S, E> Responder<WebContext<'r, C, B>> for Sse<F>
+where
+    F: FnOnce(Option<String>) -> S,
+    S: Stream<Item = Result<Event, E>> + 'static,
+    E: Into<BodyError> + 'static,
+{
+    type Response = WebResponse;
+    type Error = Error;
+
+    async fn respond(self, ctx: WebContext<'r, C, B>) -> Result<Self::Response, Self::Error> {
+        let last_event_id = ctx
+            .req()
+            .headers()
+            .get(LAST_EVENT_ID)
+            .and_then(|v| v.to_str().ok())
+            .map(String::from);
+        let res = ctx.into_response(ResponseBody::none());
+        self.map_with(res, last_event_id)
+    }
+
+    /// `Last-Event-ID` header is not available when [Sse] is not the first responder of a tuple and the closure
+    /// is called with `None`.
+    fn map(self, res: Self::Response) -> Result<Self::Response, Self::Error> {
+        self.map_with(res, None)
+    }
+}
+
+impl<F> Sse<F> {
+    fn map_with<S, E>(self, mut res: WebResponse, last_event_id: Option<String>) -> Result<WebResponse, Error>
+    where
+        F: FnOnce(Option<String>) -> S,
+        S: Stream<Item = Result<Event, E>> + 'static,
+        E: Into<BodyError> + 'static,
+    {
+        let headers = res.headers_mut();
+        headers.insert(CONTENT_TYPE, TEXT_EVENT_STREAM);
+        headers.insert(CACHE_CONTROL, NO_CACHE);
+        // every event is flushed to client once produced. flush hint set by prior responder takes precedence.
+        res.extensions_mut().get_or_insert(Flush::Item);
+        #[cfg(feature = "__server")]
+        let shutdown = self
+            .end_on_shutdown
+            .then(|| Box::pin(xitca_server::shutdown::on_shutdown()) as ShutdownFuture);
+        #[cfg(not(feature = "__server"))]
+        let shutdown = None;
+        let stream = EventStream {
+            stream: (self.func)(last_event_id),
+            shutdown,
+            buf: BytesMut::new(),
+        };
+        Ok(res.map(|_| ResponseBody::box_stream(stream)))
+    }
+}
+
+type ShutdownFuture = Pin<Box<dyn Future<Output = ()>>>;
+
+pin_project! {
+    struct EventStream<S> {
+        #[pin]
+        stream: S,
+        // stream ends when shutdown future resolves.
+        shutdown: Option<ShutdownFuture>,
+        buf: BytesMut,
+    }
+}
+
+impl<S, E> Stream for EventStream<S>
+where
+    S: Stream<Item = Result<Event, E>>,
+{
+    type Item = Result<Bytes, E>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+        if let Some(shutdown) = this.shutdown {
+            if shutdown.as_mut().poll(cx).is_ready() {
+                return Poll::Ready(None);
+            }
+        }
+        match ready!(this.stream.poll_next(cx)) {
+            Some(Ok(event)) => {
+                event.encode(this.buf);
+                Poll::Ready(Some(Ok(this.buf.split().freeze())))
+            }
+            Some(Err(e)) => Poll::Ready(Some(Err(e))),
+            None => Poll::Ready(None),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.stream.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use core::convert::Infallible;
+
+    use futures_util::stream;
+    use xitca_unsafe_collection::futures::NowOrPanic;
+
+    use crate::{
+        handler::handler_service,
+        http::{StatusCode, WebRequest},
+        service::Service,
+        test::collect_string_body,
+        App,
+    };
+
+    use super::*;
+
+    #[test]
+    fn encode() {
+        let mut buf = BytesMut::new();
+        Event::new()
+            .comment("hi")
+            .event("update")
+            .id("7")
+            .retry(Duration::from_secs(3))
+            .data("a\r\nb\rc\n")
+            .encode(&mut buf);
+        assert_eq!(
+            &buf[..],
+            b": hi\nevent: update\nid: 7\nretry: 3000\ndata: a\ndata: b\ndata: c\ndata: \n\n"
+        );
+
+        let mut buf = BytesMut::new();
+        Event::new().data("").encode(&mut buf);
+        assert_eq!(&buf[..], b"data: \n\n");
+    }
+
+    #[test]
+    fn last_event_id() {
+        let handler = || async {
+            Sse::new(|id: Option<String>| {
+                let start = id.and_then(|id| id.parse::<usize>().ok()).map_or(0, |id| id + 1);
+                stream::iter(
+                    (start..3).map(|i| Ok::<_, Infallible>(Event::new().id(i.to_string()).data(i.to_string()))),
+                )
+            })
+        };
+
+        let service = App::new()
+            .at("/", handler_service(handler))
+            .finish()
+            .call(())
+            .now_or_panic()
+            .unwrap();
+
+        let res = service.call(WebRequest::default()).now_or_panic().unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers().get(CONTENT_TYPE).unwrap(), "text/event-stream");
+        assert_eq!(res.headers().get(CACHE_CONTROL).unwrap(), "no-cache");
+        assert_eq!(res.extensions().get::<Flush>(), Some(&Flush::Item));
+        let body = collect_string_body(res.into_body()).now_or_panic().unwrap();
+        assert_eq!(body, "id: 0\ndata: 0\n\nid: 1\ndata: 1\n\nid: 2\ndata: 2\n\n");
+
+        let mut req = WebRequest::default();
+        req.headers_mut().insert(LAST_EVENT_ID, HeaderValue::from_static("1"));
+        let res = service.call(req).now_or_panic().unwrap();
+        let body = collect_string_body(res.into_body()).now_or_panic().unwrap();
+        assert_eq!(body, "id: 2\ndata: 2\n\n");
+    }
+
+    #[test]
+    fn end_on_shutdown() {
+        let stream = EventStream {
+            stream: stream::repeat_with(|| Ok::<_, Infallible>(Event::new().data("tick"))),
+            shutdown: Some(Box::pin(core::future::ready(()))),
+            buf: BytesMut::new(),
+        };
+        let body = collect_string_body(stream).now_or_panic().unwrap();
+        assert!(body.is_empty());
+    }
+}
@@ -45,6 +45,35 @@ pointer_impl!(std::sync::Arc<T>);
 
 /// App state extractor.
 /// S type must be the same with the type passed to App::with_xxx_state(S).
+///
+/// # Borrowing state directly
+/// A bare `&S` can not be an extractor argument. A blanket `FromRequest` impl for `&S` would overlap with the
+/// impls of other borrowed extractors like `&Method` and `&WebRequest<()>` when `S` happens to be the same type.
+/// `StateRef` derefs to `S` and can be destructured in function arguments for the same ergonomics. When the whole
+/// request context is needed `&WebContext<'_, S>` can be extracted and [`WebContext::state`] gives `&S`.
+///
+/// Borrowed extractors only look into request head and state. They compose freely with each other and with one
+/// body consuming extractor in the same function.
+///
+/// ```rust
+/// # use xitca_web::{handler::{handler_service, state::StateRef}, http::{Method, WebRequest}, App, WebContext};
+/// async fn handler(
+///     StateRef(state): StateRef<'_, String>,
+///     ctx: &WebContext<'_, String>,
+///     req: &WebRequest<()>,
+///     method: &Method,
+///     body: String,
+/// ) -> String {
+///     assert_eq!(state, ctx.state());
+///     assert_eq!(method, req.method());
+///     body
+/// }
+///
+/// App::new()
+///     .with_state(String::from("state"))
+///     .at("/", handler_service(handler))
+///     # .at("/infer", handler_service(|_: &WebContext<'_, String>| async{ "infer type" }));
+/// ```
 pub struct StateRef<'a, S>(pub &'a S)
 where
     S: ?Sized;
@@ -206,6 +235,47 @@ mod test {
             .unwrap();
     }
 
+    #[test]
+    fn state_extract_with_borrowed_and_body() {
+        use crate::{body::RequestBody, bytes::Bytes, http::Method};
+
+        async fn handler(
+            req: &WebRequest<()>,
+            StateRef(state): StateRef<'_, String>,
+            method: &Method,
+            body: String,
+            ctx: &WebContext<'_, State>,
+        ) -> String {
+            assert_eq!(method, req.method());
+            assert_eq!(state, &ctx.state().field1);
+            assert_eq!(body, "hello,world!");
+            body
+        }
+
+        let state = State {
+            field1: String::from("state"),
+            field2: 996,
+            field3: Arc::new(String::new()),
+        };
+
+        let res = App::new()
+            .with_state(state)
+            .at("/", get(handler_service(handler)))
+            .finish()
+            .call(())
+            .now_or_panic()
+            .ok()
+            .unwrap()
+            .call(
+                WebRequest::default()
+                    .map(|ext| ext.map_body(|_: ()| RequestBody::from(Bytes::from_static(b"hello,world!")))),
+            )
+            .now_or_panic()
+            .unwrap();
+
+        assert_eq!(res.status().as_u16(), 200);
+    }
+
     #[test]
     fn state_extract_deref() {
         use std::{any::Any, sync::Arc};
@@ -70,7 +70,8 @@ where
 
     fn map(self, res: Self::Response) -> Result<Self::Response, Self::Error> {
         let mut res = res.map(|_| self.0.into());
-        res.headers_mut().insert(CONTENT_TYPE, TEXT_UTF8);
+        // content type set by prior responder takes precedence.
+        res.headers_mut().entry(CONTENT_TYPE).or_insert(TEXT_UTF8);
         Ok(res)
     }
 }
@@ -0,0 +1,53 @@
+//! type extractor for taking over http/1 connection of CONNECT request.
+//!
+//! [OnUpgrade] can be extracted from CONNECT request served by http/1 dispatcher. See it's documentation for how
+//! connection is taken over. It's not available with `io-uring` feature.
+//!
+//! # Examples
+//! ```rust
+//! # use xitca_web::{
+//! #   handler::{handler_service, upgrade::OnUpgrade},
+//! #   http::StatusCode,
+//! #   route::connect,
+//! #   App, WebContext
+//! # };
+//! async fn tunnel(on_upgrade: OnUpgrade) -> StatusCode {
+//!     // connection is handed over after response is sent. await it in a separate task.
+//!     tokio::task::spawn_local(async move {
+//!         let Ok(io) = on_upgrade.await else { return };
+//!         // io implements AsyncRead and AsyncWrite traits. a proxy would connect to upstream and copy bytes
+//!         // between them with tokio::io::copy_bidirectional.
+//!         drop(io);
+//!     });
+//!     StatusCode::OK
+//! }
+//!
+//! App::new()
+//!     .at("/", connect(handler_service(tunnel)))
+//!     # .at("/infer", handler_service(|_: &WebContext<'_>| async{ "infer type" }))
+//!     ;
+//! ```
+
+use crate::{
+    context::WebContext,
+    error::{Error, ExtensionNotFound},
+    handler::FromRequest,
+};
+
+pub use xitca_http::h1::{OnUpgrade, Upgraded};
+
+// Extraction fails with "500 Internal Server Error" response when request is not a CONNECT request served by http/1
+// dispatcher.
+impl<'a, 'r, C, B> FromRequest<'a, WebContext<'r, C, B>> for OnUpgrade {
+    type Type<'b> = OnUpgrade;
+    type Error = Error;
+
+    #[inline]
+    async fn from_request(ctx: &'a WebContext<'r, C, B>) -> Result<Self, Self::Error> {
+        ctx.req()
+            .extensions()
+            .get::<OnUpgrade>()
+            .cloned()
+            .ok_or_else(|| Error::from_service(ExtensionNotFound::from_type::<OnUpgrade>()))
+    }
+}
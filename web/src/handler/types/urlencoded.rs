@@ -0,0 +1,322 @@
+//! shared urlencoded parsing for [Query](super::query::Query) and [Form](super::form::Form) extractors.
+
+use core::str::FromStr;
+
+use serde::de::{
+    self,
+    value::{Error, MapDeserializer, SeqDeserializer, StringDeserializer},
+    Deserialize, Deserializer, IntoDeserializer, Visitor,
+};
+
+/// parsing mode of urlencoded extractors.
+///
+/// Default mode is [UrlencodedMode::Strict]. Other mode can be opted in by attaching it to request extensions with
+/// [Extension](crate::middleware::Extension) middleware.
+///
+/// # Syntax of [UrlencodedMode::Nested]
+/// - `key=value` is a plain value.
+/// - `tag=a&tag=b` (repeated key) and `tag[]=a&tag[]=b` (empty bracket) are collected into a sequence. A single
+///   `tag=a` is accepted as a sequence of one element.
+/// - `filter[name]=x&filter[age]=1` is collected into a nested map/struct. Nesting can go up to 8 levels deep.
+/// - percent encoding of key and value is decoded before parsing brackets. `+` is decoded as space.
+///
+/// # Error
+/// Any of the following produces [serde::de::value::Error] and "400 Bad Request" response:
+/// - same key used as both plain value/sequence and nested map. e.g. `a=1&a[b]=2`.
+/// - nested key after empty bracket. e.g. `a[][b]=1`. Sequence of maps is not supported.
+/// - unbalanced bracket or text after closing bracket. e.g. `a[b=1`, `a[b]c=1`.
+/// - nesting deeper than 8 levels.
+/// - numeric index like `a[0]=1` is treated as map key and can not be deserialized into sequence.
+///
+/// # Examples
+/// ```rust
+/// # use xitca_web::{
+/// #   handler::{handler_service, query::{Query, UrlencodedMode}},
+/// #   middleware::Extension,
+/// #   App, WebContext
+/// # };
+/// #[derive(serde::Deserialize)]
+/// struct Filter {
+///     name: String,
+/// }
+///
+/// #[derive(serde::Deserialize)]
+/// struct Search {
+///     tag: Vec<String>,
+///     filter: Filter,
+/// }
+///
+/// // handle query like "?tag=a&tag=b&filter[name]=x"
+/// async fn handler(Query(_): Query<Search>) -> &'static str {
+///     "done"
+/// }
+///
+/// App::new()
+///     .at("/", handler_service(handler))
+///     # .at("/infer", handler_service(|_: &WebContext<'_>| async { "used for infer type" }))
+///     .enclosed(Extension::new(UrlencodedMode::Nested));
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum UrlencodedMode {
+    /// plain `serde_urlencoded` parsing. repeated key and bracketed key are not supported.
+    #[default]
+    Strict,
+    /// `serde_qs` style parsing with repeated key and bracketed key support.
+    Nested,
+}
+
+const MAX_DEPTH: usize = 8;
+
+pub(super) fn from_bytes<T>(mode: UrlencodedMode, bytes: &[u8]) -> Result<T, Error>
+where
+    T: for<'de> Deserialize<'de>,
+{
+    match mode {
+        UrlencodedMode::Strict => serde_urlencoded::from_bytes(bytes),
+        UrlencodedMode::Nested => {
+            let pairs = serde_urlencoded::from_bytes::<Vec<(String, String)>>(bytes)?;
+            let mut root = Vec::new();
+            for (key, value) in pairs {
+                let path = parse_key(&key)?;
+                insert(&mut root, &path, value)?;
+            }
+            T::deserialize(Node::Map(root))
+        }
+    }
+}
+
+// split key like "a[b][]" into ["a", "b", ""].
+fn parse_key(key: &str) -> Result<Vec<&str>, Error> {
+    let (head, mut rest) = match key.find('[') {
+        Some(idx) => key.split_at(idx),
+        None => return Ok(vec![key]),
+    };
+
+    let mut path = vec![head];
+    while !rest.is_empty() {
+        let end = rest
+            .strip_prefix('[')
+            .and_then(|r| r.find(']'))
+            .ok_or_else(|| de::Error::custom(format_args!("malformed bracket in key: {key}")))?;
+        path.push(&rest[1..end + 1]);
+        rest = &rest[end + 2..];
+        if path.len() > MAX_DEPTH + 1 {
+            return Err(de::Error::custom(format_args!("key nested too deep: {key}")));
+        }
+    }
+
+    Ok(path)
+}
+
+enum Node {
+    Value(String),
+    Seq(Vec<Node>),
+    Map(Vec<(String, Node)>),
+}
+
+fn insert(map: &mut Vec<(String, Node)>, path: &[&str], value: String) -> Result<(), Error> {
+    let (key, rest) = path.split_first().expect("path must not be empty");
+
+    let idx = match map.iter().position(|(k, _)| k == key) {
+        Some(idx) => idx,
+        None => {
+            let node = match rest {
+                [] => Node::Value(value),
+                [""] => Node::Seq(vec![Node::Value(value)]),
+                ["", ..] => return Err(conflict(key)),
+                _ => {
+                    let mut inner = Vec::new();
+                    insert(&mut inner, rest, value)?;
+                    Node::Map(inner)
+                }
+            };
+            map.push((key.to_string(), node));
+            return Ok(());
+        }
+    };
+
+    let node = &mut map[idx].1;
+    match (node, rest) {
+        (Node::Seq(seq), [] | [""]) => seq.push(Node::Value(value)),
+        (node @ Node::Value(_), [] | [""]) => {
+            let first = core::mem::replace(node, Node::Seq(Vec::new()));
+            *node = Node::Seq(vec![first, Node::Value(value)]);
+        }
+        (Node::Map(inner), [next, ..]) if !next.is_empty() => insert(inner, rest, value)?,
+        _ => return Err(conflict(key)),
+    }
+
+    Ok(())
+}
+
+fn conflict(key: &str) -> Error {
+    de::Error::custom(format_args!("ambiguous value for key: {key}"))
+}
+
+impl<'de> IntoDeserializer<'de, Error> for Node {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
+    }
+}
+
+macro_rules! deserialize_parse {
+    ($($method: ident => $visit: ident),*) => {
+        $(
+            fn $method<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+            where
+                V: Visitor<'de>,
+            {
+                match self {
+                    Self::Value(v) => visitor.$visit(parse(&v)?),
+                    node => node.deserialize_any(visitor),
+                }
+            }
+        )*
+    };
+}
+
+fn parse<T>(v: &str) -> Result<T, Error>
+where
+    T: FromStr,
+    T::Err: core::fmt::Display,
+{
+    v.parse().map_err(de::Error::custom)
+}
+
+impl<'de> Deserializer<'de> for Node {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Self::Value(v) => visitor.visit_string(v),
+            Self::Seq(seq) => visitor.visit_seq(SeqDeserializer::new(seq.into_iter())),
+            Self::Map(map) => visitor.visit_map(MapDeserializer::new(map.into_iter())),
+        }
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Self::Value(v) => visitor.visit_seq(SeqDeserializer::new(core::iter::once(Self::Value(v)))),
+            node => node.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V>(self, _: &'static str, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _: &'static str,
+        _: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Self::Value(v) => visitor.visit_enum(StringDeserializer::<Error>::new(v)),
+            _ => Err(de::Error::custom("expect plain value for enum")),
+        }
+    }
+
+    deserialize_parse! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+        deserialize_char => visit_char
+    }
+
+    serde::forward_to_deserialize_any! {
+        i128 u128 str string bytes byte_buf unit unit_struct tuple
+        tuple_struct map struct identifier ignored_any
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[derive(serde::Deserialize, Debug, PartialEq)]
+    struct Filter {
+        name: String,
+        age: u8,
+    }
+
+    #[derive(serde::Deserialize, Debug, PartialEq)]
+    struct Search {
+        tag: Vec<String>,
+        filter: Filter,
+        page: Option<u32>,
+    }
+
+    fn nested<T: for<'de> Deserialize<'de>>(s: &str) -> Result<T, Error> {
+        from_bytes(UrlencodedMode::Nested, s.as_bytes())
+    }
+
+    #[test]
+    fn repeated_and_bracketed() {
+        let s = nested::<Search>("tag=a&tag=b&filter[name]=x%20y&filter[age]=14&page=2").unwrap();
+        assert_eq!(s.tag, ["a", "b"]);
+        assert_eq!(
+            s.filter,
+            Filter {
+                name: "x y".into(),
+                age: 14
+            }
+        );
+        assert_eq!(s.page, Some(2));
+
+        let s = nested::<Search>("tag[]=a&filter%5Bname%5D=x&filter[age]=1").unwrap();
+        assert_eq!(s.tag, ["a"]);
+        assert_eq!(s.filter.name, "x");
+        assert_eq!(s.page, None);
+
+        let s = nested::<HashMap<String, Vec<u8>>>("a=1&a=2&b=3").unwrap();
+        assert_eq!(s["a"], [1, 2]);
+        assert_eq!(s["b"], [3]);
+    }
+
+    #[test]
+    fn ambiguous() {
+        assert!(nested::<HashMap<String, String>>("a=1&a[b]=2").is_err());
+        assert!(nested::<HashMap<String, String>>("a[b]=1&a=2").is_err());
+        assert!(nested::<HashMap<String, String>>("a[][b]=1").is_err());
+        assert!(nested::<HashMap<String, String>>("a[b=1").is_err());
+        assert!(nested::<HashMap<String, String>>("a[b]c=1").is_err());
+        assert!(nested::<HashMap<String, String>>("a[1][2][3][4][5][6][7][8][9]=1").is_err());
+    }
+
+    #[test]
+    fn strict() {
+        assert!(from_bytes::<HashMap<String, Vec<String>>>(UrlencodedMode::Strict, b"a=1&a=2").is_err());
+    }
+}
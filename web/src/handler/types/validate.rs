@@ -0,0 +1,165 @@
+//! type extractor for validating the output of other type extractors.
+
+use core::{fmt, marker::PhantomData, ops::Deref};
+
+use crate::{context::WebContext, error::Error, handler::FromRequest};
+
+#[cfg(feature = "validator")]
+mod validator;
+
+#[cfg(feature = "validator")]
+pub use self::validator::Validator;
+
+/// validation logic for value produced by type extractor.
+///
+/// See [Validated] for usage.
+pub trait Validate<T> {
+    /// error type produced on validation failure. it must be convertible to [Error] and it's http response
+    /// is what [Validated] extractor produces. [ValidationError] is a ready to use "422 Unprocessable Entity"
+    /// error type.
+    ///
+    /// [ValidationError]: crate::error::ValidationError
+    type Error;
+
+    /// validate extracted value.
+    fn validate(value: &T) -> Result<(), Self::Error>;
+}
+
+/// Extract type `T` and validate it with `V`'s [Validate] impl.
+///
+/// Only owned type extractors(where the extractor type does not borrow from request) can be validated.
+///
+/// # Examples
+/// ```rust
+/// # use xitca_web::{
+/// #   error::ValidationError,
+/// #   handler::{handler_service, validate::{Validate, Validated}},
+/// #   App, WebContext,
+/// # };
+/// // validator type.
+/// struct NotEmpty;
+///
+/// // validate request body in String type must not be empty.
+/// impl Validate<String> for NotEmpty {
+///     type Error = ValidationError;
+///
+///     fn validate(value: &String) -> Result<(), Self::Error> {
+///         if value.is_empty() {
+///             return Err(ValidationError::new("body must not be empty"));
+///         }
+///         Ok(())
+///     }
+/// }
+///
+/// async fn handler(Validated(body, _): Validated<String, NotEmpty>) -> String {
+///     body
+/// }
+///
+/// App::new()
+///     .at("/", handler_service(handler))
+///     # .at("/nah", handler_service(|_: &WebContext<'_>| async { "used for infer type" }));
+/// ```
+pub struct Validated<T, V>(pub T, pub PhantomData<fn(V)>);
+
+impl<T, V> Validated<T, V> {
+    /// consume self and return inner validated value.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T, V> fmt::Debug for Validated<T, V>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Validated").field(&self.0).finish()
+    }
+}
+
+impl<T, V> Deref for Validated<T, V> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<'a, 'r, C, B, T, V, E> FromRequest<'a, WebContext<'r, C, B>> for Validated<T, V>
+where
+    T: for<'a2> FromRequest<'a2, WebContext<'r, C, B>, Type<'a2> = T, Error = E>,
+    V: Validate<T>,
+    Error: From<E> + From<V::Error>,
+{
+    type Type<'b> = Validated<T, V>;
+    type Error = Error;
+
+    #[inline]
+    async fn from_request(ctx: &'a WebContext<'r, C, B>) -> Result<Self, Self::Error> {
+        let value = T::from_request(ctx).await?;
+        V::validate(&value)?;
+        Ok(Validated(value, PhantomData))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use xitca_unsafe_collection::futures::NowOrPanic;
+
+    use crate::{
+        error::ValidationError,
+        http::{Method, StatusCode},
+    };
+
+    use super::*;
+
+    struct IsGet;
+
+    impl Validate<Method> for IsGet {
+        type Error = ValidationError;
+
+        fn validate(value: &Method) -> Result<(), Self::Error> {
+            if value != Method::GET {
+                return Err(ValidationError::new("method must be GET"));
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn validate() {
+        let mut ctx = WebContext::new_test(());
+        let mut ctx = ctx.as_web_ctx();
+
+        let Validated(method, _) = Validated::<Method, IsGet>::from_request(&ctx).now_or_panic().unwrap();
+        assert_eq!(method, Method::GET);
+
+        *ctx.req_mut().method_mut() = Method::POST;
+
+        let e = Validated::<Method, IsGet>::from_request(&ctx)
+            .now_or_panic()
+            .unwrap_err();
+        assert!(e.upcast().downcast_ref::<ValidationError>().is_some());
+
+        let res = crate::service::Service::call(&e, ctx).now_or_panic().unwrap();
+        assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[test]
+    fn optional() {
+        let mut ctx = WebContext::new_test(());
+        let mut ctx = ctx.as_web_ctx();
+
+        let v = Option::<Validated<Method, IsGet>>::from_request(&ctx)
+            .now_or_panic()
+            .unwrap();
+        assert_eq!(v.unwrap().into_inner(), Method::GET);
+
+        // present but invalid value is not swallowed by Option.
+        *ctx.req_mut().method_mut() = Method::POST;
+        let e = Option::<Validated<Method, IsGet>>::from_request(&ctx)
+            .now_or_panic()
+            .unwrap_err();
+        assert!(e.upcast().downcast_ref::<ValidationError>().is_some());
+    }
+}
@@ -0,0 +1,205 @@
+use core::{convert::Infallible, ops::Deref};
+
+use serde_json::{Map, Value};
+use validator::{ValidationErrors, ValidationErrorsKind};
+
+use crate::{
+    bytes::Bytes,
+    context::WebContext,
+    error::error_from_service,
+    handler::json::JsonErrorFormat,
+    http::{const_header_value::JSON, header::CONTENT_TYPE, StatusCode, WebResponse},
+    service::Service,
+};
+
+use super::Validate;
+
+/// [Validate] type backed by [validator::Validate] trait. It validates type extractor dereferencing to a type
+/// implementing [validator::Validate]. e.g. [Json], [Form] and [Query].
+///
+/// On validation failure [ValidationErrors] is produced as error and it's rendered to a "422 Unprocessable Entity"
+/// response with json object body containing error of every invalid field. See [JsonErrorFormat] for detail of
+/// the body format.
+///
+/// # Examples
+/// ```rust
+/// # use serde::Deserialize;
+/// # use xitca_web::{
+/// #   handler::{handler_service, json::Json, validate::{Validated, Validator}},
+/// #   App, WebContext,
+/// # };
+/// #[derive(Deserialize, validator::Validate)]
+/// struct User {
+///     #[validate(length(min = 1, max = 32))]
+///     name: String,
+///     #[validate(range(min = 18))]
+///     age: u8,
+/// }
+///
+/// async fn handler(Validated(Json(user), _): Validated<Json<User>, Validator>) -> String {
+///     format!("hello, {}", user.name)
+/// }
+///
+/// App::new()
+///     .at("/", handler_service(handler))
+///     # .at("/infer", handler_service(|_: &WebContext<'_>| async { "used for infer type" }))
+///     ;
+/// ```
+///
+/// [Json]: crate::handler::json::Json
+/// [Form]: crate::handler::form::Form
+/// [Query]: crate::handler::query::Query
+pub struct Validator;
+
+impl<T> Validate<T> for Validator
+where
+    T: Deref,
+    T::Target: validator::Validate,
+{
+    type Error = ValidationErrors;
+
+    #[inline]
+    fn validate(value: &T) -> Result<(), Self::Error> {
+        validator::Validate::validate(value.deref())
+    }
+}
+
+error_from_service!(ValidationErrors);
+
+/// "422 Unprocessable Entity" response with json object body.
+///
+/// By default([JsonErrorFormat::Blank]) the body maps every invalid field to an array of it's error messages.
+/// Error code of validation rule is used when it has no message. Nested struct and list fields are mapped to
+/// nested objects. e.g. `{"name": ["length"], "address": {"city": ["city must not be empty"]}}`.
+///
+/// With [JsonErrorFormat::Verbose] the body is [ValidationErrors] serialized as is. It additionally contains the
+/// parameters of validation rules. e.g. the min and max value of length check.
+impl<'r, C, B> Service<WebContext<'r, C, B>> for ValidationErrors {
+    type Response = WebResponse;
+    type Error = Infallible;
+
+    async fn call(&self, ctx: WebContext<'r, C, B>) -> Result<Self::Response, Self::Error> {
+        let format = ctx
+            .req()
+            .extensions()
+            .get::<JsonErrorFormat>()
+            .copied()
+            .unwrap_or_default();
+        let body = match format {
+            JsonErrorFormat::Blank => Value::Object(fields(self)),
+            JsonErrorFormat::Verbose => serde_json::to_value(self).unwrap_or_default(),
+        };
+        let mut res = ctx.into_response(Bytes::from(body.to_string()));
+        *res.status_mut() = StatusCode::UNPROCESSABLE_ENTITY;
+        res.headers_mut().insert(CONTENT_TYPE, JSON);
+        Ok(res)
+    }
+}
+
+fn fields(errors: &ValidationErrors) -> Map<String, Value> {
+    errors
+        .errors()
+        .iter()
+        .map(|(field, kind)| {
+            let value = match kind {
+                ValidationErrorsKind::Field(errors) => errors
+                    .iter()
+                    .map(|e| Value::from(e.message.as_ref().unwrap_or(&e.code).to_string()))
+                    .collect(),
+                ValidationErrorsKind::Struct(errors) => Value::Object(fields(errors)),
+                ValidationErrorsKind::List(list) => Value::Object(
+                    list.iter()
+                        .map(|(idx, errors)| (idx.to_string(), Value::Object(fields(errors))))
+                        .collect(),
+                ),
+            };
+            (field.to_string(), value)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use std::borrow::Cow;
+
+    use validator::ValidationError;
+    use xitca_unsafe_collection::futures::NowOrPanic;
+
+    use crate::{
+        error::Error,
+        handler::{json::Json, validate::Validated, FromRequest},
+        http::header::CONTENT_LENGTH,
+        test::collect_string_body,
+    };
+
+    use super::*;
+
+    #[derive(Debug, serde::Deserialize)]
+    struct User {
+        name: String,
+    }
+
+    impl validator::Validate for User {
+        fn validate(&self) -> Result<(), ValidationErrors> {
+            let mut errors = ValidationErrors::new();
+            if self.name.is_empty() {
+                errors.add("name", ValidationError::new("length"));
+            }
+            if self.name.starts_with(' ') {
+                let mut e = ValidationError::new("trim");
+                e.message = Some(Cow::Borrowed("name must be trimmed"));
+                errors.add("name", e);
+            }
+            if errors.is_empty() {
+                Ok(())
+            } else {
+                Err(errors)
+            }
+        }
+    }
+
+    type ValidUser = Validated<Json<User>, Validator>;
+
+    fn extract(body: &'static str) -> Result<ValidUser, Error> {
+        let mut ctx = WebContext::new_test(());
+        let mut ctx = ctx.as_web_ctx();
+
+        ctx.req_mut().headers_mut().insert(CONTENT_TYPE, JSON);
+        ctx.req_mut().headers_mut().insert(CONTENT_LENGTH, body.len().into());
+        *ctx.body_borrow_mut() = body.as_bytes().into();
+
+        let res = ValidUser::from_request(&ctx).now_or_panic();
+        res
+    }
+
+    fn render(e: Error, format: JsonErrorFormat) -> WebResponse {
+        let mut ctx = WebContext::new_test(());
+        let mut ctx = ctx.as_web_ctx();
+        ctx.req_mut().extensions_mut().insert(format);
+        let res = e.call(ctx).now_or_panic().unwrap();
+        res
+    }
+
+    #[test]
+    fn validator() {
+        let Validated(Json(user), _) = extract(r#"{"name":"foo"}"#).unwrap();
+        assert_eq!(user.name, "foo");
+
+        let res = render(extract(r#"{"name":" "}"#).unwrap_err(), JsonErrorFormat::Blank);
+        assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(res.headers().get(CONTENT_TYPE).unwrap(), JSON);
+        let body = collect_string_body(res.into_body()).now_or_panic().unwrap();
+        let value = serde_json::from_str::<Value>(&body).unwrap();
+        assert_eq!(value, serde_json::json!({ "name": ["name must be trimmed"] }));
+
+        let res = render(extract(r#"{"name":""}"#).unwrap_err(), JsonErrorFormat::Verbose);
+        assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body = collect_string_body(res.into_body()).now_or_panic().unwrap();
+        let value = serde_json::from_str::<Value>(&body).unwrap();
+        assert_eq!(value["name"][0]["code"], "length");
+
+        // error of inner extractor is passed through.
+        let res = render(extract(r#"{"name":996}"#).unwrap_err(), JsonErrorFormat::Blank);
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    }
+}
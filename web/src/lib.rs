@@ -306,6 +306,43 @@ pub mod codegen {
     /// ```
     pub use xitca_codegen::State;
 
+    /// Derive macro for enum type where every variant is rendered to a different response.
+    ///
+    /// Variant can be unit or carry exactly one field. Field is rendered by it's own
+    /// [Responder](crate::handler::Responder) impl and unit variant is rendered to empty response body. Optional
+    /// `#[responder(status = <u16>, content_type = "<value>")]` attribute on variant overrides status code and
+    /// `content-type` header of the response.
+    ///
+    /// # Example:
+    /// ```rust
+    /// # use xitca_web::{codegen::Responder, handler::{handler_service, path::PathRef}, App, WebContext};
+    /// #[derive(Responder)]
+    /// enum ApiResult<T> {
+    ///     // render inner type as is.
+    ///     Ok(T),
+    ///     // empty body with 404 status code.
+    ///     #[responder(status = 404)]
+    ///     NotFound,
+    ///     // text body with 409 status code.
+    ///     #[responder(status = 409, content_type = "text/plain; charset=utf-8")]
+    ///     Conflict(String),
+    /// }
+    ///
+    /// async fn handler(PathRef(path): PathRef<'_>) -> ApiResult<&'static str> {
+    ///     match path {
+    ///         "/" => ApiResult::Ok("996"),
+    ///         "/conflict" => ApiResult::Conflict(String::from("conflict")),
+    ///         _ => ApiResult::NotFound,
+    ///     }
+    /// }
+    ///
+    /// App::new()
+    ///     .at("/", handler_service(handler))
+    /// #   .at("/infer", handler_service(|_: &WebContext<'_>| async { "used for infer type" }))
+    ///     ;
+    /// ```
+    pub use xitca_codegen::Responder;
+
     pub use xitca_codegen::route;
 
     pub use xitca_codegen::error_impl;
@@ -354,13 +391,16 @@ pub mod route {
     //! # Ok(())
     //! # }
     //! ```
-    pub use xitca_http::util::service::route::{connect, delete, get, head, options, patch, post, put, trace, Route};
+    pub use xitca_http::util::service::{
+        route::{connect, delete, get, head, options, patch, post, put, trace, Route},
+        router::RouteInfo,
+    };
 }
 
-pub use app::{App, AppObject, NestApp};
+pub use app::{App, AppObject, AppService, NestApp};
 pub use body::BodyStream;
-pub use context::WebContext;
+pub use context::{RequestSnapshot, WebContext};
 #[cfg(feature = "__server")]
-pub use server::HttpServer;
+pub use server::{HttpServer, RuntimeModel};
 
 pub use xitca_http::bytes;
@@ -0,0 +1,279 @@
+//! response body buffering middleware.
+
+use crate::service::Service;
+
+/// default threshold of [Buffer] middleware in bytes.
+pub const DEFAULT_THRESHOLD: usize = 8 * 1024;
+
+/// middleware for sending small streaming response body with fixed length.
+///
+/// Response body with unknown size is sent with `transfer-encoding: chunked` header. `Buffer` polls such body
+/// ahead of sending and when the body ends within threshold it's sent as a single chunk with `content-length`
+/// header instead. When body exceeds threshold the already buffered part is sent as first chunk and the rest is
+/// streamed as is.
+///
+/// Response with known body size and response with `content-type: text/event-stream` header are passed through
+/// without buffering. Other long lived streaming response(for example a slow stream producing small chunks
+/// occasionally) would be delayed until threshold is reached and should not be enclosed by this middleware.
+///
+/// # Type mutation
+/// `Buffer` would mutate response body type from `B` to `BufferBody<B>`. Service enclosed by it must be able to
+/// handle it's mutation or utilize [TypeEraser] to erase the mutation.
+/// For more explanation please reference [type mutation](crate::middleware#type-mutation).
+///
+/// # Examples
+/// ```rust
+/// # use xitca_web::{handler::handler_service, middleware::buffer::Buffer, App, WebContext};
+/// App::new()
+///     .at("/", handler_service(|| async { "hello,world!" }))
+///     # .at("/infer", handler_service(|_: &WebContext<'_>| async{ "infer type" }))
+///     // buffer streaming response body up to 4KiB.
+///     .enclosed(Buffer::new().threshold(4096));
+/// ```
+///
+/// [TypeEraser]: crate::middleware::eraser::TypeEraser
+#[derive(Clone, Copy)]
+pub struct Buffer {
+    threshold: usize,
+}
+
+impl Default for Buffer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Buffer {
+    /// construct middleware with [DEFAULT_THRESHOLD].
+    pub const fn new() -> Self {
+        Self {
+            threshold: DEFAULT_THRESHOLD,
+        }
+    }
+
+    /// set max size in bytes a streaming response body can be buffered.
+    pub const fn threshold(mut self, threshold: usize) -> Self {
+        self.threshold = threshold;
+        self
+    }
+}
+
+impl<S, E> Service<Result<S, E>> for Buffer {
+    type Response = service::BufferService<S>;
+    type Error = E;
+
+    async fn call(&self, res: Result<S, E>) -> Result<Self::Response, Self::Error> {
+        res.map(|service| service::BufferService {
+            service,
+            threshold: self.threshold,
+        })
+    }
+}
+
+pub(super) use service::BufferBody;
+
+mod service {
+    use core::{
+        future::poll_fn,
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    use futures_core::stream::Stream;
+    use pin_project_lite::pin_project;
+    use xitca_http::body::BodySize;
+
+    use crate::{
+        bytes::{Bytes, BytesMut},
+        error::BodyError,
+        http::{header::CONTENT_TYPE, WebResponse},
+        service::{ready::ReadyService, Service},
+    };
+
+    pub struct BufferService<S> {
+        pub(super) service: S,
+        pub(super) threshold: usize,
+    }
+
+    impl<S, Req, ResB, E> Service<Req> for BufferService<S>
+    where
+        S: Service<Req, Response = WebResponse<ResB>>,
+        ResB: Stream<Item = Result<Bytes, E>> + Unpin,
+        E: Into<BodyError>,
+    {
+        type Response = WebResponse<BufferBody<ResB>>;
+        type Error = S::Error;
+
+        async fn call(&self, req: Req) -> Result<Self::Response, Self::Error> {
+            let res = self.service.call(req).await?;
+
+            let is_event_stream = res
+                .headers()
+                .get(CONTENT_TYPE)
+                .is_some_and(|v| v.as_bytes().starts_with(b"text/event-stream"));
+
+            if is_event_stream || !matches!(BodySize::from_stream(res.body()), BodySize::Stream) {
+                return Ok(res.map(BufferBody::new));
+            }
+
+            let (parts, mut body) = res.into_parts();
+            let mut buf = BytesMut::new();
+
+            let body = loop {
+                match poll_fn(|cx| Pin::new(&mut body).poll_next(cx)).await {
+                    Some(Ok(bytes)) => {
+                        buf.extend_from_slice(&bytes);
+                        if buf.len() > self.threshold {
+                            break BufferBody::partial(buf.freeze(), body);
+                        }
+                    }
+                    Some(Err(e)) => break BufferBody::error(buf.freeze(), e.into()),
+                    None => break BufferBody::buffered(buf.freeze()),
+                }
+            };
+
+            Ok(WebResponse::from_parts(parts, body))
+        }
+    }
+
+    impl<S> ReadyService for BufferService<S>
+    where
+        S: ReadyService,
+    {
+        type Ready = S::Ready;
+
+        #[inline]
+        async fn ready(&self) -> Self::Ready {
+            self.service.ready().await
+        }
+    }
+
+    pin_project! {
+        /// response body type produced by [Buffer](super::Buffer) and [Cache](crate::middleware::cache::Cache)
+        /// middlewares.
+        pub struct BufferBody<B> {
+            head: Option<Bytes>,
+            err: Option<BodyError>,
+            #[pin]
+            stream: Option<B>,
+        }
+    }
+
+    impl<B> BufferBody<B> {
+        pub(in crate::middleware) fn new(stream: B) -> Self {
+            Self {
+                head: None,
+                err: None,
+                stream: Some(stream),
+            }
+        }
+
+        pub(in crate::middleware) fn partial(head: Bytes, stream: B) -> Self {
+            Self {
+                head: Some(head),
+                err: None,
+                stream: Some(stream),
+            }
+        }
+
+        pub(in crate::middleware) fn error(head: Bytes, err: BodyError) -> Self {
+            Self {
+                head: (!head.is_empty()).then_some(head),
+                err: Some(err),
+                stream: None,
+            }
+        }
+
+        pub(in crate::middleware) fn buffered(head: Bytes) -> Self {
+            Self {
+                head: Some(head),
+                err: None,
+                stream: None,
+            }
+        }
+    }
+
+    impl<B, E> Stream for BufferBody<B>
+    where
+        B: Stream<Item = Result<Bytes, E>>,
+        E: Into<BodyError>,
+    {
+        type Item = Result<Bytes, BodyError>;
+
+        fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+            let this = self.project();
+
+            if let Some(bytes) = this.head.take() {
+                return Poll::Ready(Some(Ok(bytes)));
+            }
+
+            if let Some(e) = this.err.take() {
+                return Poll::Ready(Some(Err(e)));
+            }
+
+            match this.stream.as_pin_mut() {
+                Some(stream) => stream.poll_next(cx).map(|opt| opt.map(|res| res.map_err(Into::into))),
+                None => Poll::Ready(None),
+            }
+        }
+
+        fn size_hint(&self) -> (usize, Option<usize>) {
+            match (&self.head, &self.stream) {
+                (None, Some(stream)) if self.err.is_none() => stream.size_hint(),
+                (Some(head), Some(_)) => (head.len(), None),
+                (head, None) if self.err.is_none() => {
+                    let len = head.as_ref().map(Bytes::len).unwrap_or(0);
+                    (len, Some(len))
+                }
+                _ => (0, None),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use futures_util::stream;
+    use xitca_http::body::BodySize;
+    use xitca_unsafe_collection::futures::NowOrPanic;
+
+    use crate::{
+        body::ResponseBody, bytes::Bytes, error::BodyError, http::WebResponse, service::fn_service, test::collect_body,
+    };
+
+    use super::*;
+
+    // stream of n chunks with unknown size.
+    fn chunks(n: usize) -> ResponseBody {
+        ResponseBody::box_stream(stream::unfold(n, |n| async move {
+            (n > 0).then(|| (Ok::<_, BodyError>(Bytes::from_static(b"996")), n - 1))
+        }))
+    }
+
+    #[test]
+    fn buffer_small_body() {
+        let service = fn_service(|n: usize| async move { Ok::<_, ()>(WebResponse::new(chunks(n))) })
+            .call(())
+            .now_or_panic()
+            .unwrap();
+
+        let service = Buffer::new()
+            .threshold(8)
+            .call(Ok::<_, ()>(service))
+            .now_or_panic()
+            .unwrap();
+
+        let res = service.call(2).now_or_panic().unwrap();
+        assert_eq!(BodySize::from_stream(res.body()), BodySize::Sized(6));
+        let body = collect_body(res.into_body()).now_or_panic().unwrap();
+        assert_eq!(body, b"996996");
+
+        let res = service.call(4).now_or_panic().unwrap();
+        assert_eq!(BodySize::from_stream(res.body()), BodySize::Stream);
+        let body = collect_body(res.into_body()).now_or_panic().unwrap();
+        assert_eq!(body, b"996996996996");
+
+        let res = service.call(0).now_or_panic().unwrap();
+        assert_eq!(BodySize::from_stream(res.body()), BodySize::Sized(0));
+    }
+}
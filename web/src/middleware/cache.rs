@@ -0,0 +1,570 @@
+//! response caching middleware.
+
+use core::time::Duration;
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+
+use crate::{
+    bytes::Bytes,
+    http::{
+        header::{HeaderMap, HeaderValue, AGE},
+        StatusCode, WebResponse,
+    },
+    service::Service,
+};
+
+use super::buffer::BufferBody;
+
+/// default max size of response body [Cache] middleware would buffer and store in bytes.
+pub const DEFAULT_MAX_BODY_SIZE: usize = 1024 * 1024;
+
+/// default max count of entries [MemoryStore] would hold.
+pub const DEFAULT_CAPACITY: usize = 1024;
+
+/// middleware for caching response of GET request in a [CacheStore].
+///
+/// Response is cached by request's method, host, path and query and served from store until it's expired. Host is
+/// taken from request's `Host` header or uri authority when header is absent.
+///
+/// # Cacheable response
+/// A response is stored when all following conditions are met:
+/// - request is GET without `Authorization` header and without `no-cache` or `no-store` `Cache-Control` directive.
+/// - response status passes [Cache::cacheable_status] filter. By default only 2xx success status is cached and
+///   error response is never cached.
+/// - response has no `Set-Cookie` header, no `Vary` header and no `no-store`, `no-cache` or `private`
+///   `Cache-Control` directive. Response varied by request headers is not cached as it can not be told apart by
+///   cache key.
+/// - response body is not larger than [Cache::max_body_size]. Streaming body is buffered up to the limit and
+///   streamed as is when exceeding it.
+///
+/// Time to live of cached response is taken from `s-maxage` or `max-age` `Cache-Control` directive of response
+/// and falls back to the default ttl given to [Cache::new].
+///
+/// # Invalidation
+/// A clone of Cache shares the same store and can be used to invalidate entries with [Cache::invalidate] and
+/// [Cache::clear]. Successful POST, PUT, PATCH and DELETE request invalidates cached response of the same host,
+/// path and query automatically.
+///
+/// # Type mutation
+/// `Cache` would mutate response body type from `B` to `BufferBody<B>`. Service enclosed by it must be able to
+/// handle it's mutation or utilize [TypeEraser] to erase the mutation.
+/// For more explanation please reference [type mutation](crate::middleware#type-mutation).
+///
+/// # Examples
+/// ```rust
+/// # use core::time::Duration;
+/// # use xitca_web::{handler::handler_service, middleware::cache::Cache, App, WebContext};
+/// // cache response for 60 seconds unless handler specify it's own max-age.
+/// let cache = Cache::new(Duration::from_secs(60));
+///
+/// // a clone of cache can be used to invalidate cached response.
+/// let handle = cache.clone();
+///
+/// App::new()
+///     .at("/", handler_service(|| async { "hello,world!" }))
+///     # .at("/infer", handler_service(|_: &WebContext<'_>| async{ "infer type" }))
+///     .enclosed(cache);
+///
+/// handle.invalidate("example.com", "/");
+/// ```
+///
+/// [TypeEraser]: crate::middleware::eraser::TypeEraser
+pub struct Cache<S = MemoryStore> {
+    store: Arc<S>,
+    ttl: Duration,
+    max_body_size: usize,
+    cacheable_status: fn(StatusCode) -> bool,
+}
+
+impl<S> Clone for Cache<S> {
+    fn clone(&self) -> Self {
+        Self {
+            store: self.store.clone(),
+            ttl: self.ttl,
+            max_body_size: self.max_body_size,
+            cacheable_status: self.cacheable_status,
+        }
+    }
+}
+
+impl Cache {
+    /// construct middleware with [MemoryStore] and given default time to live of cached response.
+    pub fn new(ttl: Duration) -> Self {
+        Self::with_store(MemoryStore::new(DEFAULT_CAPACITY), ttl)
+    }
+}
+
+impl<S> Cache<S>
+where
+    S: CacheStore,
+{
+    /// construct middleware with given store and default time to live of cached response.
+    pub fn with_store(store: S, ttl: Duration) -> Self {
+        Self {
+            store: Arc::new(store),
+            ttl,
+            max_body_size: DEFAULT_MAX_BODY_SIZE,
+            cacheable_status: |status| status.is_success(),
+        }
+    }
+
+    /// set max size in bytes a response body can be buffered and cached.
+    /// Default to [DEFAULT_MAX_BODY_SIZE].
+    pub fn max_body_size(mut self, size: usize) -> Self {
+        self.max_body_size = size;
+        self
+    }
+
+    /// set filter of response status that can be cached. Default to [StatusCode::is_success].
+    pub fn cacheable_status(mut self, filter: fn(StatusCode) -> bool) -> Self {
+        self.cacheable_status = filter;
+        self
+    }
+
+    /// remove cached response of GET request with given host, path and query. e.g. `("example.com", "/users?page=1")`
+    pub fn invalidate(&self, host: &str, path_and_query: &str) {
+        self.store.remove(&cache_key(host, path_and_query));
+    }
+
+    /// remove all cached responses.
+    pub fn clear(&self) {
+        self.store.clear();
+    }
+}
+
+impl<S, Svc, E> Service<Result<Svc, E>> for Cache<S> {
+    type Response = service::CacheService<Svc, S>;
+    type Error = E;
+
+    async fn call(&self, res: Result<Svc, E>) -> Result<Self::Response, Self::Error> {
+        res.map(|service| service::CacheService {
+            service,
+            cache: self.clone(),
+        })
+    }
+}
+
+/// storage of cached responses. Store is shared by all workers of server.
+///
+/// Store is free to evict entries at any time. Expired entry must not be returned by [CacheStore::get].
+pub trait CacheStore: Send + Sync + 'static {
+    /// look up cached response by key.
+    fn get(&self, key: &str) -> Option<CachedResponse>;
+
+    /// store cached response with key. previous entry with the same key should be replaced.
+    fn insert(&self, key: String, res: CachedResponse);
+
+    /// remove cached response by key.
+    fn remove(&self, key: &str);
+
+    /// remove all cached responses.
+    fn clear(&self);
+}
+
+/// response stored in [CacheStore].
+#[derive(Clone)]
+pub struct CachedResponse {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Bytes,
+    stored: Instant,
+    expires: Instant,
+}
+
+impl CachedResponse {
+    /// time when response is expired.
+    pub fn expires(&self) -> Instant {
+        self.expires
+    }
+
+    /// check if response is expired.
+    pub fn is_expired(&self) -> bool {
+        self.expires <= Instant::now()
+    }
+
+    fn to_response<B>(&self) -> WebResponse<BufferBody<B>> {
+        let mut res = WebResponse::new(BufferBody::buffered(self.body.clone()));
+        *res.status_mut() = self.status;
+        *res.headers_mut() = self.headers.clone();
+        res.headers_mut()
+            .insert(AGE, HeaderValue::from(self.stored.elapsed().as_secs()));
+        res
+    }
+}
+
+/// in memory [CacheStore] with bounded count of entries.
+///
+/// When store is full expired entries are removed on insertion and new entry is dropped when there is still no
+/// room for it.
+pub struct MemoryStore {
+    capacity: usize,
+    map: Mutex<HashMap<String, CachedResponse>>,
+}
+
+impl MemoryStore {
+    /// construct store holding up to capacity count of entries.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            map: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl CacheStore for MemoryStore {
+    fn get(&self, key: &str) -> Option<CachedResponse> {
+        let mut map = self.map.lock().unwrap();
+        match map.get(key) {
+            Some(res) if res.is_expired() => {
+                map.remove(key);
+                None
+            }
+            res => res.cloned(),
+        }
+    }
+
+    fn insert(&self, key: String, res: CachedResponse) {
+        let mut map = self.map.lock().unwrap();
+        if map.len() >= self.capacity && !map.contains_key(&key) {
+            map.retain(|_, res| !res.is_expired());
+            if map.len() >= self.capacity {
+                return;
+            }
+        }
+        map.insert(key, res);
+    }
+
+    fn remove(&self, key: &str) {
+        self.map.lock().unwrap().remove(key);
+    }
+
+    fn clear(&self) {
+        self.map.lock().unwrap().clear();
+    }
+}
+
+fn cache_key(host: &str, path_and_query: &str) -> String {
+    format!("GET {host}{path_and_query}")
+}
+
+mod service {
+    use core::{future::poll_fn, pin::Pin};
+
+    use futures_core::stream::Stream;
+    use xitca_http::body::BodySize;
+
+    use crate::{
+        bytes::BytesMut,
+        error::{BodyError, Error},
+        http::{
+            header::{AUTHORIZATION, CACHE_CONTROL, HOST, SET_COOKIE, VARY},
+            Method,
+        },
+        service::ready::ReadyService,
+        WebContext,
+    };
+
+    use super::*;
+
+    pub struct CacheService<Svc, S> {
+        pub(super) service: Svc,
+        pub(super) cache: Cache<S>,
+    }
+
+    impl<'r, C, B, Svc, S, ResB, BE> Service<WebContext<'r, C, B>> for CacheService<Svc, S>
+    where
+        Svc: for<'r2> Service<WebContext<'r2, C, B>, Response = WebResponse<ResB>, Error = Error>,
+        S: CacheStore,
+        ResB: Stream<Item = Result<Bytes, BE>> + Unpin,
+        BE: Into<BodyError>,
+    {
+        type Response = WebResponse<BufferBody<ResB>>;
+        type Error = Error;
+
+        async fn call(&self, ctx: WebContext<'r, C, B>) -> Result<Self::Response, Self::Error> {
+            let req = ctx.req();
+            let host = req
+                .headers()
+                .get(HOST)
+                .and_then(|v| v.to_str().ok())
+                .or_else(|| req.uri().authority().map(|a| a.as_str()))
+                .unwrap_or("");
+            let key = cache_key(host, req.uri().path_and_query().map(|p| p.as_str()).unwrap_or("/"));
+
+            if req.method() != Method::GET {
+                let invalidate = matches!(
+                    *req.method(),
+                    Method::POST | Method::PUT | Method::PATCH | Method::DELETE
+                );
+                let res = self.service.call(ctx).await?;
+                if invalidate && (res.status().is_success() || res.status().is_redirection()) {
+                    self.cache.store.remove(&key);
+                }
+                return Ok(res.map(BufferBody::new));
+            }
+
+            let directive = Directive::from_headers(req.headers());
+            if req.headers().contains_key(AUTHORIZATION) || directive.no_store || directive.no_cache {
+                return self.service.call(ctx).await.map(|res| res.map(BufferBody::new));
+            }
+
+            if let Some(cached) = self.cache.store.get(&key) {
+                return Ok(cached.to_response());
+            }
+
+            let res = self.service.call(ctx).await?;
+
+            let Some(ttl) = self.ttl(&res) else {
+                return Ok(res.map(BufferBody::new));
+            };
+
+            let (parts, mut body) = res.into_parts();
+            let mut buf = BytesMut::new();
+
+            let body = loop {
+                match poll_fn(|cx| Pin::new(&mut body).poll_next(cx)).await {
+                    Some(Ok(bytes)) => {
+                        buf.extend_from_slice(&bytes);
+                        if buf.len() > self.cache.max_body_size {
+                            break BufferBody::partial(buf.freeze(), body);
+                        }
+                    }
+                    Some(Err(e)) => break BufferBody::error(buf.freeze(), e.into()),
+                    None => {
+                        let body = buf.freeze();
+                        let now = Instant::now();
+                        let cached = CachedResponse {
+                            status: parts.status,
+                            headers: parts.headers.clone(),
+                            body: body.clone(),
+                            stored: now,
+                            expires: now + ttl,
+                        };
+                        self.cache.store.insert(key, cached);
+                        break BufferBody::buffered(body);
+                    }
+                }
+            };
+
+            Ok(WebResponse::from_parts(parts, body))
+        }
+    }
+
+    impl<Svc, S> CacheService<Svc, S> {
+        // check if response can be cached and produce it's ttl.
+        fn ttl<ResB>(&self, res: &WebResponse<ResB>) -> Option<Duration>
+        where
+            ResB: Stream,
+        {
+            let headers = res.headers();
+            if !(self.cache.cacheable_status)(res.status())
+                || headers.contains_key(SET_COOKIE)
+                || headers.contains_key(VARY)
+            {
+                return None;
+            }
+
+            match BodySize::from_stream(res.body()) {
+                BodySize::Sized(size) if size > self.cache.max_body_size => return None,
+                _ => {}
+            }
+
+            let directive = Directive::from_headers(res.headers());
+            if directive.no_store || directive.no_cache || directive.private {
+                return None;
+            }
+
+            let ttl = directive.max_age.map(Duration::from_secs).unwrap_or(self.cache.ttl);
+            (!ttl.is_zero()).then_some(ttl)
+        }
+    }
+
+    impl<Svc, S> ReadyService for CacheService<Svc, S>
+    where
+        Svc: ReadyService,
+    {
+        type Ready = Svc::Ready;
+
+        #[inline]
+        async fn ready(&self) -> Self::Ready {
+            self.service.ready().await
+        }
+    }
+
+    #[derive(Default)]
+    pub(super) struct Directive {
+        pub(super) no_store: bool,
+        pub(super) no_cache: bool,
+        pub(super) private: bool,
+        pub(super) max_age: Option<u64>,
+    }
+
+    impl Directive {
+        pub(super) fn from_headers(headers: &HeaderMap) -> Self {
+            let mut directive = Self::default();
+            let mut s_maxage = None;
+
+            let values = headers
+                .get_all(CACHE_CONTROL)
+                .iter()
+                .filter_map(|v| v.to_str().ok())
+                .flat_map(|v| v.split(','))
+                .map(str::trim);
+
+            for value in values {
+                let (name, arg) = match value.split_once('=') {
+                    Some((name, arg)) => (name.trim(), Some(arg.trim().trim_matches('"'))),
+                    None => (value, None),
+                };
+
+                if name.eq_ignore_ascii_case("no-store") {
+                    directive.no_store = true;
+                } else if name.eq_ignore_ascii_case("no-cache") {
+                    directive.no_cache = true;
+                } else if name.eq_ignore_ascii_case("private") {
+                    directive.private = true;
+                } else if name.eq_ignore_ascii_case("max-age") {
+                    directive.max_age = arg.and_then(|a| a.parse().ok());
+                } else if name.eq_ignore_ascii_case("s-maxage") {
+                    s_maxage = arg.and_then(|a| a.parse().ok());
+                }
+            }
+
+            // s-maxage overrides max-age for shared cache.
+            if s_maxage.is_some() {
+                directive.max_age = s_maxage;
+            }
+
+            directive
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use core::sync::atomic::{AtomicUsize, Ordering};
+
+    use xitca_unsafe_collection::futures::NowOrPanic;
+
+    use crate::{
+        handler::handler_service,
+        http::{
+            header::{CACHE_CONTROL, HOST, VARY},
+            Method, Uri, WebRequest,
+        },
+        test::collect_string_body,
+        App,
+    };
+
+    use super::*;
+
+    fn req(method: Method, path: &'static str) -> WebRequest {
+        let mut req = WebRequest::default();
+        *req.method_mut() = method;
+        *req.uri_mut() = Uri::from_static(path);
+        req
+    }
+
+    #[test]
+    fn cache_and_invalidate() {
+        static COUNT: AtomicUsize = AtomicUsize::new(0);
+
+        async fn handler(uri: &Uri) -> (HeaderMap, String) {
+            let n = COUNT.fetch_add(1, Ordering::SeqCst);
+            let mut headers = HeaderMap::new();
+            match uri.path() {
+                "/no-store" => {
+                    headers.insert(CACHE_CONTROL, HeaderValue::from_static("no-store"));
+                }
+                "/expired" => {
+                    headers.insert(CACHE_CONTROL, HeaderValue::from_static("public, max-age=0"));
+                }
+                "/vary" => {
+                    headers.insert(VARY, HeaderValue::from_static("accept-language"));
+                }
+                _ => {}
+            }
+            (headers, n.to_string())
+        }
+
+        let cache = Cache::new(Duration::from_secs(60));
+
+        let service = App::new()
+            .at("/foo", handler_service(handler))
+            .at("/no-store", handler_service(handler))
+            .at("/expired", handler_service(handler))
+            .at("/vary", handler_service(handler))
+            .enclosed(cache.clone())
+            .finish()
+            .call(())
+            .now_or_panic()
+            .unwrap();
+
+        let call = |req: WebRequest| {
+            let res = service.call(req).now_or_panic().unwrap();
+            collect_string_body(res.into_body()).now_or_panic().unwrap()
+        };
+
+        let first = call(req(Method::GET, "/foo"));
+        assert_eq!(call(req(Method::GET, "/foo")), first);
+        assert_ne!(call(req(Method::GET, "/foo?bar=1")), first);
+
+        let res = service.call(req(Method::GET, "/foo")).now_or_panic().unwrap();
+        assert!(res.headers().contains_key(AGE));
+
+        // unsafe method invalidates cached response.
+        call(req(Method::POST, "/foo"));
+        let second = call(req(Method::GET, "/foo"));
+        assert_ne!(second, first);
+        assert_eq!(call(req(Method::GET, "/foo")), second);
+
+        // invalidate by handle.
+        cache.invalidate("", "/foo");
+        assert_ne!(call(req(Method::GET, "/foo")), second);
+
+        let a = call(req(Method::GET, "/no-store"));
+        assert_ne!(call(req(Method::GET, "/no-store")), a);
+
+        let a = call(req(Method::GET, "/expired"));
+        assert_ne!(call(req(Method::GET, "/expired")), a);
+
+        // response varied by request header is not cached.
+        let a = call(req(Method::GET, "/vary"));
+        assert_ne!(call(req(Method::GET, "/vary")), a);
+
+        // response is cached per host.
+        let host = |host| {
+            let mut req = req(Method::GET, "/foo");
+            req.headers_mut().insert(HOST, HeaderValue::from_static(host));
+            req
+        };
+        let a = call(host("a.com"));
+        assert_eq!(call(host("a.com")), a);
+        let b = call(host("b.com"));
+        assert_ne!(b, a);
+        assert_eq!(call(host("b.com")), b);
+
+        cache.invalidate("a.com", "/foo");
+        assert_ne!(call(host("a.com")), a);
+        assert_eq!(call(host("b.com")), b);
+    }
+
+    #[test]
+    fn directive() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            CACHE_CONTROL,
+            HeaderValue::from_static("public, max-age=60, s-maxage=\"30\""),
+        );
+        let directive = service::Directive::from_headers(&headers);
+        assert_eq!(directive.max_age, Some(30));
+        assert!(!directive.no_store && !directive.private);
+
+        headers.append(CACHE_CONTROL, HeaderValue::from_static("Private"));
+        assert!(service::Directive::from_headers(&headers).private);
+    }
+}
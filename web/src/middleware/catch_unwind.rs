@@ -10,6 +10,20 @@ use crate::{
 
 /// middleware for catching panic inside [`Service::call`] and return a 500 error response.
 ///
+/// The middleware is opt-in. Without it a panic in handler would unwind through http dispatcher and terminate the
+/// connection it happens on. Applications prefer fail fast behavior can leave it out.
+///
+/// # Panic payload
+/// Caught panic is converted to [`ThreadJoinError`] wrapped in [`Error`]. The panic payload is kept in it and can be
+/// downcast from error handling middleware. When `logger` feature is enabled the panic message is logged as error
+/// event. Backtrace of panic is captured and printed by Rust's panic hook. (See [`std::panic::set_hook`])
+///
+/// # Unwind safety
+/// Service future is treated as [`UnwindSafe`](std::panic::UnwindSafe) by the middleware. Shared state mutated by
+/// the panicked handler(through interior mutability types like [`RefCell`](core::cell::RefCell)) may be left in a
+/// broken state and it's up to user to decide if it's safe to be observed by following requests. Lock types like
+/// [`Mutex`](std::sync::Mutex) would be poisoned by panic.
+///
 /// # Examples:
 /// ```rust
 /// # use xitca_web::{handler::handler_service, middleware::CatchUnwind, service::ServiceExt, App, WebContext};
@@ -48,17 +62,24 @@ where
 
 pub struct CatchUnwindService<S>(S);
 
-impl<'r, C, B, S> Service<WebContext<'r, C, B>> for CatchUnwindService<S>
+impl<'r, C, B, S, E> Service<WebContext<'r, C, B>> for CatchUnwindService<S>
 where
-    S: Service<WebContext<'r, C, B>>,
-    S::Error: Into<Error>,
+    S: Service<WebContext<'r, C, B>, Error = CatchUnwindError<E>>,
+    E: Into<Error>,
 {
     type Response = S::Response;
     type Error = Error;
 
     #[inline]
     async fn call(&self, ctx: WebContext<'r, C, B>) -> Result<Self::Response, Self::Error> {
-        self.0.call(ctx).await.map_err(Into::into)
+        self.0.call(ctx).await.map_err(|e| {
+            let e = Error::from(e);
+            #[cfg(feature = "logger")]
+            if let Some(e) = e.upcast().downcast_ref::<ThreadJoinError>() {
+                tracing::error!("handler panicked: {e}");
+            }
+            e
+        })
     }
 }
 
@@ -93,6 +114,7 @@ mod test {
     use crate::{
         handler::handler_service,
         http::{Request, StatusCode},
+        service::ServiceExt,
         App,
     };
 
@@ -118,4 +140,25 @@ mod test {
 
         assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
     }
+
+    #[test]
+    fn panic_payload() {
+        struct Foo;
+
+        async fn handler() -> &'static str {
+            std::panic::panic_any(Foo);
+        }
+
+        let service = handler_service(handler)
+            .enclosed(CatchUnwind)
+            .call(())
+            .now_or_panic()
+            .unwrap();
+
+        let mut ctx = WebContext::new_test(());
+        let err = service.call(ctx.as_web_ctx()).now_or_panic().err().unwrap();
+
+        let err = err.upcast().downcast_ref::<ThreadJoinError>().unwrap();
+        assert!(err.0.lock().unwrap().downcast_ref::<Foo>().is_some());
+    }
 }
@@ -0,0 +1,235 @@
+//! request concurrency limiting middleware.
+
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use tokio::sync::Semaphore;
+
+use crate::service::Service;
+
+/// middleware for capping the count of in flight requests.
+///
+/// When limit is reached new requests are queued until a slot is available. [`ConcurrencyLimit::reject`]
+/// can be used to reject them immediately with "503 Service Unavailable" response instead.
+///
+/// The slot a request holds is released when it's response is produced or it's service future is dropped
+/// (for example when client disconnected or request timed out).
+///
+/// # Examples
+/// ```rust
+/// # use xitca_web::{handler::handler_service, middleware::concurrency_limit::ConcurrencyLimit, App, WebContext};
+/// // limit the application to 128 in flight requests globally.
+/// let limit = ConcurrencyLimit::new(128);
+///
+/// // a clone of limit can be used to observe in flight request count.
+/// let observer = limit.clone();
+///
+/// App::new()
+///     .at("/", handler_service(|| async { "hello,world!" }))
+///     # .at("/infer", handler_service(|_: &WebContext<'_>| async{ "infer type" }))
+///     .enclosed(limit);
+///
+/// assert_eq!(observer.in_flight(), 0);
+/// ```
+#[derive(Clone)]
+pub struct ConcurrencyLimit {
+    max: usize,
+    shared: Option<Arc<Semaphore>>,
+    reject: bool,
+    in_flight: Arc<AtomicUsize>,
+}
+
+impl ConcurrencyLimit {
+    /// construct a limit that is shared by all workers of server.
+    ///
+    /// # Panics
+    /// - When max is zero.
+    pub fn new(max: usize) -> Self {
+        Self::_new(max, true)
+    }
+
+    /// construct a limit that is applied to every worker of server separately.
+    ///
+    /// # Panics
+    /// - When max is zero.
+    pub fn per_worker(max: usize) -> Self {
+        Self::_new(max, false)
+    }
+
+    fn _new(max: usize, shared: bool) -> Self {
+        assert!(max > 0, "ConcurrencyLimit must allow at least one in flight request");
+        Self {
+            max,
+            shared: shared.then(|| Arc::new(Semaphore::new(max))),
+            reject: false,
+            in_flight: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// reject request with "503 Service Unavailable" response when limit is reached instead of
+    /// queuing it.
+    pub fn reject(mut self) -> Self {
+        self.reject = true;
+        self
+    }
+
+    /// current count of in flight requests among all services built from this limit.
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::Relaxed)
+    }
+}
+
+impl<S, E> Service<Result<S, E>> for ConcurrencyLimit {
+    type Response = service::ConcurrencyLimitService<S>;
+    type Error = E;
+
+    async fn call(&self, res: Result<S, E>) -> Result<Self::Response, Self::Error> {
+        res.map(|service| service::ConcurrencyLimitService {
+            service,
+            semaphore: self
+                .shared
+                .clone()
+                .unwrap_or_else(|| Arc::new(Semaphore::new(self.max))),
+            reject: self.reject,
+            in_flight: self.in_flight.clone(),
+        })
+    }
+}
+
+mod service {
+    use crate::{
+        error::Error,
+        http::{StatusCode, WebResponse},
+        service::{ready::ReadyService, Service},
+        WebContext,
+    };
+
+    use super::*;
+
+    pub struct ConcurrencyLimitService<S> {
+        pub(super) service: S,
+        pub(super) semaphore: Arc<Semaphore>,
+        pub(super) reject: bool,
+        pub(super) in_flight: Arc<AtomicUsize>,
+    }
+
+    impl<S> ConcurrencyLimitService<S> {
+        /// current count of in flight requests among all services share the same limit.
+        pub fn in_flight(&self) -> usize {
+            self.in_flight.load(Ordering::Relaxed)
+        }
+    }
+
+    impl<'r, C, B, S, ResB> Service<WebContext<'r, C, B>> for ConcurrencyLimitService<S>
+    where
+        S: for<'r2> Service<WebContext<'r2, C, B>, Response = WebResponse<ResB>, Error = Error>,
+    {
+        type Response = WebResponse<ResB>;
+        type Error = Error;
+
+        async fn call(&self, ctx: WebContext<'r, C, B>) -> Result<Self::Response, Self::Error> {
+            // permit is bound to the lifetime of call future and released on drop.
+            let _permit = if self.reject {
+                self.semaphore
+                    .try_acquire()
+                    .map_err(|_| Error::from(StatusCode::SERVICE_UNAVAILABLE))?
+            } else {
+                self.semaphore.acquire().await.expect("semaphore must not be closed")
+            };
+            let _guard = InFlightGuard::new(&self.in_flight);
+            self.service.call(ctx).await
+        }
+    }
+
+    impl<S> ReadyService for ConcurrencyLimitService<S>
+    where
+        S: ReadyService,
+    {
+        type Ready = S::Ready;
+
+        #[inline]
+        async fn ready(&self) -> Self::Ready {
+            self.service.ready().await
+        }
+    }
+
+    struct InFlightGuard<'a>(&'a AtomicUsize);
+
+    impl<'a> InFlightGuard<'a> {
+        fn new(in_flight: &'a AtomicUsize) -> Self {
+            in_flight.fetch_add(1, Ordering::Relaxed);
+            Self(in_flight)
+        }
+    }
+
+    impl Drop for InFlightGuard<'_> {
+        fn drop(&mut self) {
+            self.0.fetch_sub(1, Ordering::Relaxed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use core::{
+        future::{pending, Future},
+        pin::pin,
+        task::Context,
+    };
+
+    use futures_util::task::noop_waker_ref;
+    use xitca_unsafe_collection::futures::NowOrPanic;
+
+    use crate::{
+        handler::handler_service,
+        http::{StatusCode, Uri, WebRequest},
+        App,
+    };
+
+    use super::*;
+
+    #[test]
+    fn reject_and_release() {
+        async fn handler(uri: &Uri) -> &'static str {
+            if uri.path() == "/pending" {
+                pending::<()>().await;
+            }
+            "hello,world!"
+        }
+
+        let limit = ConcurrencyLimit::new(1).reject();
+
+        let service = App::new()
+            .at("/", handler_service(handler))
+            .at("/pending", handler_service(handler))
+            .enclosed(limit.clone())
+            .finish()
+            .call(())
+            .now_or_panic()
+            .unwrap();
+
+        let req = |path: &'static str| {
+            let mut req = WebRequest::default();
+            *req.uri_mut() = Uri::from_static(path);
+            req
+        };
+
+        {
+            let mut fut = pin!(service.call(req("/pending")));
+            let mut cx = Context::from_waker(noop_waker_ref());
+            assert!(fut.as_mut().poll(&mut cx).is_pending());
+            assert_eq!(limit.in_flight(), 1);
+
+            let res = service.call(req("/")).now_or_panic().unwrap();
+            assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+        }
+
+        // dropped future must release it's slot.
+        assert_eq!(limit.in_flight(), 0);
+
+        let res = service.call(req("/")).now_or_panic().unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+}
@@ -0,0 +1,192 @@
+//! middleware for asserting response content type of route.
+
+use crate::service::Service;
+
+/// middleware for declaring expected content type of response produced by a route.
+///
+/// In debug build every successful(2xx except "204 No Content") response passed through the middleware is checked
+/// and a mismatching or missing `Content-Type` header panics with path of request and the actual value. It helps
+/// catching handler accidentally responding with wrong type, for example a json endpoint returning plain text.
+/// Only mime type is compared(parameters like `charset` are ignored) and comparison is case insensitive.
+///
+/// In release build the middleware does not do any check and forwards response as is.
+///
+/// # Examples
+/// ```rust
+/// # use xitca_web::{
+/// #   handler::handler_service,
+/// #   middleware::content_type::ExpectContentType,
+/// #   route::get,
+/// #   service::ServiceExt,
+/// #   App, WebContext,
+/// # };
+/// // declare the route produces plain text. responding json from it would panic in debug build.
+/// let route = get(handler_service(|| async { "hello,world!" }).enclosed(ExpectContentType::text()));
+///
+/// App::new()
+///     .at("/", route)
+///     # .at("/infer", handler_service(|_: &WebContext<'_>| async{ "infer type" }))
+///     ;
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct ExpectContentType {
+    mime: &'static str,
+}
+
+impl ExpectContentType {
+    /// construct middleware expecting given mime type. e.g. `text/csv`
+    pub const fn new(mime: &'static str) -> Self {
+        Self { mime }
+    }
+
+    /// construct middleware expecting `application/json`.
+    pub const fn json() -> Self {
+        Self::new("application/json")
+    }
+
+    /// construct middleware expecting `text/html`.
+    pub const fn html() -> Self {
+        Self::new("text/html")
+    }
+
+    /// construct middleware expecting `text/plain`.
+    pub const fn text() -> Self {
+        Self::new("text/plain")
+    }
+}
+
+impl<S, E> Service<Result<S, E>> for ExpectContentType {
+    type Response = service::ExpectContentTypeService<S>;
+    type Error = E;
+
+    async fn call(&self, res: Result<S, E>) -> Result<Self::Response, Self::Error> {
+        res.map(|service| service::ExpectContentTypeService {
+            service,
+            mime: self.mime,
+        })
+    }
+}
+
+mod service {
+    use crate::{error::Error, http::WebResponse, service::ready::ReadyService, WebContext};
+
+    use super::*;
+
+    pub struct ExpectContentTypeService<S> {
+        pub(super) service: S,
+        #[cfg_attr(not(debug_assertions), allow(dead_code))]
+        pub(super) mime: &'static str,
+    }
+
+    impl<'r, C, B, S, ResB> Service<WebContext<'r, C, B>> for ExpectContentTypeService<S>
+    where
+        S: for<'r2> Service<WebContext<'r2, C, B>, Response = WebResponse<ResB>, Error = Error>,
+    {
+        type Response = WebResponse<ResB>;
+        type Error = Error;
+
+        #[cfg(debug_assertions)]
+        async fn call(&self, ctx: WebContext<'r, C, B>) -> Result<Self::Response, Self::Error> {
+            let path = ctx.req().uri().path().to_owned();
+            let res = self.service.call(ctx).await?;
+            assert_content_type(self.mime, &path, &res);
+            Ok(res)
+        }
+
+        #[cfg(not(debug_assertions))]
+        #[inline]
+        async fn call(&self, ctx: WebContext<'r, C, B>) -> Result<Self::Response, Self::Error> {
+            self.service.call(ctx).await
+        }
+    }
+
+    impl<S> ReadyService for ExpectContentTypeService<S>
+    where
+        S: ReadyService,
+    {
+        type Ready = S::Ready;
+
+        #[inline]
+        async fn ready(&self) -> Self::Ready {
+            self.service.ready().await
+        }
+    }
+
+    #[cfg(debug_assertions)]
+    fn assert_content_type<B>(expected: &str, path: &str, res: &WebResponse<B>) {
+        use crate::http::{header::CONTENT_TYPE, StatusCode};
+
+        let status = res.status();
+        if !status.is_success() || status == StatusCode::NO_CONTENT {
+            return;
+        }
+
+        let actual = res.headers().get(CONTENT_TYPE);
+        let matched = actual
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.split(';').next())
+            .is_some_and(|mime| mime.trim().eq_ignore_ascii_case(expected));
+
+        assert!(
+            matched,
+            "route {path} is expected to respond with content-type {expected} but responded with {actual:?}"
+        );
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use xitca_unsafe_collection::futures::NowOrPanic;
+
+    use crate::{
+        handler::handler_service,
+        http::{StatusCode, WebRequest},
+        service::ServiceExt,
+        App,
+    };
+
+    use super::*;
+
+    #[test]
+    fn matched() {
+        let service = App::new()
+            .at(
+                "/",
+                handler_service(|| async { "996" }).enclosed(ExpectContentType::text()),
+            )
+            .at(
+                "/error",
+                handler_service(|| async { StatusCode::NOT_FOUND }).enclosed(ExpectContentType::text()),
+            )
+            .finish()
+            .call(())
+            .now_or_panic()
+            .unwrap();
+
+        let res = service.call(WebRequest::default()).now_or_panic().unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+
+        // non success response is not checked.
+        let mut req = WebRequest::default();
+        *req.uri_mut() = crate::http::Uri::from_static("/error");
+        let res = service.call(req).now_or_panic().unwrap();
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "route / is expected to respond with content-type application/json")]
+    fn mismatched() {
+        let service = App::new()
+            .at(
+                "/",
+                handler_service(|| async { "996" }).enclosed(ExpectContentType::json()),
+            )
+            .finish()
+            .call(())
+            .now_or_panic()
+            .unwrap();
+
+        let _ = service.call(WebRequest::default()).now_or_panic();
+    }
+}
@@ -56,7 +56,7 @@ impl<S, E> Service<Result<S, E>> for WebContext {
     }
 }
 
-mod service {
+pub(crate) mod service {
     use core::{cell::RefCell, convert::Infallible};
 
     use xitca_http::util::middleware::context::Context;
@@ -84,16 +84,29 @@ mod service {
 
         async fn call(&self, ctx: Context<'c, WebRequest, C>) -> Result<Self::Response, Self::Error> {
             let (req, state) = ctx.into_parts();
-            let (parts, ext) = req.into_parts();
-            let (ext, body) = ext.replace_body(());
-            let mut req = WebRequest::from_parts(parts, ext);
-            let mut body = RefCell::new(body);
-            let mut ctx = WebContext::new(&mut req, &mut body, state);
+            call_with_state(&self.service, req, state).await
+        }
+    }
+
+    // convert request to WebContext with reference of state and call service with it.
+    pub(crate) async fn call_with_state<S, C, ResB, SE>(
+        service: &S,
+        req: WebRequest,
+        state: &C,
+    ) -> Result<WebResponse<EitherResBody<ResB>>, Infallible>
+    where
+        S: for<'r> Service<WebContext<'r, C>, Response = WebResponse<ResB>, Error = SE>,
+        SE: for<'r> Service<WebContext<'r, C>, Response = WebResponse, Error = Infallible>,
+    {
+        let (parts, ext) = req.into_parts();
+        let (ext, body) = ext.replace_body(());
+        let mut req = WebRequest::from_parts(parts, ext);
+        let mut body = RefCell::new(body);
+        let mut ctx = WebContext::new(&mut req, &mut body, state);
 
-            match self.service.call(ctx.reborrow()).await {
-                Ok(res) => Ok(res.map(Either::left)),
-                Err(e) => e.call(ctx).await.map(|res| res.map(Either::right)),
-            }
+        match service.call(ctx.reborrow()).await {
+            Ok(res) => Ok(res.map(Either::left)),
+            Err(e) => e.call(ctx).await.map(|res| res.map(Either::right)),
         }
     }
 
@@ -0,0 +1,487 @@
+//! middleware for cross-origin resource sharing(CORS).
+
+use core::time::Duration;
+
+use std::sync::Arc;
+
+use crate::{
+    http::{
+        header::{HeaderName, HeaderValue},
+        Method,
+    },
+    service::Service,
+};
+
+/// middleware for handling cross-origin resource sharing(CORS) requests.
+///
+/// # Preflight
+/// `OPTIONS` request with `Origin` and `Access-Control-Request-Method` headers is answered by the middleware with
+/// "204 No Content" response without reaching enclosed service. The response is constructed with default value of
+/// enclosed service's response body type. Preflight from origin not allowed or asking for method/headers not allowed
+/// is rejected with "403 Forbidden" response.
+///
+/// # Actual request
+/// Request from allowed origin is passed to enclosed service and it's response gets `Access-Control-Allow-Origin`
+/// header(and `Access-Control-Allow-Credentials` header when [`Cors::allow_credentials`] is set). Request from origin
+/// not allowed is passed through without CORS headers and browser would block script from reading it's response.
+/// Error returned by enclosed service is passed through as is.
+///
+/// # Preflight caching
+/// By default browser caches preflight result for a short duration decided by itself(5 seconds for Chrome and
+/// Firefox). [`Cors::max_age`] adds `Access-Control-Max-Age` header to preflight response for caching it longer and
+/// reducing round trips of cross-origin requests.
+///
+/// # Private network access
+/// Chrome's Private Network Access sends preflight with `Access-Control-Request-Private-Network: true` header when a
+/// public origin requests a server on private network(local network devices, localhost, etc). Such preflight only
+/// succeeds when response carries `Access-Control-Allow-Private-Network: true` header. The header is reflected only
+/// when [`Cors::allow_private_network`] is set and the preflight asks for it.
+///
+/// # Examples
+/// ```rust
+/// # use core::time::Duration;
+/// # use xitca_web::{
+/// #   handler::handler_service,
+/// #   http::{header::CONTENT_TYPE, Method},
+/// #   middleware::cors::Cors,
+/// #   App, WebContext
+/// # };
+/// App::new()
+///     .at("/", handler_service(|| async { "hello,world!" }))
+///     # .at("/infer", handler_service(|_: &WebContext<'_>| async{ "infer type" }))
+///     .enclosed(
+///         Cors::new()
+///             .allow_origin("https://example.com")
+///             .allow_methods([Method::GET, Method::POST])
+///             .allow_headers([CONTENT_TYPE])
+///             .max_age(Duration::from_secs(600))
+///             .allow_private_network(),
+///     );
+/// ```
+#[derive(Clone)]
+pub struct Cors {
+    origins: Origins,
+    methods: Vec<Method>,
+    headers: Vec<HeaderName>,
+    credentials: bool,
+    max_age: Option<Duration>,
+    private_network: bool,
+}
+
+#[derive(Clone)]
+enum Origins {
+    Any,
+    List(Vec<HeaderValue>),
+}
+
+impl Default for Cors {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Cors {
+    /// construct middleware with no allowed origin. Every cross-origin request is treated as not allowed until
+    /// origin is added. `GET`, `HEAD` and `POST` methods are allowed by default.
+    pub fn new() -> Self {
+        Self {
+            origins: Origins::List(Vec::new()),
+            methods: vec![Method::GET, Method::HEAD, Method::POST],
+            headers: Vec::new(),
+            credentials: false,
+            max_age: None,
+            private_network: false,
+        }
+    }
+
+    /// allow request from any origin.
+    pub fn allow_any_origin(mut self) -> Self {
+        self.origins = Origins::Any;
+        self
+    }
+
+    /// add origin to allowlist. origin is compared with `Origin` header of request as is. e.g. `https://example.com`
+    ///
+    /// # Panics
+    /// When origin is not a valid header value.
+    pub fn allow_origin(mut self, origin: impl AsRef<str>) -> Self {
+        let origin = HeaderValue::try_from(origin.as_ref()).expect("origin must be valid header value");
+        match self.origins {
+            Origins::List(ref mut list) => list.push(origin),
+            Origins::Any => self.origins = Origins::List(vec![origin]),
+        }
+        self
+    }
+
+    /// replace allowed methods of cross-origin request.
+    pub fn allow_methods<I>(mut self, methods: I) -> Self
+    where
+        I: IntoIterator<Item = Method>,
+    {
+        self.methods = methods.into_iter().collect();
+        self
+    }
+
+    /// replace allowed headers of cross-origin request. CORS safelisted headers are always allowed by browser and
+    /// don't have to be added.
+    pub fn allow_headers<I>(mut self, headers: I) -> Self
+    where
+        I: IntoIterator<Item = HeaderName>,
+    {
+        self.headers = headers.into_iter().collect();
+        self
+    }
+
+    /// allow cross-origin request carrying credentials(cookies, authorization header, etc).
+    /// When enabled along with [`Cors::allow_any_origin`] origin of request is echoed instead of using wildcard as
+    /// browser rejects wildcard origin for credentialed request.
+    pub fn allow_credentials(mut self) -> Self {
+        self.credentials = true;
+        self
+    }
+
+    /// set duration of preflight result cached by browser. Duration is rounded down to whole seconds.
+    pub fn max_age(mut self, dur: Duration) -> Self {
+        self.max_age = Some(dur);
+        self
+    }
+
+    /// reflect `Access-Control-Allow-Private-Network` header for preflight asking for private network access.
+    pub fn allow_private_network(mut self) -> Self {
+        self.private_network = true;
+        self
+    }
+
+    fn is_allowed(&self, origin: &HeaderValue) -> bool {
+        match self.origins {
+            Origins::Any => true,
+            Origins::List(ref list) => list.contains(origin),
+        }
+    }
+}
+
+impl<S, E> Service<Result<S, E>> for Cors {
+    type Response = service::CorsService<S>;
+    type Error = E;
+
+    async fn call(&self, res: Result<S, E>) -> Result<Self::Response, Self::Error> {
+        res.map(|service| service::CorsService {
+            service,
+            config: Arc::new(self.clone()),
+        })
+    }
+}
+
+mod service {
+    use crate::{
+        error::Error,
+        http::{
+            header::{
+                HeaderMap, ACCESS_CONTROL_ALLOW_CREDENTIALS, ACCESS_CONTROL_ALLOW_HEADERS,
+                ACCESS_CONTROL_ALLOW_METHODS, ACCESS_CONTROL_ALLOW_ORIGIN, ACCESS_CONTROL_MAX_AGE,
+                ACCESS_CONTROL_REQUEST_HEADERS, ACCESS_CONTROL_REQUEST_METHOD, ORIGIN, VARY,
+            },
+            StatusCode, WebRequest, WebResponse,
+        },
+        service::{ready::ReadyService, Service},
+        WebContext,
+    };
+
+    use super::*;
+
+    const ACCESS_CONTROL_REQUEST_PRIVATE_NETWORK: HeaderName =
+        HeaderName::from_static("access-control-request-private-network");
+    const ACCESS_CONTROL_ALLOW_PRIVATE_NETWORK: HeaderName =
+        HeaderName::from_static("access-control-allow-private-network");
+
+    const TRUE: HeaderValue = HeaderValue::from_static("true");
+
+    pub struct CorsService<S> {
+        pub(super) service: S,
+        pub(super) config: Arc<Cors>,
+    }
+
+    impl<'r, C, B, S, ResB> Service<WebContext<'r, C, B>> for CorsService<S>
+    where
+        S: for<'r2> Service<WebContext<'r2, C, B>, Response = WebResponse<ResB>, Error = Error>,
+        ResB: Default,
+    {
+        type Response = WebResponse<ResB>;
+        type Error = Error;
+
+        async fn call(&self, ctx: WebContext<'r, C, B>) -> Result<Self::Response, Self::Error> {
+            let Some(origin) = ctx.req().headers().get(ORIGIN).cloned() else {
+                return self.service.call(ctx).await;
+            };
+
+            let allowed = self.config.is_allowed(&origin);
+
+            if is_preflight(ctx.req()) {
+                return match allowed.then(|| self.config.preflight(ctx.req(), &origin)).flatten() {
+                    Some(headers) => {
+                        let mut res = WebResponse::new(ResB::default());
+                        *res.status_mut() = StatusCode::NO_CONTENT;
+                        res.headers_mut().extend(headers);
+                        Ok(res)
+                    }
+                    None => Err(Error::from(StatusCode::FORBIDDEN)),
+                };
+            }
+
+            let mut res = self.service.call(ctx).await?;
+            if allowed {
+                self.config.insert_origin(res.headers_mut(), origin);
+            } else if matches!(self.config.origins, Origins::List(_)) {
+                res.headers_mut().append(VARY, HeaderValue::from_static("origin"));
+            }
+            Ok(res)
+        }
+    }
+
+    impl<S> ReadyService for CorsService<S>
+    where
+        S: ReadyService,
+    {
+        type Ready = S::Ready;
+
+        #[inline]
+        async fn ready(&self) -> Self::Ready {
+            self.service.ready().await
+        }
+    }
+
+    fn is_preflight(req: &WebRequest<()>) -> bool {
+        req.method() == Method::OPTIONS && req.headers().contains_key(ACCESS_CONTROL_REQUEST_METHOD)
+    }
+
+    impl Cors {
+        // headers of successful preflight response. None when requested method or headers are not allowed.
+        fn preflight(&self, req: &WebRequest<()>, origin: &HeaderValue) -> Option<HeaderMap> {
+            let headers = req.headers();
+
+            let method = headers.get(ACCESS_CONTROL_REQUEST_METHOD)?.as_bytes();
+            if !self.methods.iter().any(|m| m.as_str().as_bytes() == method) {
+                return None;
+            }
+
+            for value in headers.get_all(ACCESS_CONTROL_REQUEST_HEADERS) {
+                for name in value.to_str().ok()?.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+                    if !self.headers.iter().any(|h| h.as_str().eq_ignore_ascii_case(name)) {
+                        return None;
+                    }
+                }
+            }
+
+            let mut map = HeaderMap::new();
+            self.insert_origin(&mut map, origin.clone());
+
+            let methods = self.methods.iter().map(Method::as_str).collect::<Vec<_>>().join(", ");
+            map.insert(ACCESS_CONTROL_ALLOW_METHODS, HeaderValue::try_from(methods).ok()?);
+
+            if !self.headers.is_empty() {
+                let names = self
+                    .headers
+                    .iter()
+                    .map(HeaderName::as_str)
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                map.insert(ACCESS_CONTROL_ALLOW_HEADERS, HeaderValue::try_from(names).ok()?);
+            }
+
+            if let Some(dur) = self.max_age {
+                map.insert(ACCESS_CONTROL_MAX_AGE, HeaderValue::from(dur.as_secs()));
+            }
+
+            if self.private_network
+                && headers
+                    .get(ACCESS_CONTROL_REQUEST_PRIVATE_NETWORK)
+                    .is_some_and(|v| v.as_bytes().eq_ignore_ascii_case(b"true"))
+            {
+                map.insert(ACCESS_CONTROL_ALLOW_PRIVATE_NETWORK, TRUE);
+            }
+
+            Some(map)
+        }
+
+        fn insert_origin(&self, headers: &mut HeaderMap, origin: HeaderValue) {
+            if matches!(self.origins, Origins::Any) && !self.credentials {
+                headers.insert(ACCESS_CONTROL_ALLOW_ORIGIN, HeaderValue::from_static("*"));
+            } else {
+                headers.insert(ACCESS_CONTROL_ALLOW_ORIGIN, origin);
+                // response differs by origin and must not be shared by caches between origins.
+                headers.append(VARY, HeaderValue::from_static("origin"));
+            }
+
+            if self.credentials {
+                headers.insert(ACCESS_CONTROL_ALLOW_CREDENTIALS, TRUE);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use xitca_unsafe_collection::futures::NowOrPanic;
+
+    use crate::{
+        handler::handler_service,
+        http::{
+            header::{
+                ACCESS_CONTROL_ALLOW_CREDENTIALS, ACCESS_CONTROL_ALLOW_HEADERS, ACCESS_CONTROL_ALLOW_METHODS,
+                ACCESS_CONTROL_ALLOW_ORIGIN, ACCESS_CONTROL_MAX_AGE, ACCESS_CONTROL_REQUEST_HEADERS, CONTENT_TYPE,
+                VARY,
+            },
+            StatusCode, WebRequest, WebResponse,
+        },
+        App,
+    };
+
+    use super::*;
+
+    const PRIVATE_NETWORK_REQ: &str = "access-control-request-private-network";
+    const PRIVATE_NETWORK_RES: &str = "access-control-allow-private-network";
+
+    fn req(method: Method, headers: &[(&'static str, &'static str)]) -> WebRequest {
+        let mut req = WebRequest::default();
+        *req.method_mut() = method;
+        for &(name, value) in headers {
+            req.headers_mut()
+                .insert(HeaderName::from_static(name), HeaderValue::from_static(value));
+        }
+        req
+    }
+
+    // response body is dropped as cors only touches status and headers.
+    fn call(cors: Cors, req: WebRequest) -> WebResponse<()> {
+        App::new()
+            .at("/", handler_service(|| async { "hello,world!" }))
+            .enclosed(cors)
+            .finish()
+            .call(())
+            .now_or_panic()
+            .unwrap()
+            .call(req)
+            .now_or_panic()
+            .unwrap()
+            .map(|_| ())
+    }
+
+    fn cors() -> Cors {
+        Cors::new()
+            .allow_origin("https://example.com")
+            .allow_methods([Method::GET, Method::PUT])
+            .allow_headers([CONTENT_TYPE])
+    }
+
+    #[test]
+    fn preflight() {
+        let preflight = |cors| {
+            call(
+                cors,
+                req(
+                    Method::OPTIONS,
+                    &[
+                        ("origin", "https://example.com"),
+                        ("access-control-request-method", "PUT"),
+                        ("access-control-request-headers", "Content-Type"),
+                        (PRIVATE_NETWORK_REQ, "true"),
+                    ],
+                ),
+            )
+        };
+
+        // caching and private network access are opt-in.
+        let res = preflight(cors());
+        assert_eq!(res.status(), StatusCode::NO_CONTENT);
+        let headers = res.headers();
+        assert_eq!(headers.get(ACCESS_CONTROL_ALLOW_ORIGIN).unwrap(), "https://example.com");
+        assert_eq!(headers.get(ACCESS_CONTROL_ALLOW_METHODS).unwrap(), "GET, PUT");
+        assert_eq!(headers.get(ACCESS_CONTROL_ALLOW_HEADERS).unwrap(), "content-type");
+        assert_eq!(headers.get(VARY).unwrap(), "origin");
+        assert!(!headers.contains_key(ACCESS_CONTROL_MAX_AGE));
+        assert!(!headers.contains_key(PRIVATE_NETWORK_RES));
+        assert!(!headers.contains_key(ACCESS_CONTROL_ALLOW_CREDENTIALS));
+
+        let res = preflight(cors().max_age(Duration::from_secs(600)).allow_private_network());
+        assert_eq!(res.status(), StatusCode::NO_CONTENT);
+        assert_eq!(res.headers().get(ACCESS_CONTROL_MAX_AGE).unwrap(), "600");
+        assert_eq!(res.headers().get(PRIVATE_NETWORK_RES).unwrap(), "true");
+
+        // private network header is only reflected when asked for.
+        let res = call(
+            cors().allow_private_network(),
+            req(
+                Method::OPTIONS,
+                &[
+                    ("origin", "https://example.com"),
+                    ("access-control-request-method", "GET"),
+                ],
+            ),
+        );
+        assert_eq!(res.status(), StatusCode::NO_CONTENT);
+        assert!(!res.headers().contains_key(PRIVATE_NETWORK_RES));
+    }
+
+    #[test]
+    fn preflight_reject() {
+        for headers in [
+            [("origin", "https://evil.com"), ("access-control-request-method", "GET")],
+            [
+                ("origin", "https://example.com"),
+                ("access-control-request-method", "DELETE"),
+            ],
+        ] {
+            let res = call(cors(), req(Method::OPTIONS, &headers));
+            assert_eq!(res.status(), StatusCode::FORBIDDEN);
+        }
+
+        let mut preflight = req(
+            Method::OPTIONS,
+            &[
+                ("origin", "https://example.com"),
+                ("access-control-request-method", "GET"),
+            ],
+        );
+        preflight
+            .headers_mut()
+            .insert(ACCESS_CONTROL_REQUEST_HEADERS, HeaderValue::from_static("x-custom"));
+        let res = call(cors(), preflight);
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+
+        // OPTIONS request without request method header is not a preflight and reaches enclosed service.
+        let res = call(cors(), req(Method::OPTIONS, &[("origin", "https://example.com")]));
+        assert_eq!(res.status(), StatusCode::OK);
+        assert!(!res.headers().contains_key(ACCESS_CONTROL_ALLOW_METHODS));
+    }
+
+    #[test]
+    fn actual_request() {
+        let res = call(cors(), req(Method::GET, &[("origin", "https://example.com")]));
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(
+            res.headers().get(ACCESS_CONTROL_ALLOW_ORIGIN).unwrap(),
+            "https://example.com"
+        );
+
+        let res = call(cors(), req(Method::GET, &[("origin", "https://evil.com")]));
+        assert_eq!(res.status(), StatusCode::OK);
+        assert!(!res.headers().contains_key(ACCESS_CONTROL_ALLOW_ORIGIN));
+        assert_eq!(res.headers().get(VARY).unwrap(), "origin");
+
+        let res = call(cors(), req(Method::GET, &[]));
+        assert!(!res.headers().contains_key(ACCESS_CONTROL_ALLOW_ORIGIN));
+
+        let res = call(
+            Cors::new().allow_any_origin(),
+            req(Method::GET, &[("origin", "https://a.com")]),
+        );
+        assert_eq!(res.headers().get(ACCESS_CONTROL_ALLOW_ORIGIN).unwrap(), "*");
+
+        let res = call(
+            Cors::new().allow_any_origin().allow_credentials(),
+            req(Method::GET, &[("origin", "https://a.com")]),
+        );
+        assert_eq!(res.headers().get(ACCESS_CONTROL_ALLOW_ORIGIN).unwrap(), "https://a.com");
+        assert_eq!(res.headers().get(ACCESS_CONTROL_ALLOW_CREDENTIALS).unwrap(), "true");
+    }
+}
@@ -2,35 +2,251 @@
 
 use crate::service::Service;
 
+pub use body::DecompressBody;
+
+/// default max size of decompressed request body in bytes.
+pub const DEFAULT_MAX_SIZE: usize = 16 * 1024 * 1024;
+
 /// decompress middleware.
 ///
 /// look into [WebContext]'s `Content-Encoding` header and apply according decompression to
 /// it according to enabled compress feature.
 /// `compress-x` feature must be enabled for this middleware to function correctly.
 ///
+/// # Decompression bomb
+/// A small compressed request body can expand to huge amount of data. [`Decompress::max_size`] and
+/// [`Decompress::max_ratio`] can be used to cap the decompressed body. The caps are checked against the
+/// decompressed body stream so they apply uniformly to every extractor consuming it(`Json`, `Form`, `Multipart`,
+/// `Body` etc). Body going beyond any of them would produce [`DecompressOverFlow`] error with
+/// "413 Payload Too Large" response. By default decompressed body is capped at [`DEFAULT_MAX_SIZE`] and ratio cap is
+/// disabled.
+///
 /// # Type mutation
-/// `Decompress` would mutate request body type from `B` to `Coder<B>`. Service enclosed
+/// `Decompress` would mutate request body type from `B` to [`DecompressBody<B>`]. Service enclosed
 /// by it must be able to handle it's mutation or utilize [TypeEraser] to erase the mutation.
 /// For more explanation please reference [type mutation](crate::middleware#type-mutation).
 ///
+/// # Examples
+/// ```rust
+/// # use xitca_web::{handler::handler_service, middleware::decompress::Decompress, App, WebContext};
+/// App::new()
+///     .at("/", handler_service(|_: Vec<u8>| async { "hello,world!" }))
+///     # .at("/infer", handler_service(|_: &WebContext<'_>| async{ "infer type" }))
+///     // decompressed body can not be larger than 1MB or 100 times of it's compressed size.
+///     .enclosed(Decompress::new().max_size(1024 * 1024).max_ratio(100));
+///
+/// // middleware with default caps can be used like an unit struct.
+/// App::new()
+///     .at("/", handler_service(|_: Vec<u8>| async { "hello,world!" }))
+///     # .at("/infer", handler_service(|_: &WebContext<'_>| async{ "infer type" }))
+///     .enclosed(Decompress);
+/// ```
+///
 /// [WebContext]: crate::WebContext
 /// [TypeEraser]: crate::middleware::eraser::TypeEraser
-#[derive(Clone)]
-pub struct Decompress;
+/// [`DecompressOverFlow`]: crate::error::DecompressOverFlow
+#[derive(Clone, Copy)]
+pub struct Decompress {
+    max_size: usize,
+    max_ratio: usize,
+}
+
+impl Default for Decompress {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// decompress middleware with default caps. It keeps `.enclosed(Decompress)` working as if [`Decompress`] is an
+/// unit struct.
+#[allow(non_upper_case_globals)]
+pub const Decompress: Decompress = Decompress::new();
+
+impl Decompress {
+    /// construct decompress middleware with default caps on decompressed body.
+    pub const fn new() -> Self {
+        Self {
+            max_size: DEFAULT_MAX_SIZE,
+            max_ratio: usize::MAX,
+        }
+    }
+
+    /// Set max size in byte unit the request body can be after decompression.
+    /// Default to [`DEFAULT_MAX_SIZE`]. `usize::MAX` can be used to disable the cap.
+    pub fn max_size(mut self, size: usize) -> Self {
+        self.max_size = size;
+        self
+    }
+
+    /// Set max ratio between size of request body after and before decompression.
+    ///
+    /// Ratio is checked against total bytes of body observed so far. Well compressed legit body can reach a high
+    /// ratio and this cap should be set with expected content in mind.
+    ///
+    /// # Panics
+    /// When ratio is zero.
+    pub fn max_ratio(mut self, ratio: usize) -> Self {
+        assert!(ratio > 0, "compression ratio limit must not be zero");
+        self.max_ratio = ratio;
+        self
+    }
+}
 
 impl<S, E> Service<Result<S, E>> for Decompress {
     type Response = service::DecompressService<S>;
     type Error = E;
 
     async fn call(&self, res: Result<S, E>) -> Result<Self::Response, Self::Error> {
-        res.map(service::DecompressService)
+        res.map(|service| service::DecompressService { service, config: *self })
+    }
+}
+
+mod body {
+    use core::{
+        pin::Pin,
+        task::{ready, Context, Poll},
+    };
+
+    use futures_core::stream::Stream;
+    use http_encoding::{Coder, FeaturedCode};
+    use pin_project_lite::pin_project;
+
+    use crate::{
+        body::BodyStream,
+        bytes::Bytes,
+        error::{BodyError, DecompressOverFlow, DecompressOverFlowKind},
+    };
+
+    use super::Decompress;
+
+    pin_project! {
+        /// request body type decompressed by [`Decompress`] middleware.
+        pub struct DecompressBody<B> {
+            #[pin]
+            body: Coder<CountBody<B>, FeaturedCode>,
+            config: Decompress,
+            decoded: usize,
+        }
+    }
+
+    impl<B: Default> Default for DecompressBody<B> {
+        fn default() -> Self {
+            Self {
+                body: Coder::default(),
+                config: Decompress::new(),
+                decoded: 0,
+            }
+        }
+    }
+
+    impl<B> DecompressBody<B> {
+        pub(super) fn new(body: Coder<CountBody<B>, FeaturedCode>, config: Decompress) -> Self {
+            Self {
+                body,
+                config,
+                decoded: 0,
+            }
+        }
+
+        pub(super) fn into_inner(self) -> B
+        where
+            B: BodyStream,
+        {
+            self.body.into_inner().body
+        }
+    }
+
+    impl<B> Stream for DecompressBody<B>
+    where
+        B: BodyStream,
+    {
+        type Item = Result<Bytes, BodyError>;
+
+        fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+            let mut this = self.project();
+
+            // body went beyond limit keeps yielding error.
+            if let Some(e) = over_flow(*this.decoded, this.body.get_ref().read, this.config) {
+                return Poll::Ready(Some(Err(e)));
+            }
+
+            let Some(res) = ready!(this.body.as_mut().poll_next(cx)) else {
+                return Poll::Ready(None);
+            };
+            let chunk = res?;
+
+            *this.decoded += chunk.len();
+
+            if let Some(e) = over_flow(*this.decoded, this.body.get_ref().read, this.config) {
+                return Poll::Ready(Some(Err(e)));
+            }
+
+            Poll::Ready(Some(Ok(chunk)))
+        }
+
+        #[inline]
+        fn size_hint(&self) -> (usize, Option<usize>) {
+            self.body.size_hint()
+        }
+    }
+
+    fn over_flow(decoded: usize, encoded: usize, config: &Decompress) -> Option<BodyError> {
+        let kind = if decoded > config.max_size {
+            DecompressOverFlowKind::Size(config.max_size)
+        } else if decoded > encoded.saturating_mul(config.max_ratio) {
+            DecompressOverFlowKind::Ratio(config.max_ratio)
+        } else {
+            return None;
+        };
+        // search error module for downcast_ref::<DecompressOverFlow>() before considering change the error type.
+        Some(BodyError::from(DecompressOverFlow { kind }))
+    }
+
+    pin_project! {
+        // body wrapper counting bytes of request body before decompression.
+        #[derive(Default)]
+        pub struct CountBody<B> {
+            #[pin]
+            body: B,
+            read: usize,
+        }
+    }
+
+    impl<B> CountBody<B> {
+        pub(super) const fn new(body: B) -> Self {
+            Self { body, read: 0 }
+        }
+    }
+
+    impl<B> Stream for CountBody<B>
+    where
+        B: BodyStream,
+    {
+        type Item = Result<B::Chunk, BodyError>;
+
+        fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+            let this = self.project();
+            match ready!(this.body.poll_next(cx)) {
+                Some(res) => {
+                    let chunk = res.map_err(Into::into)?;
+                    *this.read += chunk.as_ref().len();
+                    Poll::Ready(Some(Ok(chunk)))
+                }
+                None => Poll::Ready(None),
+            }
+        }
+
+        #[inline]
+        fn size_hint(&self) -> (usize, Option<usize>) {
+            self.body.size_hint()
+        }
     }
 }
 
 mod service {
     use core::{cell::RefCell, convert::Infallible};
 
-    use http_encoding::{error::EncodingError, Coder};
+    use http_encoding::error::EncodingError;
 
     use crate::{
         body::BodyStream,
@@ -41,14 +257,20 @@ mod service {
         service::ready::ReadyService,
     };
 
-    use super::*;
+    use super::{
+        body::{CountBody, DecompressBody},
+        *,
+    };
 
-    pub struct DecompressService<S>(pub(super) S);
+    pub struct DecompressService<S> {
+        pub(super) service: S,
+        pub(super) config: Decompress,
+    }
 
     impl<'r, S, C, B, Res, Err> Service<WebContext<'r, C, B>> for DecompressService<S>
     where
         B: BodyStream + Default,
-        S: for<'rs> Service<WebContext<'rs, C, Coder<B>>, Response = Res, Error = Err>,
+        S: for<'rs> Service<WebContext<'rs, C, DecompressBody<B>>, Response = Res, Error = Err>,
         Err: Into<Error>,
     {
         type Response = Res;
@@ -60,11 +282,11 @@ mod service {
             let (ext, body) = ext.replace_body(());
             let req = Request::from_parts(parts, ());
 
-            let decoder = http_encoding::try_decoder(req.headers(), body)?;
-            let mut body = RefCell::new(decoder);
+            let decoder = http_encoding::try_decoder(req.headers(), CountBody::new(body))?;
+            let mut body = RefCell::new(DecompressBody::new(decoder, self.config));
             let mut req = req.map(|_| ext);
 
-            self.0
+            self.service
                 .call(WebContext::new(&mut req, &mut body, state))
                 .await
                 .map_err(|e| {
@@ -84,7 +306,7 @@ mod service {
 
         #[inline]
         async fn ready(&self) -> Self::Ready {
-            self.0.ready().await
+            self.service.ready().await
         }
     }
 
@@ -112,7 +334,7 @@ mod test {
         body::ResponseBody,
         handler::handler_service,
         http::header::CONTENT_ENCODING,
-        http::{WebRequest, WebResponse},
+        http::{StatusCode, WebRequest, WebResponse},
         test::collect_body,
         App,
     };
@@ -151,7 +373,7 @@ mod test {
         let req = WebRequest::default().map(|ext| ext.map_body(|_: ()| Q.into()));
         App::new()
             .at("/", handler_service(handler))
-            .enclosed(Decompress)
+            .enclosed(Decompress::new())
             .finish()
             .call(())
             .now_or_panic()
@@ -162,11 +384,9 @@ mod test {
             .unwrap();
     }
 
-    #[cfg(any(feature = "compress-br", feature = "compress-gz", feature = "compress-de"))]
-    #[test]
-    fn compressed() {
-        // a hack to generate a compressed client request from server response.
-        let res = WebResponse::<ResponseBody>::new(Q.into());
+    // a hack to generate a compressed client request from server response.
+    fn compressed_req(body: impl Into<ResponseBody>) -> WebRequest {
+        let res = WebResponse::<ResponseBody>::new(body.into());
 
         #[allow(unreachable_code)]
         let encoding = || {
@@ -199,16 +419,53 @@ mod test {
         req.headers_mut()
             .insert(CONTENT_ENCODING, parts.headers.remove(CONTENT_ENCODING).unwrap());
 
+        req
+    }
+
+    #[test]
+    fn compressed() {
         App::new()
             .at("/", handler_service(handler))
-            .enclosed(Decompress)
+            .enclosed(Decompress::new())
             .finish()
             .call(())
             .now_or_panic()
             .unwrap()
-            .call(req)
+            .call(compressed_req(Q))
             .now_or_panic()
             .ok()
             .unwrap();
     }
+
+    #[test]
+    fn bomb() {
+        const BOMB: &[u8] = &[0; 64 * 1024];
+
+        async fn handler(vec: Vec<u8>) -> &'static str {
+            assert_eq!(BOMB, vec);
+            A
+        }
+
+        let call = |decompress: Decompress| {
+            App::new()
+                .at("/", handler_service(handler))
+                .enclosed(decompress)
+                .finish()
+                .call(())
+                .now_or_panic()
+                .unwrap()
+                .call(compressed_req(BOMB))
+                .now_or_panic()
+                .ok()
+                .unwrap()
+                .status()
+        };
+
+        assert_eq!(call(Decompress), StatusCode::OK);
+        assert_eq!(call(Decompress::new()), StatusCode::OK);
+        assert_eq!(call(Decompress::new().max_size(BOMB.len())), StatusCode::OK);
+        assert_eq!(call(Decompress::new().max_size(1024)), StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(call(Decompress::new().max_ratio(10)), StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(call(Decompress::new().max_ratio(usize::MAX)), StatusCode::OK);
+    }
 }
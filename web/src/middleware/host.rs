@@ -0,0 +1,373 @@
+//! middleware for validating and normalizing request host.
+
+use std::sync::Arc;
+
+use crate::service::Service;
+
+/// middleware for validating host of request against an allowlist and optionally rewriting it to a canonical value.
+///
+/// # Security
+/// Host of request is controlled by client. Application using it for building absolute urls(password reset link,
+/// redirect location, etc), selecting tenant or generating cache keys can be tricked into trusting a host owned by
+/// attacker. (so called host header injection) This middleware rejects request with host not in the allowlist with
+/// "400 Bad Request" response before it reaches routing so handlers enclosed by it only observe trusted hosts.
+///
+/// # Host detection
+/// Host is read from uri authority of request(`:authority` pseudo header of http/2 and http/3) and falls back to
+/// `Host` header(http/1). Request carrying both with different values is rejected. Request without host is rejected.
+///
+/// # Matching
+/// - host is compared case insensitively and trailing dot is ignored.
+/// - allowed host without port matches request host without port or with default port of request scheme.(80 for
+///   http and 443 for https) Scheme is read from uri of request and falls back to if the connection is secure.
+/// - allowed host with port only matches request host with the same port. Request host without port is treated as
+///   having default port of request scheme.
+/// - allowed host starting with `*.` matches any sub domain of it. e.g. `*.example.com` matches `api.example.com`
+///   but not `example.com`.
+///
+/// # Rewrite
+/// When [`AllowHost::canonical`] is set every allowed request has it's `Host` header and uri authority(if present)
+/// rewritten to the canonical value. Otherwise host is normalized to lowercase with default port of request scheme
+/// removed.
+///
+/// # Examples
+/// ```rust
+/// # use xitca_web::{handler::handler_service, middleware::host::AllowHost, App, WebContext};
+/// App::new()
+///     .at("/", handler_service(|| async { "hello,world!" }))
+///     # .at("/infer", handler_service(|_: &WebContext<'_>| async{ "infer type" }))
+///     .enclosed(
+///         AllowHost::new()
+///             .host("example.com")
+///             .host("*.example.com")
+///             .host("localhost:8080")
+///             .canonical("example.com"),
+///     );
+/// ```
+#[derive(Clone, Default)]
+pub struct AllowHost {
+    hosts: Vec<Box<str>>,
+    canonical: Option<Box<str>>,
+}
+
+impl AllowHost {
+    /// construct middleware with empty allowlist. Every request is rejected until host is added.
+    pub const fn new() -> Self {
+        Self {
+            hosts: Vec::new(),
+            canonical: None,
+        }
+    }
+
+    /// add host to allowlist. host can include a port and can start with `*.` for matching sub domains.
+    /// e.g. `example.com`, `example.com:8443` or `*.example.com`
+    pub fn host(mut self, host: impl AsRef<str>) -> Self {
+        self.hosts.push(normalize(host.as_ref(), None).into_boxed_str());
+        self
+    }
+
+    /// rewrite host of allowed request to given canonical value. host can include a port.
+    ///
+    /// # Panics
+    /// When host is not a valid uri authority.
+    pub fn canonical(mut self, host: impl AsRef<str>) -> Self {
+        let host = normalize(host.as_ref(), None);
+        assert!(
+            host.parse::<crate::http::uri::Authority>().is_ok(),
+            "canonical host must be valid uri authority"
+        );
+        self.canonical = Some(host.into_boxed_str());
+        self
+    }
+
+    // host is normalized with default port of request scheme removed.
+    fn is_allowed(&self, host: &str, default_port: &str) -> bool {
+        let (name, port) = split_port(host);
+        self.hosts.iter().any(|allowed| {
+            let (allowed_name, allowed_port) = split_port(allowed);
+            if allowed_port != port && (port.is_some() || allowed_port != Some(default_port)) {
+                return false;
+            }
+            match allowed_name.strip_prefix("*.") {
+                Some(domain) => name
+                    .strip_suffix(domain)
+                    .and_then(|sub| sub.strip_suffix('.'))
+                    .is_some_and(|sub| !sub.is_empty()),
+                None => allowed_name == name,
+            }
+        })
+    }
+}
+
+// lowercase host, remove trailing dot of domain and given default port.
+fn normalize(host: &str, default_port: Option<&str>) -> String {
+    let host = host.to_ascii_lowercase();
+    let (name, port) = split_port(&host);
+    let name = name.strip_suffix('.').unwrap_or(name);
+    match port {
+        Some(port) if Some(port) != default_port => format!("{name}:{port}"),
+        _ => name.to_owned(),
+    }
+}
+
+// split host into name and port. empty port is treated as no port.
+fn split_port(host: &str) -> (&str, Option<&str>) {
+    // ipv6 literal is bracketed and it's port follows closing bracket.
+    let idx = match host.rfind(']') {
+        Some(end) => host[end..].find(':').map(|i| i + end),
+        None => host.rfind(':'),
+    };
+    match idx {
+        Some(idx) => match &host[idx + 1..] {
+            "" => (&host[..idx], None),
+            port => (&host[..idx], Some(port)),
+        },
+        None => (host, None),
+    }
+}
+
+impl<S, E> Service<Result<S, E>> for AllowHost {
+    type Response = service::AllowHostService<S>;
+    type Error = E;
+
+    async fn call(&self, res: Result<S, E>) -> Result<Self::Response, Self::Error> {
+        res.map(|service| service::AllowHostService {
+            service,
+            config: Arc::new(self.clone()),
+        })
+    }
+}
+
+mod service {
+    use crate::{
+        error::Error,
+        http::{
+            header::{HeaderValue, HOST},
+            uri::{Authority, Uri},
+            StatusCode, WebResponse,
+        },
+        service::{ready::ReadyService, Service},
+        WebContext,
+    };
+
+    use super::*;
+
+    pub struct AllowHostService<S> {
+        pub(super) service: S,
+        pub(super) config: Arc<AllowHost>,
+    }
+
+    impl<'r, C, B, S, ResB> Service<WebContext<'r, C, B>> for AllowHostService<S>
+    where
+        S: for<'r2> Service<WebContext<'r2, C, B>, Response = WebResponse<ResB>, Error = Error>,
+    {
+        type Response = WebResponse<ResB>;
+        type Error = Error;
+
+        async fn call(&self, mut ctx: WebContext<'r, C, B>) -> Result<Self::Response, Self::Error> {
+            let default_port = default_port(&ctx);
+            let host = request_host(&ctx, default_port).ok_or(StatusCode::BAD_REQUEST)?;
+
+            if !self.config.is_allowed(&host, default_port) {
+                return Err(Error::from(StatusCode::BAD_REQUEST));
+            }
+
+            let host = self.config.canonical.as_deref().unwrap_or(&host);
+            rewrite_host(&mut ctx, host);
+
+            self.service.call(ctx).await
+        }
+    }
+
+    impl<S> ReadyService for AllowHostService<S>
+    where
+        S: ReadyService,
+    {
+        type Ready = S::Ready;
+
+        #[inline]
+        async fn ready(&self) -> Self::Ready {
+            self.service.ready().await
+        }
+    }
+
+    // default port of request scheme. scheme of uri takes precedence over security of connection.
+    fn default_port<C, B>(ctx: &WebContext<'_, C, B>) -> &'static str {
+        let req = ctx.req();
+        let is_https = match req.uri().scheme_str() {
+            Some(scheme) => scheme.eq_ignore_ascii_case("https"),
+            None => req.body().is_secure(),
+        };
+        if is_https {
+            "443"
+        } else {
+            "80"
+        }
+    }
+
+    // normalized host of request. None when host is absent, invalid or ambiguous.
+    fn request_host<C, B>(ctx: &WebContext<'_, C, B>, default_port: &str) -> Option<String> {
+        let req = ctx.req();
+
+        let header = match req.headers().get(HOST) {
+            Some(host) => Some(host.to_str().ok()?.parse::<Authority>().ok()?),
+            None => None,
+        };
+
+        let host = match (req.uri().authority(), header) {
+            (Some(authority), Some(header)) => {
+                let authority = normalize(authority.as_str(), Some(default_port));
+                (authority == normalize(header.as_str(), Some(default_port))).then_some(authority)?
+            }
+            (Some(authority), None) => normalize(authority.as_str(), Some(default_port)),
+            (None, Some(header)) => normalize(header.as_str(), Some(default_port)),
+            (None, None) => return None,
+        };
+
+        // userinfo is not a part of host and is rejected.
+        (!host.contains('@')).then_some(host)
+    }
+
+    fn rewrite_host<C, B>(ctx: &mut WebContext<'_, C, B>, host: &str) {
+        let Ok(value) = HeaderValue::try_from(host) else {
+            return;
+        };
+
+        let req = ctx.req_mut();
+
+        if req.uri().authority().is_some_and(|a| a.as_str() != host) {
+            let mut parts = req.uri().clone().into_parts();
+            parts.authority = host.parse().ok();
+            if let Ok(uri) = Uri::from_parts(parts) {
+                *req.uri_mut() = uri;
+            }
+        }
+
+        req.headers_mut().insert(HOST, value);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use xitca_unsafe_collection::futures::NowOrPanic;
+
+    use crate::{
+        handler::handler_service,
+        http::{
+            header::{HeaderValue, HOST},
+            StatusCode, Uri, WebRequest,
+        },
+        test::collect_string_body,
+        App, WebContext,
+    };
+
+    use super::*;
+
+    fn req(uri: &'static str, host: Option<&'static str>) -> WebRequest {
+        let mut req = WebRequest::default();
+        *req.uri_mut() = Uri::from_static(uri);
+        if let Some(host) = host {
+            req.headers_mut().insert(HOST, HeaderValue::from_static(host));
+        }
+        req
+    }
+
+    async fn handler(ctx: &WebContext<'_>) -> String {
+        let host = ctx.req().headers().get(HOST).unwrap().to_str().unwrap();
+        let authority = ctx.req().uri().authority().map(|a| a.as_str()).unwrap_or("");
+        format!("{host} {authority}")
+    }
+
+    fn call(host: AllowHost, req: WebRequest) -> (StatusCode, String) {
+        let service = App::new()
+            .at("/", handler_service(handler))
+            .enclosed(host)
+            .finish()
+            .call(())
+            .now_or_panic()
+            .unwrap();
+        let res = service.call(req).now_or_panic().unwrap();
+        let status = res.status();
+        let body = collect_string_body(res.into_body()).now_or_panic().unwrap();
+        (status, body)
+    }
+
+    #[test]
+    fn matching() {
+        let host = AllowHost::new()
+            .host("Example.com")
+            .host("*.example.org")
+            .host("localhost:8080")
+            .host("[::1]:3000");
+
+        let is_allowed =
+            |host_str: &str, default_port| host.is_allowed(&normalize(host_str, Some(default_port)), default_port);
+
+        for allowed in [
+            "example.com",
+            "EXAMPLE.com.",
+            "example.com:80",
+            "api.example.org",
+            "a.b.example.org",
+            "localhost:8080",
+            "[::1]:3000",
+        ] {
+            assert!(is_allowed(allowed, "80"), "{allowed}");
+        }
+
+        for rejected in [
+            "example.com:443",
+            "example.com:8080",
+            "evil.com",
+            "example.com.evil.com",
+            "example.org",
+            "evilexample.org",
+            "localhost",
+            "[::1]",
+        ] {
+            assert!(!is_allowed(rejected, "80"), "{rejected}");
+        }
+
+        // default port is decided by scheme.
+        assert!(is_allowed("example.com:443", "443"));
+        assert!(!is_allowed("example.com:80", "443"));
+
+        // allowed host with default port matches request host without port.
+        let host = AllowHost::new().host("example.com:443");
+        assert!(host.is_allowed(&normalize("example.com", Some("443")), "443"));
+        assert!(host.is_allowed(&normalize("example.com:443", Some("443")), "443"));
+        assert!(!host.is_allowed(&normalize("example.com", Some("80")), "80"));
+    }
+
+    #[test]
+    fn reject_and_rewrite() {
+        let host = || AllowHost::new().host("example.com").host("www.example.com");
+
+        let (status, body) = call(host(), req("/", Some("Example.com:80")));
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, "example.com ");
+
+        // 443 is not default port of plain http request.
+        let (status, _) = call(host(), req("/", Some("example.com:443")));
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let (status, body) = call(host(), req("https://example.com:443/", None));
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, "example.com example.com");
+
+        let (status, _) = call(host(), req("/", Some("evil.com")));
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let (status, _) = call(host(), req("/", None));
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        // http/2 style request with authority in uri.
+        let (status, body) = call(host().canonical("example.com"), req("https://www.example.com/", None));
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, "example.com example.com");
+
+        // authority and host header disagree.
+        let (status, _) = call(host(), req("https://example.com/", Some("www.example.com")));
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,326 @@
+//! middleware for enforcing https connection.
+
+use crate::service::Service;
+
+/// middleware for redirecting or rejecting request not sent through https connection.
+///
+/// By default plain http request is redirected with "308 Permanent Redirect" response to the same path and query
+/// with https scheme. [`RequireHttps::reject`] can be used to respond with "403 Forbidden" instead.
+///
+/// # Detection
+/// A request is considered sent through https when:
+/// - it's received from a connection secured by tls. (See [`RequestExt::is_secure`]. http/3 is always secure)
+/// - [`RequireHttps::trust_forwarded_proto`] is enabled and the first element of `X-Forwarded-Proto` or `Forwarded`
+///   header tells `https`. `X-Forwarded-Proto` takes precedence when both headers are present.
+///
+/// Scheme of request uri is claimed by client and it's ignored. (e.g. http/2 cleartext request can carry `https` in
+/// it's `:scheme` pseudo header)
+///
+/// [`RequestExt::is_secure`]: crate::http::RequestExt::is_secure
+///
+/// # Redirect target
+/// Location of redirect is built from request's `Host` header(or uri authority when header is absent) with it's port
+/// removed. [`RequireHttps::host`] can be used to redirect to a canonical host instead. Request without a usable
+/// host is responded with "400 Bad Request".
+///
+/// # Examples
+/// ```rust
+/// # use xitca_web::{handler::handler_service, middleware::https::RequireHttps, App, WebContext};
+/// App::new()
+///     .at("/", handler_service(|| async { "hello,world!" }))
+///     # .at("/infer", handler_service(|_: &WebContext<'_>| async{ "infer type" }))
+///     // application deployed behind a tls terminating reverse proxy.
+///     .enclosed(RequireHttps::new().host("example.com").trust_forwarded_proto());
+/// ```
+#[derive(Clone, Default)]
+pub struct RequireHttps {
+    reject: bool,
+    forwarded_proto: bool,
+    host: Option<String>,
+}
+
+impl RequireHttps {
+    /// construct middleware redirecting plain http request to https.
+    pub const fn new() -> Self {
+        Self {
+            reject: false,
+            forwarded_proto: false,
+            host: None,
+        }
+    }
+
+    /// reject plain http request with "403 Forbidden" response instead of redirecting it.
+    pub fn reject(mut self) -> Self {
+        self.reject = true;
+        self
+    }
+
+    /// use `X-Forwarded-Proto` and `Forwarded` headers for detecting https connection.
+    ///
+    /// The headers can be set by any client and it should only be trusted when application is deployed behind a
+    /// reverse proxy that always overwrites them.
+    pub fn trust_forwarded_proto(mut self) -> Self {
+        self.forwarded_proto = true;
+        self
+    }
+
+    /// redirect to given canonical host instead of the host requested. host can include a port.
+    /// e.g. `example.com` or `example.com:8443`
+    pub fn host(mut self, host: impl Into<String>) -> Self {
+        self.host = Some(host.into());
+        self
+    }
+}
+
+impl<S, E> Service<Result<S, E>> for RequireHttps {
+    type Response = service::RequireHttpsService<S>;
+    type Error = E;
+
+    async fn call(&self, res: Result<S, E>) -> Result<Self::Response, Self::Error> {
+        res.map(|service| service::RequireHttpsService {
+            service,
+            config: self.clone(),
+        })
+    }
+}
+
+mod service {
+    use core::{convert::Infallible, fmt};
+
+    use std::error;
+
+    use crate::{
+        body::ResponseBody,
+        error::Error,
+        http::{
+            header::{HeaderName, HeaderValue, FORWARDED, HOST, LOCATION},
+            uri::Authority,
+            StatusCode, WebRequest, WebResponse,
+        },
+        service::{ready::ReadyService, Service},
+        WebContext,
+    };
+
+    use super::RequireHttps;
+
+    const X_FORWARDED_PROTO: HeaderName = HeaderName::from_static("x-forwarded-proto");
+
+    pub struct RequireHttpsService<S> {
+        pub(super) service: S,
+        pub(super) config: RequireHttps,
+    }
+
+    impl<'r, C, B, S, ResB> Service<WebContext<'r, C, B>> for RequireHttpsService<S>
+    where
+        S: for<'r2> Service<WebContext<'r2, C, B>, Response = WebResponse<ResB>, Error = Error>,
+    {
+        type Response = WebResponse<ResB>;
+        type Error = Error;
+
+        async fn call(&self, ctx: WebContext<'r, C, B>) -> Result<Self::Response, Self::Error> {
+            if is_https(ctx.req(), self.config.forwarded_proto) {
+                return self.service.call(ctx).await;
+            }
+
+            if self.config.reject {
+                return Err(Error::from(StatusCode::FORBIDDEN));
+            }
+
+            location(ctx.req(), self.config.host.as_deref())
+                .map(|location| Err(Error::from(HttpsRedirect(location))))
+                .unwrap_or_else(|| Err(Error::from(StatusCode::BAD_REQUEST)))
+        }
+    }
+
+    impl<S> ReadyService for RequireHttpsService<S>
+    where
+        S: ReadyService,
+    {
+        type Ready = S::Ready;
+
+        #[inline]
+        async fn ready(&self) -> Self::Ready {
+            self.service.ready().await
+        }
+    }
+
+    fn is_https(req: &WebRequest<()>, forwarded_proto: bool) -> bool {
+        if req.body().is_secure() {
+            return true;
+        }
+
+        if !forwarded_proto {
+            return false;
+        }
+
+        let headers = req.headers();
+
+        let proto = match headers.get(X_FORWARDED_PROTO) {
+            Some(v) => v.to_str().ok().and_then(|v| v.split(',').next()),
+            // Forwarded: for=192.0.2.60;proto=https;by=203.0.113.43, for=198.51.100.17
+            None => headers
+                .get(FORWARDED)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.split(',').next())
+                .and_then(|v| {
+                    v.split(';').find_map(|pair| {
+                        let (k, v) = pair.split_once('=')?;
+                        k.trim()
+                            .eq_ignore_ascii_case("proto")
+                            .then(|| v.trim().trim_matches('"'))
+                    })
+                }),
+        };
+
+        proto.is_some_and(|proto| proto.trim().eq_ignore_ascii_case("https"))
+    }
+
+    fn location(req: &WebRequest<()>, canonical: Option<&str>) -> Option<HeaderValue> {
+        let path = req.uri().path_and_query().map(|p| p.as_str()).unwrap_or("/");
+
+        let location = match canonical {
+            Some(host) => format!("https://{host}{path}"),
+            None => {
+                let authority = match req.headers().get(HOST) {
+                    Some(host) => host.to_str().ok()?.parse::<Authority>().ok()?,
+                    None => req.uri().authority()?.clone(),
+                };
+                // port of plain http is not relevant to https and is removed.
+                format!("https://{}{path}", authority.host())
+            }
+        };
+
+        HeaderValue::try_from(location).ok()
+    }
+
+    // redirect response is produced through error path so the middleware does not have to construct response
+    // body type of the service it encloses.
+    struct HttpsRedirect(HeaderValue);
+
+    impl fmt::Debug for HttpsRedirect {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_tuple("HttpsRedirect").field(&self.0).finish()
+        }
+    }
+
+    impl fmt::Display for HttpsRedirect {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("plain http request redirected to https")
+        }
+    }
+
+    impl error::Error for HttpsRedirect {}
+
+    impl<'r, C, B> Service<WebContext<'r, C, B>> for HttpsRedirect {
+        type Response = WebResponse;
+        type Error = Infallible;
+
+        async fn call(&self, ctx: WebContext<'r, C, B>) -> Result<Self::Response, Self::Error> {
+            let mut res = ctx.into_response(ResponseBody::empty());
+            *res.status_mut() = StatusCode::PERMANENT_REDIRECT;
+            res.headers_mut().insert(LOCATION, self.0.clone());
+            Ok(res)
+        }
+    }
+
+    crate::error::error_from_service!(HttpsRedirect);
+}
+
+#[cfg(test)]
+mod test {
+    use xitca_unsafe_collection::futures::NowOrPanic;
+
+    use crate::{
+        handler::handler_service,
+        http::{
+            header::{HeaderValue, FORWARDED, HOST, LOCATION},
+            StatusCode, Uri, WebRequest,
+        },
+        App,
+    };
+
+    use super::*;
+
+    fn req(uri: &'static str, host: Option<&'static str>, proto: Option<&'static str>) -> WebRequest {
+        let mut req = WebRequest::default();
+        *req.uri_mut() = Uri::from_static(uri);
+        if let Some(host) = host {
+            req.headers_mut().insert(HOST, HeaderValue::from_static(host));
+        }
+        if let Some(proto) = proto {
+            req.headers_mut()
+                .insert("x-forwarded-proto", HeaderValue::from_static(proto));
+        }
+        req
+    }
+
+    #[test]
+    fn redirect_and_reject() {
+        let build = |https: RequireHttps| {
+            App::new()
+                .at("/foo", handler_service(|| async { "hello,world!" }))
+                .enclosed(https)
+                .finish()
+                .call(())
+                .now_or_panic()
+                .unwrap()
+        };
+
+        let service = build(RequireHttps::new());
+
+        let res = service
+            .call(req("/foo?bar=996", Some("example.com:8080"), Some("https")))
+            .now_or_panic()
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::PERMANENT_REDIRECT);
+        assert_eq!(res.headers().get(LOCATION).unwrap(), "https://example.com/foo?bar=996");
+
+        let res = service.call(req("/foo", None, None)).now_or_panic().unwrap();
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+        // scheme claimed by request uri is ignored.
+        let res = service
+            .call(req("https://example.com/foo", None, None))
+            .now_or_panic()
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::PERMANENT_REDIRECT);
+        assert_eq!(res.headers().get(LOCATION).unwrap(), "https://example.com/foo");
+
+        let service = build(RequireHttps::new().host("example.com:8443").trust_forwarded_proto());
+
+        let res = service
+            .call(req("/foo", Some("evil.com"), Some("http")))
+            .now_or_panic()
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::PERMANENT_REDIRECT);
+        assert_eq!(res.headers().get(LOCATION).unwrap(), "https://example.com:8443/foo");
+
+        let res = service
+            .call(req("/foo", Some("example.com"), Some("https, http")))
+            .now_or_panic()
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let mut r = req("/foo", Some("example.com"), None);
+        r.headers_mut().insert(
+            FORWARDED,
+            HeaderValue::from_static("for=192.0.2.60;Proto=\"https\";by=203.0.113.43, proto=http"),
+        );
+        let res = service.call(r).now_or_panic().unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let mut r = req("/foo", Some("example.com"), None);
+        r.headers_mut()
+            .insert(FORWARDED, HeaderValue::from_static("for=192.0.2.60;proto=http"));
+        let res = service.call(r).now_or_panic().unwrap();
+        assert_eq!(res.status(), StatusCode::PERMANENT_REDIRECT);
+
+        let service = build(RequireHttps::new().reject());
+
+        let res = service
+            .call(req("/foo", Some("example.com"), Some("https")))
+            .now_or_panic()
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+    }
+}
@@ -0,0 +1,631 @@
+//! idempotency key middleware.
+
+use core::time::Duration;
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+
+use crate::{
+    bytes::Bytes,
+    http::{
+        header::{HeaderMap, HeaderName, HeaderValue},
+        StatusCode, WebResponse,
+    },
+    service::Service,
+};
+
+use super::buffer::BufferBody;
+
+/// default max size of response body [Idempotency] middleware would buffer and store in bytes.
+pub const DEFAULT_MAX_BODY_SIZE: usize = 1024 * 1024;
+
+/// default max count of entries [MemoryStore] would hold.
+pub const DEFAULT_CAPACITY: usize = 1024;
+
+/// max length of idempotency key in bytes.
+pub const MAX_KEY_LENGTH: usize = 255;
+
+#[allow(clippy::declare_interior_mutable_const)]
+pub const IDEMPOTENCY_KEY: HeaderName = HeaderName::from_static("idempotency-key");
+
+#[allow(clippy::declare_interior_mutable_const)]
+pub const IDEMPOTENT_REPLAYED: HeaderName = HeaderName::from_static("idempotent-replayed");
+
+/// middleware for deduplicating retried requests with `Idempotency-Key` header in a [IdempotencyStore].
+///
+/// Request with unsafe method(any method other than GET, HEAD, OPTIONS and TRACE) carrying `Idempotency-Key`
+/// header reserves it's key before calling enclosed service and the produced response is stored under the key
+/// until it's expired. Retry of the request with the same key is served with the stored response and
+/// `Idempotent-Replayed: true` header without calling enclosed service again. Key is scoped to method and path of
+/// request so the same key sent to different endpoint is treated as a different request.
+///
+/// Request without the header or with safe method is passed through. Key that is empty, not visible ascii or
+/// longer than [MAX_KEY_LENGTH] is rejected with "400 Bad Request" response.
+///
+/// # Concurrency
+/// Request arriving while another request with the same key is still in flight is rejected with
+/// "409 Conflict" response. Client is expected to retry it later and receive the stored response.
+///
+/// Reservation is released when enclosed service returns error, when the response status does not pass
+/// [Idempotency::storable_status] filter or when the request is cancelled before producing response(for example
+/// client disconnected). Retry after release calls enclosed service again.
+///
+/// Request is rejected with "503 Service Unavailable" response when the store has no room for a new key.
+///
+/// # Stored response
+/// A response is stored when it passes [Idempotency::storable_status] filter and it's body is not larger than
+/// [Idempotency::max_body_size]. By default all response except 5xx server error is stored so client can retry
+/// server error. Streaming body is buffered up to the limit and streamed as is when exceeding it.
+///
+/// Response with body larger than the limit or failed to be buffered can not be replayed. It's key is kept as
+/// completed until expired and retry is rejected with "422 Unprocessable Entity" response without calling enclosed
+/// service again.
+///
+/// # Type mutation
+/// `Idempotency` would mutate response body type from `B` to `BufferBody<B>`. Service enclosed by it must be able
+/// to handle it's mutation or utilize [TypeEraser] to erase the mutation.
+/// For more explanation please reference [type mutation](crate::middleware#type-mutation).
+///
+/// # Examples
+/// ```rust
+/// # use core::time::Duration;
+/// # use xitca_web::{handler::handler_service, middleware::idempotency::Idempotency, route::post, App, WebContext};
+/// App::new()
+///     .at("/payment", post(handler_service(|| async { "charged" })))
+///     # .at("/infer", handler_service(|_: &WebContext<'_>| async{ "infer type" }))
+///     // keep response of request with idempotency key for 24 hours.
+///     .enclosed(Idempotency::new(Duration::from_secs(24 * 60 * 60)));
+/// ```
+///
+/// [TypeEraser]: crate::middleware::eraser::TypeEraser
+pub struct Idempotency<S = MemoryStore> {
+    store: Arc<S>,
+    ttl: Duration,
+    max_body_size: usize,
+    storable_status: fn(StatusCode) -> bool,
+}
+
+impl<S> Clone for Idempotency<S> {
+    fn clone(&self) -> Self {
+        Self {
+            store: self.store.clone(),
+            ttl: self.ttl,
+            max_body_size: self.max_body_size,
+            storable_status: self.storable_status,
+        }
+    }
+}
+
+impl Idempotency {
+    /// construct middleware with [MemoryStore] and given time to live of stored response.
+    pub fn new(ttl: Duration) -> Self {
+        Self::with_store(MemoryStore::new(DEFAULT_CAPACITY), ttl)
+    }
+}
+
+impl<S> Idempotency<S>
+where
+    S: IdempotencyStore,
+{
+    /// construct middleware with given store and time to live of stored response.
+    pub fn with_store(store: S, ttl: Duration) -> Self {
+        Self {
+            store: Arc::new(store),
+            ttl,
+            max_body_size: DEFAULT_MAX_BODY_SIZE,
+            storable_status: |status| !status.is_server_error(),
+        }
+    }
+
+    /// set max size in bytes a response body can be buffered and stored.
+    /// Default to [DEFAULT_MAX_BODY_SIZE].
+    pub fn max_body_size(mut self, size: usize) -> Self {
+        self.max_body_size = size;
+        self
+    }
+
+    /// set filter of response status that can be stored. Default to all status except 5xx server error.
+    pub fn storable_status(mut self, filter: fn(StatusCode) -> bool) -> Self {
+        self.storable_status = filter;
+        self
+    }
+}
+
+impl<S, Svc, E> Service<Result<Svc, E>> for Idempotency<S> {
+    type Response = service::IdempotencyService<Svc, S>;
+    type Error = E;
+
+    async fn call(&self, res: Result<Svc, E>) -> Result<Self::Response, Self::Error> {
+        res.map(|service| service::IdempotencyService {
+            service,
+            idempotency: self.clone(),
+        })
+    }
+}
+
+/// storage of reservations and stored responses. Store is shared by all workers of server.
+///
+/// [IdempotencyStore::reserve] must be atomic: among concurrent calls with the same key only one can observe
+/// [Reservation::Reserved]. Expired entry must be treated as absent.
+pub trait IdempotencyStore: Send + Sync + 'static {
+    /// try to reserve key for a new request. A reservation is kept until it's completed or released or until
+    /// given ttl passed.
+    fn reserve(&self, key: &str, ttl: Duration) -> Reservation;
+
+    /// complete reservation of key with response. stored response replaces the reservation.
+    fn complete(&self, key: &str, res: StoredResponse);
+
+    /// complete reservation of key without response. the key is kept until given expire time and reserving it
+    /// must observe [Reservation::Unreplayable].
+    fn complete_unreplayable(&self, key: &str, expires: Instant);
+
+    /// release reservation of key without storing response. stored response must not be removed.
+    fn release(&self, key: &str);
+}
+
+/// outcome of [IdempotencyStore::reserve].
+pub enum Reservation {
+    /// key is reserved by caller.
+    Reserved,
+    /// key is reserved by another request still in flight.
+    InFlight,
+    /// a response is stored for key.
+    Completed(StoredResponse),
+    /// request of key is completed but it's response can not be replayed.
+    Unreplayable,
+    /// store has no room for key.
+    Full,
+}
+
+/// response stored in [IdempotencyStore].
+#[derive(Clone)]
+pub struct StoredResponse {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Bytes,
+    expires: Instant,
+}
+
+impl StoredResponse {
+    /// time when response is expired.
+    pub fn expires(&self) -> Instant {
+        self.expires
+    }
+
+    /// check if response is expired.
+    pub fn is_expired(&self) -> bool {
+        self.expires <= Instant::now()
+    }
+
+    fn to_response<B>(&self) -> WebResponse<BufferBody<B>> {
+        let mut res = WebResponse::new(BufferBody::buffered(self.body.clone()));
+        *res.status_mut() = self.status;
+        *res.headers_mut() = self.headers.clone();
+        res.headers_mut()
+            .insert(IDEMPOTENT_REPLAYED, HeaderValue::from_static("true"));
+        res
+    }
+}
+
+enum Entry {
+    InFlight(Instant),
+    Completed(StoredResponse),
+    Unreplayable(Instant),
+}
+
+impl Entry {
+    fn expires(&self) -> Instant {
+        match self {
+            Self::InFlight(expires) | Self::Unreplayable(expires) => *expires,
+            Self::Completed(res) => res.expires,
+        }
+    }
+
+    fn is_expired(&self) -> bool {
+        self.expires() <= Instant::now()
+    }
+}
+
+/// in memory [IdempotencyStore] with bounded count of entries.
+///
+/// When store is full expired entries are removed on reservation. When there is still no room for new key the
+/// completed entry closest to expire is evicted and [Reservation::Full] is returned when all entries are in flight.
+pub struct MemoryStore {
+    capacity: usize,
+    map: Mutex<HashMap<String, Entry>>,
+}
+
+impl MemoryStore {
+    /// construct store holding up to capacity count of entries.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            map: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl IdempotencyStore for MemoryStore {
+    fn reserve(&self, key: &str, ttl: Duration) -> Reservation {
+        let mut map = self.map.lock().unwrap();
+        match map.get(key) {
+            Some(entry) if entry.is_expired() => {}
+            Some(Entry::InFlight(_)) => return Reservation::InFlight,
+            Some(Entry::Completed(res)) => return Reservation::Completed(res.clone()),
+            Some(Entry::Unreplayable(_)) => return Reservation::Unreplayable,
+            None => {
+                if map.len() >= self.capacity {
+                    map.retain(|_, entry| !entry.is_expired());
+                    if map.len() >= self.capacity {
+                        let oldest = map
+                            .iter()
+                            .filter(|(_, entry)| !matches!(entry, Entry::InFlight(_)))
+                            .min_by_key(|(_, entry)| entry.expires())
+                            .map(|(key, _)| key.clone());
+                        match oldest {
+                            Some(key) => map.remove(&key),
+                            None => return Reservation::Full,
+                        };
+                    }
+                }
+            }
+        }
+        map.insert(key.to_owned(), Entry::InFlight(Instant::now() + ttl));
+        Reservation::Reserved
+    }
+
+    fn complete(&self, key: &str, res: StoredResponse) {
+        if let Some(entry) = self.map.lock().unwrap().get_mut(key) {
+            *entry = Entry::Completed(res);
+        }
+    }
+
+    fn complete_unreplayable(&self, key: &str, expires: Instant) {
+        if let Some(entry) = self.map.lock().unwrap().get_mut(key) {
+            *entry = Entry::Unreplayable(expires);
+        }
+    }
+
+    fn release(&self, key: &str) {
+        let mut map = self.map.lock().unwrap();
+        if let Some(Entry::InFlight(_)) = map.get(key) {
+            map.remove(key);
+        }
+    }
+}
+
+mod service {
+    use core::{future::poll_fn, pin::Pin};
+
+    use futures_core::stream::Stream;
+    use xitca_http::body::BodySize;
+
+    use crate::{
+        bytes::BytesMut,
+        error::{BodyError, Error, InvalidHeaderValue},
+        http::Method,
+        service::ready::ReadyService,
+        WebContext,
+    };
+
+    use super::*;
+
+    pub struct IdempotencyService<Svc, S> {
+        pub(super) service: Svc,
+        pub(super) idempotency: Idempotency<S>,
+    }
+
+    impl<'r, C, B, Svc, S, ResB, BE> Service<WebContext<'r, C, B>> for IdempotencyService<Svc, S>
+    where
+        Svc: for<'r2> Service<WebContext<'r2, C, B>, Response = WebResponse<ResB>, Error = Error>,
+        S: IdempotencyStore,
+        ResB: Stream<Item = Result<Bytes, BE>> + Unpin,
+        BE: Into<BodyError>,
+    {
+        type Response = WebResponse<BufferBody<ResB>>;
+        type Error = Error;
+
+        async fn call(&self, ctx: WebContext<'r, C, B>) -> Result<Self::Response, Self::Error> {
+            let req = ctx.req();
+
+            let (Some(key), false) = (
+                req.headers().get(IDEMPOTENCY_KEY),
+                matches!(
+                    *req.method(),
+                    Method::GET | Method::HEAD | Method::OPTIONS | Method::TRACE
+                ),
+            ) else {
+                return self.service.call(ctx).await.map(|res| res.map(BufferBody::new));
+            };
+
+            let key = key
+                .to_str()
+                .ok()
+                .filter(|key| !key.is_empty() && key.len() <= MAX_KEY_LENGTH)
+                .ok_or(InvalidHeaderValue(IDEMPOTENCY_KEY))?;
+            let key = format!("{} {} {key}", req.method(), req.uri().path());
+
+            let store = &*self.idempotency.store;
+            match store.reserve(&key, self.idempotency.ttl) {
+                Reservation::Reserved => {}
+                Reservation::InFlight => return Err(Error::from(StatusCode::CONFLICT)),
+                Reservation::Completed(res) => return Ok(res.to_response()),
+                Reservation::Unreplayable => return Err(Error::from(StatusCode::UNPROCESSABLE_ENTITY)),
+                Reservation::Full => return Err(Error::from(StatusCode::SERVICE_UNAVAILABLE)),
+            }
+
+            // reservation is bound to the lifetime of call future and released on drop unless completed.
+            let guard = ReservationGuard { store, key: &key };
+
+            let res = self.service.call(ctx).await?;
+
+            if !(self.idempotency.storable_status)(res.status()) {
+                return Ok(res.map(BufferBody::new));
+            }
+
+            let expires = Instant::now() + self.idempotency.ttl;
+
+            match BodySize::from_stream(res.body()) {
+                BodySize::Sized(size) if size > self.idempotency.max_body_size => {
+                    guard.complete_unreplayable(expires);
+                    return Ok(res.map(BufferBody::new));
+                }
+                _ => {}
+            }
+
+            let (parts, mut body) = res.into_parts();
+            let mut buf = BytesMut::new();
+
+            let body = loop {
+                match poll_fn(|cx| Pin::new(&mut body).poll_next(cx)).await {
+                    Some(Ok(bytes)) => {
+                        buf.extend_from_slice(&bytes);
+                        if buf.len() > self.idempotency.max_body_size {
+                            guard.complete_unreplayable(expires);
+                            break BufferBody::partial(buf.freeze(), body);
+                        }
+                    }
+                    Some(Err(e)) => {
+                        guard.complete_unreplayable(expires);
+                        break BufferBody::error(buf.freeze(), e.into());
+                    }
+                    None => {
+                        let body = buf.freeze();
+                        let stored = StoredResponse {
+                            status: parts.status,
+                            headers: parts.headers.clone(),
+                            body: body.clone(),
+                            expires,
+                        };
+                        guard.complete(stored);
+                        break BufferBody::buffered(body);
+                    }
+                }
+            };
+
+            Ok(WebResponse::from_parts(parts, body))
+        }
+    }
+
+    impl<Svc, S> ReadyService for IdempotencyService<Svc, S>
+    where
+        Svc: ReadyService,
+    {
+        type Ready = Svc::Ready;
+
+        #[inline]
+        async fn ready(&self) -> Self::Ready {
+            self.service.ready().await
+        }
+    }
+
+    struct ReservationGuard<'a, S>
+    where
+        S: IdempotencyStore,
+    {
+        store: &'a S,
+        key: &'a str,
+    }
+
+    impl<S> ReservationGuard<'_, S>
+    where
+        S: IdempotencyStore,
+    {
+        fn complete(self, res: StoredResponse) {
+            self.store.complete(self.key, res);
+            core::mem::forget(self);
+        }
+
+        fn complete_unreplayable(self, expires: Instant) {
+            self.store.complete_unreplayable(self.key, expires);
+            core::mem::forget(self);
+        }
+    }
+
+    impl<S> Drop for ReservationGuard<'_, S>
+    where
+        S: IdempotencyStore,
+    {
+        fn drop(&mut self) {
+            self.store.release(self.key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use core::{
+        future::{pending, Future},
+        pin::pin,
+        sync::atomic::{AtomicUsize, Ordering},
+        task::Context,
+    };
+
+    use futures_util::task::noop_waker_ref;
+    use xitca_unsafe_collection::futures::NowOrPanic;
+
+    use crate::{
+        handler::handler_service,
+        http::{Method, Uri, WebRequest},
+        test::collect_string_body,
+        App,
+    };
+
+    use super::*;
+
+    fn req(method: Method, path: &'static str, key: Option<&'static str>) -> WebRequest {
+        let mut req = WebRequest::default();
+        *req.method_mut() = method;
+        *req.uri_mut() = Uri::from_static(path);
+        if let Some(key) = key {
+            req.headers_mut().insert(IDEMPOTENCY_KEY, HeaderValue::from_static(key));
+        }
+        req
+    }
+
+    #[test]
+    fn replay_and_conflict() {
+        static COUNT: AtomicUsize = AtomicUsize::new(0);
+
+        async fn handler(uri: &Uri) -> Result<String, StatusCode> {
+            let n = COUNT.fetch_add(1, Ordering::SeqCst);
+            match uri.path() {
+                "/pending" => pending().await,
+                "/error" => Err(StatusCode::INTERNAL_SERVER_ERROR),
+                _ => Ok(n.to_string()),
+            }
+        }
+
+        let service = App::new()
+            .at("/", handler_service(handler))
+            .at("/pending", handler_service(handler))
+            .at("/error", handler_service(handler))
+            .enclosed(Idempotency::new(Duration::from_secs(60)))
+            .finish()
+            .call(())
+            .now_or_panic()
+            .unwrap();
+
+        let call = |req: WebRequest| {
+            let res = service.call(req).now_or_panic().unwrap();
+            let replayed = res.headers().contains_key(IDEMPOTENT_REPLAYED);
+            let status = res.status();
+            let body = collect_string_body(res.into_body()).now_or_panic().unwrap();
+            (status, replayed, body)
+        };
+
+        let (status, replayed, first) = call(req(Method::POST, "/", Some("a")));
+        assert_eq!(status, StatusCode::OK);
+        assert!(!replayed);
+        assert_eq!(
+            call(req(Method::POST, "/", Some("a"))),
+            (StatusCode::OK, true, first.clone())
+        );
+
+        // different key, method or request without key is not deduplicated.
+        assert_ne!(call(req(Method::POST, "/", Some("b"))).2, first);
+        assert_ne!(call(req(Method::PUT, "/", Some("a"))).2, first);
+        assert_ne!(call(req(Method::POST, "/", None)).2, first);
+        assert!(!call(req(Method::GET, "/", Some("a"))).1);
+
+        // server error is not stored.
+        let before = COUNT.load(Ordering::SeqCst);
+        assert_eq!(
+            call(req(Method::POST, "/error", Some("a"))).0,
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+        assert_eq!(
+            call(req(Method::POST, "/error", Some("a"))).0,
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+        assert_eq!(COUNT.load(Ordering::SeqCst), before + 2);
+
+        {
+            let mut fut = pin!(service.call(req(Method::POST, "/pending", Some("a"))));
+            let mut cx = Context::from_waker(noop_waker_ref());
+            assert!(fut.as_mut().poll(&mut cx).is_pending());
+
+            let (status, ..) = call(req(Method::POST, "/pending", Some("a")));
+            assert_eq!(status, StatusCode::CONFLICT);
+        }
+
+        // dropped future must release it's reservation.
+        let before = COUNT.load(Ordering::SeqCst);
+        let mut fut = pin!(service.call(req(Method::POST, "/pending", Some("a"))));
+        let mut cx = Context::from_waker(noop_waker_ref());
+        assert!(fut.as_mut().poll(&mut cx).is_pending());
+        assert_eq!(COUNT.load(Ordering::SeqCst), before + 1);
+
+        let (status, ..) = call(req(Method::POST, "/", Some("")));
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn unreplayable() {
+        static COUNT: AtomicUsize = AtomicUsize::new(0);
+
+        async fn handler() -> &'static str {
+            COUNT.fetch_add(1, Ordering::SeqCst);
+            "oversized"
+        }
+
+        let service = App::new()
+            .at("/", handler_service(handler))
+            .enclosed(Idempotency::new(Duration::from_secs(60)).max_body_size(1))
+            .finish()
+            .call(())
+            .now_or_panic()
+            .unwrap();
+
+        let res = service.call(req(Method::POST, "/", Some("a"))).now_or_panic().unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = collect_string_body(res.into_body()).now_or_panic().unwrap();
+        assert_eq!(body, "oversized");
+
+        // oversized response can not be replayed and retry must not call handler again.
+        let res = service.call(req(Method::POST, "/", Some("a"))).now_or_panic().unwrap();
+        assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(COUNT.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn memory_store() {
+        let store = MemoryStore::new(1);
+        let ttl = Duration::from_secs(60);
+
+        assert!(matches!(store.reserve("a", ttl), Reservation::Reserved));
+        assert!(matches!(store.reserve("a", ttl), Reservation::InFlight));
+        // full store of in flight entries rejects new key.
+        assert!(matches!(store.reserve("b", ttl), Reservation::Full));
+
+        store.release("a");
+        assert!(matches!(store.reserve("a", ttl), Reservation::Reserved));
+
+        let res = StoredResponse {
+            status: StatusCode::OK,
+            headers: HeaderMap::new(),
+            body: Bytes::new(),
+            expires: Instant::now() + ttl,
+        };
+        store.complete("a", res);
+        store.release("a");
+        assert!(matches!(store.reserve("a", ttl), Reservation::Completed(_)));
+
+        // completed entry is evicted for new key.
+        assert!(matches!(store.reserve("b", ttl), Reservation::Reserved));
+        assert!(matches!(store.reserve("a", ttl), Reservation::Full));
+
+        store.complete_unreplayable("b", Instant::now() + ttl);
+        store.release("b");
+        assert!(matches!(store.reserve("b", ttl), Reservation::Unreplayable));
+
+        // expired reservation can be taken over.
+        let store = MemoryStore::new(DEFAULT_CAPACITY);
+        assert!(matches!(store.reserve("c", Duration::ZERO), Reservation::Reserved));
+        assert!(matches!(store.reserve("c", ttl), Reservation::Reserved));
+        assert!(matches!(store.reserve("c", ttl), Reservation::InFlight));
+    }
+}
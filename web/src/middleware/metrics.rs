@@ -0,0 +1,629 @@
+//! middleware for recording http metrics and exporting them in Prometheus text format.
+
+use core::{
+    fmt::Write,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
+
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+};
+
+use xitca_http::util::service::router::MatchedRoute;
+
+use crate::{
+    http::{Method, StatusCode},
+    service::Service,
+};
+
+const DURATION_BUCKETS: [f64; 11] = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+const SIZE_BUCKETS: [f64; 6] = [100.0, 1_000.0, 10_000.0, 100_000.0, 1_000_000.0, 10_000_000.0];
+
+/// middleware for recording http metrics of requests handled by enclosed application.
+///
+/// Following metrics are recorded into a registry shared by all clones of [Metrics] and it's [MetricsExporter]:
+/// - `http_requests_total`: counter of handled requests.
+/// - `http_request_duration_seconds`: histogram of duration from middleware receiving request to it receiving
+///   response.
+/// - `http_requests_in_flight`: gauge of requests being handled.
+/// - `http_response_size_bytes`: histogram of response body size. Recorded when response body is dropped and
+///   only counts bytes actually yielded by it.
+///
+/// Metrics except the in flight gauge are labeled with `method`, `route` and `status`. To keep cardinality of
+/// labels under control `route` is the path pattern of matched route(for example `/users/:id`) instead of the
+/// request path and it's `unmatched` for request not matching any route. Methods not defined by http
+/// specification are labeled as `OTHER`. Errors produced by enclosed application are rendered to response by the
+/// middleware and recorded the same way as other responses.
+///
+/// # Examples
+/// ```rust
+/// # use xitca_web::{handler::handler_service, middleware::metrics::Metrics, App, WebContext};
+/// let metrics = Metrics::new();
+///
+/// App::new()
+///     .at("/users/:id", handler_service(|| async { "hello,world!" }))
+///     // expose metrics in Prometheus text format.
+///     .at("/metrics", metrics.exporter())
+///     # .at("/infer", handler_service(|_: &WebContext<'_>| async{ "infer type" }))
+///     .enclosed(metrics);
+/// ```
+#[derive(Clone)]
+pub struct Metrics {
+    registry: Arc<Registry>,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Metrics {
+    /// construct middleware with default histogram buckets.
+    ///
+    /// Duration buckets are `0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1, 2.5, 5, 10` seconds and response size
+    /// buckets are `100, 1000, 10000, 100000, 1000000, 10000000` bytes.
+    pub fn new() -> Self {
+        Self {
+            registry: Arc::new(Registry::new(&DURATION_BUCKETS, &SIZE_BUCKETS)),
+        }
+    }
+
+    /// set upper bounds of buckets of request duration histogram in seconds.
+    ///
+    /// # Panics
+    /// When called after [Metrics] is cloned or [Metrics::exporter] is called.
+    pub fn duration_buckets<I>(mut self, buckets: I) -> Self
+    where
+        I: IntoIterator<Item = f64>,
+    {
+        self.registry_mut().duration_buckets = buckets_from_iter(buckets);
+        self
+    }
+
+    /// set upper bounds of buckets of response size histogram in bytes.
+    ///
+    /// # Panics
+    /// When called after [Metrics] is cloned or [Metrics::exporter] is called.
+    pub fn size_buckets<I>(mut self, buckets: I) -> Self
+    where
+        I: IntoIterator<Item = f64>,
+    {
+        self.registry_mut().size_buckets = buckets_from_iter(buckets);
+        self
+    }
+
+    /// construct a service exporting recorded metrics in Prometheus text format. It can be registered as a route
+    /// of application.
+    pub fn exporter(&self) -> MetricsExporter {
+        MetricsExporter {
+            registry: self.registry.clone(),
+        }
+    }
+
+    /// render recorded metrics in Prometheus text format.
+    pub fn render(&self) -> String {
+        self.registry.render()
+    }
+
+    fn registry_mut(&mut self) -> &mut Registry {
+        Arc::get_mut(&mut self.registry).expect("histogram buckets must be set before Metrics is shared")
+    }
+}
+
+fn buckets_from_iter<I>(buckets: I) -> Box<[f64]>
+where
+    I: IntoIterator<Item = f64>,
+{
+    let mut buckets = buckets.into_iter().filter(|b| b.is_finite()).collect::<Vec<_>>();
+    buckets.sort_by(f64::total_cmp);
+    buckets.dedup();
+    buckets.into_boxed_slice()
+}
+
+impl<S, E> Service<Result<S, E>> for Metrics {
+    type Response = service::MetricsService<S>;
+    type Error = E;
+
+    async fn call(&self, res: Result<S, E>) -> Result<Self::Response, Self::Error> {
+        res.map(|service| service::MetricsService {
+            service,
+            registry: self.registry.clone(),
+        })
+    }
+}
+
+/// service exporting metrics recorded by [Metrics] in Prometheus text format. See [Metrics] for example.
+#[derive(Clone)]
+pub struct MetricsExporter {
+    registry: Arc<Registry>,
+}
+
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct Key {
+    method: &'static str,
+    route: MatchedRoute,
+    status: StatusCode,
+}
+
+impl Key {
+    fn method_label(method: &Method) -> &'static str {
+        match *method {
+            Method::GET => "GET",
+            Method::HEAD => "HEAD",
+            Method::POST => "POST",
+            Method::PUT => "PUT",
+            Method::DELETE => "DELETE",
+            Method::CONNECT => "CONNECT",
+            Method::OPTIONS => "OPTIONS",
+            Method::TRACE => "TRACE",
+            Method::PATCH => "PATCH",
+            _ => "OTHER",
+        }
+    }
+
+    fn write_labels(&self, buf: &mut String) {
+        let _ = write!(buf, "method=\"{}\",route=\"", self.method);
+        match self.route.as_str() {
+            Some(route) => escape(route, buf),
+            None => buf.push_str("unmatched"),
+        }
+        let _ = write!(buf, "\",status=\"{}\"", self.status.as_u16());
+    }
+}
+
+// escape label value according to Prometheus text format.
+fn escape(value: &str, buf: &mut String) {
+    for c in value.chars() {
+        match c {
+            '\\' => buf.push_str("\\\\"),
+            '"' => buf.push_str("\\\""),
+            '\n' => buf.push_str("\\n"),
+            c => buf.push(c),
+        }
+    }
+}
+
+struct Registry {
+    duration_buckets: Box<[f64]>,
+    size_buckets: Box<[f64]>,
+    in_flight: AtomicUsize,
+    series: Mutex<BTreeMap<Key, Series>>,
+}
+
+struct Series {
+    duration: Histogram,
+    size: Histogram,
+}
+
+struct Histogram {
+    // cumulative count of observations per bucket.
+    buckets: Box<[u64]>,
+    sum: f64,
+    count: u64,
+}
+
+impl Histogram {
+    fn new(len: usize) -> Self {
+        Self {
+            buckets: vec![0; len].into_boxed_slice(),
+            sum: 0.0,
+            count: 0,
+        }
+    }
+
+    fn observe(&mut self, bounds: &[f64], value: f64) {
+        bounds
+            .iter()
+            .zip(self.buckets.iter_mut())
+            .filter(|(bound, _)| value <= **bound)
+            .for_each(|(_, count)| *count += 1);
+        self.sum += value;
+        self.count += 1;
+    }
+}
+
+impl Registry {
+    fn new(duration_buckets: &[f64], size_buckets: &[f64]) -> Self {
+        Self {
+            duration_buckets: duration_buckets.into(),
+            size_buckets: size_buckets.into(),
+            in_flight: AtomicUsize::new(0),
+            series: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    fn with_series<F>(&self, key: &Key, func: F)
+    where
+        F: FnOnce(&mut Series),
+    {
+        let mut series = self.series.lock().unwrap();
+        match series.get_mut(key) {
+            Some(series) => func(series),
+            None => {
+                let mut s = Series {
+                    duration: Histogram::new(self.duration_buckets.len()),
+                    size: Histogram::new(self.size_buckets.len()),
+                };
+                func(&mut s);
+                series.insert(key.clone(), s);
+            }
+        }
+    }
+
+    fn record_duration(&self, key: &Key, dur: Duration) {
+        self.with_series(key, |s| s.duration.observe(&self.duration_buckets, dur.as_secs_f64()));
+    }
+
+    fn record_size(&self, key: &Key, size: usize) {
+        self.with_series(key, |s| s.size.observe(&self.size_buckets, size as f64));
+    }
+
+    fn render(&self) -> String {
+        let series = self.series.lock().unwrap();
+        let mut buf = String::new();
+
+        header(
+            &mut buf,
+            "http_requests_total",
+            "counter",
+            "Total number of handled http requests.",
+        );
+        for (key, s) in series.iter() {
+            buf.push_str("http_requests_total{");
+            key.write_labels(&mut buf);
+            let _ = writeln!(buf, "}} {}", s.duration.count);
+        }
+
+        header(
+            &mut buf,
+            "http_request_duration_seconds",
+            "histogram",
+            "Duration of handling http requests in seconds.",
+        );
+        for (key, s) in series.iter() {
+            histogram(
+                &mut buf,
+                "http_request_duration_seconds",
+                key,
+                &self.duration_buckets,
+                &s.duration,
+            );
+        }
+
+        header(
+            &mut buf,
+            "http_requests_in_flight",
+            "gauge",
+            "Number of http requests being handled.",
+        );
+        let _ = writeln!(
+            buf,
+            "http_requests_in_flight {}",
+            self.in_flight.load(Ordering::Relaxed)
+        );
+
+        header(
+            &mut buf,
+            "http_response_size_bytes",
+            "histogram",
+            "Size of http response body in bytes.",
+        );
+        for (key, s) in series.iter().filter(|(_, s)| s.size.count > 0) {
+            histogram(&mut buf, "http_response_size_bytes", key, &self.size_buckets, &s.size);
+        }
+
+        buf
+    }
+}
+
+fn header(buf: &mut String, name: &str, ty: &str, help: &str) {
+    let _ = writeln!(buf, "# HELP {name} {help}");
+    let _ = writeln!(buf, "# TYPE {name} {ty}");
+}
+
+fn histogram(buf: &mut String, name: &str, key: &Key, bounds: &[f64], histogram: &Histogram) {
+    for (bound, count) in bounds.iter().zip(histogram.buckets.iter()) {
+        let _ = write!(buf, "{name}_bucket{{");
+        key.write_labels(buf);
+        let _ = writeln!(buf, ",le=\"{bound}\"}} {count}");
+    }
+
+    let _ = write!(buf, "{name}_bucket{{");
+    key.write_labels(buf);
+    let _ = writeln!(buf, ",le=\"+Inf\"}} {}", histogram.count);
+
+    let _ = write!(buf, "{name}_sum{{");
+    key.write_labels(buf);
+    let _ = writeln!(buf, "}} {}", histogram.sum);
+
+    let _ = write!(buf, "{name}_count{{");
+    key.write_labels(buf);
+    let _ = writeln!(buf, "}} {}", histogram.count);
+}
+
+mod exporter {
+    use core::convert::Infallible;
+
+    use xitca_http::util::service::router::{PathGen, RouteGen, RouterMapErr};
+
+    use crate::{
+        error::Error,
+        http::{
+            header::{HeaderValue, CONTENT_TYPE},
+            WebResponse,
+        },
+        WebContext,
+    };
+
+    use super::*;
+
+    const TEXT_FORMAT: HeaderValue = HeaderValue::from_static("text/plain; version=0.0.4; charset=utf-8");
+
+    impl PathGen for MetricsExporter {}
+
+    impl RouteGen for MetricsExporter {
+        type Route<R> = RouterMapErr<R>;
+
+        fn route_gen<R>(route: R) -> Self::Route<R> {
+            RouterMapErr(route)
+        }
+    }
+
+    impl Service for MetricsExporter {
+        type Response = Self;
+        type Error = Infallible;
+
+        async fn call(&self, _: ()) -> Result<Self::Response, Self::Error> {
+            Ok(self.clone())
+        }
+    }
+
+    impl<'r, C, B> Service<WebContext<'r, C, B>> for MetricsExporter {
+        type Response = WebResponse;
+        type Error = Error;
+
+        async fn call(&self, ctx: WebContext<'r, C, B>) -> Result<Self::Response, Self::Error> {
+            let mut res = ctx.into_response(self.registry.render());
+            res.headers_mut().insert(CONTENT_TYPE, TEXT_FORMAT);
+            Ok(res)
+        }
+    }
+}
+
+mod service {
+    use core::{
+        convert::Infallible,
+        pin::Pin,
+        task::{ready, Context, Poll},
+    };
+
+    use std::time::Instant;
+
+    use futures_core::stream::Stream;
+    use pin_project_lite::pin_project;
+
+    use crate::{
+        body::{Either, ResponseBody},
+        bytes::Bytes,
+        http::WebResponse,
+        service::{ready::ReadyService, Service},
+        WebContext,
+    };
+
+    use super::*;
+
+    pub struct MetricsService<S> {
+        pub(super) service: S,
+        pub(super) registry: Arc<Registry>,
+    }
+
+    // decrement in flight gauge when request handling is finished or cancelled.
+    struct InFlight<'a>(&'a Registry);
+
+    impl<'a> InFlight<'a> {
+        fn new(registry: &'a Registry) -> Self {
+            registry.in_flight.fetch_add(1, Ordering::Relaxed);
+            Self(registry)
+        }
+    }
+
+    impl Drop for InFlight<'_> {
+        fn drop(&mut self) {
+            self.0.in_flight.fetch_sub(1, Ordering::Relaxed);
+        }
+    }
+
+    impl<'r, C, S, ResB, Err> Service<WebContext<'r, C>> for MetricsService<S>
+    where
+        S: for<'r2> Service<WebContext<'r2, C>, Response = WebResponse<ResB>, Error = Err>,
+        Err: for<'r2> Service<WebContext<'r2, C>, Response = WebResponse, Error = Infallible>,
+    {
+        type Response = WebResponse<MetricsBody<Either<ResB, ResponseBody>>>;
+        type Error = Infallible;
+
+        async fn call(&self, mut ctx: WebContext<'r, C>) -> Result<Self::Response, Self::Error> {
+            let method = Key::method_label(ctx.req().method());
+            let start = Instant::now();
+
+            let res = {
+                let _in_flight = InFlight::new(&self.registry);
+                self.service.call(ctx.reborrow()).await
+            };
+
+            let dur = start.elapsed();
+            // matched route is kept in request head after request is consumed by inner services.
+            let route = ctx.req().body().matched_route().clone();
+
+            let res = match res {
+                Ok(res) => res.map(Either::left),
+                Err(e) => match e.call(ctx).await {
+                    Ok(res) => res.map(Either::right),
+                    Err(i) => match i {},
+                },
+            };
+
+            let key = Key {
+                method,
+                route,
+                status: res.status(),
+            };
+            self.registry.record_duration(&key, dur);
+            let registry = self.registry.clone();
+            Ok(res.map(|body| MetricsBody {
+                body,
+                size: 0,
+                record: Some((registry, key)),
+            }))
+        }
+    }
+
+    impl<S> ReadyService for MetricsService<S>
+    where
+        S: ReadyService,
+    {
+        type Ready = S::Ready;
+
+        #[inline]
+        async fn ready(&self) -> Self::Ready {
+            self.service.ready().await
+        }
+    }
+
+    pin_project! {
+        /// response body counting bytes yielded by it and recording the size to [Metrics] registry when dropped.
+        pub struct MetricsBody<B> {
+            #[pin]
+            body: B,
+            size: usize,
+            record: Option<(Arc<Registry>, Key)>,
+        }
+
+        impl<B> PinnedDrop for MetricsBody<B> {
+            fn drop(this: Pin<&mut Self>) {
+                let this = this.project();
+                if let Some((registry, key)) = this.record.take() {
+                    registry.record_size(&key, *this.size);
+                }
+            }
+        }
+    }
+
+    impl<B, E> Stream for MetricsBody<B>
+    where
+        B: Stream<Item = Result<Bytes, E>>,
+    {
+        type Item = B::Item;
+
+        fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+            let this = self.project();
+            let res = ready!(this.body.poll_next(cx));
+            if let Some(Ok(ref bytes)) = res {
+                *this.size += bytes.len();
+            }
+            Poll::Ready(res)
+        }
+
+        #[inline]
+        fn size_hint(&self) -> (usize, Option<usize>) {
+            self.body.size_hint()
+        }
+    }
+}
+
+pub use self::service::MetricsBody;
+
+#[cfg(test)]
+mod test {
+    use xitca_unsafe_collection::futures::NowOrPanic;
+
+    use crate::{
+        error::Error,
+        handler::handler_service,
+        http::{Method, StatusCode, Uri, WebRequest},
+        test::collect_string_body,
+        App,
+    };
+
+    use super::*;
+
+    fn req(uri: &str) -> WebRequest {
+        let mut req = WebRequest::default();
+        *req.uri_mut() = Uri::try_from(uri).unwrap();
+        req
+    }
+
+    #[test]
+    fn record_and_export() {
+        let metrics = Metrics::new().duration_buckets([1.0, 0.5]).size_buckets([10.0]);
+
+        let service = App::new()
+            .at("/users/:id", handler_service(|| async { "hello,world!" }))
+            .at(
+                "/error",
+                handler_service(|| async { Err::<&str, _>(Error::from(StatusCode::BAD_REQUEST)) }),
+            )
+            .at("/metrics", metrics.exporter())
+            .enclosed(metrics.clone())
+            .finish()
+            .call(())
+            .now_or_panic()
+            .unwrap();
+
+        for uri in ["/users/1", "/users/2"] {
+            let res = service.call(req(uri)).now_or_panic().unwrap();
+            let body = collect_string_body(res.into_body()).now_or_panic().unwrap();
+            assert_eq!(body, "hello,world!");
+        }
+
+        let _ = service.call(req("/error")).now_or_panic();
+        let _ = service.call(req("/404")).now_or_panic();
+
+        // response body is dropped without being collected.
+        let mut brew = req("/users/3");
+        *brew.method_mut() = Method::from_bytes(b"BREW").unwrap();
+        drop(service.call(brew).now_or_panic().unwrap());
+
+        let res = service.call(req("/metrics")).now_or_panic().unwrap();
+        assert_eq!(
+            res.headers().get("content-type").unwrap(),
+            "text/plain; version=0.0.4; charset=utf-8"
+        );
+        let body = collect_string_body(res.into_body()).now_or_panic().unwrap();
+
+        let users = "method=\"GET\",route=\"/users/:id\",status=\"200\"";
+        assert!(body.contains(&format!("http_requests_total{{{users}}} 2\n")));
+        assert!(body.contains(&format!(
+            "http_request_duration_seconds_bucket{{{users},le=\"0.5\"}} 2\n"
+        )));
+        assert!(body.contains(&format!("http_request_duration_seconds_count{{{users}}} 2\n")));
+        assert!(body.contains(&format!("http_response_size_bytes_bucket{{{users},le=\"10\"}} 0\n")));
+        assert!(body.contains(&format!("http_response_size_bytes_bucket{{{users},le=\"+Inf\"}} 2\n")));
+        assert!(body.contains(&format!("http_response_size_bytes_sum{{{users}}} 24\n")));
+
+        assert!(body.contains("http_requests_total{method=\"GET\",route=\"/error\",status=\"400\"} 1\n"));
+        assert!(body.contains("http_requests_total{method=\"GET\",route=\"unmatched\",status=\"404\"} 1\n"));
+        assert!(body.contains("http_requests_total{method=\"OTHER\",route=\"/users/:id\",status=\"200\"} 1\n"));
+
+        // exporter request itself is in flight.
+        assert!(body.contains("http_requests_in_flight 1\n"));
+        assert_eq!(metrics.registry.in_flight.load(Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "histogram buckets must be set before Metrics is shared")]
+    fn buckets_after_shared() {
+        let metrics = Metrics::new();
+        let _exporter = metrics.exporter();
+        let _ = metrics.duration_buckets([1.0]);
+    }
+
+    #[test]
+    fn escape_label() {
+        let mut buf = String::new();
+        escape("/a\"b\\c\n", &mut buf);
+        assert_eq!(buf, "/a\\\"b\\\\c\\n");
+    }
+}
@@ -148,6 +148,10 @@
 //! # Variants
 //! ## async function as middleware
 //! Please reference `Types in middleware` part above
+//! ## async function as guard
+//! Middleware only deciding if request should reach application service (e.g. authentication) can be
+//! written as async function taking `&mut WebContext` and returning [`ControlFlow`] without calling the
+//! service. Please reference [`App::enclosed_guard_fn`] for example.
 //! ## named type as middleware
 //! ```rust
 //! # use xitca_web::{error::Error, handler::handler_service, http::WebResponse, service::Service, App, WebContext};
@@ -283,6 +287,8 @@
 //!   into new type by services and middleware while type eraser is able to reverse the process.
 //!
 //! [`App::with_state`]: crate::App::with_state
+//! [`App::enclosed_guard_fn`]: crate::App::enclosed_guard_fn
+//! [`ControlFlow`]: core::ops::ControlFlow
 //! [`Service`]: crate::service::Service
 //! [`ServiceExt`]: crate::service::ServiceExt
 //! [`WebContext<'_, C, B>`]: crate::WebContext
@@ -297,15 +303,28 @@
 pub mod compress;
 #[cfg(any(feature = "compress-br", feature = "compress-gz", feature = "compress-de"))]
 pub mod decompress;
+#[cfg(feature = "metrics")]
+pub mod metrics;
 #[cfg(feature = "rate-limit")]
 pub mod rate_limit;
 #[cfg(not(target_family = "wasm"))]
 pub mod sync;
 #[cfg(feature = "tower-http-compat")]
 pub mod tower_http_compat;
+#[cfg(feature = "tracing")]
+pub mod trace;
 
+pub mod buffer;
+pub mod cache;
+pub mod concurrency_limit;
+pub mod content_type;
+pub mod cors;
 pub mod eraser;
+pub mod host;
+pub mod https;
+pub mod idempotency;
 pub mod limit;
+pub mod server_timing;
 
 #[cfg(feature = "logger")]
 mod logger;
@@ -313,7 +332,7 @@ mod logger;
 pub use logger::Logger;
 
 mod catch_unwind;
-mod context;
+pub(crate) mod context;
 
 pub use catch_unwind::CatchUnwind;
 pub use context::WebContext;
@@ -8,6 +8,15 @@ use crate::service::Service;
 
 /// builder for client ip address based rate limiting middleware.
 ///
+/// Rate limit algorithm is selected by constructor:
+/// - [RateLimit::per_second], [RateLimit::per_minute], [RateLimit::per_hour] and [RateLimit::with_period] use
+///   token bucket algorithm.
+/// - [RateLimit::fixed_window] uses fixed window counter algorithm.
+/// - [RateLimit::sliding_window] uses sliding window counter algorithm.
+///
+/// All algorithms determine client the same way and produce "429 Too Many Requests" response when limit is
+/// reached. See [http_rate::RateLimit] for the memory and accuracy tradeoffs of each algorithm.
+///
 /// # Examples
 /// ```rust
 /// # use xitca_web::{handler::handler_service, middleware::rate_limit::RateLimit, route::get, App, WebContext};
@@ -16,8 +25,22 @@ use crate::service::Service;
 ///     # .at("/infer", handler_service(|_: &WebContext<'_>| async{ "infer type" }))
 ///     // rate limit to 60 rps for one ip address.
 ///     .enclosed(RateLimit::per_minute(60));
+///
+/// # use core::time::Duration;
+/// App::new()
+///     .at("/", get(handler_service(|| async { "hello,world!" })))
+///     # .at("/infer", handler_service(|_: &WebContext<'_>| async{ "infer type" }))
+///     // allow 100 requests from one ip address in any 60 seconds.
+///     .enclosed(RateLimit::sliding_window(100, Duration::from_secs(60)));
 /// ```
-pub struct RateLimit(Quota);
+pub struct RateLimit(Algorithm);
+
+#[derive(Clone, Copy)]
+enum Algorithm {
+    TokenBucket(Quota),
+    FixedWindow(u32, Duration),
+    SlidingWindow(u32, Duration),
+}
 
 macro_rules! constructor {
     ($method: tt) => {
@@ -27,7 +50,7 @@ macro_rules! constructor {
         /// # Panics
         /// - When max_burst is zero.
         pub fn $method(max_burst: u32) -> Self {
-            Self(Quota::$method(max_burst))
+            Self(Algorithm::TokenBucket(Quota::$method(max_burst)))
         }
     };
 }
@@ -43,7 +66,33 @@ impl RateLimit {
     /// # Panics
     /// - When the Duration is zero.
     pub fn with_period(replenish_1_per: Duration) -> Self {
-        Self(Quota::with_period(replenish_1_per).unwrap())
+        Self(Algorithm::TokenBucket(Quota::with_period(replenish_1_per).unwrap()))
+    }
+
+    /// Construct a RateLimit allowing a number of requests in every fixed window of time.
+    /// The count is reset at the end of every window.
+    ///
+    /// # Panics
+    /// - When limit or window is zero.
+    pub fn fixed_window(limit: u32, window: Duration) -> Self {
+        assert!(
+            limit > 0 && !window.is_zero(),
+            "limit and window of RateLimit must not be zero"
+        );
+        Self(Algorithm::FixedWindow(limit, window))
+    }
+
+    /// Construct a RateLimit allowing a number of requests in a sliding window of time ending at
+    /// the time request is received.
+    ///
+    /// # Panics
+    /// - When limit or window is zero.
+    pub fn sliding_window(limit: u32, window: Duration) -> Self {
+        assert!(
+            limit > 0 && !window.is_zero(),
+            "limit and window of RateLimit must not be zero"
+        );
+        Self(Algorithm::SlidingWindow(limit, window))
     }
 }
 
@@ -54,7 +103,11 @@ impl<S, E> Service<Result<S, E>> for RateLimit {
     async fn call(&self, res: Result<S, E>) -> Result<Self::Response, Self::Error> {
         res.map(|service| service::RateLimitService {
             service,
-            rate_limit: http_rate::RateLimit::new(self.0),
+            rate_limit: match self.0 {
+                Algorithm::TokenBucket(quota) => http_rate::RateLimit::new(quota),
+                Algorithm::FixedWindow(limit, window) => http_rate::RateLimit::fixed_window(limit, window),
+                Algorithm::SlidingWindow(limit, window) => http_rate::RateLimit::sliding_window(limit, window),
+            },
         })
     }
 }
@@ -0,0 +1,384 @@
+//! middleware for emitting `Server-Timing` response header.
+
+use core::{fmt::Write, time::Duration};
+
+use std::{
+    borrow::Cow,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::Instant,
+};
+
+use crate::{
+    http::{header::HeaderValue, Extensions},
+    service::Service,
+};
+
+/// phase of request handling measured by framework.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Phase {
+    /// duration from [ServerTiming] middleware receiving the request to the matched route service starting.
+    /// It includes route matching and middleware enclosed by [ServerTiming].
+    Routing,
+    /// duration of matched route service. It includes extracting request types, running handler function and
+    /// producing response.
+    Handler,
+    /// duration of serializing response body. Currently measured by [Json] responder.
+    ///
+    /// [Json]: crate::handler::json::Json
+    Serialization,
+    /// duration from [ServerTiming] middleware receiving the request to it receiving the response.
+    Total,
+}
+
+impl Phase {
+    const ALL: [Self; 4] = [Self::Routing, Self::Handler, Self::Serialization, Self::Total];
+
+    const fn name(self) -> &'static str {
+        match self {
+            Self::Routing => "routing",
+            Self::Handler => "handler",
+            Self::Serialization => "serialization",
+            Self::Total => "total",
+        }
+    }
+
+    const fn bit(self) -> u8 {
+        1 << self as u8
+    }
+}
+
+/// middleware for emitting `Server-Timing` response header with durations of request handling phases.
+///
+/// The header is visible in browser devtools and helps debugging latency of response. Every measured [Phase] is
+/// enabled by default and [ServerTiming::phases] can be used to select them. Middleware is only active when it's
+/// enclosed to application and no timing is measured otherwise.
+///
+/// A [Timings] handle is inserted into request extensions by the middleware. It can be extracted with
+/// [ExtensionRef] to record custom metrics which are rendered into the header along with framework measured
+/// phases.
+///
+/// # Examples
+/// ```rust
+/// # use core::time::Duration;
+/// # use xitca_web::{
+/// #   handler::{extension::ExtensionRef, handler_service},
+/// #   middleware::server_timing::{Phase, ServerTiming, Timings},
+/// #   App, WebContext
+/// # };
+/// async fn handler(ExtensionRef(timings): ExtensionRef<'_, Timings>) -> &'static str {
+///     // record custom metric.
+///     timings.record("db", Duration::from_millis(3));
+///     "hello,world!"
+/// }
+///
+/// App::new()
+///     .at("/", handler_service(handler))
+///     # .at("/infer", handler_service(|_: &WebContext<'_>| async{ "infer type" }))
+///     // only measure routing and total duration of request.
+///     .enclosed(ServerTiming::new().phases([Phase::Routing, Phase::Total]));
+/// ```
+///
+/// [ExtensionRef]: crate::handler::extension::ExtensionRef
+#[derive(Debug, Clone, Copy)]
+pub struct ServerTiming {
+    phases: u8,
+}
+
+impl Default for ServerTiming {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ServerTiming {
+    /// construct middleware with every [Phase] enabled.
+    pub const fn new() -> Self {
+        let mut phases = 0;
+        let mut i = 0;
+        while i < Phase::ALL.len() {
+            phases |= Phase::ALL[i].bit();
+            i += 1;
+        }
+        Self { phases }
+    }
+
+    /// set the phases measured and rendered into `Server-Timing` header.
+    pub fn phases<I>(mut self, phases: I) -> Self
+    where
+        I: IntoIterator<Item = Phase>,
+    {
+        self.phases = phases.into_iter().fold(0, |bits, phase| bits | phase.bit());
+        self
+    }
+}
+
+impl<S, E> Service<Result<S, E>> for ServerTiming {
+    type Response = service::ServerTimingService<S>;
+    type Error = E;
+
+    async fn call(&self, res: Result<S, E>) -> Result<Self::Response, Self::Error> {
+        INSTALLED.store(true, Ordering::Relaxed);
+        res.map(|service| service::ServerTimingService {
+            service,
+            phases: self.phases,
+        })
+    }
+}
+
+/// handle of timing metrics of current request. See [ServerTiming] for detail.
+#[derive(Clone)]
+pub struct Timings {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    start: Instant,
+    phases: u8,
+    metrics: Mutex<Metrics>,
+}
+
+#[derive(Default)]
+struct Metrics {
+    phases: [Option<Duration>; 4],
+    custom: Vec<(Cow<'static, str>, Duration)>,
+}
+
+impl Timings {
+    fn new(phases: u8) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                start: Instant::now(),
+                phases,
+                metrics: Mutex::new(Metrics::default()),
+            }),
+        }
+    }
+
+    /// duration elapsed since request is received by [ServerTiming] middleware.
+    pub fn elapsed(&self) -> Duration {
+        self.inner.start.elapsed()
+    }
+
+    /// record custom metric with given name and duration. name must be a valid http token and metric with invalid
+    /// name prevents `Server-Timing` header from being emitted.
+    pub fn record<N>(&self, name: N, dur: Duration)
+    where
+        N: Into<Cow<'static, str>>,
+    {
+        self.inner.metrics.lock().unwrap().custom.push((name.into(), dur));
+    }
+
+    fn is_enabled(&self, phase: Phase) -> bool {
+        self.inner.phases & phase.bit() != 0
+    }
+
+    fn record_phase(&self, phase: Phase, dur: Duration) {
+        if !self.is_enabled(phase) {
+            return;
+        }
+        let mut metrics = self.inner.metrics.lock().unwrap();
+        let slot = &mut metrics.phases[phase as usize];
+        match phase {
+            // nested application's route service is called inside outer route service. keep the innermost handler
+            // duration which is recorded first.
+            Phase::Handler if slot.is_some() => {}
+            _ => *slot = Some(dur),
+        }
+    }
+
+    // the longest recorded phase(except total) or custom metric.
+    #[cfg(feature = "tracing")]
+    pub(crate) fn slowest(&self) -> Option<(Cow<'static, str>, Duration)> {
+        let metrics = self.inner.metrics.lock().unwrap();
+
+        let phases = Phase::ALL
+            .iter()
+            .filter(|phase| **phase != Phase::Total)
+            .filter_map(|phase| metrics.phases[*phase as usize].map(|dur| (Cow::Borrowed(phase.name()), dur)));
+        let custom = metrics.custom.iter().map(|(name, dur)| (name.clone(), *dur));
+
+        phases.chain(custom).max_by_key(|(_, dur)| *dur)
+    }
+
+    fn header_value(&self) -> Option<HeaderValue> {
+        let metrics = self.inner.metrics.lock().unwrap();
+
+        let phases = Phase::ALL
+            .iter()
+            .filter_map(|phase| metrics.phases[*phase as usize].map(|dur| (phase.name(), dur)));
+        let custom = metrics.custom.iter().map(|(name, dur)| (name.as_ref(), *dur));
+
+        let mut value = String::new();
+        for (name, dur) in phases.chain(custom) {
+            if !value.is_empty() {
+                value.push_str(", ");
+            }
+            let _ = write!(value, "{name};dur={:.3}", dur.as_secs_f64() * 1000.0);
+        }
+
+        if value.is_empty() {
+            return None;
+        }
+
+        HeaderValue::try_from(value).ok()
+    }
+}
+
+// measure duration of given closure as phase when request has timing handle.
+#[cfg(feature = "json")]
+pub(crate) fn measure<F, O>(timings: Option<Timings>, phase: Phase, func: F) -> O
+where
+    F: FnOnce() -> O,
+{
+    match timings {
+        Some(timings) if timings.is_enabled(phase) => {
+            let start = Instant::now();
+            let res = func();
+            timings.record_phase(phase, start.elapsed());
+            res
+        }
+        _ => func(),
+    }
+}
+
+// set when ServerTiming middleware is built. timing handle is not looked up from request extensions before that so
+// route services and responders of application not using the middleware don't pay for it.
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+
+// get timing handle from request extensions.
+pub(crate) fn timings(ext: &Extensions) -> Option<Timings> {
+    if !INSTALLED.load(Ordering::Relaxed) {
+        return None;
+    }
+    ext.get::<Timings>().cloned()
+}
+
+pub(crate) mod service {
+    use crate::{
+        http::{header::HeaderName, WebResponse},
+        service::{ready::ReadyService, Service},
+        WebContext,
+    };
+
+    use super::*;
+
+    const SERVER_TIMING: HeaderName = HeaderName::from_static("server-timing");
+
+    pub struct ServerTimingService<S> {
+        pub(super) service: S,
+        pub(super) phases: u8,
+    }
+
+    impl<'r, C, B, S, ResB, Err> Service<WebContext<'r, C, B>> for ServerTimingService<S>
+    where
+        S: for<'r2> Service<WebContext<'r2, C, B>, Response = WebResponse<ResB>, Error = Err>,
+    {
+        type Response = WebResponse<ResB>;
+        type Error = Err;
+
+        async fn call(&self, mut ctx: WebContext<'r, C, B>) -> Result<Self::Response, Self::Error> {
+            let timings = Timings::new(self.phases);
+            ctx.req_mut().extensions_mut().insert(timings.clone());
+
+            let mut res = self.service.call(ctx).await?;
+
+            timings.record_phase(Phase::Total, timings.elapsed());
+            if let Some(value) = timings.header_value() {
+                res.headers_mut().append(SERVER_TIMING, value);
+            }
+
+            Ok(res)
+        }
+    }
+
+    impl<S> ReadyService for ServerTimingService<S>
+    where
+        S: ReadyService,
+    {
+        type Ready = S::Ready;
+
+        #[inline]
+        async fn ready(&self) -> Self::Ready {
+            self.service.ready().await
+        }
+    }
+
+    // wrapper of route service measuring routing and handler phases.
+    pub struct TimedRoute<S>(pub(crate) S);
+
+    impl<'r, C, B, S, Res, Err> Service<WebContext<'r, C, B>> for TimedRoute<S>
+    where
+        S: for<'r2> Service<WebContext<'r2, C, B>, Response = Res, Error = Err>,
+    {
+        type Response = Res;
+        type Error = Err;
+
+        #[inline]
+        async fn call(&self, ctx: WebContext<'r, C, B>) -> Result<Self::Response, Self::Error> {
+            let Some(timings) = timings(ctx.req().extensions()) else {
+                return self.0.call(ctx).await;
+            };
+
+            timings.record_phase(Phase::Routing, timings.elapsed());
+            let start = Instant::now();
+            let res = self.0.call(ctx).await;
+            timings.record_phase(Phase::Handler, start.elapsed());
+            res
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use xitca_unsafe_collection::futures::NowOrPanic;
+
+    use crate::{
+        handler::{extension::ExtensionRef, handler_service},
+        http::{header::HeaderName, WebRequest},
+        App,
+    };
+
+    use super::*;
+
+    async fn handler(ExtensionRef(timings): ExtensionRef<'_, Timings>) -> &'static str {
+        timings.record("db", Duration::from_millis(3));
+        "hello,world!"
+    }
+
+    fn server_timing(timing: ServerTiming) -> String {
+        let service = App::new()
+            .at("/", handler_service(handler))
+            .enclosed(timing)
+            .finish()
+            .call(())
+            .now_or_panic()
+            .unwrap();
+
+        let res = service.call(WebRequest::default()).now_or_panic().unwrap();
+        res.headers()
+            .get(HeaderName::from_static("server-timing"))
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .to_owned()
+    }
+
+    #[test]
+    fn phases() {
+        let value = server_timing(ServerTiming::new());
+        let names = value
+            .split(", ")
+            .map(|metric| metric.split_once(";dur=").unwrap().0)
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["routing", "handler", "total", "db"]);
+        assert!(value.ends_with("db;dur=3.000"));
+
+        let value = server_timing(ServerTiming::new().phases([Phase::Total]));
+        assert!(value.starts_with("total;dur="));
+        assert!(!value.contains("routing"));
+        assert!(!value.contains("handler"));
+    }
+}
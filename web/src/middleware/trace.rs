@@ -0,0 +1,704 @@
+//! middleware for opening a tracing span per request and logging errors and slow requests.
+
+use core::time::Duration;
+
+use tracing::{Level, Span};
+
+use crate::{
+    http::{header::HeaderName, StatusCode, WebRequest},
+    service::Service,
+};
+
+const X_REQUEST_ID: HeaderName = HeaderName::from_static("x-request-id");
+
+/// middleware for opening a [`Span`] for every request and instrumenting the enclosed service with it.
+///
+/// All events and spans produced by enclosed service(including the async handler across it's await points) are
+/// recorded within the span and can be correlated by it's fields. Unlike [`Logger`] this middleware does not emit any
+/// event by itself and it does not initialize trace dispatcher.
+///
+/// # Fields
+/// By default span is named `request` with [`Level::INFO`] verbosity and it records:
+/// - `method`: http method of request.
+/// - `path`: uri path of request.
+/// - `request_id`: value of `x-request-id` header when it's present.
+/// - `route`: empty field that can be recorded by enclosed service with [`Span::record`]. Routing happens inside
+///   application service and this middleware can not observe the matched route pattern.
+///
+/// [`RequestSpan::with_span_fn`] can be used to customize the span and it's field set.
+///
+/// # Examples
+/// ```rust
+/// # use xitca_web::{handler::handler_service, middleware::trace::RequestSpan, route::get, App, WebContext};
+/// async fn handler() -> &'static str {
+///     // record matched route to the span opened by middleware.
+///     tracing::Span::current().record("route", "/users/:id");
+///     tracing::info!("event is recorded within request span");
+///     "hello,world!"
+/// }
+///
+/// App::new()
+///     .at("/users/:id", get(handler_service(handler)))
+///     # .at("/infer", handler_service(|_: &WebContext<'_>| async{ "infer type" }))
+///     .enclosed(RequestSpan::new());
+///
+/// // custom span with it's own field set.
+/// App::new()
+///     .at("/", get(handler_service(|| async { "hello,world!" })))
+///     # .at("/infer", handler_service(|_: &WebContext<'_>| async{ "infer type" }))
+///     .enclosed(RequestSpan::with_span_fn(|req| {
+///         tracing::debug_span!("http", uri = %req.uri(), version = ?req.version())
+///     }));
+/// ```
+///
+/// [`Logger`]: crate::middleware::Logger
+/// [`Level::INFO`]: tracing::Level::INFO
+#[derive(Clone)]
+pub struct RequestSpan<F = fn(&WebRequest<()>) -> Span> {
+    make_span: F,
+}
+
+impl Default for RequestSpan {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RequestSpan {
+    /// construct middleware opening span with default field set.
+    pub fn new() -> Self {
+        Self {
+            make_span: default_span,
+        }
+    }
+}
+
+impl<F> RequestSpan<F> {
+    /// construct middleware opening span with given function. the function is called with request head before
+    /// it's passed to enclosed service.
+    pub fn with_span_fn(make_span: F) -> Self
+    where
+        F: Fn(&WebRequest<()>) -> Span,
+    {
+        Self { make_span }
+    }
+}
+
+fn default_span(req: &WebRequest<()>) -> Span {
+    let request_id = req.headers().get(X_REQUEST_ID).and_then(|v| v.to_str().ok());
+    tracing::info_span!(
+        "request",
+        method = %req.method(),
+        path = req.uri().path(),
+        request_id,
+        route = tracing::field::Empty,
+    )
+}
+
+impl<S, E, F> Service<Result<S, E>> for RequestSpan<F>
+where
+    F: Clone,
+{
+    type Response = service::RequestSpanService<S, F>;
+    type Error = E;
+
+    async fn call(&self, res: Result<S, E>) -> Result<Self::Response, Self::Error> {
+        res.map(|service| service::RequestSpanService {
+            service,
+            make_span: self.make_span.clone(),
+        })
+    }
+}
+
+/// middleware for logging errors produced by enclosed service when they are rendered to response.
+///
+/// Error is rendered to response by it's own [`Service`] impl and the status code of response decides the level of
+/// event. By default "5xx" server errors are logged with [`Level::ERROR`] and other errors(including "4xx" client
+/// errors) are logged with [`Level::DEBUG`]. Event carries both `Display` and `Debug` format of error.
+///
+/// The rendered response is passed to outer services in place of the error. Middleware enclosed outside of this one
+/// observes it as a regular response.
+///
+/// # Examples
+/// ```rust
+/// # use tracing::Level;
+/// # use xitca_web::{
+/// #     error::Error, handler::handler_service, http::StatusCode, middleware::trace::ErrorLog, App, WebContext,
+/// # };
+/// App::new()
+///     .at("/", handler_service(|| async { Err::<&str, _>(Error::from(StatusCode::INTERNAL_SERVER_ERROR)) }))
+///     # .at("/infer", handler_service(|_: &WebContext<'_>| async{ "infer type" }))
+///     // log client errors with info level too.
+///     .enclosed(ErrorLog::new().client_error_level(Level::INFO));
+/// ```
+#[derive(Clone, Copy)]
+pub struct ErrorLog {
+    server_error: Level,
+    client_error: Level,
+}
+
+impl Default for ErrorLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ErrorLog {
+    /// construct middleware logging server errors with [`Level::ERROR`] and other errors with [`Level::DEBUG`].
+    pub const fn new() -> Self {
+        Self {
+            server_error: Level::ERROR,
+            client_error: Level::DEBUG,
+        }
+    }
+
+    /// set level for errors rendered to "5xx" response.
+    pub fn server_error_level(mut self, level: Level) -> Self {
+        self.server_error = level;
+        self
+    }
+
+    /// set level for errors rendered to response other than "5xx".
+    pub fn client_error_level(mut self, level: Level) -> Self {
+        self.client_error = level;
+        self
+    }
+
+    fn level(&self, status: StatusCode) -> Level {
+        if status.is_server_error() {
+            self.server_error
+        } else {
+            self.client_error
+        }
+    }
+}
+
+impl<S, E> Service<Result<S, E>> for ErrorLog {
+    type Response = service::ErrorLogService<S>;
+    type Error = E;
+
+    async fn call(&self, res: Result<S, E>) -> Result<Self::Response, Self::Error> {
+        res.map(|service| service::ErrorLogService { service, config: *self })
+    }
+}
+
+/// middleware for logging requests taking longer than given threshold.
+///
+/// Duration is wall clock time from middleware receiving the request to response body being fully streamed or
+/// dropped. Request finished within threshold is not logged so pathological slow endpoints can be surfaced without
+/// logging every request. By default event is emitted with [`Level::WARN`].
+///
+/// # Fields
+/// - `method` and `path`: of request.
+/// - `route`: path pattern of matched route. Empty when request does not match any route.
+/// - `status`: status code of response. Empty when enclosed service produces error.
+/// - `elapsed`: measured duration.
+/// - `phase` and `phase_elapsed`: the slowest phase and it's duration when [`ServerTiming`] middleware is enclosed
+///   outside of this middleware. Custom metric recorded with [`Timings::record`] is treated as phase too. Empty
+///   when no timing is available.
+///
+/// # Examples
+/// ```rust
+/// # use core::time::Duration;
+/// # use tracing::Level;
+/// # use xitca_web::{
+/// #   handler::handler_service,
+/// #   middleware::{server_timing::ServerTiming, trace::SlowRequest},
+/// #   App, WebContext
+/// # };
+/// App::new()
+///     .at("/", handler_service(|| async { "hello,world!" }))
+///     # .at("/infer", handler_service(|_: &WebContext<'_>| async{ "infer type" }))
+///     // log request taking longer than 500 milliseconds with error level.
+///     .enclosed(SlowRequest::new(Duration::from_millis(500)).level(Level::ERROR))
+///     // enclose ServerTiming outside for logging the slowest phase of request.
+///     .enclosed(ServerTiming::new());
+/// ```
+///
+/// [`ServerTiming`]: crate::middleware::server_timing::ServerTiming
+/// [`Timings::record`]: crate::middleware::server_timing::Timings::record
+/// [`Level::WARN`]: tracing::Level::WARN
+#[derive(Clone, Copy)]
+pub struct SlowRequest {
+    threshold: Duration,
+    level: Level,
+}
+
+impl SlowRequest {
+    /// construct middleware logging request taking longer than given threshold with [`Level::WARN`].
+    pub const fn new(threshold: Duration) -> Self {
+        Self {
+            threshold,
+            level: Level::WARN,
+        }
+    }
+
+    /// set level of slow request event.
+    pub fn level(mut self, level: Level) -> Self {
+        self.level = level;
+        self
+    }
+
+    fn is_slow(&self, elapsed: Duration) -> bool {
+        elapsed >= self.threshold
+    }
+}
+
+impl<S, E> Service<Result<S, E>> for SlowRequest {
+    type Response = service::SlowRequestService<S>;
+    type Error = E;
+
+    async fn call(&self, res: Result<S, E>) -> Result<Self::Response, Self::Error> {
+        res.map(|service| service::SlowRequestService { service, config: *self })
+    }
+}
+
+mod service {
+    use core::{
+        convert::Infallible,
+        fmt,
+        pin::Pin,
+        task::{ready, Context, Poll},
+    };
+
+    use std::time::Instant;
+
+    use futures_core::stream::Stream;
+    use pin_project_lite::pin_project;
+    use tracing::Instrument;
+    use xitca_http::util::service::router::MatchedRoute;
+
+    use crate::{
+        body::{Either, ResponseBody},
+        bytes::Bytes,
+        http::{Method, Uri, WebResponse},
+        middleware::server_timing::{self, Timings},
+        service::{ready::ReadyService, Service},
+        WebContext,
+    };
+
+    use super::*;
+
+    pub struct RequestSpanService<S, F> {
+        pub(super) service: S,
+        pub(super) make_span: F,
+    }
+
+    impl<'r, C, B, S, F, Res, Err> Service<WebContext<'r, C, B>> for RequestSpanService<S, F>
+    where
+        S: for<'r2> Service<WebContext<'r2, C, B>, Response = Res, Error = Err>,
+        F: Fn(&WebRequest<()>) -> Span,
+    {
+        type Response = Res;
+        type Error = Err;
+
+        async fn call(&self, ctx: WebContext<'r, C, B>) -> Result<Self::Response, Self::Error> {
+            let span = (self.make_span)(ctx.req());
+            self.service.call(ctx).instrument(span).await
+        }
+    }
+
+    impl<S, F> ReadyService for RequestSpanService<S, F>
+    where
+        S: ReadyService,
+    {
+        type Ready = S::Ready;
+
+        #[inline]
+        async fn ready(&self) -> Self::Ready {
+            self.service.ready().await
+        }
+    }
+
+    pub struct ErrorLogService<S> {
+        pub(super) service: S,
+        pub(super) config: ErrorLog,
+    }
+
+    impl<'r, C, S, ResB, Err> Service<WebContext<'r, C>> for ErrorLogService<S>
+    where
+        S: for<'r2> Service<WebContext<'r2, C>, Response = WebResponse<ResB>, Error = Err>,
+        Err: for<'r2> Service<WebContext<'r2, C>, Response = WebResponse, Error = Infallible>
+            + fmt::Debug
+            + fmt::Display,
+    {
+        type Response = WebResponse<Either<ResB, ResponseBody>>;
+        type Error = Infallible;
+
+        async fn call(&self, mut ctx: WebContext<'r, C>) -> Result<Self::Response, Self::Error> {
+            match self.service.call(ctx.reborrow()).await {
+                Ok(res) => Ok(res.map(Either::left)),
+                Err(e) => {
+                    let res = match e.call(ctx).await {
+                        Ok(res) => res,
+                        Err(i) => match i {},
+                    };
+                    let status = res.status();
+
+                    // event macro requires level to be constant.
+                    macro_rules! log {
+                        ($($lvl: ident),*) => {
+                            match self.config.level(status) {
+                                $(
+                                    Level::$lvl => {
+                                        tracing::event!(Level::$lvl, %status, error = %e, debug = ?e, "request failed")
+                                    }
+                                )*
+                            }
+                        }
+                    }
+
+                    log!(TRACE, DEBUG, INFO, WARN, ERROR);
+
+                    Ok(res.map(Either::right))
+                }
+            }
+        }
+    }
+
+    impl<S> ReadyService for ErrorLogService<S>
+    where
+        S: ReadyService,
+    {
+        type Ready = S::Ready;
+
+        #[inline]
+        async fn ready(&self) -> Self::Ready {
+            self.service.ready().await
+        }
+    }
+
+    pub struct SlowRequestService<S> {
+        pub(super) service: S,
+        pub(super) config: SlowRequest,
+    }
+
+    impl<'r, C, B, S, ResB, Err> Service<WebContext<'r, C, B>> for SlowRequestService<S>
+    where
+        S: for<'r2> Service<WebContext<'r2, C, B>, Response = WebResponse<ResB>, Error = Err>,
+    {
+        type Response = WebResponse<SlowRequestBody<ResB>>;
+        type Error = Err;
+
+        async fn call(&self, mut ctx: WebContext<'r, C, B>) -> Result<Self::Response, Self::Error> {
+            let start = Instant::now();
+
+            // snapshot request head before it's consumed by enclosed service.
+            let req = ctx.req();
+            let method = req.method().clone();
+            let uri = req.uri().clone();
+            let timings = server_timing::timings(req.extensions());
+
+            let res = self.service.call(ctx.reborrow()).await;
+
+            let mut record = Record {
+                config: self.config,
+                start,
+                method,
+                uri,
+                // route is matched inside enclosed service and kept in request head after it's consumed.
+                route: ctx.req().body().matched_route().clone(),
+                status: None,
+                timings,
+            };
+
+            match res {
+                Ok(res) => {
+                    record.status = Some(res.status().as_u16());
+                    Ok(res.map(|body| SlowRequestBody {
+                        body,
+                        record: Some(record),
+                    }))
+                }
+                Err(e) => {
+                    record.log();
+                    Err(e)
+                }
+            }
+        }
+    }
+
+    impl<S> ReadyService for SlowRequestService<S>
+    where
+        S: ReadyService,
+    {
+        type Ready = S::Ready;
+
+        #[inline]
+        async fn ready(&self) -> Self::Ready {
+            self.service.ready().await
+        }
+    }
+
+    // request information kept until response body is finished.
+    struct Record {
+        config: SlowRequest,
+        start: Instant,
+        method: Method,
+        uri: Uri,
+        route: MatchedRoute,
+        status: Option<u16>,
+        timings: Option<Timings>,
+    }
+
+    impl Record {
+        fn log(self) {
+            let elapsed = self.start.elapsed();
+
+            if !self.config.is_slow(elapsed) {
+                return;
+            }
+
+            let (phase, phase_elapsed) = self.timings.as_ref().and_then(Timings::slowest).unzip();
+
+            // event macro requires level to be constant.
+            macro_rules! log {
+                ($($lvl: ident),*) => {
+                    match self.config.level {
+                        $(
+                            Level::$lvl => tracing::event!(
+                                Level::$lvl,
+                                method = %self.method,
+                                path = self.uri.path(),
+                                route = self.route.as_str(),
+                                status = self.status,
+                                elapsed = ?elapsed,
+                                phase = phase.as_deref(),
+                                phase_elapsed = phase_elapsed.map(tracing::field::debug),
+                                "slow request"
+                            ),
+                        )*
+                    }
+                }
+            }
+
+            log!(TRACE, DEBUG, INFO, WARN, ERROR);
+        }
+    }
+
+    pin_project! {
+        /// response body logging slow request when it's fully streamed or dropped. See [SlowRequest] for detail.
+        pub struct SlowRequestBody<B> {
+            #[pin]
+            body: B,
+            record: Option<Record>,
+        }
+
+        impl<B> PinnedDrop for SlowRequestBody<B> {
+            fn drop(this: Pin<&mut Self>) {
+                if let Some(record) = this.project().record.take() {
+                    record.log();
+                }
+            }
+        }
+    }
+
+    impl<B, E> Stream for SlowRequestBody<B>
+    where
+        B: Stream<Item = Result<Bytes, E>>,
+    {
+        type Item = B::Item;
+
+        fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+            let this = self.project();
+            let res = ready!(this.body.poll_next(cx));
+            if res.is_none() {
+                if let Some(record) = this.record.take() {
+                    record.log();
+                }
+            }
+            Poll::Ready(res)
+        }
+
+        #[inline]
+        fn size_hint(&self) -> (usize, Option<usize>) {
+            self.body.size_hint()
+        }
+    }
+}
+
+pub use self::service::SlowRequestBody;
+
+#[cfg(test)]
+mod test {
+    use core::fmt;
+
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    };
+
+    use tracing::{
+        field::Field,
+        span::{Attributes, Id, Record},
+        Event, Metadata, Subscriber,
+    };
+    use xitca_unsafe_collection::futures::NowOrPanic;
+
+    use crate::{
+        handler::{extension::ExtensionRef, handler_service},
+        http::{header::HeaderValue, Method, StatusCode, Uri},
+        middleware::server_timing::{ServerTiming, Timings},
+        test::collect_string_body,
+        App,
+    };
+
+    use super::*;
+
+    #[test]
+    fn span_per_request() {
+        static SPANS: AtomicUsize = AtomicUsize::new(0);
+
+        let service = App::new()
+            .at("/foo", handler_service(|| async { "hello,world!" }))
+            .enclosed(RequestSpan::with_span_fn(|req: &WebRequest<()>| {
+                assert_eq!(req.method(), Method::POST);
+                assert_eq!(req.uri().path(), "/foo");
+                assert_eq!(req.headers().get(X_REQUEST_ID).unwrap(), "996");
+                SPANS.fetch_add(1, Ordering::SeqCst);
+                default_span(req)
+            }))
+            .finish()
+            .call(())
+            .now_or_panic()
+            .unwrap();
+
+        for n in 1..=2 {
+            let mut req = WebRequest::default();
+            *req.method_mut() = Method::POST;
+            *req.uri_mut() = Uri::from_static("/foo");
+            req.headers_mut().insert(X_REQUEST_ID, HeaderValue::from_static("996"));
+
+            let res = service.call(req).now_or_panic().unwrap();
+            assert_eq!(res.status(), StatusCode::OK);
+            assert_eq!(SPANS.load(Ordering::SeqCst), n);
+        }
+    }
+
+    #[test]
+    fn error_log() {
+        let log = ErrorLog::new();
+        assert_eq!(log.level(StatusCode::INTERNAL_SERVER_ERROR), Level::ERROR);
+        assert_eq!(log.level(StatusCode::NOT_FOUND), Level::DEBUG);
+
+        let log = log.client_error_level(Level::INFO).server_error_level(Level::WARN);
+        assert_eq!(log.level(StatusCode::BAD_REQUEST), Level::INFO);
+        assert_eq!(log.level(StatusCode::BAD_GATEWAY), Level::WARN);
+
+        let service = App::new()
+            .at("/", handler_service(|| async { "hello,world!" }))
+            .enclosed(ErrorLog::new())
+            .finish()
+            .call(())
+            .now_or_panic()
+            .unwrap();
+
+        let res = service.call(WebRequest::default()).now_or_panic().unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+
+        // error is rendered by middleware and passed to outer services as response.
+        let mut req = WebRequest::default();
+        *req.uri_mut() = Uri::from_static("/foo");
+        let res = service.call(req).now_or_panic().unwrap();
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    }
+
+    // field name and value in debug format of one event.
+    type Fields = Vec<(&'static str, String)>;
+
+    // subscriber collecting fields of events.
+    #[derive(Clone, Default)]
+    struct Collect(Arc<Mutex<Vec<Fields>>>);
+
+    impl Collect {
+        fn take(&self) -> Vec<Fields> {
+            core::mem::take(&mut *self.0.lock().unwrap())
+        }
+    }
+
+    impl Subscriber for Collect {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, _: &Attributes<'_>) -> Id {
+            Id::from_u64(1)
+        }
+
+        fn record(&self, _: &Id, _: &Record<'_>) {}
+
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            let mut fields = Vec::new();
+            event
+                .record(&mut |field: &Field, value: &dyn fmt::Debug| fields.push((field.name(), format!("{value:?}"))));
+            self.0.lock().unwrap().push(fields);
+        }
+
+        fn enter(&self, _: &Id) {}
+
+        fn exit(&self, _: &Id) {}
+    }
+
+    #[test]
+    fn slow_request() {
+        let slow = SlowRequest::new(Duration::from_millis(100));
+        assert!(!slow.is_slow(Duration::from_millis(99)));
+        assert!(slow.is_slow(Duration::from_millis(100)));
+
+        let service = App::new()
+            .at(
+                "/users/:id",
+                handler_service(|ExtensionRef(timings): ExtensionRef<'_, Timings>| {
+                    timings.record("db", Duration::from_secs(1));
+                    async { "hello,world!" }
+                }),
+            )
+            .enclosed(SlowRequest::new(Duration::ZERO).level(Level::INFO))
+            .enclosed(ServerTiming::new())
+            .finish()
+            .call(())
+            .now_or_panic()
+            .unwrap();
+
+        let collect = Collect::default();
+
+        tracing::subscriber::with_default(collect.clone(), || {
+            let mut req = WebRequest::default();
+            *req.method_mut() = Method::POST;
+            *req.uri_mut() = Uri::from_static("/users/1");
+            let res = service.call(req).now_or_panic().unwrap();
+            assert_eq!(res.status(), StatusCode::OK);
+            let body = collect_string_body(res.into_body()).now_or_panic().unwrap();
+            assert_eq!(body, "hello,world!");
+        });
+
+        let events = collect.take();
+        assert_eq!(events.len(), 1);
+        let fields = &events[0];
+        let field = |name| fields.iter().find(|(n, _)| *n == name).map(|(_, v)| v.as_str());
+        assert_eq!(field("method"), Some("POST"));
+        assert_eq!(field("path"), Some("\"/users/1\""));
+        assert_eq!(field("route"), Some("\"/users/:id\""));
+        assert_eq!(field("status"), Some("200"));
+        assert_eq!(field("phase"), Some("\"db\""));
+        assert_eq!(field("phase_elapsed"), Some("1s"));
+
+        tracing::subscriber::with_default(collect.clone(), || {
+            let mut req = WebRequest::default();
+            *req.uri_mut() = Uri::from_static("/foo");
+            let res = service.call(req).now_or_panic().unwrap();
+            assert_eq!(res.status(), StatusCode::NOT_FOUND);
+        });
+
+        let events = collect.take();
+        assert_eq!(events.len(), 1);
+        let fields = &events[0];
+        let field = |name| fields.iter().find(|(n, _)| *n == name).map(|(_, v)| v.as_str());
+        assert_eq!(field("method"), Some("GET"));
+        assert_eq!(field("path"), Some("\"/foo\""));
+        assert_eq!(field("route"), None);
+        assert_eq!(field("status"), None);
+    }
+}
@@ -7,6 +7,8 @@ use xitca_http::{
     HttpServiceBuilder,
 };
 use xitca_server::{Builder, ServerFuture};
+
+pub use xitca_server::RuntimeModel;
 use xitca_service::ServiceExt;
 
 use crate::{
@@ -16,6 +18,42 @@ use crate::{
 };
 
 /// multi protocol handling http server
+///
+/// # Multiple listeners
+/// One application can be served on multiple listeners by calling `bind_xxx` APIs multiple times. e.g. plain http
+/// on one port and https on another. The application service is not rebuilt for every listener by user. Instead it's
+/// kept by server as a service factory and it's [`Service::call`] is called once for every listener on every worker
+/// thread to construct the service handling connections. This means application state constructed by `App::with_xxx_state`
+/// APIs is constructed separately for every listener and worker. State must be wrapped in smart pointer like
+/// [`Arc`] and cloned into state builder when it needs to be shared between them.
+///
+/// ```rust,no_run
+/// # use xitca_web::{handler::handler_service, route::get, App};
+/// # fn serve() -> std::io::Result<()> {
+/// App::new()
+///     .at("/", get(handler_service(|| async { "Hello,World!" })))
+///     .serve()
+///     .bind("127.0.0.1:8080")?
+///     .bind("[::1]:8080")?
+///     .run()
+///     .wait()
+/// # }
+/// ```
+///
+/// # Runtime model
+/// Every worker thread either runs it's own single threaded runtime([`RuntimeModel::ThreadPerCore`], default) or
+/// drives a multi-threaded runtime shared by all workers([`RuntimeModel::Shared`]). It's set with
+/// [`HttpServer::runtime_model`] and see [`Builder`]'s doc for detail. In both models application service is
+/// constructed and called on every worker thread and it means:
+/// - state produced by [`App::with_async_state`] is owned by one worker and it's not bound to `Send` and `Sync`.
+///   `Rc` and `RefCell` can be used in it and a thread local resource like a `xitca_postgres::Client` connected per
+///   worker can be stored in it without a connection pool shared by workers.
+/// - state produced by [`App::with_state`] is cloned for every worker and it must be `Send + Sync`.
+/// - resource bound to the runtime creating it(for example a connection pool spawning it's connection tasks with
+///   `tokio::spawn`) can only be shared between workers with [`RuntimeModel::Shared`].
+///
+/// [`App::with_async_state`]: crate::App::with_async_state
+/// [`App::with_state`]: crate::App::with_state
 pub struct HttpServer<
     S,
     const HEADER_LIMIT: usize = DEFAULT_HEADER_LIMIT,
@@ -25,6 +63,8 @@ pub struct HttpServer<
     service: Arc<S>,
     builder: Builder,
     config: HttpServiceConfig<HEADER_LIMIT, READ_BUF_LIMIT, WRITE_BUF_LIMIT>,
+    // count of bound listeners. used for generating unique listener name.
+    listeners: usize,
 }
 
 impl<S> HttpServer<S>
@@ -36,6 +76,7 @@ where
             service: Arc::new(service),
             builder: Builder::new(),
             config: HttpServiceConfig::default(),
+            listeners: 0,
         }
     }
 }
@@ -69,12 +110,22 @@ where
 
     /// Set max number of threads for each worker's blocking task thread pool.
     ///
-    /// One thread pool is set up **per worker**; not shared across workers.
+    /// One thread pool is set up **per worker**; not shared across workers. With [`RuntimeModel::Shared`] one thread
+    /// pool is set up for the shared runtime.
     pub fn worker_max_blocking_threads(mut self, num: usize) -> Self {
         self.builder = self.builder.worker_max_blocking_threads(num);
         self
     }
 
+    /// Set runtime model of worker threads.
+    ///
+    /// Default set to [`RuntimeModel::ThreadPerCore`]. [`RuntimeModel::Shared`] is not supported with `io-uring`
+    /// feature and server would fail to start with it.
+    pub fn runtime_model(mut self, model: RuntimeModel) -> Self {
+        self.builder = self.builder.runtime_model(model);
+        self
+    }
+
     /// Disable signal listening.
     ///
     /// `tokio::signal` is used for listening and it only functions in tokio runtime 1.x.
@@ -123,6 +174,122 @@ where
         self
     }
 
+    /// Disable `date` header automatically added to response.
+    ///
+    /// Response with `date` header set by application is not affected by this setting.
+    pub fn disable_date_header(mut self) -> Self {
+        self.config = self.config.disable_date_header();
+        self
+    }
+
+    /// Define value of `server` header automatically added to response.
+    ///
+    /// By default no `server` header is added. Response with `server` header set by application is not affected
+    /// by this setting.
+    ///
+    /// # Panics
+    /// When value is not a valid header value.
+    pub fn server_header(mut self, value: &'static str) -> Self {
+        self.config = self.config.server_header(value);
+        self
+    }
+
+    /// Disable `connection: keep-alive` and `keep-alive: timeout=<secs>` headers automatically added to http/1
+    /// response.
+    ///
+    /// By default these headers are added to response of HTTP/1.0 request when it's connection is kept alive.
+    pub fn disable_keep_alive_header(mut self) -> Self {
+        self.config = self.config.disable_keep_alive_header();
+        self
+    }
+
+    /// Change max length of http/1 request uri in bytes.
+    ///
+    /// Request with longer uri is rejected with "414 URI Too Long" response before the rest of request head is
+    /// received.
+    ///
+    /// Default to 8kb.
+    pub fn max_uri_len(mut self, len: usize) -> Self {
+        self.config = self.config.max_uri_len(len);
+        self
+    }
+
+    /// Change initial flow control window size of every http/2 request stream.
+    ///
+    /// This is the upper bound of request body bytes buffered for a single request before the application
+    /// consumes them.
+    pub fn h2_initial_window_size(mut self, size: u32) -> Self {
+        self.config = self.config.h2_initial_window_size(size);
+        self
+    }
+
+    /// Change initial flow control window size of http/2 connection.
+    ///
+    /// This is the upper bound of request body bytes buffered for all requests of a single connection before the
+    /// application consumes them.
+    pub fn h2_initial_connection_window_size(mut self, size: u32) -> Self {
+        self.config = self.config.h2_initial_connection_window_size(size);
+        self
+    }
+
+    /// Change max number of concurrent http/2 request streams of a connection.
+    ///
+    /// Stream opened beyond the limit is refused with `REFUSED_STREAM` error code without being dispatched to
+    /// application.
+    pub fn h2_max_concurrent_streams(mut self, max: u32) -> Self {
+        self.config = self.config.h2_max_concurrent_streams(max);
+        self
+    }
+
+    /// Change duration of how long a http/2 connection stays idle before server sends a PING frame to probe the
+    /// liveness of peer.
+    ///
+    /// By default the value of [HttpServer::keep_alive_timeout] is used.
+    pub fn h2_ping_interval(mut self, dur: Duration) -> Self {
+        self.config = self.config.h2_ping_interval(dur);
+        self
+    }
+
+    /// Change duration of how long server waits for PONG frame after sending PING frame.
+    ///
+    /// Peer fails to answer in time is treated as dead and it's connection is closed.
+    ///
+    /// By default 10 times of the value of [HttpServer::keep_alive_timeout] is used.
+    pub fn h2_ping_timeout(mut self, dur: Duration) -> Self {
+        self.config = self.config.h2_ping_timeout(dur);
+        self
+    }
+
+    /// Enable lenient parsing of http/1 request head for legacy client.
+    ///
+    /// See [HttpServiceConfig::lenient_request_parsing] for detail.
+    pub fn lenient_request_parsing(mut self) -> Self {
+        self.config = self.config.lenient_request_parsing();
+        self
+    }
+
+    /// Enable per worker load shedding for http/1 connections.
+    ///
+    /// When bytes buffered by connections of a worker exceed `high_water_mark` new requests are answered with
+    /// "503 Service Unavailable" and their connections are closed until buffered bytes drop to `low_water_mark`.
+    ///
+    /// # Panics
+    /// When `low_water_mark` is larger than `high_water_mark`.
+    #[cfg(feature = "http1")]
+    pub fn load_shed(mut self, high_water_mark: usize, low_water_mark: usize) -> Self {
+        self.config = self.config.load_shed(high_water_mark, low_water_mark);
+        self
+    }
+
+    /// Enable cleartext http/2 (h2c) on connections not negotiated as http/2 by tls.
+    ///
+    /// Client can start http/2 with prior knowledge or with a http/1 request carrying `Upgrade: h2c` header.
+    #[cfg(all(feature = "http1", feature = "http2"))]
+    pub fn h2c(mut self) -> Self {
+        self.config = self.config.h2c();
+        self
+    }
+
     /// Change max size for request head.
     ///
     /// Request has a bigger head than it would be reject with error.
@@ -180,7 +347,8 @@ where
     {
         let config = self.config;
         let service = self.service.clone().enclosed(HttpServiceBuilder::with_config(config));
-        self.builder = self.builder.bind("xitca-web", addr, service)?;
+        let name = self.listener_name("xitca-web");
+        self.builder = self.builder.bind(name, addr, service)?;
         Ok(self)
     }
 
@@ -196,7 +364,8 @@ where
     {
         let config = self.config;
         let service = self.service.clone().enclosed(HttpServiceBuilder::with_config(config));
-        self.builder = self.builder.listen("xitca-web", listener, service);
+        let name = self.listener_name("xitca-web");
+        self.builder = self.builder.listen(name, listener, service);
         Ok(self)
     }
 
@@ -251,7 +420,8 @@ where
             .clone()
             .enclosed(HttpServiceBuilder::with_config(config).openssl(acceptor));
 
-        self.builder = self.builder.bind("xitca-web-openssl", addr, service)?;
+        let name = self.listener_name("xitca-web-openssl");
+        self.builder = self.builder.bind(name, addr, service)?;
 
         Ok(self)
     }
@@ -287,8 +457,42 @@ where
             .clone()
             .enclosed(HttpServiceBuilder::with_config(service_config).rustls(config));
 
-        self.builder = self.builder.bind("xitca-web-rustls", addr, service)?;
+        let name = self.listener_name("xitca-web-rustls");
+        self.builder = self.builder.bind(name, addr, service)?;
+
+        Ok(self)
+    }
+
+    /// bind to udp socket of given address and serve application with http/3 over quic.
+    ///
+    /// Crypto config of given [QuicConfig] must advertise `h3` protocol through alpn or client would refuse to
+    /// establish the connection. http/3 has no cleartext variant and it can be used along with [HttpServer::bind]
+    /// or other tcp listeners on the same port. Client usually discovers http/3 service from `Alt-Svc` response
+    /// header sent through http/1 or http/2.
+    ///
+    /// [QuicConfig]: xitca_io::net::QuicConfig
+    #[cfg(feature = "http3")]
+    pub fn bind_h3<A: std::net::ToSocketAddrs, ResB, BE>(
+        mut self,
+        addr: A,
+        config: xitca_io::net::QuicConfig,
+    ) -> std::io::Result<Self>
+    where
+        S: Service + 'static,
+        S::Response: ReadyService + Service<Request<RequestExt<RequestBody>>, Response = Response<ResB>> + 'static,
+        S::Error: fmt::Debug,
+        <S::Response as Service<Request<RequestExt<RequestBody>>>>::Error: fmt::Debug,
 
+        ResB: Stream<Item = Result<Bytes, BE>> + 'static,
+        BE: fmt::Debug + 'static,
+    {
+        let service_config = self.config;
+        let service = self
+            .service
+            .clone()
+            .enclosed(HttpServiceBuilder::with_config(service_config));
+        let name = self.listener_name("xitca-web-h3");
+        self.builder = self.builder.bind_h3(name, addr, config, service)?;
         Ok(self)
     }
 
@@ -305,7 +509,8 @@ where
     {
         let config = self.config;
         let service = self.service.clone().enclosed(HttpServiceBuilder::with_config(config));
-        self.builder = self.builder.bind_unix("xitca-web", path, service)?;
+        let name = self.listener_name("xitca-web-unix");
+        self.builder = self.builder.bind_unix(name, path, service)?;
         Ok(self)
     }
 
@@ -322,6 +527,15 @@ where
             config: self
                 .config
                 .mutate_const_generic::<HEADER_LIMIT2, READ_BUF_LIMIT2, WRITE_BUF_LIMIT2>(),
+            listeners: self.listeners,
         }
     }
+
+    // every listener must have it's own name. listeners sharing the same name are served by the last service
+    // bound with it.
+    fn listener_name(&mut self, prefix: &str) -> String {
+        let name = format!("{prefix}-{}", self.listeners);
+        self.listeners += 1;
+        name
+    }
 }
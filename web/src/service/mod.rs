@@ -6,4 +6,9 @@ pub mod tower_http_compat;
 #[cfg(feature = "file-raw")]
 pub mod file;
 
+#[cfg(feature = "proxy")]
+pub mod proxy;
+
+pub mod mount;
+
 pub use xitca_service::*;
@@ -0,0 +1,253 @@
+//! service for mounting a finished application as route of another application.
+
+use core::{convert::Infallible, fmt};
+
+use futures_core::stream::Stream;
+use xitca_http::util::service::router::{PathGen, RouteGen, RouterMapErr};
+
+use crate::{
+    body::ResponseBody,
+    bytes::Bytes,
+    context::WebContext,
+    error::{BodyError, Error},
+    http::{
+        uri::{PathAndQuery, Uri},
+        StatusCode, WebRequest, WebResponse,
+    },
+    service::{ready::ReadyService, Service},
+};
+
+/// builder type for mounting independently built application(or any compatible service) as route of another
+/// application.
+///
+/// Unlike nesting [`App`] directly with [`App::at`] the mounted application is already finished with
+/// [`App::finish`] and it keeps it's own routing, middleware stack and state. Request is forwarded to it with the
+/// mount prefix stripped from uri path. e.g. mounted at `/api`, request to `/api/users?id=1` is seen as
+/// `/users?id=1` by mounted application.
+/// Request to mount prefix itself(`/api` and `/api/`) is seen as `/` by mounted application.
+///
+/// # State
+/// Mounted application builds it's own state independent from parent. Sharing state between them can be done by
+/// passing the same value (e.g. an `Arc` wrapped type) to both [`App::with_state`].
+///
+/// # Not found
+/// Mounted application renders it's own responses including "404 Not Found" for unmatched path. They are returned to
+/// parent as successful response and parent does not try to match the request with other routes.
+///
+/// # Panics
+/// Failure of building mounted application panics. Same as nested application with fallible state builder.
+///
+/// # Examples
+/// ```rust
+/// # use xitca_web::{handler::handler_service, middleware::CatchUnwind, service::mount::Mount, App, WebContext};
+/// // independently built module with it's own state and middleware.
+/// let admin = App::new()
+///     .with_state(String::from("admin"))
+///     .at("/users", handler_service(|_: &WebContext<'_, String>| async { "admin users" }))
+///     .enclosed(CatchUnwind)
+///     .finish();
+///
+/// App::new()
+///     .at("/", handler_service(|| async { "index" }))
+///     // "/admin/users" is handled by admin application.
+///     .at("/admin", Mount::new(admin))
+///     # .at("/infer", handler_service(|_: &WebContext<'_>| async{ "infer type" }))
+///     ;
+/// ```
+///
+/// [`App`]: crate::App
+/// [`App::at`]: crate::App::at
+/// [`App::finish`]: crate::App::finish
+/// [`App::with_state`]: crate::App::with_state
+pub struct Mount<S> {
+    service: S,
+}
+
+impl<S> Mount<S> {
+    /// construct mount route with given service builder. e.g. the output of [`App::finish`].
+    ///
+    /// [`App::finish`]: crate::App::finish
+    pub const fn new(service: S) -> Self {
+        Self { service }
+    }
+}
+
+impl<S> PathGen for Mount<S> {
+    fn path_gen(&mut self, prefix: &str) -> String {
+        let mut prefix = String::from(prefix);
+        if prefix.ends_with('/') {
+            prefix.pop();
+        }
+
+        prefix.push_str("/*p");
+
+        prefix
+    }
+
+    // catch all pattern does not match mount prefix itself. register it and it's trailing slash variant as alias.
+    fn path_alias(&self, prefix: &str) -> Vec<String> {
+        let prefix = prefix.trim_end_matches('/');
+        if prefix.is_empty() {
+            return vec![String::from("/")];
+        }
+        vec![String::from(prefix), format!("{prefix}/")]
+    }
+}
+
+impl<S> RouteGen for Mount<S> {
+    type Route<R> = RouterMapErr<R>;
+
+    fn route_gen<R>(route: R) -> Self::Route<R> {
+        RouterMapErr(route)
+    }
+}
+
+impl<S> Service for Mount<S>
+where
+    S: Service,
+    S::Error: fmt::Debug,
+{
+    type Response = MountService<S::Response>;
+    type Error = Infallible;
+
+    async fn call(&self, _: ()) -> Result<Self::Response, Self::Error> {
+        let service = self
+            .service
+            .call(())
+            .await
+            .expect("fallible mounted application builder is not supported");
+        Ok(MountService(service))
+    }
+}
+
+pub struct MountService<S>(S);
+
+impl<'r, C, B, S, ResB, T, E> Service<WebContext<'r, C, B>> for MountService<S>
+where
+    B: Default,
+    S: Service<WebRequest<B>, Response = WebResponse<ResB>, Error = Infallible>,
+    ResB: Stream<Item = Result<T, E>> + 'static,
+    T: Into<Bytes>,
+    E: Into<BodyError>,
+{
+    type Response = WebResponse;
+    type Error = Error;
+
+    async fn call(&self, mut ctx: WebContext<'r, C, B>) -> Result<Self::Response, Self::Error> {
+        let uri = {
+            let req = ctx.req();
+            let suffix = req.body().params().get("p").unwrap_or_default();
+            strip_uri(req.uri(), suffix).ok_or(Error::from(StatusCode::BAD_REQUEST))?
+        };
+
+        let mut req = ctx.take_request();
+        *req.uri_mut() = uri;
+
+        match self.0.call(req).await {
+            Ok(res) => Ok(res.map(ResponseBody::box_stream)),
+            Err(e) => match e {},
+        }
+    }
+}
+
+impl<S> ReadyService for MountService<S>
+where
+    S: ReadyService,
+{
+    type Ready = S::Ready;
+
+    #[inline]
+    async fn ready(&self) -> Self::Ready {
+        self.0.ready().await
+    }
+}
+
+// replace uri path with path suffix after mount prefix. scheme, authority and query are kept as is.
+fn strip_uri(uri: &Uri, suffix: &str) -> Option<Uri> {
+    let mut path = String::with_capacity(suffix.len() + 1);
+    path.push('/');
+    path.push_str(suffix.trim_start_matches('/'));
+    if let Some(query) = uri.query() {
+        path.push('?');
+        path.push_str(query);
+    }
+
+    let mut parts = uri.clone().into_parts();
+    parts.path_and_query = Some(PathAndQuery::try_from(path).ok()?);
+    Uri::from_parts(parts).ok()
+}
+
+#[cfg(test)]
+mod test {
+    use xitca_unsafe_collection::futures::NowOrPanic;
+
+    use crate::{
+        handler::{handler_service, path::PathRef, state::StateRef},
+        http::{header::HeaderValue, Request},
+        test::collect_string_body,
+        App,
+    };
+
+    use super::*;
+
+    async fn handler(StateRef(state): StateRef<'_, String>, PathRef(path): PathRef<'_>) -> String {
+        format!("{state}:{path}")
+    }
+
+    async fn middleware<S, C>(s: &S, ctx: WebContext<'_, C>) -> Result<WebResponse, Error>
+    where
+        S: for<'r> Service<WebContext<'r, C>, Response = WebResponse, Error = Error>,
+    {
+        let mut res = s.call(ctx).await?;
+        res.headers_mut().insert("x-mount", HeaderValue::from_static("sub"));
+        Ok(res)
+    }
+
+    #[test]
+    fn mount() {
+        let sub = App::new()
+            .with_state(String::from("sub"))
+            .at("/", handler_service(handler))
+            .at("/users", handler_service(handler))
+            .enclosed_fn(middleware)
+            .finish();
+
+        let service = App::new()
+            .with_state(String::from("root"))
+            .at("/", handler_service(handler))
+            .at("/sub", Mount::new(sub))
+            .finish()
+            .call(())
+            .now_or_panic()
+            .unwrap();
+
+        let call = |uri: &str| {
+            let req = Request::builder().uri(uri).body(Default::default()).unwrap();
+            let res = service.call(req).now_or_panic().unwrap();
+            let status = res.status();
+            let mounted = res.headers().contains_key("x-mount");
+            let body = collect_string_body(res.into_body()).now_or_panic().unwrap();
+            (status, mounted, body)
+        };
+
+        assert_eq!(call("/"), (StatusCode::OK, false, String::from("root:/")));
+        // middleware and state of mounted application apply and mount prefix is stripped.
+        assert_eq!(
+            call("/sub/users?id=1"),
+            (StatusCode::OK, true, String::from("sub:/users"))
+        );
+        // not found of mounted application is rendered by itself.
+        assert_eq!(call("/sub/none").0, StatusCode::NOT_FOUND);
+        assert_eq!(call("/none").0, StatusCode::NOT_FOUND);
+        // mount prefix with and without trailing slash is routed to root of mounted application.
+        assert_eq!(call("/sub"), (StatusCode::OK, true, String::from("sub:/")));
+        assert_eq!(call("/sub/"), (StatusCode::OK, true, String::from("sub:/")));
+        assert_eq!(call("/sub?id=1"), (StatusCode::OK, true, String::from("sub:/")));
+
+        assert_eq!(
+            strip_uri(&Uri::from_static("https://example.com/sub/users?id=1"), "users").unwrap(),
+            "https://example.com/users?id=1"
+        );
+        assert_eq!(strip_uri(&Uri::from_static("/sub"), "").unwrap(), "/");
+    }
+}
@@ -0,0 +1,632 @@
+//! reverse proxy service.
+
+use core::{convert::Infallible, fmt, time::Duration};
+
+use std::{error, sync::Arc};
+
+use xitca_client::Client;
+use xitca_http::util::service::router::{PathGen, RouteGen, RouterMapErr};
+
+use crate::{
+    http::{
+        header::{self, HeaderMap, HeaderName},
+        uri::{Authority, PathAndQuery, Scheme, Uri},
+    },
+    service::Service,
+};
+
+/// builder type for reverse proxy service.
+///
+/// Request is forwarded to upstream with the path suffix after the mounting path appended to upstream uri.
+/// Request and response bodies are streamed, hop-by-hop headers are stripped and `x-forwarded-for`,
+/// `x-forwarded-host` and `x-forwarded-proto` headers are added to upstream request.
+///
+/// `x-forwarded-proto` is decided by the connection request is received from. See
+/// [RequestExt::is_secure](crate::http::RequestExt::is_secure) for detail.
+///
+/// Connections to upstream are pooled by an http client owned by each server worker thread. Upstream
+/// failure produces "502 Bad Gateway" response and timeout produces "504 Gateway Timeout" response.
+///
+/// Exchange with upstream is spawned as a task with [tokio::task::spawn_local] so the service must run inside
+/// a [tokio::task::LocalSet], which is what server worker threads of xitca-web do.
+///
+/// # Example
+/// ```rust
+/// # use xitca_web::{handler::handler_service, service::proxy::Proxy, App, WebContext};
+/// App::new()
+///     // "/api/users?id=1" would be forwarded to "http://backend:8080/users?id=1"
+///     .at("/api", Proxy::new("http://backend:8080"))
+///     # .at("/infer", handler_service(|_: &WebContext<'_>| async { "used for infer type" }));
+/// ```
+#[derive(Clone)]
+pub struct Proxy {
+    upstream: Uri,
+    timeout: Option<Duration>,
+    client: Arc<dyn Fn() -> Client + Send + Sync>,
+}
+
+impl Proxy {
+    /// construct a new proxy service forwarding request to given upstream uri. uri path of upstream is
+    /// used as prefix of forwarded request path.
+    ///
+    /// # Panics
+    /// - when upstream is not a valid absolute uri with scheme and authority.
+    pub fn new<U>(upstream: U) -> Self
+    where
+        Uri: TryFrom<U>,
+        <Uri as TryFrom<U>>::Error: fmt::Debug,
+    {
+        let upstream = Uri::try_from(upstream).expect("Proxy upstream must be a valid uri");
+        assert!(
+            upstream.scheme().is_some() && upstream.authority().is_some(),
+            "Proxy upstream must contain scheme and authority"
+        );
+        Self {
+            upstream,
+            timeout: None,
+            client: Arc::new(Client::new),
+        }
+    }
+
+    /// set timeout of receiving response head from upstream. timeout produces "504 Gateway Timeout" response.
+    ///
+    /// # Default
+    /// timeout of client is used. See [Proxy::client].
+    pub fn timeout(mut self, dur: Duration) -> Self {
+        self.timeout = Some(dur);
+        self
+    }
+
+    /// set function for constructing http client used for sending request to upstream. function is called
+    /// once on each server worker thread.
+    ///
+    /// # Default
+    /// [Client::new] is used.
+    pub fn client<F>(mut self, func: F) -> Self
+    where
+        F: Fn() -> Client + Send + Sync + 'static,
+    {
+        self.client = Arc::new(func);
+        self
+    }
+}
+
+impl PathGen for Proxy {
+    fn path_gen(&mut self, prefix: &str) -> String {
+        let mut prefix = String::from(prefix);
+        if prefix.ends_with('/') {
+            prefix.pop();
+        }
+
+        prefix.push_str("/*p");
+
+        prefix
+    }
+}
+
+impl RouteGen for Proxy {
+    type Route<R> = RouterMapErr<R>;
+
+    fn route_gen<R>(route: R) -> Self::Route<R> {
+        RouterMapErr(route)
+    }
+}
+
+impl Service for Proxy {
+    type Response = service::ProxyService;
+    type Error = Infallible;
+
+    async fn call(&self, _: ()) -> Result<Self::Response, Self::Error> {
+        let mut base = self.upstream.path().to_owned();
+        if base.ends_with('/') {
+            base.pop();
+        }
+
+        Ok(service::ProxyService {
+            client: Arc::new((self.client)()),
+            scheme: self.upstream.scheme().cloned().unwrap(),
+            authority: self.upstream.authority().cloned().unwrap(),
+            base,
+            timeout: self.timeout,
+        })
+    }
+}
+
+/// error type of proxy service. produce "504 Gateway Timeout" response when upstream timed out and
+/// "502 Bad Gateway" response for other upstream failures.
+#[derive(Debug)]
+pub struct ProxyError(xitca_client::error::Error);
+
+impl ProxyError {
+    /// true when error is caused by upstream timeout.
+    pub fn is_timeout(&self) -> bool {
+        match self.0 {
+            xitca_client::error::Error::Std(ref e) => e.is::<xitca_client::error::TimeoutError>(),
+            _ => false,
+        }
+    }
+
+    /// the underlying http client error.
+    pub fn into_inner(self) -> xitca_client::error::Error {
+        self.0
+    }
+}
+
+impl fmt::Display for ProxyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "proxy upstream error: {}", self.0)
+    }
+}
+
+impl error::Error for ProxyError {}
+
+crate::error::error_from_service!(ProxyError);
+
+impl<'r, C, B> Service<crate::WebContext<'r, C, B>> for ProxyError {
+    type Response = crate::http::WebResponse;
+    type Error = Infallible;
+
+    async fn call(&self, ctx: crate::WebContext<'r, C, B>) -> Result<Self::Response, Self::Error> {
+        let status = if self.is_timeout() {
+            crate::http::StatusCode::GATEWAY_TIMEOUT
+        } else {
+            crate::http::StatusCode::BAD_GATEWAY
+        };
+        status.call(ctx).await
+    }
+}
+
+#[allow(clippy::declare_interior_mutable_const)]
+const X_FORWARDED_FOR: HeaderName = HeaderName::from_static("x-forwarded-for");
+#[allow(clippy::declare_interior_mutable_const)]
+const X_FORWARDED_HOST: HeaderName = HeaderName::from_static("x-forwarded-host");
+#[allow(clippy::declare_interior_mutable_const)]
+const X_FORWARDED_PROTO: HeaderName = HeaderName::from_static("x-forwarded-proto");
+#[allow(clippy::declare_interior_mutable_const)]
+const KEEP_ALIVE: HeaderName = HeaderName::from_static("keep-alive");
+
+// join upstream base path with path suffix of request.
+fn upstream_uri(scheme: &Scheme, authority: &Authority, base: &str, suffix: &str, query: Option<&str>) -> Option<Uri> {
+    let mut path = String::with_capacity(base.len() + suffix.len() + 1);
+    path.push_str(base);
+    path.push('/');
+    path.push_str(suffix.trim_start_matches('/'));
+    if let Some(query) = query {
+        path.push('?');
+        path.push_str(query);
+    }
+
+    Uri::builder()
+        .scheme(scheme.clone())
+        .authority(authority.clone())
+        .path_and_query(PathAndQuery::try_from(path).ok()?)
+        .build()
+        .ok()
+}
+
+// remove hop-by-hop headers including the ones listed in connection header.
+fn strip_hop_by_hop(headers: &mut HeaderMap) {
+    let listed = headers
+        .get_all(header::CONNECTION)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .filter_map(|name| HeaderName::from_bytes(name.trim().as_bytes()).ok())
+        .collect::<Vec<_>>();
+
+    for name in listed {
+        headers.remove(name);
+    }
+
+    for name in [
+        header::CONNECTION,
+        KEEP_ALIVE,
+        header::PROXY_AUTHENTICATE,
+        header::PROXY_AUTHORIZATION,
+        header::TE,
+        header::TRAILER,
+        header::TRANSFER_ENCODING,
+        header::UPGRADE,
+    ] {
+        headers.remove(name);
+    }
+}
+
+mod service {
+    use core::{
+        future::{poll_fn, Future},
+        pin::{pin, Pin},
+        task::{Context, Poll},
+    };
+
+    use futures_core::stream::Stream;
+    use tokio::sync::{mpsc, oneshot};
+    use xitca_client::ResponseBody as ClientBody;
+    use xitca_http::body::{BodySize, NoneBody};
+    use xitca_unsafe_collection::futures::{Select, SelectOutput};
+
+    use crate::{
+        body::{BodyStream, ResponseBody},
+        bytes::Bytes,
+        context::WebContext,
+        error::{BodyError, Error},
+        http::{
+            header::{HeaderValue, HOST},
+            response::Parts,
+            Request, Response, StatusCode, WebResponse,
+        },
+    };
+
+    use super::*;
+
+    pub struct ProxyService {
+        pub(super) client: Arc<Client>,
+        pub(super) scheme: Scheme,
+        pub(super) authority: Authority,
+        pub(super) base: String,
+        pub(super) timeout: Option<Duration>,
+    }
+
+    impl<'r, C, B> Service<WebContext<'r, C, B>> for ProxyService
+    where
+        B: BodyStream + Default + 'static,
+        B::Chunk: Into<Bytes>,
+    {
+        type Response = WebResponse;
+        type Error = Error;
+
+        async fn call(&self, ctx: WebContext<'r, C, B>) -> Result<Self::Response, Self::Error> {
+            let req = ctx.req();
+
+            let uri = {
+                let suffix = req.body().params().get("p").unwrap_or_default();
+                upstream_uri(&self.scheme, &self.authority, &self.base, suffix, req.uri().query())
+                    .ok_or(Error::from(StatusCode::BAD_REQUEST))?
+            };
+
+            let mut headers = req.headers().clone();
+            strip_hop_by_hop(&mut headers);
+
+            if let Some(host) = headers.remove(HOST).or_else(|| {
+                req.uri()
+                    .authority()
+                    .and_then(|a| HeaderValue::from_str(a.as_str()).ok())
+            }) {
+                headers.insert(X_FORWARDED_HOST, host);
+            }
+
+            // scheme is decided by the connection request is received from. uri scheme is claimed by client.
+            let proto = if req.body().is_secure() { "https" } else { "http" };
+            headers.insert(X_FORWARDED_PROTO, HeaderValue::from_static(proto));
+
+            let addr = req.body().socket_addr().ip().to_string();
+            let forwarded_for = match headers.get(X_FORWARDED_FOR).and_then(|v| v.to_str().ok()) {
+                Some(prev) => format!("{prev}, {addr}"),
+                None => addr,
+            };
+            headers.insert(X_FORWARDED_FOR, HeaderValue::try_from(forwarded_for).unwrap());
+
+            // decide from request body itself. http/2 and http/3 request can have a streaming body without
+            // content-length header.
+            let has_body = !matches!(BodySize::from_stream(&*ctx.body()), BodySize::None);
+
+            let mut upstream = Request::new(());
+            *upstream.method_mut() = req.method().clone();
+            *upstream.uri_mut() = uri;
+            *upstream.headers_mut() = headers;
+
+            let (head_tx, head_rx) = oneshot::channel();
+            let (body_tx, body_rx) = mpsc::channel(8);
+
+            // response body borrows client for returning it's connection to pool. exchange with upstream is
+            // spawned as a task owning the client and response body is forwarded to downstream through channel.
+            if has_body {
+                // request body is not thread safe. forward it to client through a channel while request is
+                // being sent.
+                let (tx, rx) = mpsc::channel(8);
+                let body = ctx.take_body_ref();
+
+                let pump = async move {
+                    let mut body = pin!(body);
+                    while let Some(chunk) = poll_fn(|cx| body.as_mut().poll_next(cx)).await {
+                        let chunk = chunk.map(Into::into).map_err(Into::into);
+                        if tx.send(chunk).await.is_err() {
+                            break;
+                        }
+                    }
+                };
+
+                let req = upstream.map(|_| ChannelBody(rx));
+                tokio::task::spawn_local(self.exchange(req, pump, head_tx, body_tx));
+            } else {
+                let req = upstream.map(|_| NoneBody::<Bytes>::default());
+                tokio::task::spawn_local(self.exchange(req, async {}, head_tx, body_tx));
+            }
+
+            let parts = head_rx.await.map_err(|_| Error::from(StatusCode::BAD_GATEWAY))??;
+
+            Ok(WebResponse::from_parts(
+                parts,
+                ResponseBody::box_stream(ChannelBody(body_rx)),
+            ))
+        }
+    }
+
+    impl ProxyService {
+        fn exchange<B, E, F>(
+            &self,
+            req: Request<B>,
+            pump: F,
+            head_tx: oneshot::Sender<Result<Parts, ProxyError>>,
+            body_tx: mpsc::Sender<Result<Bytes, BodyError>>,
+        ) -> impl Future<Output = ()> + 'static
+        where
+            B: Stream<Item = Result<Bytes, E>> + Send + 'static,
+            BodyError: From<E>,
+            F: Future<Output = ()> + 'static,
+        {
+            let client = self.client.clone();
+            let timeout = self.timeout;
+
+            async move {
+                let mut pump = pin!(pump);
+                let mut send = pin!(send(&client, timeout, req));
+
+                // response head can arrive before request body is fully forwarded. in that case pump is kept
+                // alive and driven along with response body until either of them finishes.
+                let (res, pumping) = match send.as_mut().select(pump.as_mut()).await {
+                    SelectOutput::A(res) => (res, true),
+                    SelectOutput::B(_) => (send.await, false),
+                };
+
+                let (mut parts, body) = match res {
+                    Ok(res) => res.into_parts(),
+                    Err(e) => {
+                        let _ = head_tx.send(Err(e));
+                        return;
+                    }
+                };
+
+                strip_hop_by_hop(&mut parts.headers);
+
+                if head_tx.send(Ok(parts)).is_err() {
+                    return;
+                }
+
+                let mut forward = pin!(async {
+                    let mut body = pin!(body);
+                    while let Some(chunk) = poll_fn(|cx| body.as_mut().poll_next(cx)).await {
+                        if body_tx.send(chunk).await.is_err() {
+                            break;
+                        }
+                    }
+                });
+
+                if pumping {
+                    if let SelectOutput::B(_) = forward.as_mut().select(pump).await {
+                        forward.await;
+                    }
+                } else {
+                    forward.await;
+                }
+            }
+        }
+    }
+
+    async fn send<B, E>(
+        client: &Client,
+        timeout: Option<Duration>,
+        req: Request<B>,
+    ) -> Result<Response<ClientBody<'_>>, ProxyError>
+    where
+        B: Stream<Item = Result<Bytes, E>> + Send + 'static,
+        BodyError: From<E>,
+    {
+        let mut builder = client.request(req);
+        if let Some(dur) = timeout {
+            builder = builder.timeout(dur);
+        }
+        builder.send().await.map(|res| res.into_inner()).map_err(ProxyError)
+    }
+
+    struct ChannelBody(mpsc::Receiver<Result<Bytes, BodyError>>);
+
+    impl Stream for ChannelBody {
+        type Item = Result<Bytes, BodyError>;
+
+        fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+            self.get_mut().0.poll_recv(cx)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+        task::LocalSet,
+    };
+
+    use crate::{
+        body::RequestBody,
+        bytes::Bytes,
+        http::{Request, RequestExt, StatusCode},
+        test::collect_string_body,
+        App,
+    };
+
+    use super::*;
+
+    // accept one connection and read request head from it.
+    async fn accept_head(listener: &TcpListener) -> (tokio::net::TcpStream, String) {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut head = Vec::new();
+        while !head.ends_with(b"\r\n\r\n") {
+            let mut byte = [0];
+            stream.read_exact(&mut byte).await.unwrap();
+            head.push(byte[0]);
+        }
+        (stream, String::from_utf8(head).unwrap().to_lowercase())
+    }
+
+    async fn call(
+        upstream: String,
+        timeout: Option<Duration>,
+        req: Request<RequestExt<RequestBody>>,
+    ) -> (StatusCode, String) {
+        let mut proxy = Proxy::new(upstream);
+        if let Some(dur) = timeout {
+            proxy = proxy.timeout(dur);
+        }
+
+        let service = App::new().at("/api", proxy).finish().call(()).await.unwrap();
+        let res = service.call(req).await.unwrap();
+        let status = res.status();
+        let body = collect_string_body(res.into_body()).await.unwrap();
+        (status, body)
+    }
+
+    #[test]
+    fn hop_by_hop() {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::CONNECTION, "keep-alive, x-custom".parse().unwrap());
+        headers.insert(KEEP_ALIVE, "timeout=5".parse().unwrap());
+        headers.insert(HeaderName::from_static("x-custom"), "1".parse().unwrap());
+        headers.insert(header::TRANSFER_ENCODING, "chunked".parse().unwrap());
+        headers.insert(header::ACCEPT, "*/*".parse().unwrap());
+
+        strip_hop_by_hop(&mut headers);
+
+        assert_eq!(headers.len(), 1);
+        assert_eq!(headers.get(header::ACCEPT).unwrap(), "*/*");
+    }
+
+    #[test]
+    fn upstream_path() {
+        let scheme = "http".parse().unwrap();
+        let authority = "backend:8080".parse().unwrap();
+
+        let uri = upstream_uri(&scheme, &authority, "/v1", "users", Some("id=1")).unwrap();
+        assert_eq!(uri, "http://backend:8080/v1/users?id=1");
+
+        let uri = upstream_uri(&scheme, &authority, "", "", None).unwrap();
+        assert_eq!(uri, "http://backend:8080/");
+    }
+
+    #[tokio::test]
+    async fn forward() {
+        LocalSet::new()
+            .run_until(async {
+                let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+                let addr = listener.local_addr().unwrap();
+
+                let upstream = tokio::task::spawn_local(async move {
+                    let (mut stream, head) = accept_head(&listener).await;
+                    stream
+                        .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 5\r\nkeep-alive: timeout=5\r\n\r\nhello")
+                        .await
+                        .unwrap();
+                    head
+                });
+
+                let req = Request::builder()
+                    .uri("/api/users?id=1")
+                    .header(header::HOST, "example.com")
+                    .header(header::CONNECTION, "x-custom")
+                    .header("x-custom", "1")
+                    .header(X_FORWARDED_FOR, "10.0.0.1")
+                    .body(Default::default())
+                    .unwrap();
+
+                let (status, body) = call(format!("http://{addr}/v1"), None, req).await;
+                assert_eq!(status, StatusCode::OK);
+                assert_eq!(body, "hello");
+
+                let head = upstream.await.unwrap();
+                assert!(head.starts_with("get /v1/users?id=1 http/1.1\r\n"));
+                assert!(head.contains("x-forwarded-host: example.com\r\n"));
+                assert!(head.contains("x-forwarded-for: 10.0.0.1, 0.0.0.0\r\n"));
+                // request is not received from tls connection. uri scheme claimed by client is ignored.
+                assert!(head.contains("x-forwarded-proto: http\r\n"));
+                assert!(!head.contains("x-custom"));
+            })
+            .await
+    }
+
+    #[tokio::test]
+    async fn forward_body() {
+        LocalSet::new()
+            .run_until(async {
+                let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+                let addr = listener.local_addr().unwrap();
+
+                let upstream = tokio::task::spawn_local(async move {
+                    let (mut stream, head) = accept_head(&listener).await;
+                    let mut body = Vec::new();
+                    while !body.ends_with(b"0\r\n\r\n") {
+                        let mut byte = [0];
+                        stream.read_exact(&mut byte).await.unwrap();
+                        body.push(byte[0]);
+                    }
+                    stream
+                        .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\n\r\n")
+                        .await
+                        .unwrap();
+                    (head, String::from_utf8(body).unwrap())
+                });
+
+                // body without content-length header like a http/2 request.
+                let req = Request::builder()
+                    .method("POST")
+                    .uri("/api/users")
+                    .body(RequestExt::default().map_body(|_: ()| RequestBody::from(Bytes::from_static(b"hello"))))
+                    .unwrap();
+
+                let (status, _) = call(format!("http://{addr}"), None, req).await;
+                assert_eq!(status, StatusCode::OK);
+
+                let (head, body) = upstream.await.unwrap();
+                assert!(head.starts_with("post /users http/1.1\r\n"));
+                assert!(body.contains("hello"));
+            })
+            .await
+    }
+
+    #[tokio::test]
+    async fn bad_gateway() {
+        LocalSet::new()
+            .run_until(async {
+                // bind and drop listener to get an address refusing connection.
+                let addr = TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap();
+
+                let req = Request::builder().uri("/api/users").body(Default::default()).unwrap();
+                let (status, _) = call(format!("http://{addr}"), None, req).await;
+                assert_eq!(status, StatusCode::BAD_GATEWAY);
+            })
+            .await
+    }
+
+    #[tokio::test]
+    async fn gateway_timeout() {
+        LocalSet::new()
+            .run_until(async {
+                let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+                let addr = listener.local_addr().unwrap();
+
+                // upstream accepts request and never responds.
+                let upstream = tokio::task::spawn_local(async move {
+                    let (stream, _) = accept_head(&listener).await;
+                    core::future::pending::<()>().await;
+                    drop(stream);
+                });
+
+                let req = Request::builder().uri("/api/users").body(Default::default()).unwrap();
+                let (status, _) = call(format!("http://{addr}"), Some(Duration::from_millis(100)), req).await;
+                assert_eq!(status, StatusCode::GATEWAY_TIMEOUT);
+
+                upstream.abort();
+            })
+            .await
+    }
+}